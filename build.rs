@@ -0,0 +1,14 @@
+// 编译第 15 章 FFI 示例使用的 C 库（csrc/guide_ffi.c），并把它作为静态库链接进来
+// 需要系统中有可用的 C 编译器（cc / gcc / clang / MSVC），由 cc crate 负责查找
+
+fn main() {
+    println!("cargo:rerun-if-changed=csrc/guide_ffi.c");
+    println!("cargo:rerun-if-changed=csrc/guide_ffi.h");
+
+    cc::Build::new()
+        .file("csrc/guide_ffi.c")
+        .include("csrc")
+        .std("c11")
+        .warnings(true)
+        .compile("guide_ffi");
+}
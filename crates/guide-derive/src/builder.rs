@@ -0,0 +1,262 @@
+// #[derive(Builder)]
+// 对结构体 User 生成：
+//   pub struct UserBuilder      每个字段对应一个 Option，setter 按值接收 self 以便链式调用
+//   pub enum UserBuilderError   缺少必填字段、字段校验失败、整体校验失败
+//   User::builder()、UserBuilder::build()
+
+use crate::fields;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DeriveInput, Expr, Field, Ident, Path, Type};
+
+enum Fallback {
+    // 必填字段
+    Required,
+    // Option 字段，未设置时为 None
+    Optional,
+    Trait,
+    Expr(Expr),
+}
+
+struct FieldOptions {
+    default: Fallback,
+    into: bool,
+    validate: Option<Path>,
+}
+
+struct ContainerOptions {
+    into: bool,
+    validate: Option<Path>,
+}
+
+fn container_options(input: &DeriveInput) -> syn::Result<ContainerOptions> {
+    let mut options = ContainerOptions {
+        into: false,
+        validate: None,
+    };
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("builder")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("into") {
+                options.into = true;
+            } else if meta.path.is_ident("validate") {
+                options.validate = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("结构体上的 builder 参数只能是 into 或 validate = 函数"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+fn field_options(field: &Field) -> syn::Result<FieldOptions> {
+    let optional = fields::option_inner(&field.ty).is_some();
+    let mut options = FieldOptions {
+        default: if optional {
+            Fallback::Optional
+        } else {
+            Fallback::Required
+        },
+        into: false,
+        validate: None,
+    };
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("builder")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                if optional {
+                    return Err(meta.error("Option 字段未设置时就是 None，不需要 default"));
+                }
+                options.default = if meta.input.peek(syn::Token![=]) {
+                    Fallback::Expr(meta.value()?.parse()?)
+                } else {
+                    Fallback::Trait
+                };
+            } else if meta.path.is_ident("into") {
+                options.into = true;
+            } else if meta.path.is_ident("validate") {
+                options.validate = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "字段上的 builder 参数只能是 default、default = 表达式、into 或 validate = 函数",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = fields::named(input, "Builder")?;
+    let container = container_options(input)?;
+    let options = fields
+        .iter()
+        .map(field_options)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let builder = format_ident!("{}Builder", name);
+    let error = format_ident!("{}BuilderError", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let generics = &input.generics;
+
+    let idents: Vec<&Ident> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    // builder 中保存的类型：Option 字段保存内层的 T，其他字段保存字段类型本身
+    let stored: Vec<&Type> = fields
+        .iter()
+        .map(|f| fields::option_inner(&f.ty).unwrap_or(&f.ty))
+        .collect();
+
+    let setters = idents
+        .iter()
+        .zip(&stored)
+        .zip(&options)
+        .map(|((ident, ty), options)| {
+            if options.into || container.into {
+                quote! {
+                    #vis fn #ident(mut self, value: impl ::std::convert::Into<#ty>) -> Self {
+                        self.#ident = ::std::option::Option::Some(value.into());
+                        self
+                    }
+                }
+            } else {
+                quote! {
+                    #vis fn #ident(mut self, value: #ty) -> Self {
+                        self.#ident = ::std::option::Option::Some(value);
+                        self
+                    }
+                }
+            }
+        });
+
+    let takes = idents.iter().zip(&options).map(|(ident, options)| {
+        let field = ident.to_string();
+        match &options.default {
+            Fallback::Required => quote! {
+                let #ident = match self.#ident {
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(#error::MissingField(#field));
+                    }
+                };
+            },
+            Fallback::Optional => quote! { let #ident = self.#ident; },
+            Fallback::Trait => quote! { let #ident = self.#ident.unwrap_or_default(); },
+            Fallback::Expr(expr) => quote! {
+                let #ident = match self.#ident {
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => #expr,
+                };
+            },
+        }
+    });
+
+    let field_checks = idents.iter().zip(&options).filter_map(|(ident, options)| {
+        let validate = options.validate.as_ref()?;
+        let field = ident.to_string();
+        // 校验函数的签名不对时，错误指向属性中的函数名
+        let call = quote_spanned! {validate.span()=>
+            #validate(value)
+        };
+        let check = quote! {
+            let result: ::std::result::Result<(), ::std::string::String> = #call;
+            if let ::std::result::Result::Err(message) = result {
+                return ::std::result::Result::Err(#error::InvalidField {
+                    field: #field,
+                    message,
+                });
+            }
+        };
+        Some(match options.default {
+            Fallback::Optional => quote! {
+                if let ::std::option::Option::Some(value) = &#ident {
+                    #check
+                }
+            },
+            _ => quote! {
+                {
+                    let value = &#ident;
+                    #check
+                }
+            },
+        })
+    });
+
+    let container_check = container.validate.as_ref().map(|validate| {
+        let call = quote_spanned! {validate.span()=>
+            #validate(&value)
+        };
+        quote! {
+            let result: ::std::result::Result<(), ::std::string::String> = #call;
+            if let ::std::result::Result::Err(message) = result {
+                return ::std::result::Result::Err(#error::Invalid(message));
+            }
+        }
+    });
+
+    let type_name = name.to_string();
+    let builder_doc = format!("{} 的构建器，由 #[derive(Builder)] 生成", name);
+    let error_doc = format!("{}::build 的错误", builder);
+
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #vis struct #builder #generics #where_clause {
+            #(#idents: ::std::option::Option<#stored>,)*
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error {
+            MissingField(&'static str),
+            InvalidField {
+                field: &'static str,
+                message: ::std::string::String,
+            },
+            Invalid(::std::string::String),
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    #error::MissingField(field) => {
+                        ::std::write!(f, "{} 缺少必填字段 {}", #type_name, field)
+                    }
+                    #error::InvalidField { field, message } => {
+                        ::std::write!(f, "{} 的字段 {} 无效: {}", #type_name, field, message)
+                    }
+                    #error::Invalid(message) => ::std::write!(f, "{} 无效: {}", #type_name, message),
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        impl #impl_generics ::std::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                #builder {
+                    #(#idents: ::std::option::Option::None,)*
+                }
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #vis fn builder() -> #builder #ty_generics {
+                ::std::default::Default::default()
+            }
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #(#setters)*
+
+            #vis fn build(self) -> ::std::result::Result<#name #ty_generics, #error> {
+                #(#takes)*
+                #(#field_checks)*
+                let value = #name { #(#idents,)* };
+                #container_check
+                ::std::result::Result::Ok(value)
+            }
+        }
+    })
+}
@@ -0,0 +1,39 @@
+// 三个派生宏共用的输入检查
+
+use syn::{Data, DeriveInput, Field, Fields, GenericArgument, PathArguments, Type};
+
+// 只支持具名字段的结构体，其他情况在类型名上报错
+pub fn named(input: &DeriveInput, derive: &str) -> syn::Result<Vec<Field>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields.named.iter().cloned().collect()),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                format!("#[derive({})] 只支持具名字段的结构体", derive),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("#[derive({})] 只支持结构体", derive),
+        )),
+    }
+}
+
+// Option<T> 中的 T；只按最后一段路径判断，std::option::Option<T> 也能识别
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,86 @@
+// #[derive(Getters)]
+// 每个字段生成 pub fn 字段名(&self) -> &T，#[getter(mut)] 另外生成 字段名_mut，
+// #[getter(copy)] 改为按值返回
+
+use crate::fields;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Attribute, DeriveInput};
+
+#[derive(Default, Clone, Copy)]
+struct Options {
+    skip: bool,
+    mutable: bool,
+    copy: bool,
+}
+
+// field 为 false 时是结构体上的属性，不允许 skip
+fn parse(attrs: &[Attribute], mut options: Options, field: bool) -> syn::Result<Options> {
+    for attr in attrs.iter().filter(|a| a.path().is_ident("getter")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") && field {
+                options.skip = true;
+            } else if meta.path.is_ident("mut") {
+                options.mutable = true;
+            } else if meta.path.is_ident("copy") {
+                options.copy = true;
+            } else if field {
+                return Err(meta.error("字段上的 getter 参数只能是 skip、mut 或 copy"));
+            } else {
+                return Err(meta.error("结构体上的 getter 参数只能是 mut 或 copy"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = fields::named(input, "Getters")?;
+    let container = parse(&input.attrs, Options::default(), false)?;
+
+    let mut methods = Vec::new();
+    for field in &fields {
+        let options = parse(&field.attrs, container, true)?;
+        if options.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        if options.copy {
+            // 字段类型没有实现 Copy 时，错误指向字段类型
+            let bound = quote_spanned! {ty.span()=> #ty: ::std::marker::Copy };
+            methods.push(quote! {
+                pub fn #ident(&self) -> #ty
+                where
+                    #bound,
+                {
+                    self.#ident
+                }
+            });
+        } else {
+            methods.push(quote! {
+                pub fn #ident(&self) -> &#ty {
+                    &self.#ident
+                }
+            });
+        }
+        if options.mutable {
+            let ident_mut = format_ident!("{}_mut", ident);
+            methods.push(quote! {
+                pub fn #ident_mut(&mut self) -> &mut #ty {
+                    &mut self.#ident
+                }
+            });
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
@@ -0,0 +1,85 @@
+// #[derive(Summary)]
+// 生成 impl Summary for 类型 { fn summarize(&self) -> String }。
+// Summary 按名字引用，由调用处负责把 trait 引入作用域
+
+use crate::fields;
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DeriveInput, LitStr};
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = fields::named(input, "Summary")?;
+
+    let mut template: Option<LitStr> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("summary")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("template") {
+                template = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("结构体上的 summary 参数只能是 template = \"...\""))
+            }
+        })?;
+    }
+
+    let mut shown = Vec::new();
+    for field in &fields {
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("summary")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("字段上的 summary 参数只能是 skip"))
+                }
+            })?;
+        }
+        if !skip {
+            shown.push(field);
+        }
+    }
+
+    let body = match template {
+        // 把所有字段绑定成同名的局部变量，模板中的 {字段名} 直接捕获它们；
+        // 模板引用了不存在的字段时，错误指向模板字符串
+        Some(template) => {
+            let idents = fields.iter().map(|f| f.ident.as_ref().unwrap());
+            quote! {
+                #[allow(unused_variables)]
+                let Self { #(#idents,)* } = self;
+                ::std::format!(#template)
+            }
+        }
+        None => {
+            let mut format = format!("{}(", input.ident);
+            for (i, field) in shown.iter().enumerate() {
+                if i > 0 {
+                    format.push_str(", ");
+                }
+                format.push_str(&field.ident.as_ref().unwrap().to_string());
+                format.push_str(": {}");
+            }
+            format.push(')');
+            // 字段没有实现 Display 时，错误指向字段类型
+            let args = shown.iter().map(|f| {
+                let ident = f.ident.as_ref().unwrap();
+                quote_spanned! {f.ty.span()=> &self.#ident }
+            });
+            quote! {
+                ::std::format!(#format, #(#args),*)
+            }
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics Summary for #name #ty_generics #where_clause {
+            fn summarize(&self) -> ::std::string::String {
+                #body
+            }
+        }
+    })
+}
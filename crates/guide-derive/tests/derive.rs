@@ -0,0 +1,175 @@
+// 三个派生宏的行为测试，以及 tests/ui/ 中的 compile-fail 用例
+// 用法错误的报错位置和文本与 .stderr 比较；编译器升级导致输出变化时，
+// 用 TRYBUILD=overwrite cargo test -p guide-derive 重新生成
+
+use guide_derive::{Builder, Getters, Summary};
+
+fn check_email(email: &str) -> Result<(), String> {
+    if email.contains('@') {
+        Ok(())
+    } else {
+        Err(format!("{} 不是邮箱地址", email))
+    }
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(validate = Account::check)]
+struct Account {
+    #[builder(into)]
+    name: String,
+    #[builder(into, validate = check_email)]
+    email: String,
+    #[builder(default)]
+    age: u32,
+    #[builder(default = vec!["user".to_string()])]
+    roles: Vec<String>,
+    #[builder(into)]
+    phone: Option<String>,
+}
+
+impl Account {
+    fn check(&self) -> Result<(), String> {
+        if self.roles.is_empty() {
+            Err("至少需要一个角色".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn builder_fills_defaults_and_optional_fields() {
+    let account = Account::builder()
+        .name("张三")
+        .email("zhangsan@example.com")
+        .build()
+        .unwrap();
+    assert_eq!(
+        account,
+        Account {
+            name: "张三".to_string(),
+            email: "zhangsan@example.com".to_string(),
+            age: 0,
+            roles: vec!["user".to_string()],
+            phone: None,
+        }
+    );
+
+    let account = Account::builder()
+        .name("李四")
+        .email("lisi@example.com")
+        .age(30)
+        .phone("123")
+        .build()
+        .unwrap();
+    assert_eq!((account.age, account.phone.as_deref()), (30, Some("123")));
+}
+
+#[test]
+fn builder_reports_typed_errors() {
+    let err = Account::builder().email("a@b").build().unwrap_err();
+    assert_eq!(err, AccountBuilderError::MissingField("name"));
+    assert_eq!(err.to_string(), "Account 缺少必填字段 name");
+
+    let err = Account::builder()
+        .name("王五")
+        .email("wangwu")
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        AccountBuilderError::InvalidField {
+            field: "email",
+            message: "wangwu 不是邮箱地址".to_string(),
+        }
+    );
+
+    let err = Account::builder()
+        .name("王五")
+        .email("wangwu@example.com")
+        .roles(Vec::new())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Account 无效: 至少需要一个角色");
+}
+
+#[derive(Debug, Builder)]
+struct Pair<T: Clone> {
+    left: T,
+    right: Option<T>,
+}
+
+#[test]
+fn builder_supports_generics() {
+    let pair = Pair::builder().left(1u8).build().unwrap();
+    assert_eq!((pair.left, pair.right), (1, None));
+}
+
+#[derive(Getters)]
+#[getter(mut)]
+#[allow(dead_code)]
+struct Person {
+    name: String,
+    #[getter(copy)]
+    age: u32,
+    #[getter(skip)]
+    password: String,
+}
+
+#[test]
+fn getters_return_references_or_copies() {
+    let mut person = Person {
+        name: "Bob".to_string(),
+        age: 25,
+        password: String::new(),
+    };
+    assert_eq!(person.name(), "Bob");
+    let age: u32 = person.age();
+    *person.age_mut() = age + 1;
+    person.name_mut().push_str(" Smith");
+    assert_eq!((person.name().as_str(), person.age()), ("Bob Smith", 26));
+}
+
+trait Summary {
+    fn summarize(&self) -> String;
+}
+
+#[derive(Summary)]
+#[allow(dead_code)]
+struct Book {
+    title: String,
+    pages: u32,
+    #[summary(skip)]
+    isbn: String,
+}
+
+#[derive(Summary)]
+#[summary(template = "《{title}》，作者 {author}")]
+struct Article {
+    title: String,
+    author: String,
+    body: String,
+}
+
+#[test]
+fn summary_uses_display_or_template() {
+    let book = Book {
+        title: "The Rust Book".to_string(),
+        pages: 500,
+        isbn: "978".to_string(),
+    };
+    assert_eq!(book.summarize(), "Book(title: The Rust Book, pages: 500)");
+
+    let article = Article {
+        title: "所有权".to_string(),
+        author: "Alice".to_string(),
+        body: String::new(),
+    };
+    assert_eq!(article.summarize(), "《所有权》，作者 Alice");
+}
+
+#[test]
+fn misuse_is_reported_at_the_right_span() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,11 @@
+// Option 字段本身就是可选的
+use guide_derive::Builder;
+
+#[derive(Builder)]
+struct User {
+    name: String,
+    #[builder(default)]
+    phone: Option<String>,
+}
+
+fn main() {}
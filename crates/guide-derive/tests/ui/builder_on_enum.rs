@@ -0,0 +1,9 @@
+// Builder 只支持具名字段的结构体
+use guide_derive::Builder;
+
+#[derive(Builder)]
+enum Shape {
+    Circle(f64),
+}
+
+fn main() {}
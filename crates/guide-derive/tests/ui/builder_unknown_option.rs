@@ -0,0 +1,10 @@
+// 未知的参数指向参数本身
+use guide_derive::Builder;
+
+#[derive(Builder)]
+struct User {
+    #[builder(required)]
+    name: String,
+}
+
+fn main() {}
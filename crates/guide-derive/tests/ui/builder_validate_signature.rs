@@ -0,0 +1,14 @@
+// 校验函数的签名不对时，错误指向属性中的函数名
+use guide_derive::Builder;
+
+fn positive(age: &i64) -> bool {
+    *age > 0
+}
+
+#[derive(Builder)]
+struct User {
+    #[builder(validate = positive)]
+    age: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+// #[getter(copy)] 要求字段类型实现 Copy
+use guide_derive::Getters;
+
+#[derive(Getters)]
+struct User {
+    #[getter(copy)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// 默认格式用 Display 输出字段
+use guide_derive::Summary;
+
+trait Summary {
+    fn summarize(&self) -> String;
+}
+
+#[derive(Summary)]
+struct Playlist {
+    name: String,
+    songs: Vec<String>,
+}
+
+fn main() {}
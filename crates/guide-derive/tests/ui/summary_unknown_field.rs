@@ -0,0 +1,15 @@
+// 模板引用了不存在的字段
+use guide_derive::Summary;
+
+trait Summary {
+    fn summarize(&self) -> String;
+}
+
+#[derive(Summary)]
+#[summary(template = "{title} by {writer}")]
+struct Article {
+    title: String,
+    author: String,
+}
+
+fn main() {}
@@ -0,0 +1,181 @@
+// #[derive(Describe)]
+// 在编译期把类型声明整理成文本，生成：
+//   fn describe() -> &'static str        去掉属性和可见性之后的声明，文档注释保留为 ///
+//   fn variant_name(&self) -> &'static str  只为枚举生成，返回当前值的变体名
+// 例如
+//   /// 二维平面上的点
+//   #[derive(Describe)]
+//   pub struct Point { pub x: i32, pub y: i32 }
+// 的 Point::describe() 是 "/// 二维平面上的点\nstruct Point {\n    x: i32,\n    y: i32,\n}"
+
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{Attribute, Data, DeriveInput, Expr, Fields, Lit, Meta};
+
+// 文档注释 /// 文本 在属性中是 #[doc = " 文本"]
+fn doc_lines(attrs: &[Attribute], indent: &str, out: &mut String) {
+    for attr in attrs {
+        if let Meta::NameValue(meta) = &attr.meta
+            && meta.path.is_ident("doc")
+            && let Expr::Lit(expr) = &meta.value
+            && let Lit::Str(text) = &expr.lit
+        {
+            out.push_str(indent);
+            out.push_str("///");
+            let text = text.value();
+            let text = text.trim_end();
+            if !text.is_empty() {
+                out.push(' ');
+                out.push_str(text.trim_start());
+            }
+            out.push('\n');
+        }
+    }
+}
+
+// TokenStream::to_string 在每个 token 之间都加空格（Vec < String >），
+// 这里只保留两个单词之间的空格，再在逗号、分号、单个冒号之后以及 -> 和 + 两侧补上空格
+fn source(tokens: impl ToTokens) -> String {
+    let text = tokens.to_token_stream().to_string();
+    let chars: Vec<char> = text.chars().collect();
+    let word = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let mut compact = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == ' ' {
+            let before = i.checked_sub(1).map(|j| chars[j]);
+            let after = chars.get(i + 1).copied();
+            if before.is_some_and(word) && after.is_some_and(word) {
+                compact.push(c);
+            }
+        } else {
+            compact.push(c);
+        }
+    }
+    compact
+        .replace(',', ", ")
+        .replace(';', "; ")
+        .replace("->", " -> ")
+        .replace('+', " + ")
+        .replace("::", "\0")
+        .replace(':', ": ")
+        .replace('\0', "::")
+}
+
+// 元组字段写成 (T, U)，具名字段写成 { a: T, b: U }
+fn inline_fields(fields: &Fields) -> String {
+    match fields {
+        Fields::Named(fields) => {
+            let fields: Vec<String> = fields
+                .named
+                .iter()
+                .map(|f| format!("{}: {}", f.ident.as_ref().unwrap(), source(&f.ty)))
+                .collect();
+            format!(" {{ {} }}", fields.join(", "))
+        }
+        Fields::Unnamed(fields) => {
+            let fields: Vec<String> = fields.unnamed.iter().map(|f| source(&f.ty)).collect();
+            format!("({})", fields.join(", "))
+        }
+        Fields::Unit => String::new(),
+    }
+}
+
+fn declaration(input: &DeriveInput) -> syn::Result<String> {
+    let mut out = String::new();
+    doc_lines(&input.attrs, "", &mut out);
+    let generics = &input.generics;
+    let head = format!("{}{}", input.ident, source(generics));
+    let where_clause = generics
+        .where_clause
+        .as_ref()
+        .map(|w| format!(" {}", source(w)))
+        .unwrap_or_default();
+
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                out.push_str(&format!("struct {}{} {{\n", head, where_clause));
+                for field in &fields.named {
+                    doc_lines(&field.attrs, "    ", &mut out);
+                    out.push_str(&format!(
+                        "    {}: {},\n",
+                        field.ident.as_ref().unwrap(),
+                        source(&field.ty)
+                    ));
+                }
+                out.push('}');
+            }
+            fields => {
+                out.push_str(&format!(
+                    "struct {}{}{};",
+                    head,
+                    inline_fields(fields),
+                    where_clause
+                ));
+            }
+        },
+        Data::Enum(data) => {
+            out.push_str(&format!("enum {}{} {{\n", head, where_clause));
+            for variant in &data.variants {
+                doc_lines(&variant.attrs, "    ", &mut out);
+                out.push_str(&format!(
+                    "    {}{}",
+                    variant.ident,
+                    inline_fields(&variant.fields)
+                ));
+                if let Some((_, discriminant)) = &variant.discriminant {
+                    out.push_str(&format!(" = {}", source(discriminant)));
+                }
+                out.push_str(",\n");
+            }
+            out.push('}');
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "#[derive(Describe)] 不支持联合体：无法安全地得知当前是哪个字段",
+            ));
+        }
+    }
+    Ok(out)
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let text = declaration(input)?;
+
+    let variant_name = match &input.data {
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let name = ident.to_string();
+                let pattern = match &variant.fields {
+                    Fields::Named(_) => quote! { Self::#ident { .. } },
+                    Fields::Unnamed(_) => quote! { Self::#ident(..) },
+                    Fields::Unit => quote! { Self::#ident },
+                };
+                quote! { #pattern => #name, }
+            });
+            // 空枚举没有值，match 没有分支也是完整的
+            Some(quote! {
+                pub fn variant_name(&self) -> &'static str {
+                    match *self {
+                        #(#arms)*
+                    }
+                }
+            })
+        }
+        _ => None,
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn describe() -> &'static str {
+                #text
+            }
+
+            #variant_name
+        }
+    })
+}
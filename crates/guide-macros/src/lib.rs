@@ -0,0 +1,126 @@
+// 第 13 章过程宏三种形式的实际例子，每个宏的展开逻辑在同名模块中：
+// 1. sql!("...")：函数式宏，编译期检查 SQL 语法，展开为规范化的字符串字面量
+// 2. #[timed]：属性宏，为函数加上计时守卫，是第 9 节 time_block! 的过程宏版本
+// 3. #[derive(Describe)]：派生宏，把类型声明整理成文本，枚举另外可以取得变体名
+//
+// 展开函数只依赖 proc_macro2，可以脱离编译器直接调用；
+// 下面的测试把展开结果与 tests/data/expand/ 中的快照比较，tests/ui/ 中是 compile-fail 用例
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod describe;
+mod sql;
+mod timed;
+
+// sql!("SELECT name FROM users WHERE age > ?") 展开为 "SELECT name FROM users WHERE age > ?"；
+// 只接受一条 minidb 支持的语句，末尾的分号可有可无
+#[proc_macro]
+pub fn sql(input: TokenStream) -> TokenStream {
+    sql::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// 函数或方法上的 #[timed(...)]：
+//   name = "..."   报告中使用的名字，默认是函数名
+//   with = 函数     fn(&'static str, Duration)，代替默认的打印到标准输出
+#[proc_macro_attribute]
+pub fn timed(args: TokenStream, item: TokenStream) -> TokenStream {
+    timed::expand(args.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// 生成 describe() 和（枚举）variant_name()，结构体和枚举都可以派生，联合体不行
+#[proc_macro_derive(Describe)]
+pub fn derive_describe(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    describe::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// 与主 crate 的 golden 测试共用比较逻辑
+#[cfg(test)]
+#[path = "../../../tests/common/golden.rs"]
+mod golden;
+
+#[cfg(test)]
+mod tests {
+    use super::golden::check_golden;
+    use proc_macro2::TokenStream;
+    use quote::quote;
+
+    // 有意修改展开结果后，用 UPDATE_GOLDEN=1 cargo test -p guide-macros 重新生成快照
+    #[test]
+    fn test_expansion_snapshots() {
+        let derive = |input: TokenStream| {
+            let input = syn::parse2(input).unwrap();
+            super::describe::expand(&input).unwrap()
+        };
+        let cases = [
+            (
+                "sql",
+                super::sql::expand(quote! {
+                    "select id, name from users where age >= ? and name like 'A%' order by id"
+                })
+                .unwrap(),
+            ),
+            (
+                "timed",
+                super::timed::expand(
+                    TokenStream::new(),
+                    quote! {
+                        pub fn parse(text: &str) -> Result<i32, std::num::ParseIntError> {
+                            let value = text.trim().parse()?;
+                            Ok(value)
+                        }
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                "timed_with",
+                super::timed::expand(
+                    quote! { name = "加载配置", with = record },
+                    quote! {
+                        async fn load(&self) -> usize {
+                            self.items.len()
+                        }
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                "describe_struct",
+                derive(quote! {
+                    /// 二维平面上的点
+                    pub struct Point<T: Copy + Default> where T: std::fmt::Debug {
+                        /// 横坐标
+                        pub x: T,
+                        pub y: T,
+                        tags: Vec<&'static str>,
+                    }
+                }),
+            ),
+            (
+                "describe_enum",
+                derive(quote! {
+                    enum Shape {
+                        /// 圆，参数是半径
+                        Circle(f64),
+                        Rect { width: f64, height: f64 },
+                        Empty,
+                    }
+                }),
+            ),
+        ];
+        // 快照是 TokenStream 的 to_string()，每个用例一行
+        check_golden(
+            "tests/data/expand",
+            "rs",
+            cases.map(|(name, tokens)| (name, format!("{}\n", tokens))),
+        );
+    }
+}
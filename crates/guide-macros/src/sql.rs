@@ -0,0 +1,124 @@
+// sql!("...")
+// 在编译期用 guide-sql（src/minidb.rs 运行时使用的同一套语法）检查 SQL 字面量，
+// 展开为规范化的 &'static str：关键字大写，空白和注释压缩成单个空格，去掉末尾的分号。
+// 语法错误报告在字面量上，信息中带出错的字符位置
+
+use guide_sql::{Token, TokenKind};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::LitStr;
+
+// 检查单条语句并返回规范化的文本
+fn check(sql: &str) -> Result<String, String> {
+    let mut tokens = guide_sql::tokenize(sql).map_err(|e| e.message)?;
+    if tokens.last().is_some_and(|t| t.is_symbol(";")) {
+        tokens.pop();
+    }
+    if let Some(t) = tokens.iter().find(|t| t.is_symbol(";")) {
+        return Err(format!(
+            "第 {} 个字符处有第二条语句，sql! 只接受一条语句",
+            t.at
+        ));
+    }
+    if tokens.is_empty() {
+        return Err(String::from("SQL 中没有语句"));
+    }
+
+    guide_sql::parse_statement(&mut tokens).map_err(|e| e.message)?;
+    Ok(normalize(&tokens))
+}
+
+// 单个空格分隔；左括号和负号之后、右括号和逗号之前不加空格
+fn normalize(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    let mut glue = true;
+    for token in tokens {
+        if !glue && !token.is_symbol(")") && !token.is_symbol(",") {
+            out.push(' ');
+        }
+        match token.kind {
+            TokenKind::Ident if token.keyword => out.push_str(&token.text.to_ascii_uppercase()),
+            TokenKind::Text => out.push_str(&format!("'{}'", token.text.replace('\'', "''"))),
+            TokenKind::Quoted => out.push_str(&format!("\"{}\"", token.text.replace('"', "\"\""))),
+            _ => out.push_str(&token.text),
+        }
+        glue = token.is_symbol("(") || token.is_symbol("-");
+    }
+    out
+}
+
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let literal: LitStr = syn::parse2(input)
+        .map_err(|e| syn::Error::new(e.span(), "sql! 的参数必须是一个字符串字面量"))?;
+    let sql = check(&literal.value())
+        .map_err(|message| syn::Error::new(literal.span(), format!("SQL 语法错误: {}", message)))?;
+    let sql = LitStr::new(&sql, literal.span());
+    Ok(quote! { #sql })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            check("select id,name from users\n  where age >= ? -- 成年\n order by name desc;")
+                .unwrap(),
+            "SELECT id, name FROM users WHERE age >= ? ORDER BY name DESC"
+        );
+        assert_eq!(
+            check("insert into users(name, note) values ('O''Brien', null), (?, -1.5)").unwrap(),
+            "INSERT INTO users (name, note) VALUES ('O''Brien', NULL), (?, -1.5)"
+        );
+        assert_eq!(
+            check("create table if not exists t (id integer primary key, \"text\" text not null)")
+                .unwrap(),
+            "CREATE TABLE IF NOT EXISTS t (id INTEGER PRIMARY KEY, \"text\" TEXT NOT NULL)"
+        );
+        assert_eq!(
+            check("delete from t where not (a = 1 or b is not null) and c not in (1, 2)").unwrap(),
+            "DELETE FROM t WHERE NOT (a = 1 OR b IS NOT NULL) AND c NOT IN (1, 2)"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases = [
+            (
+                "SELECT name FORM users",
+                "第 13 个字符处应为 FROM，但遇到 'FORM'",
+            ),
+            (
+                "SELECT * FROM t WHERE name = 'x",
+                "第 30 个字符处的引号没有闭合",
+            ),
+            (
+                "UPDATE t SET a = 1 WHERE",
+                "应为 列名、字面量或 ?，但语句已结束",
+            ),
+            (
+                "SELECT * FROM t OFFSET -1",
+                "第 24 个字符处应为 非负整数，但遇到 '-'",
+            ),
+            (
+                "CREATE TABLE t (id BLOB)",
+                "第 20 个字符处是不支持的列类型 'BLOB'",
+            ),
+            (
+                "SELECT * FROM a; SELECT * FROM b",
+                "第 16 个字符处有第二条语句，sql! 只接受一条语句",
+            ),
+            ("  -- 只有注释", "SQL 中没有语句"),
+            ("SELECT # FROM t", "第 8 个字符 '#' 无法识别"),
+            // minidb 把超出 i64 的数字当作浮点数，不能作为 LIMIT
+            (
+                "SELECT * FROM t LIMIT 9223372036854775808",
+                "第 23 个字符处应为 非负整数，但遇到 '9223372036854775808'",
+            ),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(check(sql).unwrap_err(), expected, "{}", sql);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// #[timed]
+// 在函数体开头放一个计时守卫，守卫被 drop（函数返回、? 提前返回或 panic 展开）时报告耗时。
+// 守卫方式不改变函数体，return、? 和 async fn 都不受影响：
+//   fn load(path: &str) -> Result<String, Error> {
+//       let _guard = {
+//           struct Timed(&'static str, Instant);
+//           impl Drop for Timed { fn drop(&mut self) { /* 报告 */ } }
+//           Timed("load", Instant::now())
+//       };
+//       /* 原来的函数体 */
+//   }
+// Timed 定义在内层块中，函数体看不到它；_guard 使用 mixed_site 卫生性，函数体中的同名变量不会冲突
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Ident, ItemFn, LitStr, Path};
+
+struct Options {
+    name: Option<LitStr>,
+    with: Option<Path>,
+}
+
+fn parse_options(args: TokenStream) -> syn::Result<Options> {
+    let mut options = Options {
+        name: None,
+        with: None,
+    };
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            options.name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("with") {
+            options.with = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("timed 的参数只能是 name = \"...\" 或 with = 函数"))
+        }
+    });
+    syn::parse::Parser::parse2(parser, args)?;
+    Ok(options)
+}
+
+pub fn expand(args: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let options = parse_options(args)?;
+    let function: ItemFn = syn::parse2(item)?;
+    if let Some(constness) = &function.sig.constness {
+        return Err(syn::Error::new(
+            constness.span(),
+            "#[timed] 不能用于 const fn：编译期求值时无法读取时钟",
+        ));
+    }
+
+    let name = options
+        .name
+        .unwrap_or_else(|| LitStr::new(&function.sig.ident.to_string(), function.sig.ident.span()));
+    // 默认打印到标准输出；with = 函数 时调用 fn(&'static str, Duration)，签名不对时错误指向函数名
+    let report = match &options.with {
+        Some(with) => quote_spanned! {with.span()=>
+            #with(self.0, self.1.elapsed())
+        },
+        None => quote! {
+            ::std::println!("{} 执行耗时: {:?}", self.0, self.1.elapsed())
+        },
+    };
+
+    let timer = Ident::new("Timed", Span::mixed_site());
+    let guard = Ident::new("_guard", Span::mixed_site());
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+    let stmts = &block.stmts;
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            let #guard = {
+                struct #timer(&'static str, ::std::time::Instant);
+
+                impl ::std::ops::Drop for #timer {
+                    fn drop(&mut self) {
+                        #report;
+                    }
+                }
+
+                #timer(#name, ::std::time::Instant::now())
+            };
+            #(#stmts)*
+        }
+    })
+}
@@ -0,0 +1 @@
+impl Shape { pub fn describe () -> & 'static str { "enum Shape {\n    /// 圆，参数是半径\n    Circle(f64),\n    Rect { width: f64, height: f64 },\n    Empty,\n}" } pub fn variant_name (& self) -> & 'static str { match * self { Self :: Circle (..) => "Circle" , Self :: Rect { .. } => "Rect" , Self :: Empty => "Empty" , } } }
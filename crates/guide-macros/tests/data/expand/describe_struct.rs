@@ -0,0 +1 @@
+impl < T : Copy + Default > Point < T > where T : std :: fmt :: Debug { pub fn describe () -> & 'static str { "/// 二维平面上的点\nstruct Point<T: Copy + Default> where T: std::fmt::Debug {\n    /// 横坐标\n    x: T,\n    y: T,\n    tags: Vec<&'static str>,\n}" } }
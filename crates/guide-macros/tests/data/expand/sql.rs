@@ -0,0 +1 @@
+"SELECT id, name FROM users WHERE age >= ? AND name LIKE 'A%' ORDER BY id"
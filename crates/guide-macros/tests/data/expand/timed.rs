@@ -0,0 +1 @@
+pub fn parse (text : & str) -> Result < i32 , std :: num :: ParseIntError > { let _guard = { struct Timed (& 'static str , :: std :: time :: Instant) ; impl :: std :: ops :: Drop for Timed { fn drop (& mut self) { :: std :: println ! ("{} 执行耗时: {:?}" , self . 0 , self . 1 . elapsed ()) ; } } Timed ("parse" , :: std :: time :: Instant :: now ()) } ; let value = text . trim () . parse () ? ; Ok (value) }
@@ -0,0 +1 @@
+async fn load (& self) -> usize { let _guard = { struct Timed (& 'static str , :: std :: time :: Instant) ; impl :: std :: ops :: Drop for Timed { fn drop (& mut self) { record (self . 0 , self . 1 . elapsed ()) ; } } Timed ("加载配置" , :: std :: time :: Instant :: now ()) } ; self . items . len () }
@@ -0,0 +1,121 @@
+// 三个过程宏在真实代码中的行为，以及 tests/ui/ 中的 compile-fail 用例
+// 展开结果的快照测试在 src/lib.rs 中；编译器升级导致报错输出变化时，
+// 用 TRYBUILD=overwrite cargo test -p guide-macros 重新生成 .stderr
+
+use guide_macros::{Describe, sql, timed};
+use std::cell::RefCell;
+use std::time::Duration;
+
+#[test]
+fn sql_is_checked_and_normalized() {
+    const FIND: &str =
+        sql!("select id, name from users where age >= ? -- 成年用户\n order by name;");
+    assert_eq!(
+        FIND,
+        "SELECT id, name FROM users WHERE age >= ? ORDER BY name"
+    );
+    assert_eq!(
+        sql!("insert into \"order\" (id, note) values (1, 'it''s'), (?, null)"),
+        "INSERT INTO \"order\" (id, note) VALUES (1, 'it''s'), (?, NULL)"
+    );
+}
+
+thread_local! {
+    static RECORDS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(name: &'static str, _elapsed: Duration) {
+    RECORDS.with(|records| records.borrow_mut().push(name));
+}
+
+#[timed(with = record)]
+fn parse(text: &str) -> Result<i32, std::num::ParseIntError> {
+    // 同名变量不会与计时守卫冲突
+    let _guard = text.trim();
+    let value = _guard.parse()?;
+    Ok(value)
+}
+
+struct Cache {
+    items: Vec<u32>,
+}
+
+impl Cache {
+    #[timed(name = "Cache::total", with = record)]
+    fn total(&self) -> u32 {
+        if self.items.is_empty() {
+            return 0;
+        }
+        self.items.iter().sum()
+    }
+}
+
+#[test]
+fn timed_reports_every_exit() {
+    RECORDS.with(|records| records.borrow_mut().clear());
+
+    assert_eq!(parse(" 42 "), Ok(42));
+    // ? 提前返回时同样报告
+    assert!(parse("x").is_err());
+    let cache = Cache { items: vec![1, 2] };
+    assert_eq!(cache.total(), 3);
+    assert_eq!(Cache { items: vec![] }.total(), 0);
+
+    let records = RECORDS.with(|records| records.borrow().clone());
+    assert_eq!(records, ["parse", "parse", "Cache::total", "Cache::total"]);
+}
+
+/// 二维平面上的点
+#[derive(Describe)]
+#[allow(dead_code)]
+struct Point<T> {
+    /// 横坐标
+    x: T,
+    y: T,
+}
+
+#[derive(Describe)]
+#[allow(dead_code)]
+struct Meters(f64);
+
+#[derive(Describe)]
+#[allow(dead_code)]
+enum Shape {
+    /// 圆，参数是半径
+    Circle(f64),
+    Rect {
+        width: f64,
+        height: f64,
+    },
+    Empty,
+}
+
+#[test]
+fn describe_reports_declaration_and_variant() {
+    assert_eq!(
+        Point::<i32>::describe(),
+        "/// 二维平面上的点\nstruct Point<T> {\n    /// 横坐标\n    x: T,\n    y: T,\n}"
+    );
+    assert_eq!(Meters::describe(), "struct Meters(f64);");
+    assert_eq!(
+        Shape::describe(),
+        "enum Shape {\n    /// 圆，参数是半径\n    Circle(f64),\n    Rect { width: f64, height: f64 },\n    Empty,\n}"
+    );
+
+    let shapes = [
+        Shape::Circle(1.0),
+        Shape::Rect {
+            width: 2.0,
+            height: 3.0,
+        },
+        Shape::Empty,
+    ];
+    let names: Vec<&str> = shapes.iter().map(Shape::variant_name).collect();
+    assert_eq!(names, ["Circle", "Rect", "Empty"]);
+}
+
+#[test]
+fn misuse_is_reported_at_the_right_span() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
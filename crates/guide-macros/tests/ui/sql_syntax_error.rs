@@ -0,0 +1,5 @@
+use guide_macros::sql;
+
+fn main() {
+    let _ = sql!("SELECT name FORM users WHERE age > ?");
+}
@@ -0,0 +1,10 @@
+use guide_macros::timed;
+
+#[timed]
+const fn answer() -> u32 {
+    42
+}
+
+fn main() {
+    let _ = answer();
+}
@@ -0,0 +1,8 @@
+use guide_macros::timed;
+
+#[timed(threshold = 10)]
+fn work() {}
+
+fn main() {
+    work();
+}
@@ -0,0 +1,12 @@
+use guide_macros::timed;
+
+fn report(name: &str) {
+    println!("{}", name);
+}
+
+#[timed(with = report)]
+fn work() {}
+
+fn main() {
+    work();
+}
@@ -0,0 +1,175 @@
+// rust-code-guide 插件的 ABI 定义
+// 宿主（rust_code_guide::plugin）和插件都依赖这个 crate，双方只通过这里定义的 #[repr(C)] 类型交互：
+// 1. 插件导出一个名为 guide_plugin_entry 的 extern "C" 函数，返回指向 PluginVTable 的指针
+// 2. 宿主先检查 abi_version 和 size，再通过函数表创建实例、调用、销毁
+// 3. 插件分配的内存（PluginBuffer）必须交回插件的 free_buffer 释放，两边可能使用不同的分配器
+//
+// Rust 的 trait 对象、String、Vec 等类型没有稳定的 ABI，不能直接跨越动态库边界，
+// 所以函数表里只出现整数、裸指针和 extern "C" 函数指针
+//
+// 插件作者只需要实现 PluginImpl，再调用 export_plugin! 宏
+
+use std::ffi::{CStr, c_char, c_void};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+// 不兼容的修改（删除或重排字段、改变函数签名）必须增加版本号
+// 只在 PluginVTable 末尾追加字段时可以保持版本号不变，宿主通过 size 判断字段是否存在
+pub const ABI_VERSION: u32 = 1;
+
+// 插件必须导出的入口符号
+pub const ENTRY_SYMBOL: &CStr = c"guide_plugin_entry";
+
+pub type EntryFn = unsafe extern "C" fn() -> *const PluginVTable;
+
+// process 的返回码；STATUS_ERROR 时输出缓冲区中是错误信息
+pub const STATUS_OK: i32 = 0;
+pub const STATUS_ERROR: i32 = 1;
+pub const STATUS_INVALID_UTF8: i32 = 2;
+pub const STATUS_PANIC: i32 = 3;
+
+// 插件分配、由插件释放的字节缓冲区
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub cap: usize,
+}
+
+impl PluginBuffer {
+    pub const fn empty() -> Self {
+        PluginBuffer {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        }
+    }
+
+    pub fn from_string(text: String) -> Self {
+        let mut bytes = std::mem::ManuallyDrop::new(text.into_bytes());
+        PluginBuffer {
+            ptr: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            cap: bytes.capacity(),
+        }
+    }
+
+    /// 只读查看内容，宿主用它复制数据，然后交回 free_buffer
+    ///
+    /// # Safety
+    /// 缓冲区必须来自 from_string 或 empty，且尚未释放
+    pub unsafe fn as_bytes(&self) -> &[u8] {
+        if self.ptr.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+        }
+    }
+}
+
+// 插件函数表，宿主只能通过这里的函数指针访问插件
+#[repr(C)]
+pub struct PluginVTable {
+    pub abi_version: u32,
+    // size_of::<PluginVTable>()，用于向后兼容地追加字段
+    pub size: usize,
+    // 以 NUL 结尾的静态字符串
+    pub name: *const c_char,
+    pub version: *const c_char,
+    // 创建实例；失败时返回空指针
+    pub create: unsafe extern "C" fn() -> *mut c_void,
+    pub destroy: unsafe extern "C" fn(instance: *mut c_void),
+    // 处理一段 UTF-8 输入，结果（或错误信息）写入 out
+    pub process: unsafe extern "C" fn(
+        instance: *mut c_void,
+        input: *const u8,
+        len: usize,
+        out: *mut PluginBuffer,
+    ) -> i32,
+    pub free_buffer: unsafe extern "C" fn(buffer: PluginBuffer),
+}
+
+// 函数表只包含指向静态数据的指针和函数指针，可以放在 static 中跨线程共享
+unsafe impl Sync for PluginVTable {}
+
+// 插件作者实现的 trait；PluginVTable 中的函数由下面的泛型函数根据它生成
+pub trait PluginImpl: Default + 'static {
+    const NAME: &'static CStr;
+    const VERSION: &'static CStr;
+
+    fn process(&mut self, input: &str) -> Result<String, String>;
+}
+
+unsafe extern "C" fn create<P: PluginImpl>() -> *mut c_void {
+    catch_unwind(|| Box::into_raw(Box::new(P::default())) as *mut c_void)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+unsafe extern "C" fn destroy<P: PluginImpl>(instance: *mut c_void) {
+    if !instance.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            drop(unsafe { Box::from_raw(instance as *mut P) })
+        }));
+    }
+}
+
+unsafe extern "C" fn process<P: PluginImpl>(
+    instance: *mut c_void,
+    input: *const u8,
+    len: usize,
+    out: *mut PluginBuffer,
+) -> i32 {
+    if instance.is_null() || out.is_null() || (input.is_null() && len > 0) {
+        return STATUS_ERROR;
+    }
+    let plugin = unsafe { &mut *(instance as *mut P) };
+    let bytes = if len == 0 {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(input, len) }
+    };
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        unsafe { *out = PluginBuffer::empty() };
+        return STATUS_INVALID_UTF8;
+    };
+    // panic 不能穿过 extern "C" 边界，捕获后转换为返回码
+    let (status, message) = match catch_unwind(AssertUnwindSafe(|| plugin.process(text))) {
+        Ok(Ok(output)) => (STATUS_OK, output),
+        Ok(Err(message)) => (STATUS_ERROR, message),
+        Err(_) => (STATUS_PANIC, "插件发生 panic".to_string()),
+    };
+    unsafe { *out = PluginBuffer::from_string(message) };
+    status
+}
+
+unsafe extern "C" fn free_buffer(buffer: PluginBuffer) {
+    if !buffer.ptr.is_null() {
+        drop(unsafe { Vec::from_raw_parts(buffer.ptr, buffer.len, buffer.cap) });
+    }
+}
+
+// 为插件类型 P 生成函数表
+pub const fn vtable<P: PluginImpl>() -> PluginVTable {
+    PluginVTable {
+        abi_version: ABI_VERSION,
+        size: std::mem::size_of::<PluginVTable>(),
+        name: P::NAME.as_ptr(),
+        version: P::VERSION.as_ptr(),
+        create: create::<P>,
+        destroy: destroy::<P>,
+        process: process::<P>,
+        free_buffer,
+    }
+}
+
+// 在插件 crate（crate-type = ["cdylib"]）中导出入口函数
+#[macro_export]
+macro_rules! export_plugin {
+    ($plugin:ty) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn guide_plugin_entry() -> *const $crate::PluginVTable {
+            static VTABLE: $crate::PluginVTable = $crate::vtable::<$plugin>();
+            &VTABLE
+        }
+    };
+}
@@ -0,0 +1,745 @@
+// rust-code-guide 内存数据库（src/minidb.rs）支持的 SQL 子集
+// 运行时的 minidb 和编译期的 sql! 宏都依赖这个 crate，两边接受的语句因此完全一致：
+// 1. 词法分析：tokenize 把 SQL 切分成记号，每个记号记录原文和字符位置
+// 2. 语法分析：CREATE TABLE / INSERT / SELECT / UPDATE / DELETE，生成 Stmt 语法树
+// 3. 关键字标记：被语法分析当作关键字消耗的记号会被标记出来，sql! 据此把关键字转成大写
+//
+// 字面量用本 crate 的 Literal 表示，由使用者转换成自己的取值类型
+
+use std::fmt;
+
+// ===========================================
+// 1. 错误
+// ===========================================
+
+// 语法错误；能定位时信息以“第 N 个字符处”开头，N 从 1 开始按字符计数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub message: String,
+}
+
+impl SyntaxError {
+    fn new(message: impl Into<String>) -> Self {
+        SyntaxError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+// ===========================================
+// 2. 词法分析
+// ===========================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    // 标识符或关键字
+    Ident,
+    // 双引号标识符，永远不会被当作关键字
+    Quoted,
+    Integer(i64),
+    // 带小数点或超出 i64 范围的数字
+    Real(f64),
+    Text,
+    Param,
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    // 原文；Text 和 Quoted 是去掉引号、合并转义之后的内容
+    pub text: String,
+    // 在 SQL 中的字符位置，从 1 开始
+    pub at: usize,
+    // 被语法分析当作关键字消耗（包括列类型和 NULL）
+    pub keyword: bool,
+}
+
+impl Token {
+    pub fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.kind, TokenKind::Symbol(s) if s == symbol)
+    }
+
+    fn describe(&self) -> String {
+        match self.kind {
+            TokenKind::Text => format!("字符串 '{}'", self.text),
+            TokenKind::Quoted => format!("\"{}\"", self.text),
+            _ => format!("'{}'", self.text),
+        }
+    }
+}
+
+const SYMBOLS: &[&str] = &[
+    "<>", "!=", "<=", ">=", "(", ")", ",", ";", "*", "=", "<", ">", "-",
+];
+
+pub fn tokenize(sql: &str) -> Result<Vec<Token>, SyntaxError> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        let token = |kind, text| Token {
+            kind,
+            text,
+            at: start + 1,
+            keyword: false,
+        };
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '\'' || c == '"' {
+            // 引号内连续两个引号表示一个引号字符
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some(&q) if q == c => {
+                        if chars.get(i + 1) == Some(&c) {
+                            text.push(c);
+                            i += 2;
+                        } else {
+                            i += 1;
+                            break;
+                        }
+                    }
+                    Some(&other) => {
+                        text.push(other);
+                        i += 1;
+                    }
+                    None => {
+                        return Err(SyntaxError::new(format!(
+                            "第 {} 个字符处的引号没有闭合",
+                            start + 1
+                        )));
+                    }
+                }
+            }
+            let kind = if c == '\'' {
+                TokenKind::Text
+            } else {
+                TokenKind::Quoted
+            };
+            tokens.push(token(kind, text));
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            let kind = if let Ok(n) = number.parse::<i64>() {
+                TokenKind::Integer(n)
+            } else if let Ok(n) = number.parse::<f64>() {
+                TokenKind::Real(n)
+            } else {
+                return Err(SyntaxError::new(format!(
+                    "第 {} 个字符处是无效的数字 '{}'",
+                    start + 1,
+                    number
+                )));
+            };
+            tokens.push(token(kind, number));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(token(TokenKind::Ident, chars[start..i].iter().collect()));
+        } else if c == '?' {
+            i += 1;
+            tokens.push(token(TokenKind::Param, String::from("?")));
+        } else if let Some(&symbol) = SYMBOLS.iter().find(|symbol| {
+            symbol
+                .chars()
+                .enumerate()
+                .all(|(k, s)| chars.get(i + k) == Some(&s))
+        }) {
+            i += symbol.len();
+            tokens.push(token(TokenKind::Symbol(symbol), symbol.to_string()));
+        } else {
+            return Err(SyntaxError::new(format!(
+                "第 {} 个字符 '{}' 无法识别",
+                start + 1,
+                c
+            )));
+        }
+    }
+    Ok(tokens)
+}
+
+// ===========================================
+// 3. 语法树
+// ===========================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Text,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub ty: ColumnType,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Column(String),
+    Literal(Literal),
+    // 第几个 ?，从 0 开始
+    Param(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cond {
+    Compare(Operand, CmpOp, Operand),
+    // true 表示 IS NULL，false 表示 IS NOT NULL
+    IsNull(Operand, bool),
+    // 最后的 bool 表示带 NOT
+    Like(Operand, Operand, bool),
+    In(Operand, Vec<Operand>, bool),
+    And(Box<Cond>, Box<Cond>),
+    Or(Box<Cond>, Box<Cond>),
+    Not(Box<Cond>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    CreateTable {
+        name: String,
+        if_not_exists: bool,
+        columns: Vec<ColumnDef>,
+    },
+    Insert {
+        table: String,
+        columns: Option<Vec<String>>,
+        rows: Vec<Vec<Operand>>,
+    },
+    Select {
+        table: String,
+        columns: Option<Vec<String>>,
+        filter: Option<Cond>,
+        // (列名, 是否降序)
+        order_by: Vec<(String, bool)>,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    },
+    Update {
+        table: String,
+        assignments: Vec<(String, Operand)>,
+        filter: Option<Cond>,
+    },
+    Delete {
+        table: String,
+        filter: Option<Cond>,
+    },
+}
+
+// 解析好的单条语句，记录参数个数用于绑定时的范围检查
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed {
+    pub stmt: Stmt,
+    pub param_count: usize,
+}
+
+// ===========================================
+// 4. 语法分析
+// ===========================================
+
+const COLUMN_TYPES: &[(&str, ColumnType)] = &[
+    ("INTEGER", ColumnType::Integer),
+    ("INT", ColumnType::Integer),
+    ("REAL", ColumnType::Real),
+    ("FLOAT", ColumnType::Real),
+    ("DOUBLE", ColumnType::Real),
+    ("TEXT", ColumnType::Text),
+    ("VARCHAR", ColumnType::Text),
+];
+
+struct Parser<'a> {
+    tokens: &'a mut [Token],
+    pos: usize,
+    param_count: usize,
+}
+
+type Parse<T> = Result<T, SyntaxError>;
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    // 消耗当前记号并把它标记为关键字
+    fn take_keyword(&mut self) {
+        self.tokens[self.pos].keyword = true;
+        self.pos += 1;
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(t) if t.kind == TokenKind::Ident && t.text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.take_keyword();
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Parse<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(keyword))
+        }
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.is_symbol(symbol));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Parse<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", symbol)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> SyntaxError {
+        SyntaxError::new(match self.peek() {
+            Some(token) => format!(
+                "第 {} 个字符处应为 {}，但遇到 {}",
+                token.at,
+                expected,
+                token.describe()
+            ),
+            None => format!("应为 {}，但语句已结束", expected),
+        })
+    }
+
+    fn ident(&mut self) -> Parse<String> {
+        match self.peek() {
+            Some(t) if matches!(t.kind, TokenKind::Ident | TokenKind::Quoted) => {
+                let name = t.text.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.unexpected("标识符")),
+        }
+    }
+
+    // 用逗号分隔、至少一项的列表
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Parse<T>) -> Parse<Vec<T>> {
+        let mut items = vec![item(self)?];
+        while self.eat_symbol(",") {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn statement(&mut self) -> Parse<Stmt> {
+        if self.eat_keyword("CREATE") {
+            self.create_table()
+        } else if self.eat_keyword("INSERT") {
+            self.insert()
+        } else if self.eat_keyword("SELECT") {
+            self.select()
+        } else if self.eat_keyword("UPDATE") {
+            self.update()
+        } else if self.eat_keyword("DELETE") {
+            self.expect_keyword("FROM")?;
+            let table = self.ident()?;
+            let filter = self.filter()?;
+            Ok(Stmt::Delete { table, filter })
+        } else {
+            Err(self.unexpected("CREATE、INSERT、SELECT、UPDATE 或 DELETE"))
+        }
+    }
+
+    fn create_table(&mut self) -> Parse<Stmt> {
+        self.expect_keyword("TABLE")?;
+        let if_not_exists = self.eat_keyword("IF");
+        if if_not_exists {
+            self.expect_keyword("NOT")?;
+            self.expect_keyword("EXISTS")?;
+        }
+        let name = self.ident()?;
+        self.expect_symbol("(")?;
+        let columns = self.list(Self::column_def)?;
+        self.expect_symbol(")")?;
+        Ok(Stmt::CreateTable {
+            name,
+            if_not_exists,
+            columns,
+        })
+    }
+
+    // 列定义：名称 [类型] [NOT NULL] [PRIMARY KEY]
+    fn column_def(&mut self) -> Parse<ColumnDef> {
+        let name = self.ident()?;
+        let mut column = ColumnDef {
+            name,
+            ty: ColumnType::Any,
+            not_null: false,
+            primary_key: false,
+        };
+        loop {
+            if self.eat_keyword("NOT") {
+                self.expect_keyword("NULL")?;
+                column.not_null = true;
+            } else if self.eat_keyword("PRIMARY") {
+                self.expect_keyword("KEY")?;
+                column.primary_key = true;
+            } else if let Some(token) = self.peek().filter(|t| t.kind == TokenKind::Ident) {
+                column.ty = COLUMN_TYPES
+                    .iter()
+                    .find(|(name, _)| token.text.eq_ignore_ascii_case(name))
+                    .map(|&(_, ty)| ty)
+                    .ok_or_else(|| {
+                        SyntaxError::new(format!(
+                            "第 {} 个字符处是不支持的列类型 '{}'",
+                            token.at, token.text
+                        ))
+                    })?;
+                self.take_keyword();
+            } else {
+                return Ok(column);
+            }
+        }
+    }
+
+    fn insert(&mut self) -> Parse<Stmt> {
+        self.expect_keyword("INTO")?;
+        let table = self.ident()?;
+        let columns = if self.eat_symbol("(") {
+            let columns = self.list(Self::ident)?;
+            self.expect_symbol(")")?;
+            Some(columns)
+        } else {
+            None
+        };
+        self.expect_keyword("VALUES")?;
+        let rows = self.list(|p| {
+            p.expect_symbol("(")?;
+            let row = p.list(Self::operand)?;
+            p.expect_symbol(")")?;
+            Ok(row)
+        })?;
+        Ok(Stmt::Insert {
+            table,
+            columns,
+            rows,
+        })
+    }
+
+    fn select(&mut self) -> Parse<Stmt> {
+        let columns = if self.eat_symbol("*") {
+            None
+        } else {
+            Some(self.list(Self::ident)?)
+        };
+        self.expect_keyword("FROM")?;
+        let table = self.ident()?;
+        let filter = self.filter()?;
+
+        let mut order_by = Vec::new();
+        if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            order_by = self.list(|p| {
+                let column = p.ident()?;
+                let desc = p.eat_keyword("DESC");
+                if !desc {
+                    p.eat_keyword("ASC");
+                }
+                Ok((column, desc))
+            })?;
+        }
+        let limit = if self.eat_keyword("LIMIT") {
+            self.limit()?
+        } else {
+            None
+        };
+        let offset = if self.eat_keyword("OFFSET") {
+            Some(self.count()?)
+        } else {
+            None
+        };
+        Ok(Stmt::Select {
+            table,
+            columns,
+            filter,
+            order_by,
+            limit,
+            offset,
+        })
+    }
+
+    fn update(&mut self) -> Parse<Stmt> {
+        let table = self.ident()?;
+        self.expect_keyword("SET")?;
+        let assignments = self.list(|p| {
+            let column = p.ident()?;
+            p.expect_symbol("=")?;
+            Ok((column, p.operand()?))
+        })?;
+        let filter = self.filter()?;
+        Ok(Stmt::Update {
+            table,
+            assignments,
+            filter,
+        })
+    }
+
+    // LIMIT 的参数：与 SQLite 一样，负数表示不限行数（LIMIT -1 OFFSET n 是只跳过行的写法）
+    fn limit(&mut self) -> Parse<Option<u64>> {
+        if !self.eat_symbol("-") {
+            return Ok(Some(self.count()?));
+        }
+        match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Integer(_)) => {
+                self.pos += 1;
+                Ok(None)
+            }
+            _ => Err(self.unexpected("整数")),
+        }
+    }
+
+    // LIMIT 和 OFFSET 的参数：0..=i64::MAX 的整数
+    fn count(&mut self) -> Parse<u64> {
+        match self.peek().map(|t| &t.kind) {
+            Some(&TokenKind::Integer(n)) if n >= 0 => {
+                self.pos += 1;
+                Ok(n as u64)
+            }
+            _ => Err(self.unexpected("非负整数")),
+        }
+    }
+
+    fn filter(&mut self) -> Parse<Option<Cond>> {
+        if self.eat_keyword("WHERE") {
+            Ok(Some(self.or_cond()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // 优先级从低到高：OR < AND < NOT < 比较
+    fn or_cond(&mut self) -> Parse<Cond> {
+        let mut cond = self.and_cond()?;
+        while self.eat_keyword("OR") {
+            cond = Cond::Or(Box::new(cond), Box::new(self.and_cond()?));
+        }
+        Ok(cond)
+    }
+
+    fn and_cond(&mut self) -> Parse<Cond> {
+        let mut cond = self.not_cond()?;
+        while self.eat_keyword("AND") {
+            cond = Cond::And(Box::new(cond), Box::new(self.not_cond()?));
+        }
+        Ok(cond)
+    }
+
+    fn not_cond(&mut self) -> Parse<Cond> {
+        if self.eat_keyword("NOT") {
+            return Ok(Cond::Not(Box::new(self.not_cond()?)));
+        }
+        if self.eat_symbol("(") {
+            let cond = self.or_cond()?;
+            self.expect_symbol(")")?;
+            return Ok(cond);
+        }
+
+        let left = self.operand()?;
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Cond::IsNull(left, !negated));
+        }
+        let negated = self.eat_keyword("NOT");
+        if self.eat_keyword("LIKE") {
+            return Ok(Cond::Like(left, self.operand()?, negated));
+        }
+        if self.eat_keyword("IN") {
+            self.expect_symbol("(")?;
+            let items = self.list(Self::operand)?;
+            self.expect_symbol(")")?;
+            return Ok(Cond::In(left, items, negated));
+        }
+        if negated {
+            return Err(self.unexpected("LIKE 或 IN"));
+        }
+
+        let op = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Symbol("=")) => CmpOp::Eq,
+            Some(TokenKind::Symbol("<>" | "!=")) => CmpOp::Ne,
+            Some(TokenKind::Symbol("<")) => CmpOp::Lt,
+            Some(TokenKind::Symbol("<=")) => CmpOp::Le,
+            Some(TokenKind::Symbol(">")) => CmpOp::Gt,
+            Some(TokenKind::Symbol(">=")) => CmpOp::Ge,
+            _ => return Err(self.unexpected("比较运算符")),
+        };
+        self.pos += 1;
+        Ok(Cond::Compare(left, op, self.operand()?))
+    }
+
+    // 操作数：列名、字面量（数字可带负号）或 ? 参数
+    fn operand(&mut self) -> Parse<Operand> {
+        let negative = self.eat_symbol("-");
+        let Some(token) = self.peek() else {
+            return Err(self.unexpected("列名、字面量或 ?"));
+        };
+        let operand = match &token.kind {
+            TokenKind::Integer(n) if negative => Operand::Literal(Literal::Integer(-n)),
+            TokenKind::Integer(n) => Operand::Literal(Literal::Integer(*n)),
+            TokenKind::Real(n) if negative => Operand::Literal(Literal::Real(-n)),
+            TokenKind::Real(n) => Operand::Literal(Literal::Real(*n)),
+            _ if negative => return Err(self.unexpected("数字")),
+            TokenKind::Text => Operand::Literal(Literal::Text(token.text.clone())),
+            TokenKind::Param => {
+                self.param_count += 1;
+                Operand::Param(self.param_count - 1)
+            }
+            TokenKind::Ident if token.text.eq_ignore_ascii_case("NULL") => {
+                self.take_keyword();
+                return Ok(Operand::Literal(Literal::Null));
+            }
+            TokenKind::Ident | TokenKind::Quoted => Operand::Column(token.text.clone()),
+            TokenKind::Symbol(_) => return Err(self.unexpected("列名、字面量或 ?")),
+        };
+        self.pos += 1;
+        Ok(operand)
+    }
+}
+
+// 解析恰好构成一条语句的记号（不含分号），被当作关键字的记号会被标记
+pub fn parse_statement(tokens: &mut [Token]) -> Result<Parsed, SyntaxError> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        param_count: 0,
+    };
+    let stmt = parser.statement()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(parser.unexpected("语句结束"));
+    }
+    Ok(Parsed {
+        stmt,
+        param_count: parser.param_count,
+    })
+}
+
+// 把 SQL 文本切分成多条语句，空语句忽略
+pub fn parse_all(sql: &str) -> Result<Vec<Parsed>, SyntaxError> {
+    let mut tokens = tokenize(sql)?;
+    tokens
+        .split_mut(|token| token.is_symbol(";"))
+        .filter(|chunk| !chunk.is_empty())
+        .map(parse_statement)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_select() {
+        let parsed =
+            parse_all("select id from t where a >= -1.5 and b is not null limit 2;").unwrap();
+        assert_eq!(
+            parsed,
+            [Parsed {
+                stmt: Stmt::Select {
+                    table: "t".into(),
+                    columns: Some(vec!["id".into()]),
+                    filter: Some(Cond::And(
+                        Box::new(Cond::Compare(
+                            Operand::Column("a".into()),
+                            CmpOp::Ge,
+                            Operand::Literal(Literal::Real(-1.5))
+                        )),
+                        Box::new(Cond::IsNull(Operand::Column("b".into()), false)),
+                    )),
+                    order_by: Vec::new(),
+                    limit: Some(2),
+                    offset: None,
+                },
+                param_count: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_keywords_are_marked() {
+        let mut tokens = tokenize("insert into \"select\" (x) values (null, ?)").unwrap();
+        let parsed = parse_statement(&mut tokens).unwrap();
+        assert_eq!(parsed.param_count, 1);
+        let keywords: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t.keyword)
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(keywords, ["insert", "into", "values", "null"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases = [
+            // 超出 i64 的数字是 Real，不能作为 LIMIT
+            (
+                "SELECT * FROM t LIMIT 9223372036854775808",
+                "第 23 个字符处应为 非负整数，但遇到 '9223372036854775808'",
+            ),
+            (
+                "SELECT * FROM t WHERE a = -'x'",
+                "第 28 个字符处应为 数字，但遇到 字符串 'x'",
+            ),
+            ("DELETE FROM", "应为 标识符，但语句已结束"),
+            ("SELECT 1x FROM t", "第 8 个字符处是无效的数字 '1x'"),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(parse_all(sql).unwrap_err().message, expected, "{}", sql);
+        }
+        assert!(parse_all("SELECT * FROM t LIMIT 9223372036854775807").is_ok());
+    }
+}
@@ -0,0 +1,30 @@
+// 演示插件：把输入转换成大写并加上调用序号
+// 实例内部的计数器演示插件可以持有自己的状态；特殊输入用于测试宿主的错误处理：
+// - 空输入返回错误
+// - "panic" 会触发 panic，由 ABI 层捕获并转换为返回码
+
+use guide_plugin_api::{PluginImpl, export_plugin};
+use std::ffi::CStr;
+
+#[derive(Default)]
+struct Shout {
+    calls: u32,
+}
+
+impl PluginImpl for Shout {
+    const NAME: &'static CStr = c"shout";
+    const VERSION: &'static CStr = c"1.0.0";
+
+    fn process(&mut self, input: &str) -> Result<String, String> {
+        if input.is_empty() {
+            return Err("输入为空".to_string());
+        }
+        if input == "panic" {
+            panic!("按要求触发的 panic");
+        }
+        self.calls += 1;
+        Ok(format!("#{} {}!", self.calls, input.to_uppercase()))
+    }
+}
+
+export_plugin!(Shout);
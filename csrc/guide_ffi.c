@@ -0,0 +1,147 @@
+#include "guide_ffi.h"
+
+#include <ctype.h>
+#include <errno.h>
+#include <limits.h>
+#include <math.h>
+#include <stdalign.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* ---------- 结构体与枚举 ---------- */
+
+GuidePoint guide_point_midpoint(GuidePoint a, GuidePoint b) {
+    GuidePoint mid = {(a.x + b.x) / 2.0, (a.y + b.y) / 2.0};
+    return mid;
+}
+
+double guide_point_distance(const GuidePoint *a, const GuidePoint *b) {
+    if (a == NULL || b == NULL) {
+        return NAN;
+    }
+    return hypot(a->x - b->x, a->y - b->y);
+}
+
+size_t guide_point_size(void) { return sizeof(GuidePoint); }
+size_t guide_point_align(void) { return alignof(GuidePoint); }
+size_t guide_point_offset_y(void) { return offsetof(GuidePoint, y); }
+size_t guide_status_size(void) { return sizeof(GuideStatus); }
+
+GuidePoint guide_point_echo(GuidePoint p) { return p; }
+GuideStatus guide_status_echo(GuideStatus s) { return s; }
+
+/* ---------- 字符串工具 ---------- */
+
+size_t guide_str_length(const char *s) { return s == NULL ? 0 : strlen(s); }
+
+size_t guide_str_reverse(const char *s, char *out, size_t out_len) {
+    size_t len = guide_str_length(s);
+    if (out == NULL || out_len == 0) {
+        return len;
+    }
+    size_t written = len < out_len - 1 ? len : out_len - 1;
+    for (size_t i = 0; i < written; i++) {
+        out[i] = s[len - 1 - i];
+    }
+    out[written] = '\0';
+    return len;
+}
+
+char *guide_str_upper(const char *s) {
+    if (s == NULL) {
+        return NULL;
+    }
+    size_t len = strlen(s);
+    char *copy = malloc(len + 1);
+    if (copy == NULL) {
+        return NULL;
+    }
+    for (size_t i = 0; i < len; i++) {
+        copy[i] = (char)toupper((unsigned char)s[i]);
+    }
+    copy[len] = '\0';
+    return copy;
+}
+
+void guide_str_free(char *s) { free(s); }
+
+/* ---------- 回调 ---------- */
+
+int guide_apply(int value, int (*callback)(int)) {
+    return callback(value) + 10;
+}
+
+int guide_apply_with_context(int value, int (*callback)(void *, int), void *context) {
+    return callback(context, value) + 10;
+}
+
+void guide_sort(int *data, size_t len, int (*compare)(void *, int, int), void *context) {
+    if (data == NULL || compare == NULL) {
+        return;
+    }
+    for (size_t i = 1; i < len; i++) {
+        int item = data[i];
+        size_t j = i;
+        while (j > 0 && compare(context, data[j - 1], item) > 0) {
+            data[j] = data[j - 1];
+            j--;
+        }
+        data[j] = item;
+    }
+}
+
+/* ---------- 错误码 ---------- */
+
+static _Thread_local int last_error = GUIDE_OK;
+
+static int set_error(int code) {
+    last_error = code;
+    return code;
+}
+
+int guide_divide(double a, double b, double *out) {
+    if (out == NULL) {
+        return set_error(GUIDE_ERR_NULL_POINTER);
+    }
+    if (b == 0.0) {
+        return set_error(GUIDE_ERR_DIVIDE_BY_ZERO);
+    }
+    *out = a / b;
+    return set_error(GUIDE_OK);
+}
+
+int guide_parse_int(const char *text, long *out) {
+    if (text == NULL || out == NULL) {
+        return set_error(GUIDE_ERR_NULL_POINTER);
+    }
+    char *end = NULL;
+    errno = 0;
+    long value = strtol(text, &end, 10);
+    if (end == text || *end != '\0') {
+        return set_error(GUIDE_ERR_INVALID_NUMBER);
+    }
+    if (errno == ERANGE) {
+        return set_error(GUIDE_ERR_OUT_OF_RANGE);
+    }
+    *out = value;
+    return set_error(GUIDE_OK);
+}
+
+int guide_last_error(void) { return last_error; }
+
+const char *guide_strerror(int code) {
+    switch (code) {
+    case GUIDE_OK:
+        return "success";
+    case GUIDE_ERR_NULL_POINTER:
+        return "null pointer";
+    case GUIDE_ERR_DIVIDE_BY_ZERO:
+        return "division by zero";
+    case GUIDE_ERR_INVALID_NUMBER:
+        return "invalid number";
+    case GUIDE_ERR_OUT_OF_RANGE:
+        return "number out of range";
+    default:
+        return "unknown error";
+    }
+}
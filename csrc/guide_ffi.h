@@ -0,0 +1,71 @@
+/* 第 15 章 FFI 示例使用的 C 库
+ * 由 build.rs 通过 cc 编译并静态链接进 rust-code-guide
+ * Rust 侧的绑定在 src/mod_15_ffi.rs 的 clib 模块中，修改这里的声明时需要同步修改 */
+#ifndef GUIDE_FFI_H
+#define GUIDE_FFI_H
+
+#include <stddef.h>
+
+/* ---------- 结构体与枚举 ---------- */
+
+typedef struct {
+    double x;
+    double y;
+} GuidePoint;
+
+typedef enum {
+    GUIDE_STATUS_SUCCESS = 0,
+    GUIDE_STATUS_ERROR = 1,
+    GUIDE_STATUS_PENDING = 2,
+} GuideStatus;
+
+/* 按值传入、按值返回结构体 */
+GuidePoint guide_point_midpoint(GuidePoint a, GuidePoint b);
+double guide_point_distance(const GuidePoint *a, const GuidePoint *b);
+
+/* 布局信息，用于和 Rust 的 #[repr(C)] 定义互相核对 */
+size_t guide_point_size(void);
+size_t guide_point_align(void);
+size_t guide_point_offset_y(void);
+size_t guide_status_size(void);
+
+/* 原样返回，用于检查跨边界传递后取值不变 */
+GuidePoint guide_point_echo(GuidePoint p);
+GuideStatus guide_status_echo(GuideStatus s);
+
+/* ---------- 字符串工具 ---------- */
+
+size_t guide_str_length(const char *s);
+/* 把 s 反转后写入 out（包括结尾的 '\0'）；返回完整结果需要的长度（不含 '\0'），
+ * 与 snprintf 一样，返回值 >= out_len 说明结果被截断 */
+size_t guide_str_reverse(const char *s, char *out, size_t out_len);
+/* 返回新分配的大写副本，必须用 guide_str_free 释放；内存不足时返回 NULL */
+char *guide_str_upper(const char *s);
+void guide_str_free(char *s);
+
+/* ---------- 回调 ---------- */
+
+/* 返回 callback(value) + 10 */
+int guide_apply(int value, int (*callback)(int));
+/* 带用户数据指针的版本：返回 callback(context, value) + 10 */
+int guide_apply_with_context(int value, int (*callback)(void *context, int value), void *context);
+/* 用调用方提供的比较函数对数组做稳定排序（插入排序），compare 的返回值约定与 qsort 相同 */
+void guide_sort(int *data, size_t len, int (*compare)(void *context, int a, int b), void *context);
+
+/* ---------- 错误码 ---------- */
+
+#define GUIDE_OK 0
+#define GUIDE_ERR_NULL_POINTER 1
+#define GUIDE_ERR_DIVIDE_BY_ZERO 2
+#define GUIDE_ERR_INVALID_NUMBER 3
+#define GUIDE_ERR_OUT_OF_RANGE 4
+
+/* 失败时返回错误码并保持 *out 不变，同时记录到线程局部的"最后错误" */
+int guide_divide(double a, double b, double *out);
+int guide_parse_int(const char *text, long *out);
+/* 当前线程最近一次调用的错误码，成功的调用会把它重置为 GUIDE_OK */
+int guide_last_error(void);
+/* 错误码的静态描述字符串，调用方不能释放 */
+const char *guide_strerror(int code);
+
+#endif
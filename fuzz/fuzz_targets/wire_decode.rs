@@ -0,0 +1,15 @@
+// 用任意字节解码 PacketHeader：不能 panic，能解码的输入重新编码后必须与原始字节一致
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_code_guide::wire::PacketHeader;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((header, rest)) = PacketHeader::decode_prefix(data) {
+        let encoded = header.encode();
+        assert_eq!(encoded.len(), PacketHeader::ENCODED_LEN);
+        assert_eq!(&data[..encoded.len()], &encoded[..]);
+        assert_eq!(rest, &data[encoded.len()..]);
+        assert_eq!(PacketHeader::decode(&encoded), Ok(header));
+    }
+});
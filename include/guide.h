@@ -0,0 +1,105 @@
+/* 由 tests/capi.rs 根据 src/capi.rs 生成，不要手动修改
+ * 重新生成：UPDATE_GOLDEN=1 cargo test --test capi */
+#ifndef GUIDE_H
+#define GUIDE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* 所有可能失败的导出函数的返回值 */
+typedef enum {
+    GUIDE_RESULT_OK = 0,
+    GUIDE_RESULT_NULL_POINTER = 1,
+    GUIDE_RESULT_INVALID_UTF8 = 2,
+    GUIDE_RESULT_DIMENSION_MISMATCH = 3,
+    GUIDE_RESULT_OUT_OF_BOUNDS = 4,
+    GUIDE_RESULT_BUFFER_TOO_SMALL = 5,
+    GUIDE_RESULT_PANIC = 6,
+    GUIDE_RESULT_TOO_LARGE = 7,
+} GuideResult;
+
+/* 文本分析器句柄 */
+typedef struct GuideTextAnalyzer GuideTextAnalyzer;
+
+/* 文本统计结果，由调用方分配 */
+typedef struct {
+    size_t words;
+    size_t characters;
+    size_t lines;
+    size_t unique_words;
+} GuideTextStats;
+
+/* 矩阵句柄，元素为 double，按行主序存储 */
+typedef struct GuideMatrix GuideMatrix;
+
+/* 返回当前线程最近一次失败的错误信息，没有错误时返回 NULL
+ * 返回的字符串由调用方用 guide_string_free 释放 */
+char *guide_last_error_message(void);
+
+/* 释放本库返回的字符串，传入 NULL 是无害的空操作 */
+void guide_string_free(char *s);
+
+/* 库版本号，返回静态字符串，调用方不能释放 */
+const char *guide_version(void);
+
+/* 用 UTF-8 文本创建分析器，文本会被复制 */
+GuideResult guide_text_analyzer_new(const char *text, GuideTextAnalyzer **out);
+
+/* 释放分析器，传入 NULL 是无害的空操作 */
+void guide_text_analyzer_free(GuideTextAnalyzer *analyzer);
+
+/* 计算单词数、字符数、行数和不重复单词数 */
+GuideResult guide_text_analyzer_stats(const GuideTextAnalyzer *analyzer, GuideTextStats *out);
+
+/* 出现次数最多的单词（小写、去掉结尾标点）
+ * 文本中没有单词时 *out_word 被设为 NULL，*out_count 为 0
+ * *out_word 由调用方用 guide_string_free 释放 */
+GuideResult guide_text_analyzer_most_common_word(const GuideTextAnalyzer *analyzer, char **out_word, size_t *out_count);
+
+/* 创建全零矩阵 */
+GuideResult guide_matrix_new(size_t rows, size_t cols, GuideMatrix **out);
+
+/* 用行主序的 rows * cols 个元素创建矩阵，数据会被复制 */
+GuideResult guide_matrix_from_data(size_t rows, size_t cols, const double *data, GuideMatrix **out);
+
+/* 释放矩阵，传入 NULL 是无害的空操作 */
+void guide_matrix_free(GuideMatrix *matrix);
+
+/* 行数，matrix 为 NULL 时返回 0 */
+size_t guide_matrix_rows(const GuideMatrix *matrix);
+
+/* 列数，matrix 为 NULL 时返回 0 */
+size_t guide_matrix_cols(const GuideMatrix *matrix);
+
+/* 读取一个元素 */
+GuideResult guide_matrix_get(const GuideMatrix *matrix, size_t row, size_t col, double *out);
+
+/* 写入一个元素 */
+GuideResult guide_matrix_set(GuideMatrix *matrix, size_t row, size_t col, double value);
+
+/* 矩阵相加，结果是新矩阵，由调用方用 guide_matrix_free 释放 */
+GuideResult guide_matrix_add(const GuideMatrix *a, const GuideMatrix *b, GuideMatrix **out);
+
+/* 矩阵相乘，结果是新矩阵，由调用方用 guide_matrix_free 释放 */
+GuideResult guide_matrix_multiply(const GuideMatrix *a, const GuideMatrix *b, GuideMatrix **out);
+
+/* 转置，结果是新矩阵，由调用方用 guide_matrix_free 释放 */
+GuideResult guide_matrix_transpose(const GuideMatrix *matrix, GuideMatrix **out);
+
+/* 把全部元素按行主序复制到调用方提供的缓冲区
+ * 缓冲区不足 rows * cols 个元素时返回 GUIDE_RESULT_BUFFER_TOO_SMALL，且不写入任何数据 */
+GuideResult guide_matrix_copy_data(const GuideMatrix *matrix, double *buffer, size_t len);
+
+/* 矩阵的文本形式，每行一个 "[a, b, c]"；matrix 为 NULL 时返回 NULL
+ * 返回的字符串由调用方用 guide_string_free 释放 */
+char *guide_matrix_to_string(const GuideMatrix *matrix);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,757 @@
+// 自定义分配器
+// 第 14 章和第 16 章的 MemoryPool 演示只做到了"分配"，这里补全成可以实际使用的四种分配器：
+// 1. Arena：bump 分配，只移动一个偏移量，按类型对齐；整体释放，不逐个释放
+// 2. FreeList：固定大小的堆，首次适配分配，释放时与相邻空闲块合并以减少碎片
+// 3. Pool<T>：同一类型的对象池（slab），返回 RAII 句柄，句柄 drop 时槽位回到空闲链表
+// 4. CountingAlloc：实现 GlobalAlloc，包装另一个分配器并统计分配次数和字节数
+//
+// 地址只用于计算对齐，实际的指针都由原始指针 add 偏移得到，保留指针来源（provenance），可以在 Miri 下检查：
+// cargo +nightly miri test --features miri --test miri
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 把地址向上取整到 align 的倍数；align 必须是 2 的幂
+fn align_up(addr: usize, align: usize) -> usize {
+    debug_assert!(align.is_power_of_two());
+    (addr + align - 1) & !(align - 1)
+}
+
+// 向系统申请的一大块内存，drop 时归还
+struct Block {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Block {
+    fn new(size: usize, align: usize) -> Block {
+        let layout = Layout::from_size_align(size.max(1), align).expect("块大小溢出");
+        let ptr = unsafe { std::alloc::alloc(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
+        Block { ptr, layout }
+    }
+
+    fn size(&self) -> usize {
+        self.layout.size()
+    }
+}
+
+impl Drop for Block {
+    fn drop(&mut self) {
+        unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+    }
+}
+
+// ===========================================
+// 1. Arena：bump 分配器
+// ===========================================
+
+// 从大块内存中按顺序切出空间，分配只是移动偏移量
+// 分配出的引用与 arena 同生命周期；arena 不会调用其中对象的析构函数，
+// 需要析构的类型应放进 Pool<T> 或普通的 Box
+pub struct Arena {
+    chunks: RefCell<Vec<Block>>,
+    // 当前块（最后一块）已使用的字节数
+    used: Cell<usize>,
+    allocated: Cell<usize>,
+}
+
+impl Arena {
+    const MIN_CHUNK: usize = 1024;
+    const CHUNK_ALIGN: usize = 16;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::MIN_CHUNK)
+    }
+
+    pub fn with_capacity(bytes: usize) -> Self {
+        Arena {
+            chunks: RefCell::new(vec![Block::new(bytes, Self::CHUNK_ALIGN)]),
+            used: Cell::new(0),
+            allocated: Cell::new(0),
+        }
+    }
+
+    // 按 layout 分配未初始化的内存
+    pub fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        let mut chunks = self.chunks.borrow_mut();
+        let current = chunks.last().expect("arena 至少有一块内存");
+        let base = current.ptr.as_ptr().addr();
+        let start = align_up(base + self.used.get(), layout.align()) - base;
+
+        let offset = if start + layout.size() <= current.size() {
+            start
+        } else {
+            // 当前块放不下：新块至少翻倍，并且保证对齐后仍然放得下
+            let size = (current.size() * 2).max(layout.size() + layout.align());
+            chunks.push(Block::new(size, Self::CHUNK_ALIGN.max(layout.align())));
+            0
+        };
+        let chunk = chunks.last().unwrap();
+        self.used.set(offset + layout.size());
+        self.allocated.set(self.allocated.get() + layout.size());
+        unsafe { NonNull::new_unchecked(chunk.ptr.as_ptr().add(offset)) }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+        // 每次分配的内存互不重叠，所以可以从 &self 返回 &mut T
+        unsafe {
+            ptr.as_ptr().write(value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, values: &[T]) -> &mut [T] {
+        let layout = Layout::array::<T>(values.len()).expect("切片大小溢出");
+        let ptr = self.alloc_layout(layout).cast::<T>();
+        unsafe {
+            std::ptr::copy_nonoverlapping(values.as_ptr(), ptr.as_ptr(), values.len());
+            std::slice::from_raw_parts_mut(ptr.as_ptr(), values.len())
+        }
+    }
+
+    pub fn alloc_str(&self, text: &str) -> &str {
+        let bytes = self.alloc_slice_copy(text.as_bytes());
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+
+    // 已分配给调用者的字节数（不含对齐填充）
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated.get()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+
+    // 释放除最大一块以外的所有内存，并从头开始复用；需要 &mut self，保证没有引用还指向 arena
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if let Some(largest) = (0..chunks.len()).max_by_key(|&i| chunks[i].size()) {
+            let keep = chunks.swap_remove(largest);
+            chunks.clear();
+            chunks.push(keep);
+        }
+        self.used.set(0);
+        self.allocated.set(0);
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Arena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("chunks", &self.chunk_count())
+            .field("allocated", &self.allocated.get())
+            .finish()
+    }
+}
+
+// ===========================================
+// 2. FreeList：带合并的空闲链表分配器
+// ===========================================
+
+// 空闲块或已分配块在堆中的位置（相对于堆起点的偏移）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub offset: usize,
+    pub size: usize,
+}
+
+impl Span {
+    fn end(&self) -> usize {
+        self.offset + self.size
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeListStats {
+    pub capacity: usize,
+    pub free_bytes: usize,
+    pub free_blocks: usize,
+    pub largest_free: usize,
+    pub live_allocations: usize,
+}
+
+impl FreeListStats {
+    // 外部碎片率：空闲内存中不属于最大空闲块的比例，0 表示空闲内存连成一片
+    pub fn fragmentation(&self) -> f64 {
+        if self.free_bytes == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free as f64 / self.free_bytes as f64
+        }
+    }
+}
+
+// 在一块固定大小的堆上分配
+// 空闲块按偏移排序保存在堆外的 Vec 中，已分配块记录在 live 中：
+// 释放时能检查出重复释放和无效指针，而不是悄悄破坏堆
+pub struct FreeList {
+    heap: Block,
+    free: Vec<Span>,
+    live: Vec<Span>,
+}
+
+impl FreeList {
+    // 所有块的大小都是 GRANULE 的倍数，避免留下无法使用的零碎空间
+    pub const GRANULE: usize = 8;
+    const HEAP_ALIGN: usize = 16;
+
+    pub fn new(capacity: usize) -> Self {
+        let capacity = align_up(capacity.max(Self::GRANULE), Self::GRANULE);
+        FreeList {
+            heap: Block::new(capacity, Self::HEAP_ALIGN),
+            free: vec![Span {
+                offset: 0,
+                size: capacity,
+            }],
+            live: Vec::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.heap.size()
+    }
+
+    // 首次适配：使用第一个放得下的空闲块，剩余部分留在空闲链表中
+    // 内存不足（或碎片太多）时返回 None
+    pub fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        let size = align_up(layout.size().max(1), Self::GRANULE);
+        let align = layout.align().max(Self::GRANULE);
+        let base = self.heap.ptr.as_ptr().addr();
+
+        let (index, start) = self.free.iter().enumerate().find_map(|(i, span)| {
+            let start = align_up(base + span.offset, align) - base;
+            (start + size <= span.end()).then_some((i, start))
+        })?;
+
+        // 把空闲块拆成：对齐产生的前缀、分配出去的部分、剩余的后缀
+        let span = self.free.remove(index);
+        let suffix = Span {
+            offset: start + size,
+            size: span.end() - (start + size),
+        };
+        if suffix.size > 0 {
+            self.free.insert(index, suffix);
+        }
+        if start > span.offset {
+            self.free.insert(
+                index,
+                Span {
+                    offset: span.offset,
+                    size: start - span.offset,
+                },
+            );
+        }
+
+        let allocation = Span {
+            offset: start,
+            size,
+        };
+        let pos = self.live.partition_point(|s| s.offset < start);
+        self.live.insert(pos, allocation);
+        Some(unsafe { NonNull::new_unchecked(self.heap.ptr.as_ptr().add(start)) })
+    }
+
+    // 释放 allocate 返回的指针，并与前后相邻的空闲块合并
+    // 指针不是当前有效的分配（包括重复释放）时 panic
+    pub fn deallocate(&mut self, ptr: NonNull<u8>) {
+        let offset = ptr
+            .as_ptr()
+            .addr()
+            .wrapping_sub(self.heap.ptr.as_ptr().addr());
+        let pos = self
+            .live
+            .binary_search_by_key(&offset, |s| s.offset)
+            .unwrap_or_else(|_| panic!("{:p} 不是这个分配器中有效的分配", ptr));
+        let mut span = self.live.remove(pos);
+
+        let index = self.free.partition_point(|s| s.offset < span.offset);
+        // 与后一个空闲块相邻：吸收它
+        if let Some(next) = self.free.get(index).copied()
+            && span.end() == next.offset
+        {
+            span.size += next.size;
+            self.free.remove(index);
+        }
+        // 与前一个空闲块相邻：并入它
+        if index > 0 && self.free[index - 1].end() == span.offset {
+            self.free[index - 1].size += span.size;
+        } else {
+            self.free.insert(index, span);
+        }
+    }
+
+    pub fn free_spans(&self) -> &[Span] {
+        &self.free
+    }
+
+    pub fn stats(&self) -> FreeListStats {
+        FreeListStats {
+            capacity: self.capacity(),
+            free_bytes: self.free.iter().map(|s| s.size).sum(),
+            free_blocks: self.free.len(),
+            largest_free: self.free.iter().map(|s| s.size).max().unwrap_or(0),
+            live_allocations: self.live.len(),
+        }
+    }
+}
+
+impl fmt::Debug for FreeList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FreeList")
+            .field("free", &self.free)
+            .field("live", &self.live)
+            .finish()
+    }
+}
+
+// ===========================================
+// 3. Pool<T>：对象池
+// ===========================================
+
+type Slot<T> = UnsafeCell<MaybeUninit<T>>;
+
+// 同一类型对象的 slab：按固定大小的段分配槽位，段一旦分配就不会移动，
+// 所以已发出的句柄在池增长时依然有效；释放的槽位按后进先出复用
+pub struct Pool<T> {
+    // 段用裸指针保存：Vec 扩容时移动的只是指针，不会影响段内已借出的对象
+    segments: RefCell<Vec<NonNull<[Slot<T>]>>>,
+    segment_len: usize,
+    free: RefCell<Vec<usize>>,
+    // 从未使用过的下一个槽位
+    next: Cell<usize>,
+    live: Cell<usize>,
+}
+
+impl<T> Pool<T> {
+    pub fn new() -> Self {
+        Self::with_segment_len(64)
+    }
+
+    pub fn with_segment_len(segment_len: usize) -> Self {
+        assert!(segment_len > 0, "段长度必须大于 0");
+        Pool {
+            segments: RefCell::new(Vec::new()),
+            segment_len,
+            free: RefCell::new(Vec::new()),
+            next: Cell::new(0),
+            live: Cell::new(0),
+        }
+    }
+
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        let mut segments = self.segments.borrow_mut();
+        let segment = index / self.segment_len;
+        if segment == segments.len() {
+            let slots: Box<[Slot<T>]> = (0..self.segment_len)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect();
+            segments.push(NonNull::from(Box::leak(slots)));
+        }
+        let slots = unsafe { segments[segment].as_ref() };
+        slots[index % self.segment_len].get()
+    }
+
+    pub fn alloc(&self, value: T) -> PoolBox<'_, T> {
+        let index = self.free.borrow_mut().pop().unwrap_or_else(|| {
+            let index = self.next.get();
+            self.next.set(index + 1);
+            index
+        });
+        let slot = self.slot(index);
+        let ptr = unsafe {
+            (*slot).write(value);
+            NonNull::new_unchecked(slot as *mut T)
+        };
+        self.live.set(self.live.get() + 1);
+        PoolBox {
+            pool: self,
+            index,
+            ptr,
+            _owns: PhantomData,
+        }
+    }
+
+    // 当前存活的对象数
+    pub fn len(&self) -> usize {
+        self.live.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 已分配的槽位数（包括空闲槽位）
+    pub fn capacity(&self) -> usize {
+        self.segments.borrow().len() * self.segment_len
+    }
+
+    fn release(&self, index: usize) {
+        self.free.borrow_mut().push(index);
+        self.live.set(self.live.get() - 1);
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Pool<T> {
+    fn drop(&mut self) {
+        // 句柄借用了池，池被 drop 时所有对象都已析构，只需归还段的内存
+        for segment in self.segments.get_mut().drain(..) {
+            drop(unsafe { Box::from_raw(segment.as_ptr()) });
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+// Pool 中对象的所有权句柄，用法与 Box 相同；drop 时析构对象并把槽位还给池
+pub struct PoolBox<'pool, T> {
+    pool: &'pool Pool<T>,
+    index: usize,
+    ptr: NonNull<T>,
+    _owns: PhantomData<T>,
+}
+
+impl<T> PoolBox<'_, T> {
+    // 取出对象，槽位立即回到池中
+    pub fn into_inner(self) -> T {
+        let this = std::mem::ManuallyDrop::new(self);
+        let value = unsafe { this.ptr.as_ptr().read() };
+        this.pool.release(this.index);
+        value
+    }
+}
+
+impl<T> Deref for PoolBox<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> DerefMut for PoolBox<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> Drop for PoolBox<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.ptr.as_ptr().drop_in_place() };
+        self.pool.release(self.index);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PoolBox<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// ===========================================
+// 4. CountingAlloc：统计分配次数的全局分配器
+// ===========================================
+
+// 包装另一个分配器（默认 System），把每次调用记到原子计数器上
+// 作为全局分配器使用：
+// #[global_allocator]
+// static GLOBAL: CountingAlloc = CountingAlloc::system();
+pub struct CountingAlloc<A = System> {
+    inner: A,
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    reallocations: AtomicUsize,
+    current_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub reallocations: usize,
+    pub current_bytes: usize,
+    pub peak_bytes: usize,
+}
+
+impl AllocStats {
+    // 两次快照之间的增量；current_bytes 为两者之差（可能为负时记为 0），peak_bytes 取后者
+    pub fn since(&self, earlier: &AllocStats) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations - earlier.allocations,
+            deallocations: self.deallocations - earlier.deallocations,
+            reallocations: self.reallocations - earlier.reallocations,
+            current_bytes: self.current_bytes.saturating_sub(earlier.current_bytes),
+            peak_bytes: self.peak_bytes,
+        }
+    }
+}
+
+impl CountingAlloc<System> {
+    pub const fn system() -> Self {
+        Self::new(System)
+    }
+}
+
+impl<A> CountingAlloc<A> {
+    pub const fn new(inner: A) -> Self {
+        CountingAlloc {
+            inner,
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            reallocations: AtomicUsize::new(0),
+            current_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+            reallocations: self.reallocations.load(Ordering::Relaxed),
+            current_bytes: self.current_bytes.load(Ordering::Relaxed),
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn grow(&self, bytes: usize) {
+        let current = self.current_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak_bytes.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrink(&self, bytes: usize) {
+        self.current_bytes.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+// 统计本身不分配内存，只使用原子操作，因此不会在分配器内部递归
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            self.allocations.fetch_add(1, Ordering::Relaxed);
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            self.allocations.fetch_add(1, Ordering::Relaxed);
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        self.shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            self.reallocations.fetch_add(1, Ordering::Relaxed);
+            if new_size > layout.size() {
+                self.grow(new_size - layout.size());
+            } else {
+                self.shrink(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_alignment_and_growth() {
+        let arena = Arena::with_capacity(64);
+        let byte = arena.alloc(1u8);
+        let wide = arena.alloc(0x1122_3344_5566_7788u64);
+        #[repr(align(32))]
+        struct Aligned(u8);
+        let aligned = arena.alloc(Aligned(7));
+        assert_eq!(*byte, 1);
+        assert_eq!(*wide, 0x1122_3344_5566_7788);
+        assert_eq!((wide as *const u64).addr() % 8, 0);
+        assert_eq!((aligned as *const Aligned).addr() % 32, 0);
+        assert_eq!(aligned.0, 7);
+
+        // 放不下时开新块，之前的引用仍然有效
+        let big = arena.alloc_slice_copy(&[3u32; 100]);
+        assert!(arena.chunk_count() > 1);
+        assert_eq!(big.iter().sum::<u32>(), 300);
+        assert_eq!(arena.alloc_str("arena"), "arena");
+        assert_eq!(*wide, 0x1122_3344_5566_7788);
+        assert_eq!(arena.allocated_bytes(), 1 + 8 + 32 + 400 + 5);
+
+        let mut arena = arena;
+        arena.reset();
+        assert_eq!((arena.chunk_count(), arena.allocated_bytes()), (1, 0));
+    }
+
+    #[test]
+    fn test_free_list_fragmentation_and_coalescing() {
+        let mut heap = FreeList::new(256);
+        let layout = Layout::from_size_align(32, 8).unwrap();
+        let blocks: Vec<_> = (0..8).map(|_| heap.allocate(layout).unwrap()).collect();
+        assert!(heap.allocate(layout).is_none());
+        assert_eq!(heap.stats().free_bytes, 0);
+
+        // 释放隔一个的块：128 字节空闲，但分成 4 个不相邻的 32 字节块
+        for block in blocks.iter().step_by(2) {
+            heap.deallocate(*block);
+        }
+        let stats = heap.stats();
+        assert_eq!(
+            (stats.free_bytes, stats.free_blocks, stats.largest_free),
+            (128, 4, 32)
+        );
+        assert!((stats.fragmentation() - 0.75).abs() < 1e-9);
+        // 总空闲足够但没有连续的 64 字节
+        assert!(
+            heap.allocate(Layout::from_size_align(64, 8).unwrap())
+                .is_none()
+        );
+
+        // 释放中间的块后，三个相邻块合并成 96 字节
+        heap.deallocate(blocks[1]);
+        assert_eq!(
+            heap.free_spans()[0],
+            Span {
+                offset: 0,
+                size: 96
+            }
+        );
+        let merged = heap
+            .allocate(Layout::from_size_align(64, 8).unwrap())
+            .unwrap();
+        assert_eq!(merged, blocks[0]);
+
+        for block in [merged, blocks[3], blocks[5], blocks[7]] {
+            heap.deallocate(block);
+        }
+        assert_eq!(
+            heap.free_spans(),
+            &[Span {
+                offset: 0,
+                size: 256
+            }]
+        );
+        assert_eq!(heap.stats().fragmentation(), 0.0);
+    }
+
+    #[test]
+    fn test_free_list_alignment_padding_is_reusable() {
+        let mut heap = FreeList::new(512);
+        let small = heap.allocate(Layout::new::<u8>()).unwrap();
+        let aligned = heap
+            .allocate(Layout::from_size_align(64, 128).unwrap())
+            .unwrap();
+        assert_eq!(aligned.as_ptr().addr() % 128, 0);
+        // 对齐留下的空隙仍然可以分配
+        let filler = heap.allocate(Layout::new::<u64>()).unwrap();
+        assert!(filler < aligned);
+
+        for ptr in [aligned, small, filler] {
+            heap.deallocate(ptr);
+        }
+        assert_eq!(heap.stats().free_blocks, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "不是这个分配器中有效的分配")]
+    fn test_free_list_rejects_double_free() {
+        let mut heap = FreeList::new(64);
+        let ptr = heap.allocate(Layout::new::<u32>()).unwrap();
+        heap.deallocate(ptr);
+        heap.deallocate(ptr);
+    }
+
+    #[test]
+    fn test_pool_handles_reuse_slots_and_run_drop() {
+        use std::rc::Rc;
+
+        let marker = Rc::new(());
+        let pool = Pool::with_segment_len(2);
+        let a = pool.alloc((1, Rc::clone(&marker)));
+        let mut b = pool.alloc((2, Rc::clone(&marker)));
+        let c = pool.alloc((3, Rc::clone(&marker)));
+        assert_eq!((pool.len(), pool.capacity()), (3, 4));
+        assert_eq!(Rc::strong_count(&marker), 4);
+
+        b.0 = 20;
+        let b_addr = &*b as *const _ as usize;
+        drop(b);
+        assert_eq!(Rc::strong_count(&marker), 3);
+        // 空闲槽位被下一次分配复用；增长出的新段不影响已有句柄
+        let d = pool.alloc((4, Rc::clone(&marker)));
+        assert_eq!(&*d as *const _ as usize, b_addr);
+        assert_eq!((a.0, c.0, d.0), (1, 3, 4));
+
+        let (value, _) = c.into_inner();
+        assert_eq!(value, 3);
+        assert_eq!(pool.len(), 2);
+        drop((a, d));
+        assert!(pool.is_empty());
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn test_counting_alloc_tracks_bytes() {
+        let counter = CountingAlloc::system();
+        let layout = Layout::from_size_align(100, 8).unwrap();
+        unsafe {
+            let ptr = counter.alloc(layout);
+            let ptr = counter.realloc(ptr, layout, 300);
+            let zeroed = counter.alloc_zeroed(layout);
+            assert_eq!(*zeroed, 0);
+            let stats = counter.stats();
+            assert_eq!((stats.allocations, stats.reallocations), (2, 1));
+            assert_eq!((stats.current_bytes, stats.peak_bytes), (400, 400));
+
+            counter.dealloc(ptr, Layout::from_size_align(300, 8).unwrap());
+            counter.dealloc(zeroed, layout);
+        }
+        let stats = counter.stats();
+        assert_eq!(
+            (stats.deallocations, stats.current_bytes, stats.peak_bytes),
+            (2, 0, 400)
+        );
+    }
+}
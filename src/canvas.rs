@@ -0,0 +1,630 @@
+// 软件光栅化画布
+// 第 18 章的 Draw trait 原来只会打印“绘制按钮”，这里给它一个真正的绘图上下文：
+// 1. Canvas：包装 ImageBuffer<Rgb> 的帧缓冲，坐标用 i32，落在画布或裁剪区之外的像素直接丢弃
+// 2. 图元：Bresenham 直线、矩形边框与填充、5x7 点阵字体的文字
+// 3. 视口：with_viewport 把原点移到一个子区域并只允许在其中绘制，组件按自己的局部坐标画就行
+// 4. 像素比较：diff 找出两幅图像中不同的像素，diff_image 把差异标成红色，供黄金图像测试使用
+
+pub mod font;
+
+use crate::image::{ImageBuffer, ImageError, Pixel, Rgb};
+use std::fmt;
+use std::path::Path;
+
+// ===========================================
+// 1. 矩形区域
+// ===========================================
+
+// 左上角 (x, y)，右边和下边不包含在内
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x.saturating_add_unsigned(self.width)
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y.saturating_add_unsigned(self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x..self.right()).contains(&x) && (self.y..self.bottom()).contains(&y)
+    }
+
+    // 两个矩形的交集；不相交时宽高为 0
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Rect::new(
+            x,
+            y,
+            right.saturating_sub(x).max(0) as u32,
+            bottom.saturating_sub(y).max(0) as u32,
+        )
+    }
+
+    // 四边各向内收缩 amount，不够收缩时宽高为 0
+    pub fn inset(&self, amount: u32) -> Rect {
+        Rect::new(
+            self.x.saturating_add_unsigned(amount),
+            self.y.saturating_add_unsigned(amount),
+            self.width.saturating_sub(2 * amount),
+            self.height.saturating_sub(2 * amount),
+        )
+    }
+
+    // 平移后超出 i32 的部分停在边界上
+    fn translate(&self, dx: i32, dy: i32) -> Rect {
+        Rect::new(
+            self.x.saturating_add(dx),
+            self.y.saturating_add(dy),
+            self.width,
+            self.height,
+        )
+    }
+}
+
+// ===========================================
+// 2. Canvas
+// ===========================================
+
+// origin 和 clip 都是图像上的绝对坐标；绘图方法接收的坐标加上 origin 才是图像坐标
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    image: ImageBuffer<Rgb>,
+    origin: (i32, i32),
+    clip: Rect,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Rgb) -> Self {
+        Canvas::from_image(ImageBuffer::from_pixel(width, height, background))
+    }
+
+    // 在已有的图像上继续绘制
+    pub fn from_image(image: ImageBuffer<Rgb>) -> Self {
+        let clip = Rect::new(0, 0, image.width(), image.height());
+        Canvas {
+            image,
+            origin: (0, 0),
+            clip,
+        }
+    }
+
+    // 当前可绘制的区域，使用局部坐标；在视口内就是视口本身（再与外层的裁剪区取交集）
+    pub fn bounds(&self) -> Rect {
+        self.clip.translate(-self.origin.0, -self.origin.1)
+    }
+
+    pub fn image(&self) -> &ImageBuffer<Rgb> {
+        &self.image
+    }
+
+    pub fn into_image(self) -> ImageBuffer<Rgb> {
+        self.image
+    }
+
+    // 以二进制 PPM（P6）保存整幅图像
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> Result<(), ImageError> {
+        self.image.save_pnm(path)
+    }
+
+    // 读取局部坐标处的像素；在裁剪区之外返回 None
+    pub fn get(&self, x: i32, y: i32) -> Option<Rgb> {
+        let (x, y) = self.to_absolute(x, y)?;
+        self.clip
+            .contains(x, y)
+            .then(|| self.image.pixel(x as u32, y as u32))
+    }
+
+    // 裁剪区之外的像素被忽略，所以图元不需要自己判断边界
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: Rgb) {
+        if let Some((x, y)) = self.to_absolute(x, y)
+            && self.clip.contains(x, y)
+        {
+            self.image.put(x as u32, y as u32, color);
+        }
+    }
+
+    // 局部坐标换算成图像坐标；超出 i32 的坐标必然在画布之外
+    fn to_absolute(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        Some((x.checked_add(self.origin.0)?, y.checked_add(self.origin.1)?))
+    }
+
+    // 用 color 填满当前的可绘制区域
+    pub fn clear(&mut self, color: Rgb) {
+        self.fill_rect(self.bounds(), color);
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Rgb) {
+        // 先裁剪再逐行填充，不必对每个像素重复判断
+        let area = rect
+            .translate(self.origin.0, self.origin.1)
+            .intersect(&self.clip);
+        if area.is_empty() {
+            return;
+        }
+        let width = self.image.width() as usize;
+        let (left, right) = (area.x as usize, area.right() as usize);
+        let pixels = self.image.pixels_mut();
+        for y in area.y as usize..area.bottom() as usize {
+            pixels[y * width + left..y * width + right].fill(color);
+        }
+    }
+
+    // 1 像素宽的边框，画在矩形内侧
+    pub fn stroke_rect(&mut self, rect: Rect, color: Rgb) {
+        if rect.is_empty() {
+            return;
+        }
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+        self.line((rect.x, rect.y), (right, rect.y), color);
+        self.line((rect.x, bottom), (right, bottom), color);
+        self.line((rect.x, rect.y), (rect.x, bottom), color);
+        self.line((right, rect.y), (right, bottom), color);
+    }
+
+    // Bresenham 直线，两个端点都会被画上
+    // 先用 Cohen–Sutherland 把线段裁剪到裁剪区内，再逐像素画，很长的线也只走可见的部分；
+    // 完全在裁剪区内的线不受裁剪影响。坐标换成 i64 的图像坐标，端点差不会溢出
+    pub fn line(&mut self, from: (i32, i32), to: (i32, i32), color: Rgb) {
+        // 误差恰好为 0 时向哪边走取决于起点，先把端点排好序，反方向画出的才是同一条线
+        let (from, to) = if from <= to { (from, to) } else { (to, from) };
+        let absolute = |(x, y): (i32, i32)| {
+            (
+                x as i64 + self.origin.0 as i64,
+                y as i64 + self.origin.1 as i64,
+            )
+        };
+        let Some((from, to)) = clip_line(absolute(from), absolute(to), &self.clip) else {
+            return;
+        };
+        let (mut x, mut y) = from;
+        let dx = (to.0 - x).abs();
+        let dy = -(to.1 - y).abs();
+        let step_x = if x < to.0 { 1 } else { -1 };
+        let step_y = if y < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            // 裁剪后的端点都在裁剪区内，中间的像素也是
+            self.image.put(x as u32, y as u32, color);
+            if (x, y) == to {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    // 以 (x, y) 为左上角绘制文字，返回绘制的宽度；'\n' 换行
+    pub fn text(&mut self, x: i32, y: i32, text: &str, color: Rgb) -> u32 {
+        self.text_scaled(x, y, text, color, 1)
+    }
+
+    // 每个字体点放大成 scale x scale 的方块
+    pub fn text_scaled(&mut self, x: i32, y: i32, text: &str, color: Rgb, scale: u32) -> u32 {
+        // 坐标在 i64 中计算；超出 i32 范围的点不可能落在画布上，直接跳过
+        let size = i64::from(scale);
+        let offset = |start: i32, index: usize, step: u32| {
+            i64::from(start).saturating_add((index as i64).saturating_mul(i64::from(step) * size))
+        };
+        for (line_index, line) in text.lines().enumerate() {
+            let top = offset(y, line_index, font::LINE_HEIGHT);
+            for (index, c) in line.chars().enumerate() {
+                let left = offset(x, index, font::ADVANCE);
+                for column in 0..font::GLYPH_WIDTH {
+                    for row in 0..font::GLYPH_HEIGHT {
+                        if !font::is_set(c, column, row) {
+                            continue;
+                        }
+                        let dot_x = i32::try_from(left.saturating_add(i64::from(column) * size));
+                        let dot_y = i32::try_from(top.saturating_add(i64::from(row) * size));
+                        if let (Ok(dot_x), Ok(dot_y)) = (dot_x, dot_y) {
+                            self.fill_rect(Rect::new(dot_x, dot_y, scale, scale), color);
+                        }
+                    }
+                }
+            }
+        }
+        Canvas::text_size(text, scale).0
+    }
+
+    // 文字占据的宽高，不含最后一个字符后面的间距和最后一行下面的行距；超出 u32 时取 u32::MAX
+    pub fn text_size(text: &str, scale: u32) -> (u32, u32) {
+        let extent = |count: usize, step: u32| {
+            u32::try_from(count)
+                .unwrap_or(u32::MAX)
+                .saturating_mul(step)
+                .saturating_sub(1)
+                .saturating_mul(scale)
+        };
+        let lines = text.lines().count();
+        let columns = text.lines().map(|line| line.chars().count()).max();
+        match columns {
+            Some(columns) if columns > 0 => (
+                extent(columns, font::ADVANCE),
+                extent(lines, font::LINE_HEIGHT),
+            ),
+            _ => (0, 0),
+        }
+    }
+
+    // 在 rect（局部坐标）内绘制：f 看到的原点是 rect 的左上角，画不出 rect 的范围
+    pub fn with_viewport<R>(&mut self, rect: Rect, f: impl FnOnce(&mut Canvas) -> R) -> R {
+        let saved = (self.origin, self.clip);
+        let absolute = rect.translate(self.origin.0, self.origin.1);
+        self.origin = (absolute.x, absolute.y);
+        self.clip = absolute.intersect(&self.clip);
+        let result = f(self);
+        (self.origin, self.clip) = saved;
+        result
+    }
+}
+
+// Cohen–Sutherland 线段裁剪：区域码记录点在裁剪区的哪一侧，
+// 两端在同一侧之外时整条线不可见，否则把在外面的端点沿线段移到边界上，直到两端都在里面
+fn clip_line(from: (i64, i64), to: (i64, i64), clip: &Rect) -> Option<((i64, i64), (i64, i64))> {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const TOP: u8 = 4;
+    const BOTTOM: u8 = 8;
+
+    if clip.is_empty() {
+        return None;
+    }
+    // 裁剪区包含的像素范围，闭区间
+    let (left, top) = (clip.x as f64, clip.y as f64);
+    let (right, bottom) = (clip.right() as f64 - 1.0, clip.bottom() as f64 - 1.0);
+    let code = |(x, y): (f64, f64)| {
+        let mut code = 0;
+        if x < left {
+            code |= LEFT;
+        } else if x > right {
+            code |= RIGHT;
+        }
+        if y < top {
+            code |= TOP;
+        } else if y > bottom {
+            code |= BOTTOM;
+        }
+        code
+    };
+
+    let (mut a, mut b) = ((from.0 as f64, from.1 as f64), (to.0 as f64, to.1 as f64));
+    let (mut code_a, mut code_b) = (code(a), code(b));
+    loop {
+        if code_a | code_b == 0 {
+            break;
+        }
+        if code_a & code_b != 0 {
+            return None;
+        }
+        let outside = if code_a != 0 { code_a } else { code_b };
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let point = if outside & TOP != 0 {
+            (a.0 + dx * (top - a.1) / dy, top)
+        } else if outside & BOTTOM != 0 {
+            (a.0 + dx * (bottom - a.1) / dy, bottom)
+        } else if outside & LEFT != 0 {
+            (left, a.1 + dy * (left - a.0) / dx)
+        } else {
+            (right, a.1 + dy * (right - a.0) / dx)
+        };
+        if outside == code_a {
+            a = point;
+            code_a = code(a);
+        } else {
+            b = point;
+            code_b = code(b);
+        }
+    }
+    // 交点取最近的整数像素，舍入不会越过闭区间的边界
+    let round = |(x, y): (f64, f64)| (x.round() as i64, y.round() as i64);
+    Some((round(a), round(b)))
+}
+
+// ===========================================
+// 3. 像素比较
+// ===========================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PixelDiff {
+    Identical,
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    // count 个像素不同，它们都落在 bounds 之内
+    Pixels {
+        count: usize,
+        bounds: Rect,
+    },
+}
+
+impl fmt::Display for PixelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PixelDiff::Identical => write!(f, "图像相同"),
+            PixelDiff::SizeMismatch { expected, actual } => write!(
+                f,
+                "尺寸不同: 期望 {}x{}，实际 {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            PixelDiff::Pixels { count, bounds } => write!(
+                f,
+                "{} 个像素不同，位于 ({}, {}) 起 {}x{} 的范围内",
+                count, bounds.x, bounds.y, bounds.width, bounds.height
+            ),
+        }
+    }
+}
+
+pub fn diff(expected: &ImageBuffer<Rgb>, actual: &ImageBuffer<Rgb>) -> PixelDiff {
+    if expected.dimensions() != actual.dimensions() {
+        return PixelDiff::SizeMismatch {
+            expected: expected.dimensions(),
+            actual: actual.dimensions(),
+        };
+    }
+    let width = expected.width() as usize;
+    let mut count = 0;
+    let (mut min, mut max) = ((u32::MAX, u32::MAX), (0, 0));
+    let pairs = expected.pixels().iter().zip(actual.pixels());
+    for (index, _) in pairs.enumerate().filter(|(_, (a, b))| a != b) {
+        let (x, y) = ((index % width) as u32, (index / width) as u32);
+        count += 1;
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    }
+    if count == 0 {
+        return PixelDiff::Identical;
+    }
+    PixelDiff::Pixels {
+        count,
+        bounds: Rect::new(
+            min.0 as i32,
+            min.1 as i32,
+            max.0 - min.0 + 1,
+            max.1 - min.1 + 1,
+        ),
+    }
+}
+
+// 相同的像素变暗成灰度，不同的像素标成纯红；尺寸不同时以 actual 为准，多出的部分都算不同
+pub fn diff_image(expected: &ImageBuffer<Rgb>, actual: &ImageBuffer<Rgb>) -> ImageBuffer<Rgb> {
+    ImageBuffer::from_fn(actual.width(), actual.height(), |x, y| {
+        let pixel = actual.pixel(x, y);
+        if expected.get(x, y) == Some(pixel) {
+            let gray = pixel.luma() / 3;
+            Rgb([gray, gray, gray])
+        } else {
+            Rgb([255, 0, 0])
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgb = Rgb([255, 255, 255]);
+    const BLACK: Rgb = Rgb([0, 0, 0]);
+
+    // 黑色像素画成 '#'，其余画成 '.'，方便直接在测试里写出期望的图案
+    fn ascii(canvas: &Canvas) -> String {
+        canvas
+            .image()
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&p| if p == BLACK { '#' } else { '.' })
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+
+    // 去掉每行的缩进和首尾的空行
+    fn pattern(text: &str) -> String {
+        text.lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .map(|row| format!("{}\n", row))
+            .collect()
+    }
+
+    #[test]
+    fn test_lines_and_rects() {
+        let mut canvas = Canvas::new(7, 5, WHITE);
+        canvas.line((0, 0), (6, 3), BLACK);
+        assert_eq!(
+            ascii(&canvas),
+            pattern(
+                "
+            #......
+            .##....
+            ...##..
+            .....##
+            .......
+            "
+            )
+        );
+        // 反方向画出的是同一条线
+        let mut reversed = Canvas::new(7, 5, WHITE);
+        reversed.line((6, 3), (0, 0), BLACK);
+        assert_eq!(diff(canvas.image(), reversed.image()), PixelDiff::Identical);
+
+        let mut canvas = Canvas::new(7, 5, WHITE);
+        canvas.stroke_rect(Rect::new(1, 1, 5, 4), BLACK);
+        // 伸出画布的部分被裁掉
+        canvas.fill_rect(Rect::new(-3, -3, 4, 4), BLACK);
+        assert_eq!(
+            ascii(&canvas),
+            pattern(
+                "
+            #......
+            .#####.
+            .#...#.
+            .#...#.
+            .#####.
+            "
+            )
+        );
+    }
+
+    #[test]
+    fn test_extreme_coordinates_are_clipped() {
+        // 跨越整个 i32 范围的线只画可见的部分，也不会溢出
+        let mut canvas = Canvas::new(5, 3, WHITE);
+        canvas.line((i32::MIN, 1), (i32::MAX, 1), BLACK);
+        canvas.line((2, i32::MIN), (2, i32::MAX), BLACK);
+        canvas.line((-10, -10), (-1, 20), BLACK);
+        assert_eq!(
+            ascii(&canvas),
+            pattern(
+                "
+            ..#..
+            #####
+            ..#..
+            "
+            )
+        );
+        let mut diagonal = Canvas::new(5, 5, WHITE);
+        diagonal.line((-100, -100), (100, 100), BLACK);
+        assert!((0..5).all(|i| diagonal.get(i, i) == Some(BLACK)));
+
+        // 原点加上坐标超出 i32 时丢弃像素，而不是 panic
+        let mut canvas = Canvas::new(4, 4, WHITE);
+        canvas.with_viewport(Rect::new(2, 2, 2, 2), |canvas| {
+            canvas.set_pixel(i32::MAX, 0, BLACK);
+            assert_eq!(canvas.get(i32::MAX, i32::MAX), None);
+            canvas.fill_rect(Rect::new(i32::MAX, 0, 10, 10), BLACK);
+            canvas.with_viewport(Rect::new(i32::MAX, i32::MAX, 1, 1), |canvas| {
+                canvas.clear(BLACK)
+            });
+        });
+        assert_eq!(
+            diff(canvas.image(), Canvas::new(4, 4, WHITE).image()),
+            PixelDiff::Identical
+        );
+
+        // 文字的字形和行超出 i32 时跳过，宽高饱和到 u32::MAX
+        let mut canvas = Canvas::new(4, 4, WHITE);
+        canvas.text(i32::MAX - 2, 0, "ab", BLACK);
+        canvas.text(0, i32::MAX - 2, "a\nb", BLACK);
+        canvas.text_scaled(i32::MAX - 2, i32::MAX - 2, "ab\ncd", BLACK, u32::MAX);
+        assert_eq!(
+            diff(canvas.image(), Canvas::new(4, 4, WHITE).image()),
+            PixelDiff::Identical
+        );
+        assert_eq!(Canvas::text_size("ab", u32::MAX), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn test_text_and_viewport() {
+        let mut canvas = Canvas::new(13, 9, WHITE);
+        let width = canvas.text(1, 1, "Hi", BLACK);
+        assert_eq!(width, 11);
+        assert_eq!(Canvas::text_size("Hi\nthere", 2), (58, 30));
+        assert_eq!(
+            ascii(&canvas),
+            pattern(
+                "
+            .............
+            .#...#...#...
+            .#...#.......
+            .#...#..##...
+            .#####...#...
+            .#...#...#...
+            .#...#...#...
+            .#...#..###..
+            .............
+            "
+            )
+        );
+
+        // 视口内的坐标从视口左上角算起，超出视口的部分被裁掉
+        let mut canvas = Canvas::new(6, 4, WHITE);
+        let inner = canvas.with_viewport(Rect::new(2, 1, 3, 2), |canvas| {
+            canvas.clear(BLACK);
+            canvas.line((0, 0), (10, 0), WHITE);
+            canvas.bounds()
+        });
+        assert_eq!(inner, Rect::new(0, 0, 3, 2));
+        assert_eq!(canvas.bounds(), Rect::new(0, 0, 6, 4));
+        assert_eq!(
+            ascii(&canvas),
+            pattern(
+                "
+            ......
+            ......
+            ..###.
+            ......
+            "
+            )
+        );
+        // 中文不在点阵字体里，画成方框
+        let mut canvas = Canvas::new(5, 7, WHITE);
+        canvas.text(0, 0, "中", BLACK);
+        assert_eq!(canvas.get(0, 0), Some(BLACK));
+        assert_eq!(canvas.get(2, 3), Some(WHITE));
+        assert_eq!(canvas.get(5, 0), None);
+    }
+
+    #[test]
+    fn test_pixel_diff() {
+        let expected = Canvas::new(8, 6, WHITE).into_image();
+        let mut actual = Canvas::new(8, 6, WHITE);
+        assert_eq!(diff(&expected, actual.image()), PixelDiff::Identical);
+
+        actual.set_pixel(2, 1, BLACK);
+        actual.set_pixel(5, 4, BLACK);
+        let result = diff(&expected, actual.image());
+        assert_eq!(
+            result,
+            PixelDiff::Pixels {
+                count: 2,
+                bounds: Rect::new(2, 1, 4, 4)
+            }
+        );
+        assert_eq!(
+            result.to_string(),
+            "2 个像素不同，位于 (2, 1) 起 4x4 的范围内"
+        );
+
+        let marked = diff_image(&expected, actual.image());
+        assert_eq!(marked.pixel(2, 1), Rgb([255, 0, 0]));
+        assert_eq!(marked.pixel(0, 0), Rgb([85, 85, 85]));
+        assert!(matches!(
+            diff(&expected, &ImageBuffer::new(8, 5)),
+            PixelDiff::SizeMismatch { .. }
+        ));
+    }
+}
@@ -0,0 +1,807 @@
+// 对外导出的 C ABI
+// 第 15 章演示的是 Rust 调用 C，这个模块演示反方向：把 Rust 代码编译成 cdylib / staticlib 供 C 调用
+// 导出的接口遵循常见的 C 库约定：
+// 1. 不透明句柄：C 只拿到指针，结构体布局对 C 不可见，由 *_new / *_free 成对管理
+// 2. 错误码加最后错误信息：函数返回 GuideResult，详细信息通过 guide_last_error_message 获取
+// 3. 调用方释放：库分配的字符串交给调用方，调用方必须用 guide_string_free 释放
+//
+// include/guide.h 由 tests/capi.rs 根据本文件生成，修改导出函数后用
+// UPDATE_GOLDEN=1 cargo test --test capi 重新生成
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char};
+use std::fmt;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+// ===========================================
+// 1. 文本分析
+// ===========================================
+
+// 第 11 章迭代器示例中的文本分析器
+#[derive(Debug, Clone)]
+pub struct TextAnalyzer {
+    text: String,
+}
+
+impl TextAnalyzer {
+    pub fn new(text: impl Into<String>) -> Self {
+        TextAnalyzer { text: text.into() }
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.text.split_whitespace().count()
+    }
+
+    // 按 Unicode 字符计数，而不是字节
+    pub fn character_count(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.text.lines().count()
+    }
+
+    // 小写化并去掉结尾标点后的单词
+    fn normalized_words(&self) -> impl Iterator<Item = String> + '_ {
+        self.text
+            .split_whitespace()
+            .map(|word| {
+                word.to_lowercase()
+                    .trim_end_matches(&['.', '!', '?', ',', ';', ':'][..])
+                    .to_string()
+            })
+            .filter(|word| !word.is_empty())
+    }
+
+    pub fn unique_word_count(&self) -> usize {
+        self.normalized_words()
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+    }
+
+    // 出现次数最多的单词；次数相同时取字典序最小的，保证结果稳定
+    pub fn most_common_word(&self) -> Option<(String, usize)> {
+        let mut counts = HashMap::new();
+        for word in self.normalized_words() {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+        counts
+            .into_iter()
+            .max_by(|(a, x), (b, y)| x.cmp(y).then_with(|| b.cmp(a)))
+    }
+}
+
+// ===========================================
+// 2. 矩阵
+// ===========================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatrixError {
+    DimensionMismatch {
+        op: &'static str,
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    OutOfBounds {
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    },
+    // rows * cols 超出 usize 的范围
+    TooLarge {
+        rows: usize,
+        cols: usize,
+    },
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch { op, left, right } => write!(
+                f,
+                "矩阵维度不匹配，无法{}: {}x{} 与 {}x{}",
+                op, left.0, left.1, right.0, right.1
+            ),
+            MatrixError::OutOfBounds {
+                row,
+                col,
+                rows,
+                cols,
+            } => write!(
+                f,
+                "下标 ({}, {}) 超出 {}x{} 矩阵的范围",
+                row, col, rows, cols
+            ),
+            MatrixError::TooLarge { rows, cols } => write!(f, "矩阵尺寸 {}x{} 溢出", rows, cols),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
+// 第 14 章的行主序 f64 矩阵
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Result<Self, MatrixError> {
+        let len = rows
+            .checked_mul(cols)
+            .ok_or(MatrixError::TooLarge { rows, cols })?;
+        Ok(Matrix {
+            rows,
+            cols,
+            data: vec![0.0; len],
+        })
+    }
+
+    // data 按行主序排列，长度必须等于 rows * cols
+    pub fn from_rows(rows: usize, cols: usize, data: Vec<f64>) -> Option<Self> {
+        (rows.checked_mul(cols) == Some(data.len())).then_some(Matrix { rows, cols, data })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    fn index(&self, row: usize, col: usize) -> Result<usize, MatrixError> {
+        if row < self.rows && col < self.cols {
+            Ok(row * self.cols + col)
+        } else {
+            Err(MatrixError::OutOfBounds {
+                row,
+                col,
+                rows: self.rows,
+                cols: self.cols,
+            })
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Result<f64, MatrixError> {
+        Ok(self.data[self.index(row, col)?])
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: f64) -> Result<(), MatrixError> {
+        let index = self.index(row, col)?;
+        self.data[index] = value;
+        Ok(())
+    }
+
+    pub fn add(&self, other: &Matrix) -> Result<Matrix, MatrixError> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return Err(MatrixError::DimensionMismatch {
+                op: "相加",
+                left: (self.rows, self.cols),
+                right: (other.rows, other.cols),
+            });
+        }
+        let data = self.data.iter().zip(&other.data).map(|(a, b)| a + b);
+        Ok(Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: data.collect(),
+        })
+    }
+
+    pub fn multiply(&self, other: &Matrix) -> Result<Matrix, MatrixError> {
+        if self.cols != other.rows {
+            return Err(MatrixError::DimensionMismatch {
+                op: "相乘",
+                left: (self.rows, self.cols),
+                right: (other.rows, other.cols),
+            });
+        }
+        // 内维为 0 时两个矩阵都是空的，结果的尺寸仍然可能溢出
+        let mut result = Matrix::zeros(self.rows, other.cols)?;
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self.data[i * self.cols + k];
+                for j in 0..other.cols {
+                    result.data[i * other.cols + j] += a * other.data[k * other.cols + j];
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let mut result = Matrix::zeros(self.cols, self.rows).expect("元素个数与原矩阵相同");
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                result.data[j * self.rows + i] = self.data[i * self.cols + j];
+            }
+        }
+        result
+    }
+}
+
+// 每行一个 "[a, b, c]"
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.data.chunks(self.cols.max(1)).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let cells: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+            write!(f, "[{}]", cells.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// ===========================================
+// 3. 错误码与最后错误信息
+// ===========================================
+
+/// 所有可能失败的导出函数的返回值
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuideResult {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidUtf8 = 2,
+    DimensionMismatch = 3,
+    OutOfBounds = 4,
+    BufferTooSmall = 5,
+    Panic = 6,
+    TooLarge = 7,
+}
+
+thread_local! {
+    // 每个线程各自保存最近一次失败的信息，与 errno 的语义相同
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(message));
+}
+
+struct Failure(GuideResult, String);
+
+impl From<MatrixError> for Failure {
+    fn from(error: MatrixError) -> Self {
+        let code = match error {
+            MatrixError::DimensionMismatch { .. } => GuideResult::DimensionMismatch,
+            MatrixError::OutOfBounds { .. } => GuideResult::OutOfBounds,
+            MatrixError::TooLarge { .. } => GuideResult::TooLarge,
+        };
+        Failure(code, error.to_string())
+    }
+}
+
+// 所有导出函数的公共外壳：成功时清除最后错误，失败时记录信息，
+// 并捕获 panic，防止它穿过 extern "C" 边界
+fn ffi_call(f: impl FnOnce() -> Result<(), Failure>) -> GuideResult {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => {
+            LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+            GuideResult::Ok
+        }
+        Ok(Err(Failure(code, message))) => {
+            set_last_error(&message);
+            code
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "未知 panic".to_string());
+            set_last_error(&format!("Rust 代码发生 panic: {}", message));
+            GuideResult::Panic
+        }
+    }
+}
+
+fn non_null<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, Failure> {
+    unsafe { ptr.as_ref() }
+        .ok_or_else(|| Failure(GuideResult::NullPointer, format!("参数 {} 为空指针", name)))
+}
+
+fn non_null_mut<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T, Failure> {
+    unsafe { ptr.as_mut() }
+        .ok_or_else(|| Failure(GuideResult::NullPointer, format!("参数 {} 为空指针", name)))
+}
+
+// 把 Rust 字符串交给 C，所有权随之转移，调用方用 guide_string_free 释放
+fn into_c_string(text: &str) -> *mut c_char {
+    CString::new(text.replace('\0', ""))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// 返回当前线程最近一次失败的错误信息，没有错误时返回 NULL
+/// 返回的字符串由调用方用 guide_string_free 释放
+#[unsafe(no_mangle)]
+pub extern "C" fn guide_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|slot| match &*slot.borrow() {
+        Some(message) => message.clone().into_raw(),
+        None => ptr::null_mut(),
+    })
+}
+
+/// 释放本库返回的字符串，传入 NULL 是无害的空操作
+///
+/// # Safety
+/// s 必须为 NULL，或者是本库返回且尚未释放的字符串
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// 库版本号，返回静态字符串，调用方不能释放
+#[unsafe(no_mangle)]
+pub extern "C" fn guide_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+// ===========================================
+// 4. 文本分析的 C 接口
+// ===========================================
+
+/// 文本分析器句柄
+pub struct GuideTextAnalyzer {
+    analyzer: TextAnalyzer,
+}
+
+/// 文本统计结果，由调用方分配
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuideTextStats {
+    pub words: usize,
+    pub characters: usize,
+    pub lines: usize,
+    pub unique_words: usize,
+}
+
+/// 用 UTF-8 文本创建分析器，文本会被复制
+///
+/// # Safety
+/// text 必须是以 NUL 结尾的字符串，out 必须指向可写的指针
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_text_analyzer_new(
+    text: *const c_char,
+    out: *mut *mut GuideTextAnalyzer,
+) -> GuideResult {
+    ffi_call(|| {
+        let out = non_null_mut(out, "out")?;
+        non_null(text, "text")?;
+        let text = unsafe { CStr::from_ptr(text) }.to_str().map_err(|e| {
+            Failure(
+                GuideResult::InvalidUtf8,
+                format!("文本不是有效的 UTF-8: {}", e),
+            )
+        })?;
+        let handle = GuideTextAnalyzer {
+            analyzer: TextAnalyzer::new(text),
+        };
+        *out = Box::into_raw(Box::new(handle));
+        Ok(())
+    })
+}
+
+/// 释放分析器，传入 NULL 是无害的空操作
+///
+/// # Safety
+/// analyzer 必须为 NULL，或者是 guide_text_analyzer_new 返回且尚未释放的句柄
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_text_analyzer_free(analyzer: *mut GuideTextAnalyzer) {
+    if !analyzer.is_null() {
+        drop(unsafe { Box::from_raw(analyzer) });
+    }
+}
+
+/// 计算单词数、字符数、行数和不重复单词数
+///
+/// # Safety
+/// analyzer 必须是有效句柄，out 必须指向可写的 GuideTextStats
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_text_analyzer_stats(
+    analyzer: *const GuideTextAnalyzer,
+    out: *mut GuideTextStats,
+) -> GuideResult {
+    ffi_call(|| {
+        let analyzer = &non_null(analyzer, "analyzer")?.analyzer;
+        *non_null_mut(out, "out")? = GuideTextStats {
+            words: analyzer.word_count(),
+            characters: analyzer.character_count(),
+            lines: analyzer.line_count(),
+            unique_words: analyzer.unique_word_count(),
+        };
+        Ok(())
+    })
+}
+
+/// 出现次数最多的单词（小写、去掉结尾标点）
+/// 文本中没有单词时 *out_word 被设为 NULL，*out_count 为 0
+/// *out_word 由调用方用 guide_string_free 释放
+///
+/// # Safety
+/// analyzer 必须是有效句柄，out_word 和 out_count 必须可写
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_text_analyzer_most_common_word(
+    analyzer: *const GuideTextAnalyzer,
+    out_word: *mut *mut c_char,
+    out_count: *mut usize,
+) -> GuideResult {
+    ffi_call(|| {
+        let analyzer = &non_null(analyzer, "analyzer")?.analyzer;
+        let out_word = non_null_mut(out_word, "out_word")?;
+        let out_count = non_null_mut(out_count, "out_count")?;
+        match analyzer.most_common_word() {
+            Some((word, count)) => {
+                *out_word = into_c_string(&word);
+                *out_count = count;
+            }
+            None => {
+                *out_word = ptr::null_mut();
+                *out_count = 0;
+            }
+        }
+        Ok(())
+    })
+}
+
+// ===========================================
+// 5. 矩阵的 C 接口
+// ===========================================
+
+/// 矩阵句柄，元素为 double，按行主序存储
+pub struct GuideMatrix {
+    matrix: Matrix,
+}
+
+fn matrix_out(out: *mut *mut GuideMatrix, matrix: Matrix) -> Result<(), Failure> {
+    *non_null_mut(out, "out")? = Box::into_raw(Box::new(GuideMatrix { matrix }));
+    Ok(())
+}
+
+/// 创建全零矩阵
+///
+/// # Safety
+/// out 必须指向可写的指针
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_new(
+    rows: usize,
+    cols: usize,
+    out: *mut *mut GuideMatrix,
+) -> GuideResult {
+    ffi_call(|| matrix_out(out, Matrix::zeros(rows, cols)?))
+}
+
+/// 用行主序的 rows * cols 个元素创建矩阵，数据会被复制
+///
+/// # Safety
+/// data 必须指向至少 rows * cols 个 double，out 必须指向可写的指针
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_from_data(
+    rows: usize,
+    cols: usize,
+    data: *const f64,
+    out: *mut *mut GuideMatrix,
+) -> GuideResult {
+    ffi_call(|| {
+        let len = rows
+            .checked_mul(cols)
+            .ok_or(MatrixError::TooLarge { rows, cols })?;
+        let data = if len == 0 {
+            Vec::new()
+        } else {
+            non_null(data, "data")?;
+            unsafe { std::slice::from_raw_parts(data, len) }.to_vec()
+        };
+        let matrix = Matrix::from_rows(rows, cols, data).expect("长度已检查");
+        matrix_out(out, matrix)
+    })
+}
+
+/// 释放矩阵，传入 NULL 是无害的空操作
+///
+/// # Safety
+/// matrix 必须为 NULL，或者是本库返回且尚未释放的矩阵
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_free(matrix: *mut GuideMatrix) {
+    if !matrix.is_null() {
+        drop(unsafe { Box::from_raw(matrix) });
+    }
+}
+
+/// 行数，matrix 为 NULL 时返回 0
+///
+/// # Safety
+/// matrix 必须为 NULL 或有效句柄
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_rows(matrix: *const GuideMatrix) -> usize {
+    unsafe { matrix.as_ref() }.map_or(0, |m| m.matrix.rows())
+}
+
+/// 列数，matrix 为 NULL 时返回 0
+///
+/// # Safety
+/// matrix 必须为 NULL 或有效句柄
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_cols(matrix: *const GuideMatrix) -> usize {
+    unsafe { matrix.as_ref() }.map_or(0, |m| m.matrix.cols())
+}
+
+/// 读取一个元素
+///
+/// # Safety
+/// matrix 必须是有效句柄，out 必须可写
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_get(
+    matrix: *const GuideMatrix,
+    row: usize,
+    col: usize,
+    out: *mut f64,
+) -> GuideResult {
+    ffi_call(|| {
+        let value = non_null(matrix, "matrix")?.matrix.get(row, col)?;
+        *non_null_mut(out, "out")? = value;
+        Ok(())
+    })
+}
+
+/// 写入一个元素
+///
+/// # Safety
+/// matrix 必须是有效句柄
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_set(
+    matrix: *mut GuideMatrix,
+    row: usize,
+    col: usize,
+    value: f64,
+) -> GuideResult {
+    ffi_call(|| {
+        Ok(non_null_mut(matrix, "matrix")?
+            .matrix
+            .set(row, col, value)?)
+    })
+}
+
+/// 矩阵相加，结果是新矩阵，由调用方用 guide_matrix_free 释放
+///
+/// # Safety
+/// a 和 b 必须是有效句柄，out 必须指向可写的指针
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_add(
+    a: *const GuideMatrix,
+    b: *const GuideMatrix,
+    out: *mut *mut GuideMatrix,
+) -> GuideResult {
+    ffi_call(|| {
+        let sum = non_null(a, "a")?.matrix.add(&non_null(b, "b")?.matrix)?;
+        matrix_out(out, sum)
+    })
+}
+
+/// 矩阵相乘，结果是新矩阵，由调用方用 guide_matrix_free 释放
+///
+/// # Safety
+/// a 和 b 必须是有效句柄，out 必须指向可写的指针
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_multiply(
+    a: *const GuideMatrix,
+    b: *const GuideMatrix,
+    out: *mut *mut GuideMatrix,
+) -> GuideResult {
+    ffi_call(|| {
+        let product = non_null(a, "a")?
+            .matrix
+            .multiply(&non_null(b, "b")?.matrix)?;
+        matrix_out(out, product)
+    })
+}
+
+/// 转置，结果是新矩阵，由调用方用 guide_matrix_free 释放
+///
+/// # Safety
+/// matrix 必须是有效句柄，out 必须指向可写的指针
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_transpose(
+    matrix: *const GuideMatrix,
+    out: *mut *mut GuideMatrix,
+) -> GuideResult {
+    ffi_call(|| matrix_out(out, non_null(matrix, "matrix")?.matrix.transpose()))
+}
+
+/// 把全部元素按行主序复制到调用方提供的缓冲区
+/// 缓冲区不足 rows * cols 个元素时返回 GUIDE_RESULT_BUFFER_TOO_SMALL，且不写入任何数据
+///
+/// # Safety
+/// matrix 必须是有效句柄，buffer 必须指向至少 len 个可写的 double
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_copy_data(
+    matrix: *const GuideMatrix,
+    buffer: *mut f64,
+    len: usize,
+) -> GuideResult {
+    ffi_call(|| {
+        let data = non_null(matrix, "matrix")?.matrix.data();
+        if len < data.len() {
+            return Err(Failure(
+                GuideResult::BufferTooSmall,
+                format!("缓冲区需要 {} 个元素，只提供了 {} 个", data.len(), len),
+            ));
+        }
+        if !data.is_empty() {
+            non_null_mut(buffer, "buffer")?;
+            unsafe { ptr::copy_nonoverlapping(data.as_ptr(), buffer, data.len()) };
+        }
+        Ok(())
+    })
+}
+
+/// 矩阵的文本形式，每行一个 "[a, b, c]"；matrix 为 NULL 时返回 NULL
+/// 返回的字符串由调用方用 guide_string_free 释放
+///
+/// # Safety
+/// matrix 必须为 NULL 或有效句柄
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn guide_matrix_to_string(matrix: *const GuideMatrix) -> *mut c_char {
+    match unsafe { matrix.as_ref() } {
+        Some(m) => into_c_string(&m.matrix.to_string()),
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let message = guide_last_error_message();
+        if message.is_null() {
+            return None;
+        }
+        let text = unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { guide_string_free(message) };
+        Some(text)
+    }
+
+    #[test]
+    fn test_text_analyzer_through_c_api() {
+        unsafe {
+            let mut analyzer = ptr::null_mut();
+            let text = c"The cat saw the dog.\nThe end!";
+            assert_eq!(
+                guide_text_analyzer_new(text.as_ptr(), &mut analyzer),
+                GuideResult::Ok
+            );
+
+            let mut stats = GuideTextStats::default();
+            assert_eq!(
+                guide_text_analyzer_stats(analyzer, &mut stats),
+                GuideResult::Ok
+            );
+            assert_eq!(
+                stats,
+                GuideTextStats {
+                    words: 7,
+                    characters: 29,
+                    lines: 2,
+                    unique_words: 5
+                }
+            );
+
+            let (mut word, mut count) = (ptr::null_mut(), 0);
+            let rc = guide_text_analyzer_most_common_word(analyzer, &mut word, &mut count);
+            assert_eq!(rc, GuideResult::Ok);
+            assert_eq!((CStr::from_ptr(word).to_str().unwrap(), count), ("the", 3));
+            guide_string_free(word);
+            guide_text_analyzer_free(analyzer);
+
+            let invalid = [0xffu8, 0];
+            let rc = guide_text_analyzer_new(invalid.as_ptr() as *const c_char, &mut analyzer);
+            assert_eq!(rc, GuideResult::InvalidUtf8);
+            assert!(last_error().unwrap().contains("UTF-8"));
+        }
+    }
+
+    #[test]
+    fn test_matrix_ops_and_errors() {
+        unsafe {
+            let (mut a, mut b, mut product) = (ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+            assert_eq!(
+                guide_matrix_from_data(2, 3, data.as_ptr(), &mut a),
+                GuideResult::Ok
+            );
+            assert_eq!(guide_matrix_transpose(a, &mut b), GuideResult::Ok);
+            assert_eq!(guide_matrix_multiply(a, b, &mut product), GuideResult::Ok);
+
+            let mut out = [0.0; 4];
+            let rc = guide_matrix_copy_data(product, out.as_mut_ptr(), 3);
+            assert_eq!(rc, GuideResult::BufferTooSmall);
+            assert_eq!(
+                guide_matrix_copy_data(product, out.as_mut_ptr(), 4),
+                GuideResult::Ok
+            );
+            assert_eq!(out, [14.0, 32.0, 32.0, 77.0]);
+
+            let text = guide_matrix_to_string(product);
+            assert_eq!(CStr::from_ptr(text).to_str().unwrap(), "[14, 32]\n[32, 77]");
+            guide_string_free(text);
+
+            let mut sum = ptr::null_mut();
+            assert_eq!(
+                guide_matrix_add(a, b, &mut sum),
+                GuideResult::DimensionMismatch
+            );
+            assert!(sum.is_null());
+            assert_eq!(
+                last_error().as_deref(),
+                Some("矩阵维度不匹配，无法相加: 2x3 与 3x2")
+            );
+            let mut value = 0.0;
+            assert_eq!(
+                guide_matrix_get(a, 2, 0, &mut value),
+                GuideResult::OutOfBounds
+            );
+            assert_eq!(
+                guide_matrix_get(ptr::null(), 0, 0, &mut value),
+                GuideResult::NullPointer
+            );
+            // 成功的调用会清除最后错误
+            assert_eq!(guide_matrix_set(a, 1, 2, -1.0), GuideResult::Ok);
+            assert_eq!(last_error(), None);
+
+            for m in [a, b, product] {
+                guide_matrix_free(m);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matrix_size_overflow_is_reported() {
+        assert_eq!(
+            Matrix::zeros(usize::MAX, 2),
+            Err(MatrixError::TooLarge {
+                rows: usize::MAX,
+                cols: 2
+            })
+        );
+        assert_eq!(Matrix::from_rows(usize::MAX, 2, vec![0.0; 2]), None);
+        // 内维为 0 的两个空矩阵相乘，结果尺寸溢出
+        let wide = Matrix::from_rows(usize::MAX, 0, Vec::new()).unwrap();
+        let tall = Matrix::from_rows(0, 2, Vec::new()).unwrap();
+        assert!(matches!(
+            wide.multiply(&tall),
+            Err(MatrixError::TooLarge { .. })
+        ));
+
+        unsafe {
+            let mut m = ptr::null_mut();
+            assert_eq!(
+                guide_matrix_new(usize::MAX, 2, &mut m),
+                GuideResult::TooLarge
+            );
+            assert!(m.is_null());
+            assert_eq!(
+                last_error().as_deref(),
+                Some(format!("矩阵尺寸 {}x2 溢出", usize::MAX).as_str())
+            );
+        }
+    }
+}
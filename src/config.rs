@@ -0,0 +1,1007 @@
+// 类型化的分层配置
+// 各章节的配置示例（config! 宏、ConfigManager、AppConfig）都建立在这套设计之上：
+// 1. 来源分层：默认值 < 配置文件 < 环境变量 < 命令行 / 代码覆盖，后加载的覆盖先加载的
+// 2. 类型化：所有来源先合并成字符串，再按 schema 统一解析成结构体字段的类型
+// 3. 错误：ConfigError 记录配置项、取值、期望的类型以及取值来自哪个来源
+// 4. 发布：ConfigHandle 持有当前配置，重新加载时通知订阅者；GlobalConfig 把它放进 OnceLock 全局变量
+//
+// 配置文件是 INI / TOML 风格的子集：
+//
+// # 注释
+// [server]
+// host = "0.0.0.0"
+// port = 9000      # 行尾注释
+//
+// 节名和键名用点连接成完整的键，例如 server.port
+
+use crate::diagnostics::{Label, SourceDiagnostic};
+use crate::errors::{Diagnostic, ErrorCode};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, mpsc};
+use std::time::Duration;
+
+// ===========================================
+// 1. 配置来源
+// ===========================================
+
+// 每个取值都记得自己来自哪里，出错时可以直接指出该去改哪个地方
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File { path: String, line: usize },
+    Env(String),
+    CommandLine,
+    Override,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "默认值"),
+            Source::File { path, line } => write!(f, "文件 {} 第 {} 行", path, line),
+            Source::Env(name) => write!(f, "环境变量 {}", name),
+            Source::CommandLine => write!(f, "命令行 --set"),
+            Source::Override => write!(f, "代码覆盖"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    pub value: String,
+    pub source: Source,
+}
+
+// 合并后的原始配置：完整键 -> 字符串取值及其来源
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigValues {
+    entries: BTreeMap<String, ConfigEntry>,
+}
+
+impl ConfigValues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 同一个键再次插入时覆盖旧值
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>, source: Source) {
+        let entry = ConfigEntry {
+            value: value.into(),
+            source,
+        };
+        self.entries.insert(key.into(), entry);
+    }
+
+    pub fn entry(&self, key: &str) -> Option<&ConfigEntry> {
+        self.entries.get(key)
+    }
+
+    // 按类型读取一个配置项
+    pub fn get<T: ConfigType>(&self, key: &str) -> Result<T, ConfigError> {
+        let entry = self.entry(key).ok_or_else(|| ConfigError::Missing {
+            key: key.to_string(),
+        })?;
+        T::parse_config(&entry.value).ok_or_else(|| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: entry.value.clone(),
+            expected: T::TYPE_NAME,
+            source: entry.source.clone(),
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ConfigEntry)> {
+        self.entries
+            .iter()
+            .map(|(key, entry)| (key.as_str(), entry))
+    }
+
+    fn merge(&mut self, other: &ConfigValues) {
+        for (key, entry) in &other.entries {
+            self.entries.insert(key.clone(), entry.clone());
+        }
+    }
+}
+
+// ===========================================
+// 2. 配置错误
+// ===========================================
+
+#[derive(Debug)]
+pub enum ConfigError {
+    // 配置文件或命令行参数的格式错误；source_text 和 span 用于渲染带行号的诊断
+    Syntax {
+        file: String,
+        line: usize,
+        message: String,
+        source_text: String,
+        span: Range<usize>,
+    },
+    // 取值无法解析为字段的类型
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: &'static str,
+        source: Source,
+    },
+    // schema 中不存在的键，通常是拼写错误
+    UnknownKey {
+        key: String,
+        source: Source,
+    },
+    Missing {
+        key: String,
+    },
+    Io {
+        path: String,
+        error: std::io::Error,
+    },
+}
+
+impl ConfigError {
+    // 自定义校验失败时使用，例如端口不能为 0
+    pub fn invalid(values: &ConfigValues, key: &str, expected: &'static str) -> Self {
+        let (value, source) = match values.entry(key) {
+            Some(entry) => (entry.value.clone(), entry.source.clone()),
+            None => (String::new(), Source::Default),
+        };
+        ConfigError::InvalidValue {
+            key: key.to_string(),
+            value,
+            expected,
+            source,
+        }
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            ConfigError::InvalidValue { key, .. }
+            | ConfigError::UnknownKey { key, .. }
+            | ConfigError::Missing { key } => Some(key),
+            ConfigError::Syntax { .. } | ConfigError::Io { .. } => None,
+        }
+    }
+
+    // 渲染成带源码片段的诊断，取值错误见 invalid_value_diagnostic
+    pub fn diagnostic(&self) -> SourceDiagnostic {
+        match self {
+            ConfigError::Syntax {
+                file,
+                message,
+                source_text,
+                span,
+                ..
+            } => SourceDiagnostic::error("配置格式错误")
+                .with_code(self.code())
+                .with_source(file.clone(), source_text.clone())
+                .with_label(Label::primary(span.clone(), message.clone())),
+            ConfigError::InvalidValue {
+                key,
+                value,
+                expected,
+                source,
+            } => invalid_value_diagnostic(key, value, expected, self.code())
+                .with_note(format!("来源：{}", source)),
+            _ => SourceDiagnostic::error(self.to_string()).with_code(self.code()),
+        }
+    }
+}
+
+// 配置项取值无效的诊断：把配置项还原成 "key = value" 一行，
+// 主标签指向取值，次要标签指向配置项名称并说明期望的类型
+pub fn invalid_value_diagnostic(
+    key: &str,
+    value: &str,
+    expected: &str,
+    code: ErrorCode,
+) -> SourceDiagnostic {
+    let line = format!("{} = {}", key, value);
+    let value_start = key.len() + " = ".len();
+    SourceDiagnostic::error(format!("配置项 '{}' 的取值无效", key))
+        .with_code(code)
+        .with_source("<config>", line)
+        .with_label(Label::secondary(
+            0..key.len(),
+            format!("期望 {} 类型", expected),
+        ))
+        .with_label(Label::primary(
+            value_start..value_start + value.len(),
+            "无法按期望类型解析",
+        ))
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Syntax {
+                file,
+                line,
+                message,
+                ..
+            } => write!(f, "{}:{}: {}", file, line, message),
+            ConfigError::InvalidValue {
+                key,
+                value,
+                expected,
+                source,
+            } => write!(
+                f,
+                "配置错误: '{}' 应该是 {} 类型，但得到 '{}'（来源：{}）",
+                key, expected, value, source
+            ),
+            ConfigError::UnknownKey { key, source } => {
+                write!(f, "未知的配置项 '{}'（来源：{}）", key, source)
+            }
+            ConfigError::Missing { key } => write!(f, "缺少配置项 '{}'", key),
+            ConfigError::Io { path, error } => write!(f, "无法读取配置文件 {}: {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnostic for ConfigError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            ConfigError::Syntax { .. } => ErrorCode::CONFIG_SYNTAX,
+            ConfigError::InvalidValue { .. } => ErrorCode::CONFIG_INVALID_VALUE,
+            ConfigError::UnknownKey { .. } => ErrorCode::CONFIG_UNKNOWN_KEY,
+            ConfigError::Missing { .. } => ErrorCode::CONFIG_MISSING,
+            ConfigError::Io { error, .. } => error.code(),
+        }
+    }
+}
+
+// ===========================================
+// 3. 配置类型
+// ===========================================
+
+// 可以作为配置字段的类型：从字符串解析，解析失败返回 None
+// TYPE_NAME 出现在错误信息的"期望 … 类型"中
+pub trait ConfigType: Sized {
+    const TYPE_NAME: &'static str;
+
+    fn parse_config(raw: &str) -> Option<Self>;
+}
+
+macro_rules! impl_config_type_from_str {
+    ($($t:ty),*) => {
+        $(
+            impl ConfigType for $t {
+                const TYPE_NAME: &'static str = stringify!($t);
+
+                fn parse_config(raw: &str) -> Option<Self> {
+                    raw.trim().parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_config_type_from_str!(i8, i16, i32, i64, u8, u16, u32, u64, usize, f32, f64);
+
+impl ConfigType for String {
+    const TYPE_NAME: &'static str = "string";
+
+    fn parse_config(raw: &str) -> Option<Self> {
+        Some(raw.to_string())
+    }
+}
+
+// 除 true / false 外，也接受配置文件中常见的 yes / no、on / off、1 / 0
+impl ConfigType for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn parse_config(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some(true),
+            "false" | "no" | "off" | "0" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+// 时长：500ms、30s、5m、2h；不带单位时按秒计算
+impl ConfigType for Duration {
+    const TYPE_NAME: &'static str = "duration";
+
+    fn parse_config(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+        let (number, unit) = raw.split_at(split);
+        let number: u64 = number.parse().ok()?;
+        match unit.trim() {
+            "ms" => Some(Duration::from_millis(number)),
+            "" | "s" => Some(Duration::from_secs(number)),
+            "m" => Some(Duration::from_secs(number.checked_mul(60)?)),
+            "h" => Some(Duration::from_secs(number.checked_mul(3600)?)),
+            _ => None,
+        }
+    }
+}
+
+// 逗号分隔的列表，空字符串表示空列表
+impl<T: ConfigType> ConfigType for Vec<T> {
+    const TYPE_NAME: &'static str = "list";
+
+    fn parse_config(raw: &str) -> Option<Self> {
+        if raw.trim().is_empty() {
+            return Some(Vec::new());
+        }
+        raw.split(',')
+            .map(|item| T::parse_config(item.trim()))
+            .collect()
+    }
+}
+
+// ===========================================
+// 4. Schema 与 config! 宏
+// ===========================================
+
+// schema 中的一个字段：完整键、类型名、默认值（字符串形式）和说明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub key: &'static str,
+    pub type_name: &'static str,
+    pub default: String,
+    pub description: &'static str,
+}
+
+// 类型化的配置结构体，通常由 config! 宏生成
+pub trait ConfigSchema: Sized {
+    fn fields() -> Vec<FieldSpec>;
+
+    fn from_values(values: &ConfigValues) -> Result<Self, ConfigError>;
+}
+
+// 声明一个类型化的配置结构体：
+//
+// config! {
+//     #[derive(Debug)]
+//     pub struct ServerConfig in "server" {
+//         /// 监听地址
+//         pub host: String = "127.0.0.1",
+//         pub port: u16 = 8080,
+//         pub timeout: Duration = "30s",
+//     }
+// }
+//
+// 默认值写成字面量，和其他来源一样按字符串解析；"in" 后面的节名是键的前缀（server.port）
+// 宏同时实现 ConfigSchema 和 Default
+#[macro_export]
+macro_rules! config {
+    (
+        @define $prefix:tt
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                $field_vis:vis $field:ident : $ty:ty = $default:literal
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[doc = $doc])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $crate::config::ConfigSchema for $name {
+            fn fields() -> ::std::vec::Vec<$crate::config::FieldSpec> {
+                ::std::vec![
+                    $(
+                        $crate::config::FieldSpec {
+                            key: $crate::config_key!($prefix $field),
+                            type_name: <$ty as $crate::config::ConfigType>::TYPE_NAME,
+                            default: $default.to_string(),
+                            description: concat!($($doc),*).trim_ascii(),
+                        },
+                    )*
+                ]
+            }
+
+            fn from_values(
+                values: &$crate::config::ConfigValues,
+            ) -> ::std::result::Result<Self, $crate::config::ConfigError> {
+                ::std::result::Result::Ok($name {
+                    $(
+                        $field: values.get($crate::config_key!($prefix $field))?,
+                    )*
+                })
+            }
+        }
+
+        impl ::std::default::Default for $name {
+            fn default() -> Self {
+                $crate::config::ConfigLoader::new()
+                    .load()
+                    .expect(concat!(stringify!($name), " 的默认值无效"))
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident $(in $section:literal)? { $($body:tt)* }
+    ) => {
+        $crate::config!(@define [$($section)?] $(#[$meta])* $vis struct $name { $($body)* });
+    };
+}
+
+// 字段的完整键：有节名时为 "节名.字段名"
+#[doc(hidden)]
+#[macro_export]
+macro_rules! config_key {
+    ([] $field:ident) => {
+        stringify!($field)
+    };
+    ([$section:literal] $field:ident) => {
+        concat!($section, ".", stringify!($field))
+    };
+}
+
+// ===========================================
+// 5. 分层加载
+// ===========================================
+
+enum Layer {
+    Values(ConfigValues),
+    // 环境变量按 schema 中的键逐个查找，所以只能在知道 schema 之后展开
+    Env {
+        prefix: String,
+        vars: BTreeMap<String, String>,
+    },
+}
+
+// 按添加顺序叠加各个来源，schema 的默认值总是最底层
+#[derive(Default)]
+pub struct ConfigLoader {
+    layers: Vec<Layer>,
+}
+
+// 配置键对应的环境变量名：server.max_connections -> APP_SERVER_MAX_CONNECTIONS
+pub fn env_var_name(prefix: &str, key: &str) -> String {
+    let name = key.replace(['.', '-'], "_").to_uppercase();
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{}_{}", prefix.to_uppercase(), name)
+    }
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 解析配置文本，name 用于错误信息和来源记录
+    pub fn file_str(mut self, name: &str, text: &str) -> Result<Self, ConfigError> {
+        self.layers.push(Layer::Values(parse_file(name, text)?));
+        Ok(self)
+    }
+
+    pub fn file(self, path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|error| ConfigError::Io {
+            path: path.display().to_string(),
+            error,
+        })?;
+        self.file_str(&path.display().to_string(), &text)
+    }
+
+    // 文件不存在时跳过，其他 IO 错误照常返回
+    pub fn optional_file(self, path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(text) => self.file_str(&path.display().to_string(), &text),
+            // 保留之前已经加入的各层
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(self),
+            Err(error) => Err(ConfigError::Io {
+                path: path.display().to_string(),
+                error,
+            }),
+        }
+    }
+
+    // 环境变量以 (名称, 取值) 的形式传入，便于测试；真实环境用 process_env
+    pub fn env<I>(mut self, prefix: &str, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        self.layers.push(Layer::Env {
+            prefix: prefix.to_string(),
+            vars: vars.into_iter().collect(),
+        });
+        self
+    }
+
+    pub fn process_env(self, prefix: &str) -> Self {
+        self.env(prefix, std::env::vars())
+    }
+
+    // 命令行覆盖：--set key=value 或 --set=key=value，其他参数忽略
+    pub fn args<I>(mut self, args: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut values = ConfigValues::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let assignment = if arg == "--set" {
+                args.next().unwrap_or_default()
+            } else if let Some(rest) = arg.strip_prefix("--set=") {
+                rest.to_string()
+            } else {
+                continue;
+            };
+            match assignment.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => {
+                    values.insert(key.trim(), value.trim(), Source::CommandLine);
+                }
+                _ => {
+                    return Err(ConfigError::Syntax {
+                        file: String::from("<命令行>"),
+                        line: 1,
+                        message: String::from("--set 的参数应为 key=value"),
+                        span: 0..assignment.len(),
+                        source_text: assignment,
+                    });
+                }
+            }
+        }
+        self.layers.push(Layer::Values(values));
+        Ok(self)
+    }
+
+    // 在代码中直接覆盖某个配置项，优先级取决于调用顺序
+    pub fn set(mut self, key: &str, value: impl fmt::Display) -> Self {
+        let mut values = ConfigValues::new();
+        values.insert(key, value.to_string(), Source::Override);
+        self.layers.push(Layer::Values(values));
+        self
+    }
+
+    // 合并所有来源，得到每个键的最终取值和来源
+    // 任何来源中出现 schema 之外的键都视为错误
+    pub fn resolve<T: ConfigSchema>(&self) -> Result<ConfigValues, ConfigError> {
+        let fields = T::fields();
+        let mut resolved = ConfigValues::new();
+        for field in &fields {
+            resolved.insert(field.key, field.default.clone(), Source::Default);
+        }
+
+        for layer in &self.layers {
+            match layer {
+                Layer::Values(values) => {
+                    for (key, entry) in values.iter() {
+                        if !fields.iter().any(|field| field.key == key) {
+                            return Err(ConfigError::UnknownKey {
+                                key: key.to_string(),
+                                source: entry.source.clone(),
+                            });
+                        }
+                    }
+                    resolved.merge(values);
+                }
+                Layer::Env { prefix, vars } => {
+                    for field in &fields {
+                        let name = env_var_name(prefix, field.key);
+                        if let Some(value) = vars.get(&name) {
+                            resolved.insert(field.key, value.clone(), Source::Env(name));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    pub fn load<T: ConfigSchema>(&self) -> Result<T, ConfigError> {
+        T::from_values(&self.resolve::<T>()?)
+    }
+}
+
+// 解析 INI / TOML 风格的配置文本
+fn parse_file(name: &str, text: &str) -> Result<ConfigValues, ConfigError> {
+    let mut values = ConfigValues::new();
+    let mut section = String::new();
+    let mut line_start = 0;
+
+    for (index, raw_line) in text.split('\n').enumerate() {
+        let line_number = index + 1;
+        let syntax_error = |message: &str, span: Range<usize>| ConfigError::Syntax {
+            file: name.to_string(),
+            line: line_number,
+            message: message.to_string(),
+            source_text: text.to_string(),
+            span: line_start + span.start..line_start + span.end,
+        };
+        let line = raw_line.trim_end_matches('\r');
+        let content_start = line.len() - line.trim_start().len();
+        let content = line.trim();
+
+        if content.is_empty() || content.starts_with('#') || content.starts_with(';') {
+            // 空行和注释
+        } else if let Some(rest) = content.strip_prefix('[') {
+            let Some(inner) = rest.strip_suffix(']') else {
+                return Err(syntax_error("节名缺少 ']'", content_start..line.len()));
+            };
+            if !is_valid_key(inner.trim()) {
+                return Err(syntax_error("无效的节名", content_start..line.len()));
+            }
+            section = inner.trim().to_string();
+        } else {
+            let Some((key, raw_value)) = content.split_once('=') else {
+                return Err(syntax_error("应为 key = value", content_start..line.len()));
+            };
+            let key = key.trim();
+            if !is_valid_key(key) {
+                let key_start = content_start + content.find(key).unwrap_or(0);
+                return Err(syntax_error(
+                    "无效的键名",
+                    key_start..key_start + key.len().max(1),
+                ));
+            }
+            let value_start = content_start + content.len() - raw_value.len();
+            let value = parse_file_value(raw_value)
+                .map_err(|message| syntax_error(message, value_start..line.len()))?;
+            let full_key = if section.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", section, key)
+            };
+            let source = Source::File {
+                path: name.to_string(),
+                line: line_number,
+            };
+            values.insert(full_key, value, source);
+        }
+        line_start += raw_line.len() + 1;
+    }
+    Ok(values)
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+// 取值可以是双引号字符串（支持 \" \\ \n \t 转义），也可以是到行尾注释为止的裸值
+fn parse_file_value(raw: &str) -> Result<String, &'static str> {
+    let raw = raw.trim();
+    let Some(quoted) = raw.strip_prefix('"') else {
+        let end = raw.find('#').unwrap_or(raw.len());
+        return Ok(raw[..end].trim_end().to_string());
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let rest = quoted[i + 1..].trim_start();
+                if rest.is_empty() || rest.starts_with('#') {
+                    return Ok(value);
+                }
+                return Err("字符串之后还有多余的内容");
+            }
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('"') => value.push('"'),
+                Some('\\') => value.push('\\'),
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                _ => return Err("无效的转义序列"),
+            },
+            c => value.push(c),
+        }
+    }
+    Err("字符串缺少结束的引号")
+}
+
+// ===========================================
+// 6. 发布与热重载
+// ===========================================
+
+// 持有当前生效的配置；读取方拿到的是 Arc 快照，重新加载不会影响正在使用旧配置的代码
+pub struct ConfigHandle<T> {
+    current: RwLock<Arc<T>>,
+    version: AtomicU64,
+    subscribers: Mutex<Vec<mpsc::Sender<Arc<T>>>>,
+}
+
+impl<T> ConfigHandle<T> {
+    pub fn new(config: T) -> Self {
+        ConfigHandle {
+            current: RwLock::new(Arc::new(config)),
+            version: AtomicU64::new(1),
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn get(&self) -> Arc<T> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    // 每次替换配置版本号加一，初始版本为 1
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    // 订阅配置变更：每次替换后，新配置会发送到返回的接收端
+    pub fn subscribe(&self) -> mpsc::Receiver<Arc<T>> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    // 替换当前配置并通知订阅者，接收端已丢弃的订阅者顺便清理掉
+    pub fn replace(&self, config: T) -> Arc<T> {
+        let config = Arc::new(config);
+        *self.current.write().unwrap() = Arc::clone(&config);
+        self.version.fetch_add(1, Ordering::SeqCst);
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(Arc::clone(&config)).is_ok());
+        config
+    }
+
+    // 重新加载：成功时替换并通知，失败时保留旧配置并返回错误
+    pub fn reload<F>(&self, load: F) -> Result<Arc<T>, ConfigError>
+    where
+        F: FnOnce() -> Result<T, ConfigError>,
+    {
+        Ok(self.replace(load()?))
+    }
+}
+
+// 全局配置：static CONFIG: GlobalConfig<AppConfig> = GlobalConfig::new();
+// 第一次访问时初始化，之后所有线程共享同一个 ConfigHandle
+pub struct GlobalConfig<T> {
+    cell: OnceLock<ConfigHandle<T>>,
+}
+
+impl<T> GlobalConfig<T> {
+    pub const fn new() -> Self {
+        GlobalConfig {
+            cell: OnceLock::new(),
+        }
+    }
+
+    pub fn get_or_init<F: FnOnce() -> T>(&self, init: F) -> &ConfigHandle<T> {
+        self.cell.get_or_init(|| ConfigHandle::new(init()))
+    }
+
+    // 尚未初始化时返回 None
+    pub fn handle(&self) -> Option<&ConfigHandle<T>> {
+        self.cell.get()
+    }
+}
+
+impl<T> Default for GlobalConfig<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::config! {
+        #[derive(Debug, PartialEq)]
+        struct ServerConfig in "server" {
+            /// 监听地址
+            host: String = "127.0.0.1",
+            port: u16 = 8080,
+            debug: bool = false,
+            timeout: Duration = "30s",
+            tags: Vec<String> = "",
+        }
+    }
+
+    fn vars(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_schema_and_defaults() {
+        let fields = ServerConfig::fields();
+        assert_eq!(fields[0].key, "server.host");
+        assert_eq!(fields[0].description, "监听地址");
+        assert_eq!(fields[1].type_name, "u16");
+        assert_eq!(fields[3].default, "30s");
+
+        let config = ServerConfig::default();
+        assert_eq!(config.host, "127.0.0.1");
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.timeout, Duration::from_secs(30));
+        assert!(config.tags.is_empty());
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let file = "# 服务器\n[server]\nhost = \"0.0.0.0\"  # 注释\nport = 9000\ntags = a, b\n";
+        let loader = ConfigLoader::new()
+            .file_str("app.ini", file)
+            .unwrap()
+            .env(
+                "APP",
+                vars(&[("APP_SERVER_PORT", "9100"), ("APP_SERVER_DEBUG", "yes")]),
+            )
+            .args(["--set", "server.port=9200"].map(String::from))
+            .unwrap();
+
+        let values = loader.resolve::<ServerConfig>().unwrap();
+        assert_eq!(
+            values.entry("server.host").unwrap().source,
+            Source::File {
+                path: "app.ini".into(),
+                line: 3
+            }
+        );
+        assert_eq!(
+            values.entry("server.debug").unwrap().source,
+            Source::Env("APP_SERVER_DEBUG".into())
+        );
+        assert_eq!(
+            values.entry("server.port").unwrap().source,
+            Source::CommandLine
+        );
+        assert_eq!(
+            values.entry("server.timeout").unwrap().source,
+            Source::Default
+        );
+
+        let config: ServerConfig = loader.load().unwrap();
+        assert_eq!(
+            config,
+            ServerConfig {
+                host: "0.0.0.0".into(),
+                port: 9200,
+                debug: true,
+                timeout: Duration::from_secs(30),
+                tags: vec!["a".into(), "b".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_optional_file_keeps_earlier_layers() {
+        let config: ServerConfig = ConfigLoader::new()
+            .set("server.port", 99)
+            .optional_file("/nonexistent.ini")
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(config.port, 99);
+    }
+
+    #[test]
+    fn test_invalid_value_reports_key_value_type_and_source() {
+        let err = ConfigLoader::new()
+            .env("APP", vars(&[("APP_SERVER_PORT", "80800")]))
+            .load::<ServerConfig>()
+            .unwrap_err();
+        assert_eq!(err.key(), Some("server.port"));
+        assert_eq!(err.code(), ErrorCode::CONFIG_INVALID_VALUE);
+        assert_eq!(
+            err.to_string(),
+            "配置错误: 'server.port' 应该是 u16 类型，但得到 '80800'（来源：环境变量 APP_SERVER_PORT）"
+        );
+        let rendered = err.diagnostic().render();
+        assert!(rendered.contains("server.port = 80800"));
+        assert!(rendered.contains("期望 u16 类型"));
+
+        let err = ConfigLoader::new()
+            .set("server.prot", 1)
+            .load::<ServerConfig>()
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CONFIG_UNKNOWN_KEY);
+        assert_eq!(err.key(), Some("server.prot"));
+    }
+
+    #[test]
+    fn test_file_syntax_errors() {
+        let cases = [
+            ("[server\n", 1, "节名缺少 ']'"),
+            ("a = 1\nport 9000\n", 2, "应为 key = value"),
+            ("a = \"abc\n", 1, "字符串缺少结束的引号"),
+            ("a = \"x\" y\n", 1, "字符串之后还有多余的内容"),
+            ("bad key = 1\n", 1, "无效的键名"),
+        ];
+        for (text, expected_line, expected_message) in cases {
+            match ConfigLoader::new().file_str("app.ini", text) {
+                Err(ConfigError::Syntax { line, message, .. }) => {
+                    assert_eq!((line, message.as_str()), (expected_line, expected_message));
+                }
+                other => panic!("{:?} 应当是格式错误，实际为 {:?}", text, other.err()),
+            }
+        }
+
+        let err = ConfigLoader::new()
+            .file_str("app.ini", "[server]\nport 9000\n")
+            .err()
+            .unwrap();
+        assert_eq!(err.diagnostic().location(), Some((2, 1)));
+        assert!(
+            ConfigLoader::new()
+                .args(["--set", "oops"].map(String::from))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_config_types() {
+        assert_eq!(
+            Duration::parse_config("500ms"),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(Duration::parse_config("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(Duration::parse_config("10"), Some(Duration::from_secs(10)));
+        assert_eq!(Duration::parse_config("10x"), None);
+        assert_eq!(bool::parse_config("OFF"), Some(false));
+        assert_eq!(bool::parse_config("maybe"), None);
+        assert_eq!(Vec::<u8>::parse_config("1, 2,3"), Some(vec![1, 2, 3]));
+        assert_eq!(Vec::<u8>::parse_config("1, x"), None);
+        assert_eq!(
+            env_var_name("app", "server.max-conn"),
+            "APP_SERVER_MAX_CONN"
+        );
+    }
+
+    #[test]
+    fn test_handle_reload_notifies_subscribers() {
+        let handle = ConfigHandle::new(ServerConfig::default());
+        let rx = handle.subscribe();
+        let dropped = handle.subscribe();
+        drop(dropped);
+
+        let old = handle.get();
+        handle
+            .reload(|| ConfigLoader::new().set("server.port", 9000).load())
+            .unwrap();
+        assert_eq!(handle.version(), 2);
+        assert_eq!(handle.get().port, 9000);
+        assert_eq!(rx.try_recv().unwrap().port, 9000);
+        // 旧快照不受影响
+        assert_eq!(old.port, 8080);
+        assert_eq!(handle.subscribers.lock().unwrap().len(), 1);
+
+        // 加载失败时保留旧配置，也不发送通知
+        let result = handle.reload(|| ConfigLoader::new().set("server.port", "x").load());
+        assert!(result.is_err());
+        assert_eq!(handle.get().port, 9000);
+        assert_eq!(handle.version(), 2);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_global_config_initializes_once() {
+        static GLOBAL: GlobalConfig<ServerConfig> = GlobalConfig::new();
+        assert!(GLOBAL.handle().is_none());
+
+        let handle = GLOBAL.get_or_init(ServerConfig::default);
+        handle.replace(ServerConfig {
+            port: 1,
+            ..ServerConfig::default()
+        });
+        let again = GLOBAL.get_or_init(|| panic!("不应再次初始化"));
+        assert_eq!(again.get().port, 1);
+    }
+}
@@ -0,0 +1,211 @@
+// 可复现的演示随机数
+// 所有使用随机数的演示都从同一个带种子的 DemoRng 取数，而不是直接调用 rand::random
+// 运行器会打印本次使用的种子，用 --seed <种子> 或环境变量 RUST_GUIDE_SEED 即可原样重放输出
+
+use rand::distr::uniform::{SampleRange, SampleUniform};
+use rand::distr::{Distribution, StandardUniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+// 指定种子的环境变量名
+pub const SEED_ENV_VAR: &str = "RUST_GUIDE_SEED";
+
+// ===========================================
+// 1. DemoRng
+// ===========================================
+
+// 带种子的随机数源
+// 内部使用 StdRng 加互斥锁，可以在线程和异步任务之间共享 &DemoRng
+pub struct DemoRng {
+    seed: u64,
+    rng: Mutex<StdRng>,
+}
+
+impl DemoRng {
+    pub fn from_seed(seed: u64) -> Self {
+        DemoRng {
+            seed,
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // 派生一个独立的子随机源：种子由父种子和标签共同决定
+    // 每个章节使用自己的子随机源，某一章多取或少取随机数不会影响其他章节的输出
+    pub fn derive(&self, label: &str) -> DemoRng {
+        DemoRng::from_seed(twox_hash::XxHash64::oneshot(self.seed, label.as_bytes()))
+    }
+
+    pub fn random<T>(&self) -> T
+    where
+        StandardUniform: Distribution<T>,
+    {
+        self.rng.lock().unwrap().random()
+    }
+
+    pub fn random_range<T, R>(&self, range: R) -> T
+    where
+        T: SampleUniform,
+        R: SampleRange<T>,
+    {
+        self.rng.lock().unwrap().random_range(range)
+    }
+
+    // 以概率 p 返回 true
+    pub fn random_bool(&self, p: f64) -> bool {
+        self.rng.lock().unwrap().random_bool(p)
+    }
+}
+
+impl fmt::Debug for DemoRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DemoRng").field("seed", &self.seed).finish()
+    }
+}
+
+// ===========================================
+// 2. 种子解析
+// ===========================================
+
+// 种子的来源，运行器打印种子时一并说明
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedSource {
+    CommandLine, // --seed <种子> 或 --seed=<种子>
+    Environment, // RUST_GUIDE_SEED
+    Generated,   // 两者都未提供，随机生成
+}
+
+impl fmt::Display for SeedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedSource::CommandLine => write!(f, "命令行 --seed"),
+            SeedSource::Environment => write!(f, "环境变量 {}", SEED_ENV_VAR),
+            SeedSource::Generated => write!(f, "随机生成"),
+        }
+    }
+}
+
+// 按优先级解析种子：命令行 > 环境变量 > 随机生成
+// 参数和环境变量以值的形式传入，便于测试
+pub fn resolve_seed<I>(args: I, env_value: Option<String>) -> Result<(u64, SeedSource), String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == "--seed" {
+            args.next().ok_or("--seed 需要一个参数")?
+        } else if let Some(value) = arg.strip_prefix("--seed=") {
+            value.to_string()
+        } else {
+            continue;
+        };
+        return parse_seed(&value).map(|seed| (seed, SeedSource::CommandLine));
+    }
+
+    if let Some(value) = env_value {
+        return parse_seed(&value).map(|seed| (seed, SeedSource::Environment));
+    }
+
+    Ok((rand::random(), SeedSource::Generated))
+}
+
+fn parse_seed(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|e| format!("无效的随机种子 '{}': {}", value, e))
+}
+
+// ===========================================
+// 3. 全局上下文
+// ===========================================
+
+static GLOBAL: OnceLock<DemoRng> = OnceLock::new();
+
+// 由运行器在启动时调用一次，安装全局随机源
+// 重复调用时保留第一次安装的种子
+pub fn init(seed: u64) -> &'static DemoRng {
+    GLOBAL.get_or_init(|| DemoRng::from_seed(seed))
+}
+
+// 获取全局随机源
+// 单独运行某个章节（例如在测试中）时运行器没有调用 init，此时按相同规则从命令行和环境变量解析种子
+pub fn global() -> &'static DemoRng {
+    GLOBAL.get_or_init(|| {
+        let (seed, _) = resolve_seed(std::env::args().skip(1), std::env::var(SEED_ENV_VAR).ok())
+            .unwrap_or_else(|_| (rand::random(), SeedSource::Generated));
+        DemoRng::from_seed(seed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let a = DemoRng::from_seed(42);
+        let b = DemoRng::from_seed(42);
+        let seq_a: Vec<u64> = (0..16).map(|_| a.random()).collect();
+        let seq_b: Vec<u64> = (0..16).map(|_| b.random()).collect();
+        assert_eq!(seq_a, seq_b);
+
+        let c = DemoRng::from_seed(43);
+        let seq_c: Vec<u64> = (0..16).map(|_| c.random()).collect();
+        assert_ne!(seq_a, seq_c);
+
+        for _ in 0..100 {
+            let value: u8 = a.random_range(0..3);
+            assert!(value < 3);
+        }
+    }
+
+    #[test]
+    fn test_derived_streams_are_stable_and_independent() {
+        let root = DemoRng::from_seed(7);
+        assert_eq!(
+            root.derive("mod_06").seed(),
+            DemoRng::from_seed(7).derive("mod_06").seed()
+        );
+        assert_ne!(root.derive("mod_06").seed(), root.derive("mod_17").seed());
+
+        // 派生与父随机源的消耗进度无关
+        let before = root.derive("mod_17").seed();
+        let _: u64 = root.random();
+        assert_eq!(root.derive("mod_17").seed(), before);
+    }
+
+    #[test]
+    fn test_resolve_seed_precedence() {
+        assert_eq!(
+            resolve_seed(args(&["--seed", "123"]), Some("9".to_string())),
+            Ok((123, SeedSource::CommandLine))
+        );
+        assert_eq!(
+            resolve_seed(args(&["--verbose", "--seed=77"]), None),
+            Ok((77, SeedSource::CommandLine))
+        );
+        assert_eq!(
+            resolve_seed(args(&[]), Some(" 9 ".to_string())),
+            Ok((9, SeedSource::Environment))
+        );
+        assert_eq!(
+            resolve_seed(args(&[]), None).unwrap().1,
+            SeedSource::Generated
+        );
+
+        assert!(resolve_seed(args(&["--seed"]), None).is_err());
+        assert!(resolve_seed(args(&["--seed", "abc"]), None).is_err());
+        assert!(resolve_seed(args(&[]), Some("-1".to_string())).is_err());
+    }
+}
@@ -0,0 +1,458 @@
+// 源码诊断渲染
+// 把"哪一段文本出了什么问题"渲染成 rustc 风格的带行号片段：
+//
+// error[E2001]: 无法解析数字
+//  --> numbers.txt:2:1
+//   |
+// 2 | abc
+//   | ^^^ 不是有效的整数
+//   |
+//   = note: 每行应包含一个非负整数
+//
+// 诊断保存源文本、字节区间（span）和标签，解析类示例用它代替只有一句话的 String 错误
+
+use crate::errors::ErrorCode;
+use std::fmt;
+use std::ops::Range;
+
+// ===========================================
+// 1. 位置换算
+// ===========================================
+
+// 字节偏移 -> (行号, 列号)，两者都从 1 开始，列号按字符计数
+// 偏移超出文本时定位到文本末尾
+pub fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let offset = floor_char_boundary(text, offset.min(text.len()));
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = text[line_start..offset].chars().count() + 1;
+    (line, column)
+}
+
+fn floor_char_boundary(text: &str, mut offset: usize) -> usize {
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+// 终端中的显示宽度：中日韩字符和全角符号占两列
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6 => 2,
+        _ => 1,
+    }
+}
+
+fn str_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+// ===========================================
+// 2. 诊断数据结构
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+// 标签：一段字节区间加一句说明
+// 主标签用 ^ 标出，指向问题本身；次要标签用 - 标出，提供相关的上下文
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Range<usize>,
+    pub message: String,
+    pub primary: bool,
+}
+
+impl Label {
+    pub fn primary(span: Range<usize>, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+            primary: true,
+        }
+    }
+
+    pub fn secondary(span: Range<usize>, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+            primary: false,
+        }
+    }
+}
+
+// 带源码的诊断，同时也是一个标准错误：Display 只输出一行摘要，render() 输出完整片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDiagnostic {
+    pub severity: Severity,
+    pub code: Option<ErrorCode>,
+    pub message: String,
+    pub file_name: String,
+    pub source: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl SourceDiagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        SourceDiagnostic {
+            severity,
+            code: None,
+            message: message.into(),
+            file_name: String::from("<input>"),
+            source: String::new(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    // 诊断所针对的源文本，标签的区间都是相对于它的字节偏移
+    pub fn with_source(mut self, file_name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self.source = source.into();
+        self
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    // 主标签的起始位置（行号, 列号），没有主标签时取第一个标签
+    pub fn location(&self) -> Option<(usize, usize)> {
+        let label = self
+            .labels
+            .iter()
+            .find(|label| label.primary)
+            .or(self.labels.first())?;
+        Some(line_col(&self.source, label.span.start))
+    }
+
+    // 渲染完整的带行号片段
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        match self.code {
+            Some(code) => out.push_str(&format!("{}[{}]: {}\n", self.severity, code, self.message)),
+            None => out.push_str(&format!("{}: {}\n", self.severity, self.message)),
+        }
+
+        // 按行分组标签；跨行的标签只在起始行上标注到行尾
+        let lines: Vec<(usize, &str)> = line_spans(&self.source);
+        let mut by_line: Vec<(usize, Vec<&Label>)> = Vec::new();
+        for label in &self.labels {
+            let (line, _) = line_col(&self.source, label.span.start);
+            match by_line.iter_mut().find(|(l, _)| *l == line) {
+                Some((_, labels)) => labels.push(label),
+                None => by_line.push((line, vec![label])),
+            }
+        }
+        by_line.sort_by_key(|(line, _)| *line);
+
+        let max_line = by_line.last().map_or(1, |(line, _)| *line);
+        let gutter = max_line.to_string().len();
+        let pad = " ".repeat(gutter);
+
+        match self.location() {
+            Some((line, column)) => out.push_str(&format!(
+                "{}--> {}:{}:{}\n",
+                pad, self.file_name, line, column
+            )),
+            None => out.push_str(&format!("{}--> {}\n", pad, self.file_name)),
+        }
+
+        if !by_line.is_empty() {
+            out.push_str(&format!("{} |\n", pad));
+        }
+        let mut previous_line: Option<usize> = None;
+        for (line_number, labels) in &by_line {
+            if let Some(previous) = previous_line
+                && line_number - previous > 1
+            {
+                out.push_str("...\n");
+            }
+            previous_line = Some(*line_number);
+
+            let (line_start, line_text) = lines
+                .get(line_number - 1)
+                .copied()
+                .unwrap_or((self.source.len(), ""));
+            out.push_str(&format!(
+                "{:>gutter$} | {}\n",
+                line_number,
+                line_text.trim_end()
+            ));
+            render_annotations(&mut out, &pad, line_start, line_text, labels);
+        }
+
+        if !self.notes.is_empty() || !self.help.is_empty() {
+            if !by_line.is_empty() {
+                out.push_str(&format!("{} |\n", pad));
+            }
+            for note in &self.notes {
+                out.push_str(&format!("{} = note: {}\n", pad, note));
+            }
+            for help in &self.help {
+                out.push_str(&format!("{} = help: {}\n", pad, help));
+            }
+        }
+        out
+    }
+}
+
+// 每一行的 (起始字节偏移, 去掉换行符的内容)
+fn line_spans(text: &str) -> Vec<(usize, &str)> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for raw in text.split_inclusive('\n') {
+        let line = raw.strip_suffix('\n').unwrap_or(raw);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        spans.push((offset, line));
+        offset += raw.len();
+    }
+    if spans.is_empty() || text.ends_with('\n') {
+        spans.push((offset, ""));
+    }
+    spans
+}
+
+// 一个标签在当前行上的显示列范围
+struct Mark<'a> {
+    column: usize,
+    width: usize,
+    primary: bool,
+    message: &'a str,
+}
+
+fn render_annotations(
+    out: &mut String,
+    pad: &str,
+    line_start: usize,
+    line: &str,
+    labels: &[&Label],
+) {
+    let column_of = |offset: usize| {
+        let offset = offset.clamp(line_start, line_start + line.len()) - line_start;
+        str_width(&line[..floor_char_boundary(line, offset)])
+    };
+
+    let mut marks: Vec<Mark> = labels
+        .iter()
+        .map(|label| {
+            let start = column_of(label.span.start);
+            let end = column_of(label.span.end.max(label.span.start));
+            Mark {
+                column: start,
+                // 空区间（例如"此处缺少内容"）也至少标出一列
+                width: (end - start).max(1),
+                primary: label.primary,
+                message: &label.message,
+            }
+        })
+        .collect();
+    marks.sort_by_key(|mark| mark.column);
+
+    // 第一行：所有下划线，最右侧标签的说明直接跟在后面
+    let mut underline = String::new();
+    for mark in &marks {
+        while str_width(&underline) < mark.column {
+            underline.push(' ');
+        }
+        let symbol = if mark.primary { '^' } else { '-' };
+        underline.extend(std::iter::repeat_n(symbol, mark.width));
+    }
+    if let Some(last) = marks.last()
+        && !last.message.is_empty()
+    {
+        underline.push(' ');
+        underline.push_str(last.message);
+    }
+    out.push_str(&format!("{} | {}\n", pad, underline.trim_end()));
+
+    // 其余标签从右往左，用竖线把说明引到下方
+    for index in (0..marks.len().saturating_sub(1)).rev() {
+        if marks[index].message.is_empty() {
+            continue;
+        }
+        let connectors = |upto: usize| {
+            let mut text = String::new();
+            for mark in &marks[..upto] {
+                while text.len() < mark.column {
+                    text.push(' ');
+                }
+                text.push('|');
+            }
+            text
+        };
+        out.push_str(&format!("{} | {}\n", pad, connectors(index + 1)));
+        let mut message_line = connectors(index);
+        while message_line.len() < marks[index].column {
+            message_line.push(' ');
+        }
+        message_line.push_str(marks[index].message);
+        out.push_str(&format!("{} | {}\n", pad, message_line));
+    }
+}
+
+impl fmt::Display for SourceDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location() {
+            Some((line, column)) => {
+                write!(
+                    f,
+                    "{}:{}:{}: {}",
+                    self.file_name, line, column, self.message
+                )
+            }
+            None => write!(f, "{}: {}", self.file_name, self.message),
+        }
+    }
+}
+
+impl std::error::Error for SourceDiagnostic {}
+
+impl crate::errors::Diagnostic for SourceDiagnostic {
+    fn code(&self) -> ErrorCode {
+        self.code.unwrap_or(ErrorCode::PARSE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col() {
+        let text = "ab\ncd\n中文x";
+        assert_eq!(line_col(text, 0), (1, 1));
+        assert_eq!(line_col(text, 4), (2, 2));
+        assert_eq!(line_col(text, 6), (3, 1));
+        // "中" 占 3 个字节，但只算一列
+        assert_eq!(line_col(text, 12), (3, 3));
+        assert_eq!(line_col(text, 999), (3, 4));
+    }
+
+    #[test]
+    fn test_render_single_label_with_note() {
+        let source = "10\nabc\n30\n";
+        let diagnostic = SourceDiagnostic::error("无法解析数字")
+            .with_code(ErrorCode::PARSE_INT)
+            .with_source("numbers.txt", source)
+            .with_label(Label::primary(3..6, "不是有效的整数"))
+            .with_note("每行应包含一个非负整数");
+
+        let expected = "\
+error[E2001]: 无法解析数字
+ --> numbers.txt:2:1
+  |
+2 | abc
+  | ^^^ 不是有效的整数
+  |
+  = note: 每行应包含一个非负整数
+";
+        assert_eq!(diagnostic.render(), expected);
+        assert_eq!(diagnostic.to_string(), "numbers.txt:2:1: 无法解析数字");
+    }
+
+    #[test]
+    fn test_render_multiple_labels_on_one_line() {
+        let source = "let total = price + tax;";
+        let diagnostic = SourceDiagnostic::error("类型不匹配")
+            .with_source("main.rs", source)
+            .with_label(Label::secondary(12..17, "f64"))
+            .with_label(Label::primary(20..23, "String"))
+            .with_help("先把 tax 解析为数字");
+
+        let expected = "\
+error: 类型不匹配
+ --> main.rs:1:21
+  |
+1 | let total = price + tax;
+  |             -----   ^^^ String
+  |             |
+  |             f64
+  |
+  = help: 先把 tax 解析为数字
+";
+        assert_eq!(diagnostic.render(), expected);
+    }
+
+    #[test]
+    fn test_render_labels_on_distant_lines_and_empty_span() {
+        let source = "[server]\nhost = a\nport = 1\n\n\nmax = \n";
+        let diagnostic = SourceDiagnostic::warning("配置不完整")
+            .with_source("app.ini", source)
+            .with_label(Label::secondary(0..8, "在这个小节中"))
+            .with_label(Label::primary(35..35, "缺少取值"));
+
+        let expected = "\
+warning: 配置不完整
+ --> app.ini:6:7
+  |
+1 | [server]
+  | -------- 在这个小节中
+...
+6 | max =
+  |       ^ 缺少取值
+";
+        assert_eq!(diagnostic.render(), expected);
+    }
+
+    #[test]
+    fn test_render_wide_characters_align() {
+        let source = "名称 = 值";
+        let diagnostic = SourceDiagnostic::error("未知字段")
+            .with_source("<input>", source)
+            .with_label(Label::primary(0..6, "这里"));
+        // "名称" 在终端中占 4 列，下划线也是 4 列
+        assert!(diagnostic.render().contains("  | ^^^^ 这里\n"));
+    }
+}
@@ -0,0 +1,491 @@
+// 可复用的错误工具箱
+// 第 6 章里的各个示例错误类型都建立在这套设计之上：
+// 1. ErrorCode：稳定的数字错误码，按千位划分类别
+// 2. Diagnostic：具体错误类型实现它来声明自己的错误码
+// 3. Error：动态错误类型，负责承载上下文、错误码和回溯（backtrace）
+// 4. Context：为 Result / Option 附加上下文信息的扩展 trait
+// 5. Chain / Report：遍历 source() 链并渲染成易读的报告
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error::Error as StdError;
+use std::fmt;
+
+// ===========================================
+// 1. 错误码
+// ===========================================
+
+// 错误码一经发布就不再改变含义，日志检索、监控告警和文档都依赖它
+// 千位表示类别：1 系统与 IO，2 输入解析与校验，3 网络，4 数据库，5 服务，6 配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ErrorCode(pub u16);
+
+impl ErrorCode {
+    // 1xxx 系统与 IO
+    pub const IO: ErrorCode = ErrorCode(1001);
+    pub const FILE_NOT_FOUND: ErrorCode = ErrorCode(1002);
+    pub const PLUGIN_LOAD: ErrorCode = ErrorCode(1101);
+    pub const PLUGIN_ABI: ErrorCode = ErrorCode(1102);
+    pub const PLUGIN_CALL: ErrorCode = ErrorCode(1103);
+
+    // 2xxx 输入解析与校验
+    pub const PARSE_INT: ErrorCode = ErrorCode(2001);
+    pub const PARSE_FLOAT: ErrorCode = ErrorCode(2002);
+    pub const PARSE_BOOL: ErrorCode = ErrorCode(2003);
+    pub const PARSE: ErrorCode = ErrorCode(2004);
+    pub const PARSE_JSON: ErrorCode = ErrorCode(2005);
+    pub const PARSE_BINARY: ErrorCode = ErrorCode(2006);
+    pub const INVALID_INPUT: ErrorCode = ErrorCode(2101);
+    pub const VALIDATION: ErrorCode = ErrorCode(2102);
+
+    // 3xxx 网络
+    pub const NETWORK: ErrorCode = ErrorCode(3001);
+    pub const TIMEOUT: ErrorCode = ErrorCode(3002);
+    pub const CONNECTION_FAILED: ErrorCode = ErrorCode(3003);
+    pub const INVALID_RESPONSE: ErrorCode = ErrorCode(3004);
+
+    // 4xxx 数据库
+    pub const DATABASE: ErrorCode = ErrorCode(4001);
+    pub const QUERY_BUILD: ErrorCode = ErrorCode(4002);
+
+    // 5xxx 服务
+    pub const SERVICE: ErrorCode = ErrorCode(5001);
+
+    // 6xxx 配置
+    pub const CONFIG_INVALID_VALUE: ErrorCode = ErrorCode(6001);
+    pub const CONFIG_UNSUPPORTED_TYPE: ErrorCode = ErrorCode(6002);
+    pub const CONFIG_UNKNOWN_KEY: ErrorCode = ErrorCode(6003);
+    pub const CONFIG_SYNTAX: ErrorCode = ErrorCode(6004);
+    pub const CONFIG_MISSING: ErrorCode = ErrorCode(6005);
+
+    // 9xxx 其他
+    pub const UNKNOWN: ErrorCode = ErrorCode(9999);
+
+    pub fn category(self) -> &'static str {
+        match self.0 / 1000 {
+            1 => "系统",
+            2 => "输入",
+            3 => "网络",
+            4 => "数据库",
+            5 => "服务",
+            6 => "配置",
+            _ => "其他",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+// ===========================================
+// 2. Diagnostic：带错误码的具体错误
+// ===========================================
+
+// 具体错误类型实现 Diagnostic 后即可通过 ? 或 Context 转换为 Error，错误码随之保留
+pub trait Diagnostic: StdError {
+    fn code(&self) -> ErrorCode;
+}
+
+// 为常见的标准库错误分配错误码
+impl Diagnostic for std::io::Error {
+    fn code(&self) -> ErrorCode {
+        match self.kind() {
+            std::io::ErrorKind::NotFound => ErrorCode::FILE_NOT_FOUND,
+            std::io::ErrorKind::TimedOut => ErrorCode::TIMEOUT,
+            _ => ErrorCode::IO,
+        }
+    }
+}
+
+impl Diagnostic for std::num::ParseIntError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::PARSE_INT
+    }
+}
+
+impl Diagnostic for std::num::ParseFloatError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::PARSE_FLOAT
+    }
+}
+
+impl Diagnostic for std::str::ParseBoolError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::PARSE_BOOL
+    }
+}
+
+// ===========================================
+// 3. Error：动态错误类型
+// ===========================================
+
+// Error 有两种形态：
+// 1. 透明包装：由 Diagnostic 错误转换而来，Display 和 source() 都委托给被包装的错误
+// 2. 上下文层：Display 显示上下文信息，source() 指向被包装的下一层错误
+// 回溯在最内层错误创建时捕获（受 RUST_BACKTRACE / RUST_LIB_BACKTRACE 控制），之后随上下文层向外传递
+pub struct Error {
+    inner: Box<Inner>,
+}
+
+struct Inner {
+    code: Option<ErrorCode>,
+    message: Option<String>,
+    source: Option<Box<dyn StdError + Send + Sync + 'static>>,
+    backtrace: Backtrace,
+}
+
+impl Error {
+    // 直接创建一个带错误码的错误
+    pub fn new(code: ErrorCode, message: impl fmt::Display) -> Self {
+        Error {
+            inner: Box::new(Inner {
+                code: Some(code),
+                message: Some(message.to_string()),
+                source: None,
+                backtrace: Backtrace::capture(),
+            }),
+        }
+    }
+
+    // 只有消息、没有错误码的错误
+    pub fn msg(message: impl fmt::Display) -> Self {
+        Error {
+            inner: Box::new(Inner {
+                code: None,
+                message: Some(message.to_string()),
+                source: None,
+                backtrace: Backtrace::capture(),
+            }),
+        }
+    }
+
+    // 包装任意标准错误（没有实现 Diagnostic 的错误也可以）
+    pub fn wrap<E>(error: E) -> Self
+    where
+        E: StdError + Send + Sync + 'static,
+    {
+        Error {
+            inner: Box::new(Inner {
+                code: None,
+                message: None,
+                source: Some(Box::new(error)),
+                backtrace: Backtrace::capture(),
+            }),
+        }
+    }
+
+    // 在当前错误外再包一层上下文，错误码和回溯向外继承
+    pub fn context(mut self, context: impl fmt::Display) -> Self {
+        let code = self.inner.code;
+        let backtrace = std::mem::replace(&mut self.inner.backtrace, Backtrace::disabled());
+        Error {
+            inner: Box::new(Inner {
+                code,
+                message: Some(context.to_string()),
+                source: Some(Box::new(self)),
+                backtrace,
+            }),
+        }
+    }
+
+    // 覆盖错误码，例如把底层 IO 错误归类为更具体的业务错误
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.inner.code = Some(code);
+        self
+    }
+
+    // 错误码；没有显式设置时返回 UNKNOWN
+    pub fn code(&self) -> ErrorCode {
+        self.inner.code.unwrap_or(ErrorCode::UNKNOWN)
+    }
+
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.inner.backtrace
+    }
+
+    // 从自身开始沿 source() 遍历整条错误链
+    pub fn chain(&self) -> Chain<'_> {
+        Chain::new(self)
+    }
+
+    // 链上的最后一个错误，通常就是问题的根源
+    pub fn root_cause(&self) -> &(dyn StdError + 'static) {
+        self.chain().last().unwrap_or(self)
+    }
+
+    // 在错误链上查找某个具体类型的错误
+    pub fn downcast_ref<E: StdError + 'static>(&self) -> Option<&E> {
+        self.chain().find_map(|link| {
+            if let Some(found) = link.downcast_ref::<E>() {
+                return Some(found);
+            }
+            // 透明包装不会出现在链上，需要看一眼它包装的那个错误
+            let wrapper = link.downcast_ref::<Error>()?;
+            match (&wrapper.inner.message, &wrapper.inner.source) {
+                (None, Some(source)) => source.downcast_ref::<E>(),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn report(&self) -> Report<'_> {
+        Report::new(self)
+    }
+}
+
+// 所有 Diagnostic 错误都可以通过 ? 自动转换为 Error
+// Error 自身不实现 Diagnostic，因此这个泛型实现不会与 From<T> for T 冲突
+impl<E> From<E> for Error
+where
+    E: Diagnostic + Send + Sync + 'static,
+{
+    fn from(error: E) -> Self {
+        let code = error.code();
+        Error::wrap(error).with_code(code)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.inner.message, &self.inner.source) {
+            (Some(message), _) => f.write_str(message),
+            (None, Some(source)) => fmt::Display::fmt(source, f),
+            (None, None) => f.write_str("未知错误"),
+        }
+    }
+}
+
+impl fmt::Debug for Error {
+    // {:?} 输出完整报告，这样 main 返回 Result<(), Error> 时也能看到错误链
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.report().with_backtrace(true), f)
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        let source = self.inner.source.as_deref()?;
+        if self.inner.message.is_some() {
+            Some(source)
+        } else {
+            // 透明包装：跳过被包装的错误本身，避免同一条消息在链上出现两次
+            source.source()
+        }
+    }
+}
+
+// ===========================================
+// 4. Context 扩展 trait
+// ===========================================
+
+// 让 Result 和 Option 可以直接附加上下文：
+// let text = fs::read_to_string(path).with_context(|| format!("读取 {} 失败", path))?;
+pub trait Context<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, Error>;
+
+    // 仅在出错时才构造上下文，适合需要 format! 的场景
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, Error> {
+        self.map_err(|error| error.into().context(context))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T, Error> {
+        self.map_err(|error| error.into().context(f()))
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, Error> {
+        self.ok_or_else(|| Error::msg(context))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T, Error> {
+        self.ok_or_else(|| Error::msg(f()))
+    }
+}
+
+// ===========================================
+// 5. 错误链与报告
+// ===========================================
+
+// 沿 source() 遍历错误链的迭代器，第一个元素是错误本身
+pub struct Chain<'a> {
+    next: Option<&'a (dyn StdError + 'static)>,
+}
+
+impl<'a> Chain<'a> {
+    pub fn new(head: &'a (dyn StdError + 'static)) -> Self {
+        Chain { next: Some(head) }
+    }
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn StdError + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = current.source();
+        Some(current)
+    }
+}
+
+// 把任意错误渲染成多行报告：
+// 错误[E4001]: 获取用户数据失败
+// 原因:
+//    0: 数据库错误: 用户不存在
+//    1: 网络错误: 连接超时
+pub struct Report<'a> {
+    error: &'a (dyn StdError + 'static),
+    show_backtrace: bool,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(error: &'a (dyn StdError + 'static)) -> Self {
+        Report {
+            error,
+            show_backtrace: false,
+        }
+    }
+
+    // 若回溯已被捕获（设置了 RUST_BACKTRACE=1），在报告末尾附上
+    pub fn with_backtrace(mut self, show: bool) -> Self {
+        self.show_backtrace = show;
+        self
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let as_error = self.error.downcast_ref::<Error>();
+        match as_error {
+            Some(error) => write!(f, "错误[{}]: {}", error.code(), self.error)?,
+            None => write!(f, "错误: {}", self.error)?,
+        }
+
+        let mut causes = Chain::new(self.error).skip(1).peekable();
+        if causes.peek().is_some() {
+            write!(f, "\n原因:")?;
+            for (index, cause) in causes.enumerate() {
+                write!(f, "\n  {:>2}: {}", index, cause)?;
+            }
+        }
+
+        if self.show_backtrace
+            && let Some(error) = as_error
+            && error.backtrace().status() == BacktraceStatus::Captured
+        {
+            write!(f, "\n\n回溯:\n{}", error.backtrace())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct LowLevel;
+
+    impl fmt::Display for LowLevel {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "连接被重置")
+        }
+    }
+
+    impl StdError for LowLevel {}
+
+    impl Diagnostic for LowLevel {
+        fn code(&self) -> ErrorCode {
+            ErrorCode::CONNECTION_FAILED
+        }
+    }
+
+    fn connect() -> Result<(), LowLevel> {
+        Err(LowLevel)
+    }
+
+    fn fetch() -> Result<(), Error> {
+        connect().context("请求 /users 失败")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_code_display_and_category() {
+        assert_eq!(ErrorCode::IO.to_string(), "E1001");
+        assert_eq!(ErrorCode(42).to_string(), "E0042");
+        assert_eq!(ErrorCode::DATABASE.category(), "数据库");
+        assert_eq!(ErrorCode::UNKNOWN.category(), "其他");
+    }
+
+    #[test]
+    fn test_context_preserves_code_and_builds_chain() {
+        let error = fetch().context("加载用户列表失败").unwrap_err();
+        assert_eq!(error.code(), ErrorCode::CONNECTION_FAILED);
+
+        let messages: Vec<String> = error.chain().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            ["加载用户列表失败", "请求 /users 失败", "连接被重置"]
+        );
+        assert_eq!(error.root_cause().to_string(), "连接被重置");
+        assert!(error.downcast_ref::<LowLevel>().is_some());
+    }
+
+    #[test]
+    fn test_transparent_wrapping_does_not_duplicate() {
+        let error: Error = "abc".parse::<i32>().unwrap_err().into();
+        assert_eq!(error.code(), ErrorCode::PARSE_INT);
+        assert_eq!(error.chain().count(), 1);
+        assert_eq!(error.to_string(), "invalid digit found in string");
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing.txt");
+        assert_eq!(Error::from(io).code(), ErrorCode::FILE_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_option_context_and_code_override() {
+        let missing: Option<u32> = None;
+        let error = missing
+            .with_context(|| format!("缺少字段 {}", "port"))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "缺少字段 port");
+        assert_eq!(error.code(), ErrorCode::UNKNOWN);
+
+        let error = error.with_code(ErrorCode::CONFIG_INVALID_VALUE);
+        assert_eq!(error.code(), ErrorCode::CONFIG_INVALID_VALUE);
+        assert_eq!(Some(1).context("不会用到").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_report_rendering() {
+        let error = fetch().context("加载用户列表失败").unwrap_err();
+        assert_eq!(
+            error.report().to_string(),
+            "错误[E3003]: 加载用户列表失败\n原因:\n   0: 请求 /users 失败\n   1: 连接被重置"
+        );
+
+        // 非 Error 类型也可以渲染，只是没有错误码
+        assert_eq!(Report::new(&LowLevel).to_string(), "错误: 连接被重置");
+    }
+
+    #[test]
+    fn test_backtrace_is_moved_outwards() {
+        let error = Error::new(ErrorCode::SERVICE, "内部错误").context("外层");
+        // 是否真正捕获取决于 RUST_BACKTRACE，外层拿到的就是最内层创建时捕获的那一份
+        assert_eq!(error.backtrace().status(), Backtrace::capture().status());
+
+        let inner = error
+            .chain()
+            .nth(1)
+            .unwrap()
+            .downcast_ref::<Error>()
+            .unwrap();
+        assert_eq!(inner.backtrace().status(), BacktraceStatus::Disabled);
+    }
+}
@@ -0,0 +1,68 @@
+// 固定容量的集合
+// 第 14 章的 FixedSizeBuffer<const SIZE> 和 ValidatedArray<T, N> 只演示了 const 泛型参数，这里做成可以实际使用的集合，
+// 元素直接存放在结构体内部，不分配堆内存：
+// 1. ArrayVec<T, N>：Vec 风格的 API，容量固定为 N
+// 2. ArrayString<N>：最多 N 字节的 UTF-8 字符串
+// 3. RingBuffer<T, N>：环形缓冲区，两端都可以进出，写满后可以覆盖最旧的元素
+// 4. BitSet<N, WORDS>：存放 0..N 的位集合；N > 64 时需要写出字数 WORDS，可以用 BitSet<N, { words_for(N) }>
+//
+// 容量不足时 try_* 方法返回 CapacityError 并交还元素，对应的无 try_ 版本直接 panic。
+// 未初始化的槽位用 MaybeUninit 表示，只有已初始化的元素会被读取和析构；
+// 构造函数都是 const fn，可以直接用在 static 中。unsafe 代码的测试可以在 Miri 下运行：
+// cargo +nightly miri test --features miri --test miri
+
+use crate::errors::{Diagnostic, ErrorCode};
+use std::fmt;
+
+pub mod array_string;
+pub mod array_vec;
+pub mod bit_set;
+pub mod ring_buffer;
+
+pub use array_string::ArrayString;
+pub use array_vec::ArrayVec;
+pub use bit_set::{BitSet, words_for};
+pub use ring_buffer::RingBuffer;
+
+// 容量已满，element 是没能放进去的元素
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T = ()> {
+    element: T,
+}
+
+impl<T> CapacityError<T> {
+    pub const fn new(element: T) -> Self {
+        CapacityError { element }
+    }
+
+    // 取回没能放进去的元素
+    pub fn element(self) -> T {
+        self.element
+    }
+
+    // 丢弃元素，方便用 ? 转换成不带类型参数的错误
+    pub fn simplify(self) -> CapacityError {
+        CapacityError::new(())
+    }
+}
+
+// 不要求 T: Debug，这样任何元素类型的 try_push(...).unwrap() 都能编译
+impl<T> fmt::Debug for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CapacityError")
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("容量已满")
+    }
+}
+
+impl<T> std::error::Error for CapacityError<T> {}
+
+impl<T> Diagnostic for CapacityError<T> {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::INVALID_INPUT
+    }
+}
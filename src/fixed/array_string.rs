@@ -0,0 +1,341 @@
+// ArrayString<N>：最多 N 字节的 UTF-8 字符串
+// bytes[..len] 始终是合法的 UTF-8；存储全是 u8，所以整个类型可以是 Copy
+
+use super::CapacityError;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::{fmt, slice, str};
+
+#[derive(Clone, Copy)]
+pub struct ArrayString<const N: usize> {
+    bytes: [MaybeUninit<u8>; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayString<N> {
+    pub const CAPACITY: usize = N;
+
+    pub const fn new() -> Self {
+        ArrayString {
+            bytes: [MaybeUninit::uninit(); N],
+            len: 0,
+        }
+    }
+
+    pub const fn try_from_str(s: &str) -> Result<Self, CapacityError<&str>> {
+        let mut string = Self::new();
+        match string.try_push_str(s) {
+            Ok(()) => Ok(string),
+            Err(e) => Err(e),
+        }
+    }
+
+    // 给 static 使用：放不下时在编译期报错
+    pub const fn from_static(s: &'static str) -> Self {
+        match Self::try_from_str(s) {
+            Ok(string) => string,
+            Err(_) => panic!("字符串超出 ArrayString 的容量"),
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    pub const fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.bytes.as_ptr().cast(), self.len) }
+    }
+
+    pub const fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(self.as_bytes()) }
+    }
+
+    pub const fn as_mut_str(&mut self) -> &mut str {
+        unsafe {
+            let bytes = slice::from_raw_parts_mut(self.bytes.as_mut_ptr().cast(), self.len);
+            str::from_utf8_unchecked_mut(bytes)
+        }
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        if self.try_push_str(s).is_err() {
+            panic!(
+                "ArrayString 剩余 {} 字节，放不下 {} 字节",
+                self.remaining_capacity(),
+                s.len()
+            );
+        }
+    }
+
+    // 放不下时一个字节都不写入
+    pub const fn try_push_str<'a>(&mut self, s: &'a str) -> Result<(), CapacityError<&'a str>> {
+        if s.len() > self.remaining_capacity() {
+            return Err(CapacityError::new(s));
+        }
+        let bytes = s.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            self.bytes[self.len + i] = MaybeUninit::new(bytes[i]);
+            i += 1;
+        }
+        self.len += bytes.len();
+        Ok(())
+    }
+
+    pub fn push(&mut self, c: char) {
+        if self.try_push(c).is_err() {
+            panic!(
+                "ArrayString 剩余 {} 字节，放不下 {:?}",
+                self.remaining_capacity(),
+                c
+            );
+        }
+    }
+
+    pub fn try_push(&mut self, c: char) -> Result<(), CapacityError<char>> {
+        let mut buffer = [0; 4];
+        self.try_push_str(c.encode_utf8(&mut buffer))
+            .map_err(|_| CapacityError::new(c))
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        self.len -= c.len_utf8();
+        Some(c)
+    }
+
+    // new_len 必须落在字符边界上
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len {
+            assert!(self.is_char_boundary(new_len), "{} 不是字符边界", new_len);
+            self.len = new_len;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn insert(&mut self, index: usize, c: char) {
+        if self.try_insert(index, c).is_err() {
+            panic!(
+                "ArrayString 剩余 {} 字节，放不下 {:?}",
+                self.remaining_capacity(),
+                c
+            );
+        }
+    }
+
+    pub fn try_insert(&mut self, index: usize, c: char) -> Result<(), CapacityError<char>> {
+        assert!(self.is_char_boundary(index), "{} 不是字符边界", index);
+        let width = c.len_utf8();
+        if width > self.remaining_capacity() {
+            return Err(CapacityError::new(c));
+        }
+        self.bytes.copy_within(index..self.len, index + width);
+        let mut buffer = [0; 4];
+        for (slot, &byte) in self.bytes[index..index + width]
+            .iter_mut()
+            .zip(c.encode_utf8(&mut buffer).as_bytes())
+        {
+            *slot = MaybeUninit::new(byte);
+        }
+        self.len += width;
+        Ok(())
+    }
+
+    // 移除 index 处的字符；index 必须是字符边界
+    pub fn remove(&mut self, index: usize) -> char {
+        let c = self.as_str()[index..]
+            .chars()
+            .next()
+            .unwrap_or_else(|| panic!("移除位置 {} 超出长度 {}", index, self.len));
+        self.bytes
+            .copy_within(index + c.len_utf8()..self.len, index);
+        self.len -= c.len_utf8();
+        c
+    }
+}
+
+impl<const N: usize> Default for ArrayString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for ArrayString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> DerefMut for ArrayString<N> {
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+// 写不下时返回 fmt::Error，已经写入的部分保留
+impl<const N: usize> fmt::Write for ArrayString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> fmt::Display for ArrayString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for ArrayString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<ArrayString<M>> for ArrayString<N> {
+    fn eq(&self, other: &ArrayString<M>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq<str> for ArrayString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for ArrayString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialEq<ArrayString<N>> for str {
+    fn eq(&self, other: &ArrayString<N>) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for ArrayString<N> {}
+
+impl<const N: usize> PartialOrd for ArrayString<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for ArrayString<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// 与 str 的哈希一致，HashMap<ArrayString<N>, _> 可以用 &str 查询
+impl<const N: usize> Hash for ArrayString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> Borrow<str> for ArrayString<N> {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> AsRef<str> for ArrayString<N> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ArrayString<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<'a, const N: usize> TryFrom<&'a str> for ArrayString<N> {
+    type Error = CapacityError<&'a str>;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        Self::try_from_str(s)
+    }
+}
+
+impl<const N: usize> FromStr for ArrayString<N> {
+    type Err = CapacityError;
+
+    fn from_str(s: &str) -> Result<Self, CapacityError> {
+        Self::try_from_str(s).map_err(CapacityError::simplify)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::fmt::Write;
+
+    static GREETING: ArrayString<16> = ArrayString::from_static("你好, Rust");
+
+    #[test]
+    fn test_push_pop_and_utf8_boundaries() {
+        assert_eq!(GREETING, "你好, Rust");
+        assert_eq!(GREETING.len(), 12);
+
+        let mut s = ArrayString::<8>::new();
+        s.push_str("ab");
+        s.push('中');
+        assert_eq!((s.as_str(), s.len()), ("ab中", 5));
+        // 剩 3 字节，4 字节的 emoji 放不下，内容保持不变
+        assert_eq!(s.try_push('🦀').unwrap_err().element(), '🦀');
+        assert_eq!(s.try_push_str("defg").unwrap_err().element(), "defg");
+        s.insert(1, 'é');
+        assert_eq!(s, "aéb中");
+        assert_eq!(s.remove(0), 'a');
+        assert_eq!(s.pop(), Some('中'));
+        s.truncate(2);
+        assert_eq!(s, "é");
+        s.make_ascii_uppercase();
+        assert_eq!(s.to_uppercase(), "É");
+    }
+
+    #[test]
+    fn test_format_and_lookup() {
+        let mut s = ArrayString::<10>::new();
+        write!(s, "{}-ab", 12).unwrap();
+        assert_eq!(s, "12-ab");
+        assert!(write!(s, "{}", 123456).is_err());
+
+        let key: ArrayString<8> = "key".parse().unwrap();
+        let mut map = HashMap::new();
+        map.insert(key, 1);
+        assert_eq!(map.get("key"), Some(&1));
+        assert!("too long".parse::<ArrayString<4>>().is_err());
+    }
+}
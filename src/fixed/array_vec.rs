@@ -0,0 +1,701 @@
+// ArrayVec<T, N>：容量为 N 的 Vec
+// data[..len] 已初始化，data[len..] 未初始化；所有修改 len 的路径都先保证这个不变式再析构元素，
+// 元素的 Drop 或回调发生 panic 时，元素可能泄漏，但不会被重复析构
+
+use super::CapacityError;
+use std::borrow::{Borrow, BorrowMut};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+use std::{fmt, io, ptr, slice};
+
+pub struct ArrayVec<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    pub const CAPACITY: usize = N;
+
+    pub const fn new() -> Self {
+        ArrayVec {
+            data: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    // 用一个完整的数组构造，结果是满的
+    pub const fn from_array(array: [T; N]) -> Self {
+        let array = ManuallyDrop::new(array);
+        // ManuallyDrop<[T; N]> 与 [MaybeUninit<T>; N] 的布局相同
+        let data = unsafe { ptr::read((&raw const array).cast::<[MaybeUninit<T>; N]>()) };
+        ArrayVec { data, len: N }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    pub const fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    pub const fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    pub const fn as_ptr(&self) -> *const T {
+        self.data.as_ptr().cast()
+    }
+
+    pub const fn as_mut_ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr().cast()
+    }
+
+    pub fn push(&mut self, value: T) {
+        assert!(!self.is_full(), "ArrayVec 已满（容量 {}）", N);
+        unsafe { self.push_unchecked(value) }
+    }
+
+    pub fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+        unsafe { self.push_unchecked(value) };
+        Ok(())
+    }
+
+    /// # Safety
+    /// 调用方保证还有剩余容量
+    pub unsafe fn push_unchecked(&mut self, value: T) {
+        debug_assert!(!self.is_full());
+        unsafe { self.data.get_unchecked_mut(self.len).write(value) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.data.get_unchecked(self.len).assume_init_read() })
+    }
+
+    // 在 index 处插入，后面的元素后移；index > len 时 panic
+    pub fn insert(&mut self, index: usize, value: T) {
+        if self.try_insert(index, value).is_err() {
+            panic!("ArrayVec 已满（容量 {}）", N);
+        }
+    }
+
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<(), CapacityError<T>> {
+        assert!(
+            index <= self.len,
+            "插入位置 {} 超出长度 {}",
+            index,
+            self.len
+        );
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            p.write(value);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    // 移除 index 处的元素，后面的元素前移
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "移除位置 {} 超出长度 {}", index, self.len);
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let value = p.read();
+            ptr::copy(p.add(1), p, self.len - index - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    // 用最后一个元素填补被移除的位置，O(1) 但不保持顺序
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "移除位置 {} 超出长度 {}", index, self.len);
+        let last = self.len - 1;
+        self.as_mut_slice().swap(index, last);
+        self.pop().unwrap()
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail =
+            ptr::slice_from_raw_parts_mut(unsafe { self.as_mut_ptr().add(len) }, self.len - len);
+        self.len = len;
+        unsafe { ptr::drop_in_place(tail) };
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    // 只保留 keep 返回 true 的元素，保持原有顺序
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        self.retain_mut(|item| keep(item));
+    }
+
+    pub fn retain_mut(&mut self, mut keep: impl FnMut(&mut T) -> bool) {
+        // keep 或元素析构 panic 时，guard 把尚未处理的元素接到保留的元素后面，每个元素仍然只析构一次
+        struct Guard<'v, T, const N: usize> {
+            vec: &'v mut ArrayVec<T, N>,
+            len: usize,
+            processed: usize,
+            kept: usize,
+        }
+
+        impl<T, const N: usize> Drop for Guard<'_, T, N> {
+            fn drop(&mut self) {
+                let tail = self.len - self.processed;
+                unsafe {
+                    let base = self.vec.as_mut_ptr();
+                    ptr::copy(base.add(self.processed), base.add(self.kept), tail);
+                }
+                self.vec.len = self.kept + tail;
+            }
+        }
+
+        let len = self.len;
+        self.len = 0;
+        let mut guard = Guard {
+            vec: self,
+            len,
+            processed: 0,
+            kept: 0,
+        };
+        while guard.processed < len {
+            unsafe {
+                let base = guard.vec.as_mut_ptr();
+                let p = base.add(guard.processed);
+                if keep(&mut *p) {
+                    if guard.kept != guard.processed {
+                        ptr::copy_nonoverlapping(p, base.add(guard.kept), 1);
+                    }
+                    guard.kept += 1;
+                    guard.processed += 1;
+                } else {
+                    guard.processed += 1;
+                    ptr::drop_in_place(p);
+                }
+            }
+        }
+    }
+
+    // 移除相邻的重复元素
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|current, previous| current == previous);
+    }
+
+    // same(current, previous) 返回 true 时移除 current；previous 是上一个保留下来的元素
+    pub fn dedup_by(&mut self, mut same: impl FnMut(&mut T, &mut T) -> bool) {
+        if self.len <= 1 {
+            return;
+        }
+        let len = self.len;
+        // same 或析构 panic 时泄漏剩余的元素，而不是重复析构
+        self.len = 0;
+        let base = self.as_mut_ptr();
+        let mut kept = 1;
+        for i in 1..len {
+            unsafe {
+                let current = base.add(i);
+                if same(&mut *current, &mut *base.add(kept - 1)) {
+                    ptr::drop_in_place(current);
+                } else {
+                    if kept != i {
+                        ptr::copy_nonoverlapping(current, base.add(kept), 1);
+                    }
+                    kept += 1;
+                }
+            }
+        }
+        self.len = kept;
+    }
+
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        if self.try_extend_from_slice(other).is_err() {
+            panic!(
+                "ArrayVec 剩余容量 {}，放不下 {} 个元素",
+                self.remaining_capacity(),
+                other.len()
+            );
+        }
+    }
+
+    // 容量不够时一个都不添加
+    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<(), CapacityError>
+    where
+        T: Clone,
+    {
+        if other.len() > self.remaining_capacity() {
+            return Err(CapacityError::new(()));
+        }
+        for item in other {
+            unsafe { self.push_unchecked(item.clone()) };
+        }
+        Ok(())
+    }
+
+    // 移除 range 中的元素并以迭代器返回；迭代器 drop 时未取出的元素被析构，后面的元素前移
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T, N> {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n + 1,
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end && end <= self.len,
+            "drain 范围 {}..{} 超出长度 {}",
+            start,
+            end,
+            self.len
+        );
+        let tail_len = self.len - end;
+        // 迭代期间 vec 只看得到 range 之前的元素
+        self.len = start;
+        Drain {
+            vec: self,
+            next: start,
+            end,
+            tail_start: end,
+            tail_len,
+        }
+    }
+
+    // 满的时候转换成数组，否则原样返回
+    pub fn into_inner(self) -> Result<[T; N], Self> {
+        if !self.is_full() {
+            return Err(self);
+        }
+        let this = ManuallyDrop::new(self);
+        Ok(unsafe { ptr::read(this.as_ptr().cast::<[T; N]>()) })
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVec<T, N> {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new();
+        for item in self.iter() {
+            unsafe { clone.push_unchecked(item.clone()) };
+        }
+        clone
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<ArrayVec<T, M>> for ArrayVec<T, N> {
+    fn eq(&self, other: &ArrayVec<T, M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T]> for ArrayVec<T, N> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<&[T]> for ArrayVec<T, N> {
+    fn eq(&self, other: &&[T]) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<[T; M]> for ArrayVec<T, N> {
+    fn eq(&self, other: &[T; M]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayVec<T, N> {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for ArrayVec<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for ArrayVec<T, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Hash, const N: usize> Hash for ArrayVec<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for ArrayVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> AsMut<[T]> for ArrayVec<T, N> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize> Borrow<[T]> for ArrayVec<T, N> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> BorrowMut<[T]> for ArrayVec<T, N> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for ArrayVec<T, N> {
+    fn from(array: [T; N]) -> Self {
+        Self::from_array(array)
+    }
+}
+
+impl<T: Clone, const N: usize> TryFrom<&[T]> for ArrayVec<T, N> {
+    type Error = CapacityError;
+
+    fn try_from(slice: &[T]) -> Result<Self, CapacityError> {
+        let mut vec = Self::new();
+        vec.try_extend_from_slice(slice)?;
+        Ok(vec)
+    }
+}
+
+// 元素个数超过容量时 panic
+impl<T, const N: usize> Extend<T> for ArrayVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArrayVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> IntoIter<T, N> {
+        let this = ManuallyDrop::new(self);
+        IntoIter {
+            data: unsafe { ptr::read(&this.data) },
+            start: 0,
+            end: this.len,
+        }
+    }
+}
+
+// 写满后返回 0，write_all 会因此报 WriteZero
+impl<const N: usize> io::Write for ArrayVec<u8, N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.remaining_capacity());
+        self.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// 按值迭代，data[start..end] 是尚未取出的元素
+pub struct IntoIter<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    start: usize,
+    end: usize,
+}
+
+impl<T, const N: usize> IntoIter<T, N> {
+    pub fn as_slice(&self) -> &[T] {
+        unsafe {
+            slice::from_raw_parts(
+                self.data.as_ptr().add(self.start).cast(),
+                self.end - self.start,
+            )
+        }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        Some(unsafe { self.data[self.start - 1].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { self.data[self.end].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let remaining = ptr::slice_from_raw_parts_mut(
+            unsafe { self.data.as_mut_ptr().add(self.start).cast::<T>() },
+            self.end - self.start,
+        );
+        self.start = self.end;
+        unsafe { ptr::drop_in_place(remaining) };
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for IntoIter<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+// ArrayVec::drain 返回的迭代器
+// vec.len 已经缩短到 range 的起点；data[next..end] 是尚未取出的元素，data[tail_start..] 是 range 之后的元素
+pub struct Drain<'a, T, const N: usize> {
+    vec: &'a mut ArrayVec<T, N>,
+    next: usize,
+    end: usize,
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<T, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(unsafe { self.vec.data[self.next - 1].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for Drain<'_, T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { self.vec.data[self.end].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Drain<'_, T, N> {}
+
+impl<T, const N: usize> Drop for Drain<'_, T, N> {
+    fn drop(&mut self) {
+        // 析构没有取出的元素之后把 range 之后的元素接回去；放在 guard 里，析构 panic 展开时也会执行
+        struct MoveTail<'r, 'a, T, const N: usize>(&'r mut Drain<'a, T, N>);
+
+        impl<T, const N: usize> Drop for MoveTail<'_, '_, T, N> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                let start = drain.vec.len;
+                unsafe {
+                    let base = drain.vec.as_mut_ptr();
+                    ptr::copy(base.add(drain.tail_start), base.add(start), drain.tail_len);
+                }
+                drain.vec.len = start + drain.tail_len;
+            }
+        }
+
+        let remaining = ptr::slice_from_raw_parts_mut(
+            unsafe { self.vec.as_mut_ptr().add(self.next) },
+            self.end - self.next,
+        );
+        self.next = self.end;
+        let _move_tail = MoveTail(self);
+        unsafe { ptr::drop_in_place(remaining) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // const fn new 可以直接初始化 static
+    static RECENT: Mutex<ArrayVec<&str, 3>> = Mutex::new(ArrayVec::new());
+
+    #[test]
+    fn test_push_pop_and_capacity_errors() {
+        let mut vec = ArrayVec::<String, 3>::new();
+        assert_eq!((vec.len(), vec.capacity()), (0, 3));
+        vec.push("a".to_string());
+        vec.push("b".to_string());
+        vec.try_push("c".to_string()).unwrap();
+        assert!(vec.is_full());
+        let err = vec.try_push("d".to_string()).unwrap_err();
+        assert_eq!(err.element(), "d");
+        assert_eq!(vec, ["a", "b", "c"].map(String::from));
+
+        assert_eq!(vec.pop().as_deref(), Some("c"));
+        vec.insert(0, "z".to_string());
+        assert_eq!(vec.remove(1), "a");
+        assert_eq!(vec.swap_remove(0), "z");
+        assert_eq!(vec.as_slice(), ["b".to_string()]);
+
+        RECENT.lock().unwrap().push("static");
+        assert_eq!(RECENT.lock().unwrap().as_slice(), ["static"]);
+    }
+
+    #[test]
+    fn test_retain_drain_and_into_iter() {
+        let mut vec: ArrayVec<i32, 10> = (1..=8).collect();
+        vec.retain(|x| *x % 3 != 0);
+        assert_eq!(vec, [1, 2, 4, 5, 7, 8]);
+
+        let drained: Vec<i32> = vec.drain(1..4).collect();
+        assert_eq!(drained, [2, 4, 5]);
+        assert_eq!(vec, [1, 7, 8]);
+
+        // 只取出一部分就 drop，剩下的元素被析构，尾部接回来
+        let mut words: ArrayVec<String, 5> = ["a", "b", "c", "d", "e"].map(String::from).into();
+        assert_eq!(words.drain(1..=3).next_back().as_deref(), Some("d"));
+        assert_eq!(words, ["a", "e"].map(String::from));
+
+        let mut iter = words.into_iter();
+        assert_eq!(iter.next().as_deref(), Some("a"));
+        assert_eq!(iter.len(), 1);
+
+        let mut dup = ArrayVec::from_array([1, 1, 2, 2, 2, 3, 1]);
+        dup.dedup();
+        assert_eq!(dup, [1, 2, 3, 1]);
+        assert!(dup.try_extend_from_slice(&[0; 4]).is_err());
+        assert_eq!(dup.len(), 4);
+    }
+
+    #[test]
+    fn test_conversions_and_write() {
+        let full = ArrayVec::<u8, 3>::try_from(&[1u8, 2, 3][..]).unwrap();
+        assert_eq!(full.clone().into_inner(), Ok([1, 2, 3]));
+        assert!(ArrayVec::<u8, 2>::try_from(&[1u8, 2, 3][..]).is_err());
+
+        let mut buffer = ArrayVec::<u8, 8>::new();
+        use std::io::Write;
+        write!(buffer, "abc{}", 12).unwrap();
+        assert_eq!(&buffer[..], b"abc12");
+        assert!(buffer.write_all(b"overflow").is_err());
+        assert!(buffer.is_full());
+    }
+}
@@ -0,0 +1,379 @@
+// BitSet<N, WORDS>：可以存放 0..N 中整数的位集合，N 是位数，位存放在 WORDS 个 u64 中
+// 由 N 算出字数需要尚未稳定的 generic_const_exprs，所以字数作为第二个参数写出来，默认为 1：
+// N <= 64 时直接写 BitSet<N>；更大的集合写成 BitSet<200, 4> 或 BitSet<200, { words_for(200) }>。
+// WORDS 与 N 不匹配时在编译期报错，不会悄悄多出或少掉容量
+
+use super::CapacityError;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+
+// 存放 0..bits 需要的字数
+pub const fn words_for(bits: usize) -> usize {
+    bits.div_ceil(64)
+}
+
+// 不变式：N 及以上的位始终为 0
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet<const N: usize, const WORDS: usize = 1> {
+    words: [u64; WORDS],
+}
+
+impl<const N: usize, const WORDS: usize> BitSet<N, WORDS> {
+    pub const CAPACITY: usize = N;
+
+    // 每个构造函数都引用它，WORDS 写错的类型一旦被使用就无法编译
+    const WORDS_MATCH: () = assert!(
+        WORDS == words_for(N) || (N == 0 && WORDS == 1),
+        "BitSet<N, WORDS> 的 WORDS 必须等于 words_for(N)"
+    );
+
+    pub const fn new() -> Self {
+        let () = Self::WORDS_MATCH;
+        BitSet { words: [0; WORDS] }
+    }
+
+    // 超出 N 的位被丢弃
+    pub const fn from_words(mut words: [u64; WORDS]) -> Self {
+        let () = Self::WORDS_MATCH;
+        if !N.is_multiple_of(64) {
+            words[WORDS - 1] &= (1 << (N % 64)) - 1;
+        }
+        BitSet { words }
+    }
+
+    pub const fn words(&self) -> &[u64; WORDS] {
+        &self.words
+    }
+
+    // 链式插入，给 static 使用：超出容量时在编译期报错
+    pub const fn with(mut self, value: usize) -> Self {
+        assert!(value < Self::CAPACITY, "超出 BitSet 的容量");
+        self.words[value / 64] |= 1 << (value % 64);
+        self
+    }
+
+    pub const fn capacity(&self) -> usize {
+        Self::CAPACITY
+    }
+
+    pub const fn contains(&self, value: usize) -> bool {
+        value < Self::CAPACITY && self.words[value / 64] & (1 << (value % 64)) != 0
+    }
+
+    pub const fn len(&self) -> usize {
+        let mut count = 0;
+        let mut i = 0;
+        while i < WORDS {
+            count += self.words[i].count_ones() as usize;
+            i += 1;
+        }
+        count
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        let mut i = 0;
+        while i < WORDS {
+            if self.words[i] != 0 {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    // 返回插入前是否不存在；超出容量时 panic
+    pub fn insert(&mut self, value: usize) -> bool {
+        self.try_insert(value)
+            .unwrap_or_else(|_| panic!("{} 超出 BitSet 的容量 {}", value, Self::CAPACITY))
+    }
+
+    pub fn try_insert(&mut self, value: usize) -> Result<bool, CapacityError<usize>> {
+        if value >= Self::CAPACITY {
+            return Err(CapacityError::new(value));
+        }
+        let added = !self.contains(value);
+        self.words[value / 64] |= 1 << (value % 64);
+        Ok(added)
+    }
+
+    // 返回移除前是否存在
+    pub fn remove(&mut self, value: usize) -> bool {
+        let present = self.contains(value);
+        if present {
+            self.words[value / 64] &= !(1 << (value % 64));
+        }
+        present
+    }
+
+    // 切换一位，返回切换后是否存在；超出容量时 panic
+    pub fn toggle(&mut self, value: usize) -> bool {
+        assert!(
+            value < Self::CAPACITY,
+            "{} 超出 BitSet 的容量 {}",
+            value,
+            Self::CAPACITY
+        );
+        self.words[value / 64] ^= 1 << (value % 64);
+        self.contains(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.words = [0; WORDS];
+    }
+
+    // 按升序迭代
+    pub fn iter(&self) -> Iter<'_, N, WORDS> {
+        Iter {
+            set: self,
+            front: 0,
+            back: Self::CAPACITY,
+        }
+    }
+
+    pub fn first(&self) -> Option<usize> {
+        self.iter().next()
+    }
+
+    pub fn last(&self) -> Option<usize> {
+        self.iter().next_back()
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    fn zip_with(mut self, other: Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        for (a, b) in self.words.iter_mut().zip(other.words) {
+            *a = f(*a, b);
+        }
+        self
+    }
+}
+
+impl<const N: usize, const WORDS: usize> Default for BitSet<N, WORDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 按集合的形式输出，如 {1, 5, 64}
+impl<const N: usize, const WORDS: usize> fmt::Debug for BitSet<N, WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+// 并集
+impl<const N: usize, const WORDS: usize> BitOr for BitSet<N, WORDS> {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a | b)
+    }
+}
+
+// 交集
+impl<const N: usize, const WORDS: usize> BitAnd for BitSet<N, WORDS> {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a & b)
+    }
+}
+
+// 对称差
+impl<const N: usize, const WORDS: usize> BitXor for BitSet<N, WORDS> {
+    type Output = Self;
+
+    fn bitxor(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+}
+
+// 差集
+impl<const N: usize, const WORDS: usize> Sub for BitSet<N, WORDS> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a & !b)
+    }
+}
+
+impl<const N: usize, const WORDS: usize> BitOrAssign for BitSet<N, WORDS> {
+    fn bitor_assign(&mut self, other: Self) {
+        *self = *self | other;
+    }
+}
+
+impl<const N: usize, const WORDS: usize> BitAndAssign for BitSet<N, WORDS> {
+    fn bitand_assign(&mut self, other: Self) {
+        *self = *self & other;
+    }
+}
+
+impl<const N: usize, const WORDS: usize> BitXorAssign for BitSet<N, WORDS> {
+    fn bitxor_assign(&mut self, other: Self) {
+        *self = *self ^ other;
+    }
+}
+
+impl<const N: usize, const WORDS: usize> SubAssign for BitSet<N, WORDS> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+// 超出容量的值会 panic
+impl<const N: usize, const WORDS: usize> Extend<usize> for BitSet<N, WORDS> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<const N: usize, const WORDS: usize> FromIterator<usize> for BitSet<N, WORDS> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, const N: usize, const WORDS: usize> IntoIterator for &'a BitSet<N, WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<'a, N, WORDS>;
+
+    fn into_iter(self) -> Iter<'a, N, WORDS> {
+        self.iter()
+    }
+}
+
+// 在 [front, back) 中查找置位的位，每次跳过整段的 0
+#[derive(Debug, Clone)]
+pub struct Iter<'a, const N: usize, const WORDS: usize> {
+    set: &'a BitSet<N, WORDS>,
+    front: usize,
+    back: usize,
+}
+
+impl<const N: usize, const WORDS: usize> Iterator for Iter<'_, N, WORDS> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.front < self.back {
+            let word = self.front / 64;
+            // 去掉当前字中 front 之前的位
+            let bits = self.set.words[word] & (u64::MAX << (self.front % 64));
+            if bits == 0 {
+                self.front = (word + 1) * 64;
+                continue;
+            }
+            let found = word * 64 + bits.trailing_zeros() as usize;
+            if found >= self.back {
+                break;
+            }
+            self.front = found + 1;
+            return Some(found);
+        }
+        self.front = self.back;
+        None
+    }
+}
+
+impl<const N: usize, const WORDS: usize> DoubleEndedIterator for Iter<'_, N, WORDS> {
+    fn next_back(&mut self) -> Option<usize> {
+        while self.front < self.back {
+            let last = self.back - 1;
+            let word = last / 64;
+            // 去掉当前字中 last 之后的位
+            let bits = self.set.words[word] & (u64::MAX >> (63 - last % 64));
+            if bits == 0 {
+                self.back = word * 64;
+                continue;
+            }
+            let found = word * 64 + 63 - bits.leading_zeros() as usize;
+            if found < self.front {
+                break;
+            }
+            self.back = found;
+            return Some(found);
+        }
+        self.back = self.front;
+        None
+    }
+}
+
+impl<const N: usize, const WORDS: usize> FusedIterator for Iter<'_, N, WORDS> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PRIMES: BitSet<16> = BitSet::new().with(2).with(3).with(5).with(7).with(11);
+
+    #[test]
+    fn test_insert_remove_and_iterate() {
+        assert_eq!(PRIMES.len(), 5);
+        assert!(PRIMES.contains(7) && !PRIMES.contains(9) && !PRIMES.contains(1000));
+
+        // 容量就是位数，不向上取整到 64 的倍数
+        let mut set = BitSet::<100, 2>::new();
+        assert_eq!(set.capacity(), 100);
+        assert!(set.insert(0));
+        assert!(set.insert(64));
+        assert!(set.insert(99));
+        assert!(!set.insert(64));
+        assert_eq!(set.try_insert(100).unwrap_err().element(), 100);
+        assert!(!set.contains(127));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0, 64, 99]);
+        assert_eq!(set.iter().rev().collect::<Vec<_>>(), [99, 64, 0]);
+        assert_eq!((set.first(), set.last()), (Some(0), Some(99)));
+
+        // 从两端交替取，不会重复
+        let mut iter = set.iter();
+        assert_eq!((iter.next(), iter.next_back()), (Some(0), Some(99)));
+        assert_eq!((iter.next_back(), iter.next()), (Some(64), None));
+
+        assert!(set.remove(64) && !set.remove(64));
+        assert_eq!(BitSet::<200, { words_for(200) }>::CAPACITY, 200);
+        assert_eq!((words_for(0), words_for(64), words_for(65)), (0, 1, 2));
+        assert!(!set.toggle(0));
+        assert_eq!(format!("{:?}", set), "{99}");
+
+        // from_words 丢弃超出容量的位
+        let full = BitSet::<70, 2>::from_words([u64::MAX; 2]);
+        assert_eq!((full.len(), full.last()), (70, Some(69)));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: BitSet<128, 2> = [1, 2, 3, 70].into_iter().collect();
+        let b: BitSet<128, 2> = [3, 4, 70, 100].into_iter().collect();
+        assert_eq!((a | b).iter().collect::<Vec<_>>(), [1, 2, 3, 4, 70, 100]);
+        assert_eq!((a & b).iter().collect::<Vec<_>>(), [3, 70]);
+        assert_eq!((a - b).iter().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!((a ^ b).iter().collect::<Vec<_>>(), [1, 2, 4, 100]);
+        assert!((a & b).is_subset(&a) && a.is_superset(&(a - b)));
+        assert!((a - b).is_disjoint(&b));
+
+        let mut c = a;
+        c |= b;
+        c -= a;
+        assert_eq!(c.iter().collect::<Vec<_>>(), [4, 100]);
+        c.clear();
+        assert!(c.is_empty());
+    }
+}
@@ -0,0 +1,441 @@
+// RingBuffer<T, N>：容量为 N 的环形缓冲区（双端队列）
+// 逻辑上的第 i 个元素存放在 data[(head + i) % N]，从 head 开始的 len 个槽位已初始化
+
+use super::CapacityError;
+use std::iter::FusedIterator;
+use std::mem::MaybeUninit;
+use std::ops::{Index, IndexMut};
+use std::{fmt, ptr, slice};
+
+pub struct RingBuffer<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    pub const CAPACITY: usize = N;
+
+    pub const fn new() -> Self {
+        RingBuffer {
+            data: [const { MaybeUninit::uninit() }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    // 逻辑下标对应的槽位；调用方保证 N > 0
+    fn slot(&self, index: usize) -> usize {
+        let slot = self.head + index;
+        if slot >= N { slot - N } else { slot }
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        if self.try_push_back(value).is_err() {
+            panic!("RingBuffer 已满（容量 {}）", N);
+        }
+    }
+
+    pub fn try_push_back(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+        let slot = self.slot(self.len);
+        self.data[slot].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        if self.try_push_front(value).is_err() {
+            panic!("RingBuffer 已满（容量 {}）", N);
+        }
+    }
+
+    pub fn try_push_front(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+        self.head = if self.head == 0 { N - 1 } else { self.head - 1 };
+        self.data[self.head].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    // 写满时丢弃最旧（最前面）的元素并返回它；容量为 0 时直接返回 value
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        if N == 0 {
+            return Some(value);
+        }
+        let evicted = if self.is_full() {
+            self.pop_front()
+        } else {
+            None
+        };
+        let slot = self.slot(self.len);
+        self.data[slot].write(value);
+        self.len += 1;
+        evicted
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = unsafe { self.data[self.head].assume_init_read() };
+        self.head = self.slot(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        Some(unsafe { self.data[slot].assume_init_read() })
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        (index < self.len).then(|| unsafe { self.data[self.slot(index)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.slot(index);
+        Some(unsafe { self.data[slot].assume_init_mut() })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    // 按逻辑顺序分成两段：从 head 到数组末尾，以及绕回开头的部分
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (first, second) = self.split_ranges();
+        let base = self.data.as_ptr().cast::<T>();
+        unsafe {
+            (
+                slice::from_raw_parts(base.add(first.0), first.1),
+                slice::from_raw_parts(base.add(second.0), second.1),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (first, second) = self.split_ranges();
+        let base = self.data.as_mut_ptr().cast::<T>();
+        unsafe {
+            (
+                slice::from_raw_parts_mut(base.add(first.0), first.1),
+                slice::from_raw_parts_mut(base.add(second.0), second.1),
+            )
+        }
+    }
+
+    // 两段的 (起点, 长度)
+    fn split_ranges(&self) -> ((usize, usize), (usize, usize)) {
+        let first_len = self.len.min(N - self.head);
+        ((self.head, first_len), (0, self.len - first_len))
+    }
+
+    // 把元素旋转到数组开头，之后整个缓冲区是一段连续的切片
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        // 旋转的是 MaybeUninit 槽位，未初始化的槽位一起移动不影响正确性
+        self.data.rotate_left(self.head);
+        self.head = 0;
+        self.as_mut_slices().0
+    }
+
+    pub fn clear(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        let (front, back) = (front as *mut [T], back as *mut [T]);
+        // 先清空再析构，析构 panic 时泄漏而不是重复析构
+        self.head = 0;
+        self.len = 0;
+        unsafe {
+            ptr::drop_in_place(front);
+            ptr::drop_in_place(back);
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for RingBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for RingBuffer<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for RingBuffer<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<RingBuffer<T, M>>
+    for RingBuffer<T, N>
+{
+    fn eq(&self, other: &RingBuffer<T, M>) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T]> for RingBuffer<T, N> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.len == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<[T; M]> for RingBuffer<T, N> {
+    fn eq(&self, other: &[T; M]) -> bool {
+        *self == other[..]
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for RingBuffer<T, N> {}
+
+impl<T, const N: usize> Index<usize> for RingBuffer<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index)
+            .unwrap_or_else(|| panic!("下标 {} 超出长度 {}", index, len))
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for RingBuffer<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("下标 {} 超出长度 {}", index, len))
+    }
+}
+
+// 与 Vec 不同，环形缓冲区的 extend 写满后覆盖最旧的元素，结果是最后 N 个元素
+impl<T, const N: usize> Extend<T> for RingBuffer<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_overwrite(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for RingBuffer<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut ring = Self::new();
+        ring.extend(iter);
+        ring
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a RingBuffer<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut RingBuffer<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for RingBuffer<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> IntoIter<T, N> {
+        IntoIter(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+// 按值迭代，剩余元素随内部的 RingBuffer 一起析构
+#[derive(Debug, Clone)]
+pub struct IntoIter<T, const N: usize>(RingBuffer<T, N>);
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wraps_around_both_ends() {
+        let mut ring = RingBuffer::<i32, 4>::new();
+        ring.push_back(1);
+        ring.push_back(2);
+        ring.push_front(0);
+        ring.push_front(-1);
+        assert!(ring.is_full());
+        assert_eq!(ring.try_push_back(3).unwrap_err().element(), 3);
+        assert_eq!(ring, [-1, 0, 1, 2]);
+        // head 此时在数组末尾，元素分成两段
+        assert_eq!(ring.as_slices(), (&[-1, 0][..], &[1, 2][..]));
+
+        assert_eq!((ring.pop_front(), ring.pop_back()), (Some(-1), Some(2)));
+        ring.push_back(5);
+        ring[2] *= 10;
+        assert_eq!(ring.iter().rev().copied().collect::<Vec<_>>(), [50, 1, 0]);
+        assert_eq!((ring.front(), ring.back()), (Some(&0), Some(&50)));
+        assert_eq!(ring.make_contiguous(), [0, 1, 50]);
+        assert_eq!(ring.as_slices().1, []);
+    }
+
+    #[test]
+    fn test_overwrite_keeps_latest() {
+        let mut recent = RingBuffer::<String, 3>::new();
+        for word in ["a", "b", "c"] {
+            assert_eq!(recent.push_overwrite(word.to_string()), None);
+        }
+        assert_eq!(recent.push_overwrite("d".to_string()).as_deref(), Some("a"));
+        assert_eq!(recent, ["b", "c", "d"].map(String::from));
+
+        let last_three: RingBuffer<u32, 3> = (1..=10).collect();
+        assert_eq!(last_three.into_iter().collect::<Vec<_>>(), [8, 9, 10]);
+
+        let mut empty = RingBuffer::<u8, 0>::new();
+        assert_eq!(empty.push_overwrite(1), Some(1));
+        assert!(empty.is_empty());
+    }
+}
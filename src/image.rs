@@ -0,0 +1,990 @@
+// 图像缓冲区与处理内核
+// 第 15 章 practical_examples 中的 #[repr(C)] Image { width, height, data } 只是一块裸内存，这里给它配上安全的类型：
+// 1. ImageBuffer<P: Pixel>：按行存储的像素，像素类型 Luma / Rgb / Rgba 决定每个像素的通道数
+// 2. 处理内核：灰度化、盒式模糊、任意卷积核、缩放和翻转，都是纯 Rust 实现
+// 3. PNM：读写 PGM（灰度）和 PPM（RGB），支持二进制（P5/P6）和文本（P2/P3）两种格式
+// 4. FFI：像素类型的内存布局就是连续的 u8，as_raw_mut / from_raw 与 C 结构体 RawImage 互相转换时不复制像素；
+//    into_raw 只有在 Vec 的容量大于长度时才会先收缩（可能重新分配一次）
+
+use crate::errors::{Diagnostic, ErrorCode};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+// ===========================================
+// 1. 像素类型
+// ===========================================
+
+/// 像素：CHANNELS 个 u8 通道
+///
+/// # Safety
+/// 实现类型必须是 #[repr(transparent)] 或 #[repr(C)]，内存布局恰好是 [u8; CHANNELS]：
+/// 对齐为 1、没有填充，ImageBuffer 依赖这一点把像素切片直接当作字节切片使用
+pub unsafe trait Pixel: Copy + Default + PartialEq + fmt::Debug + 'static {
+    const CHANNELS: usize;
+
+    fn channels(&self) -> &[u8];
+    fn channels_mut(&mut self) -> &mut [u8];
+
+    // 亮度，使用 ITU-R BT.601 的整数权重
+    fn luma(&self) -> u8;
+
+    fn from_channels(channels: &[u8]) -> Self {
+        let mut pixel = Self::default();
+        pixel.channels_mut().copy_from_slice(channels);
+        pixel
+    }
+}
+
+// 灰度像素
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Luma(pub [u8; 1]);
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb(pub [u8; 3]);
+
+// 带透明度的 RGB；各处理内核把 alpha 当作普通通道处理
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgba(pub [u8; 4]);
+
+fn bt601(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32 + 500) / 1000) as u8
+}
+
+unsafe impl Pixel for Luma {
+    const CHANNELS: usize = 1;
+
+    fn channels(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn channels_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+
+    fn luma(&self) -> u8 {
+        self.0[0]
+    }
+}
+
+unsafe impl Pixel for Rgb {
+    const CHANNELS: usize = 3;
+
+    fn channels(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn channels_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+
+    fn luma(&self) -> u8 {
+        let [r, g, b] = self.0;
+        bt601(r, g, b)
+    }
+}
+
+unsafe impl Pixel for Rgba {
+    const CHANNELS: usize = 4;
+
+    fn channels(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn channels_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+
+    fn luma(&self) -> u8 {
+        let [r, g, b, _] = self.0;
+        bt601(r, g, b)
+    }
+}
+
+impl From<Luma> for Rgb {
+    fn from(Luma([l]): Luma) -> Rgb {
+        Rgb([l, l, l])
+    }
+}
+
+impl From<Rgb> for Rgba {
+    fn from(Rgb([r, g, b]): Rgb) -> Rgba {
+        Rgba([r, g, b, 255])
+    }
+}
+
+// 丢弃 alpha 通道，不做预乘
+impl From<Rgba> for Rgb {
+    fn from(Rgba([r, g, b, _]): Rgba) -> Rgb {
+        Rgb([r, g, b])
+    }
+}
+
+// ===========================================
+// 2. 错误
+// ===========================================
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    // PNM 文件头或像素数据不合法
+    Format(String),
+    // 像素数量与宽高不符
+    Dimensions { width: u32, height: u32, len: usize },
+    InvalidKernel(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "读写图像失败: {}", e),
+            ImageError::Format(message) => write!(f, "图像格式错误: {}", message),
+            ImageError::Dimensions { width, height, len } => write!(
+                f,
+                "{}x{} 的图像需要 {} 个像素，实际提供了 {} 个",
+                width,
+                height,
+                *width as u64 * *height as u64,
+                len
+            ),
+            ImageError::InvalidKernel(message) => write!(f, "卷积核无效: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+impl Diagnostic for ImageError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            ImageError::Io(e) => e.code(),
+            ImageError::Format(_) => ErrorCode::PARSE,
+            ImageError::Dimensions { .. } | ImageError::InvalidKernel(_) => {
+                ErrorCode::INVALID_INPUT
+            }
+        }
+    }
+}
+
+// ===========================================
+// 3. ImageBuffer
+// ===========================================
+
+// 按行优先顺序存储的图像，(0, 0) 在左上角
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageBuffer<P: Pixel> {
+    width: u32,
+    height: u32,
+    pixels: Vec<P>,
+}
+
+fn pixel_count(width: u32, height: u32) -> usize {
+    usize::try_from(width as u64 * height as u64).expect("图像尺寸超出地址空间")
+}
+
+impl<P: Pixel> ImageBuffer<P> {
+    // 所有像素为 P::default()（全 0）
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::from_pixel(width, height, P::default())
+    }
+
+    pub fn from_pixel(width: u32, height: u32, pixel: P) -> Self {
+        ImageBuffer {
+            width,
+            height,
+            pixels: vec![pixel; pixel_count(width, height)],
+        }
+    }
+
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(u32, u32) -> P) -> Self {
+        let mut pixels = Vec::with_capacity(pixel_count(width, height));
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y));
+            }
+        }
+        ImageBuffer {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<P>) -> Result<Self, ImageError> {
+        if pixels.len() as u64 != width as u64 * height as u64 {
+            return Err(ImageError::Dimensions {
+                width,
+                height,
+                len: pixels.len(),
+            });
+        }
+        Ok(ImageBuffer {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    // 从交错存储的通道字节构造，例如 RGBRGB...
+    pub fn from_bytes(width: u32, height: u32, bytes: &[u8]) -> Result<Self, ImageError> {
+        let pixels = bytes.chunks_exact(P::CHANNELS).map(P::from_channels);
+        if !bytes.len().is_multiple_of(P::CHANNELS) {
+            return Err(ImageError::Dimensions {
+                width,
+                height,
+                len: bytes.len() / P::CHANNELS,
+            });
+        }
+        Self::from_pixels(width, height, pixels.collect())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<P> {
+        self.index(x, y).map(|i| self.pixels[i])
+    }
+
+    // 越界时 panic，与切片下标一致
+    pub fn pixel(&self, x: u32, y: u32) -> P {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!(
+                "像素 ({}, {}) 超出 {}x{} 的图像",
+                x, y, self.width, self.height
+            )
+        })
+    }
+
+    pub fn put(&mut self, x: u32, y: u32, pixel: P) {
+        let index = self.index(x, y).unwrap_or_else(|| {
+            panic!(
+                "像素 ({}, {}) 超出 {}x{} 的图像",
+                x, y, self.width, self.height
+            )
+        });
+        self.pixels[index] = pixel;
+    }
+
+    pub fn pixels(&self) -> &[P] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [P] {
+        &mut self.pixels
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[P]> {
+        // 宽度为 0 时 chunks_exact 不接受 0，此时图像没有像素，按 1 切分得到空迭代器
+        self.pixels.chunks_exact(self.width.max(1) as usize)
+    }
+
+    // 像素按通道展开后的字节，不复制
+    pub fn as_bytes(&self) -> &[u8] {
+        // Pixel 的安全约定保证 [P] 与 [u8; CHANNELS * len] 布局相同
+        unsafe {
+            std::slice::from_raw_parts(
+                self.pixels.as_ptr() as *const u8,
+                self.pixels.len() * P::CHANNELS,
+            )
+        }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.pixels.as_mut_ptr() as *mut u8,
+                self.pixels.len() * P::CHANNELS,
+            )
+        }
+    }
+
+    // 逐像素转换，例如 image.map(Rgba::from)
+    pub fn map<Q: Pixel>(&self, f: impl FnMut(P) -> Q) -> ImageBuffer<Q> {
+        ImageBuffer {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.iter().copied().map(f).collect(),
+        }
+    }
+
+    // 像素数据的 XxHash64，用于比较图像内容（测试中的 golden 值）
+    pub fn checksum(&self) -> u64 {
+        twox_hash::XxHash64::oneshot(0, self.as_bytes())
+    }
+}
+
+// ===========================================
+// 4. 处理内核
+// ===========================================
+
+// 卷积核：宽高必须是奇数，中心对准目标像素
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    weights: Vec<f32>,
+}
+
+impl Kernel {
+    pub fn new(width: usize, height: usize, weights: Vec<f32>) -> Result<Kernel, ImageError> {
+        if width.is_multiple_of(2) || height.is_multiple_of(2) {
+            return Err(ImageError::InvalidKernel(format!(
+                "尺寸 {}x{} 必须是奇数",
+                width, height
+            )));
+        }
+        if weights.len() != width * height {
+            return Err(ImageError::InvalidKernel(format!(
+                "{}x{} 的卷积核需要 {} 个权重，实际为 {}",
+                width,
+                height,
+                width * height,
+                weights.len()
+            )));
+        }
+        Ok(Kernel {
+            width,
+            height,
+            weights,
+        })
+    }
+
+    fn square(size: usize, weights: Vec<f32>) -> Kernel {
+        Kernel::new(size, size, weights).expect("内置卷积核的尺寸正确")
+    }
+
+    pub fn identity() -> Kernel {
+        Kernel::square(1, vec![1.0])
+    }
+
+    pub fn box_blur(radius: usize) -> Kernel {
+        let size = 2 * radius + 1;
+        let weight = 1.0 / (size * size) as f32;
+        Kernel::square(size, vec![weight; size * size])
+    }
+
+    // 3x3 高斯近似 [1 2 1] ⊗ [1 2 1] / 16
+    pub fn gaussian_3x3() -> Kernel {
+        let weights = [1.0, 2.0, 1.0, 2.0, 4.0, 2.0, 1.0, 2.0, 1.0];
+        Kernel::square(3, weights.iter().map(|w| w / 16.0).collect())
+    }
+
+    pub fn sharpen() -> Kernel {
+        Kernel::square(3, vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0])
+    }
+
+    // 拉普拉斯边缘检测：平坦区域输出 0
+    pub fn edge_detect() -> Kernel {
+        Kernel::square(3, vec![-1.0, -1.0, -1.0, -1.0, 8.0, -1.0, -1.0, -1.0, -1.0])
+    }
+}
+
+// 缩放时的采样方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+// 越界坐标夹到边缘：卷积和模糊在边缘处重复最外圈的像素
+fn clamp_coord(value: i64, len: u32) -> u32 {
+    value.clamp(0, len as i64 - 1) as u32
+}
+
+fn to_channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+impl<P: Pixel> ImageBuffer<P> {
+    pub fn grayscale(&self) -> ImageBuffer<Luma> {
+        self.map(|p| Luma([p.luma()]))
+    }
+
+    // 半径为 radius 的盒式模糊，先水平后垂直两次一维平均，结果与 (2r+1)² 的方形均值核相近，但只需要 O(r) 次加法
+    pub fn box_blur(&self, radius: u32) -> Self {
+        if radius == 0 || self.is_empty() {
+            return self.clone();
+        }
+        self.blur_pass(radius, true).blur_pass(radius, false)
+    }
+
+    fn blur_pass(&self, radius: u32, horizontal: bool) -> Self {
+        let r = radius as i64;
+        let n = 2 * radius + 1;
+        Self::from_fn(self.width, self.height, |x, y| {
+            let mut sums = [0u32; 4];
+            for d in -r..=r {
+                let source = if horizontal {
+                    self.pixel(clamp_coord(x as i64 + d, self.width), y)
+                } else {
+                    self.pixel(x, clamp_coord(y as i64 + d, self.height))
+                };
+                for (sum, &c) in sums.iter_mut().zip(source.channels()) {
+                    *sum += c as u32;
+                }
+            }
+            let mut out = P::default();
+            for (c, sum) in out.channels_mut().iter_mut().zip(sums) {
+                *c = ((sum + n / 2) / n) as u8;
+            }
+            out
+        })
+    }
+
+    // 对每个通道分别卷积，结果四舍五入并截断到 0..=255
+    pub fn convolve(&self, kernel: &Kernel) -> Self {
+        if self.is_empty() {
+            return self.clone();
+        }
+        let (cx, cy) = ((kernel.width / 2) as i64, (kernel.height / 2) as i64);
+        Self::from_fn(self.width, self.height, |x, y| {
+            let mut sums = [0f32; 4];
+            for (i, &weight) in kernel.weights.iter().enumerate() {
+                let kx = (i % kernel.width) as i64 - cx;
+                let ky = (i / kernel.width) as i64 - cy;
+                let source = self.pixel(
+                    clamp_coord(x as i64 + kx, self.width),
+                    clamp_coord(y as i64 + ky, self.height),
+                );
+                for (sum, &c) in sums.iter_mut().zip(source.channels()) {
+                    *sum += weight * c as f32;
+                }
+            }
+            let mut out = P::default();
+            for (c, sum) in out.channels_mut().iter_mut().zip(sums) {
+                *c = to_channel(sum);
+            }
+            out
+        })
+    }
+
+    // 缩放到新尺寸；采样点取目标像素中心对应的源坐标
+    pub fn resize(&self, width: u32, height: u32, filter: Filter) -> Self {
+        if self.is_empty() {
+            return Self::new(width, height);
+        }
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+        match filter {
+            Filter::Nearest => Self::from_fn(width, height, |x, y| {
+                let sx = ((x as f32 + 0.5) * scale_x) as u32;
+                let sy = ((y as f32 + 0.5) * scale_y) as u32;
+                self.pixel(sx.min(self.width - 1), sy.min(self.height - 1))
+            }),
+            Filter::Bilinear => Self::from_fn(width, height, |x, y| {
+                let fx = ((x as f32 + 0.5) * scale_x - 0.5).max(0.0);
+                let fy = ((y as f32 + 0.5) * scale_y - 0.5).max(0.0);
+                let (x0, y0) = (fx as u32, fy as u32);
+                let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+                let corners = [
+                    (self.pixel(x0, y0), (1.0 - tx) * (1.0 - ty)),
+                    (self.pixel(x1, y0), tx * (1.0 - ty)),
+                    (self.pixel(x0, y1), (1.0 - tx) * ty),
+                    (self.pixel(x1, y1), tx * ty),
+                ];
+                let mut out = P::default();
+                for (i, c) in out.channels_mut().iter_mut().enumerate() {
+                    let value: f32 = corners
+                        .iter()
+                        .map(|(p, weight)| p.channels()[i] as f32 * weight)
+                        .sum();
+                    *c = to_channel(value);
+                }
+                out
+            }),
+        }
+    }
+
+    // 左右镜像
+    pub fn flip_horizontal(&mut self) {
+        if self.width > 0 {
+            for row in self.pixels.chunks_exact_mut(self.width as usize) {
+                row.reverse();
+            }
+        }
+    }
+
+    // 上下颠倒
+    pub fn flip_vertical(&mut self) {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        for y in 0..height / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((height - 1 - y) * width);
+            top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+        }
+    }
+}
+
+// ===========================================
+// 5. PNM（PGM / PPM）
+// ===========================================
+
+// 可以存成 PNM 的像素类型
+pub trait PnmPixel: Pixel {
+    // 二进制和文本格式的魔数，例如 PPM 是 P6 / P3
+    const BINARY_MAGIC: &'static str;
+    const ASCII_MAGIC: &'static str;
+}
+
+impl PnmPixel for Luma {
+    const BINARY_MAGIC: &'static str = "P5";
+    const ASCII_MAGIC: &'static str = "P2";
+}
+
+impl PnmPixel for Rgb {
+    const BINARY_MAGIC: &'static str = "P6";
+    const ASCII_MAGIC: &'static str = "P3";
+}
+
+// PNM 文件头的词法：以空白分隔，# 到行尾是注释
+struct PnmReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PnmReader<'a> {
+    fn skip_space(&mut self) {
+        while let Some(&b) = self.data.get(self.pos) {
+            if b == b'#' {
+                while self.data.get(self.pos).is_some_and(|&b| b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Option<&'a str> {
+        self.skip_space();
+        let start = self.pos;
+        while self
+            .data
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .filter(|t| !t.is_empty())
+    }
+
+    fn number(&mut self, what: &str) -> Result<u32, ImageError> {
+        let token = self
+            .token()
+            .ok_or_else(|| ImageError::Format(format!("缺少{}", what)))?;
+        token
+            .parse()
+            .map_err(|_| ImageError::Format(format!("{}不是有效的数字: {}", what, token)))
+    }
+}
+
+impl<P: PnmPixel> ImageBuffer<P> {
+    // 写出二进制格式（P5 / P6）
+    pub fn write_pnm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(
+            writer,
+            "{}\n{} {}\n255\n",
+            P::BINARY_MAGIC,
+            self.width,
+            self.height
+        )?;
+        writer.write_all(self.as_bytes())?;
+        writer.flush()
+    }
+
+    // 文本格式（P2 / P3），每行一个像素；比二进制大几倍，但可以逐行比较差异
+    pub fn to_pnm_ascii(&self) -> String {
+        let mut text = format!("{}\n{} {}\n255\n", P::ASCII_MAGIC, self.width, self.height);
+        for pixel in self.as_bytes().chunks(P::CHANNELS) {
+            let samples: Vec<String> = pixel.iter().map(u8::to_string).collect();
+            text.push_str(&samples.join(" "));
+            text.push('\n');
+        }
+        text
+    }
+
+    // 读取二进制或文本格式；最大值不是 255 时按比例换算到 0..=255
+    pub fn read_pnm<R: Read>(mut reader: R) -> Result<Self, ImageError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut input = PnmReader {
+            data: &data,
+            pos: 0,
+        };
+
+        let magic = input.token().unwrap_or_default();
+        let binary = if magic == P::BINARY_MAGIC {
+            true
+        } else if magic == P::ASCII_MAGIC {
+            false
+        } else {
+            return Err(ImageError::Format(format!(
+                "需要 {} 或 {} 格式，实际文件头为 '{}'",
+                P::BINARY_MAGIC,
+                P::ASCII_MAGIC,
+                magic
+            )));
+        };
+        let width = input.number("宽度")?;
+        let height = input.number("高度")?;
+        let max = input.number("最大值")?;
+        if !(1..=255).contains(&max) {
+            return Err(ImageError::Format(format!(
+                "只支持 1..=255 的最大值，实际为 {}",
+                max
+            )));
+        }
+        // 文件头中的尺寸不可信，计算样本数时检查溢出
+        let expected = (width as u64)
+            .checked_mul(height as u64)
+            .and_then(|n| n.checked_mul(P::CHANNELS as u64))
+            .and_then(|n| usize::try_from(n).ok())
+            .ok_or_else(|| ImageError::Format(format!("图像尺寸 {}x{} 过大", width, height)))?;
+
+        let mut samples: Vec<u32> = Vec::new();
+        if binary {
+            // 最大值后面恰好一个空白字符，然后是像素数据；先确认数据足够再分配
+            let start = input.pos + 1;
+            let available = data.len().saturating_sub(start);
+            if expected > available {
+                return Err(ImageError::Format(format!(
+                    "像素数据不完整：需要 {} 字节，实际 {} 字节",
+                    expected, available
+                )));
+            }
+            samples.extend(data[start..start + expected].iter().map(|&b| b as u32));
+        } else {
+            // 不按文件头预留空间，样本数随实际读到的内容增长
+            for _ in 0..expected {
+                samples.push(input.number("像素值")?);
+            }
+            if let Some(extra) = input.token() {
+                return Err(ImageError::Format(format!(
+                    "像素数据之后有多余内容: {}",
+                    extra
+                )));
+            }
+        }
+        if let Some(&value) = samples.iter().find(|&&v| v > max) {
+            return Err(ImageError::Format(format!(
+                "像素值 {} 超过最大值 {}",
+                value, max
+            )));
+        }
+
+        let bytes: Vec<u8> = samples
+            .into_iter()
+            .map(|v| ((v * 255 + max / 2) / max) as u8)
+            .collect();
+        Self::from_bytes(width, height, &bytes)
+    }
+
+    pub fn save_pnm(&self, path: impl AsRef<Path>) -> Result<(), ImageError> {
+        let file = std::fs::File::create(path)?;
+        self.write_pnm(io::BufWriter::new(file))?;
+        Ok(())
+    }
+
+    pub fn open_pnm(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Self::read_pnm(std::fs::File::open(path)?)
+    }
+}
+
+// ===========================================
+// 6. 与 C 结构体互相转换
+// ===========================================
+
+// 与第 15 章 Image 相同的 C 布局：宽、高和指向交错通道数据的指针
+// 结构体本身不记录通道数，双方需要事先约定像素格式
+#[repr(C)]
+#[derive(Debug)]
+pub struct RawImage {
+    pub width: i32,
+    pub height: i32,
+    pub data: *mut u8,
+}
+
+// 借用 ImageBuffer 得到的 RawImage，生命周期保证传给 C 期间缓冲区不会被释放或移动
+#[derive(Debug)]
+pub struct RawImageMut<'a> {
+    raw: RawImage,
+    _buffer: PhantomData<&'a mut [u8]>,
+}
+
+impl RawImageMut<'_> {
+    pub fn as_mut_ptr(&mut self) -> *mut RawImage {
+        &mut self.raw
+    }
+}
+
+impl<P: Pixel> ImageBuffer<P> {
+    fn ffi_dimensions(&self) -> (i32, i32) {
+        match (i32::try_from(self.width), i32::try_from(self.height)) {
+            (Ok(w), Ok(h)) => (w, h),
+            _ => panic!("{}x{} 超出 C 接口的 int 范围", self.width, self.height),
+        }
+    }
+
+    // 把像素内存的所有权交给 C；之后必须用 from_raw 收回，否则内存泄漏
+    // 宽或高超过 i32::MAX 时 panic
+    pub fn into_raw(self) -> RawImage {
+        let (width, height) = self.ffi_dimensions();
+        // RawImage 不记录容量，所以先转成 Box<[P]>：from_pixel / from_fn 构造的 Vec 容量等于长度，这一步不分配；
+        // from_pixels 传入的 Vec 可能有多余容量，此时 into_boxed_slice 会收缩，可能复制一次像素
+        let pixels = Box::into_raw(self.pixels.into_boxed_slice());
+        RawImage {
+            width,
+            height,
+            data: pixels as *mut P as *mut u8,
+        }
+    }
+
+    /// 收回 into_raw 交出的内存
+    ///
+    /// # Safety
+    /// raw 必须来自同一像素类型 P 的 into_raw，且只能收回一次；期间 C 代码可以修改像素，但不能改变宽高
+    pub unsafe fn from_raw(raw: RawImage) -> Self {
+        assert!(raw.width >= 0 && raw.height >= 0, "宽高不能为负");
+        let (width, height) = (raw.width as u32, raw.height as u32);
+        let len = pixel_count(width, height);
+        let slice = std::ptr::slice_from_raw_parts_mut(raw.data as *mut P, len);
+        ImageBuffer {
+            width,
+            height,
+            pixels: unsafe { Box::from_raw(slice) }.into_vec(),
+        }
+    }
+
+    // 临时借给 C 函数读写，所有权仍在 Rust 这边
+    pub fn as_raw_mut(&mut self) -> RawImageMut<'_> {
+        let (width, height) = self.ffi_dimensions();
+        RawImageMut {
+            raw: RawImage {
+                width,
+                height,
+                data: self.pixels.as_mut_ptr() as *mut u8,
+            },
+            _buffer: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 生成测试图像：水平红色渐变、垂直绿色渐变、对角线蓝色条纹
+    fn gradient(width: u32, height: u32) -> ImageBuffer<Rgb> {
+        ImageBuffer::from_fn(width, height, |x, y| {
+            Rgb([
+                (x * 255 / (width - 1)) as u8,
+                (y * 255 / (height - 1)) as u8,
+                if (x + y) % 8 < 4 { 200 } else { 30 },
+            ])
+        })
+    }
+
+    fn checker(size: u32, cell: u32) -> ImageBuffer<Luma> {
+        ImageBuffer::from_fn(size, size, |x, y| {
+            Luma([if (x / cell + y / cell).is_multiple_of(2) {
+                255
+            } else {
+                0
+            }])
+        })
+    }
+
+    #[test]
+    fn test_kernel_checksums() {
+        let image = gradient(64, 48);
+        assert_eq!(image.checksum(), 0x9753_8ea3_7574_bae1);
+        assert_eq!(image.grayscale().checksum(), 0x87a2_32f9_a1fd_3757);
+        assert_eq!(image.box_blur(2).checksum(), 0x9fce_160d_4cd9_9bde);
+        assert_eq!(
+            image.convolve(&Kernel::sharpen()).checksum(),
+            0xcee4_2e11_130e_6507
+        );
+        assert_eq!(
+            image.convolve(&Kernel::gaussian_3x3()).checksum(),
+            0x5290_2aee_e832_2cb7
+        );
+        assert_eq!(
+            image.resize(32, 24, Filter::Bilinear).checksum(),
+            0x8962_9f5e_5730_26d2
+        );
+        assert_eq!(
+            image.resize(100, 75, Filter::Nearest).checksum(),
+            0xe5c2_b4e4_0a6d_ec06
+        );
+
+        let board = checker(32, 4);
+        assert_eq!(
+            board.convolve(&Kernel::edge_detect()).checksum(),
+            0x6012_c016_7261_be38
+        );
+        assert_eq!(board.box_blur(1).checksum(), 0xcb69_33b0_ea23_2d14);
+    }
+
+    #[test]
+    fn test_kernels_preserve_invariants() {
+        let image = gradient(17, 9);
+
+        // 恒等核、半径 0 的模糊、同尺寸双线性缩放都不改变图像
+        assert_eq!(image.convolve(&Kernel::identity()), image);
+        assert_eq!(image.box_blur(0), image);
+        assert_eq!(image.resize(17, 9, Filter::Bilinear), image);
+
+        // 均匀图像模糊后不变，边缘检测全为 0
+        let flat = ImageBuffer::from_pixel(8, 8, Rgb([10, 120, 240]));
+        assert_eq!(flat.box_blur(3), flat);
+        assert_eq!(flat.convolve(&Kernel::gaussian_3x3()), flat);
+        assert!(
+            flat.convolve(&Kernel::edge_detect())
+                .as_bytes()
+                .iter()
+                .all(|&b| b == 0)
+        );
+
+        // 翻转两次还原；奇数高度时中间一行不动
+        let mut flipped = image.clone();
+        flipped.flip_horizontal();
+        assert_eq!(flipped.pixel(0, 3), image.pixel(16, 3));
+        flipped.flip_vertical();
+        assert_eq!(flipped.pixel(0, 0), image.pixel(16, 8));
+        assert_eq!(flipped.pixel(5, 4), image.pixel(11, 4));
+        flipped.flip_vertical();
+        flipped.flip_horizontal();
+        assert_eq!(flipped, image);
+
+        // 最近邻放大 2 倍：每个源像素变成 2x2 的块
+        let big = image.resize(34, 18, Filter::Nearest);
+        assert_eq!(big.pixel(7, 5), image.pixel(3, 2));
+        assert_eq!(big.pixel(6, 4), image.pixel(3, 2));
+
+        // 灰色像素灰度化后亮度不变；转换为 RGBA 时 alpha 为不透明
+        assert_eq!(Rgb([90, 90, 90]).luma(), 90);
+        assert_eq!(image.grayscale().dimensions(), (17, 9));
+        assert_eq!(image.map(Rgba::from).pixel(0, 0).0[3], 255);
+
+        assert!(matches!(
+            Kernel::new(2, 3, vec![0.0; 6]),
+            Err(ImageError::InvalidKernel(_))
+        ));
+        let err = ImageBuffer::<Rgb>::from_bytes(2, 2, &[0; 9]).unwrap_err();
+        assert!(matches!(err, ImageError::Dimensions { len: 3, .. }));
+        assert_eq!(err.code(), ErrorCode::INVALID_INPUT);
+    }
+
+    #[test]
+    fn test_pnm_round_trip_and_ascii() {
+        let image = gradient(5, 3);
+        let mut ppm = Vec::new();
+        image.write_pnm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n5 3\n255\n"));
+        assert_eq!(ppm.len(), 11 + 5 * 3 * 3);
+        assert_eq!(ImageBuffer::<Rgb>::read_pnm(&ppm[..]).unwrap(), image);
+
+        let gray = checker(4, 2);
+        let mut pgm = Vec::new();
+        gray.write_pnm(&mut pgm).unwrap();
+        assert_eq!(ImageBuffer::<Luma>::read_pnm(&pgm[..]).unwrap(), gray);
+
+        // 文本格式，带注释，最大值 15 换算到 0..=255
+        let text = "P2\n# 注释\n3 2 # 宽 高\n15\n0 15 7\n15 0 8\n";
+        let parsed = ImageBuffer::<Luma>::read_pnm(text.as_bytes()).unwrap();
+        assert_eq!(parsed.as_bytes(), &[0, 255, 119, 255, 0, 136]);
+        assert_eq!(
+            parsed.to_pnm_ascii(),
+            "P2\n3 2\n255\n0\n255\n119\n255\n0\n136\n"
+        );
+        let ascii = image.to_pnm_ascii();
+        assert_eq!(
+            ImageBuffer::<Rgb>::read_pnm(ascii.as_bytes()).unwrap(),
+            image
+        );
+
+        let err = ImageBuffer::<Rgb>::read_pnm(&pgm[..]).unwrap_err();
+        assert!(matches!(&err, ImageError::Format(m) if m.contains("P6")));
+        assert_eq!(err.code(), ErrorCode::PARSE);
+        assert!(ImageBuffer::<Rgb>::read_pnm(&ppm[..ppm.len() - 1]).is_err());
+        assert!(ImageBuffer::<Luma>::read_pnm("P2 1 1 3 4".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_pnm_rejects_oversized_headers() {
+        // 样本数溢出 usize：报格式错误而不是 panic
+        let err =
+            ImageBuffer::<Rgb>::read_pnm("P6 4294967295 4294967295 255\n".as_bytes()).unwrap_err();
+        assert!(matches!(&err, ImageError::Format(m) if m.contains("过大")));
+
+        // 文件头声称 400 亿个样本，但实际数据很少：不会按文件头预先分配
+        let err =
+            ImageBuffer::<Luma>::read_pnm("P2 200000 200000 255\n1 2 3".as_bytes()).unwrap_err();
+        assert!(matches!(&err, ImageError::Format(m) if m.contains("像素值")));
+        let err =
+            ImageBuffer::<Luma>::read_pnm("P5 200000 200000 255\n\x01".as_bytes()).unwrap_err();
+        assert!(matches!(&err, ImageError::Format(m) if m.contains("不完整")));
+    }
+
+    #[test]
+    fn test_raw_image_round_trip_without_copy() {
+        let image = gradient(6, 4);
+        let expected = image.checksum();
+        let data = image.pixels().as_ptr() as *const u8;
+
+        let raw = image.into_raw();
+        assert_eq!((raw.width, raw.height), (6, 4));
+        assert_eq!(raw.data as *const u8, data);
+        // 模拟 C 代码直接修改像素：把 (1, 0) 的红色通道改为 0
+        unsafe { *raw.data.add(3) = 0 };
+        let mut back = unsafe { ImageBuffer::<Rgb>::from_raw(raw) };
+        assert_eq!(back.pixels().as_ptr() as *const u8, data);
+        assert_eq!(back.pixel(1, 0).0[0], 0);
+        assert_ne!(back.checksum(), expected);
+
+        // 借用形式：C 函数通过 *mut RawImage 访问，所有权留在 Rust
+        let mut borrowed = back.as_raw_mut();
+        let ptr = borrowed.as_mut_ptr();
+        unsafe { *(*ptr).data.add(3) = gradient(6, 4).pixel(1, 0).0[0] };
+        assert_eq!(back.checksum(), expected);
+    }
+}
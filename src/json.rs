@@ -0,0 +1,1029 @@
+// JSON 值、解析器与序列化器
+// 不依赖 serde_json 的最小完整实现，供各章节示例使用：
+// 1. JsonValue：null / 布尔 / 数字 / 字符串 / 数组 / 对象
+// 2. parse：遵循 RFC 8259 的严格解析器，错误带字节偏移和行列号
+// 3. 序列化：Display 输出紧凑格式，{:#} 输出两空格缩进的格式化文本
+// 4. JSON Pointer（RFC 6901）：按 "/a/0/b" 这样的路径取值
+// 5. json! 宏：递归构建嵌套的带类型值
+//
+// 数字统一用 f64 存储，与 JavaScript 一致；超出 ±2^53 的整数会丢失精度
+
+use crate::diagnostics::{Label, SourceDiagnostic};
+use crate::errors::{Diagnostic, ErrorCode};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Index;
+use std::str::FromStr;
+
+// 对象按键排序存储，序列化结果稳定，便于比较和测试
+pub type JsonMap = BTreeMap<String, JsonValue>;
+
+// 嵌套深度上限，防止恶意输入（如十万层 [[[[...）耗尽调用栈
+pub const MAX_DEPTH: usize = 128;
+
+// ===========================================
+// 1. JsonValue
+// ===========================================
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum JsonValue {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(JsonMap),
+}
+
+// 索引不存在时返回的共享 Null
+static NULL: JsonValue = JsonValue::Null;
+
+impl JsonValue {
+    // 类型名称，用于错误信息
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    // 只有没有小数部分且在 i64 范围内的数字才能转换
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 9.2e18 => Some(*n as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&JsonMap> {
+        match self {
+            JsonValue::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut JsonMap> {
+        match self {
+            JsonValue::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    // 对象字段查找；不是对象或字段不存在时返回 None
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?.get(key)
+    }
+
+    // ===========================================
+    // JSON Pointer（RFC 6901）
+    // ===========================================
+
+    // "" 指向整个文档，"/a/0" 指向字段 a 的第 0 个元素
+    // 令牌中的 "~1" 表示 '/'，"~0" 表示 '~'
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_token)
+            .try_fold(self, |value, token| match value {
+                JsonValue::Object(map) => map.get(token.as_ref()),
+                JsonValue::Array(items) => items.get(parse_index(&token)?),
+                _ => None,
+            })
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(unescape_token)
+            .try_fold(self, |value, token| match value {
+                JsonValue::Object(map) => map.get_mut(token.as_ref()),
+                JsonValue::Array(items) => items.get_mut(parse_index(&token)?),
+                _ => None,
+            })
+    }
+
+    // ===========================================
+    // 序列化
+    // ===========================================
+
+    // 紧凑格式，等价于 to_string()
+    pub fn to_compact_string(&self) -> String {
+        self.to_string()
+    }
+
+    // 两空格缩进的格式化文本，等价于 format!("{:#}", value)
+    pub fn to_pretty_string(&self) -> String {
+        format!("{:#}", self)
+    }
+}
+
+fn unescape_token(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        // 先替换 ~1 再替换 ~0，"~01" 才会正确地变成 "~1"
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+// 数组下标：十进制数字且没有前导零；"-"（末尾之后）在只读查找中不指向任何元素
+fn parse_index(token: &str) -> Option<usize> {
+    let valid = token == "0"
+        || (!token.is_empty()
+            && !token.starts_with('0')
+            && token.bytes().all(|b| b.is_ascii_digit()));
+    if valid { token.parse().ok() } else { None }
+}
+
+// value["key"] 和 value[0]：缺失时返回 Null 而不是 panic，便于链式访问
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        self.as_array()
+            .and_then(|items| items.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+// ===========================================
+// 2. 类型转换
+// ===========================================
+
+macro_rules! impl_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for JsonValue {
+                fn from(n: $t) -> Self {
+                    JsonValue::Number(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Bool(b)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::String(s.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::String(s)
+    }
+}
+
+impl From<&String> for JsonValue {
+    fn from(s: &String) -> Self {
+        JsonValue::String(s.clone())
+    }
+}
+
+impl From<()> for JsonValue {
+    fn from(_: ()) -> Self {
+        JsonValue::Null
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(items: Vec<T>) -> Self {
+        JsonValue::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<JsonMap> for JsonValue {
+    fn from(map: JsonMap) -> Self {
+        JsonValue::Object(map)
+    }
+}
+
+impl<T: Into<JsonValue>> FromIterator<T> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        JsonValue::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+// ===========================================
+// 3. 序列化实现
+// ===========================================
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = if f.alternate() { Some(2) } else { None };
+        write_value(f, self, indent, 0)
+    }
+}
+
+fn write_value<W: fmt::Write>(
+    out: &mut W,
+    value: &JsonValue,
+    indent: Option<usize>,
+    level: usize,
+) -> fmt::Result {
+    match value {
+        JsonValue::Null => out.write_str("null"),
+        JsonValue::Bool(b) => write!(out, "{}", b),
+        JsonValue::Number(n) => write_number(out, *n),
+        JsonValue::String(s) => write_string(out, s),
+        JsonValue::Array(items) if items.is_empty() => out.write_str("[]"),
+        JsonValue::Array(items) => {
+            out.write_char('[')?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+                write_newline(out, indent, level + 1)?;
+                write_value(out, item, indent, level + 1)?;
+            }
+            write_newline(out, indent, level)?;
+            out.write_char(']')
+        }
+        JsonValue::Object(map) if map.is_empty() => out.write_str("{}"),
+        JsonValue::Object(map) => {
+            out.write_char('{')?;
+            for (i, (key, item)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+                write_newline(out, indent, level + 1)?;
+                write_string(out, key)?;
+                out.write_str(if indent.is_some() { ": " } else { ":" })?;
+                write_value(out, item, indent, level + 1)?;
+            }
+            write_newline(out, indent, level)?;
+            out.write_char('}')
+        }
+    }
+}
+
+fn write_newline<W: fmt::Write>(out: &mut W, indent: Option<usize>, level: usize) -> fmt::Result {
+    match indent {
+        Some(width) => write!(out, "\n{:1$}", "", width * level),
+        None => Ok(()),
+    }
+}
+
+// 整数按整数输出（1 而不是 1.0），很大或很小的数用指数形式
+// NaN 和无穷大在 JSON 中没有表示，与 JavaScript 的 JSON.stringify 一样输出 null
+fn write_number<W: fmt::Write>(out: &mut W, n: f64) -> fmt::Result {
+    if !n.is_finite() {
+        out.write_str("null")
+    } else if n.fract() == 0.0 && n.abs() < 1e16 {
+        write!(out, "{}", n as i64)
+    } else if n != 0.0 && (n.abs() >= 1e16 || n.abs() < 1e-6) {
+        write!(out, "{:e}", n)
+    } else {
+        write!(out, "{}", n)
+    }
+}
+
+fn write_string<W: fmt::Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            '\u{08}' => out.write_str("\\b")?,
+            '\u{0C}' => out.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+// ===========================================
+// 4. 解析错误
+// ===========================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonErrorKind {
+    UnexpectedEnd,
+    UnexpectedChar(char),
+    InvalidNumber,
+    NumberOutOfRange,
+    InvalidEscape,
+    InvalidUnicode,
+    ControlCharacter,
+    TrailingCharacters,
+    DepthLimitExceeded,
+}
+
+impl fmt::Display for JsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonErrorKind::UnexpectedEnd => write!(f, "输入意外结束"),
+            JsonErrorKind::UnexpectedChar(c) => write!(f, "意外的字符 {:?}", c),
+            JsonErrorKind::InvalidNumber => write!(f, "无效的数字"),
+            JsonErrorKind::NumberOutOfRange => write!(f, "数字超出范围"),
+            JsonErrorKind::InvalidEscape => write!(f, "无效的转义序列"),
+            JsonErrorKind::InvalidUnicode => write!(f, "无效的 Unicode 转义"),
+            JsonErrorKind::ControlCharacter => write!(f, "字符串中不能包含未转义的控制字符"),
+            JsonErrorKind::TrailingCharacters => write!(f, "JSON 值之后还有多余的字符"),
+            JsonErrorKind::DepthLimitExceeded => write!(f, "嵌套层数超过 {}", MAX_DEPTH),
+        }
+    }
+}
+
+// 解析错误：记录出错的字节偏移，以及换算好的行号和列号（从 1 开始）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    kind: JsonErrorKind,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl JsonError {
+    fn new(kind: JsonErrorKind, text: &str, offset: usize) -> Self {
+        let (line, column) = crate::diagnostics::line_col(text, offset);
+        JsonError {
+            kind,
+            offset,
+            line,
+            column,
+        }
+    }
+
+    pub fn kind(&self) -> &JsonErrorKind {
+        &self.kind
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    // 转换为带源码片段的诊断，source 必须是解析时的原始文本
+    pub fn to_diagnostic(&self, file_name: &str, source: &str) -> SourceDiagnostic {
+        let end = source[self.offset..]
+            .chars()
+            .next()
+            .map_or(self.offset, |c| self.offset + c.len_utf8());
+        SourceDiagnostic::error("无效的 JSON")
+            .with_code(self.code())
+            .with_source(file_name, source)
+            .with_label(Label::primary(self.offset..end, self.kind.to_string()))
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "第 {} 行第 {} 列: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl Diagnostic for JsonError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::PARSE_JSON
+    }
+}
+
+// ===========================================
+// 5. 解析器
+// ===========================================
+
+// 解析一个完整的 JSON 文本；值前后只允许空白
+pub fn parse(text: &str) -> Result<JsonValue, JsonError> {
+    let mut parser = Parser {
+        text,
+        bytes: text.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return Err(parser.error(JsonErrorKind::TrailingCharacters));
+    }
+    Ok(value)
+}
+
+impl FromStr for JsonValue {
+    type Err = JsonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+// 递归下降解析器，按字节扫描；输入是 &str，UTF-8 合法性已由类型保证
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, kind: JsonErrorKind) -> JsonError {
+        JsonError::new(kind, self.text, self.pos)
+    }
+
+    // 当前位置的"意外"错误：到达末尾时报告输入结束，否则报告当前字符
+    fn unexpected(&self) -> JsonError {
+        match self.text[self.pos..].chars().next() {
+            Some(c) => self.error(JsonErrorKind::UnexpectedChar(c)),
+            None => self.error(JsonErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    // JSON 只有四种空白：空格、制表符、换行、回车
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.parse_literal("null", JsonValue::Null),
+            Some(b't') => self.parse_literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.parse_literal("false", JsonValue::Bool(false)),
+            Some(b'"') => self.parse_string().map(JsonValue::String),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, JsonError> {
+        for expected in literal.bytes() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    // number = [ "-" ] ( "0" / 非零数字 *数字 ) [ "." 1*数字 ] [ ( "e" / "E" ) [ "+" / "-" ] 1*数字 ]
+    fn parse_number(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => self.skip_digits(),
+            _ => return Err(self.number_error()),
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            self.require_digits()?;
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            self.require_digits()?;
+        }
+
+        let n: f64 = self.text[start..self.pos]
+            .parse()
+            .map_err(|_| JsonError::new(JsonErrorKind::InvalidNumber, self.text, start))?;
+        if n.is_infinite() {
+            return Err(JsonError::new(
+                JsonErrorKind::NumberOutOfRange,
+                self.text,
+                start,
+            ));
+        }
+        Ok(JsonValue::Number(n))
+    }
+
+    fn skip_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn require_digits(&mut self) -> Result<(), JsonError> {
+        match self.peek() {
+            Some(b'0'..=b'9') => {
+                self.skip_digits();
+                Ok(())
+            }
+            _ => Err(self.number_error()),
+        }
+    }
+
+    fn number_error(&self) -> JsonError {
+        match self.peek() {
+            None => self.error(JsonErrorKind::UnexpectedEnd),
+            Some(_) => self.error(JsonErrorKind::InvalidNumber),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            // 普通字符成段复制；段的边界都是 ASCII 字节，切片一定落在字符边界上
+            let run_start = self.pos;
+            while let Some(b) = self.peek() {
+                if b == b'"' || b == b'\\' || b < 0x20 {
+                    break;
+                }
+                self.pos += 1;
+            }
+            out.push_str(&self.text[run_start..self.pos]);
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    self.parse_escape(&mut out)?;
+                }
+                Some(_) => return Err(self.error(JsonErrorKind::ControlCharacter)),
+                None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, out: &mut String) -> Result<(), JsonError> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{08}',
+            Some(b'f') => '\u{0C}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.pos += 1;
+                return self.parse_unicode_escape(out);
+            }
+            Some(_) => return Err(self.error(JsonErrorKind::InvalidEscape)),
+            None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+        };
+        self.pos += 1;
+        out.push(c);
+        Ok(())
+    }
+
+    // \uXXXX；基本平面之外的字符写作 UTF-16 代理对 \uD83D\uDE00
+    // 不成对的代理项不是合法的 Unicode 标量值，直接报错
+    fn parse_unicode_escape(&mut self, out: &mut String) -> Result<(), JsonError> {
+        let escape_start = self.pos - 2;
+        let unit = self.parse_hex4()?;
+        let code = match unit {
+            0xD800..=0xDBFF => {
+                if !self.bytes[self.pos..].starts_with(b"\\u") {
+                    return Err(JsonError::new(
+                        JsonErrorKind::InvalidUnicode,
+                        self.text,
+                        escape_start,
+                    ));
+                }
+                self.pos += 2;
+                let low = self.parse_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(JsonError::new(
+                        JsonErrorKind::InvalidUnicode,
+                        self.text,
+                        escape_start,
+                    ));
+                }
+                0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => {
+                return Err(JsonError::new(
+                    JsonErrorKind::InvalidUnicode,
+                    self.text,
+                    escape_start,
+                ));
+            }
+            unit => unit,
+        };
+        // 排除了代理项之后，码点一定是合法的 char
+        out.extend(char::from_u32(code));
+        Ok(())
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, JsonError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = match self.peek() {
+                Some(b) => (b as char)
+                    .to_digit(16)
+                    .ok_or_else(|| self.error(JsonErrorKind::InvalidUnicode))?,
+                None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+            };
+            value = value * 16 + digit;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn enter(&mut self) -> Result<(), JsonError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(JsonErrorKind::DepthLimitExceeded));
+        }
+        Ok(())
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, JsonError> {
+        self.enter()?;
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+        } else {
+            loop {
+                items.push(self.parse_value()?);
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b']') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(self.unexpected()),
+                }
+            }
+        }
+        self.depth -= 1;
+        Ok(JsonValue::Array(items))
+    }
+
+    // 重复的键以最后一次出现为准
+    fn parse_object(&mut self) -> Result<JsonValue, JsonError> {
+        self.enter()?;
+        self.expect(b'{')?;
+        let mut map = JsonMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+        } else {
+            loop {
+                self.skip_whitespace();
+                if self.peek() != Some(b'"') {
+                    return Err(self.unexpected());
+                }
+                let key = self.parse_string()?;
+                self.skip_whitespace();
+                self.expect(b':')?;
+                let value = self.parse_value()?;
+                map.insert(key, value);
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b'}') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(self.unexpected()),
+                }
+            }
+        }
+        self.depth -= 1;
+        Ok(JsonValue::Object(map))
+    }
+}
+
+// ===========================================
+// 6. json! 宏
+// ===========================================
+
+// 用接近 JSON 的语法构建 JsonValue，对象和数组可以任意嵌套：
+//
+// let user = json!({
+//     "name": name,
+//     "tags": ["admin", "dev"],
+//     "address": { "city": "Beijing", "zip": null }
+// });
+//
+// 叶子值是任意实现了 Into<JsonValue> 的表达式；对象的键是字符串字面量或括号包裹的表达式
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::json::JsonValue::Null
+    };
+    ([ $($tt:tt)* ]) => {
+        $crate::json::JsonValue::Array($crate::json_internal!(@array [] $($tt)*))
+    };
+    ({ $($tt:tt)* }) => {
+        $crate::json::JsonValue::Object({
+            #[allow(unused_mut)]
+            let mut object = $crate::json::JsonMap::new();
+            $crate::json_internal!(@object object $($tt)*);
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::json::JsonValue::from($other)
+    };
+}
+
+// json! 的逐项展开（tt muncher），每次吃掉一个元素或一个键值对
+// null、数组和对象按 token 树递归交给 json!，其余按表达式处理
+#[doc(hidden)]
+#[macro_export]
+macro_rules! json_internal {
+    // 数组：已展开的元素累积在方括号里
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!(null),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!([$($array)*]),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!({$($object)*}),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json!($last),])
+    };
+
+    // 对象：直接插入到外层宏创建的 map 中
+    (@object $object:ident) => {};
+    (@object $object:ident $key:tt : null $(, $($rest:tt)*)?) => {
+        $object.insert(::std::string::String::from($key), $crate::json!(null));
+        $crate::json_internal!(@object $object $($($rest)*)?);
+    };
+    (@object $object:ident $key:tt : [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $object.insert(::std::string::String::from($key), $crate::json!([$($array)*]));
+        $crate::json_internal!(@object $object $($($rest)*)?);
+    };
+    (@object $object:ident $key:tt : {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $object.insert(::std::string::String::from($key), $crate::json!({$($inner)*}));
+        $crate::json_internal!(@object $object $($($rest)*)?);
+    };
+    (@object $object:ident $key:tt : $value:expr, $($rest:tt)*) => {
+        $object.insert(::std::string::String::from($key), $crate::json!($value));
+        $crate::json_internal!(@object $object $($rest)*);
+    };
+    (@object $object:ident $key:tt : $value:expr) => {
+        $object.insert(::std::string::String::from($key), $crate::json!($value));
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(parse("null"), Ok(JsonValue::Null));
+        assert_eq!(parse(" true "), Ok(JsonValue::Bool(true)));
+        assert_eq!(parse("-12.5e1"), Ok(JsonValue::Number(-125.0)));
+        assert_eq!(
+            parse("\"a\\n\\u00e9\""),
+            Ok(JsonValue::String("a\né".into()))
+        );
+
+        let value = parse(r#"{"a": [1, {"b": null}], "c": "d"}"#).unwrap();
+        assert_eq!(value["a"][0].as_i64(), Some(1));
+        assert!(value["a"][1]["b"].is_null());
+        assert_eq!(value["c"].as_str(), Some("d"));
+        assert!(value["missing"][3].is_null());
+
+        // 代理对组成基本平面之外的字符
+        assert_eq!(parse(r#""\ud83d\ude00""#).unwrap().as_str(), Some("😀"));
+        // 重复键以最后一次为准
+        assert_eq!(parse(r#"{"k":1,"k":2}"#).unwrap()["k"].as_i64(), Some(2));
+    }
+
+    #[test]
+    fn test_parse_errors_report_position() {
+        let err = parse("{\n  \"a\": 1,\n  \"b\": tru\n}").unwrap_err();
+        assert_eq!(err.kind(), &JsonErrorKind::UnexpectedChar('\n'));
+        assert_eq!((err.line(), err.column()), (3, 11));
+
+        let err = parse("[1, 2,]").unwrap_err();
+        assert_eq!(err.kind(), &JsonErrorKind::UnexpectedChar(']'));
+        assert_eq!(err.offset(), 6);
+
+        let cases = [
+            ("", JsonErrorKind::UnexpectedEnd),
+            ("01", JsonErrorKind::TrailingCharacters),
+            ("1.", JsonErrorKind::UnexpectedEnd),
+            ("-x", JsonErrorKind::InvalidNumber),
+            ("1e999", JsonErrorKind::NumberOutOfRange),
+            ("\"\\x\"", JsonErrorKind::InvalidEscape),
+            ("\"\\ud800\"", JsonErrorKind::InvalidUnicode),
+            ("\"a\tb\"", JsonErrorKind::ControlCharacter),
+            ("{'a': 1}", JsonErrorKind::UnexpectedChar('\'')),
+            ("[] []", JsonErrorKind::TrailingCharacters),
+        ];
+        for (input, kind) in cases {
+            assert_eq!(parse(input).unwrap_err().kind(), &kind, "输入: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let ok = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&ok).is_ok());
+
+        let deep = "[".repeat(100_000);
+        let err = parse(&deep).unwrap_err();
+        assert_eq!(err.kind(), &JsonErrorKind::DepthLimitExceeded);
+        assert_eq!(err.offset(), MAX_DEPTH);
+    }
+
+    #[test]
+    fn test_serialize_compact_and_pretty() {
+        let value = json!({
+            "name": "Al\"ice",
+            "scores": [1, 2.5, -0.0],
+            "empty": [],
+            "nested": { "ok": true, "none": null }
+        });
+        assert_eq!(
+            value.to_compact_string(),
+            r#"{"empty":[],"name":"Al\"ice","nested":{"none":null,"ok":true},"scores":[1,2.5,0]}"#
+        );
+        assert_eq!(
+            value.to_pretty_string(),
+            "{\n  \"empty\": [],\n  \"name\": \"Al\\\"ice\",\n  \"nested\": {\n    \"none\": null,\n    \"ok\": true\n  },\n  \"scores\": [\n    1,\n    2.5,\n    0\n  ]\n}"
+        );
+
+        assert_eq!(json!("\u{1}\t").to_string(), r#""\u0001\t""#);
+        assert_eq!(json!(1e300).to_string(), "1e300");
+        assert_eq!(json!(f64::NAN).to_string(), "null");
+
+        // 序列化结果可以原样解析回来
+        for text in [value.to_compact_string(), value.to_pretty_string()] {
+            assert_eq!(parse(&text), Ok(value.clone()));
+        }
+    }
+
+    #[test]
+    fn test_json_pointer() {
+        // RFC 6901 第 5 节的示例文档
+        let mut doc = parse(
+            r#"{"foo": ["bar", "baz"], "": 0, "a/b": 1, "c%d": 2, "e^f": 3,
+                "g|h": 4, "i\\j": 5, "k\"l": 6, " ": 7, "m~n": 8}"#,
+        )
+        .unwrap();
+
+        assert_eq!(doc.pointer(""), Some(&doc));
+        assert_eq!(doc.pointer("/foo"), Some(&json!(["bar", "baz"])));
+        assert_eq!(doc.pointer("/foo/0"), Some(&json!("bar")));
+        assert_eq!(doc.pointer("/"), Some(&json!(0)));
+        assert_eq!(doc.pointer("/a~1b"), Some(&json!(1)));
+        assert_eq!(doc.pointer("/i\\j"), Some(&json!(5)));
+        assert_eq!(doc.pointer("/ "), Some(&json!(7)));
+        assert_eq!(doc.pointer("/m~0n"), Some(&json!(8)));
+
+        assert_eq!(doc.pointer("foo"), None);
+        assert_eq!(doc.pointer("/foo/01"), None);
+        assert_eq!(doc.pointer("/foo/-"), None);
+        assert_eq!(doc.pointer("/foo/0/x"), None);
+
+        *doc.pointer_mut("/foo/1").unwrap() = json!(null);
+        assert_eq!(doc["foo"], json!(["bar", null]));
+    }
+
+    #[test]
+    fn test_json_macro_builds_typed_values() {
+        let name = String::from("Bob");
+        let key = "dynamic";
+        let maybe: Option<u8> = None;
+        let value = json!({
+            "name": name,
+            "age": 20 + 10,
+            "tags": ["a", ["nested", null], { "deep": [] }],
+            (key): maybe,
+            "trailing": [1, 2,],
+        });
+
+        assert_eq!(value["name"], JsonValue::String("Bob".into()));
+        assert_eq!(value["age"].as_i64(), Some(30));
+        assert_eq!(value["tags"][1][0].as_str(), Some("nested"));
+        assert!(value["tags"][1][1].is_null());
+        assert_eq!(value["tags"][2]["deep"], JsonValue::Array(vec![]));
+        assert!(value["dynamic"].is_null());
+        assert_eq!(value["trailing"].as_array().map(Vec::len), Some(2));
+
+        assert_eq!(json!({}), JsonValue::Object(JsonMap::new()));
+        assert_eq!(json!([]), JsonValue::Array(Vec::new()));
+        assert_eq!(json!(vec![1, 2]), json!([1, 2]));
+    }
+
+    #[test]
+    fn test_error_to_diagnostic() {
+        let source = "{\"a\": 1,\n \"b\" 2}";
+        let err = parse(source).unwrap_err();
+        assert_eq!(err.to_string(), "第 2 行第 6 列: 意外的字符 '2'");
+        assert_eq!(err.code(), ErrorCode::PARSE_JSON);
+
+        let diagnostic = err.to_diagnostic("data.json", source);
+        assert_eq!(diagnostic.location(), Some((2, 6)));
+        assert!(diagnostic.render().contains("2 |  \"b\" 2}"));
+    }
+}
@@ -21,6 +21,31 @@ pub mod mod_16_smart_pointers;
 pub mod mod_17_async_await;
 pub mod mod_18_oop_features;
 
+// 可复用的实用模块，供各章节的示例程序调用
+pub mod alloc;
+pub mod canvas;
+pub mod capi;
+pub mod config;
+pub mod demo_rng;
+pub mod diagnostics;
+pub mod errors;
+pub mod fixed;
+pub mod image;
+pub mod json;
+pub mod minidb;
+pub mod net;
+pub mod num;
+// 插件基于 dlopen，只支持类 Unix 系统
+#[cfg(unix)]
+pub mod plugin;
+pub mod resilience;
+pub mod scene;
+pub mod sql;
+pub mod tui;
+pub mod units;
+pub mod wire;
+pub mod workflow;
+
 // 库级别的测试
 #[cfg(test)]
 mod tests {
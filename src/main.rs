@@ -4,6 +4,26 @@ fn main() {
     println!("Rust 代码教程库 - 主程序");
     println!("=======================");
 
+    // 所有随机演示共享一个带种子的随机源；打印种子以便原样重放本次输出
+    let (seed, source) = match demo_rng::resolve_seed(
+        std::env::args().skip(1),
+        std::env::var(demo_rng::SEED_ENV_VAR).ok(),
+    ) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    demo_rng::init(seed);
+    println!("随机种子: {}（来源：{}）", seed, source);
+    println!(
+        "重放本次输出：cargo run -- --seed {} 或 {}={} cargo run",
+        seed,
+        demo_rng::SEED_ENV_VAR,
+        seed
+    );
+
     println!("运行各个教程模块的演示：");
     println!();
 
@@ -0,0 +1,1539 @@
+// 内存表引擎与 SQLite 风格的 C 接口
+// 第 15 章的 SQLite 包装器示例运行在这个引擎之上，分三层：
+// 1. 引擎：解析并执行 CREATE TABLE / INSERT / SELECT / UPDATE / DELETE 的一个小子集
+// 2. C 接口：与 SQLite 同名、同签名风格的 extern "C" 函数（sqlite3_open、sqlite3_exec、sqlite3_step……），
+//    通过裸指针和返回码交互，就像链接了真正的 C 库一样
+// 3. 安全包装器：Connection / Statement 用 RAII 管理句柄，把返回码转换为 Result
+//
+// 取值类型复用 sql::Value，query! 宏和 Query 构建器生成的 SqlQuery 可以直接执行
+
+use crate::errors::{Diagnostic, ErrorCode};
+use crate::sql::{SqlQuery, Value};
+use guide_sql::{CmpOp, ColumnDef, ColumnType, Cond, Literal, Operand, Parsed, Stmt, SyntaxError};
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::{self, NonNull};
+
+// 返回码，取值与 SQLite 一致
+pub const SQLITE_OK: c_int = 0;
+pub const SQLITE_ERROR: c_int = 1;
+pub const SQLITE_ABORT: c_int = 4;
+pub const SQLITE_CONSTRAINT: c_int = 19;
+pub const SQLITE_MISMATCH: c_int = 20;
+pub const SQLITE_MISUSE: c_int = 21;
+pub const SQLITE_RANGE: c_int = 25;
+pub const SQLITE_ROW: c_int = 100;
+pub const SQLITE_DONE: c_int = 101;
+
+// sqlite3_column_type 的返回值
+pub const SQLITE_INTEGER: c_int = 1;
+pub const SQLITE_FLOAT: c_int = 2;
+pub const SQLITE_TEXT: c_int = 3;
+pub const SQLITE_NULL: c_int = 5;
+
+// ===========================================
+// 1. 错误
+// ===========================================
+
+// 引擎和包装器共用的错误：返回码加上错误信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbError {
+    pub code: c_int,
+    pub message: String,
+}
+
+impl DbError {
+    fn new(code: c_int, message: impl Into<String>) -> Self {
+        DbError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn syntax(message: impl Into<String>) -> Self {
+        DbError::new(SQLITE_ERROR, message)
+    }
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}（返回码 {}）", self.message, self.code)
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl Diagnostic for DbError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::DATABASE
+    }
+}
+
+// ===========================================
+// 2. 词法与语法分析
+// ===========================================
+
+// 词法、语法分析和语法树都在 crates/guide-sql 中，
+// sql! 宏在编译期用同一套语法检查字面量，编译通过的语句这里一定能解析
+
+impl From<SyntaxError> for DbError {
+    fn from(error: SyntaxError) -> Self {
+        DbError::syntax(error.message)
+    }
+}
+
+impl From<&Literal> for Value {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::Null => Value::Null,
+            Literal::Integer(n) => Value::Integer(*n),
+            Literal::Real(n) => Value::Real(*n),
+            Literal::Text(s) => Value::Text(s.clone()),
+        }
+    }
+}
+
+// 把 SQL 文本切分成多条语句，空语句忽略
+fn parse_all(sql: &str) -> Result<Vec<Parsed>, DbError> {
+    Ok(guide_sql::parse_all(sql)?)
+}
+
+fn parse_one(sql: &str) -> Result<Parsed, DbError> {
+    let mut statements = parse_all(sql)?;
+    match statements.len() {
+        1 => Ok(statements.remove(0)),
+        0 => Err(DbError::new(SQLITE_MISUSE, "SQL 中没有语句")),
+        _ => Err(DbError::new(SQLITE_MISUSE, "一次只能预处理一条语句")),
+    }
+}
+
+// ===========================================
+// 3. 执行引擎
+// ===========================================
+
+#[derive(Debug, Clone)]
+struct Table {
+    columns: Vec<ColumnDef>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn column_index(&self, name: &str) -> Result<usize, DbError> {
+        self.columns
+            .iter()
+            .position(|column| column.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| DbError::new(SQLITE_ERROR, format!("没有名为 '{}' 的列", name)))
+    }
+
+    // 按列类型检查并转换取值：整数可以存入 REAL 列，其余类型必须一致
+    fn check_value(&self, index: usize, value: Value) -> Result<Value, DbError> {
+        let column = &self.columns[index];
+        let value = match (column.ty, value) {
+            (_, Value::Null) if column.not_null || column.primary_key => {
+                return Err(DbError::new(
+                    SQLITE_CONSTRAINT,
+                    format!("列 '{}' 不能为 NULL", column.name),
+                ));
+            }
+            (ColumnType::Real, Value::Integer(n)) => Value::Real(n as f64),
+            (ColumnType::Any, value)
+            | (_, value @ Value::Null)
+            | (ColumnType::Integer, value @ Value::Integer(_))
+            | (ColumnType::Real, value @ Value::Real(_))
+            | (ColumnType::Text, value @ Value::Text(_)) => value,
+            (_, value) => {
+                return Err(DbError::new(
+                    SQLITE_MISMATCH,
+                    format!("列 '{}' 的类型与取值 {} 不匹配", column.name, value),
+                ));
+            }
+        };
+        Ok(value)
+    }
+
+    // pending 是同一条语句中已经检查过、尚未写入的行
+    fn check_primary_key(
+        &self,
+        row: &[Value],
+        skip: Option<usize>,
+        pending: &[Vec<Value>],
+    ) -> Result<(), DbError> {
+        for (index, column) in self.columns.iter().enumerate() {
+            if !column.primary_key {
+                continue;
+            }
+            let duplicate = self
+                .rows
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| Some(*i) != skip)
+                .map(|(_, existing)| existing)
+                .chain(pending)
+                .any(|existing| existing[index] == row[index]);
+            if duplicate {
+                return Err(DbError::new(
+                    SQLITE_CONSTRAINT,
+                    format!("主键 '{}' 的取值 {} 已存在", column.name, row[index]),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 语句的执行结果：SELECT 返回列名和行，其他语句返回受影响的行数
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rows {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub changes: usize,
+}
+
+// 内存数据库：表名不区分大小写
+#[derive(Debug, Default)]
+pub struct Database {
+    tables: BTreeMap<String, Table>,
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 执行单条语句，params 依次对应 SQL 中的 ?
+    pub fn execute(&mut self, sql: &str, params: &[Value]) -> Result<Rows, DbError> {
+        self.run(&parse_one(sql)?, params)
+    }
+
+    pub fn table_names(&self) -> Vec<String> {
+        self.tables.keys().cloned().collect()
+    }
+
+    fn table(&self, name: &str) -> Result<&Table, DbError> {
+        self.tables
+            .get(&name.to_ascii_lowercase())
+            .ok_or_else(|| DbError::new(SQLITE_ERROR, format!("没有名为 '{}' 的表", name)))
+    }
+
+    fn table_mut(&mut self, name: &str) -> Result<&mut Table, DbError> {
+        self.tables
+            .get_mut(&name.to_ascii_lowercase())
+            .ok_or_else(|| DbError::new(SQLITE_ERROR, format!("没有名为 '{}' 的表", name)))
+    }
+
+    fn run(&mut self, prepared: &Parsed, params: &[Value]) -> Result<Rows, DbError> {
+        match &prepared.stmt {
+            Stmt::CreateTable {
+                name,
+                if_not_exists,
+                columns,
+            } => {
+                let key = name.to_ascii_lowercase();
+                if self.tables.contains_key(&key) {
+                    if *if_not_exists {
+                        return Ok(Rows::default());
+                    }
+                    return Err(DbError::new(SQLITE_ERROR, format!("表 '{}' 已存在", name)));
+                }
+                let table = Table {
+                    columns: columns.clone(),
+                    rows: Vec::new(),
+                };
+                self.tables.insert(key, table);
+                Ok(Rows::default())
+            }
+            Stmt::Insert {
+                table,
+                columns,
+                rows,
+            } => {
+                let table = self.table_mut(table)?;
+                let indices = match columns {
+                    Some(names) => names
+                        .iter()
+                        .map(|name| table.column_index(name))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => (0..table.columns.len()).collect(),
+                };
+                // 先检查所有行，全部通过后再写入，保证语句要么全部生效要么不生效
+                let mut new_rows = Vec::with_capacity(rows.len());
+                for operands in rows {
+                    if operands.len() != indices.len() {
+                        return Err(DbError::new(
+                            SQLITE_ERROR,
+                            format!("提供了 {} 个值，但有 {} 列", operands.len(), indices.len()),
+                        ));
+                    }
+                    let mut row = vec![Value::Null; table.columns.len()];
+                    for (&index, operand) in indices.iter().zip(operands) {
+                        row[index] = eval(operand, None, params)?;
+                    }
+                    for (index, value) in row.iter_mut().enumerate() {
+                        *value = table.check_value(index, std::mem::replace(value, Value::Null))?;
+                    }
+                    table.check_primary_key(&row, None, &new_rows)?;
+                    new_rows.push(row);
+                }
+                let changes = new_rows.len();
+                table.rows.extend(new_rows);
+                Ok(Rows {
+                    changes,
+                    ..Rows::default()
+                })
+            }
+            Stmt::Select {
+                table,
+                columns,
+                filter,
+                order_by,
+                limit,
+                offset,
+            } => {
+                let table = self.table(table)?;
+                let indices = match columns {
+                    Some(names) => names
+                        .iter()
+                        .map(|name| table.column_index(name))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => (0..table.columns.len()).collect(),
+                };
+                let mut matched = Vec::new();
+                for row in &table.rows {
+                    if matches(filter, table, row, params)? {
+                        matched.push(row);
+                    }
+                }
+                let order = order_by
+                    .iter()
+                    .map(|(name, desc)| Ok((table.column_index(name)?, *desc)))
+                    .collect::<Result<Vec<_>, DbError>>()?;
+                matched.sort_by(|a, b| {
+                    order
+                        .iter()
+                        .map(|&(index, desc)| {
+                            let ordering = compare_values(&a[index], &b[index]);
+                            if desc { ordering.reverse() } else { ordering }
+                        })
+                        .find(|ordering| ordering.is_ne())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                let rows = matched
+                    .into_iter()
+                    .skip(offset.unwrap_or(0) as usize)
+                    .take(limit.map_or(usize::MAX, |n| n as usize))
+                    .map(|row| indices.iter().map(|&i| row[i].clone()).collect())
+                    .collect();
+                Ok(Rows {
+                    columns: indices
+                        .iter()
+                        .map(|&i| table.columns[i].name.clone())
+                        .collect(),
+                    rows,
+                    changes: 0,
+                })
+            }
+            Stmt::Update {
+                table,
+                assignments,
+                filter,
+            } => {
+                let table = self.table_mut(table)?;
+                let targets = assignments
+                    .iter()
+                    .map(|(name, operand)| Ok((table.column_index(name)?, operand)))
+                    .collect::<Result<Vec<_>, DbError>>()?;
+                // 同样先计算出所有新行，全部检查通过后再替换
+                let mut updated = Vec::new();
+                let mut new_rows = Vec::new();
+                for (i, row) in table.rows.iter().enumerate() {
+                    if !matches(filter, table, row, params)? {
+                        continue;
+                    }
+                    let mut new_row = row.clone();
+                    for &(index, operand) in &targets {
+                        let value = eval(operand, Some((table, row)), params)?;
+                        new_row[index] = table.check_value(index, value)?;
+                    }
+                    table.check_primary_key(&new_row, Some(i), &new_rows)?;
+                    updated.push(i);
+                    new_rows.push(new_row);
+                }
+                let changes = updated.len();
+                for (i, row) in updated.into_iter().zip(new_rows) {
+                    table.rows[i] = row;
+                }
+                Ok(Rows {
+                    changes,
+                    ..Rows::default()
+                })
+            }
+            Stmt::Delete { table, filter } => {
+                let table = self.table_mut(table)?;
+                let mut keep = Vec::with_capacity(table.rows.len());
+                for row in &table.rows {
+                    keep.push(!matches(filter, table, row, params)?);
+                }
+                let before = table.rows.len();
+                let mut flags = keep.into_iter();
+                table.rows.retain(|_| flags.next().unwrap_or(true));
+                Ok(Rows {
+                    changes: before - table.rows.len(),
+                    ..Rows::default()
+                })
+            }
+        }
+    }
+}
+
+// 求操作数的值；未绑定的参数按 SQLite 的规则视为 NULL
+fn eval(
+    operand: &Operand,
+    row: Option<(&Table, &Vec<Value>)>,
+    params: &[Value],
+) -> Result<Value, DbError> {
+    match operand {
+        Operand::Literal(literal) => Ok(Value::from(literal)),
+        Operand::Param(index) => Ok(params.get(*index).cloned().unwrap_or(Value::Null)),
+        Operand::Column(name) => match row {
+            Some((table, row)) => Ok(row[table.column_index(name)?].clone()),
+            None => Err(DbError::new(
+                SQLITE_ERROR,
+                format!("VALUES 中不能引用列 '{}'", name),
+            )),
+        },
+    }
+}
+
+fn matches(
+    filter: &Option<Cond>,
+    table: &Table,
+    row: &Vec<Value>,
+    params: &[Value],
+) -> Result<bool, DbError> {
+    match filter {
+        Some(cond) => Ok(test(cond, table, row, params)? == Some(true)),
+        None => Ok(true),
+    }
+}
+
+// SQL 的三值逻辑：与 NULL 比较的结果是"未知"（None），WHERE 只保留结果为 true 的行
+fn test(
+    cond: &Cond,
+    table: &Table,
+    row: &Vec<Value>,
+    params: &[Value],
+) -> Result<Option<bool>, DbError> {
+    let value = |operand: &Operand| eval(operand, Some((table, row)), params);
+    Ok(match cond {
+        Cond::Compare(left, op, right) => {
+            let (left, right) = (value(left)?, value(right)?);
+            if left == Value::Null || right == Value::Null {
+                None
+            } else {
+                let ordering = compare_values(&left, &right);
+                Some(match op {
+                    CmpOp::Eq => ordering.is_eq(),
+                    CmpOp::Ne => ordering.is_ne(),
+                    CmpOp::Lt => ordering.is_lt(),
+                    CmpOp::Le => ordering.is_le(),
+                    CmpOp::Gt => ordering.is_gt(),
+                    CmpOp::Ge => ordering.is_ge(),
+                })
+            }
+        }
+        Cond::IsNull(operand, is_null) => Some((value(operand)? == Value::Null) == *is_null),
+        Cond::Like(left, pattern, negated) => match (value(left)?, value(pattern)?) {
+            (Value::Null, _) | (_, Value::Null) => None,
+            (left, pattern) => Some(like(&text_of(&left), &text_of(&pattern)) != *negated),
+        },
+        Cond::In(left, items, negated) => {
+            let left = value(left)?;
+            if left == Value::Null {
+                None
+            } else {
+                let mut found = false;
+                for item in items {
+                    found |= compare_values(&left, &value(item)?).is_eq();
+                }
+                Some(found != *negated)
+            }
+        }
+        Cond::And(left, right) => {
+            match (
+                test(left, table, row, params)?,
+                test(right, table, row, params)?,
+            ) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            }
+        }
+        Cond::Or(left, right) => {
+            match (
+                test(left, table, row, params)?,
+                test(right, table, row, params)?,
+            ) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            }
+        }
+        Cond::Not(inner) => test(inner, table, row, params)?.map(|b| !b),
+    })
+}
+
+// 排序规则与 SQLite 相同：NULL < 数字 < 文本
+fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Integer(_) | Value::Real(_) => 1,
+            Value::Text(_) => 2,
+        }
+    }
+    match (a, b) {
+        (Value::Integer(x), Value::Integer(y)) => x.cmp(y),
+        (Value::Text(x), Value::Text(y)) => x.cmp(y),
+        (Value::Integer(_) | Value::Real(_), Value::Integer(_) | Value::Real(_)) => number_of(a)
+            .partial_cmp(&number_of(b))
+            .unwrap_or(Ordering::Equal),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn number_of(value: &Value) -> f64 {
+    match value {
+        Value::Integer(n) => *n as f64,
+        Value::Real(n) => *n,
+        _ => 0.0,
+    }
+}
+
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Integer(n) => n.to_string(),
+        Value::Real(n) => n.to_string(),
+        Value::Text(s) => s.clone(),
+    }
+}
+
+// LIKE：% 匹配任意多个字符，_ 匹配一个字符，ASCII 字母不区分大小写
+fn like(text: &str, pattern: &str) -> bool {
+    fn go(text: &[char], pattern: &[char]) -> bool {
+        match pattern.split_first() {
+            None => text.is_empty(),
+            Some(('%', rest)) => (0..=text.len()).any(|i| go(&text[i..], rest)),
+            Some(('_', rest)) => !text.is_empty() && go(&text[1..], rest),
+            Some((c, rest)) => text
+                .split_first()
+                .is_some_and(|(t, text)| t.eq_ignore_ascii_case(c) && go(text, rest)),
+        }
+    }
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    go(&text, &pattern)
+}
+
+// ===========================================
+// 4. SQLite 风格的 C 接口
+// ===========================================
+
+// 数据库连接句柄，对 C 调用方不透明
+pub struct Sqlite3 {
+    db: Database,
+    errmsg: CString,
+    changes: c_int,
+}
+
+impl Sqlite3 {
+    fn set_error(&mut self, error: &DbError) -> c_int {
+        self.errmsg = to_cstring(&error.message);
+        error.code
+    }
+}
+
+// 预处理语句句柄
+pub struct Sqlite3Stmt {
+    db: *mut Sqlite3,
+    prepared: Parsed,
+    params: Vec<Value>,
+    result: Option<Rows>,
+    // 下一行的下标；当前行是 next_row - 1
+    next_row: usize,
+    // 当前行各列的文本形式，sqlite3_column_text 返回的指针在下一次 step 之前有效
+    text: Vec<Option<CString>>,
+    names: Vec<CString>,
+}
+
+// sqlite3_exec 的行回调：(用户参数, 列数, 各列的值, 各列的名称)，返回非 0 时中止查询
+pub type Sqlite3Callback =
+    extern "C" fn(*mut c_void, c_int, *mut *mut c_char, *mut *mut c_char) -> c_int;
+
+// 文本中的 NUL 字符无法放进 C 字符串，直接去掉
+fn to_cstring(text: &str) -> CString {
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
+
+fn value_text(value: &Value) -> Option<CString> {
+    match value {
+        Value::Null => None,
+        value => Some(to_cstring(&text_of(value))),
+    }
+}
+
+/// 打开一个内存数据库，文件名只用于展示，所有数据都保存在内存中
+///
+/// # Safety
+/// `filename` 必须是有效的 C 字符串，`db` 必须指向可写的指针
+pub unsafe extern "C" fn sqlite3_open(filename: *const c_char, db: *mut *mut Sqlite3) -> c_int {
+    if filename.is_null() || db.is_null() {
+        return SQLITE_MISUSE;
+    }
+    let handle = Box::new(Sqlite3 {
+        db: Database::new(),
+        errmsg: to_cstring("not an error"),
+        changes: 0,
+    });
+    unsafe { *db = Box::into_raw(handle) };
+    SQLITE_OK
+}
+
+/// 关闭连接并释放句柄，传入空指针是无害的空操作
+///
+/// # Safety
+/// `db` 必须是 sqlite3_open 返回的句柄，且关闭后不能再使用
+pub unsafe extern "C" fn sqlite3_close(db: *mut Sqlite3) -> c_int {
+    if !db.is_null() {
+        drop(unsafe { Box::from_raw(db) });
+    }
+    SQLITE_OK
+}
+
+/// 依次执行以分号分隔的多条语句，SELECT 的每一行调用一次回调
+/// 出错时如果 `errmsg` 非空，写入一个需要用 sqlite3_free 释放的错误信息
+/// 调用回调时不持有对句柄的引用，回调中可以在同一个连接上继续执行语句
+///
+/// # Safety
+/// `db` 必须是有效句柄，`sql` 必须是有效的 C 字符串，`errmsg` 为空或指向可写的指针；
+/// 回调返回之前不能关闭 `db`
+pub unsafe extern "C" fn sqlite3_exec(
+    db: *mut Sqlite3,
+    sql: *const c_char,
+    callback: Option<Sqlite3Callback>,
+    callback_arg: *mut c_void,
+    errmsg: *mut *mut c_char,
+) -> c_int {
+    if db.is_null() || sql.is_null() {
+        return SQLITE_MISUSE;
+    }
+    let sql = unsafe { CStr::from_ptr(sql) }.to_string_lossy();
+
+    let result = parse_all(&sql).and_then(|statements| {
+        for prepared in &statements {
+            // 对句柄的可变借用只在执行语句期间存在，结果行已经复制出来
+            let rows = {
+                let handle = unsafe { &mut *db };
+                let rows = handle.db.run(prepared, &[])?;
+                handle.changes = rows.changes as c_int;
+                rows
+            };
+            let Some(callback) = callback else { continue };
+
+            let names: Vec<CString> = rows.columns.iter().map(|n| to_cstring(n)).collect();
+            let mut name_ptrs: Vec<*mut c_char> =
+                names.iter().map(|n| n.as_ptr() as *mut c_char).collect();
+            for row in &rows.rows {
+                let values: Vec<Option<CString>> = row.iter().map(value_text).collect();
+                let mut value_ptrs: Vec<*mut c_char> = values
+                    .iter()
+                    .map(|v| {
+                        v.as_ref()
+                            .map_or(ptr::null_mut(), |v| v.as_ptr() as *mut c_char)
+                    })
+                    .collect();
+                let rc = callback(
+                    callback_arg,
+                    row.len() as c_int,
+                    value_ptrs.as_mut_ptr(),
+                    name_ptrs.as_mut_ptr(),
+                );
+                if rc != 0 {
+                    return Err(DbError::new(SQLITE_ABORT, "查询被回调函数中止"));
+                }
+            }
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => SQLITE_OK,
+        Err(error) => {
+            if !errmsg.is_null() {
+                unsafe { *errmsg = to_cstring(&error.message).into_raw() };
+            }
+            unsafe { (*db).set_error(&error) }
+        }
+    }
+}
+
+/// 释放 sqlite3_exec 写入的错误信息
+///
+/// # Safety
+/// `ptr` 必须为空，或者是 sqlite3_exec 通过 errmsg 返回且尚未释放的指针
+pub unsafe extern "C" fn sqlite3_free(ptr: *mut c_void) {
+    if !ptr.is_null() {
+        drop(unsafe { CString::from_raw(ptr as *mut c_char) });
+    }
+}
+
+/// 最近一次失败的错误信息，指针在下一次调用之前有效
+///
+/// # Safety
+/// `db` 必须是有效句柄
+pub unsafe extern "C" fn sqlite3_errmsg(db: *mut Sqlite3) -> *const c_char {
+    if db.is_null() {
+        return c"out of memory".as_ptr();
+    }
+    unsafe { (*db).errmsg.as_ptr() }
+}
+
+/// 最近一条 INSERT / UPDATE / DELETE 影响的行数
+///
+/// # Safety
+/// `db` 必须是有效句柄
+pub unsafe extern "C" fn sqlite3_changes(db: *mut Sqlite3) -> c_int {
+    if db.is_null() {
+        return 0;
+    }
+    unsafe { (*db).changes }
+}
+
+/// 把一条语句编译成预处理语句；`n_byte` 为负数时读取到 NUL 为止
+/// 只支持单条语句，`tail` 总是被设为空指针
+///
+/// # Safety
+/// `db` 必须是有效句柄，`sql` 指向至少 `n_byte` 个字节（或以 NUL 结尾），`stmt` 必须可写
+pub unsafe extern "C" fn sqlite3_prepare_v2(
+    db: *mut Sqlite3,
+    sql: *const c_char,
+    n_byte: c_int,
+    stmt: *mut *mut Sqlite3Stmt,
+    tail: *mut *const c_char,
+) -> c_int {
+    if db.is_null() || sql.is_null() || stmt.is_null() {
+        return SQLITE_MISUSE;
+    }
+    let handle = unsafe { &mut *db };
+    let bytes = if n_byte < 0 {
+        unsafe { CStr::from_ptr(sql) }.to_bytes()
+    } else {
+        unsafe { std::slice::from_raw_parts(sql as *const u8, n_byte as usize) }
+    };
+    if !tail.is_null() {
+        unsafe { *tail = ptr::null() };
+    }
+    unsafe { *stmt = ptr::null_mut() };
+
+    match parse_one(&String::from_utf8_lossy(bytes)) {
+        Ok(prepared) => {
+            let statement = Box::new(Sqlite3Stmt {
+                db,
+                params: vec![Value::Null; prepared.param_count],
+                prepared,
+                result: None,
+                next_row: 0,
+                text: Vec::new(),
+                names: Vec::new(),
+            });
+            unsafe { *stmt = Box::into_raw(statement) };
+            SQLITE_OK
+        }
+        Err(error) => handle.set_error(&error),
+    }
+}
+
+// 绑定参数的公共逻辑：下标从 1 开始，语句执行过之后需要先 reset 才能重新绑定
+unsafe fn bind(stmt: *mut Sqlite3Stmt, index: c_int, value: Value) -> c_int {
+    if stmt.is_null() {
+        return SQLITE_MISUSE;
+    }
+    let statement = unsafe { &mut *stmt };
+    if statement.result.is_some() {
+        return SQLITE_MISUSE;
+    }
+    match usize::try_from(index)
+        .ok()
+        .and_then(|i| i.checked_sub(1))
+        .and_then(|i| statement.params.get_mut(i))
+    {
+        Some(slot) => {
+            *slot = value;
+            SQLITE_OK
+        }
+        None => SQLITE_RANGE,
+    }
+}
+
+/// # Safety
+/// `stmt` 必须是 sqlite3_prepare_v2 返回且尚未 finalize 的语句
+pub unsafe extern "C" fn sqlite3_bind_int64(
+    stmt: *mut Sqlite3Stmt,
+    index: c_int,
+    value: i64,
+) -> c_int {
+    unsafe { bind(stmt, index, Value::Integer(value)) }
+}
+
+/// # Safety
+/// `stmt` 必须是 sqlite3_prepare_v2 返回且尚未 finalize 的语句
+pub unsafe extern "C" fn sqlite3_bind_double(
+    stmt: *mut Sqlite3Stmt,
+    index: c_int,
+    value: f64,
+) -> c_int {
+    unsafe { bind(stmt, index, Value::Real(value)) }
+}
+
+/// 绑定文本，内容会被复制；`n` 为负数时读取到 NUL 为止
+///
+/// # Safety
+/// `stmt` 必须是有效语句，`text` 指向至少 `n` 个字节（或以 NUL 结尾）
+pub unsafe extern "C" fn sqlite3_bind_text(
+    stmt: *mut Sqlite3Stmt,
+    index: c_int,
+    text: *const c_char,
+    n: c_int,
+) -> c_int {
+    if text.is_null() {
+        return unsafe { bind(stmt, index, Value::Null) };
+    }
+    let bytes = if n < 0 {
+        unsafe { CStr::from_ptr(text) }.to_bytes()
+    } else {
+        unsafe { std::slice::from_raw_parts(text as *const u8, n as usize) }
+    };
+    let value = Value::Text(String::from_utf8_lossy(bytes).into_owned());
+    unsafe { bind(stmt, index, value) }
+}
+
+/// # Safety
+/// `stmt` 必须是 sqlite3_prepare_v2 返回且尚未 finalize 的语句
+pub unsafe extern "C" fn sqlite3_bind_null(stmt: *mut Sqlite3Stmt, index: c_int) -> c_int {
+    unsafe { bind(stmt, index, Value::Null) }
+}
+
+/// 第一次调用时执行语句；之后每次调用前进一行
+/// 有下一行时返回 SQLITE_ROW，结束时返回 SQLITE_DONE
+///
+/// # Safety
+/// `stmt` 必须是有效语句，且所属的连接仍然打开
+pub unsafe extern "C" fn sqlite3_step(stmt: *mut Sqlite3Stmt) -> c_int {
+    if stmt.is_null() {
+        return SQLITE_MISUSE;
+    }
+    let statement = unsafe { &mut *stmt };
+    let handle = unsafe { &mut *statement.db };
+
+    if statement.result.is_none() {
+        match handle.db.run(&statement.prepared, &statement.params) {
+            Ok(rows) => {
+                handle.changes = rows.changes as c_int;
+                statement.names = rows.columns.iter().map(|n| to_cstring(n)).collect();
+                statement.result = Some(rows);
+                statement.next_row = 0;
+            }
+            Err(error) => return handle.set_error(&error),
+        }
+    }
+
+    let rows = statement.result.as_ref().map_or(&[][..], |r| &r.rows[..]);
+    match rows.get(statement.next_row) {
+        Some(row) => {
+            statement.text = row.iter().map(value_text).collect();
+            statement.next_row += 1;
+            SQLITE_ROW
+        }
+        None => {
+            statement.text.clear();
+            SQLITE_DONE
+        }
+    }
+}
+
+// 当前行的某一列；没有当前行或下标越界时返回 None
+unsafe fn current_value<'a>(stmt: *mut Sqlite3Stmt, column: c_int) -> Option<&'a Value> {
+    if stmt.is_null() {
+        return None;
+    }
+    let statement = unsafe { &*stmt };
+    let row = statement
+        .result
+        .as_ref()?
+        .rows
+        .get(statement.next_row.checked_sub(1)?)?;
+    row.get(usize::try_from(column).ok()?)
+}
+
+/// # Safety
+/// `stmt` 必须是有效语句
+pub unsafe extern "C" fn sqlite3_column_count(stmt: *mut Sqlite3Stmt) -> c_int {
+    if stmt.is_null() {
+        return 0;
+    }
+    let statement = unsafe { &*stmt };
+    match &statement.prepared.stmt {
+        Stmt::Select { .. } => match &statement.result {
+            Some(rows) => rows.columns.len() as c_int,
+            None => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// # Safety
+/// `stmt` 必须是有效语句
+pub unsafe extern "C" fn sqlite3_column_type(stmt: *mut Sqlite3Stmt, column: c_int) -> c_int {
+    match unsafe { current_value(stmt, column) } {
+        Some(Value::Integer(_)) => SQLITE_INTEGER,
+        Some(Value::Real(_)) => SQLITE_FLOAT,
+        Some(Value::Text(_)) => SQLITE_TEXT,
+        Some(Value::Null) | None => SQLITE_NULL,
+    }
+}
+
+/// # Safety
+/// `stmt` 必须是有效语句
+pub unsafe extern "C" fn sqlite3_column_int64(stmt: *mut Sqlite3Stmt, column: c_int) -> i64 {
+    match unsafe { current_value(stmt, column) } {
+        Some(Value::Integer(n)) => *n,
+        Some(Value::Real(n)) => *n as i64,
+        Some(Value::Text(s)) => s.trim().parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// # Safety
+/// `stmt` 必须是有效语句
+pub unsafe extern "C" fn sqlite3_column_double(stmt: *mut Sqlite3Stmt, column: c_int) -> f64 {
+    match unsafe { current_value(stmt, column) } {
+        Some(Value::Integer(n)) => *n as f64,
+        Some(Value::Real(n)) => *n,
+        Some(Value::Text(s)) => s.trim().parse().unwrap_or(0.0),
+        _ => 0.0,
+    }
+}
+
+/// 当前行某一列的文本形式，NULL 返回空指针；指针在下一次 step / reset / finalize 之前有效
+///
+/// # Safety
+/// `stmt` 必须是有效语句
+pub unsafe extern "C" fn sqlite3_column_text(
+    stmt: *mut Sqlite3Stmt,
+    column: c_int,
+) -> *const c_char {
+    if stmt.is_null() {
+        return ptr::null();
+    }
+    let statement = unsafe { &*stmt };
+    usize::try_from(column)
+        .ok()
+        .and_then(|i| statement.text.get(i))
+        .and_then(|text| text.as_ref())
+        .map_or(ptr::null(), |text| text.as_ptr())
+}
+
+/// # Safety
+/// `stmt` 必须是有效语句
+pub unsafe extern "C" fn sqlite3_column_name(
+    stmt: *mut Sqlite3Stmt,
+    column: c_int,
+) -> *const c_char {
+    if stmt.is_null() {
+        return ptr::null();
+    }
+    let statement = unsafe { &*stmt };
+    usize::try_from(column)
+        .ok()
+        .and_then(|i| statement.names.get(i))
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// 回到执行前的状态，保留已绑定的参数
+///
+/// # Safety
+/// `stmt` 必须是有效语句
+pub unsafe extern "C" fn sqlite3_reset(stmt: *mut Sqlite3Stmt) -> c_int {
+    if stmt.is_null() {
+        return SQLITE_MISUSE;
+    }
+    let statement = unsafe { &mut *stmt };
+    statement.result = None;
+    statement.next_row = 0;
+    statement.text.clear();
+    SQLITE_OK
+}
+
+/// 销毁预处理语句，传入空指针是无害的空操作
+///
+/// # Safety
+/// `stmt` 必须是 sqlite3_prepare_v2 返回且尚未 finalize 的语句
+pub unsafe extern "C" fn sqlite3_finalize(stmt: *mut Sqlite3Stmt) -> c_int {
+    if !stmt.is_null() {
+        drop(unsafe { Box::from_raw(stmt) });
+    }
+    SQLITE_OK
+}
+
+// ===========================================
+// 5. 安全包装器
+// ===========================================
+
+// 数据库连接：创建时打开，Drop 时关闭
+// 持有裸指针，因此既不是 Send 也不是 Sync，与 SQLite 单线程模式的约束一致
+pub struct Connection {
+    raw: NonNull<Sqlite3>,
+}
+
+impl Connection {
+    pub fn open(filename: &str) -> Result<Self, DbError> {
+        let filename = CString::new(filename)
+            .map_err(|_| DbError::new(SQLITE_MISUSE, "文件名中不能包含 NUL 字符"))?;
+        let mut raw = ptr::null_mut();
+        let rc = unsafe { sqlite3_open(filename.as_ptr(), &mut raw) };
+        match NonNull::new(raw) {
+            Some(raw) if rc == SQLITE_OK => Ok(Connection { raw }),
+            _ => Err(DbError::new(rc, "无法打开数据库")),
+        }
+    }
+
+    fn check(&self, rc: c_int) -> Result<(), DbError> {
+        if rc == SQLITE_OK {
+            return Ok(());
+        }
+        let message = unsafe { CStr::from_ptr(sqlite3_errmsg(self.raw.as_ptr())) };
+        Err(DbError::new(rc, message.to_string_lossy()))
+    }
+
+    // 执行一段不返回结果的 SQL（可以包含多条语句）
+    pub fn execute_batch(&self, sql: &str) -> Result<(), DbError> {
+        let sql = to_cstring(sql);
+        let rc = unsafe {
+            sqlite3_exec(
+                self.raw.as_ptr(),
+                sql.as_ptr(),
+                None,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        self.check(rc)
+    }
+
+    // 通过 sqlite3_exec 的回调逐行处理结果
+    // 闭包经由 void* 参数传给 C 侧，再由泛型的 extern "C" 跳板函数还原；闭包返回 false 时停止
+    // 闭包 panic 时先中止查询，sqlite3_exec 返回后再在调用方继续展开
+    pub fn query_each<F>(&self, sql: &str, f: F) -> Result<(), DbError>
+    where
+        F: FnMut(&[&str], &[Option<&str>]) -> bool,
+    {
+        struct State<F> {
+            f: F,
+            panic: Option<Box<dyn std::any::Any + Send>>,
+        }
+
+        extern "C" fn trampoline<F>(
+            arg: *mut c_void,
+            count: c_int,
+            values: *mut *mut c_char,
+            names: *mut *mut c_char,
+        ) -> c_int
+        where
+            F: FnMut(&[&str], &[Option<&str>]) -> bool,
+        {
+            let state = unsafe { &mut *(arg as *mut State<F>) };
+            // panic 不能穿过 extern "C" 边界，捕获后保存起来并中止查询
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let count = count as usize;
+                let (values, names) = unsafe {
+                    (
+                        std::slice::from_raw_parts(values, count),
+                        std::slice::from_raw_parts(names, count),
+                    )
+                };
+                let names: Vec<&str> = names
+                    .iter()
+                    .map(|&p| unsafe { CStr::from_ptr(p) }.to_str().unwrap_or(""))
+                    .collect();
+                let values: Vec<Option<&str>> = values
+                    .iter()
+                    .map(|&p| {
+                        (!p.is_null()).then(|| unsafe { CStr::from_ptr(p) }.to_str().unwrap_or(""))
+                    })
+                    .collect();
+                if (state.f)(&names, &values) { 0 } else { 1 }
+            }));
+            result.unwrap_or_else(|payload| {
+                state.panic = Some(payload);
+                1
+            })
+        }
+
+        let mut state = State { f, panic: None };
+        let sql = to_cstring(sql);
+        let rc = unsafe {
+            sqlite3_exec(
+                self.raw.as_ptr(),
+                sql.as_ptr(),
+                Some(trampoline::<F>),
+                &mut state as *mut State<F> as *mut c_void,
+                ptr::null_mut(),
+            )
+        };
+        if let Some(payload) = state.panic {
+            std::panic::resume_unwind(payload);
+        }
+        // 闭包主动停止不算错误
+        if rc == SQLITE_ABORT {
+            return Ok(());
+        }
+        self.check(rc)
+    }
+
+    pub fn prepare(&self, sql: &str) -> Result<Statement<'_>, DbError> {
+        let mut raw = ptr::null_mut();
+        let rc = unsafe {
+            sqlite3_prepare_v2(
+                self.raw.as_ptr(),
+                sql.as_ptr() as *const c_char,
+                sql.len() as c_int,
+                &mut raw,
+                ptr::null_mut(),
+            )
+        };
+        self.check(rc)?;
+        let raw = NonNull::new(raw).ok_or_else(|| DbError::new(SQLITE_MISUSE, "空语句"))?;
+        Ok(Statement {
+            raw,
+            conn: self,
+            _marker: PhantomData,
+        })
+    }
+
+    // 执行构建器生成的查询，返回受影响的行数
+    pub fn execute(&self, query: &SqlQuery) -> Result<usize, DbError> {
+        let mut statement = self.prepare(&query.sql)?;
+        statement.bind_all(&query.params)?;
+        while statement.step()?.is_some() {}
+        Ok(self.changes())
+    }
+
+    // 执行构建器生成的查询，收集所有结果行
+    pub fn query(&self, query: &SqlQuery) -> Result<Vec<Vec<Value>>, DbError> {
+        let mut statement = self.prepare(&query.sql)?;
+        statement.bind_all(&query.params)?;
+        let mut rows = Vec::new();
+        while let Some(row) = statement.step()? {
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    pub fn changes(&self) -> usize {
+        unsafe { sqlite3_changes(self.raw.as_ptr()) as usize }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe {
+            sqlite3_close(self.raw.as_ptr());
+        }
+    }
+}
+
+// 预处理语句：借用所属的连接，保证连接先于语句关闭的情况在编译期就被拒绝
+pub struct Statement<'conn> {
+    raw: NonNull<Sqlite3Stmt>,
+    conn: &'conn Connection,
+    _marker: PhantomData<*mut Sqlite3Stmt>,
+}
+
+impl Statement<'_> {
+    // 下标从 1 开始，与 C 接口一致
+    pub fn bind(&mut self, index: usize, value: &Value) -> Result<(), DbError> {
+        let raw = self.raw.as_ptr();
+        let index = index as c_int;
+        let rc = unsafe {
+            match value {
+                Value::Null => sqlite3_bind_null(raw, index),
+                Value::Integer(n) => sqlite3_bind_int64(raw, index, *n),
+                Value::Real(n) => sqlite3_bind_double(raw, index, *n),
+                Value::Text(s) => {
+                    sqlite3_bind_text(raw, index, s.as_ptr() as *const c_char, s.len() as c_int)
+                }
+            }
+        };
+        match rc {
+            SQLITE_OK => Ok(()),
+            SQLITE_RANGE => Err(DbError::new(rc, format!("参数下标 {} 超出范围", index))),
+            _ => Err(DbError::new(rc, "绑定参数前需要先 reset")),
+        }
+    }
+
+    pub fn bind_all(&mut self, values: &[Value]) -> Result<(), DbError> {
+        for (i, value) in values.iter().enumerate() {
+            self.bind(i + 1, value)?;
+        }
+        Ok(())
+    }
+
+    // 前进一行：有结果时返回该行，语句执行完毕时返回 None
+    pub fn step(&mut self) -> Result<Option<Vec<Value>>, DbError> {
+        let raw = self.raw.as_ptr();
+        match unsafe { sqlite3_step(raw) } {
+            SQLITE_ROW => {
+                let count = unsafe { sqlite3_column_count(raw) };
+                let row = (0..count)
+                    .map(|i| unsafe {
+                        match sqlite3_column_type(raw, i) {
+                            SQLITE_INTEGER => Value::Integer(sqlite3_column_int64(raw, i)),
+                            SQLITE_FLOAT => Value::Real(sqlite3_column_double(raw, i)),
+                            SQLITE_TEXT => Value::Text(
+                                CStr::from_ptr(sqlite3_column_text(raw, i))
+                                    .to_string_lossy()
+                                    .into_owned(),
+                            ),
+                            _ => Value::Null,
+                        }
+                    })
+                    .collect();
+                Ok(Some(row))
+            }
+            SQLITE_DONE => Ok(None),
+            rc => self.conn.check(rc).map(|_| None),
+        }
+    }
+
+    // 结果列名，只有执行过（至少 step 一次）的 SELECT 才有
+    pub fn column_names(&self) -> Vec<String> {
+        let raw = self.raw.as_ptr();
+        let count = unsafe { sqlite3_column_count(raw) };
+        (0..count)
+            .map(|i| {
+                unsafe { CStr::from_ptr(sqlite3_column_name(raw, i)) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    pub fn reset(&mut self) {
+        unsafe {
+            sqlite3_reset(self.raw.as_ptr());
+        }
+    }
+}
+
+impl Drop for Statement<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            sqlite3_finalize(self.raw.as_ptr());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::{Query, col};
+
+    fn users() -> Connection {
+        let conn = Connection::open(":memory:").unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, age INTEGER, score REAL);
+             INSERT INTO users VALUES (1, 'Alice', 30, 9.5), (2, 'Bob', 17, 7), (3, 'Carol', NULL, 8.25);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_engine_select_where_order_limit() {
+        let mut db = Database::new();
+        db.execute("CREATE TABLE t (a INTEGER, b TEXT)", &[])
+            .unwrap();
+        db.execute(
+            "INSERT INTO t (a, b) VALUES (3, 'x'), (1, 'y'), (2, NULL), (-4, 'it''s')",
+            &[],
+        )
+        .unwrap();
+
+        let rows = db
+            .execute(
+                "SELECT b, a FROM t WHERE a > ? ORDER BY a DESC LIMIT 2",
+                &[Value::from(0)],
+            )
+            .unwrap();
+        assert_eq!(rows.columns, vec!["b", "a"]);
+        assert_eq!(
+            rows.rows,
+            vec![
+                vec![Value::from("x"), Value::from(3)],
+                vec![Value::Null, Value::from(2)]
+            ]
+        );
+
+        // 三值逻辑：b 为 NULL 的行既不满足 b = 'x' 也不满足 NOT (b = 'x')
+        let mut count = |sql: &str| db.execute(sql, &[]).unwrap().rows.len();
+        assert_eq!(count("SELECT * FROM t WHERE NOT (b = 'x')"), 2);
+        assert_eq!(count("SELECT * FROM t WHERE b IS NULL OR a < 0"), 2);
+        assert_eq!(
+            count("SELECT * FROM t WHERE b LIKE 'IT%' AND a IN (-4, 5)"),
+            1
+        );
+        assert_eq!(count("SELECT * FROM t WHERE a NOT IN (1, 2) OFFSET 1"), 1);
+        // 负数 LIMIT 与 SQLite 一样表示不限行数
+        assert_eq!(count("SELECT * FROM t LIMIT -1 OFFSET 1"), 3);
+    }
+
+    #[test]
+    fn test_engine_errors() {
+        let mut db = Database::new();
+        db.execute(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, n TEXT NOT NULL)",
+            &[],
+        )
+        .unwrap();
+        let code = |db: &mut Database, sql: &str| db.execute(sql, &[]).unwrap_err().code;
+
+        assert_eq!(code(&mut db, "CREATE TABLE t (a)"), SQLITE_ERROR);
+        assert_eq!(code(&mut db, "SELECT * FROM missing"), SQLITE_ERROR);
+        assert_eq!(code(&mut db, "SELECT nope FROM t"), SQLITE_ERROR);
+        assert_eq!(code(&mut db, "SELEKT * FROM t"), SQLITE_ERROR);
+        assert_eq!(
+            code(&mut db, "INSERT INTO t VALUES ('a', 'b')"),
+            SQLITE_MISMATCH
+        );
+        assert_eq!(
+            code(&mut db, "INSERT INTO t VALUES (1, NULL)"),
+            SQLITE_CONSTRAINT
+        );
+        // 整条 INSERT 失败时，前面已经检查通过的行也不会写入
+        assert_eq!(
+            code(&mut db, "INSERT INTO t VALUES (1, 'a'), (1, 'b')"),
+            SQLITE_CONSTRAINT
+        );
+        assert!(db.execute("SELECT * FROM t", &[]).unwrap().rows.is_empty());
+        assert!(db.execute("CREATE TABLE IF NOT EXISTS t (a)", &[]).is_ok());
+    }
+
+    #[test]
+    fn test_c_api_exec_with_callback() {
+        extern "C" fn collect(
+            arg: *mut c_void,
+            count: c_int,
+            values: *mut *mut c_char,
+            names: *mut *mut c_char,
+        ) -> c_int {
+            let out = unsafe { &mut *(arg as *mut Vec<String>) };
+            for i in 0..count as usize {
+                let (name, value) = unsafe { (*names.add(i), *values.add(i)) };
+                let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+                let value = if value.is_null() {
+                    "NULL".into()
+                } else {
+                    unsafe { CStr::from_ptr(value) }.to_string_lossy()
+                };
+                out.push(format!("{}={}", name, value));
+            }
+            0
+        }
+
+        unsafe {
+            let mut db = ptr::null_mut();
+            assert_eq!(sqlite3_open(c":memory:".as_ptr(), &mut db), SQLITE_OK);
+            let sql = c"CREATE TABLE t (a, b); INSERT INTO t VALUES (1, NULL); SELECT a, b FROM t";
+            let mut out: Vec<String> = Vec::new();
+            let rc = sqlite3_exec(
+                db,
+                sql.as_ptr(),
+                Some(collect),
+                &mut out as *mut Vec<String> as *mut c_void,
+                ptr::null_mut(),
+            );
+            assert_eq!(rc, SQLITE_OK);
+            assert_eq!(out, vec!["a=1", "b=NULL"]);
+
+            let mut errmsg = ptr::null_mut();
+            let rc = sqlite3_exec(
+                db,
+                c"SELECT * FROM nope".as_ptr(),
+                None,
+                ptr::null_mut(),
+                &mut errmsg,
+            );
+            assert_eq!(rc, SQLITE_ERROR);
+            assert_eq!(
+                CStr::from_ptr(errmsg).to_str().unwrap(),
+                "没有名为 'nope' 的表"
+            );
+            assert_eq!(
+                CStr::from_ptr(sqlite3_errmsg(db)).to_str().unwrap(),
+                "没有名为 'nope' 的表"
+            );
+            sqlite3_free(errmsg as *mut c_void);
+
+            assert_eq!(sqlite3_close(db), SQLITE_OK);
+        }
+    }
+
+    #[test]
+    fn test_connection_with_query_builder() {
+        let conn = users();
+        let query = Query::select(["name", "score"])
+            .from("users")
+            .where_(col("age").ge(18).or(col("age").is_null()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            conn.query(&query).unwrap(),
+            vec![
+                vec![Value::from("Alice"), Value::from(9.5)],
+                vec![Value::from("Carol"), Value::from(8.25)]
+            ]
+        );
+
+        // 参数中的引号只是普通字符
+        let insert = Query::insert_into("users")
+            .columns(["id", "name"])
+            .values([Value::from(4), Value::from("x'); DROP TABLE users; --")])
+            .build()
+            .unwrap();
+        assert_eq!(conn.execute(&insert).unwrap(), 1);
+
+        let update = crate::query!(UPDATE users SET age = 18 WHERE name = "Bob")
+            .build()
+            .unwrap();
+        assert_eq!(conn.execute(&update).unwrap(), 1);
+        let delete = crate::query!(DELETE FROM users WHERE age IS NULL)
+            .build()
+            .unwrap();
+        assert_eq!(conn.execute(&delete).unwrap(), 2);
+
+        let mut names = Vec::new();
+        conn.query_each("SELECT name FROM users ORDER BY id", |_, values| {
+            names.push(values[0].unwrap_or("NULL").to_string());
+            true
+        })
+        .unwrap();
+        assert_eq!(names, vec!["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn test_statement_bind_step_reset() {
+        let conn = users();
+        let mut statement = conn
+            .prepare("SELECT id, name FROM users WHERE age < ? OR score > ?")
+            .unwrap();
+        assert_eq!(
+            statement.bind(3, &Value::from(1)).unwrap_err().code,
+            SQLITE_RANGE
+        );
+        statement
+            .bind_all(&[Value::from(18), Value::from(9.0)])
+            .unwrap();
+
+        let first = statement.step().unwrap();
+        assert_eq!(first, Some(vec![Value::from(1), Value::from("Alice")]));
+        assert_eq!(statement.column_names(), vec!["id", "name"]);
+        // 执行过程中不能重新绑定
+        assert_eq!(
+            statement.bind(1, &Value::from(0)).unwrap_err().code,
+            SQLITE_MISUSE
+        );
+        assert_eq!(
+            statement.step().unwrap(),
+            Some(vec![Value::from(2), Value::from("Bob")])
+        );
+        assert_eq!(statement.step().unwrap(), None);
+
+        statement.reset();
+        statement.bind(2, &Value::from(100.0)).unwrap();
+        assert_eq!(
+            statement.step().unwrap(),
+            Some(vec![Value::from(2), Value::from("Bob")])
+        );
+        assert_eq!(statement.step().unwrap(), None);
+
+        let err = conn
+            .prepare("SELECT * FROM users; SELECT * FROM users")
+            .err()
+            .unwrap();
+        assert_eq!(err.code, SQLITE_MISUSE);
+        let err = conn.prepare("SELECT * FROM users WHERE").err().unwrap();
+        assert_eq!(err.code, SQLITE_ERROR);
+        assert!(err.message.contains("语句已结束"));
+    }
+
+    #[test]
+    fn test_query_each_can_stop_early() {
+        let conn = users();
+        let mut seen = 0;
+        conn.query_each("SELECT * FROM users", |names, _| {
+            assert_eq!(names, ["id", "name", "age", "score"]);
+            seen += 1;
+            false
+        })
+        .unwrap();
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn test_query_each_callback_can_reenter_connection() {
+        let conn = users();
+        conn.execute_batch("CREATE TABLE log (name TEXT)").unwrap();
+        // 回调中在同一个连接上执行语句，sqlite3_exec 不会在回调期间持有句柄的引用
+        conn.query_each("SELECT name FROM users ORDER BY id", |_, values| {
+            let sql = format!("INSERT INTO log VALUES ('{}')", values[0].unwrap());
+            conn.execute_batch(&sql).unwrap();
+            true
+        })
+        .unwrap();
+
+        let mut logged = Vec::new();
+        conn.query_each("SELECT name FROM log", |_, values| {
+            logged.push(values[0].unwrap().to_string());
+            true
+        })
+        .unwrap();
+        let mut users = Vec::new();
+        conn.query_each("SELECT name FROM users ORDER BY id", |_, values| {
+            users.push(values[0].unwrap().to_string());
+            true
+        })
+        .unwrap();
+        assert_eq!(logged, users);
+    }
+
+    #[test]
+    fn test_query_each_propagates_callback_panic() {
+        let conn = users();
+        let mut seen = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            conn.query_each("SELECT name FROM users", |_, _| {
+                seen += 1;
+                panic!("回调出错");
+            })
+        }));
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"回调出错"));
+        // panic 之后查询立即中止，连接仍然可用
+        assert_eq!(seen, 1);
+        conn.query_each("SELECT name FROM users", |_, _| false)
+            .unwrap();
+    }
+}
@@ -669,31 +669,78 @@ fn let_else_statement() {
         max_connections: u32,
     }
 
-    fn parse_server_config(config_str: &str) -> Option<ServerConfig> {
-        let parts: Vec<&str> = config_str.split(':').collect();
-        if parts.len() != 3 {
-            return None;
+    // 解析失败时返回带源码位置的诊断，而不是一个说不清错在哪里的 None
+    // 诊断体积较大，装箱后返回以保持 Result 小巧
+    use crate::diagnostics::{Label, SourceDiagnostic};
+    use crate::errors::ErrorCode;
+
+    fn parse_server_config(config_str: &str) -> Result<ServerConfig, Box<SourceDiagnostic>> {
+        // 记录每个字段的字节区间，出错时可以精确标出是哪一段
+        let mut fields = Vec::new();
+        let mut start = 0;
+        for part in config_str.split(':') {
+            fields.push((part, start..start + part.len()));
+            start += part.len() + 1;
         }
+        let error = |message: &str| {
+            SourceDiagnostic::error(message)
+                .with_code(ErrorCode::PARSE)
+                .with_source("<server>", config_str)
+        };
 
-        let host = parts[0].to_string();
-        let port: u16 = parts[1].parse().ok()?;
-        let max_connections: u32 = parts[2].parse().ok()?;
+        // let-else 与切片模式配合：字段数量不对时直接返回诊断
+        let [(host, _), (port, port_span), (max, max_span)] = fields.as_slice() else {
+            return Err(Box::new(
+                error("字段数量不正确")
+                    .with_label(Label::primary(
+                        0..config_str.len(),
+                        format!("找到 {} 个字段，需要 3 个", fields.len()),
+                    ))
+                    .with_note("格式应为 host:port:max_connections"),
+            ));
+        };
 
-        Some(ServerConfig {
-            host,
-            port,
-            max_connections,
-        })
+        // 两个数字字段都检查完再返回，一次报告所有问题
+        match (port.parse::<u16>(), max.parse::<u32>()) {
+            (Ok(port), Ok(max_connections)) => Ok(ServerConfig {
+                host: host.to_string(),
+                port,
+                max_connections,
+            }),
+            (port_result, max_result) => {
+                let mut diagnostic = error("服务器配置包含无效的数字");
+                if let Err(e) = port_result {
+                    diagnostic = diagnostic
+                        .with_label(Label::primary(port_span.clone(), format!("端口无效: {}", e)));
+                }
+                if let Err(e) = max_result {
+                    diagnostic = diagnostic.with_label(Label::primary(
+                        max_span.clone(),
+                        format!("最大连接数无效: {}", e),
+                    ));
+                }
+                Err(Box::new(
+                    diagnostic.with_help("端口范围为 0-65535，最大连接数必须是非负整数"),
+                ))
+            }
+        }
     }
 
     let config_str = "localhost:8080:100";
-    let Some(config) = parse_server_config(config_str) else {
+    let Ok(config) = parse_server_config(config_str) else {
         println!("配置解析失败: {}", config_str);
         return;
     };
 
     println!("服务器配置: {:?}", config);
 
+    // 解析失败时，诊断会用插入符标出出错的字段
+    for bad_config in ["localhost:80800:many", "localhost:8080"] {
+        if let Err(diagnostic) = parse_server_config(bad_config) {
+            print!("{}", diagnostic.render());
+        }
+    }
+
     // let-else 语句的最佳实践：
     // 1. 适合提前返回的场景：当模式匹配失败时希望立即返回或中断
     // 2. 保持 else 块简洁：else 块通常应该包含返回、break 或 continue
@@ -12,6 +12,10 @@
 // 深入讲解枚举定义、模式匹配、Option、Result 等核心概念
 // 枚举是 Rust 中表达类型安全联合体的强大工具，支持数据变体和模式匹配
 
+// 红绿灯和网络连接的状态转换交给 workflow 模块的状态机
+use crate::workflow::{Workflow, WorkflowError};
+use std::sync::OnceLock;
+
 // ===========================================
 // 1. 基础枚举 (Basic Enums)
 // ===========================================
@@ -849,87 +853,104 @@ fn enums_and_ownership() {
 // 枚举可以实现方法，这为枚举添加了行为
 // 实现方法使枚举成为真正的面向对象数据类型
 
-fn enum_implementation() {
-    println!("=== 枚举实现 ===");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrafficLight {
+    Red,
+    Yellow,
+    Green,
+}
 
-    #[derive(Debug, Clone)]
-    enum TrafficLight {
-        Red,
-        Yellow,
-        Green,
+// 为枚举实现方法
+impl TrafficLight {
+    // 构造函数：创建新的红绿灯实例
+    fn new() -> Self {
+        TrafficLight::Red
     }
 
-    // 为枚举实现方法
-    impl TrafficLight {
-        // 构造函数：创建新的红绿灯实例
-        fn new() -> Self {
-            TrafficLight::Red
-        }
+    // 状态转换：获取下一个状态，转换规则由 light_workflow 给出
+    fn next(&self) -> Self {
+        light_workflow()
+            .fire(*self, LightEvent::Timer, &mut ())
+            .expect("每种灯色都有定时器转换")
+    }
 
-        // 状态转换：获取下一个状态
-        fn next(&self) -> Self {
-            match self {
-                TrafficLight::Red => TrafficLight::Green,
-                TrafficLight::Yellow => TrafficLight::Red,
-                TrafficLight::Green => TrafficLight::Yellow,
-            }
+    // 获取持续时间：返回当前状态的持续时间（秒）
+    fn duration(&self) -> u32 {
+        match self {
+            TrafficLight::Red => 30,
+            TrafficLight::Yellow => 5,
+            TrafficLight::Green => 25,
         }
+    }
 
-        // 获取持续时间：返回当前状态的持续时间（秒）
-        fn duration(&self) -> u32 {
-            match self {
-                TrafficLight::Red => 30,
-                TrafficLight::Yellow => 5,
-                TrafficLight::Green => 25,
-            }
-        }
+    // 检查是否为停止状态
+    fn is_stop(&self) -> bool {
+        matches!(self, TrafficLight::Red) // 使用 matches! 宏
+    }
 
-        // 检查是否为停止状态
-        fn is_stop(&self) -> bool {
-            matches!(self, TrafficLight::Red) // 使用 matches! 宏
+    // 获取描述信息
+    fn description(&self) -> &'static str {
+        match self {
+            TrafficLight::Red => "停止",
+            TrafficLight::Yellow => "准备",
+            TrafficLight::Green => "通行",
         }
+    }
 
-        // 获取描述信息
-        fn description(&self) -> &'static str {
-            match self {
-                TrafficLight::Red => "停止",
-                TrafficLight::Yellow => "准备",
-                TrafficLight::Green => "通行",
-            }
+    // 获取状态颜色
+    fn color(&self) -> &'static str {
+        match self {
+            TrafficLight::Red => "红色",
+            TrafficLight::Yellow => "黄色",
+            TrafficLight::Green => "绿色",
         }
+    }
 
-        // 获取状态颜色
-        fn color(&self) -> &'static str {
-            match self {
-                TrafficLight::Red => "红色",
-                TrafficLight::Yellow => "黄色",
-                TrafficLight::Green => "绿色",
+    // 检查是否可以通行
+    fn can_pass(&self) -> bool {
+        matches!(self, TrafficLight::Green)
+    }
+
+    // 模拟运行一段时间后的状态
+    fn after_duration(&self, seconds: u32) -> Self {
+        let mut current = *self;
+        let mut remaining = seconds;
+
+        while remaining > 0 {
+            let duration = current.duration();
+            if remaining >= duration {
+                current = current.next();
+                remaining -= duration;
+            } else {
+                break;
             }
         }
 
-        // 检查是否可以通行
-        fn can_pass(&self) -> bool {
-            matches!(self, TrafficLight::Green)
-        }
+        current
+    }
+}
 
-        // 模拟运行一段时间后的状态
-        fn after_duration(&self, seconds: u32) -> Self {
-            let mut current = self.clone();
-            let mut remaining = seconds;
+// 红绿灯只有一种事件：定时器到点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightEvent {
+    Timer,
+}
 
-            while remaining > 0 {
-                let duration = current.duration();
-                if remaining >= duration {
-                    current = current.next();
-                    remaining -= duration;
-                } else {
-                    break;
-                }
-            }
+// 转换规则交给 workflow::Workflow 声明，枚举只负责描述每种状态本身的属性
+fn light_workflow() -> &'static Workflow<TrafficLight, LightEvent> {
+    static WORKFLOW: OnceLock<Workflow<TrafficLight, LightEvent>> = OnceLock::new();
+    WORKFLOW.get_or_init(|| {
+        Workflow::builder("红绿灯", TrafficLight::Red)
+            .transition(TrafficLight::Red, LightEvent::Timer, TrafficLight::Green)
+            .transition(TrafficLight::Green, LightEvent::Timer, TrafficLight::Yellow)
+            .transition(TrafficLight::Yellow, LightEvent::Timer, TrafficLight::Red)
+            .build()
+            .expect("红绿灯工作流的定义有效")
+    })
+}
 
-            current
-        }
-    }
+fn enum_implementation() {
+    println!("=== 枚举实现 ===");
 
     // 演示红绿灯系统
     let mut light = TrafficLight::new();
@@ -973,6 +994,9 @@ fn enum_implementation() {
         future_light.description()
     );
 
+    // 转换表可以导出成 Graphviz 图
+    println!("红绿灯工作流:\n{}", light_workflow().to_dot());
+
     // 枚举实现的优势：
     // 1. 封装性：将数据和操作封装在一起
     // 2. 可扩展性：可以轻松添加新的方法和功能
@@ -1295,6 +1319,78 @@ fn enum_example_program() {
 // 3. 减少样板代码：自动生成默认实现
 // 4. 增强类型安全：编译时保证默认值的有效性
 
+// 连接状态机：状态和转换声明在 network_workflow 中
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionState {
+    #[default] // 默认状态
+    Disconnected,
+    Connecting,
+    Connected,
+    Disconnecting,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkEvent {
+    Connect,
+    Established,
+    Failed,
+    Disconnect,
+    Closed,
+    Reset,
+}
+
+#[derive(Debug)]
+pub struct NetworkManager {
+    state: ConnectionState,
+    retry_count: u32,
+}
+
+impl Default for NetworkManager {
+    fn default() -> Self {
+        NetworkManager {
+            state: ConnectionState::default(), // 从默认状态开始
+            retry_count: 0,
+        }
+    }
+}
+
+impl NetworkManager {
+    const MAX_RETRIES: u32 = 3;
+
+    // 不能处理的事件返回错误，状态保持不变
+    pub fn handle(&mut self, event: NetworkEvent) -> Result<ConnectionState, WorkflowError> {
+        self.state = network_workflow().fire(self.state, event, self)?;
+        Ok(self.state)
+    }
+}
+
+// Connecting 收到 Failed 时有两个候选转换，按声明的顺序选第一个守卫通过的
+fn network_workflow() -> &'static Workflow<ConnectionState, NetworkEvent, NetworkManager> {
+    static WORKFLOW: OnceLock<Workflow<ConnectionState, NetworkEvent, NetworkManager>> =
+        OnceLock::new();
+    WORKFLOW.get_or_init(|| {
+        use ConnectionState::*;
+        use NetworkEvent::*;
+        Workflow::builder("网络连接", Disconnected)
+            .transition(Disconnected, Connect, Connecting)
+            .transition(Connecting, Established, Connected)
+            .action(|manager: &mut NetworkManager, _| manager.retry_count = 0)
+            .transition(Connecting, Failed, Connecting)
+            .guard("可以重试", |manager: &NetworkManager| {
+                manager.retry_count < NetworkManager::MAX_RETRIES
+            })
+            .action(|manager, _| manager.retry_count += 1)
+            .transition(Connecting, Failed, Error)
+            .transition(Connected, Disconnect, Disconnecting)
+            .transition(Disconnecting, Closed, Disconnected)
+            .transition(Error, Reset, Disconnected)
+            .action(|manager, _| manager.retry_count = 0)
+            .build()
+            .expect("网络连接工作流的定义有效")
+    })
+}
+
 fn enhanced_enum_features() {
     println!("=== Rust 1.62+ 枚举增强特性 ===");
 
@@ -1385,54 +1481,73 @@ fn enhanced_enum_features() {
         Error,
     }
 
-    #[derive(Debug)]
-    struct AppConfig {
-        log_level: LogLevel,
-        max_connections: u32,
-    }
+    // 让 LogLevel 可以作为配置字段：从 "info"、"debug" 这样的字符串解析
+    impl crate::config::ConfigType for LogLevel {
+        const TYPE_NAME: &'static str = "log level";
 
-    impl Default for AppConfig {
-        fn default() -> Self {
-            AppConfig {
-                log_level: LogLevel::default(), // 使用默认日志级别
-                max_connections: 100,
+        fn parse_config(raw: &str) -> Option<Self> {
+            match raw.trim().to_ascii_lowercase().as_str() {
+                "info" => Some(LogLevel::Info),
+                "debug" => Some(LogLevel::Debug),
+                "warn" => Some(LogLevel::Warn),
+                "error" => Some(LogLevel::Error),
+                _ => None,
             }
         }
     }
 
+    // config! 宏（见 src/config.rs）生成结构体和 Default 实现，默认值写在字段声明中
+    crate::config! {
+        #[derive(Debug)]
+        struct AppConfig {
+            log_level: LogLevel = "info", // 与 LogLevel 的 #[default] 保持一致
+            max_connections: u32 = 100,
+        }
+    }
+
     // 从环境变量或配置文件中读取配置，如果未设置则使用默认值
     let config = AppConfig::default();
     println!("默认配置：{:?}", config);
+    println!(
+        "默认日志级别一致：{}",
+        config.log_level == LogLevel::default()
+    );
 
-    // 5. 状态机中的默认状态
-    #[derive(Debug, PartialEq, Default)]
-    enum ConnectionState {
-        #[default] // 默认状态
-        Disconnected,
-        Connecting,
-        Connected,
-        Disconnecting,
-        Error,
+    // 设置了环境变量时覆盖默认值（这里用固定的变量列表代替真实的进程环境）
+    let env = vec![("APP_LOG_LEVEL".to_string(), "debug".to_string())];
+    match crate::config::ConfigLoader::new()
+        .env("APP", env)
+        .load::<AppConfig>()
+    {
+        Ok(config) => println!("叠加环境变量后的配置：{:?}", config),
+        Err(e) => println!("配置加载失败：{}", e),
     }
 
-    #[derive(Debug)]
-    struct NetworkManager {
-        state: ConnectionState,
-        retry_count: u32,
-    }
+    // 5. 状态机中的默认状态
+    // ConnectionState 的 #[default] 变体就是工作流的初始状态
+    let mut manager = NetworkManager::default();
+    println!("网络管理器初始状态：{:?}", manager);
 
-    impl Default for NetworkManager {
-        fn default() -> Self {
-            NetworkManager {
-                state: ConnectionState::default(), // 从默认状态开始
-                retry_count: 0,
-            }
+    // 连接失败时先重试，重试次数用尽后进入 Error，Reset 之后从头开始
+    let events = [
+        NetworkEvent::Connect,
+        NetworkEvent::Failed,
+        NetworkEvent::Failed,
+        NetworkEvent::Established,
+        NetworkEvent::Connect, // Connected 状态不能再次连接
+        NetworkEvent::Disconnect,
+        NetworkEvent::Closed,
+    ];
+    for event in events {
+        match manager.handle(event) {
+            Ok(state) => println!(
+                "{:?} -> {:?}（重试 {} 次）",
+                event, state, manager.retry_count
+            ),
+            Err(e) => println!("{:?} 被忽略：{}", event, e),
         }
     }
 
-    let manager = NetworkManager::default();
-    println!("网络管理器初始状态：{:?}", manager);
-
     // 6. 在 API 版本管理中的应用
     #[derive(Debug, PartialEq, Default)]
     enum ApiVersion {
@@ -1534,27 +1649,6 @@ mod tests {
     // 测试交通信号灯系统
     #[test]
     fn test_traffic_light() {
-        #[derive(Debug, Clone)]
-        enum TrafficLight {
-            Red,
-            Yellow,
-            Green,
-        }
-
-        impl TrafficLight {
-            fn next(&self) -> Self {
-                match self {
-                    TrafficLight::Red => TrafficLight::Green,
-                    TrafficLight::Yellow => TrafficLight::Red,
-                    TrafficLight::Green => TrafficLight::Yellow,
-                }
-            }
-
-            fn is_stop(&self) -> bool {
-                matches!(self, TrafficLight::Red)
-            }
-        }
-
         let red = TrafficLight::Red;
         let green = red.next();
         let yellow = green.next();
@@ -1564,6 +1658,9 @@ mod tests {
         assert!(!green.is_stop());
         assert!(!yellow.is_stop());
         assert!(red_again.is_stop());
+        assert_eq!(red_again, red);
+        assert_eq!(TrafficLight::new().after_duration(40), TrafficLight::Green);
+        assert!(!light_workflow().is_final(TrafficLight::Red));
     }
 
     // 测试 HTTP 状态码
@@ -1754,33 +1851,37 @@ mod tests {
 
     #[test]
     fn test_state_machine_with_default() {
-        #[derive(Debug, PartialEq, Default)]
-        enum ConnectionState {
-            #[default]
-            Disconnected,
-            Connecting,
-            Connected,
-            Disconnecting,
-            Error,
-        }
-
-        #[derive(Debug)]
-        struct NetworkManager {
-            state: ConnectionState,
-            retry_count: u32,
-        }
-
-        impl Default for NetworkManager {
-            fn default() -> Self {
-                NetworkManager {
-                    state: ConnectionState::default(),
-                    retry_count: 0,
-                }
-            }
-        }
-
         let manager = NetworkManager::default();
         assert_eq!(manager.state, ConnectionState::Disconnected);
         assert_eq!(manager.retry_count, 0);
     }
+
+    #[test]
+    fn test_network_manager_retries() {
+        let mut manager = NetworkManager::default();
+        manager.handle(NetworkEvent::Connect).unwrap();
+        for _ in 0..NetworkManager::MAX_RETRIES {
+            assert_eq!(
+                manager.handle(NetworkEvent::Failed),
+                Ok(ConnectionState::Connecting)
+            );
+        }
+        assert_eq!(manager.retry_count, 3);
+        assert_eq!(
+            manager.handle(NetworkEvent::Failed),
+            Ok(ConnectionState::Error)
+        );
+        assert!(manager.handle(NetworkEvent::Connect).is_err());
+        assert_eq!(manager.state, ConnectionState::Error);
+
+        manager.handle(NetworkEvent::Reset).unwrap();
+        assert_eq!(manager.retry_count, 0);
+        manager.handle(NetworkEvent::Connect).unwrap();
+        manager.handle(NetworkEvent::Failed).unwrap();
+        assert_eq!(
+            manager.handle(NetworkEvent::Established),
+            Ok(ConnectionState::Connected)
+        );
+        assert_eq!(manager.retry_count, 0);
+    }
 }
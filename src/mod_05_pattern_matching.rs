@@ -644,28 +644,31 @@ fn matches_macro() {
     ); // false
 
     // 场景 3：数据类型检查
-    // 注意：serde_json 是一个外部依赖 crate，需要添加到 Cargo.toml 才能使用
-    // 这里展示的是使用外部 JSON 库的类型检查示例
-    /*
-    fn is_string_value(value: &serde_json::Value) -> bool {
-        matches!(value, serde_json::Value::String(_))
+    // 这里使用本项目的 json 模块（见 src/json.rs），用法与 serde_json::Value 相同
+    use crate::json::JsonValue;
+
+    fn is_string_value(value: &JsonValue) -> bool {
+        matches!(value, JsonValue::String(_))
     }
-    */
 
-    // 这里使用模拟的 JSON 值检查
-    #[derive(Debug)]
-    enum JsonValue {
-        String(String),
-        Number(i64),
-        Boolean(bool),
-        Null,
+    // 嵌套模式：检查数组的第一个元素是否为数字
+    fn starts_with_number(value: &JsonValue) -> bool {
+        matches!(value, JsonValue::Array(items) if matches!(items.first(), Some(JsonValue::Number(_))))
     }
 
-    let string_value = JsonValue::String("hello".to_string());
-    let number_value = JsonValue::Number(42);
+    let string_value = crate::json!("hello");
+    let number_value = crate::json!(42);
+    let array_value = crate::json!([1, "two", null]);
 
-    println!("字符串值: {}", matches!(string_value, JsonValue::String(_))); // true
-    println!("数字值: {}", matches!(number_value, JsonValue::String(_))); // false
+    println!("字符串值: {}", is_string_value(&string_value)); // true
+    println!("数字值: {}", is_string_value(&number_value)); // false
+    println!("数组以数字开头: {}", starts_with_number(&array_value)); // true
+    println!(
+        "数组元素类型: {:?}",
+        array_value
+            .as_array()
+            .map(|items| items.iter().map(JsonValue::type_name).collect::<Vec<_>>())
+    );
 
     // matches! 与 if 表达式的对比
     // 传统的 if let 方式需要更多的代码
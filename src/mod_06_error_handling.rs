@@ -11,6 +11,11 @@
 // Rust 错误处理
 // 深入讲解 Rust 错误处理系统，包括 panic、Result、Option、错误传播和自定义错误类型
 
+use crate::demo_rng::DemoRng;
+// 本章的示例错误类型都实现了 errors::Diagnostic，可以与 errors::Error 无缝组合
+use crate::diagnostics::{Label, SourceDiagnostic};
+use crate::errors::{Chain, Context, Diagnostic, ErrorCode, Report};
+
 // ===========================================
 // 1. panic! 宏和不可恢复错误
 // ===========================================
@@ -352,6 +357,38 @@ fn multiple_error_types() {
         }
     }
 
+    // 实现 Display 和 Error：让 MyError 成为标准错误，source() 暴露底层错误
+    impl std::fmt::Display for MyError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self {
+                MyError::Io(err) => write!(f, "IO 错误: {}", err),
+                MyError::Parse(err) => write!(f, "解析错误: {}", err),
+                MyError::Custom(msg) => write!(f, "{}", msg),
+            }
+        }
+    }
+
+    impl std::error::Error for MyError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                MyError::Io(err) => Some(err),
+                MyError::Parse(err) => Some(err),
+                MyError::Custom(_) => None,
+            }
+        }
+    }
+
+    // 实现 Diagnostic：每个变体对应一个稳定的错误码
+    impl Diagnostic for MyError {
+        fn code(&self) -> ErrorCode {
+            match self {
+                MyError::Io(err) => err.code(),
+                MyError::Parse(err) => err.code(),
+                MyError::Custom(_) => ErrorCode::INVALID_INPUT,
+            }
+        }
+    }
+
     // 使用自定义错误类型
     fn complex_operation(file_path: &str) -> Result<i32, MyError> {
         let mut file = std::fs::File::open(file_path)?; // 可能产生 Io 错误
@@ -373,7 +410,7 @@ fn multiple_error_types() {
     for path in test_paths {
         match complex_operation(path) {
             Ok(result) => println!("操作成功: {}", result),
-            Err(error) => println!("操作失败: {:?}", error),
+            Err(error) => println!("操作失败 [{}]: {}", error.code(), error),
         }
     }
 
@@ -584,6 +621,18 @@ fn custom_error_types() {
         }
     }
 
+    // 实现 Diagnostic：为每种错误分配稳定的错误码，便于日志检索和监控
+    impl Diagnostic for AppError {
+        fn code(&self) -> ErrorCode {
+            match self {
+                AppError::Io(err) => err.code(),
+                AppError::ParseInt(err) => err.code(),
+                AppError::InvalidInput(_) => ErrorCode::INVALID_INPUT,
+                AppError::NetworkError(_) => ErrorCode::NETWORK,
+            }
+        }
+    }
+
     // 使用自定义错误类型
     fn process_file_with_custom_error(file_path: &str) -> Result<i32, AppError> {
         let mut file = std::fs::File::open(file_path)?;
@@ -606,6 +655,14 @@ fn custom_error_types() {
         Err(e) => println!("处理失败: {} - {:?}", e, e),
     }
 
+    // 在调用方附加上下文：Context 把 AppError 转换为 errors::Error 并保留错误码
+    // 报告会沿 source() 链逐层展示：上下文 -> AppError -> 底层 io::Error
+    let result = process_file_with_custom_error(test_file)
+        .with_context(|| format!("加载配置文件 '{}' 失败", test_file));
+    if let Err(e) = result {
+        println!("{}", e.report());
+    }
+
     // 自定义错误的最佳实践：
     // 1. 为错误类型提供清晰的名称
     // 2. 实现 Display trait 提供用户友好的信息
@@ -763,52 +820,86 @@ fn error_handling_best_practices() {
 
 // 通过实际的示例程序展示错误处理的完整应用
 
-fn error_handling_example_program() {
+fn error_handling_example_program(rng: &DemoRng) {
     println!("=== 错误处理示例程序 ===");
 
     // 示例：文件处理系统
+    // 解析和验证错误携带源文本与出错的字节区间，可以渲染成带行号的诊断
+    // 只保存一句 String 会丢失"哪个文件、哪一段"这些定位信息
     #[derive(Debug)]
     enum ProcessingError {
         FileNotFound,
-        ParseError(String),
-        ValidationError(String),
+        ParseError(Box<SourceDiagnostic>),
+        ValidationError(Box<SourceDiagnostic>),
+    }
+
+    impl ProcessingError {
+        fn diagnostic(&self) -> Option<&SourceDiagnostic> {
+            match self {
+                ProcessingError::FileNotFound => None,
+                ProcessingError::ParseError(d) | ProcessingError::ValidationError(d) => Some(d),
+            }
+        }
     }
 
     impl std::fmt::Display for ProcessingError {
         fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
             match self {
                 ProcessingError::FileNotFound => write!(f, "文件未找到"),
-                ProcessingError::ParseError(msg) => write!(f, "解析错误: {}", msg),
-                ProcessingError::ValidationError(msg) => write!(f, "验证错误: {}", msg),
+                ProcessingError::ParseError(d) => write!(f, "解析错误: {}", d),
+                ProcessingError::ValidationError(d) => write!(f, "验证错误: {}", d),
             }
         }
     }
 
     impl std::error::Error for ProcessingError {}
 
+    impl Diagnostic for ProcessingError {
+        fn code(&self) -> ErrorCode {
+            match self {
+                ProcessingError::FileNotFound => ErrorCode::FILE_NOT_FOUND,
+                // 错误码由诊断给出，例如整数解析失败是 E2001
+                ProcessingError::ParseError(d) | ProcessingError::ValidationError(d) => d.code(),
+            }
+        }
+    }
+
     // 处理包含数字的文件
     fn process_numeric_file(file_path: &str) -> Result<Vec<i32>, ProcessingError> {
         // 读取文件
         let content =
             std::fs::read_to_string(file_path).map_err(|_| ProcessingError::FileNotFound)?;
 
-        // 解析内容
+        // 解析内容：逐行记录字节偏移，出错时用区间标出具体的那一段文本
         let mut numbers = Vec::new();
-        for (line_num, line) in content.lines().enumerate() {
-            let line = line.trim();
+        let mut line_start = 0;
+        for (line_num, raw_line) in content.split_inclusive('\n').enumerate() {
+            let start = line_start + (raw_line.len() - raw_line.trim_start().len());
+            line_start += raw_line.len();
+
+            let line = raw_line.trim();
             if line.is_empty() {
                 continue;
             }
-
-            let number: i32 = line.parse().map_err(|e| {
-                ProcessingError::ParseError(format!("第 {} 行 '{}': {}", line_num + 1, line, e))
+            let span = start..start + line.len();
+
+            let number: i32 = line.parse().map_err(|e: std::num::ParseIntError| {
+                ProcessingError::ParseError(Box::new(
+                    SourceDiagnostic::error(format!("第 {} 行无法解析为整数", line_num + 1))
+                        .with_code(ErrorCode::PARSE_INT)
+                        .with_source(file_path, content.as_str())
+                        .with_label(Label::primary(span.clone(), e.to_string()))
+                        .with_note("每行应只包含一个整数"),
+                ))
             })?;
 
             if number < 0 {
-                return Err(ProcessingError::ValidationError(format!(
-                    "第 {} 行: 负数不允许: {}",
-                    line_num + 1,
-                    number
+                return Err(ProcessingError::ValidationError(Box::new(
+                    SourceDiagnostic::error(format!("第 {} 行包含负数", line_num + 1))
+                        .with_code(ErrorCode::VALIDATION)
+                        .with_source(file_path, content.as_str())
+                        .with_label(Label::primary(span, "负数不允许"))
+                        .with_help("删除这一行，或改为非负数"),
                 )));
             }
 
@@ -816,9 +907,11 @@ fn error_handling_example_program() {
         }
 
         if numbers.is_empty() {
-            return Err(ProcessingError::ValidationError(
-                "文件不包含有效数字".to_string(),
-            ));
+            return Err(ProcessingError::ValidationError(Box::new(
+                SourceDiagnostic::error("文件不包含有效数字")
+                    .with_code(ErrorCode::VALIDATION)
+                    .with_source(file_path, content.as_str()),
+            )));
         }
 
         Ok(numbers)
@@ -870,7 +963,13 @@ fn error_handling_example_program() {
                 println!("  数字列表: {:?}", numbers);
                 println!("  统计: 平均值={:.2}, 最大值={}, 总和={}", avg, max, sum);
             }
-            Err(e) => println!("✗ 处理失败: {}", e),
+            Err(e) => {
+                println!("✗ 处理失败 [{}]: {}", e.code(), e);
+                // 带源码的诊断：行号、出错片段和插入符一目了然
+                if let Some(diagnostic) = e.diagnostic() {
+                    print!("{}", diagnostic.render());
+                }
+            }
         }
 
         // 清理测试文件
@@ -930,12 +1029,25 @@ fn error_handling_example_program() {
         }
     }
 
-    fn simulate_network_request(success_rate: f64) -> Result<String, NetworkError> {
+    impl std::error::Error for NetworkError {}
+
+    impl Diagnostic for NetworkError {
+        fn code(&self) -> ErrorCode {
+            match self {
+                NetworkError::Timeout => ErrorCode::TIMEOUT,
+                NetworkError::ConnectionFailed => ErrorCode::CONNECTION_FAILED,
+                NetworkError::InvalidResponse(_) => ErrorCode::INVALID_RESPONSE,
+            }
+        }
+    }
+
+    // 随机数来自带种子的 DemoRng，同一个种子总能重放出相同的成功 / 失败序列
+    fn simulate_network_request(rng: &DemoRng, success_rate: f64) -> Result<String, NetworkError> {
         use std::time::Duration;
         std::thread::sleep(Duration::from_millis(100)); // 模拟网络延迟
 
-        if rand::random::<f64>() > success_rate {
-            let error_type = rand::random::<u8>() % 3;
+        if rng.random::<f64>() > success_rate {
+            let error_type = rng.random_range(0..3u8);
             match error_type {
                 0 => Err(NetworkError::Timeout),
                 1 => Err(NetworkError::ConnectionFailed),
@@ -950,10 +1062,49 @@ fn error_handling_example_program() {
 
     // 模拟多次请求，展示错误处理
     for i in 1..=5 {
-        match simulate_network_request(0.7) {
+        match simulate_network_request(rng, 0.7) {
+            Ok(response) => println!("请求 {}: ✓ {}", i, response),
+            Err(e) => println!("请求 {}: ✗ [{}] {}", i, e.code(), e),
+        }
+    }
+
+    // 用限流器和熔断器保护不稳定的下游服务
+    // 熔断器连续失败 2 次后打开，之后的请求直接快速失败，不再付出网络延迟的代价
+    // 令牌桶限制请求速率，避免在下游恢复期间被突发流量再次压垮
+    use crate::resilience::{
+        BreakerError, CircuitBreaker, CircuitBreakerConfig, RateLimiter, TokenBucket,
+    };
+
+    println!("\n=== 限流与熔断保护 ===");
+    let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+        failure_threshold: 2,
+        success_threshold: 1,
+        open_timeout: std::time::Duration::from_millis(250),
+        half_open_max_calls: 1,
+    });
+    let limiter = TokenBucket::new(3, 20.0);
+
+    for i in 1..=8 {
+        if let Err(limited) = limiter.try_acquire() {
+            println!(
+                "请求 {}: 限流，等待 {} ms",
+                i,
+                limited.retry_after.as_millis()
+            );
+            limiter.acquire_blocking();
+        }
+        match breaker.call(|| simulate_network_request(rng, 0.3)) {
             Ok(response) => println!("请求 {}: ✓ {}", i, response),
-            Err(e) => println!("请求 {}: ✗ {}", i, e),
+            Err(BreakerError::Open { retry_after }) => {
+                println!(
+                    "请求 {}: ⚡ 熔断中，快速失败（{} ms 后重试）",
+                    i,
+                    retry_after.as_millis()
+                )
+            }
+            Err(BreakerError::Inner(e)) => println!("请求 {}: ✗ {}", i, e),
         }
+        println!("  熔断器状态: {:?}", breaker.state());
     }
 
     // 实际应用中的错误处理要点：
@@ -1036,6 +1187,13 @@ fn enhanced_error_trait() {
         // }
     }
 
+    // 在 provide 稳定之前，错误码通过 errors::Diagnostic 以稳定的方式提供
+    impl Diagnostic for DatabaseError {
+        fn code(&self) -> ErrorCode {
+            ErrorCode::DATABASE
+        }
+    }
+
     // 2. 错误链和上下文（Rust 1.76+ 改进）
     // 新的 Error trait 提供了更好的错误链处理能力
 
@@ -1063,6 +1221,15 @@ fn enhanced_error_trait() {
         // }
     }
 
+    impl Diagnostic for NetworkError {
+        fn code(&self) -> ErrorCode {
+            match self.status_code {
+                None => ErrorCode::CONNECTION_FAILED,
+                Some(_) => ErrorCode::INVALID_RESPONSE,
+            }
+        }
+    }
+
     // 创建嵌套的错误链
     fn fetch_user_data(user_id: u32) -> Result<String, DatabaseError> {
         // 模拟数据库查询失败
@@ -1150,6 +1317,12 @@ fn enhanced_error_trait() {
         // }
     }
 
+    impl Diagnostic for ServiceError {
+        fn code(&self) -> ErrorCode {
+            ErrorCode::SERVICE
+        }
+    }
+
     // 使用错误包装
     fn process_user_request(user_id: u32) -> Result<String, ServiceError> {
         fetch_user_data(user_id)
@@ -1160,26 +1333,22 @@ fn enhanced_error_trait() {
     match process_user_request(999) {
         Ok(result) => println!("处理成功: {}", result),
         Err(e) => {
-            println!("包装错误:");
+            println!("包装错误 [{}]:", e.code());
             println!("  服务错误: {}", e);
 
-            // 遍历错误链
-            let mut current_error: &dyn Error = &e;
-            let mut level = 0;
-
-            loop {
-                println!("  层级 {}: {}", level, current_error);
-
-                if let Some(source) = current_error.source() {
-                    current_error = source;
-                    level += 1;
-                } else {
-                    break;
-                }
+            // 遍历错误链：errors::Chain 沿 source() 逐层迭代，第一个元素是错误本身
+            for (level, error) in Chain::new(&e).enumerate() {
+                println!("  层级 {}: {}", level, error);
             }
         }
     }
 
+    // 只为附加一句说明而定义 ServiceError 这样的包装类型往往得不偿失
+    // errors::Context 可以直接给 Result 附加上下文，错误码沿用底层 DatabaseError 的 E4001
+    if let Err(e) = fetch_user_data(999).context("process_user_request: 获取用户数据失败") {
+        println!("{}", e.report());
+    }
+
     // 4. 错误报告和诊断（Rust 1.76+ 改进）
     // 更好的错误报告和诊断信息
 
@@ -1210,6 +1379,31 @@ fn enhanced_error_trait() {
         // }
     }
 
+    impl Diagnostic for ConfigError {
+        fn code(&self) -> ErrorCode {
+            match self.expected_type.as_str() {
+                "integer" | "boolean" => ErrorCode::CONFIG_INVALID_VALUE,
+                _ => ErrorCode::CONFIG_UNSUPPORTED_TYPE,
+            }
+        }
+    }
+
+    impl ConfigError {
+        // 与 crate::config 的取值错误渲染成同样的诊断：主标签指向取值，次要标签说明期望的类型
+        fn diagnostic(&self) -> SourceDiagnostic {
+            let diagnostic = crate::config::invalid_value_diagnostic(
+                &self.key,
+                &self.value,
+                &self.expected_type,
+                self.code(),
+            );
+            match self.expected_type.as_str() {
+                "integer" | "boolean" => diagnostic,
+                _ => diagnostic.with_note("支持的类型: integer, boolean"),
+            }
+        }
+    }
+
     fn parse_config_value(key: &str, value: &str, expected_type: &str) -> Result<(), ConfigError> {
         match expected_type {
             "integer" => {
@@ -1249,7 +1443,8 @@ fn enhanced_error_trait() {
             Ok(_) => println!("配置 '{}' = '{}' (类型: {}) ✓", key, value, expected_type),
             Err(e) => {
                 println!("配置 '{}' = '{}' (类型: {}) ✗", key, value, expected_type);
-                println!("  错误: {}", e);
+                println!("  错误 [{}]: {}", e.code(), e);
+                print!("{}", e.diagnostic().render());
 
                 // 使用增强的错误信息（需要 nightly 编译器和 unstable 特性）
                 // if let Some(config_key) = std::error::request_value::<String>(&e, "config_key") {
@@ -1265,12 +1460,10 @@ fn enhanced_error_trait() {
     // 5. 错误日志和监控（Rust 1.76+ 改进）
     // 更好的错误日志记录和监控支持
 
-    fn log_error_enhanced(error: &dyn Error) {
-        println!("[ERROR] {}", error);
-
-        // 记录错误源信息
-        if let Some(source) = error.source() {
-            println!("[ERROR]   源: {}", source);
+    fn log_error_enhanced(error: &(dyn Error + 'static)) {
+        // errors::Report 把整条错误链渲染成多行报告，逐行加上日志前缀
+        for line in Report::new(error).to_string().lines() {
+            println!("[ERROR] {}", line);
         }
 
         // 记录结构化的错误信息
@@ -1299,7 +1492,12 @@ fn enhanced_error_trait() {
 
     log_error_enhanced(&complex_error);
 
+    // 转换为 errors::Error 后，报告的第一行还会带上错误码
+    let complex_error: crate::errors::Error = complex_error.into();
+    log_error_enhanced(&complex_error);
+
     // Rust 1.76+ Error trait 改进的最佳实践：
+    // 0. 在 provide 稳定之前，用 errors::Diagnostic 提供错误码这类结构化信息
     // 1. 实现provide方法：为错误提供丰富的上下文信息
     // 2. 正确的错误链：使用source()方法提供错误溯源
     // 3. 结构化错误信息：使用request_value等方法提供结构化数据
@@ -1326,7 +1524,9 @@ pub fn main() {
     custom_error_types();
     error_handling_best_practices();
     enhanced_error_trait();
-    error_handling_example_program();
+    // 本章的随机演示使用从全局种子派生的独立随机源
+    let rng = crate::demo_rng::global().derive("mod_06_error_handling");
+    error_handling_example_program(&rng);
 
     println!("错误处理演示完成！");
 }
@@ -1487,6 +1687,28 @@ mod tests {
         assert!(error_msg.contains("读取文件"));
     }
 
+    #[test]
+    fn test_error_context_with_toolkit() {
+        // 与上一个测试相同的场景，改用 errors::Context：上下文、错误码和原始错误都被保留
+        fn read_number(path: &str) -> Result<i32, crate::errors::Error> {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("读取文件 '{}' 失败", path))?;
+            let number = text.trim().parse().context("文件内容不是整数")?;
+            Ok(number)
+        }
+
+        let error = read_number("nonexistent.txt").unwrap_err();
+        assert_eq!(error.code(), ErrorCode::FILE_NOT_FOUND);
+        assert_eq!(error.to_string(), "读取文件 'nonexistent.txt' 失败");
+        assert!(error.downcast_ref::<std::io::Error>().is_some());
+        assert!(
+            error
+                .report()
+                .to_string()
+                .starts_with("错误[E1002]: 读取文件")
+        );
+    }
+
     // Rust 1.76+ 增强的 Error trait 特性测试
     #[test]
     fn test_enhanced_error_trait() {
@@ -1054,26 +1054,36 @@ fn generic_example_program() {
     }
 
     // 配置管理器
+    // 取值统一保存在 crate::config::ConfigValues 中，泛型方法按调用方指定的类型解析
+    // 用 trait 约束 T: ConfigType 代替 Box<dyn Any> 和向下转型：类型不匹配时得到说明原因的 ConfigError
+    use crate::config::{ConfigError, ConfigType, ConfigValues, Source};
+
     struct ConfigManager {
-        configs: Vec<Box<dyn std::any::Any>>, // 使用 Any 存储不同类型的配置
+        values: ConfigValues,
+        descriptions: std::collections::HashMap<String, String>,
     }
 
     impl ConfigManager {
         fn new() -> Self {
             ConfigManager {
-                configs: Vec::new(),
+                values: ConfigValues::new(),
+                descriptions: std::collections::HashMap::new(),
             }
         }
 
-        fn add_config<T: 'static>(&mut self, config: Config<T>) {
-            self.configs.push(Box::new(config));
+        fn add_config<T: std::fmt::Display>(&mut self, config: Config<T>) {
+            self.values.insert(
+                config.name.clone(),
+                config.value.to_string(),
+                Source::Override,
+            );
+            self.descriptions.insert(config.name, config.description);
         }
 
-        fn get_config<T: 'static>(&self, name: &str) -> Option<&Config<T>> {
-            self.configs
-                .iter()
-                .filter_map(|config| config.downcast_ref::<Config<T>>())
-                .find(|config| config.name == name)
+        fn get_config<T: ConfigType>(&self, name: &str) -> Result<Config<T>, ConfigError> {
+            let value = self.values.get::<T>(name)?;
+            let description = self.descriptions.get(name).cloned().unwrap_or_default();
+            Ok(Config::new(name.to_string(), value, description))
         }
     }
 
@@ -1095,7 +1105,7 @@ fn generic_example_program() {
         "调试模式".to_string(),
     ));
 
-    if let Some(timeout_config) = manager.get_config::<i32>("timeout") {
+    if let Ok(timeout_config) = manager.get_config::<i32>("timeout") {
         println!(
             "超时配置: {} = {}",
             timeout_config.name,
@@ -1103,10 +1113,15 @@ fn generic_example_program() {
         );
     }
 
-    if let Some(debug_config) = manager.get_config::<bool>("debug_mode") {
+    if let Ok(debug_config) = manager.get_config::<bool>("debug_mode") {
         println!("调试配置: {} = {}", debug_config.name, debug_config.get());
     }
 
+    // 按错误的类型读取时，错误信息包含配置项、取值和期望的类型
+    if let Err(e) = manager.get_config::<bool>("max_connections") {
+        println!("类型不匹配: {}", e);
+    }
+
     // 泛型示例程序的要点：
     // 1. 数据结构抽象：使用泛型实现通用的数据结构
     // 2. 算法抽象：使用泛型实现类型无关的算法
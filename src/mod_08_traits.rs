@@ -808,24 +808,6 @@ fn operator_overloading_and_default_generics() {
         }
     }
 
-    // 复数类型
-    #[derive(Debug, Clone, Copy, PartialEq)]
-    struct Complex {
-        real: f64,
-        imag: f64,
-    }
-
-    impl Add for Complex {
-        type Output = Self;
-
-        fn add(self, other: Self) -> Self::Output {
-            Complex {
-                real: self.real + other.real,
-                imag: self.imag + other.imag,
-            }
-        }
-    }
-
     // 其他运算符的实现
     use std::ops::{Div, Mul, Neg, Sub};
 
@@ -875,19 +857,33 @@ fn operator_overloading_and_default_generics() {
     println!("-v1 = {:?}", -v1);
     println!("v1 + 1.5 = {:?}", v1 + 1.5);
 
-    let c1 = Complex {
-        real: 1.0,
-        imag: 2.0,
-    };
-    let c2 = Complex {
-        real: 3.0,
-        imag: 4.0,
-    };
+    // crate::num::Vec2 / Vec3 在这些运算之外还有点积、叉积和归一化
+    use crate::num::{Vec2, Vec3};
+    let heading = Vec2::new(3.0, 4.0);
+    println!(
+        "{} 的单位向量: {}",
+        heading,
+        heading.normalize().expect("非零向量")
+    );
+    println!("x × y = {}", Vec3::new(1, 0, 0).cross(Vec3::new(0, 1, 0)));
+
+    // crate::num::Complex 是泛型的完整实现：四则运算对值和引用的四种组合都有 impl，
+    // 还有极坐标、exp / ln / pow，Display 输出的文本可以用 parse 读回来
+    use crate::num::Complex;
+    let c1 = Complex::new(1.0, 2.0);
+    let c2: Complex<f64> = "3+4i".parse().expect("合法的复数");
 
     println!("\n复数运算示例:");
-    println!("c1 = {:?}", c1);
-    println!("c2 = {:?}", c2);
-    println!("c1 + c2 = {:?}", c1 + c2);
+    println!("c1 = {}", c1);
+    println!("c2 = {}", c2);
+    println!("c1 + c2 = {}", c1 + c2);
+    println!("c1 * c2 = {}", c1 * c2);
+    println!("c2 / c1 = {}", c2 / c1);
+    println!(
+        "|c2| = {}, e^(iπ) = {:.3}",
+        c2.norm(),
+        (Complex::I * std::f64::consts::PI).exp()
+    );
 
     // 运算符重载的最佳实践：
     // 1. 语义一致性：运算符的行为应该符合数学直觉
@@ -1312,6 +1308,17 @@ fn newtype_pattern_and_type_safety() {
     let kilometers = Kilometers(2);
     println!("从千米转换: {}", kilometers.to_meters());
 
+    // 每种单位写一个 newtype 很快就不够用了：米除以秒该是什么类型？
+    // crate::units 把量纲放进类型参数，导出量纲由运算自动推出，词头由 from_prefixed 换算
+    use crate::units::{Length, Prefix, Time, Velocity};
+    let lap = Length::from_prefixed(1.5, Prefix::Kilo).expect("1.5 km 换算成米不会溢出");
+    let speed: Velocity = lap / Time::seconds(300.0);
+    println!(
+        "{} 用时 5 分钟，速度 {}",
+        lap.display_in(Prefix::Kilo).unwrap(),
+        speed
+    );
+
     // Newtype 模式的高级用法
     // 1. 为外部类型实现外部 trait
     struct Wrapper(Vec<String>);
@@ -1630,6 +1637,76 @@ fn comprehensive_graphics_system() {
         println!("  {}", rendering);
     }
 
+    // GraphicsManager 的命中测试忽略了旋转：绿色矩形转了 45°，(52, 202) 已经在它外面
+    println!(
+        "\nGraphicsManager 点击 (52, 202): {:?}",
+        manager.handle_click(52.0, 202.0)
+    );
+
+    // crate::scene 用场景图解决这些问题：节点有父子关系和仿射变换，命中测试通过逆变换进行
+    use crate::image::Rgb;
+    use crate::scene::{Pick, Point, Scene, Shape, Style, Transform, svg};
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let shapes = [
+        (
+            "红色矩形",
+            Shape::rect(100.0, 50.0),
+            Rgb([220, 40, 40]),
+            Transform::translate(10.0, 10.0),
+        ),
+        (
+            "蓝色圆形",
+            Shape::circle(30.0),
+            Rgb([40, 80, 220]),
+            Transform::translate(200.0, 100.0),
+        ),
+        (
+            "绿色矩形",
+            Shape::rect(80.0, 80.0),
+            Rgb([40, 160, 80]),
+            Transform::rotate_about(45f64.to_radians(), Point::new(40.0, 40.0))
+                .then(Transform::translate(50.0, 200.0)),
+        ),
+    ];
+    for (name, shape, color, transform) in shapes {
+        let id = scene
+            .add_shape(root, name, shape, Style::fill(color))
+            .expect("根节点是组");
+        scene.node_mut(id).unwrap().transform = transform;
+    }
+    let name_at = |scene: &Scene, x: f64, y: f64| {
+        scene
+            .hit_test(Point::new(x, y))
+            .map(|id| scene.node(id).unwrap().name.clone())
+    };
+    println!("场景图点击 (60, 35): {:?}", name_at(&scene, 60.0, 35.0));
+    println!("场景图点击 (52, 202): {:?}", name_at(&scene, 52.0, 202.0));
+    println!("场景图点击 (90, 190): {:?}", name_at(&scene, 90.0, 190.0));
+
+    // 点选红色矩形，再加选蓝色圆形，合并成组后整体平移
+    scene.select_at(Point::new(60.0, 35.0), Pick::Group);
+    scene
+        .toggle_selected(scene.find("蓝色圆形").unwrap())
+        .unwrap();
+    let group = scene.group_selected("选中的图形").unwrap();
+    scene
+        .transform_selected(Transform::translate(10.0, 5.0))
+        .unwrap();
+    let bounds = scene.world_bounds(group).expect("组中有图形");
+    println!(
+        "合并后的组包含 {} 个图形，包围盒 {} - {}",
+        scene.node(group).unwrap().children().len(),
+        bounds.min,
+        bounds.max
+    );
+    let document = svg::render(&scene, 320, 320);
+    println!(
+        "SVG 输出 {} 行，第二行: {}",
+        document.lines().count(),
+        document.lines().nth(1).unwrap()
+    );
+
     // 这个综合示例展示了：
     // 1. trait 层次结构的设计
     // 2. 动态分发与 trait 对象的使用
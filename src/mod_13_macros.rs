@@ -583,6 +583,16 @@ fn procedural_macros_basics() {
     let s = stringify!(hello world); // 将输入转换为字符串字面量
     println!("字符串化: {}", s);
 
+    // 本项目的过程宏：crates/guide-macros 是一个 proc-macro crate，三种过程宏各有一个例子
+    // 1. 派生宏 #[derive(Describe)]：见第 6 节
+    // 2. 属性宏 #[timed]：见第 7 节
+    // 3. 函数宏 sql!：编译期解析 SQL 字符串，语法错误直接成为编译错误；
+    //    展开结果是规范化后的字符串字面量，运行时没有任何解析开销
+    let query = guide_macros::sql!("select name, age from users where age >= ? order by age");
+    println!("sql! 展开结果: {}", query);
+    // 写成 sql!("SELECT name FORM users") 会在编译时报错：
+    // SQL 语法错误: 第 13 个字符处应为 FROM，但遇到 'FORM'
+
     // 编译时信息宏：提供编译时的元信息
     // 这些宏在编译时展开，提供源码位置信息
     let file = file!(); // 当前文件名
@@ -661,56 +671,79 @@ fn custom_derive_macros() {
 
     println!("文章摘要: {}", article.summarize());
 
-    // 模拟自定义派生宏的功能
-    // 在实际的项目中，我们需要创建一个过程宏 crate
-    // 这里用声明式宏来模拟派生宏的行为
-    macro_rules! impl_summary {
-        ($struct_name:ident) => {
-            // 自动为指定类型实现 Summary trait
-            impl Summary for $struct_name {
-                fn summarize(&self) -> String {
-                    format!("自定义摘要: {:?}", self)
-                }
-            }
-        };
-    }
-
-    // 使用模拟的派生宏
-    #[derive(Debug)]
+    // 使用真实的派生宏
+    // crates/guide-derive 是这个项目中的过程宏 crate，#[derive(Summary)] 读取结构体的字段，
+    // 生成 impl Summary for Book。生成的代码按名字引用 Summary，所以用的是上面定义的 trait
+    #[derive(Debug, guide_derive::Summary)]
     struct Book {
         title: String,
         pages: u32,
     }
 
-    // 调用宏来自动实现 trait
-    impl_summary!(Book);
-
     let book = Book {
         title: "The Rust Book".to_string(),
         pages: 500,
     };
 
+    // 默认格式：类型名(字段: 值, ...)
     println!("图书摘要: {}", book.summarize());
 
+    // 通过属性进行配置：模板中直接写字段名，不需要展示的字段不用出现
+    #[derive(Debug, guide_derive::Summary)]
+    #[summary(template = "{title} 第 {edition} 版")]
+    struct Edition {
+        title: String,
+        edition: u32,
+        isbn: String,
+    }
+
+    let edition = Edition {
+        title: "The Rust Book".to_string(),
+        edition: 2,
+        isbn: "978-1718503106".to_string(),
+    };
+
+    println!("版本摘要: {}", edition.summarize());
+
+    // 派生宏不一定要实现 trait，也可以生成固有方法
+    // #[derive(Describe)] 在编译期读取类型的声明和文档注释，生成 describe()；
+    // 枚举还会生成 variant_name()，返回当前值的变体名
+    /// 订单状态
+    #[derive(Debug, guide_macros::Describe)]
+    enum OrderStatus {
+        /// 等待付款
+        Pending,
+        Shipped {
+            tracking: String,
+        },
+        Cancelled(String),
+    }
+
+    println!("类型描述:\n{}", OrderStatus::describe());
+    let status = OrderStatus::Shipped {
+        tracking: "SF1024".to_string(),
+    };
+    println!("当前变体: {}", status.variant_name());
+
     // 真实的自定义派生宏结构
-    // 一个真实的派生宏项目通常包含：
-    // 1. 过程宏 crate（名称以 -proc-macro 结尾）
-    // 2. 宏定义文件（lib.rs）
-    // 3. 主要逻辑函数
+    // 一个派生宏项目通常包含：
+    // 1. 过程宏 crate（Cargo.toml 中设置 proc-macro = true）
+    // 2. 宏入口（lib.rs），用 #[proc_macro_derive] 标注
+    // 3. 主要逻辑函数：用 syn 解析输入，用 quote 生成代码
     /*
-    // 以下是真实派生宏的代码结构：
-    // 在 proc-macro crate 中：
+    // crates/guide-derive/src/lib.rs 中的入口：
     use proc_macro::TokenStream;
-    use quote::quote;
-    use syn;
+    use syn::{DeriveInput, parse_macro_input};
 
-    #[proc_macro_derive(Summary)]
-    pub fn summary_derive(input: TokenStream) -> TokenStream {
+    #[proc_macro_derive(Summary, attributes(summary))]
+    pub fn derive_summary(input: TokenStream) -> TokenStream {
         // 解析输入的语法树
-        let ast = syn::parse(input).unwrap();
+        let input = parse_macro_input!(input as DeriveInput);
 
-        // 生成实现代码
-        impl_summary(&ast)
+        // 生成实现代码；用法错误转换成指向出错位置的编译错误
+        summary::expand(&input)
+            .unwrap_or_else(syn::Error::into_compile_error)
+            .into()
     }
     */
 
@@ -815,6 +848,30 @@ fn attribute_macros() {
     let result = example_function(5);
     println!("示例函数结果: {}", result);
 
+    // 自定义属性宏：#[timed]（crates/guide-macros）
+    // 属性宏收到被修饰函数的完整语法树，可以改写函数体：#[timed] 在函数开头放一个计时守卫，
+    // 函数无论从哪里返回（包括 ? 提前返回），守卫被 drop 时都会报告耗时
+    #[guide_macros::timed]
+    fn sum_to(n: u64) -> u64 {
+        (1..=n).sum()
+    }
+
+    println!("sum_to(1000) = {}", sum_to(1000));
+
+    // 属性宏可以接受参数：name 指定报告中的名字，with 指定报告函数
+    fn report(name: &'static str, elapsed: std::time::Duration) {
+        println!("[计时] {} 用时 {} 微秒", name, elapsed.as_micros());
+    }
+
+    #[guide_macros::timed(name = "解析数字", with = report)]
+    fn parse_number(text: &str) -> Result<i32, std::num::ParseIntError> {
+        let value = text.trim().parse()?;
+        Ok(value)
+    }
+
+    println!("解析结果: {:?}", parse_number(" 42 "));
+    println!("解析结果: {:?}", parse_number("abc"));
+
     // 属性宏的语法和工作原理：
     // 1. 语法：#[attr] 或 #[attr(arg)] 或 #[attr(key = value)]
     // 2. 应用：可以应用到各种语法项上
@@ -916,6 +973,24 @@ fn function_like_macros() {
     println!("当前行号: {}", line!()); // 当前行号
     println!("当前列号: {}", column!()); // 当前列号
 
+    // 自定义函数宏：sql!（crates/guide-macros）
+    // 宏在编译期按 crate::minidb 支持的语法检查 SQL，展开为 &'static str，可以直接交给数据库执行
+    let mut db = crate::minidb::Database::new();
+    let statements = [
+        guide_macros::sql!("create table users (name text not null, age integer)"),
+        guide_macros::sql!("insert into users values ('Alice', 30), ('Bob', 17)"),
+    ];
+    for statement in statements {
+        db.execute(statement, &[]).unwrap();
+    }
+    let adults = db
+        .execute(
+            guide_macros::sql!("select name from users where age >= ?"),
+            &[18.into()],
+        )
+        .unwrap();
+    println!("成年用户: {:?}", adults.rows);
+
     // 函数宏的语法和调用形式：
     // 1. 基本语法：macro_name!(token_stream)
     // 2. 可以接受任意数量的参数
@@ -1061,7 +1136,8 @@ fn advanced_macro_patterns() {
     }
 
     // 使用计时宏计算 1 到 1000 的和
-    // 注意：由于宏语法限制，这里简化实现
+    // 注意：由于宏语法限制，这里简化实现；
+    // 第 7 节的属性宏 #[timed] 是它的过程宏版本，直接修饰整个函数
     let start = std::time::Instant::now();
     let mut total = 0;
     for i in 1..=1000 {
@@ -1166,150 +1242,138 @@ fn macro_example_program() {
     // 4. 支持自定义的断言格式
 
     // SQL 查询构建器宏（SQL Query Builder Macro）
-    // 创建一个简单的 SQL 查询构建器，演示宏的 DSL 能力
-    // 注意：由于宏解析的复杂性，这里使用简化的实现
-    macro_rules! select {
-        // 基本 SELECT 查询：SELECT columns FROM table
-        (columns: [$($column:expr),*], table: $table:expr) => {
-            // 使用 stringify! 将列名转换为字符串
-            format!("SELECT {} FROM {}", stringify!($($column),*), $table)
-        };
-        // 带条件的 SELECT 查询：SELECT columns FROM table WHERE condition
-        (columns: [$($column:expr),*], table: $table:expr, where: $condition:expr) => {
-            format!("SELECT {} FROM {} WHERE {}", stringify!($($column),*), $table, $condition)
-        };
+    // crate::query! 定义在 src/sql.rs，演示宏的 DSL 能力：
+    // 宏按子句逐段解析接近 SQL 的写法（tt muncher），展开为类型化构建器 crate::sql::Query 的调用
+    // 取值不会拼接进 SQL 文本，而是成为 ? 占位符对应的参数
+    let min_age = 18;
+    let query1 = crate::query!(SELECT id, name FROM users);
+    let query2 = crate::query!(
+        SELECT id, name FROM users WHERE age > min_age ORDER BY name LIMIT 10
+    );
+
+    for (label, query) in [("1", query1), ("2", query2)] {
+        match query.build() {
+            Ok(query) => println!(
+                "SQL 查询 {}: {}  参数: {:?}",
+                label, query.sql, query.params
+            ),
+            Err(e) => println!("SQL 查询 {} 构建失败: {}", label, e),
+        }
     }
 
-    // 使用 SQL 查询构建器
-    let query1 = select!(columns: [id, name], table: "users");
-    let query2 = select!(columns: [id, name], table: "users", where: "age > 18");
+    // 恶意输入只会成为一个普通的参数值，无法改变 SQL 语句的结构
+    let user_input = "x'; DROP TABLE users; --";
+    if let Ok(query) = crate::query!(DELETE FROM sessions WHERE user_name = user_input).build() {
+        println!("SQL 查询 3: {}  参数: {:?}", query.sql, query.params);
+    }
 
-    println!("SQL 查询 1: {}", query1);
-    println!("SQL 查询 2: {}", query2);
+    // 同一套构建器也支持 INSERT / UPDATE
+    if let Ok(query) =
+        crate::query!(UPDATE users SET age = 31 WHERE id = 1 AND deleted_at IS NULL).build()
+    {
+        println!("SQL 查询 4: {}", query.to_inline_sql());
+    }
 
     // SQL 构建器的优势：
     // 1. 类型安全的 SQL 构建方式
-    // 2. 避免手动拼接字符串的错误
-    // 3. 可以扩展支持更多 SQL 语法
-    // 4. 易于维护和修改
+    // 2. 取值以参数传递，避免 SQL 注入
+    // 3. 支持 SELECT / INSERT / UPDATE / DELETE，可以继续扩展更多 SQL 语法
+    // 4. 宏只是构建器的前端，两种写法生成完全相同的查询
 
     // JSON 构建器宏（JSON Builder Macro）
-    // 创建一个简单的 JSON 构建器，演示宏处理复杂数据结构的能力
-    macro_rules! json {
-        // JSON 对象构建：{"key": "value", ...}
-        ({ $($key:expr => $value:expr),* }) => {
-            {
-                let mut obj = std::collections::HashMap::new();
-                $(
-                    obj.insert($key.to_string(), $value.to_string());
-                )*
-                obj
-            }
-        };
-        // JSON 数组构建：["value1", "value2", ...]
-        ([$($value:expr),*]) => {
-            {
-                let mut arr = Vec::new();
-                $(
-                    arr.push($value.to_string());
-                )*
-                arr
-            }
-        };
-    }
-
-    // 使用 JSON 构建器
-    let user = json!({
-        "name" => "Alice",
-        "age" => "30",
-        "email" => "alice@example.com"
+    // crate::json! 定义在 src/json.rs，演示宏处理复杂数据结构的能力：
+    // 对象和数组按 token 树递归展开，可以任意嵌套；叶子值通过 From 转换成带类型的 JsonValue
+    let name = "Alice";
+    let user = crate::json!({
+        "name": name,
+        "age": 30,
+        "email": "alice@example.com",
+        "verified": true,
+        "address": { "city": "Beijing", "zip": null }
     });
 
-    let scores = json!(["95", "87", "92"]);
+    let scores = crate::json!([95, 87, 92]);
 
-    println!("JSON 对象: {:?}", user);
-    println!("JSON 数组: {:?}", scores);
+    println!("JSON 对象: {}", user);
+    println!("JSON 数组: {}", scores);
+    println!("格式化输出:\n{:#}", user);
+
+    // 构建出的是带类型的值，可以按路径取回原始类型
+    println!(
+        "城市: {:?}",
+        user.pointer("/address/city").and_then(|v| v.as_str())
+    );
+    println!("年龄 + 1 = {:?}", user["age"].as_i64().map(|age| age + 1));
+
+    // 同一个值也可以从文本解析得到，两种方式的结果完全相同
+    let parsed: crate::json::JsonValue = user.to_string().parse().unwrap();
+    println!("解析结果与宏构建结果相同: {}", parsed == user);
 
     // JSON 构建器的优势：
-    // 1. 声明式的 JSON 构建语法
-    // 2. 避免手动的 JSON 字符串拼接
-    // 3. 类型安全的值转换
-    // 4. 可以扩展支持更多 JSON 类型
+    // 1. 声明式的 JSON 构建语法，与 JSON 文本几乎一致
+    // 2. 避免手动的 JSON 字符串拼接和转义
+    // 3. 数字、布尔值、null 保留各自的类型，而不是统统变成字符串
+    // 4. 递归展开，支持任意层级的嵌套
 
     // 配置管理宏（Configuration Management Macro）
-    // 创建一个配置管理系统，演示宏的元编程能力
-    macro_rules! config {
-        // 配置项定义：key: value
-        ($($key:ident: $value:expr),*) => {
-            {
-                let mut cfg = std::collections::HashMap::new();
-                $(
-                    // 使用 stringify! 将标识符转换为字符串
-                    cfg.insert(stringify!($key).to_string(), $value.to_string());
-                )*
-                cfg
-            }
-        };
+    // crate::config! 定义在 src/config.rs，演示宏的元编程能力：
+    // 一次声明同时生成结构体、字段 schema（键名、类型名、默认值、说明）和 Default 实现
+    crate::config! {
+        #[derive(Debug)]
+        struct AppSettings in "app" {
+            /// 调试模式
+            debug: bool = false,
+            /// 监听端口
+            port: u16 = 8080,
+            /// 请求超时
+            timeout: std::time::Duration = "30s",
+            /// 日志级别
+            log_level: String = "info",
+        }
     }
 
-    // 使用配置管理宏
-    let app_config = config! {
-        debug: true,
-        port: 8080,
-        timeout: 30,
-        log_level: "info"
-    };
+    // 默认值之上叠加覆盖值，字符串统一按字段类型解析
+    let loader = crate::config::ConfigLoader::new().set("app.debug", true);
+    match loader.load::<AppSettings>() {
+        Ok(app_config) => println!("应用配置: {:?}", app_config),
+        Err(e) => println!("配置加载失败: {}", e),
+    }
 
-    println!("应用配置: {:?}", app_config);
+    // 宏生成的 schema 可以用来输出配置说明
+    use crate::config::ConfigSchema;
+    for field in AppSettings::fields() {
+        println!(
+            "  {} ({}, 默认 {}): {}",
+            field.key, field.type_name, field.default, field.description
+        );
+    }
+
+    // 类型错误会指出配置项、取值和期望的类型
+    if let Err(e) = crate::config::ConfigLoader::new()
+        .set("app.port", "eighty")
+        .load::<AppSettings>()
+    {
+        println!("{}", e);
+    }
 
     // 配置管理的优势：
     // 1. 声明式的配置语法
-    // 2. 自动类型转换
-    // 3. 易于扩展和维护
-    // 4. 可以支持嵌套配置结构
+    // 2. 字段带类型，加载时统一校验
+    // 3. 默认值、说明与字段写在一起，易于扩展和维护
+    // 4. 通过节名（in "app"）支持分组的配置结构
 
     // 代码生成宏（Code Generation Macro）
     // 自动生成结构体的访问器方法，减少样板代码
-    // 注意：由于宏限制，这里简化为手动实现
-    macro_rules! generate_accessors {
-        // 为结构体生成访问器方法
-        ($struct_name:ident, $($field:ident: $type:ty),*) => {
-            impl $struct_name {
-                // 手动实现访问器方法作为示例
-                // 在实际项目中，可以使用 paste crate 来实现更复杂的方法名生成
-                pub fn name(&self) -> &String {
-                    &self.name
-                }
-                pub fn name_mut(&mut self) -> &mut String {
-                    &mut self.name
-                }
-                pub fn age(&self) -> &u32 {
-                    &self.age
-                }
-                pub fn age_mut(&mut self) -> &mut u32 {
-                    &mut self.age
-                }
-                pub fn email(&self) -> &String {
-                    &self.email
-                }
-                pub fn email_mut(&mut self) -> &mut String {
-                    &mut self.email
-                }
-            }
-        };
-    }
-
-    // 定义一个结构体并使用代码生成宏
-    #[derive(Debug)]
+    // macro_rules! 无法拼接出 name_mut 这样的新标识符，所以这里用 crates/guide-derive 中的派生宏：
+    // #[derive(Getters)] 为每个字段生成 name()，#[getter(mut)] 另外生成 name_mut()
+    #[derive(Debug, guide_derive::Getters)]
+    #[getter(mut)]
     struct Person {
         name: String,
         age: u32,
         email: String,
     }
 
-    // 为 Person 结构体生成访问器
-    generate_accessors!(Person, name: String, age: u32, email: String);
-
     let mut person = Person {
         name: "Bob".to_string(),
         age: 25,
@@ -1503,6 +1567,74 @@ mod tests {
         assert_eq!(p1, p2);
     }
 
+    #[test]
+    fn test_guide_derive_macros() {
+        trait Summary {
+            fn summarize(&self) -> String;
+        }
+
+        #[derive(guide_derive::Getters, guide_derive::Summary)]
+        #[getter(mut)]
+        struct Person {
+            name: String,
+            age: u32,
+        }
+
+        let mut person = Person {
+            name: "Bob".to_string(),
+            age: 25,
+        };
+        *person.age_mut() += 1;
+        assert_eq!(person.name(), "Bob");
+        assert_eq!(*person.age(), 26);
+        assert_eq!(person.summarize(), "Person(name: Bob, age: 26)");
+    }
+
+    #[test]
+    fn test_guide_macros() {
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        static CALLS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+        fn record(name: &'static str, _: Duration) {
+            CALLS.lock().unwrap().push(name);
+        }
+
+        #[guide_macros::timed(with = record)]
+        fn halve(n: u32) -> Option<u32> {
+            if n % 2 == 1 {
+                return None;
+            }
+            Some(n / 2)
+        }
+
+        assert_eq!(halve(4), Some(2));
+        assert_eq!(halve(3), None);
+        assert_eq!(*CALLS.lock().unwrap(), ["halve", "halve"]);
+
+        #[derive(guide_macros::Describe)]
+        enum Light {
+            Red,
+            Green { seconds: u32 },
+        }
+        assert_eq!(
+            Light::describe(),
+            "enum Light {\n    Red,\n    Green { seconds: u32 },\n}"
+        );
+        assert_eq!(Light::Green { seconds: 30 }.variant_name(), "Green");
+
+        let mut db = crate::minidb::Database::new();
+        db.execute(guide_macros::sql!("create table t (n integer)"), &[])
+            .unwrap();
+        db.execute(guide_macros::sql!("insert into t values (?)"), &[7.into()])
+            .unwrap();
+        let rows = db
+            .execute(guide_macros::sql!("select n from t where n > 5"), &[])
+            .unwrap();
+        assert_eq!(rows.rows, vec![vec![7.into()]]);
+    }
+
     #[test]
     fn test_conditional_macro() {
         macro_rules! conditional {
@@ -80,6 +80,19 @@ fn ffi_basics() {
     let result = add_numbers(10, 20);
     println!("add_numbers(10, 20) = {}", result);
 
+    // 完整的反方向示例见 crate::capi：本库以 cdylib / staticlib 形式导出一组 C 接口，
+    // 头文件是 include/guide.h，tests/c/capi_test.c 是一个真正的 C 调用方
+    // 这些导出函数在 Rust 里同样可以直接调用，只是要按 C 的约定传指针、查错误码
+    unsafe {
+        let mut analyzer = std::ptr::null_mut();
+        let text = c"Rust calls C, C calls Rust";
+        crate::capi::guide_text_analyzer_new(text.as_ptr(), &mut analyzer);
+        let mut stats = crate::capi::GuideTextStats::default();
+        crate::capi::guide_text_analyzer_stats(analyzer, &mut stats);
+        println!("guide_text_analyzer_stats: {:?}", stats);
+        crate::capi::guide_text_analyzer_free(analyzer);
+    }
+
     // FFI 调用约定的重要性：
     // 1. 参数传递方式：参数如何传递到栈或寄存器
     // 2. 栈清理责任：调用者还是被调用者清理栈
@@ -144,15 +157,26 @@ fn data_type_mapping() {
     // 结构体映射 (Struct Mapping)
     // #[repr(C)] 确保结构体在内存中的布局与 C 兼容
     // Rust 默认可能会重新排列字段以优化内存使用，但 C 需要稳定的布局
-    #[repr(C)] // C 兼容的内存布局
-    #[derive(Debug)]
-    struct Point {
-        x: f64,
-        y: f64,
-    }
+    // Point 定义在下面的 clib 模块中，带有 #[repr(C)]，对应 csrc/guide_ffi.h 里的 GuidePoint
+    use clib::Point;
 
     let point = Point { x: 1.0, y: 2.0 };
     println!("Rust struct -> C struct: {:?}", point);
+
+    // 结构体可以按值传给 C 函数，也可以由 C 函数按值返回
+    let other = Point { x: 4.0, y: 6.0 };
+    unsafe {
+        let mid = clib::guide_point_midpoint(point, other);
+        let distance = clib::guide_point_distance(&point, &other);
+        println!("C 计算的中点: {:?}，距离: {}", mid, distance);
+        println!(
+            "C 看到的 GuidePoint: size = {}, align = {}；Rust: size = {}, align = {}",
+            clib::guide_point_size(),
+            clib::guide_point_align(),
+            std::mem::size_of::<Point>(),
+            std::mem::align_of::<Point>()
+        );
+    }
     // #[repr(C)] 的重要性：
     // 1. 字段顺序：按声明顺序排列，不重新排序
     // 2. 内存对齐：使用 C 的对齐规则
@@ -160,16 +184,15 @@ fn data_type_mapping() {
 
     // 枚举映射 (Enum Mapping)
     // 同样需要 #[repr(C)] 来确保枚举的表示与 C 兼容
-    #[repr(C)]
-    #[derive(Debug)]
-    enum Status {
-        Success, // 通常映射为 0
-        Error,   // 通常映射为 1
-        Pending, // 通常映射为 2
-    }
+    // clib::Status 的判别值与 C 的 GuideStatus 一一对应：Success = 0, Error = 1, Pending = 2
+    use clib::Status;
 
-    let status = Status::Success;
-    println!("Rust enum -> C enum: {:?}", status);
+    let status = Status::Pending;
+    let echoed = unsafe { clib::guide_status_echo(status) };
+    println!(
+        "Rust enum -> C enum -> Rust enum: {:?} -> {:?}",
+        status, echoed
+    );
     // 枚举映射的注意事项：
     // 1. 值分配：Rust 从 0 开始递增分配值
     // 2. 显式指定：可以使用 #[repr(C, i32)] 来指定整数类型
@@ -318,6 +341,40 @@ fn string_handling() {
     // 3. 灵活性：统一处理借用和拥有的情况
     // 4. 抽象性：隐藏了具体的内存管理细节
 
+    // 调用真正的 C 字符串函数 (Calling Real C String Functions)
+    // 下面的函数来自 csrc/guide_ffi.c，演示三种常见的字符串所有权约定
+    let input = CString::new("Hello FFI").unwrap();
+    unsafe {
+        // 1. 只读借用：C 只在调用期间读取字符串，不保留指针
+        let len = clib::guide_str_length(input.as_ptr());
+        println!("C strlen: {}", len);
+
+        // 2. 调用方提供缓冲区：Rust 分配内存，C 负责写入，返回值表示完整结果需要的长度
+        let mut buffer = [0 as c_char; 6];
+        let needed = clib::guide_str_reverse(input.as_ptr(), buffer.as_mut_ptr(), buffer.len());
+        let truncated = CStr::from_ptr(buffer.as_ptr()).to_string_lossy();
+        println!(
+            "缓冲区太小时被截断: {:?}（需要 {} 字节）",
+            truncated,
+            needed + 1
+        );
+
+        let mut buffer = vec![0 as c_char; needed + 1];
+        clib::guide_str_reverse(input.as_ptr(), buffer.as_mut_ptr(), buffer.len());
+        let reversed = CStr::from_ptr(buffer.as_ptr()).to_string_lossy();
+        println!("C 反转的字符串: {}", reversed);
+
+        // 3. C 分配、C 释放：返回的指针必须交回 C 的释放函数，不能用 CString::from_raw
+        let upper = clib::guide_str_upper(input.as_ptr());
+        if !upper.is_null() {
+            println!(
+                "C 转换的大写字符串: {}",
+                CStr::from_ptr(upper).to_string_lossy()
+            );
+            clib::guide_str_free(upper);
+        }
+    }
+
     // 字符串处理的最佳实践：
     // 1. 优先使用 CString 和 CStr 进行字符串转换
     // 2. 始终检查字符串是否包含内部空字符
@@ -351,16 +408,14 @@ fn callback_functions() {
     // fn(i32) -> i32 定义了函数的签名：接受一个 i32 参数，返回 i32
     type CCallback = extern "C" fn(i32) -> i32;
 
-    // 接受回调函数的 C 函数声明 (C Function Declaration with Callback)
-    // 这个 C 函数接受一个值和一个回调函数，将值传递给回调函数并返回结果
-    // 注意：在实际项目中，这些函数会在 C 库中实现
-    // 这里我们使用模拟实现来演示概念
+    // 接受回调函数的 C 函数 (C Function with Callback)
+    // csrc/guide_ffi.c 中的 guide_apply 接受一个值和一个回调函数，返回 callback(value) + 10
+    // 声明在 clib 模块中：fn guide_apply(value: c_int, callback: extern "C" fn(c_int) -> c_int) -> c_int
 
     // 定义 Rust 函数作为回调 (Rust Function as Callback)
-    // #[unsafe(no_mangle)] 确保函数名在编译时不被修改
     // extern "C" 使用 C 调用约定，使 C 代码能够调用此函数
-    #[unsafe(no_mangle)]
-    pub extern "C" fn rust_callback(value: i32) -> i32 {
+    // 只通过函数指针传给 C 时不需要 #[no_mangle]；只有 C 按名字链接这个函数时才需要
+    extern "C" fn rust_callback(value: i32) -> i32 {
         println!("回调函数被调用，参数: {}", value);
         value * 2 // 简单的处理逻辑：将输入值乘以 2
     }
@@ -371,79 +426,91 @@ fn callback_functions() {
     // 4. 错误处理：设计适当的错误处理机制
 
     // 使用回调函数 (Using Callback Functions)
-    unsafe {
-        // 使用模拟实现而不是真实的外部函数
-        let result = simulate_call_with_callback(21, rust_callback);
+    // 调用链：Rust -> C (guide_apply) -> Rust (rust_callback) -> C -> Rust
+    for value in [21, 15] {
+        let result = unsafe { clib::guide_apply(value, rust_callback) };
         println!("回调函数调用结果: {}", result);
     }
 
-    // 模拟 C 库的函数 (Simulating C Library Functions)
-    // 为了演示的目的，我们在 Rust 中模拟 C 函数的实现
-    unsafe fn simulate_call_with_callback(value: i32, callback: CCallback) -> i32 {
-        println!("模拟调用 C 函数，参数: {}", value);
-        let result = callback(value); // 调用回调函数
-        println!("回调函数返回: {}", result);
-        result + 10 // 对回调结果进行额外处理
-    }
-
-    unsafe {
-        let result = simulate_call_with_callback(15, rust_callback);
-        println!("模拟回调结果: {}", result);
-    }
-
     // 闭包作为回调的挑战 (Challenges with Closures as Callbacks)
     // Rust 闭包不能直接传递给 C 代码，因为：
     // 1. 闭包环境：闭包可能捕获环境变量，有复杂的内部结构
     // 2. 调用约定：闭包的调用方式与 C 函数指针不同
     // 3. 生命周期：闭包的生命周期管理比普通函数更复杂
     // 4. 类型擦除：需要将不同类型的闭包统一为函数指针
-    // 注意：不能直接将 Rust 闭包传递给 C，需要额外的包装
+    // 解决办法是 C 库的惯用做法：回调多接收一个 void* 用户数据指针
 
-    // 使用函数指针包装器 (Function Pointer Wrapper)
-    // 这个包装器旨在解决闭包作为回调的问题
+    // 闭包包装器 (Closure Wrapper)
+    // 闭包本身通过 void* 传给 C，再由泛型的 extern "C" 跳板函数把指针还原成闭包并调用
     struct CallbackWrapper<F> {
         callback: F, // 存储闭包
     }
 
     impl<F> CallbackWrapper<F>
     where
-        F: Fn(i32) -> i32,
+        F: FnMut(i32) -> i32,
     {
         fn new(callback: F) -> Self {
             CallbackWrapper { callback }
         }
 
-        // 提供一个 extern "C" 函数来调用闭包
-        // 这是一个简化的示例，实际实现会更复杂
-        fn as_c_callback(&self) -> extern "C" fn(i32) -> i32 {
-            // 这是一个泛型函数，需要为每个具体的闭包类型生成实例
-            extern "C" fn trampoline<F>(value: i32) -> i32
-            where
-                F: Fn(i32) -> i32,
-            {
-                // 这里需要实际的闭包调用逻辑
-                // 实际实现需要通过某种方式访问到具体的闭包实例
-                // 这通常涉及到全局状态或复杂的指针操作
-                value * 2 // 简化的实现
-            }
-            trampoline::<F> // 返回具体类型的函数指针
+        // 每个具体的闭包类型 F 都会单态化出一个独立的跳板函数
+        extern "C" fn trampoline(context: *mut std::ffi::c_void, value: i32) -> i32 {
+            // panic 不能穿过 extern "C" 边界，否则进程直接中止
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let wrapper = unsafe { &mut *(context as *mut Self) };
+                (wrapper.callback)(value)
+            }))
+            .unwrap_or(-1)
+        }
+
+        // &mut self 保证闭包在 C 调用期间既不会被销毁也不会被别处访问
+        fn call_through_c(&mut self, value: i32) -> i32 {
+            let context = self as *mut Self as *mut std::ffi::c_void;
+            unsafe { clib::guide_apply_with_context(value, Self::trampoline, context) }
         }
     }
 
-    // 实际的闭包包装器实现需要考虑：
+    // 闭包包装器需要考虑：
     // 1. 内存安全：确保闭包在使用期间不被销毁
     // 2. 线程安全：处理多线程环境下的回调调用
     // 3. 类型擦除：将不同类型的闭包统一为相同的函数指针类型
     // 4. 上下文传递：将闭包的环境数据传递给回调函数
 
-    // 使用包装器（演示概念）
-    let wrapper = CallbackWrapper::new(|x| x + 100);
-    let c_callback = wrapper.as_c_callback();
+    // 闭包可以捕获并修改环境；包装器离开作用域后才能再次读取 calls
+    let mut calls = 0;
+    let offset = 100;
+    let result = {
+        let mut wrapper = CallbackWrapper::new(|x| {
+            calls += 1;
+            x + offset
+        });
+        wrapper.call_through_c(25)
+    };
+    println!("包装器回调结果: {}（闭包被调用 {} 次）", result, calls);
+
+    // 回调驱动的排序 (Callback-driven Sort)
+    // C 负责排序算法，Rust 通过回调提供比较规则；用户数据指针在这里用来统计比较次数
+    extern "C" fn compare_desc(context: *mut std::ffi::c_void, a: i32, b: i32) -> i32 {
+        let comparisons = unsafe { &mut *(context as *mut usize) };
+        *comparisons += 1;
+        b.cmp(&a) as i32
+    }
 
+    let mut numbers = [5, 3, 9, 1, 7];
+    let mut comparisons = 0usize;
     unsafe {
-        let result = simulate_call_with_callback(25, c_callback);
-        println!("包装器回调结果: {}", result);
+        clib::guide_sort(
+            numbers.as_mut_ptr(),
+            numbers.len(),
+            compare_desc,
+            &mut comparisons as *mut usize as *mut std::ffi::c_void,
+        );
     }
+    println!(
+        "C 排序结果（降序）: {:?}，比较了 {} 次",
+        numbers, comparisons
+    );
 
     // 回调函数的高级模式：
     // 1. 用户数据指针：传递额外的上下文数据给回调函数
@@ -663,6 +730,42 @@ fn error_handling() {
     // 3. 标准化：POSIX 标准定义了常见的 errno 值
     // 4. 延迟检查：允许在操作后稍后检查错误
 
+    // 真实 C 库的错误码 API (Error Codes from a Real C Library)
+    // csrc/guide_ffi.c 的函数返回错误码，结果通过输出指针返回，并把错误码记录到线程局部的"最后错误"
+    // guide_last_error 用 C11 的 _Thread_local 实现，与现代 C 库中 errno 的做法相同
+    use std::ffi::CStr;
+    use std::os::raw::c_long;
+
+    // 把 C 的错误码转换成 Rust 的 Result，错误信息来自 C 返回的静态字符串
+    fn check(code: i32) -> Result<(), String> {
+        if code == clib::GUIDE_OK {
+            Ok(())
+        } else {
+            let message = unsafe { CStr::from_ptr(clib::guide_strerror(code)) };
+            Err(format!("{} (错误码 {})", message.to_string_lossy(), code))
+        }
+    }
+
+    fn c_divide(a: f64, b: f64) -> Result<f64, String> {
+        let mut out = 0.0;
+        check(unsafe { clib::guide_divide(a, b, &mut out) })?;
+        Ok(out)
+    }
+
+    fn c_parse_int(text: &str) -> Result<i64, String> {
+        let text = std::ffi::CString::new(text).map_err(|e| e.to_string())?;
+        let mut out: c_long = 0;
+        check(unsafe { clib::guide_parse_int(text.as_ptr(), &mut out) })?;
+        Ok(out as i64)
+    }
+
+    println!("C 除法 10 / 4: {:?}", c_divide(10.0, 4.0));
+    println!("C 除法 1 / 0: {:?}", c_divide(1.0, 0.0));
+    println!("C 最后错误: {}", unsafe { clib::guide_last_error() });
+    for text in ["42", "-7", "12abc", "99999999999999999999"] {
+        println!("C 解析 {:?}: {:?}", text, c_parse_int(text));
+    }
+
     // 使用 Result 类型的包装 (Result Type Wrapper)
     // 将 Rust 的 Result 类型包装为 C 兼容的结构体
     // 这种方式更好地保持了 Rust 的错误处理语义
@@ -933,20 +1036,15 @@ fn practical_examples() {
     use std::ffi::CString;
     use std::ptr;
 
-    // 模拟 SQLite 的 C API (Simulating SQLite C API)
-    // 定义与 C 头文件对应的 Rust 类型
-    #[repr(C)]
-    struct Sqlite3; // 数据库连接句柄
-
-    #[repr(C)]
-    struct Sqlite3Stmt; // 预处理语句句柄
-
-    // 回调函数类型定义
-    type Sqlite3Callback =
-        extern "C" fn(*mut std::ffi::c_void, i32, *mut *mut c_char, *mut *mut c_char) -> i32;
+    // SQLite 风格的 C API (SQLite-style C API)
+    // crate::minidb 提供了与 SQLite 同名、同签名风格的 extern "C" 函数，背后是一个内存表引擎
+    // 这里像使用真正的 C 库一样，通过裸指针、返回码和回调函数与它交互
+    use crate::minidb::{
+        Connection, SQLITE_OK, Sqlite3, sqlite3_close, sqlite3_errmsg, sqlite3_exec, sqlite3_free,
+        sqlite3_open,
+    };
+    use crate::sql::Value;
 
-    // 声明 SQLite 的 C 函数接口（在实际项目中会链接到真正的SQLite库）
-    // 这里为了演示，我们同时提供模拟实现
     // 数据库包装器的设计原则：
     // 1. 资源安全：使用 RAII 模式管理数据库连接
     // 2. 错误处理：将 C 错误代码转换为 Rust Result
@@ -954,33 +1052,31 @@ fn practical_examples() {
     // 4. 并发安全：处理多线程环境下的数据库访问
     // 5. 内存管理：正确管理字符串和结果集的内存
 
-    // 模拟实现（用于演示）
-    unsafe fn sqlite3_open(_filename: *const c_char, db: *mut *mut Sqlite3) -> i32 {
-        unsafe {
-            *db = ptr::null_mut(); // 模拟数据库句柄
-        }
-        0 // SQLITE_OK
-    }
-
-    unsafe fn sqlite3_close(_db: *mut Sqlite3) -> i32 {
-        0 // SQLITE_OK
-    }
-
-    unsafe fn sqlite3_exec(
-        _db: *mut Sqlite3,
-        _sql: *const c_char,
-        _callback: Option<Sqlite3Callback>,
-        _callback_arg: *mut std::ffi::c_void,
-        _errmsg: *mut *mut c_char,
+    // 行回调：C 侧每返回一行就调用一次，值为 NULL 时对应的指针为空
+    extern "C" fn print_row(
+        _arg: *mut std::ffi::c_void,
+        count: i32,
+        values: *mut *mut c_char,
+        names: *mut *mut c_char,
     ) -> i32 {
-        0 // SQLITE_OK
-    }
-
-    unsafe fn sqlite3_free(_ptr: *mut std::ffi::c_void) {
-        // 模拟释放内存
+        let mut fields = Vec::new();
+        for i in 0..count as usize {
+            unsafe {
+                let name = CStr::from_ptr(*names.add(i)).to_string_lossy();
+                let value = *values.add(i);
+                let value = if value.is_null() {
+                    "NULL".into()
+                } else {
+                    CStr::from_ptr(value).to_string_lossy()
+                };
+                fields.push(format!("{}={}", name, value));
+            }
+        }
+        println!("  行: {}", fields.join(", "));
+        0 // 返回非 0 会中止查询
     }
 
-    // 使用模拟的 SQLite API
+    // 直接使用 C API
     unsafe {
         let mut db: *mut Sqlite3 = ptr::null_mut();
         let filename = CString::new(":memory:").unwrap();
@@ -988,13 +1084,24 @@ fn practical_examples() {
         let result = sqlite3_open(filename.as_ptr(), &mut db);
         println!("SQLite 打开结果: {}", result);
 
-        if result == 0 {
-            let sql = CString::new("SELECT * FROM users").unwrap();
+        if result == SQLITE_OK {
+            let sql = CString::new(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, age INTEGER);
+                 INSERT INTO users VALUES (1, 'Alice', 30), (2, 'Bob', 17), (3, 'Carol', NULL);
+                 SELECT * FROM users WHERE age >= 18 OR age IS NULL",
+            )
+            .unwrap();
             let mut errmsg: *mut c_char = ptr::null_mut();
 
-            let result = sqlite3_exec(db, sql.as_ptr(), None, ptr::null_mut(), &mut errmsg);
+            let result = sqlite3_exec(
+                db,
+                sql.as_ptr(),
+                Some(print_row),
+                ptr::null_mut(),
+                &mut errmsg,
+            );
 
-            if result != 0 && !errmsg.is_null() {
+            if result != SQLITE_OK && !errmsg.is_null() {
                 let error_msg = CStr::from_ptr(errmsg).to_str().unwrap();
                 println!("SQLite 错误: {}", error_msg);
                 sqlite3_free(errmsg as *mut std::ffi::c_void);
@@ -1002,19 +1109,61 @@ fn practical_examples() {
                 println!("SQLite 查询执行成功");
             }
 
+            // 出错时错误信息既通过 errmsg 返回，也可以通过 sqlite3_errmsg 读取
+            let sql = CString::new("SELECT * FROM orders").unwrap();
+            let result = sqlite3_exec(db, sql.as_ptr(), None, ptr::null_mut(), ptr::null_mut());
+            let error_msg = CStr::from_ptr(sqlite3_errmsg(db)).to_string_lossy();
+            println!("SQLite 错误 (返回码 {}): {}", result, error_msg);
+
             sqlite3_close(db);
         }
     }
 
+    // 安全包装器：Connection 和 Statement 在 Drop 时自动关闭和销毁句柄，
+    // 返回码被转换成 Result<_, DbError>
+    let run = || -> Result<(), crate::minidb::DbError> {
+        let conn = Connection::open(":memory:")?;
+        conn.execute_batch("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")?;
+
+        // 预处理语句：参数绑定避免了 SQL 注入，同一条语句可以 reset 后重复执行
+        let mut insert = conn.prepare("INSERT INTO users (id, name, age) VALUES (?, ?, ?)")?;
+        for (id, name, age) in [(1, "Alice", 30), (2, "Bob", 17), (3, "O'Brien", 45)] {
+            insert.reset();
+            insert.bind_all(&[id.into(), name.into(), age.into()])?;
+            insert.step()?;
+        }
+        drop(insert);
+
+        // query! 宏生成的参数化查询可以直接执行
+        let min_age = 18;
+        let query =
+            crate::query!(SELECT name, age FROM users WHERE age >= min_age ORDER BY age DESC)
+                .build()
+                .expect("查询构建失败");
+        for row in conn.query(&query)? {
+            if let [Value::Text(name), Value::Integer(age)] = row.as_slice() {
+                println!("  成年用户: {} ({})", name, age);
+            }
+        }
+
+        let update = crate::query!(UPDATE users SET age = 18 WHERE name = "Bob")
+            .build()
+            .expect("查询构建失败");
+        println!("更新了 {} 行", conn.execute(&update)?);
+
+        // 违反约束时得到的是 Err，而不是需要手动检查的返回码
+        conn.execute_batch("INSERT INTO users VALUES (1, 'Dup', 1)")
+    };
+    if let Err(e) = run() {
+        println!("安全包装器返回错误: {}", e);
+    }
+
     // 图像处理库包装器示例 (Image Processing Library Wrapper Example)
     // 图像处理是 FFI 的常见应用场景，许多高性能图像处理库都是用 C/C++ 编写的
     // 这个示例展示了如何处理复杂的内存布局和资源管理
-    #[repr(C)]
-    struct Image {
-        width: i32,    // 图像宽度
-        height: i32,   // 图像高度
-        data: *mut u8, // 像素数据（RGB 格式）
-    }
+    // C 侧的图像结构体：crate::image::RawImage 与它布局相同（width、height、data）
+    // Rust 侧用 ImageBuffer<Rgb> 管理像素，into_raw / from_raw 在两者之间转换，不复制像素
+    use crate::image::{Filter, ImageBuffer, Kernel, RawImage as Image, Rgb};
     // 图像处理的内存布局考虑：
     // 1. 像素格式：RGB、RGBA、灰度等不同的像素格式
     // 2. 内存对齐：确保数据访问的对齐要求
@@ -1022,34 +1171,17 @@ fn practical_examples() {
     // 4. 内存连续性：确保数据在内存中连续存储
     // 5. 大端序/小端序：处理多字节数据的字节序
 
-    // 模拟实现（在实际项目中会链接到真正的图像处理库）
+    // 模拟实现：C 接口的创建交给 ImageBuffer 完成，释放就是收回后 drop
     unsafe fn image_create(width: i32, height: i32) -> *mut Image {
-        // 计算所需的内存大小（每个像素 3 字节：RGB）
-        let data = unsafe {
-            std::alloc::alloc(
-                std::alloc::Layout::array::<u8>((width * height * 3) as usize).unwrap(),
-            ) as *mut u8
-        };
-
-        // 创建图像结构体并包装为指针
-        Box::into_raw(Box::new(Image {
-            width,
-            height,
-            data,
-        }))
+        // 每个像素 3 字节（RGB），ImageBuffer 负责分配并清零
+        let buffer = ImageBuffer::<Rgb>::new(width as u32, height as u32);
+        Box::into_raw(Box::new(buffer.into_raw()))
     }
 
-    unsafe fn image_free(image: *mut Image) {
-        if !image.is_null() {
-            let img = unsafe { Box::from_raw(image) };
-            // 释放像素数据内存
-            unsafe {
-                std::alloc::dealloc(
-                    img.data,
-                    std::alloc::Layout::array::<u8>((img.width * img.height * 3) as usize).unwrap(),
-                );
-            }
-        }
+    // 收回 C 持有的图像，像素内存的所有权回到 Rust
+    unsafe fn image_into_buffer(image: *mut Image) -> ImageBuffer<Rgb> {
+        let raw = unsafe { Box::from_raw(image) };
+        unsafe { ImageBuffer::from_raw(*raw) }
     }
 
     // 设置图像像素
@@ -1104,7 +1236,18 @@ fn practical_examples() {
             image_get_pixel(image, 10, 10, &mut r, &mut g, &mut b);
 
             println!("图像像素值: R={}, G={}, B={}", r, g, b);
-            image_free(image);
+
+            // 交回 Rust 后用安全的处理内核继续加工，像素仍是 C 修改过的那一块内存
+            let buffer = image_into_buffer(image);
+            let blurred = buffer.convolve(&Kernel::gaussian_3x3());
+            let Rgb([r, g, b]) = blurred.pixel(10, 10);
+            println!("高斯模糊后: R={}, G={}, B={}", r, g, b);
+            let thumbnail = blurred.resize(25, 25, Filter::Bilinear).grayscale();
+            println!(
+                "缩略图 {:?}，校验和 {:016x}",
+                thumbnail.dimensions(),
+                thumbnail.checksum()
+            );
         }
     }
 
@@ -1330,141 +1473,80 @@ fn safe_wrappers() {
     }
 
     // 网络套接字的安全包装器 (Safe Socket Wrapper)
-    // 封装了网络套接字的创建和管理
-    // 处理复杂的网络地址设置和连接管理
-    struct SafeSocket {
-        sockfd: i32, // 套接字描述符
-    }
-
-    impl SafeSocket {
-        // 创建新套接字
-        fn new(domain: i32, type_: i32, protocol: i32) -> Result<Self, String> {
-            let sockfd = unsafe { libc::socket(domain, type_, protocol) };
-            if sockfd == -1 {
-                return Err("套接字创建失败".to_string());
-            }
-
-            Ok(SafeSocket { sockfd })
-        }
-
-        // 连接到远程地址
-        fn connect(&self, addr: &str, port: u16) -> Result<(), String> {
-            let c_addr = CString::new(addr).map_err(|e| e.to_string())?;
-
-            // 设置套接字地址结构
-            let mut sockaddr = libc::sockaddr_in {
-                sin_family: libc::AF_INET as u16,
-                sin_port: port.to_be(), // 网络字节序
-                sin_addr: libc::in_addr {
-                    s_addr: unsafe { libc::inet_addr(c_addr.as_ptr()) },
-                },
-                sin_zero: [0; 8], // 填充字节
-            };
-
-            let result = unsafe {
-                libc::connect(
-                    self.sockfd,
-                    &sockaddr as *const libc::sockaddr_in as *const libc::sockaddr,
-                    std::mem::size_of::<libc::sockaddr_in>() as u32,
-                )
-            };
-
-            if result == -1 {
-                return Err("连接失败".to_string());
-            }
-
-            Ok(())
-        }
-
-        // 发送数据
-        fn send(&self, data: &[u8]) -> Result<usize, String> {
-            let sent = unsafe {
-                libc::send(
-                    self.sockfd,
-                    data.as_ptr() as *const std::os::raw::c_void,
-                    data.len(),
-                    0,
-                )
-            };
-
-            if sent == -1 {
-                return Err("发送失败".to_string());
-            }
-
-            Ok(sent as usize)
-        }
-    }
-
-    impl Drop for SafeSocket {
-        fn drop(&mut self) {
-            unsafe {
-                libc::close(self.sockfd);
-            }
+    // 完整实现在 crate::net::raw::Socket：socket / bind / connect / send 等系统调用的返回值统一转换为 io::Result，
+    // 描述符保存在内部的 OwnedFd 中，离开作用域时自动关闭
+    #[cfg(target_os = "linux")]
+    {
+        use crate::net::raw::Socket;
+        use std::net::{Ipv4Addr, SocketAddrV4};
+
+        let echo = || -> std::io::Result<String> {
+            let listener = Socket::listen_on(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0), 1)?;
+            let client = Socket::connect_to(listener.local_addr()?)?;
+            let (server, peer) = listener.accept()?;
+            println!("安全套接字已连接: {} -> {}", peer, listener.local_addr()?);
+            client.send(b"hello socket")?;
+            let mut buf = [0u8; 32];
+            let n = server.recv(&mut buf)?;
+            Ok(String::from_utf8_lossy(&buf[..n]).into_owned())
+        };
+        match echo() {
+            Ok(message) => println!("服务端收到: {}", message),
+            Err(e) => println!("套接字操作失败: {}", e),
         }
     }
 
-    // 使用安全套接字包装器
-    match SafeSocket::new(libc::AF_INET, libc::SOCK_STREAM, 0) {
-        Ok(_socket) => println!("安全套接字创建成功"),
-        Err(e) => println!("套接字创建失败: {}", e),
-    }
-
     // 动态库加载的安全包装器 (Safe Dynamic Library Wrapper)
-    // 封装了动态库的加载、符号查找和卸载
-    // 确保库句柄在不需要时被正确卸载
-    struct SafeLibrary {
-        handle: *mut std::os::raw::c_void, // 库句柄
-    }
-
-    impl SafeLibrary {
-        // 打开动态库
-        fn open(path: &str) -> Result<Self, String> {
-            let c_path = CString::new(path).map_err(|e| e.to_string())?;
-            let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_LAZY) };
-
-            if handle.is_null() {
-                let error = unsafe { libc::dlerror() };
-                let error_msg = if error.is_null() {
-                    "未知错误".to_string()
-                } else {
-                    unsafe { CStr::from_ptr(error).to_str().unwrap().to_string() }
-                };
-                return Err(format!("库加载失败: {}", error_msg));
+    // 封装了动态库的加载、符号查找和卸载，确保库句柄在不需要时被正确卸载
+    // 完整实现在 crate::plugin::SafeLibrary，插件系统也建立在它之上
+    // dlopen 只在类 Unix 系统上可用，plugin 模块也只在这些平台上编译
+    #[cfg(unix)]
+    {
+        use crate::plugin::{Plugin, PluginRegistry, SafeLibrary};
+
+        match SafeLibrary::open("libm.so.6") {
+            Ok(lib) => {
+                // 符号的类型由调用者保证，所以 symbol 是 unsafe 的
+                match unsafe { lib.symbol::<extern "C" fn(f64) -> f64>(c"sqrt") } {
+                    Ok(sqrt) => println!("通过 dlsym 调用 libm 的 sqrt(2.0) = {:.4}", sqrt(2.0)),
+                    Err(e) => println!("符号查找失败: {}", e),
+                }
+                if let Err(e) = unsafe { lib.symbol::<extern "C" fn()>(c"no_such_function") } {
+                    println!("预期的错误: {}", e);
+                }
             }
-
-            Ok(SafeLibrary { handle })
+            Err(e) => println!("库加载失败: {}", e),
         }
 
-        // 获取库中的符号（函数或变量）
-        fn get_symbol<T>(&self, name: &str) -> Result<T, String> {
-            let c_name = CString::new(name).map_err(|e| e.to_string())?;
-            let symbol = unsafe { libc::dlsym(self.handle, c_name.as_ptr()) };
-
-            if symbol.is_null() {
-                return Err(format!("符号 {} 未找到", name));
-            }
-
-            // 注意：transmute_copy 是 unsafe 的，需要谨慎使用
-            Ok(unsafe { std::mem::transmute_copy(&symbol) })
-        }
-    }
-
-    impl Drop for SafeLibrary {
-        fn drop(&mut self) {
-            if !self.handle.is_null() {
-                unsafe {
-                    libc::dlclose(self.handle);
+        // 插件：目录中的每个 cdylib 导出 guide_plugin_entry，宿主检查 ABI 版本后通过 Plugin trait 调用
+        // cargo build -p sample-plugin 之后，把 GUIDE_PLUGIN_DIR 指向放置 libsample_plugin.so 的目录即可看到效果
+        match std::env::var_os("GUIDE_PLUGIN_DIR") {
+            Some(dir) => {
+                let mut registry = PluginRegistry::new();
+                match registry.load_dir(&dir) {
+                    Ok(skipped) => {
+                        for (path, e) in skipped {
+                            println!("跳过 {}: {}", path.display(), e);
+                        }
+                        let names: Vec<String> =
+                            registry.names().into_iter().map(String::from).collect();
+                        for name in names {
+                            let plugin = registry.get_mut(&name).expect("名称来自注册表");
+                            match plugin.process("hello plugin") {
+                                Ok(output) => {
+                                    println!("插件 {} v{}: {}", name, plugin.version(), output)
+                                }
+                                Err(e) => println!("插件调用失败: {}", e),
+                            }
+                        }
+                    }
+                    Err(e) => println!("{}", e),
                 }
             }
+            None => println!("设置 GUIDE_PLUGIN_DIR 可以从目录加载插件（见 crates/sample-plugin）"),
         }
     }
 
-    // 使用安全库包装器
-    match SafeLibrary::open("libmath.so") {
-        Ok(_lib) => println!("安全库加载成功"),
-        Err(e) => println!("库加载失败: {}", e),
-    }
-
     // 安全包装器的高级特性：
     // 1. 资源池：管理多个相同类型的资源
     // 2. 引用计数：支持资源的共享所有权
@@ -1660,6 +1742,8 @@ fn practical_fd_usage_unix() {
     redirect_stdio_example();
     inter_process_communication();
     fd_passing_example();
+    #[cfg(target_os = "linux")]
+    network_fd_usage();
 }
 
 // 文件描述符在异步编程中的应用
@@ -1686,21 +1770,49 @@ async fn async_fd_usage() {
 }
 
 // 文件描述符的网络编程应用
-#[cfg(unix)]
+// 监听器来自 std，等待就绪用的是 crate::net::raw::poll：两者通过 BorrowedFd 衔接
+#[cfg(target_os = "linux")]
 fn network_fd_usage() {
-    use std::net::TcpListener;
+    use crate::net::raw::{Events, PollFd, Socket, poll};
+    use std::net::{TcpListener, TcpStream};
     use std::os::fd::AsFd;
+    use std::time::Duration;
 
     println!("=== 网络编程文件描述符应用 ===");
 
     // 创建 TCP 监听器
     let listener = TcpListener::bind("127.0.0.1:0").expect("监听器创建失败");
-    println!("TCP 监听器创建成功");
+    let addr = listener.local_addr().expect("无法获取监听地址");
+    println!("TCP 监听器创建成功: {}", addr);
 
     // 获取监听器的文件描述符
     let listener_fd = listener.as_fd();
     println!("监听器文件描述符: {:?}", listener_fd);
 
+    // 没有连接时 poll 超时返回 0
+    let mut fds = [PollFd::new(listener.as_fd(), Events::READABLE)];
+    let ready = poll(&mut fds, Some(Duration::from_millis(10))).expect("poll 失败");
+    println!("等待 10ms 后就绪的描述符: {}", ready);
+
+    // 有连接等待 accept 时，监听器变为可读
+    let _client = TcpStream::connect(addr).expect("连接失败");
+    let ready = poll(&mut fds, Some(Duration::from_secs(1))).expect("poll 失败");
+    println!(
+        "客户端连接后就绪的描述符: {}，可读: {}",
+        ready,
+        fds[0].is_readable()
+    );
+
+    // std 的 TcpStream 与 Socket 之间只转移 OwnedFd 的所有权
+    let (stream, peer) = listener.accept().expect("accept 失败");
+    let socket = Socket::from(stream);
+    socket.set_nonblocking(true).expect("设置非阻塞失败");
+    println!(
+        "来自 {} 的连接转换为 Socket，非阻塞: {}",
+        peer,
+        socket.is_nonblocking().unwrap_or(false)
+    );
+
     // 文件描述符在网络编程中的优势：
     // 1. 可以使用 epoll/kqueue 等高效 I/O 多路复用
     // 2. 支持非阻塞 I/O 操作
@@ -1947,6 +2059,86 @@ mod tests {
         assert_eq!(unsafe { get_ffi_counter() }, initial_count + 1);
     }
 
+    #[test]
+    fn test_point_layout_matches_c() {
+        use std::mem::{align_of, offset_of, size_of};
+        unsafe {
+            assert_eq!(clib::guide_point_size(), size_of::<clib::Point>());
+            assert_eq!(clib::guide_point_align(), align_of::<clib::Point>());
+            assert_eq!(clib::guide_point_offset_y(), offset_of!(clib::Point, y));
+
+            let point = clib::Point { x: -1.5, y: 2.25 };
+            assert_eq!(clib::guide_point_echo(point), point);
+            let mid = clib::guide_point_midpoint(point, clib::Point { x: 1.5, y: 0.25 });
+            assert_eq!(mid, clib::Point { x: 0.0, y: 1.25 });
+            assert_eq!(clib::guide_point_distance(&point, &point), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_status_layout_matches_c() {
+        assert_eq!(
+            unsafe { clib::guide_status_size() },
+            std::mem::size_of::<clib::Status>()
+        );
+        for status in [
+            clib::Status::Success,
+            clib::Status::Error,
+            clib::Status::Pending,
+        ] {
+            assert_eq!(unsafe { clib::guide_status_echo(status) }, status);
+        }
+    }
+
+    #[test]
+    fn test_c_error_codes_and_last_error() {
+        let mut out = 0.0;
+        unsafe {
+            assert_eq!(clib::guide_divide(9.0, 3.0, &mut out), clib::GUIDE_OK);
+            assert_eq!(out, 3.0);
+            assert_eq!(
+                clib::guide_divide(1.0, 0.0, &mut out),
+                clib::GUIDE_ERR_DIVIDE_BY_ZERO
+            );
+            // 失败时输出参数保持不变
+            assert_eq!(out, 3.0);
+            assert_eq!(clib::guide_last_error(), clib::GUIDE_ERR_DIVIDE_BY_ZERO);
+
+            let mut parsed = 0;
+            assert_eq!(
+                clib::guide_parse_int(c"12x".as_ptr(), &mut parsed),
+                clib::GUIDE_ERR_INVALID_NUMBER
+            );
+            assert_eq!(
+                clib::guide_parse_int(c"-12".as_ptr(), &mut parsed),
+                clib::GUIDE_OK
+            );
+            assert_eq!(parsed, -12);
+            assert_eq!(clib::guide_last_error(), clib::GUIDE_OK);
+        }
+        // "最后错误"是线程局部的，其他线程看不到这里的错误
+        let other = std::thread::spawn(|| unsafe { clib::guide_last_error() });
+        assert_eq!(other.join().unwrap(), clib::GUIDE_OK);
+    }
+
+    #[test]
+    fn test_c_sort_with_rust_comparator() {
+        extern "C" fn by_abs(_: *mut std::ffi::c_void, a: i32, b: i32) -> i32 {
+            a.abs().cmp(&b.abs()) as i32
+        }
+        let mut data = [3, -1, 2, -3, 1];
+        unsafe {
+            clib::guide_sort(data.as_mut_ptr(), data.len(), by_abs, std::ptr::null_mut());
+        }
+        // 排序是稳定的：绝对值相同的元素保持原有顺序
+        assert_eq!(data, [-1, 1, 2, 3, -3]);
+
+        let mut buffer = [0 as c_char; 4];
+        let needed = unsafe { clib::guide_str_reverse(c"abcdef".as_ptr(), buffer.as_mut_ptr(), 4) };
+        assert_eq!(needed, 6);
+        assert_eq!(unsafe { CStr::from_ptr(buffer.as_ptr()) }, c"fed");
+    }
+
     #[test]
     fn test_latest_ffi_raw_parts_roundtrip() {
         let values = vec![1_u8, 2, 3, 4];
@@ -1966,44 +2158,75 @@ mod libc {
     use std::os::raw::{c_char, c_int, c_void};
     pub type SizeT = usize; // 在大多数平台上，SizeT 相当于 usize
 
-    pub const AF_INET: i32 = 2;
-    pub const SOCK_STREAM: i32 = 1;
     pub const O_RDWR: i32 = 2;
     pub const O_CREAT: i32 = 64;
-    pub const RTLD_LAZY: i32 = 1;
 
     unsafe extern "C" {
-        pub fn socket(domain: i32, type_: i32, protocol: i32) -> i32;
-        pub fn connect(sockfd: i32, addr: *const sockaddr, addrlen: u32) -> i32;
-        pub fn send(sockfd: i32, buf: *const c_void, len: usize, flags: i32) -> isize;
         pub fn close(fd: i32) -> i32;
         pub fn open(path: *const c_char, flags: i32, mode: u32) -> i32;
         pub fn write(fd: i32, buf: *const c_void, count: usize) -> isize;
         pub fn read(fd: i32, buf: *mut std::ffi::c_void, count: usize) -> isize;
-        pub fn dlopen(filename: *const c_char, flag: i32) -> *mut std::ffi::c_void;
-        pub fn dlclose(handle: *mut std::ffi::c_void) -> i32;
-        pub fn dlsym(handle: *mut std::ffi::c_void, symbol: *const c_char)
-        -> *mut std::ffi::c_void;
-        pub fn dlerror() -> *mut c_char;
-        pub fn inet_addr(cp: *const c_char) -> u32;
     }
+}
 
+// 随本项目一起编译的 C 库（csrc/guide_ffi.c）
+// build.rs 用 cc 把它编译成静态库 libguide_ffi.a 并链接进来，这里的声明必须与 csrc/guide_ffi.h 保持一致
+mod clib {
+    use std::os::raw::{c_char, c_int, c_long, c_void};
+
+    // 对应 C 的 GuidePoint：#[repr(C)] 保证字段顺序、对齐和大小与 C 编译器的结果相同
     #[repr(C)]
-    pub struct sockaddr {
-        pub sa_family: u16,
-        pub sa_data: [u8; 14],
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Point {
+        pub x: f64,
+        pub y: f64,
     }
 
+    // 对应 C 的 GuideStatus：判别值必须逐个写明并与 C 一致
+    // 注意：C 可能返回任意整数，如果 C 侧可能产生枚举外的值，应该按 c_int 接收后再转换
     #[repr(C)]
-    pub struct sockaddr_in {
-        pub sin_family: u16,
-        pub sin_port: u16,
-        pub sin_addr: in_addr,
-        pub sin_zero: [u8; 8],
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Status {
+        Success = 0,
+        Error = 1,
+        Pending = 2,
     }
 
-    #[repr(C)]
-    pub struct in_addr {
-        pub s_addr: u32,
+    pub const GUIDE_OK: c_int = 0;
+    pub const GUIDE_ERR_DIVIDE_BY_ZERO: c_int = 2;
+    pub const GUIDE_ERR_INVALID_NUMBER: c_int = 3;
+    pub const GUIDE_ERR_OUT_OF_RANGE: c_int = 4;
+
+    pub type Callback = extern "C" fn(c_int) -> c_int;
+    pub type ContextCallback = extern "C" fn(*mut c_void, c_int) -> c_int;
+    pub type Compare = extern "C" fn(*mut c_void, c_int, c_int) -> c_int;
+
+    unsafe extern "C" {
+        pub fn guide_point_midpoint(a: Point, b: Point) -> Point;
+        pub fn guide_point_distance(a: *const Point, b: *const Point) -> f64;
+        pub fn guide_point_size() -> usize;
+        pub fn guide_point_align() -> usize;
+        pub fn guide_point_offset_y() -> usize;
+        pub fn guide_status_size() -> usize;
+        pub fn guide_point_echo(p: Point) -> Point;
+        pub fn guide_status_echo(s: Status) -> Status;
+
+        pub fn guide_str_length(s: *const c_char) -> usize;
+        pub fn guide_str_reverse(s: *const c_char, out: *mut c_char, out_len: usize) -> usize;
+        pub fn guide_str_upper(s: *const c_char) -> *mut c_char;
+        pub fn guide_str_free(s: *mut c_char);
+
+        pub fn guide_apply(value: c_int, callback: Callback) -> c_int;
+        pub fn guide_apply_with_context(
+            value: c_int,
+            callback: ContextCallback,
+            context: *mut c_void,
+        ) -> c_int;
+        pub fn guide_sort(data: *mut c_int, len: usize, compare: Compare, context: *mut c_void);
+
+        pub fn guide_divide(a: f64, b: f64, out: *mut f64) -> c_int;
+        pub fn guide_parse_int(text: *const c_char, out: *mut c_long) -> c_int;
+        pub fn guide_last_error() -> c_int;
+        pub fn guide_strerror(code: c_int) -> *const c_char;
     }
 }
@@ -802,60 +802,28 @@ fn practical_examples() {
     subject.set_state("新状态".to_string());
 
     // 示例 3: 内存池
-    #[derive(Debug)]
-    struct MemoryPool {
-        available: Rc<RefCell<Vec<usize>>>,
-        used: Rc<RefCell<Vec<usize>>>,
-        pool_size: usize,
-    }
-
-    impl MemoryPool {
-        fn new(pool_size: usize) -> Self {
-            let available = (0..pool_size).collect();
-            Self {
-                available: Rc::new(RefCell::new(available)),
-                used: Rc::new(RefCell::new(Vec::new())),
-                pool_size,
-            }
-        }
-
-        fn allocate(&self) -> Option<usize> {
-            let mut available = self.available.borrow_mut();
-            let mut used = self.used.borrow_mut();
-
-            available.pop().map(|addr| {
-                used.push(addr);
-                addr
-            })
-        }
-
-        fn deallocate(&self, addr: usize) {
-            let mut available = self.available.borrow_mut();
-            let mut used = self.used.borrow_mut();
-
-            if let Some(pos) = used.iter().position(|&x| x == addr) {
-                used.remove(pos);
-                available.push(addr);
-            }
-        }
-
-        fn stats(&self) -> (usize, usize) {
-            (self.available.borrow().len(), self.used.borrow().len())
-        }
-    }
-
-    let pool = MemoryPool::new(10);
-    let addr1 = pool.allocate();
-    let addr2 = pool.allocate();
-
-    println!("分配的地址: {:?}, {:?}", addr1, addr2);
-    println!("内存池统计: {:?}", pool.stats());
-
-    if let Some(addr) = addr1 {
-        pool.deallocate(addr);
-    }
-
-    println!("释放后的统计: {:?}", pool.stats());
+    // crate::alloc::Pool<T> 是同一类型对象的 slab：alloc 返回像 Box 一样使用的 RAII 句柄，
+    // 句柄 drop 时对象被析构、槽位回到空闲链表，不再需要手动 deallocate
+    use crate::alloc::Pool;
+
+    let pool = Pool::with_segment_len(4);
+    let first = pool.alloc(String::from("连接 1"));
+    let mut second = pool.alloc(String::from("连接 2"));
+    second.push_str("（已认证）");
+
+    println!("分配的对象: {:?}, {:?}", first, second);
+    println!("内存池统计: 存活 {} / 槽位 {}", pool.len(), pool.capacity());
+
+    drop(first);
+    println!(
+        "释放后的统计: 存活 {} / 槽位 {}",
+        pool.len(),
+        pool.capacity()
+    );
+
+    // 释放出的槽位被下一次分配复用
+    let third = pool.alloc(String::from("连接 3"));
+    println!("复用槽位后: {:?}，存活 {}", third, pool.len());
 
     println!();
 }
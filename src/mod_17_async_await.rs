@@ -24,6 +24,8 @@ use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 use tokio::time::{sleep, timeout};
 
+use crate::demo_rng::DemoRng;
+
 fn async_concepts() {
     println!("=== 异步编程基础概念 ===");
 
@@ -370,7 +372,7 @@ async fn async_streams() {
 // 异步编程中的错误处理和超时控制
 // 确保异步程序的健壮性和可靠性
 
-async fn error_handling_and_timeouts() {
+async fn error_handling_and_timeouts(rng: &DemoRng) {
     println!("=== 错误处理和超时控制 ===");
 
     // 异步错误处理的核心概念：
@@ -428,20 +430,40 @@ async fn error_handling_and_timeouts() {
         }
     }
 
-    async fn flaky_operation() -> Result<i32, String> {
-        // 模拟有时失败的操作
-        if rand::random::<f64>() < 0.7 {
+    async fn flaky_operation(rng: &DemoRng) -> Result<i32, String> {
+        // 模拟有时失败的操作；随机数来自带种子的 DemoRng，输出可以按种子重放
+        if rng.random_bool(0.7) {
             Ok(42)
         } else {
             Err("随机失败".to_string())
         }
     }
 
-    match retry_operation(flaky_operation, 3).await {
+    match retry_operation(|| flaky_operation(rng), 3).await {
         Ok(result) => println!("重试成功: {}", result),
         Err(e) => println!("重试失败: {}", e),
     }
 
+    // 重试必须配合限流和熔断，否则重试风暴会压垮本已不稳定的下游
+    // 令牌桶控制发往下游的速率，熔断器在连续失败后短路调用
+    use crate::resilience::{CircuitBreaker, CircuitBreakerConfig, RateLimiter, TokenBucket};
+
+    let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+        failure_threshold: 2,
+        success_threshold: 1,
+        open_timeout: Duration::from_millis(100),
+        half_open_max_calls: 1,
+    });
+    let limiter = TokenBucket::new(2, 50.0);
+
+    for i in 1..=6 {
+        limiter.acquire().await;
+        match breaker.call_async(|| flaky_operation(rng)).await {
+            Ok(result) => println!("受保护的调用 {}: 成功 {}", i, result),
+            Err(e) => println!("受保护的调用 {}: {} (状态 {:?})", i, e, breaker.state()),
+        }
+    }
+
     // 错误处理的最佳实践：
     // 1. 明确错误类型：定义清晰的错误层次
     // 2. 错误上下文：提供足够的错误信息
@@ -537,7 +559,7 @@ async fn async_patterns_and_best_practices() {
 
     // 异步编程的最佳实践：
     // 1. 合理设置并发度：避免过度并发
-    // 2. 正确处理取消：支持优雅的任务取消
+    // 2. 正确处理取消：支持优雅的任务取消（见第 14 节的 CancellationToken 与 TaskGroup）
     // 3. 资源清理：确保异步资源正确释放
     // 4. 性能监控：监控异步任务的性能
     // 5. 错误处理：实现完善的错误处理机制
@@ -1961,6 +1983,322 @@ pub async fn async_closures_demo() {
     println!();
 }
 
+// ===========================================
+// 14. 结构化并发：取消令牌与任务组
+// ===========================================
+
+// 结构化并发 (Structured Concurrency) 要求子任务的生命周期被限定在父作用域内：
+// 1. 作用域结束前，所有子任务要么完成，要么被取消并回收
+// 2. 取消信号沿着父子关系向下传播，但不会向上影响父级
+// 3. 任意一个子任务失败时，兄弟任务被取消，避免做无用功
+// tokio::spawn 本身是"非结构化"的：任务可以比创建它的函数活得更久
+// 下面的 CancellationToken 和 TaskGroup 在 tokio 之上补齐了这些约束
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Weak};
+use tokio::sync::Notify;
+use tokio::task::JoinSet;
+
+// 层级取消令牌
+// 克隆得到的是同一个令牌；child_token() 创建子令牌
+// 取消父令牌会级联取消所有子令牌，取消子令牌不影响父令牌
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenInner>,
+}
+
+#[derive(Default)]
+struct TokenInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+    children: Mutex<Vec<Weak<TokenInner>>>,
+}
+
+impl TokenInner {
+    fn cancel(&self) {
+        // 先设置标志再通知，保证被唤醒的等待者一定能看到取消状态
+        if self.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.notify.notify_waiters();
+
+        let children = std::mem::take(&mut *self.children.lock().unwrap());
+        for child in children.iter().filter_map(Weak::upgrade) {
+            child.cancel();
+        }
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 创建子令牌：父令牌已取消时，子令牌一出生就是取消状态
+    pub fn child_token(&self) -> CancellationToken {
+        let child = CancellationToken::new();
+        {
+            let mut children = self.inner.children.lock().unwrap();
+            children.retain(|weak| weak.strong_count() > 0);
+            children.push(Arc::downgrade(&child.inner));
+        }
+        // 在登记之后再检查标志：要么 cancel() 能看到这个子令牌，要么这里能看到标志
+        if self.is_cancelled() {
+            child.cancel();
+        }
+        child
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    // 等待令牌被取消
+    pub async fn cancelled(&self) {
+        loop {
+            // notify_waiters 会唤醒调用之前创建的所有 Notified，
+            // 所以必须先创建 Notified 再检查标志，避免错过通知
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+// 子任务的失败原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskError<E> {
+    Failed(E), // 任务自身返回了 Err
+    Cancelled, // 任务被取消（兄弟失败、父级取消或作用域被丢弃）
+    Panicked(String),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for TaskError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::Failed(e) => write!(f, "任务失败: {}", e),
+            TaskError::Cancelled => write!(f, "任务已取消"),
+            TaskError::Panicked(msg) => write!(f, "任务 panic: {}", msg),
+        }
+    }
+}
+
+// 任务组（nursery）
+// 1. 所有子任务共享组令牌的子令牌，组令牌又是父令牌的子令牌
+// 2. 第一个失败（Err 或 panic）的子任务会取消整个组
+// 3. join() 等待全部子任务结束，按 spawn 顺序收集结果
+// 4. 未 join 就丢弃任务组时，取消令牌并 abort 所有子任务
+pub struct TaskGroup<T, E> {
+    token: CancellationToken,
+    tasks: JoinSet<(usize, Result<T, TaskError<E>>)>,
+    indices: std::collections::HashMap<tokio::task::Id, usize>,
+    first_error: Arc<Mutex<Option<usize>>>,
+}
+
+impl<T, E> TaskGroup<T, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    // 创建顶层任务组
+    pub fn new() -> Self {
+        Self::with_parent(&CancellationToken::new())
+    }
+
+    // 创建挂在父令牌下的任务组：父令牌取消时整个组随之取消
+    pub fn with_parent(parent: &CancellationToken) -> Self {
+        TaskGroup {
+            token: parent.child_token(),
+            tasks: JoinSet::new(),
+            indices: std::collections::HashMap::new(),
+            first_error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    // 启动子任务；闭包收到自己的子令牌，可用于协作式取消或继续派生子组
+    // 即使子任务不检查令牌，取消时也会在下一个 .await 点被强制中断
+    pub fn spawn<F, Fut>(&mut self, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+    {
+        let index = self.indices.len();
+        let group_token = self.token.clone();
+        let child_token = self.token.child_token();
+        let first_error = Arc::clone(&self.first_error);
+        let future = task(child_token.clone());
+
+        let handle = self.tasks.spawn(async move {
+            let result = tokio::select! {
+                biased;
+                _ = child_token.cancelled() => Err(TaskError::Cancelled),
+                result = future => result.map_err(TaskError::Failed),
+            };
+            if let Err(TaskError::Failed(_)) = result {
+                first_error.lock().unwrap().get_or_insert(index);
+                group_token.cancel();
+            }
+            (index, result)
+        });
+        self.indices.insert(handle.id(), index);
+    }
+
+    // 等待所有子任务结束，按 spawn 顺序返回每个任务的结果
+    pub async fn join(mut self) -> Vec<Result<T, TaskError<E>>> {
+        let mut results: Vec<Option<Result<T, TaskError<E>>>> =
+            (0..self.indices.len()).map(|_| None).collect();
+
+        while let Some(joined) = self.tasks.join_next_with_id().await {
+            match joined {
+                Ok((_, (index, result))) => results[index] = Some(result),
+                Err(join_error) => {
+                    let index = self.indices[&join_error.id()];
+                    let error = if join_error.is_panic() {
+                        self.first_error.lock().unwrap().get_or_insert(index);
+                        self.token.cancel();
+                        TaskError::Panicked(panic_message(join_error.into_panic()))
+                    } else {
+                        TaskError::Cancelled
+                    };
+                    results[index] = Some(Err(error));
+                }
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(TaskError::Cancelled)))
+            .collect()
+    }
+
+    // 全部成功时返回所有结果，否则返回最先发生的那个错误
+    pub async fn try_join(self) -> Result<Vec<T>, TaskError<E>> {
+        let first_error = Arc::clone(&self.first_error);
+        let mut results = self.join().await;
+
+        if let Some(index) = *first_error.lock().unwrap() {
+            return Err(results.swap_remove(index).err().unwrap());
+        }
+        // 没有子任务失败，但整个组可能被父令牌取消了
+        results.into_iter().collect()
+    }
+}
+
+impl<T, E> Default for TaskGroup<T, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, E> Drop for TaskGroup<T, E> {
+    fn drop(&mut self) {
+        // 作用域结束：取消令牌让子任务尽快退出，JoinSet 在 drop 时 abort 剩余任务
+        self.token.cancel();
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "未知 panic".to_string()
+    }
+}
+
+async fn structured_concurrency() {
+    println!("=== 结构化并发：取消令牌与任务组 ===");
+
+    // 示例 1：全部成功，结果按 spawn 顺序收集
+    let mut group: TaskGroup<u64, String> = TaskGroup::new();
+    for i in 1..=3u64 {
+        group.spawn(move |_token| async move {
+            sleep(Duration::from_millis(30 * (4 - i))).await;
+            Ok(i * 10)
+        });
+    }
+    println!("全部成功: {:?}", group.try_join().await);
+
+    // 示例 2：一个子任务失败，兄弟任务被取消
+    let mut group: TaskGroup<&'static str, String> = TaskGroup::new();
+    group.spawn(|_token| async {
+        sleep(Duration::from_millis(20)).await;
+        Err("下游服务不可用".to_string())
+    });
+    group.spawn(|token| async move {
+        // 协作式取消：长循环中定期检查令牌
+        for _ in 0..100 {
+            if token.is_cancelled() {
+                println!("  兄弟任务观察到取消信号，提前退出");
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        Ok("慢任务完成")
+    });
+    let started = Instant::now();
+    for (i, result) in group.join().await.iter().enumerate() {
+        match result {
+            Ok(value) => println!("  任务 {} 成功: {}", i, value),
+            Err(e) => println!("  任务 {} {}", i, e),
+        }
+    }
+    println!("首个错误后整组在 {:?} 内结束", started.elapsed());
+
+    // 示例 3：父令牌取消会传播到嵌套的任务组
+    let root = CancellationToken::new();
+    let mut group: TaskGroup<(), String> = TaskGroup::with_parent(&root);
+    group.spawn(|token| async move {
+        let mut inner: TaskGroup<(), String> = TaskGroup::with_parent(&token);
+        inner.spawn(|_| async {
+            sleep(Duration::from_secs(10)).await;
+            Ok(())
+        });
+        inner
+            .try_join()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    });
+    let canceller = root.clone();
+    tokio::spawn(async move {
+        sleep(Duration::from_millis(20)).await;
+        canceller.cancel();
+    });
+    println!("父令牌取消后的结果: {:?}", group.try_join().await);
+
+    // 结构化并发的要点：
+    // 1. 把 spawn 限定在 TaskGroup 里，而不是随处调用 tokio::spawn
+    // 2. 长时间运行的循环要检查令牌，实现及时、干净的退出
+    // 3. 取消是协作的 + 强制的：select! 保证在 .await 点中断未检查令牌的任务
+    // 4. join 之后不存在任何残留的子任务
+
+    println!();
+}
+
 // ===========================================
 // 主函数
 // ===========================================
@@ -1976,7 +2314,8 @@ pub async fn main() {
     async_executors().await;
     async_io_operations().await;
     async_streams().await;
-    error_handling_and_timeouts().await;
+    let rng = crate::demo_rng::global().derive("mod_17_async_await");
+    error_handling_and_timeouts(&rng).await;
     async_patterns_and_best_practices().await;
     testing_async_code().await;
     practical_examples().await;
@@ -1984,6 +2323,7 @@ pub async fn main() {
     async_closures_demo().await;
     async_function_lifetimes().await;
     practical_async_lifetime_examples().await;
+    structured_concurrency().await;
 
     println!("异步编程演示完成！");
 }
@@ -2116,4 +2456,187 @@ mod tests {
         assert_eq!(result1, "computed_value");
         assert_eq!(result2, "computed_value");
     }
+
+    #[tokio::test]
+    async fn test_cancellation_token_hierarchy() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+        let grandchild = child.child_token();
+        let sibling = parent.child_token();
+
+        // 取消子令牌只影响它自己的子树
+        child.cancel();
+        assert!(child.is_cancelled());
+        assert!(grandchild.is_cancelled());
+        assert!(!parent.is_cancelled());
+        assert!(!sibling.is_cancelled());
+
+        // 取消父令牌会传播到所有后代，包括之后才创建的子令牌
+        parent.cancel();
+        assert!(sibling.is_cancelled());
+        assert!(parent.child_token().is_cancelled());
+
+        // 已取消的令牌上 cancelled() 立即返回
+        tokio::time::timeout(Duration::from_millis(50), sibling.cancelled())
+            .await
+            .expect("已取消的令牌应立即完成");
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_token_wakes_waiters() {
+        let token = CancellationToken::new();
+        let waiter = {
+            let token = token.child_token();
+            tokio::spawn(async move { token.cancelled().await })
+        };
+        sleep(Duration::from_millis(10)).await;
+        token.cancel();
+        tokio::time::timeout(Duration::from_millis(200), waiter)
+            .await
+            .expect("等待者应被唤醒")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_task_group_collects_results_in_spawn_order() {
+        let mut group: TaskGroup<usize, String> = TaskGroup::new();
+        for i in 0..5 {
+            group.spawn(move |_| async move {
+                // 后启动的任务先完成，结果仍按 spawn 顺序排列
+                sleep(Duration::from_millis(5 * (5 - i as u64))).await;
+                Ok(i * i)
+            });
+        }
+        assert_eq!(group.len(), 5);
+        assert_eq!(group.try_join().await, Ok(vec![0, 1, 4, 9, 16]));
+    }
+
+    #[tokio::test]
+    async fn test_task_group_first_error_cancels_siblings() {
+        let mut group: TaskGroup<i32, &'static str> = TaskGroup::new();
+        group.spawn(|_| async {
+            sleep(Duration::from_secs(30)).await;
+            Ok(1)
+        });
+        group.spawn(|_| async {
+            sleep(Duration::from_millis(10)).await;
+            Err("boom")
+        });
+        group.spawn(|_| async {
+            sleep(Duration::from_millis(50)).await;
+            Err("second")
+        });
+
+        let started = Instant::now();
+        let results = group.join().await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(results[0], Err(TaskError::Cancelled));
+        assert_eq!(results[1], Err(TaskError::Failed("boom")));
+        assert_eq!(results[2], Err(TaskError::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_task_group_try_join_reports_first_error() {
+        let mut group: TaskGroup<(), String> = TaskGroup::new();
+        group.spawn(|_| async { Ok(()) });
+        group.spawn(|_| async {
+            sleep(Duration::from_millis(5)).await;
+            Err("first".to_string())
+        });
+        group.spawn(|_| async {
+            sleep(Duration::from_millis(50)).await;
+            Err("second".to_string())
+        });
+        assert_eq!(
+            group.try_join().await,
+            Err(TaskError::Failed("first".to_string()))
+        );
+
+        // panic 同样算作失败，并取消兄弟任务
+        let mut group: TaskGroup<(), String> = TaskGroup::new();
+        group.spawn(|_| async { panic!("爆炸") });
+        assert_eq!(
+            group.try_join().await,
+            Err(TaskError::Panicked("爆炸".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_group_parent_cancellation_propagates() {
+        let root = CancellationToken::new();
+        let mut outer: TaskGroup<(), ()> = TaskGroup::with_parent(&root);
+        outer.spawn(|token| async move {
+            let mut inner: TaskGroup<(), ()> = TaskGroup::with_parent(&token);
+            inner.spawn(|_| async {
+                sleep(Duration::from_secs(30)).await;
+                Ok(())
+            });
+            assert_eq!(inner.try_join().await, Err(TaskError::Cancelled));
+            Ok(())
+        });
+
+        root.cancel();
+        assert!(outer.token().is_cancelled());
+        assert_eq!(outer.try_join().await, Err(TaskError::Cancelled));
+    }
+
+    // 每个子任务持有一个守卫，守卫析构时计数减一
+    struct AliveGuard(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl AliveGuard {
+        fn new(counter: &Arc<std::sync::atomic::AtomicUsize>) -> Self {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            AliveGuard(Arc::clone(counter))
+        }
+    }
+
+    impl Drop for AliveGuard {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_no_task_outlives_joined_scope() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let alive = Arc::new(AtomicUsize::new(0));
+        let mut group: TaskGroup<(), &'static str> = TaskGroup::new();
+        for i in 0..10u64 {
+            let alive = Arc::clone(&alive);
+            group.spawn(move |_| async move {
+                let _guard = AliveGuard::new(&alive);
+                if i == 3 {
+                    return Err("fail");
+                }
+                sleep(Duration::from_millis(if i % 2 == 0 { 5 } else { 10_000 })).await;
+                Ok(())
+            });
+        }
+
+        let _ = group.join().await;
+        // join 返回时所有子任务都已结束并析构了守卫
+        assert_eq!(alive.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_task_group_aborts_children() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let observed = {
+            let mut group: TaskGroup<(), ()> = TaskGroup::new();
+            let token = group.token().clone();
+            group.spawn(move |_| async move {
+                // 任务持有 tx；任务被回收时 tx 随之析构
+                let _tx = tx;
+                sleep(Duration::from_secs(30)).await;
+                Ok(())
+            });
+            sleep(Duration::from_millis(5)).await;
+            token
+        };
+        // group 离开作用域：令牌被取消，子任务被 abort，发送端被丢弃
+        assert!(observed.is_cancelled());
+        let closed = tokio::time::timeout(Duration::from_secs(1), rx).await;
+        assert!(matches!(closed, Ok(Err(_))));
+    }
 }
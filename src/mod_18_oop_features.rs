@@ -11,6 +11,17 @@
 // Rust 面向对象特性
 // 深入讲解 Rust 中实现面向对象编程的各种模式和技术
 
+// GUI 组件通过 Draw trait 把自己画到 canvas::Canvas 帧缓冲上
+use crate::canvas::{Canvas, Rect};
+use crate::image::Rgb;
+// 终端界面的组件 trait 与本章的 Widget 结构体同名，通过模块路径 tui::Widget 区分
+use crate::tui;
+// 文章的状态模式建立在 workflow 模块的状态机上
+use crate::workflow::{Machine, Step, Workflow, WorkflowError};
+use std::sync::OnceLock;
+// 建造者由派生宏生成
+use guide_derive::Builder;
+
 // ===========================================
 // 1. Rust 面向对象特性
 // ===========================================
@@ -333,6 +344,39 @@ impl GuiButton {
     }
 }
 
+// GuiButton 也可以放进终端界面。位置和大小由所在的 tui 容器排版决定，
+// 组合的 Widget 只在像素界面中使用；按钮颜色成为真彩色背景，获得焦点时加粗并加下划线
+impl tui::Widget for GuiButton {
+    fn render(&self, area: tui::Rect, buf: &mut tui::Buffer, focused: bool) {
+        let (r, g, b) = self.color;
+        let mut style = tui::Style::new()
+            .fg(tui::Color::White)
+            .bg(tui::Color::Rgb(Rgb([r, g, b])));
+        if focused {
+            style = style.bold().underline();
+        }
+        buf.fill(area, ' ', style);
+        let width = tui::buffer::str_width(&self.text);
+        let x = area.x + area.width.saturating_sub(width) / 2;
+        let y = area.y + area.height.saturating_sub(1) / 2;
+        buf.set_str(x, y, &self.text, style, area.right() - x);
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_key(&mut self, key: tui::Key) -> tui::EventResult {
+        match key {
+            tui::Key::Enter | tui::Key::Char(' ') => {
+                self.click();
+                tui::EventResult::Consumed
+            }
+            _ => tui::EventResult::Ignored,
+        }
+    }
+}
+
 // Trait 继承示例
 // 基础 Writer trait
 trait Writer {
@@ -389,7 +433,20 @@ fn polymorphism() {
     let mut screen = Screen::new();
     screen.add_component(Box::new(DrawableButton::new(100, 40, "Submit")));
     screen.add_component(Box::new(DrawableTextField::new(200, 30, "输入内容")));
-    screen.run();
+    let canvas = screen.run();
+    // 渲染结果是真正的像素，canvas.save_ppm(path) 可以保存成图片；tests/canvas.rs 把它与期望图像比较
+    println!("像素校验和: {:016x}", canvas.image().checksum());
+
+    // 同一个 Screen 画在终端里，每个组件通过 Draw 的父 trait tui::Widget 绘制
+    let mut buf = tui::Buffer::new(24, 3);
+    tui::Widget::render(&screen, buf.area(), &mut buf, false);
+    println!("终端中的 Screen:");
+    for line in buf.lines() {
+        println!("  {}", line);
+    }
+
+    // 终端版本的 Screen：容器持有 Vec<Box<dyn tui::Widget>>，同样是动态分发
+    terminal_screen();
 
     println!("多态让代码更加灵活和可扩展");
     println!();
@@ -402,13 +459,25 @@ fn print_animal_info<T: Animal>(animal: &T) {
 }
 
 // Draw trait 用于 GUI 组件
-trait Draw {
-    fn draw(&self);
+// 组件只按自己的局部坐标绘制，(0, 0) 是组件的左上角；放在屏幕的哪里由 Screen 决定
+// 以 tui::Widget 为父 trait，同一个组件既能画成像素，也能画在终端里
+pub trait Draw: tui::Widget {
+    // 组件占据的宽高，Screen 据此排列组件
+    fn size(&self) -> (u32, u32);
+    fn draw(&self, canvas: &mut Canvas);
 }
 
+pub const SCREEN_BACKGROUND: Rgb = Rgb([236, 236, 236]);
+const BUTTON_FACE: Rgb = Rgb([52, 120, 200]);
+const BUTTON_BORDER: Rgb = Rgb([24, 60, 110]);
+const FIELD_BORDER: Rgb = Rgb([150, 150, 150]);
+const PLACEHOLDER: Rgb = Rgb([160, 160, 160]);
+const WHITE: Rgb = Rgb([255, 255, 255]);
+const BLACK: Rgb = Rgb([0, 0, 0]);
+
 // Button 组件实现 Draw
 #[derive(Debug)]
-struct DrawableButton {
+pub struct DrawableButton {
     width: u32,
     height: u32,
     label: String,
@@ -425,14 +494,45 @@ impl DrawableButton {
 }
 
 impl Draw for DrawableButton {
-    fn draw(&self) {
-        println!("绘制按钮: {} ({}x{})", self.label, self.width, self.height);
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    // 实心按钮加深色边框，标签在按钮中居中
+    fn draw(&self, canvas: &mut Canvas) {
+        let area = Rect::new(0, 0, self.width, self.height);
+        canvas.fill_rect(area, BUTTON_FACE);
+        canvas.stroke_rect(area, BUTTON_BORDER);
+        let (text_width, text_height) = Canvas::text_size(&self.label, 1);
+        let x = (self.width as i32 - text_width as i32) / 2;
+        let y = (self.height as i32 - text_height as i32) / 2;
+        canvas.text(x, y, &self.label, WHITE);
+    }
+}
+
+// 终端中是一段蓝底白字，左右各留一格
+impl tui::Widget for DrawableButton {
+    fn render(&self, area: tui::Rect, buf: &mut tui::Buffer, _focused: bool) {
+        let style = tui::Style::new()
+            .fg(tui::Color::White)
+            .bg(tui::Color::Rgb(BUTTON_FACE));
+        let width = tui::buffer::str_width(&self.label)
+            .saturating_add(2)
+            .min(area.width);
+        buf.fill(tui::Rect::new(area.x, area.y, width, 1), ' ', style);
+        buf.set_str(
+            area.x + 1,
+            area.y,
+            &self.label,
+            style,
+            width.saturating_sub(2),
+        );
     }
 }
 
 // TextField 组件实现 Draw
 #[derive(Debug)]
-struct DrawableTextField {
+pub struct DrawableTextField {
     width: u32,
     height: u32,
     placeholder: String,
@@ -449,21 +549,50 @@ impl DrawableTextField {
 }
 
 impl Draw for DrawableTextField {
-    fn draw(&self) {
-        println!(
-            "绘制文本框: {} ({}x{})",
-            self.placeholder, self.width, self.height
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    // 白底灰框，左侧是光标和灰色的占位文字；文字超出输入框的部分被裁掉
+    fn draw(&self, canvas: &mut Canvas) {
+        let area = Rect::new(0, 0, self.width, self.height);
+        canvas.fill_rect(area, WHITE);
+        canvas.stroke_rect(area, FIELD_BORDER);
+        let (_, text_height) = Canvas::text_size(&self.placeholder, 1);
+        let y = (self.height as i32 - text_height as i32) / 2;
+        canvas.line((4, y - 1), (4, y + text_height as i32), BLACK);
+        canvas.with_viewport(area.inset(1), |inner| {
+            inner.text(7, y - 1, &self.placeholder, PLACEHOLDER);
+        });
+    }
+}
+
+// 终端中是一行带下划线的输入区，占位文字为灰色，放不下的部分被截掉
+impl tui::Widget for DrawableTextField {
+    fn render(&self, area: tui::Rect, buf: &mut tui::Buffer, _focused: bool) {
+        let field = tui::Style::new().underline();
+        buf.fill(tui::Rect::new(area.x, area.y, area.width, 1), ' ', field);
+        buf.set_str(
+            area.x,
+            area.y,
+            &self.placeholder,
+            field.fg(tui::Color::Gray),
+            area.width,
         );
     }
 }
 
 // Screen 包含多个可绘制的组件
 // 使用 trait 对象 Box<dyn Draw> 实现动态分发
-struct Screen {
+#[derive(Default)]
+pub struct Screen {
     components: Vec<Box<dyn Draw>>,
 }
 
 impl Screen {
+    // 组件之间以及组件与屏幕边缘的间距
+    const MARGIN: u32 = 10;
+
     pub fn new() -> Self {
         Screen {
             components: Vec::new(),
@@ -474,14 +603,80 @@ impl Screen {
         self.components.push(component);
     }
 
-    pub fn run(&self) {
-        println!("运行屏幕渲染:");
-        for component in &self.components {
-            component.draw();
+    // 每个组件在屏幕上的位置：从上到下排成一列，左对齐
+    fn layout(&self) -> Vec<Rect> {
+        let mut y = Self::MARGIN as i32;
+        self.components
+            .iter()
+            .map(|component| {
+                let (width, height) = component.size();
+                let rect = Rect::new(Self::MARGIN as i32, y, width, height);
+                y = rect.bottom() + Self::MARGIN as i32;
+                rect
+            })
+            .collect()
+    }
+
+    // 屏幕的大小刚好容纳所有组件；每个组件画在自己的视口里，画不到别的组件上
+    pub fn run(&self) -> Canvas {
+        let layout = self.layout();
+        let width = layout.iter().map(|rect| rect.width).max().unwrap_or(0) + 2 * Self::MARGIN;
+        let height = layout.last().map_or(0, |rect| rect.bottom() as u32) + Self::MARGIN;
+        let mut canvas = Canvas::new(width, height, SCREEN_BACKGROUND);
+        println!("运行屏幕渲染: {}x{} 像素", width, height);
+        for (component, rect) in self.components.iter().zip(layout) {
+            println!(
+                "  组件位于 ({}, {})，大小 {}x{}",
+                rect.x, rect.y, rect.width, rect.height
+            );
+            canvas.with_viewport(rect, |viewport| component.draw(viewport));
+        }
+        canvas
+    }
+}
+
+// 终端中的 Screen 与像素版本的排列方式相同：组件从上到下各占一行，之间空一行，
+// 放不下的组件不画。组件都不接受焦点，所以 Screen 自己绘制它们，不通过 children 交给容器
+impl tui::Widget for Screen {
+    fn render(&self, area: tui::Rect, buf: &mut tui::Buffer, focused: bool) {
+        let rows = (area.y..area.bottom()).step_by(2);
+        for (component, y) in self.components.iter().zip(rows) {
+            component.render(tui::Rect::new(area.x, y, area.width, 1), buf, focused);
         }
     }
 }
 
+// 终端界面：GuiButton 与内置的 TextInput、Label 按弹性布局排列，Tab 切换焦点，
+// 按键交给获得焦点的组件处理。这里在内存中的虚拟终端上回放一串按键，然后打印屏幕内容
+fn terminal_screen() -> tui::App {
+    use tui::{Constraint, Flex, Key, Label, Terminal, TestBackend, TextInput};
+
+    let mut button = GuiButton::new(0, 0, 8, 1, "提交");
+    button.color = (52, 120, 200);
+    let form = Flex::column()
+        .bordered("Screen")
+        .child(
+            Constraint::Fixed(1),
+            Label::new("Tab 切换焦点，Enter 点击按钮"),
+        )
+        .child(
+            Constraint::Fixed(1),
+            Flex::row()
+                .gap(1)
+                .child(Constraint::Flex(1), TextInput::new("输入内容"))
+                .child(Constraint::Fixed(8), button),
+        );
+    let mut app = tui::App::new(form);
+    let mut terminal = Terminal::new(TestBackend::new(36, 4));
+    let keys = Key::decode(b"Rust\t\r");
+    println!("终端界面回放按键: {:?}", keys);
+    app.run(&mut terminal, keys).expect("写入虚拟终端");
+    for line in terminal.backend().lines() {
+        println!("  {}", line);
+    }
+    app
+}
+
 // ===========================================
 // 5. 对象安全 (Object Safety)
 // ===========================================
@@ -555,157 +750,210 @@ fn send_notification(notifier: &dyn Notifier, message: String) {
 // ===========================================
 
 // 状态模式是一种行为设计模式，允许对象在内部状态改变时改变其行为
-// Rust 可以通过 trait 对象优雅地实现状态模式
+// 传统写法为每个状态定义一个实现 State trait 的结构体，转换规则分散在各个状态的方法里；
+// 这里用 workflow 模块把状态、事件、守卫和钩子声明在一处，Post 只保存当前状态
 
 fn state_pattern() {
     println!("=== 状态模式 ===");
 
     let mut post = Post::new();
 
+    // 空草稿不能提交审核：守卫拒绝了转换
+    if let Err(e) = post.request_review() {
+        println!("提交审核失败: {}", e);
+    }
+
     // 草稿状态
     let _ = post.add_text("我在学习 Rust");
     println!("内容: {}", post.content());
 
     // 审核状态
-    post.request_review();
-    println!("内容: {}", post.content());
+    post.request_review().unwrap();
+    println!("状态: {:?}, 内容: {}", post.state(), post.content());
 
     // 发布状态
-    post.approve();
-    println!("内容: {}", post.content());
+    post.approve().unwrap();
+    println!("状态: {:?}, 内容: {}", post.state(), post.content());
 
     // 尝试在已发布状态下添加内容（应该失败）
     let result = post.add_text("更多内容");
     println!("尝试添加内容: {:?}", result);
+    println!("状态变化记录: {:?}", post.history());
+
+    // 工作流可以导出成 Graphviz 图，用 dot -Tsvg 渲染
+    println!("文章工作流:\n{}", post_workflow().to_dot());
+
+    // 编译期版本：每个状态是一个类型，typed::Post<Draft> 没有 Approve 的转换，
+    // 写 draft.fire(typed::Approve) 不能通过编译
+    let mut draft: typed::Post<typed::Draft> = Machine::new(String::new());
+    draft.add_text("typestate 让非法转换无法编译");
+    match draft.try_fire(typed::RequestReview) {
+        Ok(pending) => {
+            let published = pending.fire(typed::Approve);
+            println!("{}: {}", published.state(), published.content());
+        }
+        Err(rejected) => println!("{}", rejected),
+    }
 
     println!("状态模式让状态转换更加安全和清晰");
     println!();
 }
 
-// 状态 trait 定义状态的行为
-trait State {
-    // 内容是否可以被评论
-    fn is_review_allowed(&self) -> bool;
-
-    // 添加文本的行为
-    fn add_text(&self, post: &mut Post, text: &str) -> Result<(), String>;
-
-    // 请求审核
-    fn request_review(self: Box<Self>) -> Box<dyn State>;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostState {
+    Draft,
+    PendingReview,
+    Published,
+}
 
-    // 批准发布
-    fn approve(self: Box<Self>) -> Box<dyn State>;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostEvent {
+    RequestReview,
+    Approve,
+    // 审核不通过，退回草稿
+    Reject,
 }
 
-// 草稿状态
-struct Draft {}
+// 所有文章共享同一个工作流，上下文就是文章本身
+fn post_workflow() -> &'static Workflow<PostState, PostEvent, Post> {
+    static WORKFLOW: OnceLock<Workflow<PostState, PostEvent, Post>> = OnceLock::new();
+    WORKFLOW.get_or_init(|| {
+        Workflow::builder("文章", PostState::Draft)
+            .transition(
+                PostState::Draft,
+                PostEvent::RequestReview,
+                PostState::PendingReview,
+            )
+            .guard("内容不为空", |post: &Post| !post.content.is_empty())
+            .transition(
+                PostState::PendingReview,
+                PostEvent::Approve,
+                PostState::Published,
+            )
+            .transition(
+                PostState::PendingReview,
+                PostEvent::Reject,
+                PostState::Draft,
+            )
+            .on_enter(PostState::Draft, record_step)
+            .on_enter(PostState::PendingReview, record_step)
+            .on_enter(PostState::Published, record_step)
+            .build()
+            .expect("文章工作流的定义有效")
+    })
+}
 
-impl State for Draft {
-    fn is_review_allowed(&self) -> bool {
-        false
-    }
+fn record_step(post: &mut Post, step: &Step<PostState, PostEvent>) {
+    post.history.push(format!(
+        "{:?}: {:?} -> {:?}",
+        step.event, step.from, step.to
+    ));
+}
 
-    fn add_text(&self, post: &mut Post, text: &str) -> Result<(), String> {
-        post.content.push_str(text);
-        Ok(())
-    }
+// Post 结构体使用状态模式
+pub struct Post {
+    state: PostState,
+    content: String,
+    history: Vec<String>,
+}
 
-    fn request_review(self: Box<Self>) -> Box<dyn State> {
-        Box::new(PendingReview {})
+impl Post {
+    pub fn new() -> Self {
+        Post {
+            state: post_workflow().initial(),
+            content: String::new(),
+            history: Vec::new(),
+        }
     }
 
-    fn approve(self: Box<Self>) -> Box<dyn State> {
-        self // 草稿状态不能直接批准
+    // 只有草稿可以修改；这不是状态转换，直接按当前状态判断
+    pub fn add_text(&mut self, text: &str) -> Result<(), String> {
+        match self.state {
+            PostState::Draft => {
+                self.content.push_str(text);
+                Ok(())
+            }
+            PostState::PendingReview => Err("待审核状态下不能修改内容".to_string()),
+            PostState::Published => Err("已发布的文章不能修改".to_string()),
+        }
     }
-}
 
-// 待审核状态
-struct PendingReview {}
-
-impl State for PendingReview {
-    fn is_review_allowed(&self) -> bool {
-        true
+    pub fn content(&self) -> &str {
+        &self.content
     }
 
-    fn add_text(&self, _post: &mut Post, _text: &str) -> Result<(), String> {
-        Err("待审核状态下不能修改内容".to_string())
+    pub fn state(&self) -> PostState {
+        self.state
     }
 
-    fn request_review(self: Box<Self>) -> Box<dyn State> {
-        self // 已经在审核状态
+    // 每次成功的转换，格式为 "事件: 旧状态 -> 新状态"
+    pub fn history(&self) -> &[String] {
+        &self.history
     }
 
-    fn approve(self: Box<Self>) -> Box<dyn State> {
-        Box::new(Published {})
+    pub fn request_review(&mut self) -> Result<(), WorkflowError> {
+        self.fire(PostEvent::RequestReview)
     }
-}
 
-// 已发布状态
-struct Published {}
-
-impl State for Published {
-    fn is_review_allowed(&self) -> bool {
-        false
+    pub fn approve(&mut self) -> Result<(), WorkflowError> {
+        self.fire(PostEvent::Approve)
     }
 
-    fn add_text(&self, _post: &mut Post, _text: &str) -> Result<(), String> {
-        Err("已发布的文章不能修改".to_string())
+    pub fn reject(&mut self) -> Result<(), WorkflowError> {
+        self.fire(PostEvent::Reject)
     }
 
-    fn request_review(self: Box<Self>) -> Box<dyn State> {
-        self // 已发布的文章不能重新审核
+    pub fn is_review_allowed(&self) -> bool {
+        self.state == PostState::PendingReview
     }
 
-    fn approve(self: Box<Self>) -> Box<dyn State> {
-        self // 已经是发布状态
+    // 失败时状态和内容都保持不变
+    fn fire(&mut self, event: PostEvent) -> Result<(), WorkflowError> {
+        self.state = post_workflow().fire(self.state, event, self)?;
+        Ok(())
     }
 }
 
-// Post 结构体使用状态模式
-pub struct Post {
-    state: Option<Box<dyn State>>,
-    content: String,
-}
+// 同样的流程用 typestate 实现：状态是类型，转换是 trait 实现
+pub mod typed {
+    use crate::workflow::{GuardedTransition, Machine};
 
-impl Post {
-    pub fn new() -> Self {
-        Post {
-            state: Some(Box::new(Draft {})),
-            content: String::new(),
-        }
-    }
+    pub struct RequestReview;
+    pub struct Approve;
+    pub struct Reject;
 
-    pub fn add_text(&mut self, text: &str) -> Result<(), String> {
-        if let Some(state) = self.state.take() {
-            let result = state.add_text(self, text);
-            self.state = Some(state);
-            result
-        } else {
-            Err("文章状态无效".to_string())
-        }
+    crate::typestate! {
+        pub states: Draft, PendingReview, Published;
+        PendingReview + Approve => Published;
+        PendingReview + Reject => Draft;
     }
 
-    pub fn content(&self) -> &str {
-        &self.content
-    }
+    // 上下文是文章内容
+    pub type Post<S> = Machine<S, String>;
 
-    pub fn request_review(&mut self) {
-        if let Some(state) = self.state.take() {
-            self.state = Some(state.request_review());
+    impl GuardedTransition<RequestReview, String> for Draft {
+        type Next = PendingReview;
+
+        fn guard(content: &String, _event: &RequestReview) -> Result<(), String> {
+            if content.is_empty() {
+                Err("内容不能为空".to_string())
+            } else {
+                Ok(())
+            }
         }
     }
 
-    pub fn approve(&mut self) {
-        if let Some(state) = self.state.take() {
-            self.state = Some(state.approve());
+    // 修改内容的方法只为草稿状态实现
+    impl Post<Draft> {
+        pub fn add_text(&mut self, text: &str) {
+            self.context_mut().push_str(text);
         }
     }
 
-    pub fn is_review_allowed(&self) -> bool {
-        if let Some(ref state) = self.state {
-            state.is_review_allowed()
-        } else {
-            false
+    // 只有已发布的文章提供 content
+    impl Post<Published> {
+        pub fn content(&self) -> &str {
+            self.context()
         }
     }
 }
@@ -802,88 +1050,41 @@ fn builder_pattern() {
 
     println!("创建的用户: {:?}", user);
 
+    // 缺少必填字段或校验失败时返回带类型的错误
+    match User::builder().name("李四").email("lisi").build() {
+        Ok(user) => println!("创建的用户: {:?}", user),
+        Err(e) => println!("创建失败: {}", e),
+    }
+    let missing = User::builder().name("王五").build();
+    println!("缺少邮箱: {:?}", missing.unwrap_err());
+
     println!("建造者模式使复杂对象的创建更加清晰和灵活");
     println!();
 }
 
 // 用户结构体
-#[derive(Debug, Clone)]
+// UserBuilder 和 UserBuilderError 由 #[derive(Builder)] 生成：
+// name 和 email 必填，age 默认为 0，Option 字段可以不设置
+// 字符串字段的 setter 用 into 接受 &str；整数字段不加 into，否则 age(25) 中的字面量无法推断类型
+#[derive(Debug, Clone, Builder)]
 struct User {
+    #[builder(into)]
     name: String,
+    #[builder(into, validate = validate_email)]
     email: String,
+    #[builder(default)]
     age: u32,
+    #[builder(into)]
     address: Option<String>,
+    #[builder(into)]
     phone: Option<String>,
 }
 
-impl User {
-    // 建造者
-    pub fn builder() -> UserBuilder {
-        UserBuilder::new()
-    }
-}
-
-// 用户建造者
-#[derive(Debug)]
-struct UserBuilder {
-    name: Option<String>,
-    email: Option<String>,
-    age: Option<u32>,
-    address: Option<String>,
-    phone: Option<String>,
-}
-
-impl UserBuilder {
-    pub fn new() -> Self {
-        UserBuilder {
-            name: None,
-            email: None,
-            age: None,
-            address: None,
-            phone: None,
-        }
-    }
-
-    pub fn name(mut self, name: &str) -> Self {
-        self.name = Some(name.to_string());
-        self
-    }
-
-    pub fn email(mut self, email: &str) -> Self {
-        self.email = Some(email.to_string());
-        self
-    }
-
-    pub fn age(mut self, age: u32) -> Self {
-        self.age = Some(age);
-        self
-    }
-
-    pub fn address(mut self, address: &str) -> Self {
-        self.address = Some(address.to_string());
-        self
-    }
-
-    pub fn phone(mut self, phone: &str) -> Self {
-        self.phone = Some(phone.to_string());
-        self
-    }
-
-    pub fn build(self) -> Result<User, String> {
-        if self.name.is_none() {
-            return Err("用户名是必需的".to_string());
-        }
-        if self.email.is_none() {
-            return Err("邮箱是必需的".to_string());
-        }
-
-        Ok(User {
-            name: self.name.unwrap(),
-            email: self.email.unwrap(),
-            age: self.age.unwrap_or(0),
-            address: self.address,
-            phone: self.phone,
-        })
+fn validate_email(email: &str) -> Result<(), String> {
+    if email.contains('@') {
+        Ok(())
+    } else {
+        Err(format!("{} 不是有效的邮箱", email))
     }
 }
 
@@ -946,13 +1147,68 @@ mod tests {
         post.add_text("测试内容").unwrap();
         assert!(!post.is_review_allowed());
 
-        post.request_review();
+        post.request_review().unwrap();
         assert!(post.is_review_allowed());
 
-        post.approve();
+        post.approve().unwrap();
         assert!(!post.is_review_allowed());
     }
 
+    #[test]
+    fn test_post_workflow_guards_and_history() {
+        let mut post = Post::new();
+        let err = post.request_review().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "文章: 状态 Draft 的事件 RequestReview 被守卫拒绝 (内容不为空)"
+        );
+        assert!(matches!(
+            post.approve(),
+            Err(WorkflowError::NoTransition { .. })
+        ));
+        assert_eq!(post.state(), PostState::Draft);
+
+        post.add_text("草稿").unwrap();
+        post.request_review().unwrap();
+        assert!(post.add_text("再改一下").is_err());
+        post.reject().unwrap();
+        post.add_text("，修改后").unwrap();
+        post.request_review().unwrap();
+        post.approve().unwrap();
+        assert_eq!(post.content(), "草稿，修改后");
+        assert_eq!(
+            post.history(),
+            [
+                "RequestReview: Draft -> PendingReview",
+                "Reject: PendingReview -> Draft",
+                "RequestReview: Draft -> PendingReview",
+                "Approve: PendingReview -> Published",
+            ]
+        );
+
+        let dot = post_workflow().to_dot();
+        assert!(
+            dot.contains("\"Draft\" -> \"PendingReview\" [label=\"RequestReview [内容不为空]\"];")
+        );
+        assert!(dot.contains("\"Published\" [peripheries=2];"));
+    }
+
+    #[test]
+    fn test_typed_post() {
+        let draft: typed::Post<typed::Draft> = Machine::new(String::new());
+        let mut draft = draft.try_fire(typed::RequestReview).unwrap_err().machine;
+        draft.add_text("内容");
+        let pending = draft.try_fire(typed::RequestReview).unwrap();
+        let mut draft = pending.fire(typed::Reject);
+        draft.add_text("，修改");
+        let published = draft
+            .try_fire(typed::RequestReview)
+            .unwrap()
+            .fire(typed::Approve);
+        assert_eq!(published.state(), "Published");
+        assert_eq!(published.content(), "内容，修改");
+    }
+
     #[test]
     fn test_strategy_pattern() {
         let text = "hello world";
@@ -972,6 +1228,30 @@ mod tests {
         assert_eq!(user.email, "test@example.com");
     }
 
+    #[test]
+    fn test_builder_errors() {
+        let err = User::builder().email("a@b.c").build().unwrap_err();
+        assert_eq!(err, UserBuilderError::MissingField("name"));
+
+        let err = User::builder()
+            .name("测试用户")
+            .email("invalid")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "User 的字段 email 无效: invalid 不是有效的邮箱"
+        );
+
+        let user = User::builder()
+            .name("测试用户")
+            .email("test@example.com")
+            .phone("123")
+            .build()
+            .unwrap();
+        assert_eq!((user.age, user.phone.as_deref()), (0, Some("123")));
+    }
+
     #[test]
     fn test_screen_components() {
         let mut screen = Screen::new();
@@ -980,4 +1260,73 @@ mod tests {
 
         assert_eq!(screen.components.len(), 2);
     }
+
+    #[test]
+    fn test_screen_renders_in_terminal() {
+        let mut screen = Screen::new();
+        screen.add_component(Box::new(DrawableButton::new(100, 40, "Submit")));
+        screen.add_component(Box::new(DrawableTextField::new(200, 30, "输入内容")));
+        screen.add_component(Box::new(DrawableButton::new(60, 20, "放不下")));
+
+        let mut buf = tui::Buffer::new(7, 3);
+        tui::Widget::render(&screen, buf.area(), &mut buf, false);
+        assert_eq!(buf.lines(), [" Submi ", "       ", "输入内 "]);
+        assert_eq!(buf.cell(0, 0).style.bg, tui::Color::Rgb(BUTTON_FACE));
+        assert_eq!(buf.cell(0, 2).style.fg, tui::Color::Gray);
+        assert!(buf.cell(6, 2).style.underline);
+    }
+
+    #[test]
+    fn test_gui_button_in_terminal_screen() {
+        // 回放的 Tab 把焦点从输入框移到 GuiButton
+        let app = terminal_screen();
+        assert_eq!(app.focus_count(), 2);
+        assert_eq!(app.focused(), Some(1));
+
+        let mut button = GuiButton::new(0, 0, 8, 1, "OK");
+        let mut buf = tui::Buffer::new(8, 1);
+        tui::Widget::render(&button, buf.area(), &mut buf, true);
+        assert_eq!(buf.lines(), ["   OK   "]);
+        assert_eq!(buf.cell(0, 0).style.bg, tui::Color::Rgb(Rgb([0, 0, 255])));
+        assert!(buf.cell(3, 0).style.bold);
+        assert_eq!(
+            tui::Widget::handle_key(&mut button, tui::Key::Tab),
+            tui::EventResult::Ignored
+        );
+    }
+
+    #[test]
+    fn test_components_render_to_pixels() {
+        let button = DrawableButton::new(60, 20, "Submit");
+        let mut canvas = Canvas::new(60, 20, SCREEN_BACKGROUND);
+        button.draw(&mut canvas);
+        assert_eq!(canvas.get(0, 0), Some(BUTTON_BORDER));
+        assert_eq!(canvas.get(2, 2), Some(BUTTON_FACE));
+
+        let field = DrawableTextField::new(80, 16, "Search...");
+        let mut canvas = Canvas::new(80, 16, SCREEN_BACKGROUND);
+        field.draw(&mut canvas);
+        assert_eq!(canvas.get(79, 15), Some(FIELD_BORDER));
+    }
+
+    #[test]
+    fn test_screen_renders_components_in_viewports() {
+        let mut screen = Screen::new();
+        screen.add_component(Box::new(DrawableButton::new(100, 40, "Submit")));
+        screen.add_component(Box::new(DrawableTextField::new(200, 30, "输入内容")));
+        // 占位文字比输入框长，超出的部分不能画到边框和屏幕背景上
+        screen.add_component(Box::new(DrawableTextField::new(
+            60,
+            16,
+            "a placeholder that does not fit",
+        )));
+        let canvas = screen.run();
+        assert_eq!(
+            (canvas.image().width(), canvas.image().height()),
+            (220, 126)
+        );
+        assert_eq!(canvas.get(10, 10), Some(BUTTON_BORDER));
+        assert_eq!(canvas.get(69, 100), Some(FIELD_BORDER));
+        assert!((100..116).all(|y| canvas.get(70, y) == Some(SCREEN_BACKGROUND)));
+    }
 }
@@ -0,0 +1,8 @@
+// 网络工具
+// raw：直接通过 socket / connect / poll 等系统调用实现的 TCP 套接字，
+// 演示第 15 章 SafeSocket 那样的 FFI 包装如何与 std::os::fd 的 OwnedFd / BorrowedFd 配合
+//
+// 常量和结构体布局按 Linux 的 ABI 声明（其他 Unix 的 sockaddr_in、O_NONBLOCK 等取值不同），所以只在 Linux 上编译
+
+#[cfg(target_os = "linux")]
+pub mod raw;
@@ -0,0 +1,583 @@
+// 基于系统调用的 TCP 套接字
+// 1. Socket 内部就是一个 OwnedFd：关闭由 OwnedFd 的 Drop 负责，与 std 的类型可以双向转换
+// 2. 所有系统调用的返回值经过 cvt 检查，失败时返回 io::Error::last_os_error()，错误码可以用 Diagnostic 归类
+// 3. poll 借用 BorrowedFd，生命周期保证等待期间描述符不会被关闭
+//
+// 非阻塞模式下，没有数据可读或缓冲区已满时返回 ErrorKind::WouldBlock，配合 poll 等待就绪
+
+use std::ffi::{c_int, c_ulong, c_void};
+use std::io;
+use std::marker::PhantomData;
+use std::net::{Ipv4Addr, SocketAddrV4, TcpListener, TcpStream};
+use std::ops::BitOr;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::time::Duration;
+
+// ===========================================
+// 1. 系统调用声明
+// ===========================================
+
+mod sys {
+    use std::ffi::{c_int, c_ulong, c_void};
+
+    pub const AF_INET: c_int = 2;
+    pub const SOCK_STREAM: c_int = 1;
+    // 创建时就设置 close-on-exec，避免描述符泄漏到子进程
+    pub const SOCK_CLOEXEC: c_int = 0o2000000;
+    pub const SOL_SOCKET: c_int = 1;
+    pub const SO_REUSEADDR: c_int = 2;
+    pub const SO_ERROR: c_int = 4;
+    pub const F_GETFL: c_int = 3;
+    pub const F_SETFL: c_int = 4;
+    pub const O_NONBLOCK: c_int = 0o4000;
+    // 对端已关闭时 send 返回 EPIPE，而不是向整个进程发送 SIGPIPE
+    pub const MSG_NOSIGNAL: c_int = 0x4000;
+    pub const SHUT_WR: c_int = 1;
+    pub const EINTR: i32 = 4;
+
+    #[repr(C)]
+    pub struct SockAddrIn {
+        pub sin_family: u16,
+        // 端口和地址都是网络字节序（大端）
+        pub sin_port: u16,
+        pub sin_addr: u32,
+        pub sin_zero: [u8; 8],
+    }
+
+    #[repr(C)]
+    pub struct PollFd {
+        pub fd: c_int,
+        pub events: i16,
+        pub revents: i16,
+    }
+
+    unsafe extern "C" {
+        pub fn socket(domain: c_int, ty: c_int, protocol: c_int) -> c_int;
+        pub fn bind(fd: c_int, addr: *const SockAddrIn, len: u32) -> c_int;
+        pub fn listen(fd: c_int, backlog: c_int) -> c_int;
+        pub fn accept4(fd: c_int, addr: *mut SockAddrIn, len: *mut u32, flags: c_int) -> c_int;
+        pub fn connect(fd: c_int, addr: *const SockAddrIn, len: u32) -> c_int;
+        pub fn getsockname(fd: c_int, addr: *mut SockAddrIn, len: *mut u32) -> c_int;
+        pub fn getpeername(fd: c_int, addr: *mut SockAddrIn, len: *mut u32) -> c_int;
+        pub fn setsockopt(
+            fd: c_int,
+            level: c_int,
+            name: c_int,
+            value: *const c_void,
+            len: u32,
+        ) -> c_int;
+        pub fn getsockopt(
+            fd: c_int,
+            level: c_int,
+            name: c_int,
+            value: *mut c_void,
+            len: *mut u32,
+        ) -> c_int;
+        pub fn send(fd: c_int, buf: *const c_void, len: usize, flags: c_int) -> isize;
+        pub fn recv(fd: c_int, buf: *mut c_void, len: usize, flags: c_int) -> isize;
+        pub fn shutdown(fd: c_int, how: c_int) -> c_int;
+        pub fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+        pub fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+    }
+}
+
+// 系统调用约定：返回 -1 表示失败，错误码在 errno 中
+fn cvt<T: Copy + PartialEq + From<i8>>(ret: T) -> io::Result<T> {
+    if ret == T::from(-1) {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+// 被信号打断（EINTR）的调用重新执行
+fn cvt_retry<T: Copy + PartialEq + From<i8>>(mut call: impl FnMut() -> T) -> io::Result<T> {
+    loop {
+        match cvt(call()) {
+            Err(e) if e.raw_os_error() == Some(sys::EINTR) => continue,
+            other => return other,
+        }
+    }
+}
+
+fn to_sockaddr(addr: SocketAddrV4) -> sys::SockAddrIn {
+    sys::SockAddrIn {
+        sin_family: sys::AF_INET as u16,
+        sin_port: addr.port().to_be(),
+        sin_addr: u32::from(*addr.ip()).to_be(),
+        sin_zero: [0; 8],
+    }
+}
+
+fn from_sockaddr(raw: &sys::SockAddrIn) -> SocketAddrV4 {
+    SocketAddrV4::new(
+        Ipv4Addr::from(u32::from_be(raw.sin_addr)),
+        u16::from_be(raw.sin_port),
+    )
+}
+
+const SOCKADDR_LEN: u32 = std::mem::size_of::<sys::SockAddrIn>() as u32;
+
+// ===========================================
+// 2. Socket
+// ===========================================
+
+// IPv4 TCP 套接字
+#[derive(Debug)]
+pub struct Socket {
+    fd: OwnedFd,
+}
+
+impl Socket {
+    pub fn tcp_v4() -> io::Result<Socket> {
+        let fd =
+            cvt(unsafe { sys::socket(sys::AF_INET, sys::SOCK_STREAM | sys::SOCK_CLOEXEC, 0) })?;
+        // socket 成功返回的描述符归我们所有
+        Ok(Socket {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    // 创建、绑定并开始监听；端口为 0 时由系统分配，用 local_addr 查询
+    pub fn listen_on(addr: SocketAddrV4, backlog: i32) -> io::Result<Socket> {
+        let socket = Socket::tcp_v4()?;
+        socket.set_reuse_address(true)?;
+        socket.bind(addr)?;
+        socket.listen(backlog)?;
+        Ok(socket)
+    }
+
+    pub fn connect_to(addr: SocketAddrV4) -> io::Result<Socket> {
+        let socket = Socket::tcp_v4()?;
+        socket.connect(addr)?;
+        Ok(socket)
+    }
+
+    pub fn bind(&self, addr: SocketAddrV4) -> io::Result<()> {
+        let raw = to_sockaddr(addr);
+        cvt(unsafe { sys::bind(self.raw(), &raw, SOCKADDR_LEN) })?;
+        Ok(())
+    }
+
+    pub fn listen(&self, backlog: i32) -> io::Result<()> {
+        cvt(unsafe { sys::listen(self.raw(), backlog) })?;
+        Ok(())
+    }
+
+    // 新连接继承 close-on-exec，但不继承监听套接字的非阻塞模式
+    pub fn accept(&self) -> io::Result<(Socket, SocketAddrV4)> {
+        let mut raw = to_sockaddr(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let mut len = SOCKADDR_LEN;
+        let fd = cvt_retry(|| unsafe {
+            sys::accept4(self.raw(), &mut raw, &mut len, sys::SOCK_CLOEXEC)
+        })?;
+        let socket = Socket {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        };
+        Ok((socket, from_sockaddr(&raw)))
+    }
+
+    // 非阻塞模式下连接通常不能立即完成，返回 EINPROGRESS（见 is_in_progress）；
+    // 之后 poll 等待可写，再用 take_error 检查连接结果
+    pub fn connect(&self, addr: SocketAddrV4) -> io::Result<()> {
+        let raw = to_sockaddr(addr);
+        cvt(unsafe { sys::connect(self.raw(), &raw, SOCKADDR_LEN) })?;
+        Ok(())
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddrV4> {
+        self.query_addr(sys::getsockname)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddrV4> {
+        self.query_addr(sys::getpeername)
+    }
+
+    fn query_addr(
+        &self,
+        call: unsafe extern "C" fn(c_int, *mut sys::SockAddrIn, *mut u32) -> c_int,
+    ) -> io::Result<SocketAddrV4> {
+        let mut raw = to_sockaddr(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let mut len = SOCKADDR_LEN;
+        cvt(unsafe { call(self.raw(), &mut raw, &mut len) })?;
+        Ok(from_sockaddr(&raw))
+    }
+
+    pub fn send(&self, data: &[u8]) -> io::Result<usize> {
+        let sent = cvt_retry(|| unsafe {
+            sys::send(
+                self.raw(),
+                data.as_ptr() as *const c_void,
+                data.len(),
+                sys::MSG_NOSIGNAL,
+            )
+        })?;
+        Ok(sent as usize)
+    }
+
+    // 返回 Ok(0) 表示对端已经关闭了写方向
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let received = cvt_retry(|| unsafe {
+            sys::recv(self.raw(), buf.as_mut_ptr() as *mut c_void, buf.len(), 0)
+        })?;
+        Ok(received as usize)
+    }
+
+    // 关闭写方向，对端 recv 会读到 0
+    pub fn shutdown_write(&self) -> io::Result<()> {
+        cvt(unsafe { sys::shutdown(self.raw(), sys::SHUT_WR) })?;
+        Ok(())
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        let flags = cvt(unsafe { sys::fcntl(self.raw(), sys::F_GETFL) })?;
+        let flags = if nonblocking {
+            flags | sys::O_NONBLOCK
+        } else {
+            flags & !sys::O_NONBLOCK
+        };
+        cvt(unsafe { sys::fcntl(self.raw(), sys::F_SETFL, flags) })?;
+        Ok(())
+    }
+
+    pub fn is_nonblocking(&self) -> io::Result<bool> {
+        let flags = cvt(unsafe { sys::fcntl(self.raw(), sys::F_GETFL) })?;
+        Ok(flags & sys::O_NONBLOCK != 0)
+    }
+
+    pub fn set_reuse_address(&self, reuse: bool) -> io::Result<()> {
+        let value = c_int::from(reuse);
+        cvt(unsafe {
+            sys::setsockopt(
+                self.raw(),
+                sys::SOL_SOCKET,
+                sys::SO_REUSEADDR,
+                &value as *const c_int as *const c_void,
+                std::mem::size_of::<c_int>() as u32,
+            )
+        })?;
+        Ok(())
+    }
+
+    // 读取并清除套接字上挂起的错误（SO_ERROR），非阻塞 connect 完成后用它判断是否成功
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        let mut value: c_int = 0;
+        let mut len = std::mem::size_of::<c_int>() as u32;
+        cvt(unsafe {
+            sys::getsockopt(
+                self.raw(),
+                sys::SOL_SOCKET,
+                sys::SO_ERROR,
+                &mut value as *mut c_int as *mut c_void,
+                &mut len,
+            )
+        })?;
+        Ok((value != 0).then(|| io::Error::from_raw_os_error(value)))
+    }
+
+    fn raw(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+// 非阻塞 connect 尚未完成
+pub fn is_in_progress(error: &io::Error) -> bool {
+    const EINPROGRESS: i32 = 115;
+    error.raw_os_error() == Some(EINPROGRESS)
+}
+
+impl AsFd for Socket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.raw()
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_raw_fd()
+    }
+}
+
+impl FromRawFd for Socket {
+    unsafe fn from_raw_fd(fd: RawFd) -> Socket {
+        Socket {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        }
+    }
+}
+
+// 与 OwnedFd 互相转换不涉及任何系统调用，只是转移所有权
+impl From<OwnedFd> for Socket {
+    fn from(fd: OwnedFd) -> Socket {
+        Socket { fd }
+    }
+}
+
+impl From<Socket> for OwnedFd {
+    fn from(socket: Socket) -> OwnedFd {
+        socket.fd
+    }
+}
+
+// 通过 OwnedFd 与 std 的网络类型互通
+impl From<Socket> for TcpStream {
+    fn from(socket: Socket) -> TcpStream {
+        TcpStream::from(socket.fd)
+    }
+}
+
+impl From<Socket> for TcpListener {
+    fn from(socket: Socket) -> TcpListener {
+        TcpListener::from(socket.fd)
+    }
+}
+
+impl From<TcpStream> for Socket {
+    fn from(stream: TcpStream) -> Socket {
+        Socket {
+            fd: OwnedFd::from(stream),
+        }
+    }
+}
+
+// ===========================================
+// 3. poll
+// ===========================================
+
+// poll 关注或返回的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Events(i16);
+
+impl Events {
+    pub const NONE: Events = Events(0);
+    pub const READABLE: Events = Events(0x001);
+    pub const WRITABLE: Events = Events(0x004);
+    // 以下三个只会出现在结果中，不需要主动关注
+    pub const ERROR: Events = Events(0x008);
+    pub const HANGUP: Events = Events(0x010);
+    pub const INVALID: Events = Events(0x020);
+
+    pub fn contains(self, other: Events) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Events {
+    type Output = Events;
+
+    fn bitor(self, rhs: Events) -> Events {
+        Events(self.0 | rhs.0)
+    }
+}
+
+// 布局与 C 的 struct pollfd 相同，可以把切片直接传给 poll
+// 借用的生命周期 'fd 保证等待期间描述符不会被关闭或复用
+#[repr(transparent)]
+pub struct PollFd<'fd> {
+    raw: sys::PollFd,
+    _fd: PhantomData<BorrowedFd<'fd>>,
+}
+
+impl<'fd> PollFd<'fd> {
+    pub fn new(fd: BorrowedFd<'fd>, interest: Events) -> PollFd<'fd> {
+        PollFd {
+            raw: sys::PollFd {
+                fd: fd.as_raw_fd(),
+                events: interest.0,
+                revents: 0,
+            },
+            _fd: PhantomData,
+        }
+    }
+
+    // 上一次 poll 返回的就绪事件
+    pub fn ready(&self) -> Events {
+        Events(self.raw.revents)
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.ready().contains(Events::READABLE)
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.ready().contains(Events::WRITABLE)
+    }
+}
+
+impl std::fmt::Debug for PollFd<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PollFd")
+            .field("fd", &self.raw.fd)
+            .field("interest", &Events(self.raw.events))
+            .field("ready", &self.ready())
+            .finish()
+    }
+}
+
+// 等待任意一个描述符就绪，返回就绪的个数；超时返回 0
+// timeout 为 None 时一直等待；被信号打断时用原来的超时重新等待
+pub fn poll(fds: &mut [PollFd<'_>], timeout: Option<Duration>) -> io::Result<usize> {
+    let timeout = match timeout {
+        None => -1,
+        // 向上取整到毫秒，避免 1 微秒的超时变成 0（立即返回）
+        Some(d) => d.as_nanos().div_ceil(1_000_000).min(c_int::MAX as u128) as c_int,
+    };
+    for fd in fds.iter_mut() {
+        fd.raw.revents = 0;
+    }
+    let ready = cvt_retry(|| unsafe {
+        sys::poll(
+            fds.as_mut_ptr() as *mut sys::PollFd,
+            fds.len() as c_ulong,
+            timeout,
+        )
+    })?;
+    Ok(ready as usize)
+}
+
+// 等待单个描述符就绪
+pub fn wait(fd: BorrowedFd<'_>, interest: Events, timeout: Option<Duration>) -> io::Result<Events> {
+    let mut fds = [PollFd::new(fd, interest)];
+    poll(&mut fds, timeout)?;
+    Ok(fds[0].ready())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{ErrorKind, Read, Write};
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    fn loopback() -> SocketAddrV4 {
+        SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)
+    }
+
+    #[test]
+    fn test_loopback_echo_with_poll() {
+        let listener = Socket::listen_on(loopback(), 8).unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert_ne!(addr.port(), 0);
+        listener.set_nonblocking(true).unwrap();
+
+        // 没有等待中的连接：非阻塞 accept 立即返回 WouldBlock
+        assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+
+        let client = Socket::tcp_v4().unwrap();
+        client.set_nonblocking(true).unwrap();
+        match client.connect(addr) {
+            Ok(()) => {}
+            Err(e) if is_in_progress(&e) => {
+                let ready = wait(client.as_fd(), Events::WRITABLE, TIMEOUT).unwrap();
+                assert!(ready.contains(Events::WRITABLE));
+                assert!(client.take_error().unwrap().is_none());
+            }
+            Err(e) => panic!("连接失败: {}", e),
+        }
+
+        assert!(
+            wait(listener.as_fd(), Events::READABLE, TIMEOUT)
+                .unwrap()
+                .contains(Events::READABLE)
+        );
+        let (server, peer) = listener.accept().unwrap();
+        assert_eq!(peer, client.local_addr().unwrap());
+        assert_eq!(server.peer_addr().unwrap(), client.local_addr().unwrap());
+        assert!(!server.is_nonblocking().unwrap());
+        server.set_nonblocking(true).unwrap();
+
+        // 还没有数据：poll 超时返回 0，recv 返回 WouldBlock
+        let mut fds = [PollFd::new(server.as_fd(), Events::READABLE)];
+        assert_eq!(poll(&mut fds, Some(Duration::ZERO)).unwrap(), 0);
+        let mut buf = [0u8; 64];
+        assert_eq!(
+            server.recv(&mut buf).unwrap_err().kind(),
+            ErrorKind::WouldBlock
+        );
+
+        // 客户端发送，服务端等到可读后原样发回
+        assert_eq!(client.send(b"ping").unwrap(), 4);
+        assert_eq!(poll(&mut fds, TIMEOUT).unwrap(), 1);
+        assert!(fds[0].is_readable());
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        server.send(&buf[..n]).unwrap();
+
+        // 同时等待两个描述符，只有客户端可读
+        let mut fds = [
+            PollFd::new(server.as_fd(), Events::READABLE),
+            PollFd::new(client.as_fd(), Events::READABLE),
+        ];
+        assert_eq!(poll(&mut fds, TIMEOUT).unwrap(), 1);
+        assert!(!fds[0].is_readable() && fds[1].is_readable());
+        let n = client.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ping");
+
+        // 关闭写方向后，对端读到 EOF
+        client.shutdown_write().unwrap();
+        assert!(
+            wait(server.as_fd(), Events::READABLE, TIMEOUT)
+                .unwrap()
+                .contains(Events::READABLE)
+        );
+        assert_eq!(server.recv(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fd_ownership_round_trips_through_std() {
+        let listener: TcpListener = Socket::listen_on(loopback(), 1).unwrap().into();
+        let addr = match listener.local_addr().unwrap() {
+            std::net::SocketAddr::V4(addr) => addr,
+            other => panic!("意外的地址 {}", other),
+        };
+
+        // Socket -> OwnedFd -> Socket -> TcpStream：同一个描述符，所有权一路转移
+        let client = Socket::connect_to(addr).unwrap();
+        let raw = client.as_raw_fd();
+        let owned = OwnedFd::from(client);
+        assert_eq!(owned.as_raw_fd(), raw);
+        let mut stream = TcpStream::from(Socket::from(owned));
+        assert_eq!(stream.as_raw_fd(), raw);
+
+        let (mut accepted, _) = listener.accept().unwrap();
+        stream.write_all(b"via std").unwrap();
+        let mut buf = [0u8; 7];
+        accepted.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"via std");
+
+        // 反方向：std 的 TcpStream 交给 Socket 继续使用
+        let socket = Socket::from(accepted);
+        socket.send(b"back").unwrap();
+        let mut buf = [0u8; 4];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"back");
+    }
+
+    #[test]
+    fn test_errors_come_from_errno() {
+        use crate::errors::{Diagnostic, ErrorCode};
+
+        let socket = Socket::tcp_v4().unwrap();
+        // 没有连接的套接字没有对端地址
+        let err = socket.peer_addr().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotConnected);
+        assert_eq!(err.code(), ErrorCode::IO);
+
+        // 端口已被占用
+        let first = Socket::tcp_v4().unwrap();
+        first.bind(loopback()).unwrap();
+        first.listen(1).unwrap();
+        let err = socket.bind(first.local_addr().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AddrInUse);
+    }
+}
@@ -0,0 +1,211 @@
+// 数值类型
+// 第 8 章的 Vector2D 和 Complex 只实现了几个演示用的运算符，这里做成完整的数值模块：
+// 1. Complex<T>：复数的四则运算、极坐标、exp / ln / pow，Display 输出的文本可以再解析回来
+// 2. Vec2<T> / Vec3<T>：点积、叉积、长度、归一化
+// 3. fft / ifft：Complex<f64> 上的基 2 快速傅里叶变换
+//
+// 运算符对值和引用的四种组合都有实现：a + b、&a + b、a + &b、&a + &b，
+// 复合赋值也接受引用，在循环中累加时不需要先解引用或 clone。
+// 整数也能做加减乘除，需要开方、三角函数的方法只对实现了 Float 的 f32 / f64 提供。
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+// 为 impl Op<Rhs> for Lhs 补上左右两边取引用的三种组合，通过 Copy 转发给按值的实现；
+// 不带 <T> 的写法用于具体类型，比如标量在左边的 f64 * Complex<f64>
+macro_rules! forward_ref_binop {
+    (impl $imp:ident, $method:ident for $lhs:ty, $rhs:ty) => {
+        impl $imp<$rhs> for &$lhs {
+            type Output = <$lhs as $imp<$rhs>>::Output;
+
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                $imp::$method(*self, rhs)
+            }
+        }
+
+        impl $imp<&$rhs> for $lhs {
+            type Output = <$lhs as $imp<$rhs>>::Output;
+
+            fn $method(self, rhs: &$rhs) -> Self::Output {
+                $imp::$method(self, *rhs)
+            }
+        }
+
+        impl $imp<&$rhs> for &$lhs {
+            type Output = <$lhs as $imp<$rhs>>::Output;
+
+            fn $method(self, rhs: &$rhs) -> Self::Output {
+                $imp::$method(*self, *rhs)
+            }
+        }
+    };
+    (impl<$t:ident> $imp:ident, $method:ident for $lhs:ty, $rhs:ty) => {
+        impl<$t: Copy> $imp<$rhs> for &$lhs
+        where
+            $lhs: $imp<$rhs>,
+        {
+            type Output = <$lhs as $imp<$rhs>>::Output;
+
+            fn $method(self, rhs: $rhs) -> Self::Output {
+                $imp::$method(*self, rhs)
+            }
+        }
+
+        impl<$t: Copy> $imp<&$rhs> for $lhs
+        where
+            $lhs: $imp<$rhs>,
+        {
+            type Output = <$lhs as $imp<$rhs>>::Output;
+
+            fn $method(self, rhs: &$rhs) -> Self::Output {
+                $imp::$method(self, *rhs)
+            }
+        }
+
+        impl<$t: Copy> $imp<&$rhs> for &$lhs
+        where
+            $lhs: $imp<$rhs>,
+        {
+            type Output = <$lhs as $imp<$rhs>>::Output;
+
+            fn $method(self, rhs: &$rhs) -> Self::Output {
+                $imp::$method(*self, *rhs)
+            }
+        }
+    };
+}
+
+// a op= &b
+macro_rules! forward_ref_op_assign {
+    (impl<$t:ident> $imp:ident, $method:ident for $lhs:ty, $rhs:ty) => {
+        impl<$t: Copy> $imp<&$rhs> for $lhs
+        where
+            $lhs: $imp<$rhs>,
+        {
+            fn $method(&mut self, rhs: &$rhs) {
+                $imp::$method(self, *rhs)
+            }
+        }
+    };
+}
+
+// -&a
+macro_rules! forward_ref_unop {
+    (impl<$t:ident> $imp:ident, $method:ident for $operand:ty) => {
+        impl<$t: Copy> $imp for &$operand
+        where
+            $operand: $imp,
+        {
+            type Output = <$operand as $imp>::Output;
+
+            fn $method(self) -> Self::Output {
+                $imp::$method(*self)
+            }
+        }
+    };
+}
+
+pub mod complex;
+pub mod fft;
+pub mod vector;
+
+pub use complex::{Complex, ParseComplexError};
+pub use fft::{FftError, fft, fft_real, ifft};
+pub use vector::{Vec2, Vec3};
+
+// 浮点数：f32 和 f64 的公共接口
+pub trait Float:
+    Copy
+    + PartialOrd
+    + Default
+    + fmt::Debug
+    + fmt::Display
+    + FromStr
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const PI: Self;
+    const EPSILON: Self;
+
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn is_finite(self) -> bool;
+    fn is_sign_negative(self) -> bool;
+}
+
+macro_rules! float {
+    ($($t:ident),*) => {
+        $(
+            impl Float for $t {
+                const ZERO: Self = 0.0;
+                const ONE: Self = 1.0;
+                const PI: Self = std::$t::consts::PI;
+                const EPSILON: Self = $t::EPSILON;
+
+                fn abs(self) -> Self {
+                    $t::abs(self)
+                }
+
+                fn sqrt(self) -> Self {
+                    $t::sqrt(self)
+                }
+
+                fn exp(self) -> Self {
+                    $t::exp(self)
+                }
+
+                fn ln(self) -> Self {
+                    $t::ln(self)
+                }
+
+                fn powf(self, n: Self) -> Self {
+                    $t::powf(self, n)
+                }
+
+                fn sin(self) -> Self {
+                    $t::sin(self)
+                }
+
+                fn cos(self) -> Self {
+                    $t::cos(self)
+                }
+
+                fn atan2(self, other: Self) -> Self {
+                    $t::atan2(self, other)
+                }
+
+                fn hypot(self, other: Self) -> Self {
+                    $t::hypot(self, other)
+                }
+
+                fn is_finite(self) -> bool {
+                    $t::is_finite(self)
+                }
+
+                fn is_sign_negative(self) -> bool {
+                    $t::is_sign_negative(self)
+                }
+            }
+        )*
+    };
+}
+
+float!(f32, f64);
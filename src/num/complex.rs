@@ -0,0 +1,539 @@
+// Complex<T>：直角坐标形式的复数 re + im·i
+// 加减乘除只要求 T 支持对应的运算，整数复数（高斯整数）也可以使用；
+// 极坐标、exp、ln、pow 等需要 T: Float，多值函数都取主值，辐角在 (-π, π] 之间
+
+use super::Float;
+use crate::errors::{Diagnostic, ErrorCode};
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    pub const fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+}
+
+impl<T: Copy + Neg<Output = T>> Complex<T> {
+    // 共轭 re - im·i
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Mul<Output = T>> Complex<T> {
+    // |z|²，不需要开方
+    pub fn norm_sqr(self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+impl<T: Float> Complex<T> {
+    pub const ZERO: Self = Complex::new(T::ZERO, T::ZERO);
+    pub const ONE: Self = Complex::new(T::ONE, T::ZERO);
+    pub const I: Self = Complex::new(T::ZERO, T::ONE);
+
+    // r·e^(iθ)
+    pub fn from_polar(r: T, theta: T) -> Self {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    // 模；hypot 避免了 re² + im² 的中间结果溢出
+    pub fn norm(self) -> T {
+        self.re.hypot(self.im)
+    }
+
+    // 辐角，取值在 (-π, π]
+    pub fn arg(self) -> T {
+        self.im.atan2(self.re)
+    }
+
+    // (模, 辐角)
+    pub fn to_polar(self) -> (T, T) {
+        (self.norm(), self.arg())
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.re.is_finite() && self.im.is_finite()
+    }
+
+    // 1 / z
+    pub fn inv(self) -> Self {
+        let denominator = self.norm_sqr();
+        Complex::new(self.re / denominator, -self.im / denominator)
+    }
+
+    // e^z = e^re·(cos im + i·sin im)
+    pub fn exp(self) -> Self {
+        Self::from_polar(self.re.exp(), self.im)
+    }
+
+    // 主值 ln|z| + i·arg z；ln 0 的实部是 -∞
+    pub fn ln(self) -> Self {
+        let (r, theta) = self.to_polar();
+        Complex::new(r.ln(), theta)
+    }
+
+    // 主值平方根，实部非负
+    pub fn sqrt(self) -> Self {
+        let (r, theta) = self.to_polar();
+        let two = T::ONE + T::ONE;
+        Self::from_polar(r.sqrt(), theta / two)
+    }
+
+    // 整数次幂：按二进制拆分指数，只做乘法，结果比走 exp / ln 精确
+    pub fn powi(self, n: i32) -> Self {
+        let mut base = if n < 0 { self.inv() } else { self };
+        let mut exponent = n.unsigned_abs();
+        let mut result = Self::ONE;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    // 实数次幂 z^n = |z|^n·e^(i·n·arg z)；与 powi 一致，任何数的 0 次幂都是 1（包括 0^0）
+    pub fn powf(self, n: T) -> Self {
+        if n == T::ZERO {
+            return Self::ONE;
+        }
+        if self == Self::ZERO {
+            return Self::ZERO;
+        }
+        let (r, theta) = self.to_polar();
+        Self::from_polar(r.powf(n), theta * n)
+    }
+
+    // 复数次幂 z^w = e^(w·ln z)；0 次幂是 1（包括 0^0），0 的其他次幂定义为 0
+    pub fn powc(self, exponent: Self) -> Self {
+        if exponent == Self::ZERO {
+            return Self::ONE;
+        }
+        if self == Self::ZERO {
+            return Self::ZERO;
+        }
+        (exponent * self.ln()).exp()
+    }
+}
+
+impl<T: Default> From<T> for Complex<T> {
+    fn from(re: T) -> Self {
+        Complex::new(re, T::default())
+    }
+}
+
+impl<T> From<(T, T)> for Complex<T> {
+    fn from((re, im): (T, T)) -> Self {
+        Complex::new(re, im)
+    }
+}
+
+// ===========================================
+// 运算符
+// ===========================================
+
+impl<T: Add<Output = T>> Add for Complex<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+// (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Mul for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+// (a + bi) / (c + di) = ((ac + bd) + (bc - ad)i) / (c² + d²)
+// 整数复数按分量做截断除法
+impl<T> Div for Complex<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let denominator = other.norm_sqr();
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Complex<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+// 与实数运算
+impl<T: Add<Output = T>> Add<T> for Complex<T> {
+    type Output = Self;
+
+    fn add(self, other: T) -> Self {
+        Complex::new(self.re + other, self.im)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub<T> for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, other: T) -> Self {
+        Complex::new(self.re - other, self.im)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, factor: T) -> Self {
+        Complex::new(self.re * factor, self.im * factor)
+    }
+}
+
+impl<T: Copy + Div<Output = T>> Div<T> for Complex<T> {
+    type Output = Self;
+
+    fn div(self, divisor: T) -> Self {
+        Complex::new(self.re / divisor, self.im / divisor)
+    }
+}
+
+// 复合赋值都转发给对应的二元运算
+macro_rules! op_assign {
+    ($($imp:ident, $method:ident, $op:ident, $op_method:ident;)*) => {
+        $(
+            impl<T: Copy> $imp for Complex<T>
+            where
+                Complex<T>: $op<Output = Complex<T>>,
+            {
+                fn $method(&mut self, other: Self) {
+                    *self = $op::$op_method(*self, other);
+                }
+            }
+
+            impl<T: Copy> $imp<T> for Complex<T>
+            where
+                Complex<T>: $op<T, Output = Complex<T>>,
+            {
+                fn $method(&mut self, other: T) {
+                    *self = $op::$op_method(*self, other);
+                }
+            }
+
+            forward_ref_op_assign!(impl<T> $imp, $method for Complex<T>, Complex<T>);
+            forward_ref_op_assign!(impl<T> $imp, $method for Complex<T>, T);
+        )*
+    };
+}
+
+op_assign! {
+    AddAssign, add_assign, Add, add;
+    SubAssign, sub_assign, Sub, sub;
+    MulAssign, mul_assign, Mul, mul;
+    DivAssign, div_assign, Div, div;
+}
+
+forward_ref_binop!(impl<T> Add, add for Complex<T>, Complex<T>);
+forward_ref_binop!(impl<T> Sub, sub for Complex<T>, Complex<T>);
+forward_ref_binop!(impl<T> Mul, mul for Complex<T>, Complex<T>);
+forward_ref_binop!(impl<T> Div, div for Complex<T>, Complex<T>);
+forward_ref_binop!(impl<T> Add, add for Complex<T>, T);
+forward_ref_binop!(impl<T> Sub, sub for Complex<T>, T);
+forward_ref_binop!(impl<T> Mul, mul for Complex<T>, T);
+forward_ref_binop!(impl<T> Div, div for Complex<T>, T);
+forward_ref_unop!(impl<T> Neg, neg for Complex<T>);
+
+// 实数在左边：孤儿规则不允许为泛型 T 实现 T op Complex<T>，只能逐个列出
+macro_rules! scalar_lhs {
+    ($($t:ty),*) => {
+        $(
+            impl Add<Complex<$t>> for $t {
+                type Output = Complex<$t>;
+
+                fn add(self, other: Complex<$t>) -> Complex<$t> {
+                    other + self
+                }
+            }
+
+            impl Sub<Complex<$t>> for $t {
+                type Output = Complex<$t>;
+
+                fn sub(self, other: Complex<$t>) -> Complex<$t> {
+                    Complex::new(self - other.re, -other.im)
+                }
+            }
+
+            impl Mul<Complex<$t>> for $t {
+                type Output = Complex<$t>;
+
+                fn mul(self, other: Complex<$t>) -> Complex<$t> {
+                    other * self
+                }
+            }
+
+            impl Div<Complex<$t>> for $t {
+                type Output = Complex<$t>;
+
+                fn div(self, other: Complex<$t>) -> Complex<$t> {
+                    Complex::from(self) / other
+                }
+            }
+
+            forward_ref_binop!(impl Add, add for $t, Complex<$t>);
+            forward_ref_binop!(impl Sub, sub for $t, Complex<$t>);
+            forward_ref_binop!(impl Mul, mul for $t, Complex<$t>);
+            forward_ref_binop!(impl Div, div for $t, Complex<$t>);
+        )*
+    };
+}
+
+scalar_lhs!(i32, i64, f32, f64);
+
+impl<T> Sum for Complex<T>
+where
+    T: Default + Add<Output = T>,
+{
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Complex::default(), |total, z| total + z)
+    }
+}
+
+impl<'a, T> Sum<&'a Complex<T>> for Complex<T>
+where
+    T: Copy + Default + Add<Output = T> + 'a,
+{
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl<T: Float> Product for Complex<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |total, z| total * z)
+    }
+}
+
+// ===========================================
+// 文本形式
+// ===========================================
+
+// 输出 3+4i、3-4i、-2.5i 这样的形式；精度等格式参数作用于实部和虚部
+impl<T: Float> fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.im.is_sign_negative() { '-' } else { '+' };
+        match f.precision() {
+            Some(precision) => write!(
+                f,
+                "{:.*}{}{:.*}i",
+                precision,
+                self.re,
+                sign,
+                precision,
+                self.im.abs()
+            ),
+            None => write!(f, "{}{}{}i", self.re, sign, self.im.abs()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseComplexError {
+    input: String,
+}
+
+impl fmt::Display for ParseComplexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "无法解析复数: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for ParseComplexError {}
+
+impl Diagnostic for ParseComplexError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::PARSE
+    }
+}
+
+// 接受 3、-2i、i、3+4i、3 - 4i、1e-3+2.5e2i 等形式，运算符两边可以有空格
+impl<T: Float> FromStr for Complex<T> {
+    type Err = ParseComplexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseComplexError {
+            input: s.to_string(),
+        };
+        let compact: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let parse = |part: &str| part.parse::<T>().map_err(|_| error());
+
+        let Some(body) = compact.strip_suffix('i') else {
+            return Ok(Complex::new(parse(&compact)?, T::ZERO));
+        };
+        // 虚部的符号：不在开头、也不是指数（1e-3）一部分的最后一个 + 或 -
+        let bytes = body.as_bytes();
+        let split = (1..bytes.len())
+            .rev()
+            .find(|&i| matches!(bytes[i], b'+' | b'-') && !matches!(bytes[i - 1], b'e' | b'E'));
+        let (re, im) = match split {
+            Some(i) => (parse(&body[..i])?, &body[i..]),
+            None => (T::ZERO, body),
+        };
+        let im = match im {
+            "" | "+" => T::ONE,
+            "-" => -T::ONE,
+            _ => parse(im.strip_prefix('+').unwrap_or(im))?,
+        };
+        Ok(Complex::new(re, im))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    fn assert_close(actual: Complex<f64>, expected: Complex<f64>) {
+        assert!(
+            (actual - expected).norm() < 1e-12,
+            "{} 与 {} 相差过大",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_arithmetic_with_references() {
+        let a = Complex::new(1.0, 2.0);
+        let b = Complex::new(3.0, -4.0);
+        assert_eq!(a * b, Complex::new(11.0, 2.0));
+        assert_eq!(&a * b, a * &b);
+        assert_eq!(&a + &b, Complex::new(4.0, -2.0));
+        assert_eq!((a * b) / b, a);
+        assert_eq!(-&a, Complex::new(-1.0, -2.0));
+        assert_eq!(2.0 * a, a * 2.0);
+        assert_eq!(1.0 / Complex::new(0.0, 1.0), Complex::new(0.0, -1.0));
+
+        // 实数在左边时，值与引用的四种组合都能编译
+        let s = 2.0;
+        assert_eq!(s + a, Complex::new(3.0, 2.0));
+        assert_eq!(&s + a, s + a);
+        assert_eq!(s + &a, s + a);
+        assert_eq!(&s + &a, s + a);
+        assert_eq!(s - a, Complex::new(1.0, -2.0));
+        assert_eq!(&s - a, s - a);
+        assert_eq!(s - &a, s - a);
+        assert_eq!(&s - &a, s - a);
+        assert_eq!(&s * a, a * s);
+        assert_eq!(s * &a, a * s);
+        assert_eq!(&s * &a, a * s);
+        assert_eq!(s / a, Complex::new(0.4, -0.8));
+        assert_eq!(&s / a, s / a);
+        assert_eq!(s / &a, s / a);
+        assert_eq!(&s / &a, s / a);
+        assert_eq!(&3 * Complex::new(1, 2), Complex::new(3, 6));
+
+        let mut total = Complex::ZERO;
+        for z in &[a, b] {
+            total += z;
+        }
+        total *= 2.0;
+        assert_eq!(total, [a, b].iter().sum::<Complex<f64>>() * 2.0);
+
+        // 高斯整数
+        let g = Complex::new(3, 4);
+        assert_eq!(g * g.conj(), Complex::new(25, 0));
+        assert_eq!(g.norm_sqr(), 25);
+        assert_eq!(Complex::new(7, 1) / Complex::new(1, 1), Complex::new(4, -3));
+    }
+
+    #[test]
+    fn test_polar_and_transcendental() {
+        let z = Complex::new(3.0, 4.0);
+        assert_eq!(z.norm(), 5.0);
+        let (r, theta) = z.to_polar();
+        assert_close(Complex::from_polar(r, theta), z);
+
+        // 欧拉公式 e^(iπ) = -1
+        assert_close((Complex::I * PI).exp(), Complex::new(-1.0, 0.0));
+        assert_close(z.ln().exp(), z);
+        assert_close(Complex::new(-1.0, 0.0).ln(), Complex::new(0.0, PI));
+        assert_close(Complex::new(-4.0, 0.0).sqrt(), Complex::new(0.0, 2.0));
+
+        // i^i = e^(-π/2) 是实数
+        assert_close(
+            Complex::I.powc(Complex::I),
+            Complex::new((-FRAC_PI_2).exp(), 0.0),
+        );
+        assert_close(z.powi(3), z * z * z);
+        assert_close(z.powi(-2), (z * z).inv());
+        assert_close(z.powf(0.5), z.sqrt());
+        assert_eq!(Complex::<f64>::ZERO.powf(2.0), Complex::ZERO);
+        // 0^0 = 1，三种幂运算一致
+        assert_eq!(Complex::<f64>::ZERO.powi(0), Complex::ONE);
+        assert_eq!(Complex::<f64>::ZERO.powf(0.0), Complex::ONE);
+        assert_eq!(Complex::<f64>::ZERO.powc(Complex::ZERO), Complex::ONE);
+        assert_eq!(z.powf(0.0), Complex::ONE);
+        assert_eq!(Complex::<f64>::ZERO.powc(Complex::I), Complex::ZERO);
+        assert_close([z, z.inv(), Complex::I].into_iter().product(), Complex::I);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let cases = [
+            (Complex::new(3.0, 4.0), "3+4i"),
+            (Complex::new(3.0, -4.5), "3-4.5i"),
+            (Complex::new(0.0, -1.0), "0-1i"),
+            (Complex::new(-2.0, 0.0), "-2+0i"),
+        ];
+        for (z, text) in cases {
+            assert_eq!(z.to_string(), text);
+            assert_eq!(text.parse::<Complex<f64>>(), Ok(z));
+        }
+        assert_eq!(format!("{:.2}", Complex::new(PI, -1.0)), "3.14-1.00i");
+
+        let parse = |s: &str| s.parse::<Complex<f64>>();
+        assert_eq!(parse("i"), Ok(Complex::new(0.0, 1.0)));
+        assert_eq!(parse("-i"), Ok(Complex::new(0.0, -1.0)));
+        assert_eq!(parse("2.5i"), Ok(Complex::new(0.0, 2.5)));
+        assert_eq!(parse(" 1 - i "), Ok(Complex::new(1.0, -1.0)));
+        assert_eq!(parse("1e-3+2e2i"), Ok(Complex::new(0.001, 200.0)));
+        assert_eq!(parse("-1e+2-3i"), Ok(Complex::new(-100.0, -3.0)));
+        assert_eq!(parse("7"), Ok(Complex::new(7.0, 0.0)));
+        for bad in ["", "1+", "1+2", "a+bi", "1++2i", "ii"] {
+            assert!(parse(bad).is_err(), "{:?} 不应该解析成功", bad);
+        }
+        assert_eq!(parse("x").unwrap_err().code(), ErrorCode::PARSE);
+    }
+}
@@ -0,0 +1,201 @@
+// 基 2 快速傅里叶变换
+// X[k] = Σ x[n]·e^(-2πi·kn/N)，长度 N 必须是 2 的幂，时间复杂度 O(N log N)
+// 迭代实现：先按位反转的下标重排，再自底向上做 log₂N 轮蝶形运算。
+// 旋转因子 e^(-2πi·k/N) 每个都直接由 sin / cos 计算，而不是反复相乘递推，误差不会随 N 累积
+
+use super::Complex;
+use crate::errors::{Diagnostic, ErrorCode};
+use std::f64::consts::PI;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FftError {
+    NotPowerOfTwo(usize),
+}
+
+impl fmt::Display for FftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FftError::NotPowerOfTwo(len) => write!(f, "FFT 的长度必须是 2 的幂，实际为 {}", len),
+        }
+    }
+}
+
+impl std::error::Error for FftError {}
+
+impl Diagnostic for FftError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::INVALID_INPUT
+    }
+}
+
+// 原地正变换；长度不是 2 的幂时返回错误，数据保持不变
+pub fn fft(data: &mut [Complex<f64>]) -> Result<(), FftError> {
+    transform(data, -1.0)
+}
+
+// 原地逆变换，包含 1/N 的归一化，ifft(fft(x)) == x
+pub fn ifft(data: &mut [Complex<f64>]) -> Result<(), FftError> {
+    transform(data, 1.0)?;
+    let scale = 1.0 / data.len() as f64;
+    for z in data {
+        *z *= scale;
+    }
+    Ok(())
+}
+
+// 实数序列的变换，返回完整的 N 个频率分量
+pub fn fft_real(input: &[f64]) -> Result<Vec<Complex<f64>>, FftError> {
+    let mut data: Vec<_> = input.iter().map(|&x| Complex::from(x)).collect();
+    fft(&mut data)?;
+    Ok(data)
+}
+
+// sign = -1 为正变换，+1 为逆变换（不含归一化）
+fn transform(data: &mut [Complex<f64>], sign: f64) -> Result<(), FftError> {
+    let n = data.len();
+    // 空序列的变换还是空序列
+    if n <= 1 {
+        return Ok(());
+    }
+    if !n.is_power_of_two() {
+        return Err(FftError::NotPowerOfTwo(n));
+    }
+
+    // 位反转重排：下标 i 与其 log₂N 位二进制反转后的下标交换
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    // 长度为 len 的子序列用到的旋转因子是 twiddles[k·N/len]
+    let twiddles: Vec<_> = (0..n / 2)
+        .map(|k| Complex::from_polar(1.0, sign * 2.0 * PI * k as f64 / n as f64))
+        .collect();
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let stride = n / len;
+        for chunk in data.chunks_exact_mut(len) {
+            let (even, odd) = chunk.split_at_mut(half);
+            for (k, (a, b)) in even.iter_mut().zip(odd.iter_mut()).enumerate() {
+                let t = *b * twiddles[k * stride];
+                *b = *a - t;
+                *a += t;
+            }
+        }
+        len *= 2;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo_rng::DemoRng;
+
+    // O(N²) 的定义式，作为对照
+    fn naive_dft(input: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        let n = input.len();
+        (0..n)
+            .map(|k| {
+                input
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &x)| {
+                        let angle = -2.0 * PI * ((j * k) % n) as f64 / n as f64;
+                        x * Complex::from_polar(1.0, angle)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn max_error(a: &[Complex<f64>], b: &[Complex<f64>]) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).norm())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_known_transforms() {
+        // 单位脉冲的频谱处处为 1
+        let mut impulse = vec![Complex::ZERO; 8];
+        impulse[0] = Complex::ONE;
+        fft(&mut impulse).unwrap();
+        assert!(impulse.iter().all(|&z| z == Complex::ONE));
+
+        // 常数序列只有直流分量
+        let constant = fft_real(&[2.0; 16]).unwrap();
+        assert!((constant[0] - Complex::from(32.0)).norm() < 1e-12);
+        assert!(constant[1..].iter().all(|z| z.norm() < 1e-12));
+
+        // cos(2π·3n/N) 的能量集中在第 3 和第 N-3 个频率上，各为 N/2
+        let n = 64;
+        let signal: Vec<f64> = (0..n)
+            .map(|i| (2.0 * PI * 3.0 * i as f64 / n as f64).cos())
+            .collect();
+        let spectrum = fft_real(&signal).unwrap();
+        for (k, z) in spectrum.iter().enumerate() {
+            let expected = if k == 3 || k == n - 3 { 32.0 } else { 0.0 };
+            assert!(
+                (z - Complex::from(expected)).norm() < 1e-12,
+                "k = {}: {}",
+                k,
+                z
+            );
+        }
+
+        // 单一频率的复指数 e^(2πi·5n/N) 只在第 5 个频率上非零
+        let mut tone: Vec<_> = (0..n)
+            .map(|i| Complex::from_polar(1.0, 2.0 * PI * 5.0 * i as f64 / n as f64))
+            .collect();
+        fft(&mut tone).unwrap();
+        assert!((tone[5] - Complex::from(64.0)).norm() < 1e-12);
+        assert!(tone[4].norm() < 1e-12);
+        assert!(tone[6].norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_matches_naive_dft_and_inverts() {
+        let rng = DemoRng::from_seed(44);
+        for n in [1, 2, 4, 32, 256, 1024] {
+            let input: Vec<_> = (0..n)
+                .map(|_| Complex::new(rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0)))
+                .collect();
+            let mut output = input.clone();
+            fft(&mut output).unwrap();
+            assert!(max_error(&output, &naive_dft(&input)) < 1e-9, "n = {}", n);
+
+            // Parseval 定理：Σ|x|² = Σ|X|² / N
+            let energy: f64 = input.iter().map(|z| z.norm_sqr()).sum();
+            let spectrum_energy: f64 = output.iter().map(|z| z.norm_sqr()).sum();
+            assert!((energy - spectrum_energy / n as f64).abs() < 1e-9);
+
+            ifft(&mut output).unwrap();
+            assert!(max_error(&output, &input) < 1e-12, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_convolution_and_errors() {
+        // 频域相乘等于时域循环卷积：(1 + 2x)(3 + 4x) = 3 + 10x + 8x²
+        let mut a = fft_real(&[1.0, 2.0, 0.0, 0.0]).unwrap();
+        let b = fft_real(&[3.0, 4.0, 0.0, 0.0]).unwrap();
+        for (x, y) in a.iter_mut().zip(&b) {
+            *x *= y;
+        }
+        ifft(&mut a).unwrap();
+        let product: Vec<f64> = a.iter().map(|z| z.re.round()).collect();
+        assert_eq!(product, [3.0, 10.0, 8.0, 0.0]);
+
+        let mut odd = vec![Complex::ONE; 6];
+        assert_eq!(fft(&mut odd), Err(FftError::NotPowerOfTwo(6)));
+        assert_eq!(odd, vec![Complex::ONE; 6]);
+        assert!(fft(&mut []).is_ok());
+    }
+}
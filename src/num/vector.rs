@@ -0,0 +1,379 @@
+// Vec2<T> / Vec3<T>：二维和三维向量
+// 分量是公开字段；与向量相加减，与标量相乘除。长度、归一化等需要开方的方法要求 T: Float
+
+use super::Float;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Vec2 { x, y }
+    }
+}
+
+impl<T> Vec3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+impl<T: Copy + Sub<Output = T> + Mul<Output = T>> Vec2<T> {
+    // 二维叉积是标量 x1·y2 - y1·x2：两向量张成的平行四边形的有向面积，
+    // 正数表示 other 在 self 的逆时针方向
+    pub fn cross(self, other: Self) -> T {
+        self.x * other.y - self.y * other.x
+    }
+}
+
+impl<T: Copy + Neg<Output = T>> Vec2<T> {
+    // 逆时针旋转 90°
+    pub fn perp(self) -> Self {
+        Vec2::new(-self.y, self.x)
+    }
+}
+
+impl<T: Float> Vec2<T> {
+    // 与 x 轴正方向夹角为 angle 的单位向量
+    pub fn from_angle(angle: T) -> Self {
+        Vec2::new(angle.cos(), angle.sin())
+    }
+
+    // 与 x 轴正方向的夹角，取值在 (-π, π]
+    pub fn angle(self) -> T {
+        self.y.atan2(self.x)
+    }
+
+    pub fn extend(self, z: T) -> Vec3<T> {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl<T: Copy + Sub<Output = T> + Mul<Output = T>> Vec3<T> {
+    // 右手系叉积，结果垂直于两个向量，长度等于平行四边形的面积
+    pub fn cross(self, other: Self) -> Self {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+// 两种向量的公共部分：逐分量的运算和基于点积的方法
+macro_rules! vector {
+    ($name:ident { $first:ident $(, $field:ident)* }) => {
+        impl<T: Copy + Add<Output = T> + Mul<Output = T>> $name<T> {
+            pub fn dot(self, other: Self) -> T {
+                self.$first * other.$first $(+ self.$field * other.$field)*
+            }
+
+            pub fn length_squared(self) -> T {
+                self.dot(self)
+            }
+        }
+
+        impl<T: Float> $name<T> {
+            pub fn length(self) -> T {
+                self.length_squared().sqrt()
+            }
+
+            pub fn distance(self, other: Self) -> T {
+                (self - other).length()
+            }
+
+            // 同方向的单位向量；零向量（或长度不是有限值）没有方向，返回 None
+            pub fn normalize(self) -> Option<Self> {
+                let length = self.length();
+                if length > T::ZERO && length.is_finite() {
+                    Some(self / length)
+                } else {
+                    None
+                }
+            }
+
+            // t = 0 时是 self，t = 1 时是 other
+            pub fn lerp(self, other: Self, t: T) -> Self {
+                self + (other - self) * t
+            }
+
+            // 两向量的夹角，取值在 [0, π]；有零向量时为 None
+            pub fn angle_between(self, other: Self) -> Option<T> {
+                let lengths = self.length() * other.length();
+                if lengths == T::ZERO {
+                    return None;
+                }
+                // 舍入误差可能让余弦略微超出 [-1, 1]
+                let cos = self.dot(other) / lengths;
+                let cos = if cos > T::ONE {
+                    T::ONE
+                } else if cos < -T::ONE {
+                    -T::ONE
+                } else {
+                    cos
+                };
+                // atan2(sin, cos) 在夹角接近 0 或 π 时比 acos 精确
+                Some((T::ONE - cos * cos).sqrt().atan2(cos))
+            }
+
+            // 在 onto 方向上的投影；onto 是零向量时为 None
+            pub fn project_onto(self, onto: Self) -> Option<Self> {
+                let denominator = onto.length_squared();
+                if denominator == T::ZERO {
+                    return None;
+                }
+                Some(onto * (self.dot(onto) / denominator))
+            }
+        }
+
+        impl<T: Add<Output = T>> Add for $name<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                $name { $first: self.$first + other.$first $(, $field: self.$field + other.$field)* }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $name<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                $name { $first: self.$first - other.$first $(, $field: self.$field - other.$field)* }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $name<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                $name { $first: self.$first * factor $(, $field: self.$field * factor)* }
+            }
+        }
+
+        impl<T: Copy + Div<Output = T>> Div<T> for $name<T> {
+            type Output = Self;
+
+            fn div(self, divisor: T) -> Self {
+                $name { $first: self.$first / divisor $(, $field: self.$field / divisor)* }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $name<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $name { $first: -self.$first $(, $field: -self.$field)* }
+            }
+        }
+
+        impl<T: Copy + Add<Output = T>> AddAssign for $name<T> {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Copy + Sub<Output = T>> SubAssign for $name<T> {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> MulAssign<T> for $name<T> {
+            fn mul_assign(&mut self, factor: T) {
+                *self = *self * factor;
+            }
+        }
+
+        impl<T: Copy + Div<Output = T>> DivAssign<T> for $name<T> {
+            fn div_assign(&mut self, divisor: T) {
+                *self = *self / divisor;
+            }
+        }
+
+        forward_ref_binop!(impl<T> Add, add for $name<T>, $name<T>);
+        forward_ref_binop!(impl<T> Sub, sub for $name<T>, $name<T>);
+        forward_ref_binop!(impl<T> Mul, mul for $name<T>, T);
+        forward_ref_binop!(impl<T> Div, div for $name<T>, T);
+        forward_ref_unop!(impl<T> Neg, neg for $name<T>);
+        forward_ref_op_assign!(impl<T> AddAssign, add_assign for $name<T>, $name<T>);
+        forward_ref_op_assign!(impl<T> SubAssign, sub_assign for $name<T>, $name<T>);
+        forward_ref_op_assign!(impl<T> MulAssign, mul_assign for $name<T>, T);
+        forward_ref_op_assign!(impl<T> DivAssign, div_assign for $name<T>, T);
+
+        impl<T: Default + Add<Output = T>> Sum for $name<T> {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold($name::default(), |total, v| total + v)
+            }
+        }
+
+        impl<'a, T: Copy + Default + Add<Output = T> + 'a> Sum<&'a $name<T>> for $name<T> {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
+
+        // 输出 (1, 2, 3)；格式参数作用于每个分量
+        impl<T: fmt::Display> fmt::Display for $name<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("(")?;
+                fmt::Display::fmt(&self.$first, f)?;
+                $(
+                    f.write_str(", ")?;
+                    fmt::Display::fmt(&self.$field, f)?;
+                )*
+                f.write_str(")")
+            }
+        }
+    };
+}
+
+vector!(Vec2 { x, y });
+vector!(Vec3 { x, y, z });
+
+// 标量在左边，原因同 Complex；标量和向量相加减、标量除以向量没有意义，只有乘法
+macro_rules! scalar_lhs {
+    ($($t:ty),*) => {
+        $(
+            impl Mul<Vec2<$t>> for $t {
+                type Output = Vec2<$t>;
+
+                fn mul(self, v: Vec2<$t>) -> Vec2<$t> {
+                    v * self
+                }
+            }
+
+            impl Mul<Vec3<$t>> for $t {
+                type Output = Vec3<$t>;
+
+                fn mul(self, v: Vec3<$t>) -> Vec3<$t> {
+                    v * self
+                }
+            }
+
+            forward_ref_binop!(impl Mul, mul for $t, Vec2<$t>);
+            forward_ref_binop!(impl Mul, mul for $t, Vec3<$t>);
+        )*
+    };
+}
+
+scalar_lhs!(i32, i64, f32, f64);
+
+impl<T> From<[T; 2]> for Vec2<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl<T> From<(T, T)> for Vec2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl<T> From<Vec2<T>> for [T; 2] {
+    fn from(v: Vec2<T>) -> Self {
+        [v.x, v.y]
+    }
+}
+
+impl<T> From<[T; 3]> for Vec3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl<T> From<(T, T, T)> for Vec3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl<T> From<Vec3<T>> for [T; 3] {
+    fn from(v: Vec3<T>) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_vec2_operations() {
+        let a = Vec2::new(3.0, 4.0);
+        let b = Vec2::new(-4.0, 3.0);
+        assert_eq!(a.length(), 5.0);
+        assert_eq!(a.dot(b), 0.0);
+        assert_eq!(a.cross(b), 25.0);
+        assert_eq!(a.perp(), b);
+        assert_eq!(a.normalize(), Some(Vec2::new(0.6, 0.8)));
+        assert_eq!(Vec2::new(0.0, 0.0).normalize(), None);
+        assert!((a.angle_between(b).unwrap() - FRAC_PI_2).abs() < 1e-15);
+        assert!((Vec2::from_angle(FRAC_PI_4).angle() - FRAC_PI_4).abs() < 1e-15);
+        assert_eq!(a.angle_between(-a), Some(PI));
+        assert_eq!(
+            a.project_onto(Vec2::new(2.0, 0.0)),
+            Some(Vec2::new(3.0, 0.0))
+        );
+
+        // 值与引用的各种组合
+        assert_eq!(&a + &b, a + b);
+        assert_eq!(&a - b, a - &b);
+        assert_eq!(&a * 2.0, 2.0 * a);
+        assert_eq!(2.0 * &a, 2.0 * a);
+        assert_eq!(&2.0 * a, 2.0 * a);
+        assert_eq!(&2.0 * &a, 2.0 * a);
+        assert_eq!(-&a / 2.0, Vec2::new(-1.5, -2.0));
+        let mut c = a;
+        c += &b;
+        c *= 2.0;
+        assert_eq!(c, Vec2::new(-2.0, 14.0));
+        assert_eq!(a.lerp(b, 0.5), Vec2::new(-0.5, 3.5));
+        assert_eq!(format!("{:.1}", a), "(3.0, 4.0)");
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_vec3_operations() {
+        let x = Vec3::new(1, 0, 0);
+        let y = Vec3::new(0, 1, 0);
+        assert_eq!(x.cross(y), Vec3::new(0, 0, 1));
+        assert_eq!(y.cross(x), Vec3::new(0, 0, -1));
+        assert_eq!(x.dot(y), 0);
+
+        let a = Vec3::new(1.0, 2.0, 2.0);
+        let b = Vec3::new(-2.0, 0.5, 3.0);
+        let normal = a.cross(b);
+        assert_eq!((normal.dot(a), normal.dot(b)), (0.0, 0.0));
+        assert_eq!(a.length(), 3.0);
+        assert_eq!(a.distance(a + Vec3::new(0.0, 3.0, 4.0)), 5.0);
+        let unit = b.normalize().unwrap();
+        assert!((unit.length() - 1.0).abs() < 1e-15);
+
+        let vertices = [a, b, -a - b];
+        assert_eq!(vertices.iter().sum::<Vec3<f64>>(), Vec3::default());
+        assert_eq!(
+            <[f64; 3]>::from(Vec2::new(1.0, 2.0).extend(3.0)),
+            [1.0, 2.0, 3.0]
+        );
+        assert_eq!(Vec3::from((1, 2, 3)).to_string(), "(1, 2, 3)");
+        let v = Vec3::new(1, 2, 3);
+        assert_eq!(2 * &v, Vec3::new(2, 4, 6));
+        assert_eq!(&2 * v, &2 * &v);
+    }
+}
@@ -0,0 +1,479 @@
+// 动态插件加载
+// 在第 15 章 SafeLibrary（dlopen / dlsym / dlclose 的 RAII 包装）的基础上实现一个插件系统：
+// 1. 插件是导出了 guide_plugin_entry 的 cdylib，ABI 定义在 guide-plugin-api crate 中
+// 2. 加载时检查入口符号、ABI 版本和函数表大小，任何一项不符合都返回 PluginError，而不是冒险调用
+// 3. 加载成功后通过安全的 Plugin trait 调用；插件实例先于动态库销毁
+//
+// 编写插件的方法见 crates/sample-plugin：实现 PluginImpl，再调用 export_plugin!
+
+pub use guide_plugin_api as abi;
+
+use crate::errors::{Diagnostic, ErrorCode};
+use abi::{PluginBuffer, PluginVTable};
+use std::ffi::{CStr, CString, c_void};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+// ===========================================
+// 1. 错误
+// ===========================================
+
+#[derive(Debug)]
+pub enum PluginError {
+    // dlopen 失败：文件不存在、不是动态库、依赖缺失等
+    Load {
+        path: PathBuf,
+        message: String,
+    },
+    MissingSymbol {
+        path: PathBuf,
+        symbol: String,
+    },
+    AbiMismatch {
+        expected: u32,
+        found: u32,
+    },
+    VTableTooSmall {
+        expected: usize,
+        found: usize,
+    },
+    InvalidVTable(String),
+    DuplicateName(String),
+    CreateFailed(String),
+    // 插件返回了错误；status 是 abi 中定义的返回码
+    Call {
+        plugin: String,
+        status: i32,
+        message: String,
+    },
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Load { path, message } => {
+                write!(f, "无法加载 {}: {}", path.display(), message)
+            }
+            PluginError::MissingSymbol { path, symbol } => {
+                write!(f, "{} 中没有符号 {}", path.display(), symbol)
+            }
+            PluginError::AbiMismatch { expected, found } => {
+                write!(f, "插件 ABI 版本为 {}，宿主需要 {}", found, expected)
+            }
+            PluginError::VTableTooSmall { expected, found } => {
+                write!(
+                    f,
+                    "插件函数表只有 {} 字节，至少需要 {} 字节",
+                    found, expected
+                )
+            }
+            PluginError::InvalidVTable(reason) => write!(f, "插件函数表无效: {}", reason),
+            PluginError::DuplicateName(name) => write!(f, "已经加载了名为 '{}' 的插件", name),
+            PluginError::CreateFailed(name) => write!(f, "插件 '{}' 创建实例失败", name),
+            PluginError::Call {
+                plugin,
+                status,
+                message,
+            } => write!(f, "插件 '{}' 返回错误 {}: {}", plugin, status, message),
+            PluginError::Io { path, error } => {
+                write!(f, "读取插件目录 {} 失败: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PluginError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnostic for PluginError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            PluginError::Load { .. } | PluginError::MissingSymbol { .. } => ErrorCode::PLUGIN_LOAD,
+            PluginError::AbiMismatch { .. }
+            | PluginError::VTableTooSmall { .. }
+            | PluginError::InvalidVTable(_) => ErrorCode::PLUGIN_ABI,
+            PluginError::DuplicateName(_)
+            | PluginError::CreateFailed(_)
+            | PluginError::Call { .. } => ErrorCode::PLUGIN_CALL,
+            PluginError::Io { error, .. } => error.code(),
+        }
+    }
+}
+
+// ===========================================
+// 2. SafeLibrary：动态库句柄
+// ===========================================
+
+mod dl {
+    use std::ffi::{c_char, c_int, c_void};
+
+    pub const RTLD_NOW: c_int = 2;
+
+    unsafe extern "C" {
+        pub fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+        pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        pub fn dlclose(handle: *mut c_void) -> c_int;
+        pub fn dlerror() -> *mut c_char;
+    }
+}
+
+// dlopen 得到的句柄，Drop 时 dlclose
+// 从库中取出的函数指针和数据指针在句柄销毁后全部失效，调用方需要保证库活得比它们久
+pub struct SafeLibrary {
+    handle: NonNull<c_void>,
+    path: PathBuf,
+}
+
+// dlopen / dlsym / dlclose 都是线程安全的
+unsafe impl Send for SafeLibrary {}
+unsafe impl Sync for SafeLibrary {}
+
+impl SafeLibrary {
+    // 不含 '/' 的名称按系统的动态库搜索路径查找，例如 "libm.so.6"
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let path = path.as_ref().to_path_buf();
+        let load_error = |message: String| PluginError::Load {
+            path: path.clone(),
+            message,
+        };
+        let c_path = CString::new(path.as_os_str().as_encoded_bytes())
+            .map_err(|_| load_error("路径中包含 NUL 字符".to_string()))?;
+        // RTLD_NOW：加载时就解析所有符号，缺少依赖会在这里报错，而不是在第一次调用时
+        let handle = unsafe { dl::dlopen(c_path.as_ptr(), dl::RTLD_NOW) };
+        match NonNull::new(handle) {
+            Some(handle) => Ok(SafeLibrary { handle, path }),
+            None => Err(load_error(last_dl_error())),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 查找符号并把地址解释为 T（函数指针或数据指针）
+    ///
+    /// # Safety
+    /// T 必须与符号的真实类型一致，且使用结果时库必须仍然处于加载状态
+    pub unsafe fn symbol<T: Copy>(&self, name: &CStr) -> Result<T, PluginError> {
+        assert_eq!(
+            std::mem::size_of::<T>(),
+            std::mem::size_of::<*mut c_void>(),
+            "符号只能解释为指针大小的类型"
+        );
+        let address = unsafe { dl::dlsym(self.handle.as_ptr(), name.as_ptr()) };
+        if address.is_null() {
+            return Err(PluginError::MissingSymbol {
+                path: self.path.clone(),
+                symbol: name.to_string_lossy().into_owned(),
+            });
+        }
+        Ok(unsafe { std::mem::transmute_copy(&address) })
+    }
+}
+
+impl Drop for SafeLibrary {
+    fn drop(&mut self) {
+        unsafe {
+            dl::dlclose(self.handle.as_ptr());
+        }
+    }
+}
+
+fn last_dl_error() -> String {
+    let error = unsafe { dl::dlerror() };
+    if error.is_null() {
+        "未知错误".to_string()
+    } else {
+        unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+// ===========================================
+// 3. 插件
+// ===========================================
+
+// 宿主使用插件的安全接口
+pub trait Plugin {
+    fn name(&self) -> &str;
+    fn version(&self) -> &str;
+    fn process(&mut self, input: &str) -> Result<String, PluginError>;
+}
+
+/// 检查函数表：先只读取所有版本都相同的头部（abi_version、size），确认兼容后才访问其余字段
+///
+/// # Safety
+/// ptr 必须为空或指向至少包含 abi_version 和 size 两个字段的有效内存
+pub unsafe fn check_vtable<'a>(ptr: *const PluginVTable) -> Result<&'a PluginVTable, PluginError> {
+    if ptr.is_null() {
+        return Err(PluginError::InvalidVTable(
+            "入口函数返回了空指针".to_string(),
+        ));
+    }
+    let (found, size) = unsafe { ((*ptr).abi_version, (*ptr).size) };
+    if found != abi::ABI_VERSION {
+        return Err(PluginError::AbiMismatch {
+            expected: abi::ABI_VERSION,
+            found,
+        });
+    }
+    let expected = std::mem::size_of::<PluginVTable>();
+    if size < expected {
+        return Err(PluginError::VTableTooSmall {
+            expected,
+            found: size,
+        });
+    }
+    let vtable = unsafe { &*ptr };
+    if vtable.name.is_null() || vtable.version.is_null() {
+        return Err(PluginError::InvalidVTable("缺少名称或版本".to_string()));
+    }
+    Ok(vtable)
+}
+
+// 读取函数表中的静态字符串
+fn vtable_str(ptr: *const std::ffi::c_char, field: &str) -> Result<String, PluginError> {
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| PluginError::InvalidVTable(format!("{} 不是有效的 UTF-8", field)))
+}
+
+// 已加载的插件实例
+// 函数表指向库中的静态数据，因此同时持有库的引用计数，保证库在实例销毁之后才卸载
+pub struct LoadedPlugin {
+    instance: NonNull<c_void>,
+    vtable: *const PluginVTable,
+    name: String,
+    version: String,
+    // 必须是最后一个字段：Drop::drop 先销毁实例，然后字段按声明顺序析构，库最后卸载
+    _library: Arc<SafeLibrary>,
+}
+
+impl LoadedPlugin {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        Self::from_library(Arc::new(SafeLibrary::open(path)?))
+    }
+
+    // 同一个库可以创建多个实例，它们共享库的引用计数
+    pub fn from_library(library: Arc<SafeLibrary>) -> Result<Self, PluginError> {
+        let entry: abi::EntryFn = unsafe { library.symbol(abi::ENTRY_SYMBOL)? };
+        let vtable = unsafe { check_vtable(entry())? };
+        let name = vtable_str(vtable.name, "name")?;
+        let version = vtable_str(vtable.version, "version")?;
+        let instance = NonNull::new(unsafe { (vtable.create)() })
+            .ok_or_else(|| PluginError::CreateFailed(name.clone()))?;
+        Ok(LoadedPlugin {
+            instance,
+            vtable,
+            name,
+            version,
+            _library: library,
+        })
+    }
+
+    fn vtable(&self) -> &PluginVTable {
+        unsafe { &*self.vtable }
+    }
+}
+
+impl Plugin for LoadedPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn process(&mut self, input: &str) -> Result<String, PluginError> {
+        let vtable = self.vtable();
+        let mut out = PluginBuffer::empty();
+        let status = unsafe {
+            (vtable.process)(
+                self.instance.as_ptr(),
+                input.as_ptr(),
+                input.len(),
+                &mut out,
+            )
+        };
+        // 先把结果复制到宿主的内存中，再把缓冲区交回插件释放
+        let text = String::from_utf8_lossy(unsafe { out.as_bytes() }).into_owned();
+        unsafe { (vtable.free_buffer)(out) };
+        if status == abi::STATUS_OK {
+            Ok(text)
+        } else {
+            Err(PluginError::Call {
+                plugin: self.name.clone(),
+                status,
+                message: text,
+            })
+        }
+    }
+}
+
+impl Drop for LoadedPlugin {
+    fn drop(&mut self) {
+        unsafe { (self.vtable().destroy)(self.instance.as_ptr()) };
+    }
+}
+
+impl fmt::Debug for LoadedPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadedPlugin")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+// ===========================================
+// 4. 插件注册表
+// ===========================================
+
+// 按名称管理已加载的插件
+#[derive(Debug, Default)]
+pub struct PluginRegistry {
+    plugins: Vec<LoadedPlugin>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, plugin: LoadedPlugin) -> Result<&mut LoadedPlugin, PluginError> {
+        if self.plugins.iter().any(|p| p.name == plugin.name) {
+            return Err(PluginError::DuplicateName(plugin.name.clone()));
+        }
+        self.plugins.push(plugin);
+        Ok(self.plugins.last_mut().expect("刚刚插入"))
+    }
+
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<&mut LoadedPlugin, PluginError> {
+        self.add(LoadedPlugin::load(path)?)
+    }
+
+    // 加载目录中所有扩展名为平台动态库后缀（.so / .dylib）的文件，按文件名排序
+    // 单个文件加载失败不影响其他文件，失败的文件连同原因一起返回；只有目录本身无法读取时才返回 Err
+    pub fn load_dir(
+        &mut self,
+        dir: impl AsRef<Path>,
+    ) -> Result<Vec<(PathBuf, PluginError)>, PluginError> {
+        let dir = dir.as_ref();
+        let io_error = |error| PluginError::Io {
+            path: dir.to_path_buf(),
+            error,
+        };
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            let is_library =
+                path.extension().and_then(|e| e.to_str()) == Some(std::env::consts::DLL_EXTENSION);
+            if is_library && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut skipped = Vec::new();
+        for path in paths {
+            if let Err(error) = self.load(&path) {
+                skipped.push((path, error));
+            }
+        }
+        Ok(skipped)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut dyn Plugin> {
+        self.plugins
+            .iter_mut()
+            .find(|p| p.name == name)
+            .map(|p| p as &mut dyn Plugin)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.plugins.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Echo;
+
+    impl abi::PluginImpl for Echo {
+        const NAME: &'static CStr = c"echo";
+        const VERSION: &'static CStr = c"0.1.0";
+
+        fn process(&mut self, input: &str) -> Result<String, String> {
+            Ok(input.to_string())
+        }
+    }
+
+    #[test]
+    fn test_check_vtable_rejects_incompatible_tables() {
+        let good = abi::vtable::<Echo>();
+        assert!(unsafe { check_vtable(&good) }.is_ok());
+        assert!(matches!(
+            unsafe { check_vtable(std::ptr::null()) },
+            Err(PluginError::InvalidVTable(_))
+        ));
+
+        let mut newer = abi::vtable::<Echo>();
+        newer.abi_version = abi::ABI_VERSION + 1;
+        let err = unsafe { check_vtable(&newer) }.map(|_| ()).unwrap_err();
+        assert!(
+            matches!(err, PluginError::AbiMismatch { found, .. } if found == abi::ABI_VERSION + 1)
+        );
+        assert_eq!(err.code(), ErrorCode::PLUGIN_ABI);
+
+        let mut truncated = abi::vtable::<Echo>();
+        truncated.size = 8;
+        assert!(matches!(
+            unsafe { check_vtable(&truncated) },
+            Err(PluginError::VTableTooSmall { found: 8, .. })
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_safe_library_symbols_and_load_errors() {
+        let libm = SafeLibrary::open("libm.so.6").unwrap();
+        let sqrt: extern "C" fn(f64) -> f64 = unsafe { libm.symbol(c"sqrt") }.unwrap();
+        assert_eq!(sqrt(16.0), 4.0);
+
+        // 普通的动态库没有插件入口
+        let err = LoadedPlugin::from_library(Arc::new(libm)).unwrap_err();
+        assert!(
+            matches!(&err, PluginError::MissingSymbol { symbol, .. } if symbol == "guide_plugin_entry")
+        );
+
+        let err = LoadedPlugin::load("/nonexistent/libnothing.so").unwrap_err();
+        assert!(matches!(err, PluginError::Load { .. }));
+        assert_eq!(err.code(), ErrorCode::PLUGIN_LOAD);
+    }
+}
@@ -0,0 +1,774 @@
+// 弹性模式：限流与熔断
+// 为第 6 章和第 17 章中"时好时坏"的模拟服务提供真正的保护机制
+// 包含令牌桶限流、滑动窗口限流和熔断器（关闭 / 打开 / 半开），均提供同步和异步两种用法
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// ===========================================
+// 1. 可注入的时钟
+// ===========================================
+
+// 限流器和熔断器的行为都依赖"现在几点"
+// 把时间来源抽象成 trait，生产代码用系统时钟，测试用手动时钟精确控制状态转换
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+// 系统单调时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// 手动推进的时钟，克隆体共享同一个时间
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+// ===========================================
+// 2. 限流器
+// ===========================================
+
+// 被限流时返回的错误，携带建议的重试等待时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "请求过于频繁，请在 {:?} 后重试", self.retry_after)
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+// 限流器的公共接口
+// try_acquire 立即返回；acquire_blocking 阻塞线程等待；acquire 在异步任务中等待
+pub trait RateLimiter: Send + Sync {
+    fn try_acquire(&self) -> Result<(), RateLimited>;
+
+    fn acquire_blocking(&self) {
+        while let Err(limited) = self.try_acquire() {
+            std::thread::sleep(limited.retry_after);
+        }
+    }
+
+    fn acquire(&self) -> impl Future<Output = ()> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            while let Err(limited) = self.try_acquire() {
+                tokio::time::sleep(limited.retry_after).await;
+            }
+        }
+    }
+}
+
+// 令牌桶：桶里最多存 capacity 个令牌，按固定速率补充
+// 允许短时突发（桶满时可以连续取走 capacity 个），长期速率受 refill_rate 限制
+pub struct TokenBucket<C: Clock = SystemClock> {
+    capacity: f64,
+    refill_per_sec: f64,
+    clock: C,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket<SystemClock> {
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        Self::with_clock(capacity, refill_per_sec, SystemClock)
+    }
+}
+
+impl<C: Clock> TokenBucket<C> {
+    pub fn with_clock(capacity: u32, refill_per_sec: f64, clock: C) -> Self {
+        assert!(capacity > 0, "令牌桶容量必须大于 0");
+        assert!(refill_per_sec > 0.0, "补充速率必须大于 0");
+        let last_refill = clock.now();
+        TokenBucket {
+            capacity: capacity as f64,
+            refill_per_sec,
+            clock,
+            state: Mutex::new(BucketState {
+                tokens: capacity as f64,
+                last_refill,
+            }),
+        }
+    }
+
+    // 一次取走多个令牌，例如按请求大小计费
+    pub fn try_acquire_n(&self, n: u32) -> Result<(), RateLimited> {
+        let n = n as f64;
+        assert!(n <= self.capacity, "一次请求的令牌数不能超过桶容量");
+
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap();
+        let elapsed = now
+            .saturating_duration_since(state.last_refill)
+            .as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        state.last_refill = now;
+
+        if state.tokens >= n {
+            state.tokens -= n;
+            Ok(())
+        } else {
+            let missing = n - state.tokens;
+            Err(RateLimited {
+                retry_after: Duration::from_secs_f64(missing / self.refill_per_sec),
+            })
+        }
+    }
+
+    // 当前可用令牌数（向下取整）
+    pub fn available(&self) -> u32 {
+        let now = self.clock.now();
+        let state = self.state.lock().unwrap();
+        let elapsed = now
+            .saturating_duration_since(state.last_refill)
+            .as_secs_f64();
+        (state.tokens + elapsed * self.refill_per_sec).min(self.capacity) as u32
+    }
+}
+
+impl<C: Clock> RateLimiter for TokenBucket<C> {
+    fn try_acquire(&self) -> Result<(), RateLimited> {
+        self.try_acquire_n(1)
+    }
+}
+
+// 滑动窗口：任意长度为 window 的时间段内最多放行 max_requests 个请求
+// 相比固定窗口，不会在窗口边界出现两倍突发
+pub struct SlidingWindow<C: Clock = SystemClock> {
+    max_requests: usize,
+    window: Duration,
+    clock: C,
+    log: Mutex<VecDeque<Instant>>,
+}
+
+impl SlidingWindow<SystemClock> {
+    pub fn new(max_requests: usize, window: Duration) -> Self {
+        Self::with_clock(max_requests, window, SystemClock)
+    }
+}
+
+impl<C: Clock> SlidingWindow<C> {
+    pub fn with_clock(max_requests: usize, window: Duration, clock: C) -> Self {
+        assert!(max_requests > 0, "窗口内允许的请求数必须大于 0");
+        SlidingWindow {
+            max_requests,
+            window,
+            clock,
+            log: Mutex::new(VecDeque::with_capacity(max_requests)),
+        }
+    }
+
+    // 当前窗口内已放行的请求数
+    pub fn in_window(&self) -> usize {
+        let now = self.clock.now();
+        let mut log = self.log.lock().unwrap();
+        Self::evict(&mut log, now, self.window);
+        log.len()
+    }
+
+    fn evict(log: &mut VecDeque<Instant>, now: Instant, window: Duration) {
+        while let Some(&oldest) = log.front() {
+            if now.saturating_duration_since(oldest) >= window {
+                log.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl<C: Clock> RateLimiter for SlidingWindow<C> {
+    fn try_acquire(&self) -> Result<(), RateLimited> {
+        let now = self.clock.now();
+        let mut log = self.log.lock().unwrap();
+        Self::evict(&mut log, now, self.window);
+
+        if log.len() < self.max_requests {
+            log.push_back(now);
+            Ok(())
+        } else {
+            // 最早的那条记录滑出窗口后才有空位
+            let oldest = log[0];
+            Err(RateLimited {
+                retry_after: self.window - now.saturating_duration_since(oldest),
+            })
+        }
+    }
+}
+
+// ===========================================
+// 3. 熔断器
+// ===========================================
+
+// 熔断器的三种状态：
+// 1. Closed（关闭）：正常放行，统计连续失败次数
+// 2. Open（打开）：直接拒绝调用，给下游恢复的时间
+// 3. HalfOpen（半开）：冷却期结束后放行少量探测请求，成功足够多次则关闭，任一失败则重新打开
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,   // 关闭状态下连续失败多少次后打开
+    pub success_threshold: u32,   // 半开状态下连续成功多少次后关闭
+    pub open_timeout: Duration,   // 打开状态持续多久后进入半开
+    pub half_open_max_calls: u32, // 半开状态下允许同时进行的探测请求数
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 5,
+            success_threshold: 2,
+            open_timeout: Duration::from_secs(30),
+            half_open_max_calls: 1,
+        }
+    }
+}
+
+// 通过熔断器调用时可能出现的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakerError<E> {
+    Open { retry_after: Duration }, // 熔断器拒绝了调用，下游没有被访问
+    Inner(E),                       // 下游调用本身失败
+}
+
+impl<E: fmt::Display> fmt::Display for BreakerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakerError::Open { retry_after } => {
+                write!(f, "熔断器已打开，{:?} 后重试", retry_after)
+            }
+            BreakerError::Inner(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for BreakerError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BreakerError::Open { .. } => None,
+            BreakerError::Inner(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { failures: u32 },
+    Open { since: Instant },
+    HalfOpen { successes: u32, in_flight: u32 },
+}
+
+// 状态每切换一次 generation 加一；调用开始时记下当时的 generation，
+// 结束时不一致说明它开始于之前的状态（例如关闭时发出、半开时才返回的慢调用），结果直接丢弃
+#[derive(Debug)]
+struct Shared {
+    state: BreakerState,
+    generation: u64,
+}
+
+impl Shared {
+    fn transition(&mut self, state: BreakerState) {
+        self.state = state;
+        self.generation += 1;
+    }
+}
+
+pub struct CircuitBreaker<C: Clock = SystemClock> {
+    config: CircuitBreakerConfig,
+    clock: C,
+    shared: Mutex<Shared>,
+}
+
+impl CircuitBreaker<SystemClock> {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self::with_clock(config, SystemClock)
+    }
+}
+
+impl<C: Clock> CircuitBreaker<C> {
+    pub fn with_clock(config: CircuitBreakerConfig, clock: C) -> Self {
+        assert!(config.failure_threshold > 0, "失败阈值必须大于 0");
+        assert!(config.success_threshold > 0, "成功阈值必须大于 0");
+        assert!(config.half_open_max_calls > 0, "半开探测数必须大于 0");
+        CircuitBreaker {
+            config,
+            clock,
+            shared: Mutex::new(Shared {
+                state: BreakerState::Closed { failures: 0 },
+                generation: 0,
+            }),
+        }
+    }
+
+    // 当前状态；Open 超过冷却时间后读取到的是 HalfOpen
+    pub fn state(&self) -> CircuitState {
+        let mut shared = self.shared.lock().unwrap();
+        self.refresh(&mut shared, self.clock.now());
+        match shared.state {
+            BreakerState::Closed { .. } => CircuitState::Closed,
+            BreakerState::Open { .. } => CircuitState::Open,
+            BreakerState::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    // 同步调用：熔断器打开时不执行 operation
+    pub fn call<T, E, F>(&self, operation: F) -> Result<T, BreakerError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let generation = self.try_begin()?;
+        // operation panic 时守卫把这次调用记为失败，半开状态下占用的探测名额也随之释放
+        let mut guard = CallGuard {
+            breaker: self,
+            generation,
+            done: false,
+        };
+        let result = operation();
+        guard.done = true;
+        self.record(generation, result.is_ok());
+        result.map_err(BreakerError::Inner)
+    }
+
+    // 异步调用：锁只在调用前后短暂持有，不会跨越 .await
+    pub async fn call_async<T, E, F, Fut>(&self, operation: F) -> Result<T, BreakerError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let generation = self.try_begin()?;
+        // 如果 future 在完成前被丢弃（例如外层超时），守卫会把这次探测记为失败
+        let mut guard = CallGuard {
+            breaker: self,
+            generation,
+            done: false,
+        };
+        let result = operation().await;
+        guard.done = true;
+        self.record(generation, result.is_ok());
+        result.map_err(BreakerError::Inner)
+    }
+
+    // 手动复位到关闭状态，复位前开始的调用不再计入
+    pub fn reset(&self) {
+        self.shared
+            .lock()
+            .unwrap()
+            .transition(BreakerState::Closed { failures: 0 });
+    }
+
+    fn refresh(&self, shared: &mut Shared, now: Instant) {
+        if let BreakerState::Open { since } = shared.state
+            && now.saturating_duration_since(since) >= self.config.open_timeout
+        {
+            shared.transition(BreakerState::HalfOpen {
+                successes: 0,
+                in_flight: 0,
+            });
+        }
+    }
+
+    // 放行时返回当前的 generation
+    fn try_begin<E>(&self) -> Result<u64, BreakerError<E>> {
+        let mut shared = self.shared.lock().unwrap();
+        // 只读一次时钟：两次读取之间冷却时间可能恰好到期，相减会下溢
+        let now = self.clock.now();
+        self.refresh(&mut shared, now);
+        let generation = shared.generation;
+        match &mut shared.state {
+            BreakerState::Closed { .. } => Ok(generation),
+            BreakerState::Open { since } => {
+                let elapsed = now.saturating_duration_since(*since);
+                Err(BreakerError::Open {
+                    retry_after: self.config.open_timeout.saturating_sub(elapsed),
+                })
+            }
+            BreakerState::HalfOpen { in_flight, .. } => {
+                if *in_flight < self.config.half_open_max_calls {
+                    *in_flight += 1;
+                    Ok(generation)
+                } else {
+                    Err(BreakerError::Open {
+                        retry_after: Duration::ZERO,
+                    })
+                }
+            }
+        }
+    }
+
+    fn record(&self, generation: u64, success: bool) {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.generation != generation {
+            return;
+        }
+        let now = self.clock.now();
+        let next = match &mut shared.state {
+            BreakerState::Closed { failures } => {
+                if success {
+                    *failures = 0;
+                    None
+                } else {
+                    *failures += 1;
+                    (*failures >= self.config.failure_threshold)
+                        .then_some(BreakerState::Open { since: now })
+                }
+            }
+            // 打开状态不放行调用，同一 generation 的调用不会在这里结束
+            BreakerState::Open { .. } => None,
+            BreakerState::HalfOpen {
+                successes,
+                in_flight,
+            } => {
+                *in_flight = in_flight.saturating_sub(1);
+                if !success {
+                    Some(BreakerState::Open { since: now })
+                } else {
+                    *successes += 1;
+                    (*successes >= self.config.success_threshold)
+                        .then_some(BreakerState::Closed { failures: 0 })
+                }
+            }
+        };
+        if let Some(state) = next {
+            shared.transition(state);
+        }
+    }
+}
+
+struct CallGuard<'a, C: Clock> {
+    breaker: &'a CircuitBreaker<C>,
+    generation: u64,
+    done: bool,
+}
+
+impl<C: Clock> Drop for CallGuard<'_, C> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.breaker.record(self.generation, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_burst_and_refill() {
+        let clock = ManualClock::new();
+        let bucket = TokenBucket::with_clock(3, 2.0, clock.clone());
+
+        assert!(bucket.try_acquire().is_ok());
+        assert!(bucket.try_acquire().is_ok());
+        assert!(bucket.try_acquire().is_ok());
+        let limited = bucket.try_acquire().unwrap_err();
+        assert_eq!(limited.retry_after, Duration::from_millis(500));
+
+        // 补充速率为每秒 2 个，半秒后正好补回 1 个
+        clock.advance(Duration::from_millis(500));
+        assert!(bucket.try_acquire().is_ok());
+        assert!(bucket.try_acquire().is_err());
+
+        // 长时间空闲也不会超过容量
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(bucket.available(), 3);
+        assert!(bucket.try_acquire_n(3).is_ok());
+        assert!(bucket.try_acquire_n(1).is_err());
+    }
+
+    #[test]
+    fn test_sliding_window_slides() {
+        let clock = ManualClock::new();
+        let window = SlidingWindow::with_clock(2, Duration::from_secs(10), clock.clone());
+
+        assert!(window.try_acquire().is_ok());
+        clock.advance(Duration::from_secs(4));
+        assert!(window.try_acquire().is_ok());
+        assert_eq!(
+            window.try_acquire(),
+            Err(RateLimited {
+                retry_after: Duration::from_secs(6)
+            })
+        );
+
+        // 第一个请求滑出窗口，腾出一个位置
+        clock.advance(Duration::from_secs(6));
+        assert_eq!(window.in_window(), 1);
+        assert!(window.try_acquire().is_ok());
+        assert!(window.try_acquire().is_err());
+    }
+
+    fn test_breaker(clock: &ManualClock) -> CircuitBreaker<ManualClock> {
+        CircuitBreaker::with_clock(
+            CircuitBreakerConfig {
+                failure_threshold: 3,
+                success_threshold: 2,
+                open_timeout: Duration::from_secs(10),
+                half_open_max_calls: 1,
+            },
+            clock.clone(),
+        )
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_after_consecutive_failures() {
+        let clock = ManualClock::new();
+        let breaker = test_breaker(&clock);
+
+        // 成功会清零连续失败计数
+        let _ = breaker.call(|| Err::<(), _>("e"));
+        let _ = breaker.call(|| Err::<(), _>("e"));
+        let _ = breaker.call(|| Ok::<_, &str>(()));
+        let _ = breaker.call(|| Err::<(), _>("e"));
+        let _ = breaker.call(|| Err::<(), _>("e"));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        assert_eq!(
+            breaker.call(|| Err::<(), _>("e")),
+            Err(BreakerError::Inner("e"))
+        );
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // 打开状态下不会调用下游
+        clock.advance(Duration::from_secs(4));
+        let mut invoked = false;
+        let result = breaker.call(|| {
+            invoked = true;
+            Ok::<_, &str>(())
+        });
+        assert!(!invoked);
+        assert_eq!(
+            result,
+            Err(BreakerError::Open {
+                retry_after: Duration::from_secs(6)
+            })
+        );
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_transitions() {
+        let clock = ManualClock::new();
+        let breaker = test_breaker(&clock);
+        for _ in 0..3 {
+            let _ = breaker.call(|| Err::<(), _>("e"));
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // 冷却结束进入半开，探测失败立即重新打开并重新计时
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        let _ = breaker.call(|| Err::<(), _>("still down"));
+        assert_eq!(breaker.state(), CircuitState::Open);
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // 再次半开，连续成功达到阈值后关闭
+        clock.advance(Duration::from_secs(1));
+        assert!(breaker.call(|| Ok::<_, ()>(1)).is_ok());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.call(|| Ok::<_, ()>(2)).is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_limits_half_open_probes() {
+        let clock = ManualClock::new();
+        let breaker = test_breaker(&clock);
+        for _ in 0..3 {
+            let _ = breaker.call(|| Err::<(), _>("e"));
+        }
+        clock.advance(Duration::from_secs(10));
+
+        // 探测进行中时，第二个调用被拒绝
+        let nested = breaker.call(|| Ok::<_, ()>(breaker.call(|| Ok::<_, ()>(()))));
+        assert!(matches!(nested, Ok(Err(BreakerError::Open { .. }))));
+    }
+
+    #[test]
+    fn test_circuit_breaker_panicking_probe_is_released() {
+        let clock = ManualClock::new();
+        let breaker = test_breaker(&clock);
+        for _ in 0..3 {
+            let _ = breaker.call(|| Err::<(), _>("e"));
+        }
+        clock.advance(Duration::from_secs(10));
+
+        // 探测 panic 时记为失败，名额不会一直被占用
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            breaker.call(|| -> Result<(), ()> { panic!("probe panicked") })
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        clock.advance(Duration::from_secs(10));
+        assert!(breaker.call(|| Ok::<_, ()>(())).is_ok());
+    }
+
+    #[test]
+    fn test_circuit_breaker_ignores_calls_from_earlier_state() {
+        let clock = ManualClock::new();
+        let breaker = test_breaker(&clock);
+
+        // 关闭时发出的慢调用在熔断器打开、冷却、进入半开之后才失败：
+        // 它不是探测请求，既不能重新打开熔断器，也不能释放探测名额
+        let slow = breaker.call(|| {
+            for _ in 0..3 {
+                let _ = breaker.call(|| Err::<(), _>("e"));
+            }
+            clock.advance(Duration::from_secs(10));
+            let probe = breaker.call(|| {
+                let blocked = breaker.call(|| Ok::<_, ()>(()));
+                assert!(matches!(blocked, Err(BreakerError::Open { .. })));
+                Ok::<_, ()>(())
+            });
+            assert!(probe.is_ok());
+            Err::<(), _>("slow failure")
+        });
+        assert_eq!(slow, Err(BreakerError::Inner("slow failure")));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // 复位之前开始的失败调用也不计入复位后的连续失败次数
+        let stale = breaker.call(|| {
+            breaker.reset();
+            for _ in 0..2 {
+                let _ = breaker.call(|| Err::<(), _>("e"));
+            }
+            Err::<(), _>("stale")
+        });
+        assert!(stale.is_err());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    // 每次读取都前进一段时间的时钟，用来模拟两次读取之间冷却时间到期
+    #[derive(Clone)]
+    struct TickingClock {
+        inner: ManualClock,
+        step: Duration,
+    }
+
+    impl Clock for TickingClock {
+        fn now(&self) -> Instant {
+            self.inner.advance(self.step);
+            self.inner.now()
+        }
+    }
+
+    #[test]
+    fn test_circuit_breaker_retry_after_never_underflows() {
+        let clock = TickingClock {
+            inner: ManualClock::new(),
+            step: Duration::from_secs(3),
+        };
+        let breaker = CircuitBreaker::with_clock(
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                success_threshold: 1,
+                open_timeout: Duration::from_secs(4),
+                half_open_max_calls: 1,
+            },
+            clock,
+        );
+        let _ = breaker.call(|| Err::<(), _>("e"));
+        for _ in 0..4 {
+            match breaker.call(|| Err::<(), _>("e")) {
+                Err(BreakerError::Open { retry_after }) => {
+                    assert!(retry_after <= Duration::from_secs(4))
+                }
+                other => assert_eq!(other, Err(BreakerError::Inner("e"))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_async() {
+        let clock = ManualClock::new();
+        let breaker = test_breaker(&clock);
+        for _ in 0..3 {
+            let result = breaker
+                .call_async(|| async { Err::<(), _>("async failure") })
+                .await;
+            assert_eq!(result, Err(BreakerError::Inner("async failure")));
+        }
+        let result = breaker.call_async(|| async { Ok::<_, &str>(1) }).await;
+        assert!(matches!(result, Err(BreakerError::Open { .. })));
+
+        // 半开探测被超时丢弃时记为失败
+        clock.advance(Duration::from_secs(10));
+        let slow = breaker.call_async(|| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok::<_, &str>(())
+        });
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), slow)
+                .await
+                .is_err()
+        );
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_async_and_blocking_acquire() {
+        let bucket = TokenBucket::new(1, 100.0);
+        let started = Instant::now();
+        bucket.acquire().await;
+        bucket.acquire().await;
+        bucket.acquire().await;
+        // 第 2、3 个令牌各需等待约 10ms
+        assert!(started.elapsed() >= Duration::from_millis(15));
+
+        let window = SlidingWindow::new(1, Duration::from_millis(20));
+        let started = Instant::now();
+        window.acquire_blocking();
+        window.acquire_blocking();
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+}
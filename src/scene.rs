@@ -0,0 +1,834 @@
+// 保留模式的二维场景图
+// 第 8 章的 GraphicsManager 把图形放在一个扁平的 Vec<Box<dyn Interactive>> 里，
+// 命中测试只看未旋转的矩形，rotate 只是记下一个角度。这里把图形组织成树：
+// 1. 每个节点有相对父节点的仿射变换，世界变换是从根到节点的变换依次相乘
+// 2. 兄弟节点按 z_index 排序绘制，z_index 相同时按加入的先后
+// 3. 命中测试用世界变换的逆矩阵把点变回图形自己的坐标系，旋转、缩放后的图形也能正确判断
+// 4. 选择：可以选中单个图形，也可以选中它所在的最外层组；选中的节点可以一起变换或合并成新的组
+// 5. svg::render 把场景输出成 SVG 文本，输出是确定的，可以直接做快照测试
+//
+// 节点存放在 Vec 中，用 NodeId 下标引用，删除的节点留下空位，旧的 NodeId 不会指向新节点
+
+use crate::errors::{Diagnostic, ErrorCode};
+use crate::image::Rgb;
+use std::fmt;
+
+pub mod svg;
+pub mod transform;
+
+pub use transform::{Bounds, Point, Transform};
+
+// ===========================================
+// 1. 图形与样式
+// ===========================================
+
+// 图形在自己的坐标系中定义，位置、旋转、缩放都交给节点的变换
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    // 左上角在原点
+    Rect { width: f64, height: f64 },
+    // 圆心在原点
+    Circle { radius: f64 },
+    // 按顺序连接的顶点，自动闭合
+    Polygon(Vec<Point>),
+}
+
+impl Shape {
+    pub fn rect(width: f64, height: f64) -> Shape {
+        Shape::Rect { width, height }
+    }
+
+    pub fn circle(radius: f64) -> Shape {
+        Shape::Circle { radius }
+    }
+
+    pub fn polygon(points: impl IntoIterator<Item = Point>) -> Shape {
+        Shape::Polygon(points.into_iter().collect())
+    }
+
+    // p 在图形自己的坐标系中；边界上的点算作命中
+    pub fn contains(&self, p: Point) -> bool {
+        match self {
+            Shape::Rect { width, height } => {
+                (0.0..=*width).contains(&p.x) && (0.0..=*height).contains(&p.y)
+            }
+            Shape::Circle { radius } => p.length_squared() <= radius * radius,
+            // 奇偶规则：从 p 向右的射线穿过边的次数为奇数时在内部
+            Shape::Polygon(points) => {
+                let mut inside = false;
+                for (i, a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if (a.y > p.y) != (b.y > p.y) {
+                        let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                        if p.x < x {
+                            inside = !inside;
+                        }
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        match self {
+            Shape::Rect { width, height } => Some(Bounds::new(
+                Point::new(0.0, 0.0),
+                Point::new(*width, *height),
+            )),
+            Shape::Circle { radius } => Some(Bounds::new(
+                Point::new(-radius, -radius),
+                Point::new(*radius, *radius),
+            )),
+            Shape::Polygon(points) => Bounds::from_points(points.iter().copied()),
+        }
+    }
+
+    // 变换后的包围盒；圆在旋转后包围盒不变，单独处理让结果更紧
+    fn world_bounds(&self, t: Transform) -> Option<Bounds> {
+        match self {
+            Shape::Polygon(points) => Bounds::from_points(points.iter().map(|&p| t * p)),
+            Shape::Circle { radius } if t.is_translation() => Some(Bounds::new(
+                Point::new(t.e - radius, t.f - radius),
+                Point::new(t.e + radius, t.f + radius),
+            )),
+            _ => self.bounds().map(|bounds| bounds.transform(t)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    pub color: Rgb,
+    pub width: f64,
+}
+
+// 没有填充也没有描边的图形不可见，但仍然参与命中测试
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Style {
+    pub fill: Option<Rgb>,
+    pub stroke: Option<Stroke>,
+}
+
+impl Style {
+    pub fn fill(color: Rgb) -> Style {
+        Style {
+            fill: Some(color),
+            stroke: None,
+        }
+    }
+
+    pub fn stroke(color: Rgb, width: f64) -> Style {
+        Style::default().with_stroke(color, width)
+    }
+
+    pub fn with_stroke(mut self, color: Rgb, width: f64) -> Style {
+        self.stroke = Some(Stroke { color, width });
+        self
+    }
+}
+
+// ===========================================
+// 2. 节点
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Group,
+    Shape { shape: Shape, style: Style },
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    pub kind: NodeKind,
+    // 相对父节点的变换
+    pub transform: Transform,
+    pub z_index: i32,
+    // 隐藏的节点及其子树不绘制，也不参与命中测试
+    pub visible: bool,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    // 加入场景的序号，z_index 相同时决定先后
+    sequence: u64,
+}
+
+impl Node {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    // 按加入的先后排列，不是绘制顺序
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    pub fn is_group(&self) -> bool {
+        matches!(self.kind, NodeKind::Group)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneError {
+    UnknownNode(NodeId),
+    // 只有组可以有子节点
+    NotAGroup(NodeId),
+    // 把节点移动到自己的子树中
+    Cycle { node: NodeId, parent: NodeId },
+    // 根节点不能删除或移动
+    RootNode,
+    // 变换不可逆（缩放为 0），无法在保持世界坐标的前提下换父节点
+    Singular(NodeId),
+    EmptySelection,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::UnknownNode(id) => write!(f, "节点 {} 不存在", id.0),
+            SceneError::NotAGroup(id) => write!(f, "节点 {} 不是组，不能添加子节点", id.0),
+            SceneError::Cycle { node, parent } => {
+                write!(
+                    f,
+                    "节点 {} 是节点 {} 的祖先，不能成为它的子节点",
+                    node.0, parent.0
+                )
+            }
+            SceneError::RootNode => write!(f, "不能删除或移动根节点"),
+            SceneError::Singular(id) => write!(f, "节点 {} 的世界变换不可逆", id.0),
+            SceneError::EmptySelection => write!(f, "没有选中任何节点"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl Diagnostic for SceneError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::INVALID_INPUT
+    }
+}
+
+// ===========================================
+// 3. 场景
+// ===========================================
+
+// 点选时选中什么
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    // 最上层的图形本身
+    Shape,
+    // 图形所在的、根节点下的最外层节点；图形直接挂在根节点下时就是图形本身
+    Group,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    root: NodeId,
+    next_sequence: u64,
+    selection: Vec<NodeId>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene::new()
+    }
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        let mut scene = Scene {
+            nodes: Vec::new(),
+            root: NodeId(0),
+            next_sequence: 0,
+            selection: Vec::new(),
+        };
+        scene.insert(None, "root".to_string(), NodeKind::Group);
+        scene
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0)?.as_ref()
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0)?.as_mut()
+    }
+
+    fn get(&self, id: NodeId) -> Result<&Node, SceneError> {
+        self.node(id).ok_or(SceneError::UnknownNode(id))
+    }
+
+    fn get_mut(&mut self, id: NodeId) -> Result<&mut Node, SceneError> {
+        self.node_mut(id).ok_or(SceneError::UnknownNode(id))
+    }
+
+    // 按名字查找第一个匹配的节点（按 NodeId 顺序）
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.as_ref().is_some_and(|node| node.name == name))
+            .map(NodeId)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.iter().flatten().count()
+    }
+
+    // 只剩根节点
+    pub fn is_empty(&self) -> bool {
+        self.len() == 1
+    }
+
+    fn insert(&mut self, parent: Option<NodeId>, name: String, kind: NodeKind) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Some(Node {
+            name,
+            kind,
+            transform: Transform::IDENTITY,
+            z_index: 0,
+            visible: true,
+            parent,
+            children: Vec::new(),
+            sequence: self.next_sequence,
+        }));
+        self.next_sequence += 1;
+        if let Some(parent) = parent {
+            self.nodes[parent.0].as_mut().unwrap().children.push(id);
+        }
+        id
+    }
+
+    fn check_group(&self, id: NodeId) -> Result<(), SceneError> {
+        if self.get(id)?.is_group() {
+            Ok(())
+        } else {
+            Err(SceneError::NotAGroup(id))
+        }
+    }
+
+    pub fn add_group(
+        &mut self,
+        parent: NodeId,
+        name: impl Into<String>,
+    ) -> Result<NodeId, SceneError> {
+        self.check_group(parent)?;
+        Ok(self.insert(Some(parent), name.into(), NodeKind::Group))
+    }
+
+    pub fn add_shape(
+        &mut self,
+        parent: NodeId,
+        name: impl Into<String>,
+        shape: Shape,
+        style: Style,
+    ) -> Result<NodeId, SceneError> {
+        self.check_group(parent)?;
+        Ok(self.insert(Some(parent), name.into(), NodeKind::Shape { shape, style }))
+    }
+
+    // 删除节点及其整个子树
+    pub fn remove(&mut self, id: NodeId) -> Result<Node, SceneError> {
+        if id == self.root {
+            return Err(SceneError::RootNode);
+        }
+        let parent = self.get(id)?.parent.expect("非根节点都有父节点");
+        self.nodes[parent.0]
+            .as_mut()
+            .unwrap()
+            .children
+            .retain(|&child| child != id);
+        let mut pending = vec![id];
+        let mut removed = None;
+        while let Some(current) = pending.pop() {
+            let node = self.nodes[current.0].take().unwrap();
+            pending.extend(&node.children);
+            removed.get_or_insert(node);
+        }
+        let nodes = &self.nodes;
+        self.selection
+            .retain(|selected| nodes[selected.0].is_some());
+        Ok(removed.unwrap())
+    }
+
+    // 从根到 id 的路径上依次相乘
+    pub fn world_transform(&self, id: NodeId) -> Option<Transform> {
+        let node = self.node(id)?;
+        Some(match node.parent {
+            Some(parent) => self.world_transform(parent)? * node.transform,
+            None => node.transform,
+        })
+    }
+
+    pub fn is_ancestor(&self, ancestor: NodeId, mut id: NodeId) -> bool {
+        while let Some(parent) = self.node(id).and_then(Node::parent) {
+            if parent == ancestor {
+                return true;
+            }
+            id = parent;
+        }
+        false
+    }
+
+    // 移动到新的父节点下，调整局部变换使节点在屏幕上的位置不变
+    pub fn reparent(&mut self, id: NodeId, new_parent: NodeId) -> Result<(), SceneError> {
+        if id == self.root {
+            return Err(SceneError::RootNode);
+        }
+        self.check_group(new_parent)?;
+        if id == new_parent || self.is_ancestor(id, new_parent) {
+            return Err(SceneError::Cycle {
+                node: id,
+                parent: new_parent,
+            });
+        }
+        let world = self
+            .world_transform(id)
+            .ok_or(SceneError::UnknownNode(id))?;
+        let parent_inverse = self
+            .world_transform(new_parent)
+            .and_then(Transform::inverse)
+            .ok_or(SceneError::Singular(new_parent))?;
+
+        let old_parent = self.get(id)?.parent.expect("非根节点都有父节点");
+        self.get_mut(old_parent)?
+            .children
+            .retain(|&child| child != id);
+        self.get_mut(new_parent)?.children.push(id);
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        let node = self.get_mut(id)?;
+        node.parent = Some(new_parent);
+        node.transform = parent_inverse * world;
+        // 移动过来的节点排在同 z_index 的兄弟之后
+        node.sequence = sequence;
+        Ok(())
+    }
+
+    // 子节点的绘制顺序：z_index 小的先画，相同时先加入的先画
+    pub fn sorted_children(&self, id: NodeId) -> Vec<NodeId> {
+        let Some(node) = self.node(id) else {
+            return Vec::new();
+        };
+        let mut children = node.children.clone();
+        children.sort_by_key(|&child| {
+            let child = self.node(child).unwrap();
+            (child.z_index, child.sequence)
+        });
+        children
+    }
+
+    // 所有可见图形按绘制顺序排列，附带世界变换；后面的图形画在上面
+    pub fn paint_order(&self) -> Vec<(NodeId, Transform)> {
+        let mut order = Vec::new();
+        self.collect_visible(self.root, Transform::IDENTITY, &mut order);
+        order
+    }
+
+    fn collect_visible(&self, id: NodeId, parent: Transform, out: &mut Vec<(NodeId, Transform)>) {
+        let node = self.node(id).unwrap();
+        if !node.visible {
+            return;
+        }
+        let world = parent * node.transform;
+        match node.kind {
+            NodeKind::Group => {
+                for child in self.sorted_children(id) {
+                    self.collect_visible(child, world, out);
+                }
+            }
+            NodeKind::Shape { .. } => out.push((id, world)),
+        }
+    }
+
+    // 世界坐标 p 处最上层的图形
+    pub fn hit_test(&self, p: Point) -> Option<NodeId> {
+        self.paint_order()
+            .into_iter()
+            .rev()
+            .find_map(|(id, world)| {
+                let NodeKind::Shape { shape, .. } = &self.node(id)?.kind else {
+                    return None;
+                };
+                // 缩放为 0 的图形看不见，也点不中
+                let local = world.inverse()? * p;
+                shape.contains(local).then_some(id)
+            })
+    }
+
+    // 根节点下、包含 id 的最外层节点
+    pub fn top_level(&self, mut id: NodeId) -> Option<NodeId> {
+        loop {
+            let parent = self.node(id)?.parent?;
+            if parent == self.root {
+                return Some(id);
+            }
+            id = parent;
+        }
+    }
+
+    // 节点及其子树中所有图形的世界包围盒；隐藏的节点和没有图形的组返回 None
+    pub fn world_bounds(&self, id: NodeId) -> Option<Bounds> {
+        let node = self.node(id)?;
+        let parent = match node.parent {
+            Some(parent) => self.world_transform(parent)?,
+            None => Transform::IDENTITY,
+        };
+        let mut shapes = Vec::new();
+        self.collect_visible(id, parent, &mut shapes);
+        shapes
+            .into_iter()
+            .filter_map(|(id, world)| match &self.node(id)?.kind {
+                NodeKind::Shape { shape, .. } => shape.world_bounds(world),
+                NodeKind::Group => None,
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+
+    // ===========================================
+    // 4. 选择
+    // ===========================================
+
+    pub fn selection(&self) -> &[NodeId] {
+        &self.selection
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    pub fn select(&mut self, id: NodeId) -> Result<(), SceneError> {
+        self.get(id)?;
+        self.selection = vec![id];
+        Ok(())
+    }
+
+    // 已经选中时取消选中，对应按住 Shift 点击
+    pub fn toggle_selected(&mut self, id: NodeId) -> Result<(), SceneError> {
+        self.get(id)?;
+        match self.selection.iter().position(|&selected| selected == id) {
+            Some(index) => {
+                self.selection.remove(index);
+            }
+            None => self.selection.push(id),
+        }
+        Ok(())
+    }
+
+    // 点选：命中时替换当前选择，点在空白处时清空选择
+    pub fn select_at(&mut self, p: Point, pick: Pick) -> Option<NodeId> {
+        let hit = self.hit_test(p).and_then(|id| match pick {
+            Pick::Shape => Some(id),
+            Pick::Group => self.top_level(id),
+        });
+        self.selection = hit.into_iter().collect();
+        hit
+    }
+
+    // 框选：选中包围盒完全落在 area 中的最外层节点
+    pub fn select_in(&mut self, area: Bounds) -> &[NodeId] {
+        self.selection = self
+            .sorted_children(self.root)
+            .into_iter()
+            .filter(|&id| {
+                self.world_bounds(id)
+                    .is_some_and(|b| area.contains_bounds(&b))
+            })
+            .collect();
+        &self.selection
+    }
+
+    // 所有选中节点的包围盒
+    pub fn selection_bounds(&self) -> Option<Bounds> {
+        self.selection
+            .iter()
+            .filter_map(|&id| self.world_bounds(id))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    // 在世界坐标系中对选中的节点施加变换 t，如绕选区中心旋转：
+    // 新的局部变换 = 父节点世界变换的逆 · t · 原世界变换
+    // 同时选中了祖先和后代时，后代只随祖先变换一次
+    pub fn transform_selected(&mut self, t: Transform) -> Result<(), SceneError> {
+        let targets: Vec<NodeId> = self
+            .selection
+            .iter()
+            .copied()
+            .filter(|&id| {
+                !self
+                    .selection
+                    .iter()
+                    .any(|&other| self.is_ancestor(other, id))
+            })
+            .collect();
+        // 先算出所有新的局部变换，全部成功后再写回，失败时场景保持原样
+        let updates = targets
+            .into_iter()
+            .map(|id| {
+                let node = self.get(id)?;
+                let parent_world = match node.parent {
+                    Some(parent) => self
+                        .world_transform(parent)
+                        .ok_or(SceneError::UnknownNode(parent))?,
+                    None => Transform::IDENTITY,
+                };
+                let parent_inverse = parent_world
+                    .inverse()
+                    .ok_or(SceneError::Singular(node.parent.unwrap_or(id)))?;
+                Ok((id, parent_inverse * t * parent_world * node.transform))
+            })
+            .collect::<Result<Vec<_>, SceneError>>()?;
+        for (id, transform) in updates {
+            self.get_mut(id)?.transform = transform;
+        }
+        Ok(())
+    }
+
+    // 把选中的节点放进一个新的组，新组挂在根节点下，z_index 取选中节点中最大的；
+    // 各节点在屏幕上的位置不变，之后选择变为新组
+    pub fn group_selected(&mut self, name: impl Into<String>) -> Result<NodeId, SceneError> {
+        if self.selection.is_empty() {
+            return Err(SceneError::EmptySelection);
+        }
+        let members: Vec<NodeId> = self
+            .selection
+            .iter()
+            .copied()
+            .filter(|&id| {
+                !self
+                    .selection
+                    .iter()
+                    .any(|&other| self.is_ancestor(other, id))
+            })
+            .collect();
+        if members.contains(&self.root) {
+            return Err(SceneError::RootNode);
+        }
+        let z_index = members
+            .iter()
+            .map(|&id| self.get(id).map(|node| node.z_index))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .max()
+            .unwrap_or(0);
+        // 新组的世界变换就是根节点的变换；先确认它可逆，
+        // 之后的移动都不会失败，不会留下只装了一部分节点的组
+        if self.get(self.root)?.transform.inverse().is_none() {
+            return Err(SceneError::Singular(self.root));
+        }
+        let group = self.add_group(self.root, name)?;
+        self.get_mut(group)?.z_index = z_index;
+        // 按原来的绘制先后加入，组内的相对顺序不变
+        let mut ordered = members;
+        ordered.sort_by_key(|&id| {
+            let node = self.node(id).unwrap();
+            (node.z_index, node.sequence)
+        });
+        for id in ordered {
+            self.reparent(id, group)
+                .expect("节点都存在，新组也不在它们的子树中");
+            self.get_mut(id)?.z_index = 0;
+        }
+        self.selection = vec![group];
+        Ok(group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_4;
+
+    const RED: Rgb = Rgb([220, 40, 40]);
+    const BLUE: Rgb = Rgb([40, 80, 220]);
+
+    // 边长 100 的正方形，绕中心转 45° 后放在 (200, 200)
+    fn diamond_scene() -> (Scene, NodeId, NodeId) {
+        let mut scene = Scene::new();
+        let layer = scene.add_group(scene.root(), "layer").unwrap();
+        scene.node_mut(layer).unwrap().transform = Transform::translate(200.0, 200.0);
+        let diamond = scene
+            .add_shape(
+                layer,
+                "diamond",
+                Shape::rect(100.0, 100.0),
+                Style::fill(RED),
+            )
+            .unwrap();
+        scene.node_mut(diamond).unwrap().transform =
+            Transform::translate(-50.0, -50.0).then(Transform::rotate(FRAC_PI_4));
+        let dot = scene
+            .add_shape(scene.root(), "dot", Shape::circle(10.0), Style::fill(BLUE))
+            .unwrap();
+        scene.node_mut(dot).unwrap().transform = Transform::translate(200.0, 200.0);
+        (scene, diamond, dot)
+    }
+
+    #[test]
+    fn test_hit_testing_through_rotation_and_z_order() {
+        let (mut scene, diamond, dot) = diamond_scene();
+        // 转 45° 后顶点在 (200, 200 ± 70.7)；(245, 245) 在未旋转的正方形里，但在菱形外
+        assert_eq!(scene.hit_test(Point::new(200.0, 265.0)), Some(diamond));
+        assert_eq!(scene.hit_test(Point::new(245.0, 245.0)), None);
+        // dot 后加入，画在上面
+        assert_eq!(scene.hit_test(Point::new(205.0, 200.0)), Some(dot));
+        scene.node_mut(diamond).unwrap().z_index = 1;
+        let layer = scene.find("layer").unwrap();
+        scene.node_mut(layer).unwrap().z_index = 1;
+        assert_eq!(scene.hit_test(Point::new(205.0, 200.0)), Some(diamond));
+
+        // 隐藏的子树不参与命中测试
+        scene.node_mut(layer).unwrap().visible = false;
+        assert_eq!(scene.hit_test(Point::new(200.0, 265.0)), None);
+
+        scene.node_mut(layer).unwrap().visible = true;
+        let bounds = scene.world_bounds(layer).unwrap();
+        assert!((bounds.width() - 100.0 * 2f64.sqrt()).abs() < 1e-9);
+        assert!(bounds.center().distance(Point::new(200.0, 200.0)) < 1e-9);
+
+        let triangle = Shape::polygon([
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(0.0, 10.0),
+        ]);
+        assert!(
+            triangle.contains(Point::new(2.0, 2.0)) && !triangle.contains(Point::new(6.0, 6.0))
+        );
+    }
+
+    #[test]
+    fn test_group_selection_and_transform() {
+        let (mut scene, diamond, dot) = diamond_scene();
+        let layer = scene.find("layer").unwrap();
+        assert_eq!(
+            scene.select_at(Point::new(200.0, 265.0), Pick::Shape),
+            Some(diamond)
+        );
+        assert_eq!(
+            scene.select_at(Point::new(200.0, 265.0), Pick::Group),
+            Some(layer)
+        );
+        assert_eq!(scene.select_at(Point::new(0.0, 0.0), Pick::Group), None);
+        assert!(scene.selection().is_empty());
+
+        // 在世界坐标系中平移整个组，组内的菱形跟着移动
+        scene.select(layer).unwrap();
+        scene.toggle_selected(diamond).unwrap();
+        scene
+            .transform_selected(Transform::translate(100.0, 0.0))
+            .unwrap();
+        assert_eq!(scene.hit_test(Point::new(300.0, 265.0)), Some(diamond));
+
+        // 框选后合并成组：位置不变，选择变为新组
+        let area = Bounds::new(Point::new(0.0, 0.0), Point::new(400.0, 400.0));
+        assert_eq!(scene.select_in(area), [layer, dot]);
+        let group = scene.group_selected("group").unwrap();
+        assert_eq!(scene.selection(), [group]);
+        assert_eq!(scene.node(group).unwrap().children(), [layer, dot]);
+        assert_eq!(scene.hit_test(Point::new(300.0, 265.0)), Some(diamond));
+        assert_eq!(scene.hit_test(Point::new(200.0, 200.0)), Some(dot));
+
+        // 绕选区中心转半圈后，菱形的下顶点到了上面
+        let center = scene.selection_bounds().unwrap().center();
+        scene
+            .transform_selected(Transform::rotate_about(std::f64::consts::PI, center))
+            .unwrap();
+        let flipped =
+            Transform::rotate_about(std::f64::consts::PI, center) * Point::new(300.0, 265.0);
+        assert_eq!(scene.hit_test(flipped), Some(diamond));
+    }
+
+    #[test]
+    fn test_structure_errors() {
+        let (mut scene, diamond, dot) = diamond_scene();
+        let layer = scene.find("layer").unwrap();
+        assert_eq!(
+            scene.add_group(diamond, "x"),
+            Err(SceneError::NotAGroup(diamond))
+        );
+        assert_eq!(
+            scene.reparent(layer, layer),
+            Err(SceneError::Cycle {
+                node: layer,
+                parent: layer
+            })
+        );
+        assert_eq!(
+            scene.remove(scene.root()).unwrap_err(),
+            SceneError::RootNode
+        );
+        assert_eq!(
+            scene.group_selected("empty"),
+            Err(SceneError::EmptySelection)
+        );
+
+        scene.select(diamond).unwrap();
+        scene.remove(layer).unwrap();
+        assert!(scene.node(diamond).is_none() && scene.selection().is_empty());
+        assert_eq!(scene.select(diamond), Err(SceneError::UnknownNode(diamond)));
+        assert_eq!(scene.len(), 2);
+        assert_eq!(
+            scene.reparent(dot, diamond),
+            Err(SceneError::UnknownNode(diamond))
+        );
+        assert_eq!(SceneError::RootNode.code(), ErrorCode::INVALID_INPUT);
+    }
+
+    #[test]
+    fn test_failed_transform_leaves_scene_unchanged() {
+        let (mut scene, diamond, dot) = diamond_scene();
+        let layer = scene.find("layer").unwrap();
+        // 组缩放为 0，菱形的父变换不可逆；先选中的圆点也不能被移动
+        scene.node_mut(layer).unwrap().transform = Transform::scale(0.0, 0.0);
+        scene.select(dot).unwrap();
+        scene.toggle_selected(diamond).unwrap();
+        let before = (
+            scene.node(dot).unwrap().transform,
+            scene.node(diamond).unwrap().transform,
+        );
+        assert_eq!(
+            scene.transform_selected(Transform::translate(10.0, 0.0)),
+            Err(SceneError::Singular(layer))
+        );
+        let after = (
+            scene.node(dot).unwrap().transform,
+            scene.node(diamond).unwrap().transform,
+        );
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn test_failed_group_leaves_scene_unchanged() {
+        let (mut scene, diamond, dot) = diamond_scene();
+        let root = scene.root();
+        scene.node_mut(root).unwrap().transform = Transform::scale(0.0, 0.0);
+        scene.select(dot).unwrap();
+        scene.toggle_selected(diamond).unwrap();
+        let parents = (
+            scene.node(dot).unwrap().parent(),
+            scene.node(diamond).unwrap().parent(),
+        );
+        let len = scene.len();
+        assert_eq!(
+            scene.group_selected("group"),
+            Err(SceneError::Singular(root))
+        );
+        assert_eq!(scene.len(), len);
+        assert_eq!(
+            (
+                scene.node(dot).unwrap().parent(),
+                scene.node(diamond).unwrap().parent()
+            ),
+            parents
+        );
+        assert_eq!(scene.selection(), [dot, diamond]);
+    }
+}
@@ -0,0 +1,156 @@
+// 把场景输出成 SVG 文本
+// 组对应 <g>，图形对应 <rect> / <circle> / <polygon>，节点的局部变换写在 transform 属性上，
+// 与场景图的层次一一对应。数值最多保留 3 位小数并去掉多余的 0，同一个场景总是得到同样的文本
+
+use super::{NodeId, NodeKind, Scene, Shape, Style, Transform};
+use crate::image::Rgb;
+use std::fmt::Write;
+
+pub fn render(scene: &Scene, width: u32, height: u32) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+    let root = scene.node(scene.root()).expect("根节点");
+    // 根节点本身不输出 <g>，除非它有变换
+    if root.transform.is_identity() {
+        for child in scene.sorted_children(scene.root()) {
+            render_node(scene, child, 1, &mut out);
+        }
+    } else {
+        render_node(scene, scene.root(), 1, &mut out);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn render_node(scene: &Scene, id: NodeId, depth: usize, out: &mut String) {
+    let node = scene.node(id).unwrap();
+    if !node.visible {
+        return;
+    }
+    let indent = "  ".repeat(depth);
+    let mut attributes = format!(r#" id="{}""#, escape(&node.name));
+    if !node.transform.is_identity() {
+        write!(attributes, r#" transform="{}""#, transform(node.transform)).unwrap();
+    }
+    match &node.kind {
+        NodeKind::Group => {
+            let children = scene.sorted_children(id);
+            if children.is_empty() {
+                writeln!(out, "{indent}<g{attributes}/>").unwrap();
+                return;
+            }
+            writeln!(out, "{indent}<g{attributes}>").unwrap();
+            for child in children {
+                render_node(scene, child, depth + 1, out);
+            }
+            writeln!(out, "{indent}</g>").unwrap();
+        }
+        NodeKind::Shape { shape, style } => {
+            let (tag, geometry) = match shape {
+                Shape::Rect { width, height } => (
+                    "rect",
+                    format!(
+                        r#" width="{}" height="{}""#,
+                        number(*width),
+                        number(*height)
+                    ),
+                ),
+                Shape::Circle { radius } => ("circle", format!(r#" r="{}""#, number(*radius))),
+                Shape::Polygon(points) => {
+                    let points: Vec<String> = points
+                        .iter()
+                        .map(|p| format!("{},{}", number(p.x), number(p.y)))
+                        .collect();
+                    ("polygon", format!(r#" points="{}""#, points.join(" ")))
+                }
+            };
+            writeln!(
+                out,
+                "{indent}<{tag}{attributes}{geometry}{}/>",
+                paint(style)
+            )
+            .unwrap();
+        }
+    }
+}
+
+// SVG 图形默认用黑色填充，没有填充时要显式写 fill="none"
+fn paint(style: &Style) -> String {
+    let mut attributes = match style.fill {
+        Some(color) => format!(r#" fill="{}""#, hex(color)),
+        None => r#" fill="none""#.to_string(),
+    };
+    if let Some(stroke) = style.stroke {
+        write!(
+            attributes,
+            r#" stroke="{}" stroke-width="{}""#,
+            hex(stroke.color),
+            number(stroke.width)
+        )
+        .unwrap();
+    }
+    attributes
+}
+
+fn transform(t: Transform) -> String {
+    if t.is_translation() {
+        format!("translate({} {})", number(t.e), number(t.f))
+    } else {
+        let values = [t.a, t.b, t.c, t.d, t.e, t.f].map(number);
+        format!("matrix({})", values.join(" "))
+    }
+}
+
+fn hex(Rgb([r, g, b]): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// 1.500 → 1.5，2.000 → 2，-0.0001 → 0
+fn number(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_formatting_and_escaping() {
+        assert_eq!(number(1.5), "1.5");
+        assert_eq!(number(2.0), "2");
+        assert_eq!(number(std::f64::consts::FRAC_1_SQRT_2), "0.707");
+        assert_eq!(number(-1e-9), "0");
+        assert_eq!(number(-12.25), "-12.25");
+        assert_eq!(number(100.0), "100");
+        assert_eq!(escape(r#"<a & "b">"#), "&lt;a &amp; &quot;b&quot;&gt;");
+        assert_eq!(
+            transform(Transform::translate(3.0, -4.5)),
+            "translate(3 -4.5)"
+        );
+        assert_eq!(transform(Transform::scale(2.0, 1.0)), "matrix(2 0 0 1 0 0)");
+    }
+}
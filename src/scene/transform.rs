@@ -0,0 +1,266 @@
+// 二维仿射变换与轴对齐包围盒
+// 坐标系与 SVG 相同：x 向右，y 向下，所以正的旋转角在屏幕上是顺时针
+
+use crate::num::Vec2;
+use std::ops::Mul;
+
+pub type Point = Vec2<f64>;
+
+// | a c e |
+// | b d f |
+// | 0 0 1 |
+// 字段顺序与 SVG 的 matrix(a b c d e f) 一致
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Transform { a, b, c, d, e, f }
+    }
+
+    pub const fn translate(dx: f64, dy: f64) -> Self {
+        Transform::new(1.0, 0.0, 0.0, 1.0, dx, dy)
+    }
+
+    pub const fn scale(sx: f64, sy: f64) -> Self {
+        Transform::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    pub fn rotate(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    pub fn rotate_degrees(degrees: f64) -> Self {
+        Transform::rotate(degrees.to_radians())
+    }
+
+    // 绕 center 旋转
+    pub fn rotate_about(radians: f64, center: Point) -> Self {
+        Transform::translate(-center.x, -center.y)
+            .then(Transform::rotate(radians))
+            .then(Transform::translate(center.x, center.y))
+    }
+
+    // 先做 self 再做 next，等价于矩阵乘法 next * self
+    pub fn then(self, next: Transform) -> Transform {
+        next * self
+    }
+
+    pub fn apply(self, p: Point) -> Point {
+        Point::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+
+    // 只做线性部分，不平移；用于方向和位移
+    pub fn apply_vector(self, v: Point) -> Point {
+        Point::new(self.a * v.x + self.c * v.y, self.b * v.x + self.d * v.y)
+    }
+
+    pub fn determinant(self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    // 缩放为 0 的变换不可逆，返回 None
+    pub fn inverse(self) -> Option<Transform> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Transform::new(
+            a,
+            b,
+            c,
+            d,
+            -(a * self.e + c * self.f),
+            -(b * self.e + d * self.f),
+        ))
+    }
+
+    pub fn is_identity(self) -> bool {
+        self == Transform::IDENTITY
+    }
+
+    // 只有平移分量
+    pub fn is_translation(self) -> bool {
+        (self.a, self.b, self.c, self.d) == (1.0, 0.0, 0.0, 1.0)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+// 矩阵乘法：self * other 先做 other 再做 self
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, other: Transform) -> Transform {
+        Transform::new(
+            self.a * other.a + self.c * other.b,
+            self.b * other.a + self.d * other.b,
+            self.a * other.c + self.c * other.d,
+            self.b * other.c + self.d * other.d,
+            self.a * other.e + self.c * other.f + self.e,
+            self.b * other.e + self.d * other.f + self.f,
+        )
+    }
+}
+
+impl Mul<Point> for Transform {
+    type Output = Point;
+
+    fn mul(self, p: Point) -> Point {
+        self.apply(p)
+    }
+}
+
+// 轴对齐包围盒
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Self {
+        Bounds { min, max }
+    }
+
+    // 包含所有点的最小包围盒；没有点时返回 None
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Option<Bounds> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Bounds::new(first, first), |bounds, p| {
+            Bounds::new(
+                Point::new(bounds.min.x.min(p.x), bounds.min.y.min(p.y)),
+                Point::new(bounds.max.x.max(p.x), bounds.max.y.max(p.y)),
+            )
+        }))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Point {
+        self.min.lerp(self.max, 0.5)
+    }
+
+    pub fn corners(&self) -> [Point; 4] {
+        [
+            self.min,
+            Point::new(self.max.x, self.min.y),
+            self.max,
+            Point::new(self.min.x, self.max.y),
+        ]
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn contains_bounds(&self, other: &Bounds) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds::new(
+            Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        )
+    }
+
+    // 变换后四个角的包围盒；旋转后会比原来的形状大
+    pub fn transform(&self, t: Transform) -> Bounds {
+        Bounds::from_points(self.corners().map(|p| t.apply(p))).expect("四个角")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_close(actual: Point, expected: Point) {
+        assert!(
+            actual.distance(expected) < 1e-12,
+            "{} 与 {} 相差过大",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_compose_and_invert() {
+        let p = Point::new(2.0, 1.0);
+        // y 轴向下，转 90° 后 x 轴正方向指向 y 轴正方向
+        assert_close(
+            Transform::rotate(FRAC_PI_2).apply(Point::new(1.0, 0.0)),
+            Point::new(0.0, 1.0),
+        );
+
+        let t = Transform::scale(2.0, 3.0).then(Transform::translate(10.0, 0.0));
+        assert_close(t * p, Point::new(14.0, 3.0));
+        let t = Transform::translate(10.0, 0.0).then(Transform::scale(2.0, 3.0));
+        assert_close(t * p, Point::new(24.0, 3.0));
+
+        let center = Point::new(5.0, 5.0);
+        let spin = Transform::rotate_about(FRAC_PI_2, center);
+        assert_close(spin * center, center);
+        assert_close(spin * Point::new(6.0, 5.0), Point::new(5.0, 6.0));
+
+        let complex = spin
+            .then(Transform::scale(2.0, 0.5))
+            .then(Transform::translate(-3.0, 7.0));
+        let inverse = complex.inverse().unwrap();
+        assert_close(inverse * (complex * p), p);
+        assert_close((complex * inverse) * p, p);
+        assert_eq!(Transform::scale(0.0, 1.0).inverse(), None);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let square = Bounds::new(Point::new(0.0, 0.0), Point::new(2.0, 2.0));
+        let rotated = square.transform(Transform::rotate_about(
+            std::f64::consts::FRAC_PI_4,
+            square.center(),
+        ));
+        let half_diagonal = 2f64.sqrt();
+        assert!((rotated.width() - 2.0 * half_diagonal).abs() < 1e-12);
+        assert_close(rotated.center(), Point::new(1.0, 1.0));
+
+        let other = Bounds::from_points([Point::new(1.0, 1.0), Point::new(3.0, -1.0)]).unwrap();
+        assert!(square.intersects(&other) && !square.contains_bounds(&other));
+        assert_eq!(
+            square.union(&other),
+            Bounds::new(Point::new(0.0, -1.0), Point::new(3.0, 2.0))
+        );
+        assert!(Bounds::from_points([]).is_none());
+    }
+}
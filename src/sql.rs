@@ -0,0 +1,1003 @@
+// 参数化 SQL 查询构建器
+// 取值永远不会拼接进 SQL 文本：构建结果是带 ? 占位符的 SQL 和单独的参数列表，从根本上避免 SQL 注入
+//
+// let query = Query::select(["id", "name"])
+//     .from("users")
+//     .where_(col("age").gt(18))
+//     .order_by("name", Order::Asc)
+//     .limit(10)
+//     .build()?;
+// // query.sql    == "SELECT id, name FROM users WHERE age > ? ORDER BY name ASC LIMIT 10"
+// // query.params == [Value::Integer(18)]
+//
+// 标识符（表名、列名）不是合法的裸标识符或者与关键字冲突时，自动加双引号转义
+// query! 宏提供接近 SQL 的写法，展开后调用同一套构建器
+
+use crate::errors::{Diagnostic, ErrorCode};
+use std::fmt;
+
+// ===========================================
+// 1. 取值
+// ===========================================
+
+// SQL 取值，对应 SQLite 的存储类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl Value {
+    // 转成 SQL 字面量：文本中的单引号写成两个，实数总是带小数点或指数
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            Value::Null => String::from("NULL"),
+            Value::Integer(n) => n.to_string(),
+            Value::Real(n) => {
+                let text = format!("{:?}", n);
+                if text.contains(['.', 'e', 'E', 'N', 'i']) {
+                    text
+                } else {
+                    format!("{}.0", text)
+                }
+            }
+            Value::Text(s) => format!("'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_sql_literal())
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(n: $t) -> Self {
+                    Value::Integer(n as i64)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i8, i16, i32, i64, u8, u16, u32);
+
+// SQLite 没有布尔类型，按惯例存为 0 / 1
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Integer(b as i64)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(n: f32) -> Self {
+        Value::Real(n as f64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Real(n)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl From<&String> for Value {
+    fn from(s: &String) -> Self {
+        Value::Text(s.clone())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+// ===========================================
+// 2. 条件表达式
+// ===========================================
+
+// WHERE 子句中的条件，由 col(...) 上的比较方法创建，再用 and / or / ! 组合
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr(ExprKind);
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExprKind {
+    Compare(String, &'static str, Value),
+    IsNull(String, bool),
+    In(String, Vec<Value>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+// 列引用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column(String);
+
+pub fn col(name: &str) -> Column {
+    Column(name.to_string())
+}
+
+impl Column {
+    fn compare(self, op: &'static str, value: impl Into<Value>) -> Expr {
+        Expr(ExprKind::Compare(self.0, op, value.into()))
+    }
+
+    pub fn eq(self, value: impl Into<Value>) -> Expr {
+        self.compare("=", value)
+    }
+
+    pub fn ne(self, value: impl Into<Value>) -> Expr {
+        self.compare("<>", value)
+    }
+
+    pub fn lt(self, value: impl Into<Value>) -> Expr {
+        self.compare("<", value)
+    }
+
+    pub fn le(self, value: impl Into<Value>) -> Expr {
+        self.compare("<=", value)
+    }
+
+    pub fn gt(self, value: impl Into<Value>) -> Expr {
+        self.compare(">", value)
+    }
+
+    pub fn ge(self, value: impl Into<Value>) -> Expr {
+        self.compare(">=", value)
+    }
+
+    pub fn like(self, pattern: impl Into<Value>) -> Expr {
+        self.compare("LIKE", pattern)
+    }
+
+    pub fn is_null(self) -> Expr {
+        Expr(ExprKind::IsNull(self.0, true))
+    }
+
+    pub fn is_not_null(self) -> Expr {
+        Expr(ExprKind::IsNull(self.0, false))
+    }
+
+    pub fn in_list<I, V>(self, values: I) -> Expr
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        Expr(ExprKind::In(
+            self.0,
+            values.into_iter().map(Into::into).collect(),
+        ))
+    }
+}
+
+impl Expr {
+    pub fn and(self, other: Expr) -> Expr {
+        Expr(ExprKind::And(Box::new(self), Box::new(other)))
+    }
+
+    pub fn or(self, other: Expr) -> Expr {
+        Expr(ExprKind::Or(Box::new(self), Box::new(other)))
+    }
+}
+
+// !expr 生成 NOT (expr)
+impl std::ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr(ExprKind::Not(Box::new(self)))
+    }
+}
+
+// ===========================================
+// 3. 构建结果与错误
+// ===========================================
+
+// 构建好的查询：SQL 文本中的 ? 按顺序对应 params 中的取值
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlQuery {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+impl SqlQuery {
+    // 把参数代入占位符，得到可以直接执行的 SQL
+    // 只用于日志和不支持参数绑定的接口（例如 sqlite3_exec），取值已按字面量规则转义
+    pub fn to_inline_sql(&self) -> String {
+        let mut out = String::with_capacity(self.sql.len());
+        let mut params = self.params.iter();
+        let mut in_identifier = false;
+        for c in self.sql.chars() {
+            match c {
+                '"' => {
+                    in_identifier = !in_identifier;
+                    out.push(c);
+                }
+                '?' if !in_identifier => match params.next() {
+                    Some(value) => out.push_str(&value.to_sql_literal()),
+                    None => out.push(c),
+                },
+                c => out.push(c),
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for SqlQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.sql)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlError {
+    MissingTable,
+    EmptyIdentifier,
+    NoColumns,
+    NoRows,
+    RowLength {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    NoAssignments,
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlError::MissingTable => write!(f, "SELECT 查询缺少 FROM 子句"),
+            SqlError::EmptyIdentifier => write!(f, "表名或列名不能为空"),
+            SqlError::NoColumns => write!(f, "INSERT 语句没有指定列"),
+            SqlError::NoRows => write!(f, "INSERT 语句没有任何 VALUES"),
+            SqlError::RowLength {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "第 {} 行有 {} 个值，但指定了 {} 列",
+                row + 1,
+                found,
+                expected
+            ),
+            SqlError::NoAssignments => write!(f, "UPDATE 语句没有 SET 子句"),
+        }
+    }
+}
+
+impl std::error::Error for SqlError {}
+
+impl Diagnostic for SqlError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::QUERY_BUILD
+    }
+}
+
+// ===========================================
+// 4. 渲染
+// ===========================================
+
+// 与 SQL 关键字冲突的标识符需要加引号
+const RESERVED: &[&str] = &[
+    "and", "as", "asc", "by", "create", "delete", "desc", "drop", "from", "group", "in", "insert",
+    "into", "is", "like", "limit", "not", "null", "offset", "or", "order", "select", "set",
+    "table", "update", "values", "where",
+];
+
+struct Renderer {
+    sql: String,
+    params: Vec<Value>,
+}
+
+impl Renderer {
+    fn new() -> Self {
+        Renderer {
+            sql: String::new(),
+            params: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.sql.push_str(text);
+    }
+
+    fn param(&mut self, value: &Value) {
+        self.sql.push('?');
+        self.params.push(value.clone());
+    }
+
+    // 裸标识符原样输出，其余加双引号，内部的双引号写成两个
+    fn ident(&mut self, name: &str) -> Result<(), SqlError> {
+        if name.is_empty() {
+            return Err(SqlError::EmptyIdentifier);
+        }
+        let bare = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !RESERVED.contains(&name.to_ascii_lowercase().as_str());
+        if bare {
+            self.push(name);
+        } else {
+            self.sql.push('"');
+            self.push(&name.replace('"', "\"\""));
+            self.sql.push('"');
+        }
+        Ok(())
+    }
+
+    fn ident_list(&mut self, names: &[String]) -> Result<(), SqlError> {
+        for (i, name) in names.iter().enumerate() {
+            if i > 0 {
+                self.push(", ");
+            }
+            self.ident(name)?;
+        }
+        Ok(())
+    }
+
+    fn filter(&mut self, filter: &Option<Expr>) -> Result<(), SqlError> {
+        if let Some(expr) = filter {
+            self.push(" WHERE ");
+            self.expr(expr)?;
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<(), SqlError> {
+        match &expr.0 {
+            // 与 NULL 比较的结果永远是 NULL，= / <> NULL 改写成 IS NULL / IS NOT NULL
+            ExprKind::Compare(column, op @ ("=" | "<>"), Value::Null) => {
+                self.ident(column)?;
+                self.push(if *op == "=" {
+                    " IS NULL"
+                } else {
+                    " IS NOT NULL"
+                });
+            }
+            ExprKind::Compare(column, op, value) => {
+                self.ident(column)?;
+                self.push(&format!(" {} ", op));
+                self.param(value);
+            }
+            ExprKind::IsNull(column, is_null) => {
+                self.ident(column)?;
+                self.push(if *is_null { " IS NULL" } else { " IS NOT NULL" });
+            }
+            // 空列表的 IN () 不是合法 SQL，改写成恒假条件
+            ExprKind::In(_, values) if values.is_empty() => self.push("1 = 0"),
+            ExprKind::In(column, values) => {
+                self.ident(column)?;
+                self.push(" IN (");
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        self.push(", ");
+                    }
+                    self.param(value);
+                }
+                self.push(")");
+            }
+            ExprKind::And(left, right) => {
+                self.operand(left, false)?;
+                self.push(" AND ");
+                self.operand(right, false)?;
+            }
+            ExprKind::Or(left, right) => {
+                self.operand(left, true)?;
+                self.push(" OR ");
+                self.operand(right, true)?;
+            }
+            ExprKind::Not(inner) => {
+                self.push("NOT (");
+                self.expr(inner)?;
+                self.push(")");
+            }
+        }
+        Ok(())
+    }
+
+    // AND 的优先级高于 OR：AND 中的 OR 子表达式需要括号，OR 中的 AND 不需要
+    fn operand(&mut self, expr: &Expr, in_or: bool) -> Result<(), SqlError> {
+        let needs_parens = matches!(expr.0, ExprKind::Or(..)) && !in_or;
+        if needs_parens {
+            self.push("(");
+        }
+        self.expr(expr)?;
+        if needs_parens {
+            self.push(")");
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> SqlQuery {
+        SqlQuery {
+            sql: self.sql,
+            params: self.params,
+        }
+    }
+}
+
+// 多次调用 where_ 时用 AND 连接
+fn and_filter(filter: Option<Expr>, condition: Expr) -> Option<Expr> {
+    Some(match filter {
+        Some(existing) => existing.and(condition),
+        None => condition,
+    })
+}
+
+// ===========================================
+// 5. 查询构建器
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+// 各类查询的入口
+pub struct Query;
+
+impl Query {
+    // 列为空或只有 "*" 时选择所有列
+    pub fn select<I, S>(columns: I) -> Select
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Select {
+            columns: columns.into_iter().map(Into::into).collect(),
+            table: None,
+            filter: None,
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    pub fn insert_into(table: &str) -> Insert {
+        Insert {
+            table: table.to_string(),
+            columns: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn update(table: &str) -> Update {
+        Update {
+            table: table.to_string(),
+            assignments: Vec::new(),
+            filter: None,
+        }
+    }
+
+    pub fn delete_from(table: &str) -> Delete {
+        Delete {
+            table: table.to_string(),
+            filter: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Select {
+    columns: Vec<String>,
+    table: Option<String>,
+    filter: Option<Expr>,
+    order_by: Vec<(String, Order)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl Select {
+    pub fn from(mut self, table: &str) -> Self {
+        self.table = Some(table.to_string());
+        self
+    }
+
+    pub fn where_(mut self, condition: Expr) -> Self {
+        self.filter = and_filter(self.filter, condition);
+        self
+    }
+
+    pub fn order_by(mut self, column: &str, order: Order) -> Self {
+        self.order_by.push((column.to_string(), order));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn build(&self) -> Result<SqlQuery, SqlError> {
+        let table = self.table.as_deref().ok_or(SqlError::MissingTable)?;
+        let mut r = Renderer::new();
+        r.push("SELECT ");
+        if self.columns.is_empty() || self.columns == ["*"] {
+            r.push("*");
+        } else {
+            r.ident_list(&self.columns)?;
+        }
+        r.push(" FROM ");
+        r.ident(table)?;
+        r.filter(&self.filter)?;
+        for (i, (column, order)) in self.order_by.iter().enumerate() {
+            r.push(if i == 0 { " ORDER BY " } else { ", " });
+            r.ident(column)?;
+            r.push(match order {
+                Order::Asc => " ASC",
+                Order::Desc => " DESC",
+            });
+        }
+        // LIMIT / OFFSET 是整数类型，直接写入文本是安全的
+        // SQLite 的 OFFSET 必须跟在 LIMIT 后面，没有 LIMIT 时用 -1 表示不限行数
+        match (self.limit, self.offset) {
+            (Some(limit), _) => r.push(&format!(" LIMIT {}", limit)),
+            (None, Some(_)) => r.push(" LIMIT -1"),
+            (None, None) => {}
+        }
+        if let Some(offset) = self.offset {
+            r.push(&format!(" OFFSET {}", offset));
+        }
+        Ok(r.finish())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insert {
+    table: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Insert {
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    // 追加一行；多次调用生成多行 VALUES
+    pub fn values<I>(mut self, row: I) -> Self
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        self.rows.push(row.into_iter().collect());
+        self
+    }
+
+    pub fn build(&self) -> Result<SqlQuery, SqlError> {
+        if self.columns.is_empty() {
+            return Err(SqlError::NoColumns);
+        }
+        if self.rows.is_empty() {
+            return Err(SqlError::NoRows);
+        }
+        let mut r = Renderer::new();
+        r.push("INSERT INTO ");
+        r.ident(&self.table)?;
+        r.push(" (");
+        r.ident_list(&self.columns)?;
+        r.push(") VALUES ");
+        for (i, row) in self.rows.iter().enumerate() {
+            if row.len() != self.columns.len() {
+                return Err(SqlError::RowLength {
+                    row: i,
+                    expected: self.columns.len(),
+                    found: row.len(),
+                });
+            }
+            r.push(if i == 0 { "(" } else { ", (" });
+            for (j, value) in row.iter().enumerate() {
+                if j > 0 {
+                    r.push(", ");
+                }
+                r.param(value);
+            }
+            r.push(")");
+        }
+        Ok(r.finish())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Update {
+    table: String,
+    assignments: Vec<(String, Value)>,
+    filter: Option<Expr>,
+}
+
+impl Update {
+    pub fn set(mut self, column: &str, value: impl Into<Value>) -> Self {
+        self.assignments.push((column.to_string(), value.into()));
+        self
+    }
+
+    pub fn where_(mut self, condition: Expr) -> Self {
+        self.filter = and_filter(self.filter, condition);
+        self
+    }
+
+    pub fn build(&self) -> Result<SqlQuery, SqlError> {
+        if self.assignments.is_empty() {
+            return Err(SqlError::NoAssignments);
+        }
+        let mut r = Renderer::new();
+        r.push("UPDATE ");
+        r.ident(&self.table)?;
+        for (i, (column, value)) in self.assignments.iter().enumerate() {
+            r.push(if i == 0 { " SET " } else { ", " });
+            r.ident(column)?;
+            r.push(" = ");
+            r.param(value);
+        }
+        r.filter(&self.filter)?;
+        Ok(r.finish())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delete {
+    table: String,
+    filter: Option<Expr>,
+}
+
+impl Delete {
+    pub fn where_(mut self, condition: Expr) -> Self {
+        self.filter = and_filter(self.filter, condition);
+        self
+    }
+
+    pub fn build(&self) -> Result<SqlQuery, SqlError> {
+        let mut r = Renderer::new();
+        r.push("DELETE FROM ");
+        r.ident(&self.table)?;
+        r.filter(&self.filter)?;
+        Ok(r.finish())
+    }
+}
+
+// ===========================================
+// 6. query! 宏
+// ===========================================
+
+// 接近 SQL 的写法，展开为构建器调用，结果是对应的构建器（Select / Insert / Update / Delete）：
+//
+// query!(SELECT id, name FROM users WHERE age >= min_age AND name LIKE "A%" ORDER BY name DESC LIMIT 10)
+// query!(INSERT INTO users (name, age) VALUES ("Bob", 20), ("Eve", (base + 1)))
+// query!(UPDATE users SET age = 21 WHERE id = 1)
+// query!(DELETE FROM users WHERE deleted_at IS NOT NULL)
+//
+// 取值必须是单个 token：字面量、变量名，或者用括号包起来的表达式；它们都会成为参数
+// WHERE 中的条件只能用 AND 连接，需要 OR 时直接使用构建器
+#[macro_export]
+macro_rules! query {
+    (SELECT * FROM $table:ident $($rest:tt)*) => {
+        $crate::query!(@clauses
+            $crate::sql::Query::select(["*"]).from(stringify!($table)); $($rest)*)
+    };
+    (SELECT $($column:ident),+ FROM $table:ident $($rest:tt)*) => {
+        $crate::query!(@clauses
+            $crate::sql::Query::select([$(stringify!($column)),+]).from(stringify!($table));
+            $($rest)*)
+    };
+    (INSERT INTO $table:ident ($($column:ident),+) VALUES $(($($value:tt),+)),+) => {
+        $crate::sql::Query::insert_into(stringify!($table))
+            .columns([$(stringify!($column)),+])
+            $(.values([$($crate::query!(@value $value)),+]))+
+    };
+    (UPDATE $table:ident SET $($column:ident = $value:tt),+ $(WHERE $($rest:tt)*)?) => {
+        $crate::query!(@clauses
+            $crate::sql::Query::update(stringify!($table))
+                $(.set(stringify!($column), $crate::query!(@value $value)))+;
+            $(WHERE $($rest)*)?)
+    };
+    (DELETE FROM $table:ident $($rest:tt)*) => {
+        $crate::query!(@clauses $crate::sql::Query::delete_from(stringify!($table)); $($rest)*)
+    };
+
+    // 逐个处理 WHERE / ORDER BY / LIMIT / OFFSET 子句
+    (@clauses $query:expr;) => {
+        $query
+    };
+    (@clauses $query:expr; WHERE $($rest:tt)*) => {
+        $crate::query!(@where $query; $($rest)*)
+    };
+    (@clauses $query:expr; ORDER BY $column:ident DESC $($rest:tt)*) => {
+        $crate::query!(@clauses
+            $query.order_by(stringify!($column), $crate::sql::Order::Desc); $($rest)*)
+    };
+    (@clauses $query:expr; ORDER BY $column:ident ASC $($rest:tt)*) => {
+        $crate::query!(@clauses
+            $query.order_by(stringify!($column), $crate::sql::Order::Asc); $($rest)*)
+    };
+    (@clauses $query:expr; ORDER BY $column:ident $($rest:tt)*) => {
+        $crate::query!(@clauses
+            $query.order_by(stringify!($column), $crate::sql::Order::Asc); $($rest)*)
+    };
+    (@clauses $query:expr; LIMIT $limit:tt $($rest:tt)*) => {
+        $crate::query!(@clauses $query.limit($limit); $($rest)*)
+    };
+    (@clauses $query:expr; OFFSET $offset:tt $($rest:tt)*) => {
+        $crate::query!(@clauses $query.offset($offset); $($rest)*)
+    };
+
+    // WHERE 条件：每次吃掉一个条件，遇到 AND 继续，否则回到子句处理
+    (@where $query:expr; $column:ident IS NULL $($rest:tt)*) => {
+        $crate::query!(@and
+            $query.where_($crate::sql::col(stringify!($column)).is_null()); $($rest)*)
+    };
+    (@where $query:expr; $column:ident IS NOT NULL $($rest:tt)*) => {
+        $crate::query!(@and
+            $query.where_($crate::sql::col(stringify!($column)).is_not_null()); $($rest)*)
+    };
+    (@where $query:expr; $column:ident $op:tt $value:tt $($rest:tt)*) => {
+        $crate::query!(@and
+            $query.where_($crate::query!(@compare $column $op $value)); $($rest)*)
+    };
+    (@and $query:expr; AND $($rest:tt)*) => {
+        $crate::query!(@where $query; $($rest)*)
+    };
+    (@and $query:expr; $($rest:tt)*) => {
+        $crate::query!(@clauses $query; $($rest)*)
+    };
+
+    (@compare $column:ident = $value:tt) => {
+        $crate::sql::col(stringify!($column)).eq($crate::query!(@value $value))
+    };
+    (@compare $column:ident != $value:tt) => {
+        $crate::sql::col(stringify!($column)).ne($crate::query!(@value $value))
+    };
+    (@compare $column:ident < $value:tt) => {
+        $crate::sql::col(stringify!($column)).lt($crate::query!(@value $value))
+    };
+    (@compare $column:ident <= $value:tt) => {
+        $crate::sql::col(stringify!($column)).le($crate::query!(@value $value))
+    };
+    (@compare $column:ident > $value:tt) => {
+        $crate::sql::col(stringify!($column)).gt($crate::query!(@value $value))
+    };
+    (@compare $column:ident >= $value:tt) => {
+        $crate::sql::col(stringify!($column)).ge($crate::query!(@value $value))
+    };
+    (@compare $column:ident LIKE $value:tt) => {
+        $crate::sql::col(stringify!($column)).like($crate::query!(@value $value))
+    };
+
+    (@value NULL) => {
+        $crate::sql::Value::Null
+    };
+    (@value $value:tt) => {
+        $crate::sql::Value::from($value)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_builder() {
+        let query = Query::select(["id", "name"])
+            .from("users")
+            .where_(col("age").ge(18))
+            .where_(col("name").like("A%").or(col("vip").eq(true)))
+            .order_by("name", Order::Asc)
+            .order_by("id", Order::Desc)
+            .limit(10)
+            .offset(20)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.sql,
+            "SELECT id, name FROM users WHERE age >= ? AND (name LIKE ? OR vip = ?) \
+             ORDER BY name ASC, id DESC LIMIT 10 OFFSET 20"
+        );
+        assert_eq!(
+            query.params,
+            vec![
+                Value::Integer(18),
+                Value::Text("A%".into()),
+                Value::Integer(1)
+            ]
+        );
+
+        assert_eq!(
+            Query::select(Vec::<String>::new())
+                .from("t")
+                .build()
+                .unwrap()
+                .sql,
+            "SELECT * FROM t"
+        );
+        assert_eq!(Query::select(["id"]).build(), Err(SqlError::MissingTable));
+    }
+
+    #[test]
+    fn test_identifiers_are_quoted_when_needed() {
+        let query = Query::select(["order", "first name", "a\"b", "_ok1"])
+            .from("select")
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.sql,
+            r#"SELECT "order", "first name", "a""b", _ok1 FROM "select""#
+        );
+        assert_eq!(
+            Query::select(["id"]).from("").build(),
+            Err(SqlError::EmptyIdentifier)
+        );
+    }
+
+    #[test]
+    fn test_values_never_enter_sql_text() {
+        let evil = "x'; DROP TABLE users; --";
+        let query = Query::select(["id"])
+            .from("users")
+            .where_(col("name").eq(evil))
+            .build()
+            .unwrap();
+        assert_eq!(query.sql, "SELECT id FROM users WHERE name = ?");
+        assert_eq!(query.params, vec![Value::Text(evil.into())]);
+        assert_eq!(
+            query.to_inline_sql(),
+            "SELECT id FROM users WHERE name = 'x''; DROP TABLE users; --'"
+        );
+    }
+
+    #[test]
+    fn test_insert_update_delete() {
+        let insert = Query::insert_into("users")
+            .columns(["name", "age"])
+            .values([Value::from("Bob"), Value::from(20)])
+            .values([Value::from("Eve"), Value::Null]);
+        assert_eq!(
+            insert.build().unwrap().sql,
+            "INSERT INTO users (name, age) VALUES (?, ?), (?, ?)"
+        );
+        assert_eq!(insert.build().unwrap().params.len(), 4);
+
+        let bad = Query::insert_into("users")
+            .columns(["name", "age"])
+            .values([Value::from("Bob")]);
+        assert_eq!(
+            bad.build(),
+            Err(SqlError::RowLength {
+                row: 0,
+                expected: 2,
+                found: 1
+            })
+        );
+
+        // 与 NULL 的比较改写成 IS NULL
+        assert_eq!(
+            Query::delete_from("t")
+                .where_(col("a").eq(None::<i32>))
+                .build()
+                .unwrap()
+                .sql,
+            "DELETE FROM t WHERE a IS NULL"
+        );
+
+        let update = Query::update("users")
+            .set("age", 21)
+            .set("name", "Bobby")
+            .where_(col("id").eq(1).and(col("deleted").is_null()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            update.sql,
+            "UPDATE users SET age = ?, name = ? WHERE id = ? AND deleted IS NULL"
+        );
+        assert_eq!(Query::update("users").build(), Err(SqlError::NoAssignments));
+
+        let delete = Query::delete_from("users")
+            .where_(!col("id").in_list([1, 2, 3]))
+            .build()
+            .unwrap();
+        assert_eq!(delete.sql, "DELETE FROM users WHERE NOT (id IN (?, ?, ?))");
+        assert_eq!(
+            Query::delete_from("users")
+                .where_(col("id").in_list(Vec::<i64>::new()))
+                .build()
+                .unwrap()
+                .sql,
+            "DELETE FROM users WHERE 1 = 0"
+        );
+    }
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(Value::from(1.0).to_sql_literal(), "1.0");
+        assert_eq!(Value::from(2.5).to_sql_literal(), "2.5");
+        assert_eq!(Value::from(1e300).to_sql_literal(), "1e300");
+        assert_eq!(Value::from(Some("it's")).to_sql_literal(), "'it''s'");
+        assert_eq!(Value::from(None::<i32>).to_sql_literal(), "NULL");
+
+        // 双引号标识符中的 ? 不是占位符
+        let query = SqlQuery {
+            sql: String::from(r#"SELECT "why?" FROM t WHERE a = ?"#),
+            params: vec![Value::from(5)],
+        };
+        assert_eq!(query.to_inline_sql(), r#"SELECT "why?" FROM t WHERE a = 5"#);
+    }
+
+    #[test]
+    fn test_query_macro_matches_builder() {
+        let min_age = 18;
+        let from_macro = crate::query!(
+            SELECT id, name FROM users
+            WHERE age >= min_age AND name LIKE "A%" AND deleted IS NULL
+            ORDER BY name DESC LIMIT 5
+        );
+        let from_builder = Query::select(["id", "name"])
+            .from("users")
+            .where_(col("age").ge(min_age))
+            .where_(col("name").like("A%"))
+            .where_(col("deleted").is_null())
+            .order_by("name", Order::Desc)
+            .limit(5);
+        assert_eq!(from_macro, from_builder);
+
+        let base = 20;
+        assert_eq!(
+            crate::query!(INSERT INTO users (name, age) VALUES ("Bob", 20), ("Eve", (base + 1)))
+                .build()
+                .unwrap()
+                .params,
+            vec![
+                Value::from("Bob"),
+                Value::from(20),
+                Value::from("Eve"),
+                Value::from(21)
+            ]
+        );
+        assert_eq!(
+            crate::query!(UPDATE users SET age = 21, nickname = NULL WHERE id != 1)
+                .build()
+                .unwrap()
+                .sql,
+            "UPDATE users SET age = ?, nickname = ? WHERE id <> ?"
+        );
+        assert_eq!(
+            crate::query!(DELETE FROM users).build().unwrap().sql,
+            "DELETE FROM users"
+        );
+        assert_eq!(
+            crate::query!(SELECT * FROM users OFFSET 3)
+                .build()
+                .unwrap()
+                .sql,
+            "SELECT * FROM users LIMIT -1 OFFSET 3"
+        );
+    }
+}
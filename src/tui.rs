@@ -0,0 +1,330 @@
+// 终端组件工具箱
+// 第 18 章的 Widget / GuiButton / Screen 只会打印“绘制组件”，这里把它们做成真正可交互的终端界面：
+// 1. Buffer：字符单元组成的屏幕缓冲区，支持颜色、粗体、反色和占两列的中文字符
+// 2. Layout：行 / 列方向的弹性布局，固定尺寸、最小尺寸和按权重伸展
+// 3. Widget 与 App：组件树、Tab / Shift+Tab 焦点切换、按键先交给获得焦点的组件，再交给全局绑定
+// 4. Terminal：与上一帧比较，只输出变化的单元；TestBackend 是内存中的虚拟终端，供测试检查屏幕内容
+
+pub mod buffer;
+pub mod layout;
+pub mod terminal;
+pub mod widgets;
+
+pub use buffer::{Buffer, Cell, Color, Rect, Style};
+pub use layout::{Constraint, Direction, Layout};
+pub use terminal::{AnsiBackend, Backend, Terminal, TestBackend};
+pub use widgets::{Align, Button, Flex, Label, TextInput};
+
+use std::io;
+
+// ===========================================
+// 1. 按键
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    // Ctrl 加字母（小写），或者 Ctrl 加 ' '、'\'、']'、'^'、'_'
+    Ctrl(char),
+    Enter,
+    Tab,
+    // Shift+Tab
+    BackTab,
+    Backspace,
+    Delete,
+    Esc,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+}
+
+impl Key {
+    // 把终端在原始模式下发来的字节解码成按键；不认识的转义序列被跳过，不完整的 UTF-8 被丢弃
+    pub fn decode(input: &[u8]) -> Vec<Key> {
+        let mut keys = Vec::new();
+        let mut rest = input;
+        while let Some((&byte, tail)) = rest.split_first() {
+            rest = tail;
+            let key = match byte {
+                b'\r' | b'\n' => Key::Enter,
+                b'\t' => Key::Tab,
+                0x7f | 0x08 => Key::Backspace,
+                0x1b => match rest {
+                    [b'[', tail @ ..] => {
+                        // CSI 序列：参数字节之后跟一个 0x40..=0x7e 的结束字节
+                        let end = tail.iter().position(|b| (0x40..=0x7e).contains(b));
+                        let Some(end) = end else { break };
+                        rest = &tail[end + 1..];
+                        match (&tail[..end], tail[end]) {
+                            (_, b'A') => Key::Up,
+                            (_, b'B') => Key::Down,
+                            (_, b'C') => Key::Right,
+                            (_, b'D') => Key::Left,
+                            (_, b'H') | (b"1" | b"7", b'~') => Key::Home,
+                            (_, b'F') | (b"4" | b"8", b'~') => Key::End,
+                            (_, b'Z') => Key::BackTab,
+                            (b"3", b'~') => Key::Delete,
+                            _ => continue,
+                        }
+                    }
+                    _ => Key::Esc,
+                },
+                // 0x00 是 Ctrl+Space；0x1c..=0x1f 是 Ctrl 加 \ ] ^ _，不能当作字符插入
+                0x00 => Key::Ctrl(' '),
+                0x01..=0x1a => Key::Ctrl((b'a' + byte - 1) as char),
+                0x1c..=0x1f => Key::Ctrl((byte + 0x40) as char),
+                _ => {
+                    let len = match byte {
+                        0xc0..=0xdf => 2,
+                        0xe0..=0xef => 3,
+                        0xf0..=0xf7 => 4,
+                        _ => 1,
+                    };
+                    let bytes = [&[byte], &rest[..(len - 1).min(rest.len())]].concat();
+                    rest = &rest[(len - 1).min(rest.len())..];
+                    match std::str::from_utf8(&bytes)
+                        .ok()
+                        .and_then(|s| s.chars().next())
+                    {
+                        Some(c) => Key::Char(c),
+                        None => continue,
+                    }
+                }
+            };
+            keys.push(key);
+        }
+        keys
+    }
+}
+
+// ===========================================
+// 2. 组件
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventResult {
+    // 组件不处理这个按键，继续交给下一级
+    Ignored,
+    Consumed,
+    // 结束 App::run
+    Quit,
+}
+
+// 组件树中的一个节点
+// 叶子组件只需要实现 render；容器通过 children 和 layout 给出子组件和它们的区域，
+// 由 App 负责递归绘制和分发按键，所以焦点的位置只记录在 App 中
+pub trait Widget {
+    // 在 area 内绘制自身；容器在子组件之前绘制，可以画背景或边框
+    fn render(&self, area: Rect, buf: &mut Buffer, focused: bool);
+
+    // 可以获得焦点的组件参与 Tab 切换
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    fn handle_key(&mut self, _key: Key) -> EventResult {
+        EventResult::Ignored
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &[]
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        &mut []
+    }
+
+    // 与 children 一一对应的区域
+    fn layout(&self, _area: Rect) -> Vec<Rect> {
+        Vec::new()
+    }
+}
+
+// ===========================================
+// 3. App
+// ===========================================
+
+type Binding = (Key, Box<dyn FnMut() -> EventResult>);
+
+// 可聚焦的组件按深度优先的顺序编号，焦点就是其中一个编号
+pub struct App {
+    root: Box<dyn Widget>,
+    focus: usize,
+    bindings: Vec<Binding>,
+    running: bool,
+}
+
+impl App {
+    pub fn new(root: impl Widget + 'static) -> Self {
+        App {
+            root: Box::new(root),
+            focus: 0,
+            bindings: Vec::new(),
+            running: true,
+        }
+    }
+
+    // 全局按键：获得焦点的组件不处理这个按键时调用
+    pub fn bind(mut self, key: Key, handler: impl FnMut() -> EventResult + 'static) -> Self {
+        self.bindings.push((key, Box::new(handler)));
+        self
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn focus_count(&self) -> usize {
+        fn count(widget: &dyn Widget) -> usize {
+            let own = widget.focusable() as usize;
+            own + widget
+                .children()
+                .iter()
+                .map(|child| count(child.as_ref()))
+                .sum::<usize>()
+        }
+        count(self.root.as_ref())
+    }
+
+    // 获得焦点的组件编号；没有可聚焦的组件时为 None
+    pub fn focused(&self) -> Option<usize> {
+        (self.focus < self.focus_count()).then_some(self.focus)
+    }
+
+    pub fn focus_next(&mut self) {
+        let count = self.focus_count();
+        if count > 0 {
+            self.focus = (self.focus + 1) % count;
+        }
+    }
+
+    pub fn focus_prev(&mut self) {
+        let count = self.focus_count();
+        if count > 0 {
+            self.focus = (self.focus + count - 1) % count;
+        }
+    }
+
+    // 按键依次交给：获得焦点的组件、Tab / Shift+Tab 焦点切换、全局绑定
+    pub fn handle_key(&mut self, key: Key) -> EventResult {
+        let mut result = match nth_focusable(self.root.as_mut(), &mut self.focus.clone()) {
+            Some(widget) => widget.handle_key(key),
+            None => EventResult::Ignored,
+        };
+        if result == EventResult::Ignored {
+            result = match key {
+                Key::Tab => {
+                    self.focus_next();
+                    EventResult::Consumed
+                }
+                Key::BackTab => {
+                    self.focus_prev();
+                    EventResult::Consumed
+                }
+                _ => self
+                    .bindings
+                    .iter_mut()
+                    .find(|(bound, _)| *bound == key)
+                    .map_or(EventResult::Ignored, |(_, handler)| handler()),
+            };
+        }
+        if result == EventResult::Quit {
+            self.running = false;
+        }
+        result
+    }
+
+    // 把整个组件树画到 buf 上，根组件占满整个缓冲区
+    pub fn render(&self, buf: &mut Buffer) {
+        let mut index = 0;
+        render_tree(self.root.as_ref(), buf.area(), buf, self.focus, &mut index);
+    }
+
+    pub fn draw<B: Backend>(&self, terminal: &mut Terminal<B>) -> io::Result<usize> {
+        terminal.draw(|buf| self.render(buf))
+    }
+
+    // 绘制第一帧，然后每处理一个按键重绘一次，直到收到 Quit 或按键用完
+    pub fn run<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        keys: impl IntoIterator<Item = Key>,
+    ) -> io::Result<()> {
+        self.draw(terminal)?;
+        for key in keys {
+            if !self.running {
+                break;
+            }
+            self.handle_key(key);
+            self.draw(terminal)?;
+        }
+        Ok(())
+    }
+}
+
+fn render_tree(widget: &dyn Widget, area: Rect, buf: &mut Buffer, focus: usize, index: &mut usize) {
+    let focused = widget.focusable() && *index == focus;
+    if widget.focusable() {
+        *index += 1;
+    }
+    widget.render(area, buf, focused);
+    let areas = widget.layout(area);
+    for (child, child_area) in widget.children().iter().zip(areas) {
+        render_tree(child.as_ref(), child_area, buf, focus, index);
+    }
+}
+
+// 深度优先顺序中的第 n 个可聚焦组件
+fn nth_focusable<'a>(widget: &'a mut dyn Widget, n: &mut usize) -> Option<&'a mut dyn Widget> {
+    if widget.focusable() {
+        if *n == 0 {
+            return Some(widget);
+        }
+        *n -= 1;
+    }
+    widget
+        .children_mut()
+        .iter_mut()
+        .find_map(|child| nth_focusable(child.as_mut(), n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_keys() {
+        let input = "a中\r\t\x1b[Z\x1b[A\x1b[3~\x1b[5~\x7f\x03\x1b".as_bytes();
+        assert_eq!(
+            Key::decode(input),
+            [
+                Key::Char('a'),
+                Key::Char('中'),
+                Key::Enter,
+                Key::Tab,
+                Key::BackTab,
+                Key::Up,
+                Key::Delete,
+                // ESC[5~（PageUp）不认识，被跳过
+                Key::Backspace,
+                Key::Ctrl('c'),
+                Key::Esc,
+            ]
+        );
+        // 被截断的 UTF-8 丢弃
+        assert_eq!(Key::decode(&"中".as_bytes()[..2]), []);
+        assert_eq!(
+            Key::decode(b"\x00\x1c\x1d\x1e\x1f"),
+            [
+                Key::Ctrl(' '),
+                Key::Ctrl('\\'),
+                Key::Ctrl(']'),
+                Key::Ctrl('^'),
+                Key::Ctrl('_'),
+            ]
+        );
+    }
+}
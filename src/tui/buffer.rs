@@ -0,0 +1,432 @@
+// 终端缓冲区：宽 x 高个字符单元，每个单元是一个字符加上它的样式
+// 中文等宽字符占两列：左边的单元存字符，右边的单元存 CONTINUATION 占位，输出时跳过
+
+use crate::image::Rgb;
+use std::fmt::Write;
+
+// ===========================================
+// 1. 颜色与样式
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Color {
+    // 终端的默认前景色 / 背景色
+    #[default]
+    Reset,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+    // 24 位真彩色
+    Rgb(Rgb),
+}
+
+impl Color {
+    // SGR 参数；background 为 true 时是背景色
+    fn sgr(self, background: bool, out: &mut String) {
+        let base = if background { 40 } else { 30 };
+        let code = match self {
+            Color::Reset => base + 9,
+            Color::Black => base,
+            Color::Red => base + 1,
+            Color::Green => base + 2,
+            Color::Yellow => base + 3,
+            Color::Blue => base + 4,
+            Color::Magenta => base + 5,
+            Color::Cyan => base + 6,
+            Color::White => base + 7,
+            // 亮黑色，90 / 100
+            Color::Gray => base + 60,
+            Color::Rgb(Rgb([r, g, b])) => {
+                write!(out, ";{};2;{};{};{}", base + 8, r, g, b).unwrap();
+                return;
+            }
+        };
+        write!(out, ";{}", code).unwrap();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+    pub underline: bool,
+    pub reversed: bool,
+}
+
+impl Style {
+    pub const fn new() -> Self {
+        Style {
+            fg: Color::Reset,
+            bg: Color::Reset,
+            bold: false,
+            underline: false,
+            reversed: false,
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = color;
+        self
+    }
+
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = color;
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub const fn reversed(mut self) -> Self {
+        self.reversed = true;
+        self
+    }
+
+    // 完整的 SGR 序列：先用 0 清掉之前的所有属性，再设置这个样式，输出与终端原来的状态无关
+    pub fn to_sgr(self) -> String {
+        let mut out = String::from("\x1b[0");
+        if self.bold {
+            out.push_str(";1");
+        }
+        if self.underline {
+            out.push_str(";4");
+        }
+        if self.reversed {
+            out.push_str(";7");
+        }
+        if self.fg != Color::Reset {
+            self.fg.sgr(false, &mut out);
+        }
+        if self.bg != Color::Reset {
+            self.bg.sgr(true, &mut out);
+        }
+        out.push('m');
+        out
+    }
+}
+
+// ===========================================
+// 2. 区域
+// ===========================================
+
+// 以字符单元为单位的矩形，左上角 (x, y)；终端坐标不会是负数，所以都用 u16
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub const fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> u16 {
+        self.x.saturating_add(self.width)
+    }
+
+    pub fn bottom(&self) -> u16 {
+        self.y.saturating_add(self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        (self.x..self.right()).contains(&x) && (self.y..self.bottom()).contains(&y)
+    }
+
+    // 四边各向内收缩 amount
+    pub fn inset(&self, amount: u16) -> Rect {
+        Rect::new(
+            self.x.saturating_add(amount),
+            self.y.saturating_add(amount),
+            self.width.saturating_sub(2 * amount),
+            self.height.saturating_sub(2 * amount),
+        )
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Rect::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+}
+
+// ===========================================
+// 3. 字符宽度
+// ===========================================
+
+// 终端中占两列的字符：CJK 表意文字、谚文、全角符号等（East Asian Wide / Fullwidth 的常用区段）
+pub fn char_width(c: char) -> u16 {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+// 超过 u16::MAX 列时按 u16::MAX 计，终端不可能显示得下
+pub fn str_width(text: &str) -> u16 {
+    let width: usize = text.chars().map(|c| char_width(c) as usize).sum();
+    width.min(u16::MAX as usize) as u16
+}
+
+// ===========================================
+// 4. Buffer
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl Cell {
+    // 宽字符右半边的占位
+    pub const CONTINUATION: char = '\0';
+
+    pub const BLANK: Cell = Cell {
+        ch: ' ',
+        style: Style::new(),
+    };
+
+    pub fn is_continuation(&self) -> bool {
+        self.ch == Cell::CONTINUATION
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buffer {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::BLANK; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    // 越界时 panic，与 ImageBuffer::pixel 一致
+    pub fn cell(&self, x: u16, y: u16) -> Cell {
+        assert!(self.area().contains(x, y), "({}, {}) 超出缓冲区", x, y);
+        self.cells[self.index(x, y)]
+    }
+
+    pub fn reset(&mut self) {
+        self.cells.fill(Cell::BLANK);
+    }
+
+    // 写入一个字符；越界的写入被忽略。
+    // 覆盖宽字符的任意一半时，另一半变成空格，屏幕上不会留下半个字
+    pub fn set_char(&mut self, x: u16, y: u16, ch: char, style: Style) {
+        if !self.area().contains(x, y) {
+            return;
+        }
+        let width = char_width(ch);
+        // 宽字符放不下时写一个空格，而不是把它截成半个
+        let ch = if width == 2 && x + 1 >= self.width {
+            ' '
+        } else {
+            ch
+        };
+        let wide = ch != ' ' && width == 2;
+        self.clear_wide_neighbours(x, y);
+        if wide {
+            self.clear_wide_neighbours(x + 1, y);
+        }
+        let index = self.index(x, y);
+        self.cells[index] = Cell { ch, style };
+        if wide {
+            self.cells[index + 1] = Cell {
+                ch: Cell::CONTINUATION,
+                style,
+            };
+        }
+    }
+
+    fn clear_wide_neighbours(&mut self, x: u16, y: u16) {
+        let index = self.index(x, y);
+        let style = self.cells[index].style;
+        if self.cells[index].is_continuation() && x > 0 {
+            self.cells[index - 1] = Cell { ch: ' ', style };
+        }
+        if x + 1 < self.width && self.cells[index + 1].is_continuation() {
+            self.cells[index + 1] = Cell { ch: ' ', style };
+        }
+    }
+
+    // 从 (x, y) 开始写一行文字，最多占 max_width 列，返回实际占用的列数
+    pub fn set_str(&mut self, x: u16, y: u16, text: &str, style: Style, max_width: u16) -> u16 {
+        let mut used = 0;
+        for ch in text.chars() {
+            let width = char_width(ch);
+            if used + width > max_width || x.saturating_add(used) >= self.width {
+                break;
+            }
+            self.set_char(x + used, y, ch, style);
+            used += width;
+        }
+        used
+    }
+
+    pub fn fill(&mut self, area: Rect, ch: char, style: Style) {
+        let area = area.intersect(&self.area());
+        for y in area.y..area.bottom() {
+            for x in area.x..area.right() {
+                self.set_char(x, y, ch, style);
+            }
+        }
+    }
+
+    // 只改样式不改字符，用来给已经画好的区域加上高亮
+    pub fn set_style(&mut self, area: Rect, style: Style) {
+        let area = area.intersect(&self.area());
+        for y in area.y..area.bottom() {
+            for x in area.x..area.right() {
+                let index = self.index(x, y);
+                self.cells[index].style = style;
+            }
+        }
+    }
+
+    // 单线边框
+    pub fn draw_border(&mut self, area: Rect, style: Style) {
+        if area.width < 2 || area.height < 2 {
+            return;
+        }
+        let (right, bottom) = (area.right() - 1, area.bottom() - 1);
+        for x in area.x + 1..right {
+            self.set_char(x, area.y, '─', style);
+            self.set_char(x, bottom, '─', style);
+        }
+        for y in area.y + 1..bottom {
+            self.set_char(area.x, y, '│', style);
+            self.set_char(right, y, '│', style);
+        }
+        self.set_char(area.x, area.y, '┌', style);
+        self.set_char(right, area.y, '┐', style);
+        self.set_char(area.x, bottom, '└', style);
+        self.set_char(right, bottom, '┘', style);
+    }
+
+    // 每一行的文字，忽略样式；宽字符的占位不输出，所以每行的显示宽度都等于 width
+    pub fn lines(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| {
+                row.iter()
+                    .filter(|cell| !cell.is_continuation())
+                    .map(|cell| cell.ch)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_characters() {
+        let mut buffer = Buffer::new(8, 2);
+        assert_eq!(buffer.set_str(0, 0, "输入ab", Style::new(), 8), 6);
+        assert_eq!(buffer.lines()[0], "输入ab  ");
+        assert!(buffer.cell(1, 0).is_continuation());
+
+        // 覆盖“入”的右半边，左半边变成空格
+        buffer.set_char(3, 0, 'x', Style::new());
+        assert_eq!(buffer.lines()[0], "输 xab  ");
+        // 宽字符写在另一个宽字符的位置上
+        buffer.set_char(0, 0, '中', Style::new());
+        assert_eq!(buffer.lines()[0], "中 xab  ");
+
+        // 放不下的宽字符不写，最后一列的宽字符变成空格
+        assert_eq!(buffer.set_str(0, 1, "内容内容", Style::new(), 5), 4);
+        buffer.set_char(7, 1, '容', Style::new());
+        assert_eq!(buffer.lines()[1], "内容    ");
+        assert_eq!(str_width("Tab 键"), 6);
+        // 宽度超过 u16 时不溢出
+        assert_eq!(str_width(&"a".repeat(70_000)), u16::MAX);
+    }
+
+    #[test]
+    fn test_styles_and_border() {
+        assert_eq!(Style::new().to_sgr(), "\x1b[0m");
+        assert_eq!(
+            Style::new().bold().fg(Color::Red).bg(Color::Gray).to_sgr(),
+            "\x1b[0;1;31;100m"
+        );
+        assert_eq!(
+            Style::new()
+                .reversed()
+                .fg(Color::Rgb(Rgb([1, 2, 3])))
+                .to_sgr(),
+            "\x1b[0;7;38;2;1;2;3m"
+        );
+
+        let mut buffer = Buffer::new(5, 3);
+        buffer.draw_border(buffer.area(), Style::new());
+        assert_eq!(buffer.lines(), ["┌───┐", "│   │", "└───┘"]);
+        assert_eq!(
+            Rect::new(0, 0, 4, 4).intersect(&Rect::new(2, 3, 5, 5)),
+            Rect::new(2, 3, 2, 1)
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// 弹性布局：沿一个方向（行或列）切分区域
+// 与 CSS flexbox 的简化版相同：先满足固定尺寸，剩下的空间按权重分给可伸缩的子项，
+// 除不尽的余数从前往后每项多分一格，所以各项尺寸之和总是恰好等于可用空间
+
+use super::buffer::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    // 从左到右
+    Row,
+    // 从上到下
+    Column,
+}
+
+// 子项在主轴方向上的尺寸；交叉轴方向总是占满
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    // 恰好 n 格
+    Fixed(u16),
+    // 至少 n 格，有剩余空间时按权重 1 伸展
+    Min(u16),
+    // 只分剩余空间，按权重伸展
+    Flex(u16),
+}
+
+impl Constraint {
+    // 不伸展时的尺寸
+    fn base(self) -> u16 {
+        match self {
+            Constraint::Fixed(n) | Constraint::Min(n) => n,
+            Constraint::Flex(_) => 0,
+        }
+    }
+
+    fn weight(self) -> u32 {
+        match self {
+            Constraint::Fixed(_) => 0,
+            Constraint::Min(_) => 1,
+            Constraint::Flex(weight) => weight as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    direction: Direction,
+    gap: u16,
+    margin: u16,
+}
+
+impl Layout {
+    pub fn row() -> Self {
+        Layout {
+            direction: Direction::Row,
+            gap: 0,
+            margin: 0,
+        }
+    }
+
+    pub fn column() -> Self {
+        Layout {
+            direction: Direction::Column,
+            ..Layout::row()
+        }
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    // 相邻子项之间的间隔
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    // 四周留出的空白
+    pub fn margin(mut self, margin: u16) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    // 每个约束对应一个区域。空间不足时先压缩可伸缩项，再从后往前截断固定项，
+    // 完全放不下的子项得到主轴长度为 0 的区域
+    pub fn split(&self, area: Rect, constraints: &[Constraint]) -> Vec<Rect> {
+        let inner = area.inset(self.margin);
+        let (start, length) = match self.direction {
+            Direction::Row => (inner.x, inner.width),
+            Direction::Column => (inner.y, inner.height),
+        };
+        let gaps = self
+            .gap
+            .saturating_mul(constraints.len().saturating_sub(1) as u16);
+        let available = length.saturating_sub(gaps);
+
+        let fixed = constraints
+            .iter()
+            .map(|c| c.base())
+            .fold(0, u16::saturating_add);
+        let total_weight: u32 = constraints.iter().map(|c| c.weight()).sum();
+        let free = available.saturating_sub(fixed) as u32;
+        let shares: Vec<u32> = constraints
+            .iter()
+            .map(|c| (free * c.weight()).checked_div(total_weight).unwrap_or(0))
+            .collect();
+        let mut remainder = free - shares.iter().sum::<u32>();
+        let sizes = constraints.iter().zip(shares).map(|(constraint, share)| {
+            let extra = if remainder > 0 && constraint.weight() > 0 {
+                remainder -= 1;
+                1
+            } else {
+                0
+            };
+            constraint.base().saturating_add((share + extra) as u16)
+        });
+
+        let end = start.saturating_add(length);
+        let mut position = start;
+        sizes
+            .map(|size| {
+                let begin = position.min(end);
+                let size = size.min(end - begin);
+                position = begin.saturating_add(size).saturating_add(self.gap);
+                match self.direction {
+                    Direction::Row => Rect::new(begin, inner.y, size, inner.height),
+                    Direction::Column => Rect::new(inner.x, begin, inner.width, size),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widths(rects: &[Rect]) -> Vec<(u16, u16)> {
+        rects.iter().map(|r| (r.x, r.width)).collect()
+    }
+
+    #[test]
+    fn test_flex_distribution() {
+        let area = Rect::new(0, 0, 40, 5);
+        // 剩余 28 格按 1:2 分成 9 和 18，余下的 1 格给第一个可伸缩项
+        let rects = Layout::row().gap(1).split(
+            area,
+            &[
+                Constraint::Fixed(10),
+                Constraint::Flex(1),
+                Constraint::Flex(2),
+            ],
+        );
+        assert_eq!(widths(&rects), [(0, 10), (11, 10), (22, 18)]);
+        assert!(rects.iter().all(|r| r.height == 5));
+
+        // Min 至少得到自己的最小值，并和 Flex(1) 平分剩余空间
+        let rects = Layout::column().margin(1).split(
+            Rect::new(0, 0, 10, 12),
+            &[Constraint::Min(4), Constraint::Flex(1)],
+        );
+        assert_eq!(rects, [Rect::new(1, 1, 8, 7), Rect::new(1, 8, 8, 3)]);
+    }
+
+    #[test]
+    fn test_overflow_truncates() {
+        let rects = Layout::row().gap(2).split(
+            Rect::new(5, 0, 12, 1),
+            &[
+                Constraint::Fixed(6),
+                Constraint::Flex(1),
+                Constraint::Fixed(6),
+                Constraint::Fixed(3),
+            ],
+        );
+        // 12 格放不下 6 + 6 + 3 加三个间隔：第三项被截断，第四项长度为 0
+        assert_eq!(widths(&rects), [(5, 6), (13, 0), (15, 2), (17, 0)]);
+        assert_eq!(Layout::row().split(Rect::new(0, 0, 10, 1), &[]), []);
+    }
+}
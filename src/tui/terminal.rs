@@ -0,0 +1,377 @@
+// 差量渲染与终端后端
+// Terminal 保存上一帧的缓冲区，每一帧只输出发生变化的单元：相邻的变化单元共用一次光标移动，
+// 样式相同的连续单元共用一次 SGR，没有变化的帧不输出任何字节
+
+use super::buffer::{Buffer, Color, Style, char_width};
+use crate::image::Rgb;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::mem;
+
+// ===========================================
+// 1. 后端
+// ===========================================
+
+// Terminal 每一帧只调用一次 write，传入的总是完整的转义序列
+pub trait Backend {
+    fn size(&self) -> (u16, u16);
+    fn write(&mut self, output: &str) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+// 把转义序列写到任意 io::Write，例如 io::stdout()
+// 标准库不提供查询终端尺寸和切换原始模式的接口，尺寸由调用方给出
+pub struct AnsiBackend<W: Write> {
+    out: W,
+    size: (u16, u16),
+}
+
+impl<W: Write> AnsiBackend<W> {
+    pub fn new(out: W, width: u16, height: u16) -> Self {
+        AnsiBackend {
+            out,
+            size: (width, height),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> Backend for AnsiBackend<W> {
+    fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    fn write(&mut self, output: &str) -> io::Result<()> {
+        self.out.write_all(output.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// 内存中的虚拟终端：解释收到的转义序列并维护屏幕内容，测试可以检查屏幕上最终显示了什么，
+// 也可以检查渲染器输出了哪些字节。只支持 Terminal 会输出的序列，遇到其他序列直接 panic
+#[derive(Debug, Clone)]
+pub struct TestBackend {
+    screen: Buffer,
+    cursor: (u16, u16),
+    style: Style,
+    cursor_visible: bool,
+    output: String,
+}
+
+impl TestBackend {
+    pub fn new(width: u16, height: u16) -> Self {
+        TestBackend {
+            screen: Buffer::new(width, height),
+            cursor: (0, 0),
+            style: Style::new(),
+            cursor_visible: true,
+            output: String::new(),
+        }
+    }
+
+    pub fn screen(&self) -> &Buffer {
+        &self.screen
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.screen.lines()
+    }
+
+    pub fn cursor(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    pub fn is_cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    // 取出目前为止收到的原始输出
+    pub fn take_output(&mut self) -> String {
+        mem::take(&mut self.output)
+    }
+
+    // 模拟用户调整窗口大小；真实终端调整后内容不可靠，这里直接清空
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.screen = Buffer::new(width, height);
+        self.cursor = (0, 0);
+    }
+
+    fn control(&mut self, params: &str, command: char) {
+        let numbers = || params.split(';').map(|p| p.parse::<u16>().unwrap_or(0));
+        match (command, params) {
+            ('H', _) => {
+                let mut numbers = numbers();
+                let row = numbers.next().unwrap_or(1).max(1);
+                let column = numbers.next().unwrap_or(1).max(1);
+                self.cursor = (column - 1, row - 1);
+            }
+            ('J', "2") => {
+                self.screen.fill(self.screen.area(), ' ', self.style);
+            }
+            ('m', _) => self.select_graphic_rendition(&numbers().collect::<Vec<_>>()),
+            ('h', "?25") => self.cursor_visible = true,
+            ('l', "?25") => self.cursor_visible = false,
+            _ => panic!("TestBackend 不支持的转义序列: ESC[{}{}", params, command),
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, params: &[u16]) {
+        const BASIC: [Color; 8] = [
+            Color::Black,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::White,
+        ];
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => self.style = Style::new(),
+                1 => self.style.bold = true,
+                4 => self.style.underline = true,
+                7 => self.style.reversed = true,
+                30..=37 => self.style.fg = BASIC[param as usize - 30],
+                40..=47 => self.style.bg = BASIC[param as usize - 40],
+                39 => self.style.fg = Color::Reset,
+                49 => self.style.bg = Color::Reset,
+                90 => self.style.fg = Color::Gray,
+                100 => self.style.bg = Color::Gray,
+                38 | 48 => {
+                    let mut next = || params.next().expect("不完整的真彩色参数") as u8;
+                    assert_eq!(next(), 2, "只支持 24 位真彩色");
+                    let color = Color::Rgb(Rgb([next(), next(), next()]));
+                    if param == 38 {
+                        self.style.fg = color;
+                    } else {
+                        self.style.bg = color;
+                    }
+                }
+                _ => panic!("TestBackend 不支持的 SGR 参数: {}", param),
+            }
+        }
+    }
+}
+
+impl Backend for TestBackend {
+    fn size(&self) -> (u16, u16) {
+        (self.screen.width(), self.screen.height())
+    }
+
+    fn write(&mut self, output: &str) -> io::Result<()> {
+        self.output.push_str(output);
+        let mut chars = output.chars();
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                let (x, y) = self.cursor;
+                self.screen.set_char(x, y, c, self.style);
+                self.cursor.0 = (x + char_width(c)).min(self.screen.width());
+                continue;
+            }
+            assert_eq!(chars.next(), Some('['), "只支持 CSI 序列");
+            let mut params = String::new();
+            for c in chars.by_ref() {
+                if c.is_ascii_digit() || c == ';' || c == '?' {
+                    params.push(c);
+                } else {
+                    self.control(&params, c);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// ===========================================
+// 2. 差量渲染
+// ===========================================
+
+// 把终端从 previous 的画面变成 next 的画面所需的输出。
+// 约定输出开始时终端处于默认样式，结束时也恢复为默认样式
+pub fn diff(previous: &Buffer, next: &Buffer) -> String {
+    assert_eq!(
+        (previous.width(), previous.height()),
+        (next.width(), next.height()),
+        "两帧的尺寸必须相同"
+    );
+    let mut out = String::new();
+    let mut cursor = None;
+    let mut style = Style::new();
+    for y in 0..next.height() {
+        let mut x = 0;
+        while x < next.width() {
+            let cell = next.cell(x, y);
+            // 宽字符的右半边跟随左半边一起输出
+            if cell.is_continuation() {
+                x += 1;
+                continue;
+            }
+            let width = char_width(cell.ch).min(next.width() - x);
+            let changed = (x..x + width).any(|x| previous.cell(x, y) != next.cell(x, y));
+            if changed {
+                if cursor != Some((x, y)) {
+                    write!(out, "\x1b[{};{}H", y + 1, x + 1).unwrap();
+                }
+                if cell.style != style {
+                    out.push_str(&cell.style.to_sgr());
+                    style = cell.style;
+                }
+                out.push(cell.ch);
+                cursor = Some((x + width, y));
+            }
+            x += width;
+        }
+    }
+    if style != Style::new() {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+// ===========================================
+// 3. Terminal
+// ===========================================
+
+pub struct Terminal<B: Backend> {
+    backend: B,
+    previous: Buffer,
+    current: Buffer,
+    // 第一帧或尺寸变化后，需要先清屏再完整输出
+    stale: bool,
+}
+
+impl<B: Backend> Terminal<B> {
+    pub fn new(backend: B) -> Self {
+        let (width, height) = backend.size();
+        Terminal {
+            backend,
+            previous: Buffer::new(width, height),
+            current: Buffer::new(width, height),
+            stale: true,
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    // 上一帧的画面
+    pub fn buffer(&self) -> &Buffer {
+        &self.previous
+    }
+
+    // 绘制一帧：f 在空白缓冲区上画出完整的画面，只有与上一帧不同的单元会被输出。
+    // 返回这一帧输出的字节数
+    pub fn draw(&mut self, f: impl FnOnce(&mut Buffer)) -> io::Result<usize> {
+        let (width, height) = self.backend.size();
+        if (width, height) != (self.current.width(), self.current.height()) {
+            self.previous = Buffer::new(width, height);
+            self.current = Buffer::new(width, height);
+            self.stale = true;
+        }
+        self.current.reset();
+        f(&mut self.current);
+
+        let mut out = String::new();
+        if self.stale {
+            // 隐藏光标、重置样式并清屏，之后屏幕与空白缓冲区一致
+            out.push_str("\x1b[?25l\x1b[0m\x1b[2J");
+            self.previous.reset();
+            self.stale = false;
+        }
+        out.push_str(&diff(&self.previous, &self.current));
+        if !out.is_empty() {
+            self.backend.write(&out)?;
+            self.backend.flush()?;
+        }
+        mem::swap(&mut self.previous, &mut self.current);
+        Ok(out.len())
+    }
+
+    // 下一帧完整重绘，例如终端内容被其他程序破坏之后
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    // 退出前恢复终端：重置样式，光标移到画面下方并重新显示
+    pub fn restore(&mut self) -> io::Result<()> {
+        let row = self.previous.height() + 1;
+        self.backend
+            .write(&format!("\x1b[0m\x1b[{};1H\x1b[?25h", row))?;
+        self.backend.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_emits_only_changes() {
+        let previous = Buffer::new(10, 2);
+        let mut next = previous.clone();
+        assert_eq!(diff(&previous, &next), "");
+
+        // 相邻的单元不需要再次移动光标；换了样式才输出 SGR，最后恢复默认样式
+        next.set_str(2, 0, "ab", Style::new(), 10);
+        next.set_char(4, 0, 'c', Style::new().bold());
+        next.set_char(0, 1, 'z', Style::new());
+        assert_eq!(
+            diff(&previous, &next),
+            "\x1b[1;3Hab\x1b[0;1mc\x1b[2;1H\x1b[0mz"
+        );
+
+        // 宽字符只有右半边变化时也整个重新输出
+        let mut wide = Buffer::new(4, 1);
+        wide.set_char(0, 0, '中', Style::new());
+        let mut restyled = wide.clone();
+        restyled.set_char(0, 0, '中', Style::new().fg(Color::Green));
+        assert_eq!(diff(&wide, &restyled), "\x1b[1;1H\x1b[0;32m中\x1b[0m");
+    }
+
+    #[test]
+    fn test_terminal_frames() {
+        let mut terminal = Terminal::new(TestBackend::new(6, 2));
+        let first = terminal
+            .draw(|buf| {
+                buf.set_str(0, 0, "hi", Style::new(), 6);
+            })
+            .unwrap();
+        assert!(first > 0);
+        assert_eq!(terminal.backend().lines(), ["hi    ", "      "]);
+        assert!(!terminal.backend().is_cursor_visible());
+
+        // 同样的画面不输出任何内容，只改一个字符时只输出一个字符
+        terminal.backend_mut().take_output();
+        let draw = |buf: &mut Buffer| {
+            buf.set_str(0, 0, "ho", Style::new(), 6);
+        };
+        terminal.draw(draw).unwrap();
+        assert_eq!(terminal.backend_mut().take_output(), "\x1b[1;2Ho");
+        assert_eq!(terminal.draw(draw).unwrap(), 0);
+
+        // 尺寸变化后清屏重绘
+        terminal.backend_mut().resize(4, 1);
+        terminal.draw(draw).unwrap();
+        assert_eq!(terminal.backend().lines(), ["ho  "]);
+        terminal.restore().unwrap();
+        assert!(terminal.backend().is_cursor_visible());
+    }
+}
@@ -0,0 +1,421 @@
+// 内置组件：弹性容器 Flex、文本 Label、按钮 Button 和单行输入框 TextInput
+
+use super::buffer::{Buffer, Color, Rect, Style, char_width, str_width};
+use super::layout::{Constraint, Layout};
+use super::{EventResult, Key, Widget};
+
+type PressHandler = Box<dyn FnMut() -> EventResult>;
+type SubmitHandler = Box<dyn FnMut(&str) -> EventResult>;
+
+// ===========================================
+// 1. Flex 容器
+// ===========================================
+
+// 按行或列排列子组件，可以带一个有标题的边框
+pub struct Flex {
+    layout: Layout,
+    title: Option<String>,
+    constraints: Vec<Constraint>,
+    children: Vec<Box<dyn Widget>>,
+}
+
+impl Flex {
+    pub fn row() -> Self {
+        Flex::new(Layout::row())
+    }
+
+    pub fn column() -> Self {
+        Flex::new(Layout::column())
+    }
+
+    fn new(layout: Layout) -> Self {
+        Flex {
+            layout,
+            title: None,
+            constraints: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.layout = self.layout.gap(gap);
+        self
+    }
+
+    pub fn margin(mut self, margin: u16) -> Self {
+        self.layout = self.layout.margin(margin);
+        self
+    }
+
+    // 画一圈边框，标题显示在上边框上；子组件排在边框以内
+    pub fn bordered(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn child(mut self, constraint: Constraint, widget: impl Widget + 'static) -> Self {
+        self.constraints.push(constraint);
+        self.children.push(Box::new(widget));
+        self
+    }
+}
+
+impl Widget for Flex {
+    fn render(&self, area: Rect, buf: &mut Buffer, _focused: bool) {
+        if let Some(title) = &self.title {
+            buf.draw_border(area, Style::new());
+            if !title.is_empty() && area.width > 4 {
+                buf.set_str(
+                    area.x + 2,
+                    area.y,
+                    &format!(" {} ", title),
+                    Style::new().bold(),
+                    area.width - 4,
+                );
+            }
+        }
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        &mut self.children
+    }
+
+    fn layout(&self, area: Rect) -> Vec<Rect> {
+        let inner = match self.title {
+            Some(_) => area.inset(1),
+            None => area,
+        };
+        self.layout.split(inner, &self.constraints)
+    }
+}
+
+// ===========================================
+// 2. Label
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// 不可聚焦的文本，按行显示，超出区域的部分被截断
+pub struct Label {
+    text: Box<dyn Fn() -> String>,
+    style: Style,
+    align: Align,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Self {
+        let text = text.to_string();
+        Label::dynamic(move || text.clone())
+    }
+
+    // 每次绘制时调用 f 取得文字，用来显示会变化的状态
+    pub fn dynamic(f: impl Fn() -> String + 'static) -> Self {
+        Label {
+            text: Box::new(f),
+            style: Style::new(),
+            align: Align::Left,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+// 一行文字在 width 列中按 align 对齐后的起始偏移
+fn aligned_offset(text: &str, width: u16, align: Align) -> u16 {
+    let free = width.saturating_sub(str_width(text));
+    match align {
+        Align::Left => 0,
+        Align::Center => free / 2,
+        Align::Right => free,
+    }
+}
+
+impl Widget for Label {
+    fn render(&self, area: Rect, buf: &mut Buffer, _focused: bool) {
+        let text = (self.text)();
+        for (row, line) in text.lines().take(area.height as usize).enumerate() {
+            let offset = aligned_offset(line, area.width, self.align);
+            buf.set_str(
+                area.x + offset,
+                area.y + row as u16,
+                line,
+                self.style,
+                area.width - offset,
+            );
+        }
+    }
+}
+
+// ===========================================
+// 3. Button
+// ===========================================
+
+// 显示为 [ 标签 ]，获得焦点时反色；Enter 或空格触发 on_press
+pub struct Button {
+    label: String,
+    on_press: Option<PressHandler>,
+}
+
+impl Button {
+    pub fn new(label: &str) -> Self {
+        Button {
+            label: label.to_string(),
+            on_press: None,
+        }
+    }
+
+    // 按下时调用 f；f 返回 EventResult::Quit 可以结束程序
+    pub fn on_press(mut self, f: impl FnMut() -> EventResult + 'static) -> Self {
+        self.on_press = Some(Box::new(f));
+        self
+    }
+}
+
+impl Widget for Button {
+    fn render(&self, area: Rect, buf: &mut Buffer, focused: bool) {
+        if area.is_empty() {
+            return;
+        }
+        let text = format!("[ {} ]", self.label);
+        let style = if focused {
+            Style::new().bold().reversed()
+        } else {
+            Style::new().bold()
+        };
+        let offset = aligned_offset(&text, area.width, Align::Center);
+        buf.set_str(
+            area.x + offset,
+            area.y + (area.height - 1) / 2,
+            &text,
+            style,
+            area.width - offset,
+        );
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_key(&mut self, key: Key) -> EventResult {
+        match key {
+            Key::Enter | Key::Char(' ') => match &mut self.on_press {
+                Some(on_press) => on_press(),
+                None => EventResult::Consumed,
+            },
+            _ => EventResult::Ignored,
+        }
+    }
+}
+
+// ===========================================
+// 4. TextInput
+// ===========================================
+
+// 单行输入框：内容为空时显示灰色的占位文字；获得焦点时光标所在的单元反色显示，
+// 内容超出宽度时向左滚动，保证光标可见。Enter 调用 on_submit
+pub struct TextInput {
+    value: Vec<char>,
+    // 光标前面有几个字符
+    cursor: usize,
+    placeholder: String,
+    on_submit: Option<SubmitHandler>,
+}
+
+impl TextInput {
+    pub fn new(placeholder: &str) -> Self {
+        TextInput {
+            value: Vec::new(),
+            cursor: 0,
+            placeholder: placeholder.to_string(),
+            on_submit: None,
+        }
+    }
+
+    pub fn on_submit(mut self, f: impl FnMut(&str) -> EventResult + 'static) -> Self {
+        self.on_submit = Some(Box::new(f));
+        self
+    }
+
+    pub fn value(&self) -> String {
+        self.value.iter().collect()
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.chars().collect();
+        self.cursor = self.value.len();
+    }
+
+    // 从第几个字符开始显示，使光标所在的单元落在 width 列以内
+    // 宽度用 usize 累加，很长的输入不会溢出 u16
+    fn scroll(&self, width: u16) -> usize {
+        let columns = |c: char| char_width(c) as usize;
+        let mut start = 0;
+        let mut used: usize = self.value[..self.cursor].iter().map(|&c| columns(c)).sum();
+        // 光标所在的字符（在末尾时是一个空白）本身也要放得下
+        let cursor_width = self.value.get(self.cursor).map_or(1, |&c| columns(c));
+        while start < self.cursor && used + cursor_width > width as usize {
+            used -= columns(self.value[start]);
+            start += 1;
+        }
+        start
+    }
+}
+
+impl Widget for TextInput {
+    fn render(&self, area: Rect, buf: &mut Buffer, focused: bool) {
+        if area.is_empty() {
+            return;
+        }
+        let y = area.y + (area.height - 1) / 2;
+        let field = Style::new().underline();
+        buf.fill(Rect::new(area.x, y, area.width, 1), ' ', field);
+        if self.value.is_empty() && !self.placeholder.is_empty() {
+            let placeholder = field.fg(Color::Gray);
+            buf.set_str(area.x, y, &self.placeholder, placeholder, area.width);
+        }
+        let start = self.scroll(area.width);
+        let mut x = area.x;
+        for (index, &c) in self.value.iter().enumerate().skip(start) {
+            if char_width(c) > area.right() - x {
+                break;
+            }
+            let style = if focused && index == self.cursor {
+                field.reversed()
+            } else {
+                field
+            };
+            buf.set_char(x, y, c, style);
+            x += char_width(c);
+        }
+        // 光标在末尾时显示在最后一个字符后面的空白上
+        if focused && self.cursor == self.value.len() && x < area.right() {
+            let under = buf.cell(x, y);
+            buf.set_char(x, y, under.ch, field.reversed());
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_key(&mut self, key: Key) -> EventResult {
+        match key {
+            // 控制字符没有宽度，'\0' 还与宽字符的占位单元格相同，不能进入输入框
+            Key::Char(c) if !c.is_control() => {
+                self.value.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.value.remove(self.cursor);
+            }
+            Key::Delete if self.cursor < self.value.len() => {
+                self.value.remove(self.cursor);
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.value.len()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.value.len(),
+            Key::Enter => {
+                let value = self.value();
+                return match &mut self.on_submit {
+                    Some(on_submit) => on_submit(&value),
+                    None => EventResult::Consumed,
+                };
+            }
+            // 行首退格、行尾删除什么也不做，但按键仍然属于输入框
+            Key::Backspace | Key::Delete => {}
+            _ => return EventResult::Ignored,
+        }
+        EventResult::Consumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(widget: &dyn Widget, width: u16, focused: bool) -> Buffer {
+        let mut buf = Buffer::new(width, 1);
+        widget.render(buf.area(), &mut buf, focused);
+        buf
+    }
+
+    #[test]
+    fn test_text_input_editing_and_scrolling() {
+        let mut input = TextInput::new("名字");
+        assert_eq!(render(&input, 6, false).lines(), ["名字  "]);
+        assert_eq!(render(&input, 6, false).cell(0, 0).style.fg, Color::Gray);
+
+        for c in "abcdefgh".chars() {
+            input.handle_key(Key::Char(c));
+        }
+        input.handle_key(Key::Home);
+        input.handle_key(Key::Delete);
+        input.handle_key(Key::End);
+        input.handle_key(Key::Backspace);
+        input.handle_key(Key::Left);
+        input.handle_key(Key::Char('中'));
+        assert_eq!(input.value(), "bcdef中g");
+        assert_eq!(input.handle_key(Key::Char('\0')), EventResult::Ignored);
+        assert_eq!(input.handle_key(Key::Char('\u{1b}')), EventResult::Ignored);
+        assert_eq!(input.value(), "bcdef中g");
+
+        // 宽度 6 放不下全部内容，向左滚动到光标可见；光标所在的字符反色
+        let buf = render(&input, 6, true);
+        assert_eq!(buf.lines(), ["def中g"]);
+        assert!(buf.cell(5, 0).style.reversed);
+        assert!(!buf.cell(4, 0).style.reversed);
+        assert_eq!(input.handle_key(Key::Tab), EventResult::Ignored);
+    }
+
+    #[test]
+    fn test_long_values_do_not_overflow_width() {
+        let label = Label::new(&"a".repeat(70_000)).align(Align::Center);
+        assert_eq!(render(&label, 3, false).lines(), ["aaa"]);
+
+        let mut input = TextInput::new("");
+        input.set_value(&"中".repeat(33_000));
+        assert_eq!(render(&input, 6, true).lines(), ["中中  "]);
+    }
+
+    #[test]
+    fn test_label_alignment_and_button() {
+        let label = Label::new("ok").align(Align::Right);
+        assert_eq!(render(&label, 5, false).lines(), ["   ok"]);
+
+        let mut pressed = 0;
+        let counter = std::rc::Rc::new(std::cell::Cell::new(0));
+        let shared = counter.clone();
+        let mut button = Button::new("Go").on_press(move || {
+            shared.set(shared.get() + 1);
+            EventResult::Consumed
+        });
+        for key in [Key::Enter, Key::Char(' '), Key::Char('x')] {
+            if button.handle_key(key) == EventResult::Consumed {
+                pressed += 1;
+            }
+        }
+        assert_eq!((pressed, counter.get()), (2, 2));
+        let buf = render(&button, 10, true);
+        assert_eq!(buf.lines(), ["  [ Go ]  "]);
+        assert!(buf.cell(2, 0).style.reversed && !buf.cell(1, 0).style.reversed);
+    }
+}
@@ -0,0 +1,834 @@
+// 带量纲的物理量
+// 第 8 章的 Meters / Centimeters / Kilometers 和第 14 章的 Millimeters + Meters 用 newtype 防止单位混用，
+// 但每种单位都要手写一个类型，也只覆盖了长度。这里把量纲放进类型参数：
+// 1. 类型级整数：Z0、P1..P8、N1..N8，用 trait 在编译期做加减
+// 2. Quantity<L, M, T, V, E>：长度、质量、时间的指数作为类型参数，数值以 10^E 个国际单位制基本单位
+//    （m、kg、s）保存。E 默认为 0；整数用 E = -3 这样的标度就能精确保存 1 mm
+// 3. 运算：同量纲、同标度才能相加减，乘除时指数相加减，得到正确的导出量纲（m / s 就是 Velocity）
+// 4. 词头：km、mg、ms、kN 等的换算。整数要么精确完成、要么返回错误，浮点数按正常的舍入规则换算
+// 5. Display：按量纲输出单位符号，如 9.81 m/s²、10 N
+//
+// 量纲不匹配在编译期报错，见 tests/units.rs 中的 compile-fail 用例
+
+use crate::errors::{Diagnostic, ErrorCode};
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter;
+use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+// ===========================================
+// 1. 类型级整数
+// ===========================================
+
+// 编译期的整数；稳定版 Rust 还不能在 const 泛型参数上做运算（generic_const_exprs），
+// 所以每个整数是一个类型，加减法通过 trait 的关联类型求值
+pub trait Integer: Copy + Default + fmt::Debug + 'static {
+    const VALUE: i32;
+    type Neg: Integer;
+}
+
+// 后继和前驱，只在 -8..=8 范围内有定义；运算结果超出范围时编译失败
+pub trait Increment {
+    type Output: Integer;
+}
+
+pub trait Decrement {
+    type Output: Integer;
+}
+
+// Self + B
+pub trait Plus<B> {
+    type Output: Integer;
+}
+
+// Self - B
+pub trait Minus<B> {
+    type Output: Integer;
+}
+
+pub type Sum<A, B> = <A as Plus<B>>::Output;
+pub type Diff<A, B> = <A as Minus<B>>::Output;
+
+macro_rules! integers {
+    ($($name:ident = $value:expr, $neg:ident;)*) => {
+        $(
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+            pub struct $name;
+
+            impl Integer for $name {
+                const VALUE: i32 = $value;
+                type Neg = $neg;
+            }
+        )*
+    };
+}
+
+integers! {
+    N8 = -8, P8; N7 = -7, P7; N6 = -6, P6; N5 = -5, P5; N4 = -4, P4; N3 = -3, P3; N2 = -2, P2; N1 = -1, P1;
+    Z0 = 0, Z0;
+    P1 = 1, N1; P2 = 2, N2; P3 = 3, N3; P4 = 4, N4; P5 = 5, N5; P6 = 6, N6; P7 = 7, N7; P8 = 8, N8;
+}
+
+// 相邻的两个整数互为后继和前驱
+macro_rules! successors {
+    ($last:ident) => {};
+    ($a:ident $b:ident $($rest:ident)*) => {
+        impl Increment for $a {
+            type Output = $b;
+        }
+
+        impl Decrement for $b {
+            type Output = $a;
+        }
+
+        successors!($b $($rest)*);
+    };
+}
+
+successors!(N8 N7 N6 N5 N4 N3 N2 N1 Z0 P1 P2 P3 P4 P5 P6 P7 P8);
+
+impl<A: Integer> Plus<Z0> for A {
+    type Output = A;
+}
+
+// A + B = (A + 1) + (B - 1)，对正数 B 逐步递归到 Z0；负数方向对称
+macro_rules! plus {
+    ($step:ident: $($smaller:ident $b:ident)*) => {
+        $(
+            impl<A: $step> Plus<$b> for A
+            where
+                A::Output: Plus<$smaller>,
+            {
+                type Output = <A::Output as Plus<$smaller>>::Output;
+            }
+        )*
+    };
+}
+
+plus!(Increment: Z0 P1 P1 P2 P2 P3 P3 P4 P4 P5 P5 P6 P6 P7 P7 P8);
+plus!(Decrement: Z0 N1 N1 N2 N2 N3 N3 N4 N4 N5 N5 N6 N6 N7 N7 N8);
+
+impl<A, B: Integer> Minus<B> for A
+where
+    A: Plus<B::Neg>,
+{
+    type Output = <A as Plus<B::Neg>>::Output;
+}
+
+// ===========================================
+// 2. 数值类型与错误
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    // 整数换算结果不能精确表示，比如 1500 mm 换成以米为单位的整数
+    Inexact,
+    Overflow,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::Inexact => write!(f, "单位换算无法精确表示"),
+            ConversionError::Overflow => write!(f, "单位换算溢出"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl Diagnostic for ConversionError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::INVALID_INPUT
+    }
+}
+
+// Quantity 中保存的数值
+pub trait Scalar:
+    Copy
+    + PartialEq
+    + PartialOrd
+    + fmt::Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+
+    // 乘以 / 除以 10^exp；整数结果必须精确，否则返回错误
+    fn scale_up(self, exp: u32) -> Result<Self, ConversionError>;
+    fn scale_down(self, exp: u32) -> Result<Self, ConversionError>;
+}
+
+macro_rules! integer_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl Scalar for $t {
+                const ZERO: Self = 0;
+
+                fn scale_up(self, exp: u32) -> Result<Self, ConversionError> {
+                    (10 as $t)
+                        .checked_pow(exp)
+                        .and_then(|factor| self.checked_mul(factor))
+                        .ok_or(ConversionError::Overflow)
+                }
+
+                // 只有整除时才成功
+                fn scale_down(self, exp: u32) -> Result<Self, ConversionError> {
+                    match (10 as $t).checked_pow(exp) {
+                        Some(factor) if self % factor == 0 => Ok(self / factor),
+                        None if self == 0 => Ok(0),
+                        _ => Err(ConversionError::Inexact),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_scalar!(i32, i64);
+
+// 浮点数本来就是近似值，换算只做一次乘法或除法，结果按正常规则舍入；
+// 只有结果变成无穷大时才算溢出
+macro_rules! float_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl Scalar for $t {
+                const ZERO: Self = 0.0;
+
+                fn scale_up(self, exp: u32) -> Result<Self, ConversionError> {
+                    let scaled = self * (10.0 as $t).powi(exp as i32);
+                    if scaled.is_finite() {
+                        Ok(scaled)
+                    } else {
+                        Err(ConversionError::Overflow)
+                    }
+                }
+
+                fn scale_down(self, exp: u32) -> Result<Self, ConversionError> {
+                    Ok(self / (10.0 as $t).powi(exp as i32))
+                }
+            }
+        )*
+    };
+}
+
+float_scalar!(f32, f64);
+
+fn scale<V: Scalar>(value: V, exp: i32) -> Result<V, ConversionError> {
+    if exp >= 0 {
+        value.scale_up(exp as u32)
+    } else {
+        value.scale_down(exp.unsigned_abs())
+    }
+}
+
+// ===========================================
+// 3. Quantity
+// ===========================================
+
+// 长度指数 L、质量指数 M、时间指数 T；数值以 10^E 个 m、kg、s 为单位，
+// 比如 Length<i64, -3> 以毫米为单位保存，E 可以写成 { Prefix::Milli.exponent() }
+pub struct Quantity<L, M, T, V = f64, const E: i32 = 0> {
+    value: V,
+    _dimension: PhantomData<(L, M, T)>,
+}
+
+pub type Dimensionless<V = f64, const E: i32 = 0> = Quantity<Z0, Z0, Z0, V, E>;
+pub type Length<V = f64, const E: i32 = 0> = Quantity<P1, Z0, Z0, V, E>;
+pub type Mass<V = f64, const E: i32 = 0> = Quantity<Z0, P1, Z0, V, E>;
+pub type Time<V = f64, const E: i32 = 0> = Quantity<Z0, Z0, P1, V, E>;
+pub type Area<V = f64, const E: i32 = 0> = Quantity<P2, Z0, Z0, V, E>;
+pub type Volume<V = f64, const E: i32 = 0> = Quantity<P3, Z0, Z0, V, E>;
+pub type Frequency<V = f64, const E: i32 = 0> = Quantity<Z0, Z0, N1, V, E>;
+pub type Velocity<V = f64, const E: i32 = 0> = Quantity<P1, Z0, N1, V, E>;
+pub type Acceleration<V = f64, const E: i32 = 0> = Quantity<P1, Z0, N2, V, E>;
+pub type Momentum<V = f64, const E: i32 = 0> = Quantity<P1, P1, N1, V, E>;
+pub type Force<V = f64, const E: i32 = 0> = Quantity<P1, P1, N2, V, E>;
+pub type Energy<V = f64, const E: i32 = 0> = Quantity<P2, P1, N2, V, E>;
+pub type Power<V = f64, const E: i32 = 0> = Quantity<P2, P1, N3, V, E>;
+pub type Pressure<V = f64, const E: i32 = 0> = Quantity<N1, P1, N2, V, E>;
+
+impl<L, M, T, V, const E: i32> Quantity<L, M, T, V, E> {
+    // value 以 10^E 个国际单位制基本单位表示
+    pub const fn scaled(value: V) -> Self {
+        Quantity {
+            value,
+            _dimension: PhantomData,
+        }
+    }
+
+    pub fn value(self) -> V {
+        self.value
+    }
+}
+
+impl<L, M, T, V> Quantity<L, M, T, V> {
+    // value 以国际单位制的基本单位表示；其他标度用 scaled
+    // 只定义在标度 0 上，Velocity::new(2.5) 这样的写法才能推断出 E
+    pub const fn new(value: V) -> Self {
+        Self::scaled(value)
+    }
+}
+
+impl<V> Length<V> {
+    pub const fn meters(value: V) -> Self {
+        Self::scaled(value)
+    }
+}
+
+impl<V> Mass<V> {
+    pub const fn kilograms(value: V) -> Self {
+        Self::scaled(value)
+    }
+}
+
+impl<V> Time<V> {
+    pub const fn seconds(value: V) -> Self {
+        Self::scaled(value)
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> Quantity<L, M, T, V, E> {
+    pub fn zero() -> Self {
+        Self::scaled(V::ZERO)
+    }
+
+    pub fn abs(self) -> Self {
+        if self.value < V::ZERO { -self } else { self }
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        if other.value < self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        if other.value > self.value {
+            other
+        } else {
+            self
+        }
+    }
+
+    // 换算到另一个标度，如 Length<i64, -3>（毫米）换成 Length<i64>（米）
+    pub fn rescale<const F: i32>(self) -> Result<Quantity<L, M, T, V, F>, ConversionError> {
+        scale(self.value, E - F).map(Quantity::scaled)
+    }
+}
+
+// 手写而不是 derive：derive 会要求 L、M、T 也实现这些 trait
+impl<L, M, T, V: Copy, const E: i32> Clone for Quantity<L, M, T, V, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L, M, T, V: Copy, const E: i32> Copy for Quantity<L, M, T, V, E> {}
+
+impl<L, M, T, V: PartialEq, const E: i32> PartialEq for Quantity<L, M, T, V, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<L, M, T, V: PartialOrd, const E: i32> PartialOrd for Quantity<L, M, T, V, E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> Add for Quantity<L, M, T, V, E> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::scaled(self.value + other.value)
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> Sub for Quantity<L, M, T, V, E> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::scaled(self.value - other.value)
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> AddAssign for Quantity<L, M, T, V, E> {
+    fn add_assign(&mut self, other: Self) {
+        self.value = self.value + other.value;
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> SubAssign for Quantity<L, M, T, V, E> {
+    fn sub_assign(&mut self, other: Self) {
+        self.value = self.value - other.value;
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> Neg for Quantity<L, M, T, V, E> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::scaled(-self.value)
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> iter::Sum for Quantity<L, M, T, V, E> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |total, q| total + q)
+    }
+}
+
+// 量纲相乘：指数相加。稳定版 Rust 不能在类型里计算 E1 + E2，
+// 所以两个量相乘除只定义在标度 E = 0 上，其他标度先 rescale
+impl<L1, M1, T1, L2, M2, T2, V> Mul<Quantity<L2, M2, T2, V>> for Quantity<L1, M1, T1, V>
+where
+    L1: Plus<L2>,
+    M1: Plus<M2>,
+    T1: Plus<T2>,
+    V: Scalar,
+{
+    type Output = Quantity<Sum<L1, L2>, Sum<M1, M2>, Sum<T1, T2>, V>;
+
+    fn mul(self, other: Quantity<L2, M2, T2, V>) -> Self::Output {
+        Quantity::scaled(self.value * other.value)
+    }
+}
+
+// 量纲相除：指数相减
+impl<L1, M1, T1, L2, M2, T2, V> Div<Quantity<L2, M2, T2, V>> for Quantity<L1, M1, T1, V>
+where
+    L1: Minus<L2>,
+    M1: Minus<M2>,
+    T1: Minus<T2>,
+    V: Scalar,
+{
+    type Output = Quantity<Diff<L1, L2>, Diff<M1, M2>, Diff<T1, T2>, V>;
+
+    fn div(self, other: Quantity<L2, M2, T2, V>) -> Self::Output {
+        Quantity::scaled(self.value / other.value)
+    }
+}
+
+// 乘除纯数值，量纲不变
+impl<L, M, T, V: Scalar, const E: i32> Mul<V> for Quantity<L, M, T, V, E> {
+    type Output = Self;
+
+    fn mul(self, factor: V) -> Self {
+        Self::scaled(self.value * factor)
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> Div<V> for Quantity<L, M, T, V, E> {
+    type Output = Self;
+
+    fn div(self, divisor: V) -> Self {
+        Self::scaled(self.value / divisor)
+    }
+}
+
+macro_rules! scalar_times_quantity {
+    ($($t:ty),*) => {
+        $(
+            impl<L, M, T, const E: i32> Mul<Quantity<L, M, T, $t, E>> for $t {
+                type Output = Quantity<L, M, T, $t, E>;
+
+                fn mul(self, quantity: Quantity<L, M, T, $t, E>) -> Self::Output {
+                    Quantity::scaled(self * quantity.value)
+                }
+            }
+        )*
+    };
+}
+
+scalar_times_quantity!(i32, i64, f32, f64);
+
+// ===========================================
+// 4. 词头
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prefix {
+    Nano,
+    Micro,
+    Milli,
+    Centi,
+    Deci,
+    // 不带词头
+    Base,
+    Kilo,
+    Mega,
+    Giga,
+}
+
+impl Prefix {
+    pub const fn exponent(self) -> i32 {
+        match self {
+            Prefix::Nano => -9,
+            Prefix::Micro => -6,
+            Prefix::Milli => -3,
+            Prefix::Centi => -2,
+            Prefix::Deci => -1,
+            Prefix::Base => 0,
+            Prefix::Kilo => 3,
+            Prefix::Mega => 6,
+            Prefix::Giga => 9,
+        }
+    }
+
+    pub const fn from_exponent(exp: i32) -> Option<Prefix> {
+        Some(match exp {
+            -9 => Prefix::Nano,
+            -6 => Prefix::Micro,
+            -3 => Prefix::Milli,
+            -2 => Prefix::Centi,
+            -1 => Prefix::Deci,
+            0 => Prefix::Base,
+            3 => Prefix::Kilo,
+            6 => Prefix::Mega,
+            9 => Prefix::Giga,
+            _ => return None,
+        })
+    }
+
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Prefix::Nano => "n",
+            Prefix::Micro => "µ",
+            Prefix::Milli => "m",
+            Prefix::Centi => "c",
+            Prefix::Deci => "d",
+            Prefix::Base => "",
+            Prefix::Kilo => "k",
+            Prefix::Mega => "M",
+            Prefix::Giga => "G",
+        }
+    }
+}
+
+// 只用来给量纲实现 NamedUnit
+pub struct Dim<L, M, T>(PhantomData<(L, M, T)>);
+
+// 有单一符号、可以加词头的单位。SCALE 是这个符号相对国际单位制基本单位的 10 的幂：
+// 质量的基本单位 kg 本身带词头，词头要加在 g 上，1 g = 10^-3 kg
+#[diagnostic::on_unimplemented(
+    message = "量纲 {Self} 没有可以加词头的单位符号",
+    note = "只有 m、g、s 以及 Hz、N、J、W、Pa 可以加词头"
+)]
+pub trait NamedUnit {
+    const SYMBOL: &'static str;
+    const SCALE: i32;
+}
+
+macro_rules! named_units {
+    ($($l:ident $m:ident $t:ident => $symbol:literal, $scale:literal;)*) => {
+        $(
+            impl NamedUnit for Dim<$l, $m, $t> {
+                const SYMBOL: &'static str = $symbol;
+                const SCALE: i32 = $scale;
+            }
+        )*
+
+        // Display 优先使用这些符号，返回符号和它的 SCALE
+        fn named_unit(l: i32, m: i32, t: i32) -> Option<(&'static str, i32)> {
+            $(
+                if (l, m, t) == ($l::VALUE, $m::VALUE, $t::VALUE) {
+                    return Some(($symbol, $scale));
+                }
+            )*
+            None
+        }
+    };
+}
+
+named_units! {
+    P1 Z0 Z0 => "m", 0;
+    Z0 P1 Z0 => "g", -3;
+    Z0 Z0 P1 => "s", 0;
+    Z0 Z0 N1 => "Hz", 0;
+    P1 P1 N2 => "N", 0;
+    P2 P1 N2 => "J", 0;
+    P2 P1 N3 => "W", 0;
+    N1 P1 N2 => "Pa", 0;
+}
+
+// 和 new 一样只定义在标度 0 上：类型别名的默认参数不参与推断，
+// 否则 Length::from_prefixed(1.5, Prefix::Kilo) 就推不出 E。其他标度用 scaled 或 rescale
+impl<L, M, T, V: Scalar> Quantity<L, M, T, V>
+where
+    Dim<L, M, T>: NamedUnit,
+{
+    // 以带词头的单位给出数值，如 Length::from_prefixed(1.5, Prefix::Kilo) 是 1500 m
+    pub fn from_prefixed(value: V, prefix: Prefix) -> Result<Self, ConversionError> {
+        let exp = prefix.exponent() + <Dim<L, M, T>>::SCALE;
+        scale(value, exp).map(Self::new)
+    }
+}
+
+impl<L, M, T, V: Scalar, const E: i32> Quantity<L, M, T, V, E>
+where
+    Dim<L, M, T>: NamedUnit,
+{
+    pub fn to_prefixed(self, prefix: Prefix) -> Result<V, ConversionError> {
+        let exp = prefix.exponent() + <Dim<L, M, T>>::SCALE - E;
+        scale(self.value, -exp)
+    }
+
+    // 换算到带词头的单位后输出，如 1.5 km
+    pub fn display_in(self, prefix: Prefix) -> Result<Prefixed<V>, ConversionError> {
+        Ok(Prefixed {
+            value: self.to_prefixed(prefix)?,
+            prefix,
+            symbol: <Dim<L, M, T>>::SYMBOL,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Prefixed<V> {
+    value: V,
+    prefix: Prefix,
+    symbol: &'static str,
+}
+
+impl<V: fmt::Display> fmt::Display for Prefixed<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)?;
+        write!(f, " {}{}", self.prefix.symbol(), self.symbol)
+    }
+}
+
+// ===========================================
+// 5. Display
+// ===========================================
+
+fn superscript(exp: i32) -> String {
+    if exp == 1 {
+        return String::new();
+    }
+    exp.to_string()
+        .chars()
+        .map(|c| match c {
+            '-' => '⁻',
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            _ => '⁹',
+        })
+        .collect()
+}
+
+// 没有专门名称的量纲由基本单位组合而成，如 m/s²、kg·m/s、kg/m³
+pub fn unit_symbol(l: i32, m: i32, t: i32) -> String {
+    if let Some((symbol, 0)) = named_unit(l, m, t) {
+        return symbol.to_string();
+    }
+    let factors = [("kg", m), ("m", l), ("s", t)];
+    let part = |sign: i32| {
+        factors
+            .iter()
+            .filter(|(_, exp)| exp.signum() == sign)
+            .map(|(symbol, exp)| format!("{}{}", symbol, superscript(exp.abs())))
+            .collect::<Vec<_>>()
+    };
+    let (numerator, denominator) = (part(1), part(-1));
+    let numerator = if numerator.is_empty() {
+        "1".to_string()
+    } else {
+        numerator.join("·")
+    };
+    match denominator.len() {
+        0 if numerator == "1" => String::new(),
+        0 => numerator,
+        1 => format!("{}/{}", numerator, denominator[0]),
+        _ => format!("{}/({})", numerator, denominator.join("·")),
+    }
+}
+
+// 数值部分遵循格式参数，{:.2} 只影响数值
+// 标度不为 0 时优先换成带词头的单位（12 mm、3 mg），没有对应词头时写成 5×10⁻⁴ m/s
+impl<L: Integer, M: Integer, T: Integer, V: fmt::Display, const E: i32> fmt::Display
+    for Quantity<L, M, T, V, E>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)?;
+        let (l, m, t) = (L::VALUE, M::VALUE, T::VALUE);
+        if E != 0 {
+            if let Some((symbol, scale)) = named_unit(l, m, t)
+                && let Some(prefix) = Prefix::from_exponent(E - scale)
+            {
+                return write!(f, " {}{}", prefix.symbol(), symbol);
+            }
+            write!(f, "×10{}", superscript(E))?;
+        }
+        let symbol = unit_symbol(l, m, t);
+        if !symbol.is_empty() {
+            write!(f, " {}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+impl<L: Integer, M: Integer, T: Integer, V: fmt::Display, const E: i32> fmt::Debug
+    for Quantity<L, M, T, V, E>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Quantity({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_dimensions() {
+        let distance = Length::meters(100.0);
+        let time = Time::seconds(8.0);
+        let speed: Velocity = distance / time;
+        let acceleration: Acceleration = speed / time;
+        let force: Force = Mass::kilograms(80.0) * acceleration;
+        let energy: Energy = force * Length::meters(2.0);
+        let power: Power = energy / Time::seconds(0.5);
+        assert_eq!(speed.value(), 12.5);
+        assert_eq!(force.value(), 125.0);
+        assert_eq!(power.value(), 500.0);
+
+        // 同量纲的量相除得到无量纲的比值
+        let ratio: Dimensionless = Length::meters(3.0) / Length::meters(4.0);
+        assert_eq!(ratio.value(), 0.75);
+        let area: Area<i64> = Length::meters(3) * Length::meters(4);
+        let total: Length<i64> = [1, 2, 3].into_iter().map(Length::meters).sum();
+        assert_eq!((area.value(), total.value()), (12, 6));
+        assert!(Length::meters(1.0) < 2.0 * Length::meters(1.0));
+    }
+
+    #[test]
+    fn test_display_symbols() {
+        assert_eq!(Velocity::new(2.5).to_string(), "2.5 m/s");
+        assert_eq!(format!("{:.2}", Acceleration::new(9.80665)), "9.81 m/s²");
+        assert_eq!(Force::new(10).to_string(), "10 N");
+        assert_eq!(Area::new(4).to_string(), "4 m²");
+        assert_eq!(Momentum::new(3).to_string(), "3 kg·m/s");
+        assert_eq!(
+            (Mass::kilograms(1000) / Volume::new(1)).to_string(),
+            "1000 kg/m³"
+        );
+        assert_eq!(
+            (Length::meters(1.0) * Length::meters(2.0) / Time::seconds(1.0)).to_string(),
+            "2 m²/s"
+        );
+        assert_eq!(
+            Quantity::<N1, P1, N1, i32>::new(1).to_string(),
+            "1 kg/(m·s)"
+        );
+        assert_eq!(Frequency::new(50).to_string(), "50 Hz");
+        assert_eq!(Dimensionless::new(0.5).to_string(), "0.5");
+    }
+
+    #[test]
+    fn test_prefix_conversion() {
+        let run = Length::from_prefixed(1.5, Prefix::Kilo).unwrap();
+        assert_eq!(run.value(), 1500.0);
+        assert_eq!(run.to_prefixed(Prefix::Milli), Ok(1_500_000.0));
+        assert_eq!(run.display_in(Prefix::Kilo).unwrap().to_string(), "1.5 km");
+
+        // 浮点数按正常舍入换算，不要求能精确还原
+        assert_eq!(
+            Length::from_prefixed(0.23, Prefix::Centi).map(Length::value),
+            Ok(0.0023)
+        );
+        assert_eq!(
+            Length::from_prefixed(63.7, Prefix::Milli).map(Length::value),
+            Ok(0.0637)
+        );
+        assert_eq!(
+            Length::meters(f64::MAX).to_prefixed(Prefix::Milli),
+            Err(ConversionError::Overflow)
+        );
+
+        // 质量的词头加在 g 上
+        let pill = Mass::from_prefixed(250, Prefix::Milli);
+        assert_eq!(pill, Err(ConversionError::Inexact));
+        let pill = Mass::<f64>::from_prefixed(250.0, Prefix::Milli).unwrap();
+        assert_eq!(
+            pill.display_in(Prefix::Milli).unwrap().to_string(),
+            "250 mg"
+        );
+        assert_eq!(
+            Mass::kilograms(2)
+                .display_in(Prefix::Base)
+                .unwrap()
+                .to_string(),
+            "2000 g"
+        );
+
+        // 整数：放大可能溢出，缩小必须整除
+        assert_eq!(
+            Length::<i64>::from_prefixed(3, Prefix::Kilo),
+            Ok(Length::meters(3000))
+        );
+        assert_eq!(
+            Length::meters(1500).to_prefixed(Prefix::Kilo),
+            Err(ConversionError::Inexact)
+        );
+        assert_eq!(
+            Length::meters(i32::MAX).to_prefixed(Prefix::Milli),
+            Err(ConversionError::Overflow)
+        );
+        assert_eq!(
+            Force::new(2500.0)
+                .display_in(Prefix::Kilo)
+                .unwrap()
+                .to_string(),
+            "2.5 kN"
+        );
+    }
+
+    #[test]
+    fn test_scale_in_type() {
+        // 以米为单位的整数放不下 1 mm，以毫米为单位的就可以
+        const MILLI: i32 = Prefix::Milli.exponent();
+        assert_eq!(
+            Length::<i64>::from_prefixed(1, Prefix::Milli),
+            Err(ConversionError::Inexact)
+        );
+        let bolt = Length::<i64, MILLI>::scaled(1);
+        assert_eq!(bolt.to_prefixed(Prefix::Milli), Ok(1));
+        assert_eq!(bolt.to_string(), "1 mm");
+
+        let total = bolt + Length::meters(2).rescale::<MILLI>().unwrap();
+        assert_eq!(total.value(), 2001);
+        assert_eq!(
+            total.to_prefixed(Prefix::Base),
+            Err(ConversionError::Inexact)
+        );
+        assert_eq!(
+            total.display_in(Prefix::Micro).unwrap().to_string(),
+            "2001000 µm"
+        );
+        assert_eq!(total.rescale::<0>(), Err(ConversionError::Inexact));
+        assert_eq!(
+            (total + Length::scaled(999)).rescale(),
+            Ok(Length::<i64>::meters(3))
+        );
+
+        // 质量的基本单位是 kg，标度 -6 是 mg；没有对应词头的标度写成 10 的幂
+        assert_eq!(Mass::<i32, -6>::scaled(250).to_string(), "250 mg");
+        assert_eq!(Velocity::<i32, -4>::scaled(5).to_string(), "5×10⁻⁴ m/s");
+        assert_eq!(Dimensionless::<i32, 2>::scaled(7).to_string(), "7×10²");
+    }
+}
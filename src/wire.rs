@@ -0,0 +1,841 @@
+// 二进制报文编解码
+// 第 14 章 union_types 用 union 把同一块内存解释成 PacketHeader 或字节，既不处理字节序也不做任何校验。
+// 这里换成显式的编解码：
+// 1. BitWriter / BitReader：按位读写，高位在前（网络协议的惯例），读取时检查边界
+// 2. 字段编码：u8 / i8 / bool / [u8; N] / BitField<WIDTH> 直接编码，多字节数值必须写明大端或小端
+// 3. 校验和：Internet（RFC 1071）和 CRC-32，附加在报文末尾
+// 4. wire_layout! 宏：声明字段和布局，生成结构体以及 encode / decode
+//
+// 解码不信任输入：长度不足、多余字节、非法取值和校验和不匹配都返回 WireError，不会 panic
+
+use crate::errors::{Diagnostic, ErrorCode};
+use std::fmt;
+
+pub use crate::wire_layout;
+
+// ===========================================
+// 1. 错误
+// ===========================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireError {
+    // 读取 field 时剩余的位数不够
+    UnexpectedEnd {
+        field: &'static str,
+        needed_bits: usize,
+        available_bits: usize,
+    },
+    // decode 要求输入恰好是一个完整的报文
+    TrailingBytes {
+        expected: usize,
+        found: usize,
+    },
+    // encode_into 的目标缓冲区太小
+    BufferTooSmall {
+        needed: usize,
+        available: usize,
+    },
+    // 字段的位模式不是合法取值，比如 bool 只接受 0 和 1
+    InvalidValue {
+        field: &'static str,
+        value: u64,
+    },
+    ChecksumMismatch {
+        expected: u64,
+        found: u64,
+    },
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::UnexpectedEnd {
+                field,
+                needed_bits,
+                available_bits,
+            } => write!(
+                f,
+                "读取字段 {} 需要 {} 位，只剩 {} 位",
+                field, needed_bits, available_bits
+            ),
+            WireError::TrailingBytes { expected, found } => {
+                write!(f, "报文应为 {} 字节，实际有 {} 字节", expected, found)
+            }
+            WireError::BufferTooSmall { needed, available } => {
+                write!(f, "缓冲区需要 {} 字节，只有 {} 字节", needed, available)
+            }
+            WireError::InvalidValue { field, value } => {
+                write!(f, "字段 {} 的取值 {:#x} 不合法", field, value)
+            }
+            WireError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "校验和不匹配: 计算得到 {:#x}，报文中是 {:#x}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+impl Diagnostic for WireError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            WireError::BufferTooSmall { .. } => ErrorCode::INVALID_INPUT,
+            _ => ErrorCode::PARSE_BINARY,
+        }
+    }
+}
+
+// ===========================================
+// 2. 按位读写
+// ===========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+impl Endian {
+    // 当前平台的字节序，也就是 union 重新解释内存时看到的顺序
+    pub const NATIVE: Endian = if cfg!(target_endian = "big") {
+        Endian::Big
+    } else {
+        Endian::Little
+    };
+}
+
+// 写入时按位追加，最后一个字节不满时低位补 0
+#[derive(Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    bit_len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(bytes: usize) -> Self {
+        BitWriter {
+            bytes: Vec::with_capacity(bytes),
+            bit_len: 0,
+        }
+    }
+
+    // 写入 value 的低 bits 位，高位在前
+    pub fn write_bits(&mut self, value: u64, bits: u32) {
+        assert!(bits <= 64, "一次最多写入 64 位");
+        debug_assert!(
+            bits == 64 || value >> bits == 0,
+            "{} 超出了 {} 位",
+            value,
+            bits
+        );
+        let mut remaining = bits;
+        while remaining > 0 {
+            let offset = (self.bit_len % 8) as u32;
+            if offset == 0 {
+                self.bytes.push(0);
+            }
+            let take = remaining.min(8 - offset);
+            let chunk = ((value >> (remaining - take)) & ((1 << take) - 1)) as u8;
+            *self.bytes.last_mut().unwrap() |= chunk << (8 - offset - take);
+            self.bit_len += take as usize;
+            remaining -= take;
+        }
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.is_byte_aligned() {
+            self.bytes.extend_from_slice(bytes);
+            self.bit_len += bytes.len() * 8;
+        } else {
+            for &byte in bytes {
+                self.write_bits(byte as u64, 8);
+            }
+        }
+    }
+
+    pub fn is_byte_aligned(&self) -> bool {
+        self.bit_len.is_multiple_of(8)
+    }
+
+    pub fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        BitReader { data, bit_pos: 0 }
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        self.data.len() * 8 - self.bit_pos
+    }
+
+    fn ensure(&self, bits: usize, field: &'static str) -> Result<(), WireError> {
+        let available_bits = self.remaining_bits();
+        if bits > available_bits {
+            return Err(WireError::UnexpectedEnd {
+                field,
+                needed_bits: bits,
+                available_bits,
+            });
+        }
+        Ok(())
+    }
+
+    // 读取 bits 位，组成一个高位在前的整数；field 只用于错误信息
+    pub fn read_bits(&mut self, bits: u32, field: &'static str) -> Result<u64, WireError> {
+        assert!(bits <= 64, "一次最多读取 64 位");
+        self.ensure(bits as usize, field)?;
+        let mut value = 0u64;
+        let mut remaining = bits;
+        while remaining > 0 {
+            let byte = self.data[self.bit_pos / 8];
+            let offset = (self.bit_pos % 8) as u32;
+            let take = remaining.min(8 - offset);
+            let chunk = (byte >> (8 - offset - take)) & ((1u16 << take) - 1) as u8;
+            value = (value << take) | chunk as u64;
+            self.bit_pos += take as usize;
+            remaining -= take;
+        }
+        Ok(value)
+    }
+
+    pub fn read_array<const N: usize>(
+        &mut self,
+        field: &'static str,
+    ) -> Result<[u8; N], WireError> {
+        self.ensure(N * 8, field)?;
+        let mut bytes = [0u8; N];
+        if self.bit_pos.is_multiple_of(8) {
+            let start = self.bit_pos / 8;
+            bytes.copy_from_slice(&self.data[start..start + N]);
+            self.bit_pos += N * 8;
+        } else {
+            for byte in bytes.iter_mut() {
+                *byte = self.read_bits(8, field)? as u8;
+            }
+        }
+        Ok(bytes)
+    }
+
+    // 借用输入中的 len 个字节，只能在字节边界上调用
+    pub fn read_slice(&mut self, len: usize, field: &'static str) -> Result<&'a [u8], WireError> {
+        assert!(
+            self.bit_pos.is_multiple_of(8),
+            "read_slice 只能在字节边界上调用"
+        );
+        self.ensure(len * 8, field)?;
+        let start = self.bit_pos / 8;
+        self.bit_pos += len * 8;
+        Ok(&self.data[start..start + len])
+    }
+
+    // 尚未读取的完整字节
+    pub fn rest(&self) -> &'a [u8] {
+        &self.data[self.bit_pos.div_ceil(8)..]
+    }
+}
+
+// ===========================================
+// 3. 字段编码
+// ===========================================
+
+// 不需要说明字节序的字段：单字节、布尔值、字节数组和位域
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` 不能直接作为报文字段",
+    label = "多字节数值需要写明字节序，例如 `length: u16 as be`"
+)]
+pub trait WireField: Sized {
+    const BITS: usize;
+
+    fn write_to(&self, writer: &mut BitWriter);
+    fn read_from(reader: &mut BitReader<'_>, field: &'static str) -> Result<Self, WireError>;
+}
+
+impl WireField for u8 {
+    const BITS: usize = 8;
+
+    fn write_to(&self, writer: &mut BitWriter) {
+        writer.write_bits(*self as u64, 8);
+    }
+
+    fn read_from(reader: &mut BitReader<'_>, field: &'static str) -> Result<Self, WireError> {
+        Ok(reader.read_bits(8, field)? as u8)
+    }
+}
+
+impl WireField for i8 {
+    const BITS: usize = 8;
+
+    fn write_to(&self, writer: &mut BitWriter) {
+        writer.write_bits(*self as u8 as u64, 8);
+    }
+
+    fn read_from(reader: &mut BitReader<'_>, field: &'static str) -> Result<Self, WireError> {
+        Ok(reader.read_bits(8, field)? as u8 as i8)
+    }
+}
+
+// 占一个字节，只接受 0 和 1，保证每个报文只有一种编码
+impl WireField for bool {
+    const BITS: usize = 8;
+
+    fn write_to(&self, writer: &mut BitWriter) {
+        writer.write_bits(*self as u64, 8);
+    }
+
+    fn read_from(reader: &mut BitReader<'_>, field: &'static str) -> Result<Self, WireError> {
+        match reader.read_bits(8, field)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(WireError::InvalidValue { field, value }),
+        }
+    }
+}
+
+impl<const N: usize> WireField for [u8; N] {
+    const BITS: usize = N * 8;
+
+    fn write_to(&self, writer: &mut BitWriter) {
+        writer.write_bytes(self);
+    }
+
+    fn read_from(reader: &mut BitReader<'_>, field: &'static str) -> Result<Self, WireError> {
+        reader.read_array(field)
+    }
+}
+
+// 多字节数值，编码时由调用方给出字节序
+pub trait WireInt: Copy {
+    const BYTES: usize;
+
+    fn write_endian(self, writer: &mut BitWriter, endian: Endian);
+    fn read_endian(
+        reader: &mut BitReader<'_>,
+        endian: Endian,
+        field: &'static str,
+    ) -> Result<Self, WireError>;
+}
+
+macro_rules! impl_wire_int {
+    ($($t:ty),*) => {
+        $(
+            impl WireInt for $t {
+                const BYTES: usize = size_of::<$t>();
+
+                fn write_endian(self, writer: &mut BitWriter, endian: Endian) {
+                    match endian {
+                        Endian::Big => writer.write_bytes(&self.to_be_bytes()),
+                        Endian::Little => writer.write_bytes(&self.to_le_bytes()),
+                    }
+                }
+
+                fn read_endian(
+                    reader: &mut BitReader<'_>,
+                    endian: Endian,
+                    field: &'static str,
+                ) -> Result<Self, WireError> {
+                    let bytes = reader.read_array::<{ size_of::<$t>() }>(field)?;
+                    Ok(match endian {
+                        Endian::Big => <$t>::from_be_bytes(bytes),
+                        Endian::Little => <$t>::from_le_bytes(bytes),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_wire_int!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+// 宽度为 WIDTH 位的无符号整数（1..=32）
+// 第 14 章的 BitField<5> 因为需要 generic_const_exprs 只能写死宽度；
+// 掩码放在关联常量里计算就不需要这个特性，宽度不合法时在编译期报错
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BitField<const WIDTH: u32>(u32);
+
+impl<const WIDTH: u32> BitField<WIDTH> {
+    pub const MAX: u32 = {
+        assert!(
+            WIDTH != 0 && WIDTH <= 32,
+            "BitField 的宽度必须在 1..=32 之间"
+        );
+        u32::MAX >> (32 - WIDTH)
+    };
+
+    // 超出宽度时返回 None
+    pub const fn new(value: u32) -> Option<Self> {
+        if value <= Self::MAX {
+            Some(BitField(value))
+        } else {
+            None
+        }
+    }
+
+    // 丢弃超出宽度的高位
+    pub const fn truncated(value: u32) -> Self {
+        BitField(value & Self::MAX)
+    }
+
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    pub fn set(&mut self, value: u32) {
+        *self = Self::truncated(value);
+    }
+}
+
+impl<const WIDTH: u32> WireField for BitField<WIDTH> {
+    // 通过 MAX 计算，宽度不合法的位域用作字段时同样会触发编译期检查
+    const BITS: usize = Self::MAX.count_ones() as usize;
+
+    fn write_to(&self, writer: &mut BitWriter) {
+        writer.write_bits(self.0 as u64, WIDTH);
+    }
+
+    fn read_from(reader: &mut BitReader<'_>, field: &'static str) -> Result<Self, WireError> {
+        Ok(BitField(reader.read_bits(WIDTH, field)? as u32))
+    }
+}
+
+// ===========================================
+// 4. 校验和
+// ===========================================
+
+// 校验和覆盖报文中的所有字段，以大端形式附加在末尾，占 BYTES 个字节
+pub trait Checksum {
+    const BYTES: usize;
+
+    fn compute(data: &[u8]) -> u64;
+
+    fn append(body: &mut Vec<u8>) {
+        let value = Self::compute(body);
+        body.extend_from_slice(&value.to_be_bytes()[8 - Self::BYTES..]);
+    }
+
+    fn verify(body: &[u8], stored: &[u8]) -> Result<(), WireError> {
+        let expected = Self::compute(body);
+        let found = stored
+            .iter()
+            .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
+        if expected == found {
+            Ok(())
+        } else {
+            Err(WireError::ChecksumMismatch { expected, found })
+        }
+    }
+}
+
+// 不附加校验和
+pub struct NoChecksum;
+
+impl Checksum for NoChecksum {
+    const BYTES: usize = 0;
+
+    fn compute(_data: &[u8]) -> u64 {
+        0
+    }
+}
+
+// IPv4 / TCP / UDP 头部使用的校验和：按 16 位大端字累加，进位回卷，最后取反
+pub struct Internet;
+
+impl Checksum for Internet {
+    const BYTES: usize = 2;
+
+    fn compute(data: &[u8]) -> u64 {
+        let mut sum: u32 = data
+            .chunks(2)
+            .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32)
+            .sum();
+        while sum > 0xFFFF {
+            sum = (sum & 0xFFFF) + (sum >> 16);
+        }
+        !(sum as u16) as u64
+    }
+}
+
+// CRC-32（IEEE 802.3，以太网、zip 和 PNG 使用的版本），查找表在编译期生成
+pub struct Crc32;
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+impl Checksum for Crc32 {
+    const BYTES: usize = 4;
+
+    fn compute(data: &[u8]) -> u64 {
+        let crc = data.iter().fold(0xFFFF_FFFFu32, |crc, &byte| {
+            CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+        });
+        (crc ^ 0xFFFF_FFFF) as u64
+    }
+}
+
+// ===========================================
+// 5. wire_layout! 宏
+// ===========================================
+
+// 按顺序声明字段，生成结构体和编解码函数：
+//
+// wire_layout! {
+//     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//     pub struct Header {
+//         pub version: BitField<4>,   // 位域，紧密排列
+//         pub flags: BitField<4>,
+//         pub length: u16 as be,      // 多字节数值用 `as be` / `as le` 指定字节序
+//         pub tag: [u8; 2],
+//     }
+//     checksum = Crc32;               // 可选，附加在末尾
+// }
+//
+// 生成的关联项：
+// - BODY_BITS / ENCODED_LEN：字段总位数和完整报文的字节数，字段总位数不是 8 的倍数时编译失败
+// - encode / encode_into：编码，后者写入调用方提供的缓冲区
+// - decode：输入必须恰好是一个报文；decode_prefix 返回报文之后剩余的字节（比如负载）
+#[macro_export]
+macro_rules! wire_layout {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $ty:ty $(as $endian:ident)?
+            ),* $(,)?
+        }
+        $(checksum = $checksum:ty;)?
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        const _: () = assert!(
+            $name::BODY_BITS % 8 == 0,
+            concat!(stringify!($name), " 的字段总位数必须是 8 的倍数")
+        );
+
+        // 生成的是完整的编解码 API，私有布局只用到其中一部分时不报未使用
+        #[allow(dead_code)]
+        impl $name {
+            pub const BODY_BITS: usize = 0 $(+ $crate::wire_layout!(@bits $ty $(, $endian)?))*;
+            pub const ENCODED_LEN: usize = Self::BODY_BITS / 8
+                + <$crate::wire_layout!(@checksum $($checksum)?) as $crate::wire::Checksum>::BYTES;
+
+            pub fn encode(&self) -> ::std::vec::Vec<u8> {
+                let mut writer = $crate::wire::BitWriter::with_capacity(Self::ENCODED_LEN);
+                $($crate::wire_layout!(@write writer, self.$field, $ty $(, $endian)?);)*
+                let mut bytes = writer.finish();
+                <$crate::wire_layout!(@checksum $($checksum)?) as $crate::wire::Checksum>::append(
+                    &mut bytes,
+                );
+                bytes
+            }
+
+            // 返回写入的字节数
+            pub fn encode_into(&self, buffer: &mut [u8]) -> Result<usize, $crate::wire::WireError> {
+                if buffer.len() < Self::ENCODED_LEN {
+                    return Err($crate::wire::WireError::BufferTooSmall {
+                        needed: Self::ENCODED_LEN,
+                        available: buffer.len(),
+                    });
+                }
+                buffer[..Self::ENCODED_LEN].copy_from_slice(&self.encode());
+                Ok(Self::ENCODED_LEN)
+            }
+
+            pub fn decode(bytes: &[u8]) -> Result<Self, $crate::wire::WireError> {
+                let (value, rest) = Self::decode_prefix(bytes)?;
+                if !rest.is_empty() {
+                    return Err($crate::wire::WireError::TrailingBytes {
+                        expected: Self::ENCODED_LEN,
+                        found: bytes.len(),
+                    });
+                }
+                Ok(value)
+            }
+
+            pub fn decode_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), $crate::wire::WireError> {
+                let mut reader = $crate::wire::BitReader::new(bytes);
+                $(
+                    let $field = $crate::wire_layout!(
+                        @read reader, stringify!($field), $ty $(, $endian)?
+                    )?;
+                )*
+                type Sum = $crate::wire_layout!(@checksum $($checksum)?);
+                let stored = reader.read_slice(
+                    <Sum as $crate::wire::Checksum>::BYTES,
+                    "checksum",
+                )?;
+                <Sum as $crate::wire::Checksum>::verify(&bytes[..Self::BODY_BITS / 8], stored)?;
+                Ok((Self { $($field),* }, reader.rest()))
+            }
+        }
+    };
+
+    (@bits $ty:ty) => {
+        <$ty as $crate::wire::WireField>::BITS
+    };
+    (@bits $ty:ty, $endian:ident) => {
+        <$ty as $crate::wire::WireInt>::BYTES * 8
+    };
+
+    (@write $writer:ident, $value:expr, $ty:ty) => {
+        <$ty as $crate::wire::WireField>::write_to(&$value, &mut $writer)
+    };
+    (@write $writer:ident, $value:expr, $ty:ty, $endian:ident) => {
+        <$ty as $crate::wire::WireInt>::write_endian(
+            $value,
+            &mut $writer,
+            $crate::wire_layout!(@endian $endian),
+        )
+    };
+
+    (@read $reader:ident, $field:expr, $ty:ty) => {
+        <$ty as $crate::wire::WireField>::read_from(&mut $reader, $field)
+    };
+    (@read $reader:ident, $field:expr, $ty:ty, $endian:ident) => {
+        <$ty as $crate::wire::WireInt>::read_endian(
+            &mut $reader,
+            $crate::wire_layout!(@endian $endian),
+            $field,
+        )
+    };
+
+    (@endian be) => {
+        $crate::wire::Endian::Big
+    };
+    (@endian le) => {
+        $crate::wire::Endian::Little
+    };
+    (@endian $other:ident) => {
+        compile_error!(concat!("未知的字节序 `", stringify!($other), "`，只能是 be 或 le"))
+    };
+
+    (@checksum) => {
+        $crate::wire::NoChecksum
+    };
+    (@checksum $checksum:ty) => {
+        $checksum
+    };
+}
+
+// ===========================================
+// 6. 示例：PacketHeader
+// ===========================================
+
+wire_layout! {
+    // 第 14 章 union 示例中的包头：版本和标志共用第一个字节（版本在高 4 位），
+    // 长度和序号使用网络字节序，末尾是 Internet 校验和，共 10 字节
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct PacketHeader {
+        pub version: BitField<4>,
+        pub flags: BitField<4>,
+        pub kind: u8,
+        pub length: u16 as be,
+        pub sequence: u32 as be,
+    }
+    checksum = Internet;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    wire_layout! {
+        // 覆盖所有字段种类，故意让 reading 跨越字节边界
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct Telemetry {
+            active: bool,
+            mode: BitField<3>,
+            skew: BitField<4>,
+            reading: u16 as be,
+            level: BitField<9>,
+            offset: i16 as le,
+            counter: u64 as be,
+            ratio: f32 as le,
+            tag: [u8; 3],
+        }
+        checksum = Crc32;
+    }
+
+    fn telemetry() -> impl Strategy<Value = Telemetry> {
+        (
+            (any::<bool>(), 0u32..8, 0u32..16, any::<u16>(), 0u32..512),
+            (any::<i16>(), any::<u64>(), any::<f32>(), any::<[u8; 3]>()),
+        )
+            .prop_map(
+                |((active, mode, skew, reading, level), (offset, counter, ratio, tag))| Telemetry {
+                    active,
+                    mode: BitField::truncated(mode),
+                    skew: BitField::truncated(skew),
+                    reading,
+                    level: BitField::truncated(level),
+                    offset,
+                    counter,
+                    ratio,
+                    tag,
+                },
+            )
+    }
+
+    #[test]
+    fn packet_header_layout_and_checksum() {
+        let header = PacketHeader {
+            version: BitField::truncated(4),
+            flags: BitField::truncated(0b1010),
+            kind: 7,
+            length: 0x1234,
+            sequence: 0xDEAD_BEEF,
+        };
+        let bytes = header.encode();
+        assert_eq!(PacketHeader::ENCODED_LEN, 10);
+        // 长度和序号按大端排列，与主机字节序无关
+        assert_eq!(bytes[..8], [0x4A, 0x07, 0x12, 0x34, 0xDE, 0xAD, 0xBE, 0xEF]);
+        // 带上校验和重新计算，结果为 0 表示校验通过
+        assert_eq!(Internet::compute(&bytes), 0);
+        assert_eq!(PacketHeader::decode(&bytes), Ok(header));
+
+        let mut payload = bytes.clone();
+        payload.extend_from_slice(b"body");
+        let (decoded, rest) = PacketHeader::decode_prefix(&payload).unwrap();
+        assert_eq!((decoded, rest), (header, &b"body"[..]));
+        let err = PacketHeader::decode(&payload).unwrap_err();
+        assert_eq!(
+            err,
+            WireError::TrailingBytes {
+                expected: 10,
+                found: 14
+            }
+        );
+
+        let mut buffer = [0u8; 8];
+        let err = header.encode_into(&mut buffer).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::INVALID_INPUT);
+    }
+
+    #[test]
+    fn decode_reports_field_and_invalid_values() {
+        let err = PacketHeader::decode(&[0x41, 0x02, 0x00]).unwrap_err();
+        assert_eq!(
+            err,
+            WireError::UnexpectedEnd {
+                field: "length",
+                needed_bits: 16,
+                available_bits: 8
+            }
+        );
+        assert_eq!(err.code(), ErrorCode::PARSE_BINARY);
+
+        let zero = Telemetry {
+            active: false,
+            mode: BitField::default(),
+            skew: BitField::default(),
+            reading: 0,
+            level: BitField::default(),
+            offset: 0,
+            counter: 0,
+            ratio: 0.0,
+            tag: [0; 3],
+        };
+        let mut bytes = zero.encode();
+        bytes[0] = 2;
+        assert_eq!(
+            Telemetry::decode(&bytes),
+            Err(WireError::InvalidValue {
+                field: "active",
+                value: 2
+            })
+        );
+    }
+
+    #[test]
+    fn checksum_reference_values() {
+        assert_eq!(Crc32::compute(b"123456789"), 0xCBF4_3926);
+        // RFC 1071 第 3 节的例子
+        let words = [0x00, 0x01, 0xF2, 0x03, 0xF4, 0xF5, 0xF6, 0xF7];
+        assert_eq!(Internet::compute(&words), !0xDDF2u16 as u64);
+        assert_eq!(BitField::<5>::new(32), None);
+        assert_eq!(BitField::<5>::truncated(0x3F).get(), 0x1F);
+        assert_eq!(BitField::<32>::MAX, u32::MAX);
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(value in telemetry()) {
+            let bytes = value.encode();
+            prop_assert_eq!(bytes.len(), Telemetry::ENCODED_LEN);
+            let decoded = Telemetry::decode(&bytes).unwrap();
+            // NaN 不等于自身，浮点数按位比较
+            prop_assert_eq!(decoded.ratio.to_bits(), value.ratio.to_bits());
+            prop_assert_eq!(decoded.encode(), bytes);
+        }
+
+        // 任意输入都不会 panic；能解码的输入重新编码后逐字节一致
+        #[test]
+        fn decode_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..48)) {
+            if let Ok((value, rest)) = Telemetry::decode_prefix(&bytes) {
+                let encoded = value.encode();
+                prop_assert_eq!(&bytes[..encoded.len()], &encoded[..]);
+                prop_assert_eq!(rest, &bytes[encoded.len()..]);
+            }
+        }
+
+        // CRC-32 能发现任意单个位的翻转
+        #[test]
+        fn single_bit_flip_is_rejected(value in telemetry(), bit in 0..Telemetry::ENCODED_LEN * 8) {
+            let mut bytes = value.encode();
+            bytes[bit / 8] ^= 0x80 >> (bit % 8);
+            prop_assert!(Telemetry::decode(&bytes).is_err());
+        }
+
+        #[test]
+        fn truncated_input_is_rejected(value in telemetry(), len in 0..Telemetry::ENCODED_LEN) {
+            let bytes = value.encode();
+            let is_unexpected_end = matches!(
+                Telemetry::decode(&bytes[..len]),
+                Err(WireError::UnexpectedEnd { .. })
+            );
+            prop_assert!(is_unexpected_end);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// 状态机工作流
+// 第 18 章的 Post 用 Box<dyn State> 实现状态模式，第 4 章的 TrafficLight、ConnectionState 用 match 写死转换，
+// 状态、转换和约束散落在各个方法里。这里把它们声明在一处，提供两种形式：
+// 1. Workflow：运行时的转换表，状态和事件是普通的枚举值。转换可以带守卫（guard）和动作（action），
+//    状态可以挂进入 / 离开的钩子；build 时检查不可达的状态和被遮蔽的转换，to_dot 输出 Graphviz 图
+// 2. typestate：每个状态是一个类型，转换是 trait 实现，Machine<S, C> 只有在 S 实现了对应的转换时
+//    才能 fire，非法转换在编译期报错，旧状态的值在转换后被移走，不能再使用
+//
+// 运行时形式适合状态来自外部输入（网络事件、数据库中的字段）的场景，
+// typestate 适合状态顺序在代码中就能确定的场景，例如构建器和协议握手
+
+use crate::errors::{Diagnostic, ErrorCode};
+use std::fmt;
+
+pub mod machine;
+pub mod typestate;
+
+pub use machine::{Step, Workflow, WorkflowBuilder};
+pub use typestate::{GuardedTransition, Initial, Machine, Rejected, State, Transition};
+
+// ===========================================
+// 错误
+// ===========================================
+
+// 状态和事件以 Debug 格式保存，错误类型不需要带泛型参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowError {
+    // 当前状态没有这个事件的转换
+    NoTransition {
+        workflow: String,
+        state: String,
+        event: String,
+    },
+    // 有这个事件的转换，但所有守卫都没有通过
+    Rejected {
+        workflow: String,
+        state: String,
+        event: String,
+        guards: Vec<String>,
+    },
+    // 以下两种在 build 时检查
+    // 从初始状态出发无论如何都到不了的状态
+    Unreachable {
+        workflow: String,
+        state: String,
+    },
+    // 同一个状态和事件前面已经有一个没有守卫的转换，后面的转换永远不会被选中
+    Shadowed {
+        workflow: String,
+        state: String,
+        event: String,
+    },
+}
+
+impl fmt::Display for WorkflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkflowError::NoTransition {
+                workflow,
+                state,
+                event,
+            } => write!(f, "{}: 状态 {} 不能响应事件 {}", workflow, state, event),
+            WorkflowError::Rejected {
+                workflow,
+                state,
+                event,
+                guards,
+            } => write!(
+                f,
+                "{}: 状态 {} 的事件 {} 被守卫拒绝 ({})",
+                workflow,
+                state,
+                event,
+                guards.join(", ")
+            ),
+            WorkflowError::Unreachable { workflow, state } => {
+                write!(f, "{}: 状态 {} 从初始状态不可达", workflow, state)
+            }
+            WorkflowError::Shadowed {
+                workflow,
+                state,
+                event,
+            } => write!(
+                f,
+                "{}: 状态 {} 的事件 {} 已有无守卫的转换，后面的转换不会被选中",
+                workflow, state, event
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorkflowError {}
+
+impl Diagnostic for WorkflowError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            WorkflowError::NoTransition { .. } | WorkflowError::Rejected { .. } => {
+                ErrorCode::INVALID_INPUT
+            }
+            WorkflowError::Unreachable { .. } | WorkflowError::Shadowed { .. } => {
+                ErrorCode::VALIDATION
+            }
+        }
+    }
+}
@@ -0,0 +1,482 @@
+// 运行时的转换表
+// Workflow 本身不保存当前状态，fire 接收当前状态和上下文、返回新状态，
+// 同一个 Workflow 可以放在 static 中，被任意多个对象共享
+
+use super::WorkflowError;
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Write as _};
+
+type Guard<C> = Box<dyn Fn(&C) -> bool + Send + Sync>;
+type Hook<S, E, C> = Box<dyn Fn(&mut C, &Step<S, E>) + Send + Sync>;
+
+// 正在进行的一次转换，传给动作和钩子
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step<S, E> {
+    pub from: S,
+    pub event: E,
+    pub to: S,
+}
+
+struct Transition<S, E, C> {
+    from: S,
+    event: E,
+    to: S,
+    guard: Option<(String, Guard<C>)>,
+    actions: Vec<Hook<S, E, C>>,
+}
+
+// ===========================================
+// 1. 构建
+// ===========================================
+
+pub struct WorkflowBuilder<S, E, C = ()> {
+    workflow: Workflow<S, E, C>,
+}
+
+impl<S, E, C> WorkflowBuilder<S, E, C>
+where
+    S: Copy + Eq + Debug,
+    E: Copy + Eq + Debug,
+{
+    // 声明一个状态。出现在转换中的状态会自动加入，这里只用来固定 DOT 中的顺序或声明还没有转换的状态
+    pub fn state(mut self, state: S) -> Self {
+        self.workflow.add_state(state);
+        self
+    }
+
+    // 同一个状态和事件可以有多个转换，fire 时按声明的顺序选第一个守卫通过的
+    pub fn transition(mut self, from: S, event: E, to: S) -> Self {
+        self.workflow.add_state(from);
+        self.workflow.add_state(to);
+        self.workflow.transitions.push(Transition {
+            from,
+            event,
+            to,
+            guard: None,
+            actions: Vec::new(),
+        });
+        self
+    }
+
+    // 给上一个转换加守卫，label 出现在错误信息和 DOT 图中。每个转换只有一个守卫，再次调用会替换
+    pub fn guard(
+        mut self,
+        label: &str,
+        guard: impl Fn(&C) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.last_transition("guard").guard = Some((label.to_string(), Box::new(guard)));
+        self
+    }
+
+    // 给上一个转换加动作，在离开旧状态之后、进入新状态之前执行
+    pub fn action(mut self, action: impl Fn(&mut C, &Step<S, E>) + Send + Sync + 'static) -> Self {
+        self.last_transition("action")
+            .actions
+            .push(Box::new(action));
+        self
+    }
+
+    // 任何转换进入 state 时执行，包括从 state 回到自身
+    pub fn on_enter(
+        mut self,
+        state: S,
+        hook: impl Fn(&mut C, &Step<S, E>) + Send + Sync + 'static,
+    ) -> Self {
+        self.workflow.add_state(state);
+        self.workflow.on_enter.push((state, Box::new(hook)));
+        self
+    }
+
+    pub fn on_exit(
+        mut self,
+        state: S,
+        hook: impl Fn(&mut C, &Step<S, E>) + Send + Sync + 'static,
+    ) -> Self {
+        self.workflow.add_state(state);
+        self.workflow.on_exit.push((state, Box::new(hook)));
+        self
+    }
+
+    // 检查定义：所有状态都要从初始状态可达（不考虑守卫），转换不能被前面无守卫的转换遮蔽
+    pub fn build(self) -> Result<Workflow<S, E, C>, WorkflowError> {
+        let workflow = self.workflow;
+        for (i, later) in workflow.transitions.iter().enumerate() {
+            let shadowed = workflow.transitions[..i].iter().any(|earlier| {
+                earlier.guard.is_none()
+                    && (earlier.from, earlier.event) == (later.from, later.event)
+            });
+            if shadowed {
+                return Err(WorkflowError::Shadowed {
+                    workflow: workflow.name.clone(),
+                    state: format!("{:?}", later.from),
+                    event: format!("{:?}", later.event),
+                });
+            }
+        }
+
+        let mut reached = vec![false; workflow.states.len()];
+        let mut queue = VecDeque::from([workflow.initial]);
+        while let Some(state) = queue.pop_front() {
+            let index = workflow.index_of(state);
+            if reached[index] {
+                continue;
+            }
+            reached[index] = true;
+            queue.extend(
+                workflow
+                    .transitions
+                    .iter()
+                    .filter(|t| t.from == state)
+                    .map(|t| t.to),
+            );
+        }
+        if let Some(index) = reached.iter().position(|&r| !r) {
+            return Err(WorkflowError::Unreachable {
+                workflow: workflow.name.clone(),
+                state: format!("{:?}", workflow.states[index]),
+            });
+        }
+        Ok(workflow)
+    }
+
+    fn last_transition(&mut self, what: &str) -> &mut Transition<S, E, C> {
+        match self.workflow.transitions.last_mut() {
+            Some(transition) => transition,
+            None => panic!("{} 必须跟在 transition 之后", what),
+        }
+    }
+}
+
+// ===========================================
+// 2. 执行
+// ===========================================
+
+pub struct Workflow<S, E, C = ()> {
+    name: String,
+    initial: S,
+    // 按第一次出现的顺序
+    states: Vec<S>,
+    transitions: Vec<Transition<S, E, C>>,
+    on_enter: Vec<(S, Hook<S, E, C>)>,
+    on_exit: Vec<(S, Hook<S, E, C>)>,
+}
+
+impl<S, E, C> Workflow<S, E, C>
+where
+    S: Copy + Eq + Debug,
+    E: Copy + Eq + Debug,
+{
+    pub fn builder(name: &str, initial: S) -> WorkflowBuilder<S, E, C> {
+        WorkflowBuilder {
+            workflow: Workflow {
+                name: name.to_string(),
+                initial,
+                states: vec![initial],
+                transitions: Vec::new(),
+                on_enter: Vec::new(),
+                on_exit: Vec::new(),
+            },
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn initial(&self) -> S {
+        self.initial
+    }
+
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    // state 可以响应的事件，不考虑守卫，按声明的顺序去重
+    pub fn events(&self, state: S) -> Vec<E> {
+        let mut events = Vec::new();
+        for t in self.transitions.iter().filter(|t| t.from == state) {
+            if !events.contains(&t.event) {
+                events.push(t.event);
+            }
+        }
+        events
+    }
+
+    // 没有任何出边的状态
+    pub fn is_final(&self, state: S) -> bool {
+        !self.transitions.iter().any(|t| t.from == state)
+    }
+
+    // fire 会不会成功，不执行任何动作
+    pub fn can_fire(&self, state: S, event: E, context: &C) -> bool {
+        self.select(state, event, context).is_ok()
+    }
+
+    // 执行一次转换并返回新状态：依次调用旧状态的 on_exit、转换的动作、新状态的 on_enter。
+    // 失败时不调用任何钩子，上下文保持不变
+    pub fn fire(&self, state: S, event: E, context: &mut C) -> Result<S, WorkflowError> {
+        let transition = self.select(state, event, context)?;
+        let step = Step {
+            from: state,
+            event,
+            to: transition.to,
+        };
+        for (_, hook) in self.on_exit.iter().filter(|(s, _)| *s == step.from) {
+            hook(context, &step);
+        }
+        for action in &transition.actions {
+            action(context, &step);
+        }
+        for (_, hook) in self.on_enter.iter().filter(|(s, _)| *s == step.to) {
+            hook(context, &step);
+        }
+        Ok(step.to)
+    }
+
+    fn select(
+        &self,
+        state: S,
+        event: E,
+        context: &C,
+    ) -> Result<&Transition<S, E, C>, WorkflowError> {
+        let mut candidates = self
+            .transitions
+            .iter()
+            .filter(|t| t.from == state && t.event == event)
+            .peekable();
+        if candidates.peek().is_none() {
+            return Err(WorkflowError::NoTransition {
+                workflow: self.name.clone(),
+                state: format!("{:?}", state),
+                event: format!("{:?}", event),
+            });
+        }
+        let mut guards = Vec::new();
+        for t in candidates {
+            match &t.guard {
+                None => return Ok(t),
+                Some((_, guard)) if guard(context) => return Ok(t),
+                Some((label, _)) => guards.push(label.clone()),
+            }
+        }
+        Err(WorkflowError::Rejected {
+            workflow: self.name.clone(),
+            state: format!("{:?}", state),
+            event: format!("{:?}", event),
+            guards,
+        })
+    }
+
+    fn add_state(&mut self, state: S) {
+        if !self.states.contains(&state) {
+            self.states.push(state);
+        }
+    }
+
+    fn index_of(&self, state: S) -> usize {
+        self.states.iter().position(|&s| s == state).unwrap()
+    }
+}
+
+// ===========================================
+// 3. DOT 输出
+// ===========================================
+
+impl<S, E, C> Workflow<S, E, C>
+where
+    S: Copy + Eq + Debug,
+    E: Copy + Eq + Debug,
+{
+    // Graphviz 的 DOT 文本：初始状态前有一个起点，终态画双圈，守卫写在事件后面的方括号中。
+    // 输出只取决于声明的顺序，可以直接做快照测试
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph {} {{", quote(&self.name)).unwrap();
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=box, style=rounded];\n");
+        dot.push_str("    __start [shape=point];\n");
+        for &state in &self.states {
+            let name = quote(&format!("{:?}", state));
+            if self.is_final(state) {
+                writeln!(dot, "    {} [peripheries=2];", name).unwrap();
+            } else {
+                writeln!(dot, "    {};", name).unwrap();
+            }
+        }
+        writeln!(
+            dot,
+            "    __start -> {};",
+            quote(&format!("{:?}", self.initial))
+        )
+        .unwrap();
+        for t in &self.transitions {
+            let mut label = format!("{:?}", t.event);
+            if let Some((guard, _)) = &t.guard {
+                write!(label, " [{}]", guard).unwrap();
+            }
+            writeln!(
+                dot,
+                "    {} -> {} [label={}];",
+                quote(&format!("{:?}", t.from)),
+                quote(&format!("{:?}", t.to)),
+                quote(&label)
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<S: Debug, E: Debug, C> fmt::Debug for Workflow<S, E, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Workflow")
+            .field("name", &self.name)
+            .field("initial", &self.initial)
+            .field("states", &self.states)
+            .field("transitions", &self.transitions.len())
+            .finish()
+    }
+}
+
+// DOT 的带引号标识符，只需要转义反斜杠和双引号
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Door {
+        Closed,
+        Open,
+        Locked,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Action {
+        Open,
+        Close,
+        Lock,
+        Unlock,
+    }
+
+    #[derive(Default)]
+    struct Context {
+        has_key: bool,
+        log: Vec<String>,
+    }
+
+    fn door() -> Workflow<Door, Action, Context> {
+        Workflow::builder("门", Door::Closed)
+            .transition(Door::Closed, Action::Open, Door::Open)
+            .transition(Door::Open, Action::Close, Door::Closed)
+            .transition(Door::Closed, Action::Lock, Door::Locked)
+            .guard("有钥匙", |c: &Context| c.has_key)
+            .action(|c, _| c.log.push("上锁".to_string()))
+            .transition(Door::Locked, Action::Unlock, Door::Closed)
+            .guard("有钥匙", |c: &Context| c.has_key)
+            .on_exit(Door::Closed, |c, step| {
+                c.log.push(format!("离开 {:?}", step.from))
+            })
+            .on_enter(Door::Locked, |c, step| {
+                c.log.push(format!("进入 {:?}", step.to))
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fire_runs_guards_and_hooks_in_order() {
+        let door = door();
+        let mut context = Context::default();
+        assert_eq!(
+            door.fire(Door::Closed, Action::Open, &mut context),
+            Ok(Door::Open)
+        );
+        assert_eq!(context.log, ["离开 Closed"]);
+
+        // 守卫拒绝时不执行任何钩子
+        context.log.clear();
+        let err = door
+            .fire(Door::Closed, Action::Lock, &mut context)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "门: 状态 Closed 的事件 Lock 被守卫拒绝 (有钥匙)"
+        );
+        assert!(context.log.is_empty());
+
+        context.has_key = true;
+        assert!(door.can_fire(Door::Closed, Action::Lock, &context));
+        assert_eq!(
+            door.fire(Door::Closed, Action::Lock, &mut context),
+            Ok(Door::Locked)
+        );
+        assert_eq!(context.log, ["离开 Closed", "上锁", "进入 Locked"]);
+
+        let err = door
+            .fire(Door::Locked, Action::Open, &mut context)
+            .unwrap_err();
+        assert!(matches!(err, WorkflowError::NoTransition { .. }));
+        assert_eq!(door.events(Door::Closed), [Action::Open, Action::Lock]);
+        assert!(!door.is_final(Door::Locked));
+    }
+
+    #[test]
+    fn test_first_passing_guard_wins() {
+        let counter: Workflow<u8, char, u32> = Workflow::builder("计数", 0)
+            .transition(0, '+', 1)
+            .guard("小于 3", |n: &u32| *n < 3)
+            .action(|n, _| *n += 1)
+            .transition(0, '+', 2)
+            .build()
+            .unwrap();
+        let mut n = 0;
+        let states: Vec<_> = (0..4)
+            .map(|_| counter.fire(0, '+', &mut n).unwrap())
+            .collect();
+        assert_eq!(states, [1, 1, 1, 2]);
+        assert!(counter.is_final(1) && counter.is_final(2));
+    }
+
+    #[test]
+    fn test_build_validates_definition() {
+        let err = Workflow::<Door, Action>::builder("门", Door::Closed)
+            .state(Door::Locked)
+            .transition(Door::Closed, Action::Open, Door::Open)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            WorkflowError::Unreachable {
+                workflow: "门".to_string(),
+                state: "Locked".to_string(),
+            }
+        );
+
+        let err = Workflow::<Door, Action>::builder("门", Door::Closed)
+            .transition(Door::Closed, Action::Open, Door::Open)
+            .transition(Door::Closed, Action::Open, Door::Locked)
+            .guard("永远", |_| true)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, WorkflowError::Shadowed { .. }));
+    }
+
+    #[test]
+    fn test_dot_escapes_names() {
+        let workflow: Workflow<&str, &str> = Workflow::builder("say \"hi\"", "a")
+            .transition("a", "go", "b")
+            .build()
+            .unwrap();
+        assert_eq!(
+            workflow.to_dot(),
+            "digraph \"say \\\"hi\\\"\" {\n    rankdir=LR;\n    node [shape=box, style=rounded];\n    \
+             __start [shape=point];\n    \"\\\"a\\\"\";\n    \"\\\"b\\\"\" [peripheries=2];\n    \
+             __start -> \"\\\"a\\\"\";\n    \"\\\"a\\\"\" -> \"\\\"b\\\"\" [label=\"\\\"go\\\"\"];\n}\n"
+        );
+    }
+}
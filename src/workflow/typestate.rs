@@ -0,0 +1,236 @@
+// 编译期的状态机（typestate）
+// 每个状态是一个零大小的类型，Machine<S, C> 把状态放在类型参数里，只保存上下文 C。
+// 状态 S 能响应事件 E 当且仅当 S 实现了 Transition<E, C> 或 GuardedTransition<E, C>，
+// fire 按值接收 self，转换之后旧状态的 Machine 已经被移走
+
+use std::fmt;
+use std::marker::PhantomData;
+
+// ===========================================
+// 1. 状态与转换
+// ===========================================
+
+pub trait State {
+    const NAME: &'static str;
+}
+
+// 只有初始状态可以用 Machine::new 创建
+pub trait Initial: State {}
+
+// 无条件的转换；action 在状态改变时修改上下文，默认什么也不做
+#[diagnostic::on_unimplemented(
+    message = "状态 `{Self}` 没有事件 `{E}` 的转换",
+    label = "这个状态不能 fire 该事件",
+    note = "合法的转换由 Transition / GuardedTransition 的实现决定"
+)]
+pub trait Transition<E, C = ()>: State {
+    type Next: State;
+
+    fn action(_context: &mut C, _event: E) {}
+}
+
+// 带守卫的转换，只能用 try_fire 触发；守卫拒绝时返回原来的状态机和原因
+#[diagnostic::on_unimplemented(
+    message = "状态 `{Self}` 没有事件 `{E}` 的带守卫转换",
+    label = "这个状态不能 try_fire 该事件"
+)]
+pub trait GuardedTransition<E, C = ()>: State {
+    type Next: State;
+
+    fn guard(context: &C, event: &E) -> Result<(), String>;
+
+    fn action(_context: &mut C, _event: E) {}
+}
+
+// ===========================================
+// 2. Machine
+// ===========================================
+
+// try_fire 的结果：成功时进入新状态，被拒绝时退回原来的状态机
+type TryFire<S, E, C> = Result<Machine<<S as GuardedTransition<E, C>>::Next, C>, Rejected<S, C>>;
+
+pub struct Machine<S, C = ()> {
+    context: C,
+    state: PhantomData<S>,
+}
+
+impl<S: Initial, C> Machine<S, C> {
+    pub fn new(context: C) -> Self {
+        Machine {
+            context,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<S: State, C> Machine<S, C> {
+    pub fn state(&self) -> &'static str {
+        S::NAME
+    }
+
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    // 修改上下文不改变状态；需要限制在某些状态下修改时，为 Machine<那个状态, C> 单独实现方法
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    pub fn into_context(self) -> C {
+        self.context
+    }
+
+    pub fn fire<E>(mut self, event: E) -> Machine<S::Next, C>
+    where
+        S: Transition<E, C>,
+    {
+        <S as Transition<E, C>>::action(&mut self.context, event);
+        Machine {
+            context: self.context,
+            state: PhantomData,
+        }
+    }
+
+    pub fn try_fire<E>(mut self, event: E) -> TryFire<S, E, C>
+    where
+        S: GuardedTransition<E, C>,
+    {
+        if let Err(reason) = S::guard(&self.context, &event) {
+            return Err(Rejected {
+                machine: self,
+                reason,
+            });
+        }
+        <S as GuardedTransition<E, C>>::action(&mut self.context, event);
+        Ok(Machine {
+            context: self.context,
+            state: PhantomData,
+        })
+    }
+}
+
+impl<S: State, C: fmt::Debug> fmt::Debug for Machine<S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Machine")
+            .field("state", &S::NAME)
+            .field("context", &self.context)
+            .finish()
+    }
+}
+
+// 守卫拒绝了转换，状态机仍处于原来的状态
+pub struct Rejected<S, C> {
+    pub machine: Machine<S, C>,
+    pub reason: String,
+}
+
+impl<S: State, C: fmt::Debug> fmt::Debug for Rejected<S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rejected")
+            .field("machine", &self.machine)
+            .field("reason", &self.reason)
+            .finish()
+    }
+}
+
+impl<S: State, C> fmt::Display for Rejected<S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "状态 {} 拒绝了转换: {}", S::NAME, self.reason)
+    }
+}
+
+// ===========================================
+// 3. 声明宏
+// ===========================================
+
+// 声明状态类型和无条件的转换，第一个状态是初始状态：
+//
+// typestate! {
+//     pub states: Draft, PendingReview, Published;
+//     Draft + RequestReview => PendingReview;
+//     PendingReview + Approve => Published;
+// }
+//
+// 事件类型需要另外定义。生成的转换对任意上下文 C 都成立，没有动作；
+// 需要动作或守卫的转换手写 Transition / GuardedTransition 的实现
+#[macro_export]
+macro_rules! typestate {
+    (
+        $vis:vis states: $initial:ident $(, $state:ident)* $(,)?;
+        $($from:ident + $event:ty => $to:ident;)*
+    ) => {
+        $crate::typestate!(@state $vis $initial);
+        impl $crate::workflow::Initial for $initial {}
+        $($crate::typestate!(@state $vis $state);)*
+        $(
+            impl<C> $crate::workflow::Transition<$event, C> for $from {
+                type Next = $to;
+            }
+        )*
+    };
+    (@state $vis:vis $name:ident) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        $vis struct $name;
+
+        impl $crate::workflow::State for $name {
+            const NAME: &'static str = stringify!($name);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Insert(u32);
+    struct Eject;
+    struct Push;
+
+    crate::typestate! {
+        states: Locked, Unlocked;
+        Unlocked + Push => Locked;
+    }
+
+    // 投币解锁，面值不够时拒绝；退币只在解锁时可以
+    impl GuardedTransition<Insert, u32> for Locked {
+        type Next = Unlocked;
+
+        fn guard(_paid: &u32, coin: &Insert) -> Result<(), String> {
+            match coin.0 {
+                50.. => Ok(()),
+                value => Err(format!("{} 分不够", value)),
+            }
+        }
+
+        fn action(paid: &mut u32, coin: Insert) {
+            *paid += coin.0;
+        }
+    }
+
+    impl Transition<Eject, u32> for Unlocked {
+        type Next = Locked;
+
+        fn action(paid: &mut u32, _event: Eject) {
+            *paid -= 50;
+        }
+    }
+
+    #[test]
+    fn test_typestate_transitions() {
+        let turnstile: Machine<Locked, u32> = Machine::new(0);
+        let rejected = turnstile.try_fire(Insert(20)).unwrap_err();
+        assert_eq!(rejected.to_string(), "状态 Locked 拒绝了转换: 20 分不够");
+
+        let unlocked = rejected.machine.try_fire(Insert(50)).unwrap();
+        assert_eq!((unlocked.state(), *unlocked.context()), ("Unlocked", 50));
+        let locked = unlocked.fire(Push);
+        assert_eq!(locked.state(), "Locked");
+
+        let refunded = locked.try_fire(Insert(100)).unwrap().fire(Eject);
+        assert_eq!(
+            format!("{:?}", refunded),
+            "Machine { state: \"Locked\", context: 100 }"
+        );
+    }
+}
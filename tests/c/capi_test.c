@@ -0,0 +1,112 @@
+/* 从 C 调用 rust_code_guide 导出的接口，由 tests/capi.rs 编译并运行
+ * 任何检查失败都会打印位置并以非零状态退出 */
+#include "guide.h"
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+                    #cond);                                                \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+/* 取出并释放最后错误信息，返回是否包含 expected */
+static int last_error_contains(const char *expected) {
+    char *message = guide_last_error_message();
+    int found = message != NULL && strstr(message, expected) != NULL;
+    if (message != NULL) {
+        printf("  last error: %s\n", message);
+    }
+    guide_string_free(message);
+    return found;
+}
+
+static void test_text_analyzer(void) {
+    GuideTextAnalyzer *analyzer = NULL;
+    CHECK(guide_text_analyzer_new("Rust and C. Rust again!\nC again", &analyzer) ==
+          GUIDE_RESULT_OK);
+
+    GuideTextStats stats;
+    CHECK(guide_text_analyzer_stats(analyzer, &stats) == GUIDE_RESULT_OK);
+    CHECK(stats.words == 7);
+    CHECK(stats.lines == 2);
+    CHECK(stats.unique_words == 4);
+    printf("text: %zu words, %zu chars, %zu lines\n", stats.words, stats.characters,
+           stats.lines);
+
+    char *word = NULL;
+    size_t count = 0;
+    CHECK(guide_text_analyzer_most_common_word(analyzer, &word, &count) == GUIDE_RESULT_OK);
+    /* "again"、"c"、"rust" 都出现两次，取字典序最小的 */
+    CHECK(word != NULL && strcmp(word, "again") == 0 && count == 2);
+    guide_string_free(word);
+    guide_text_analyzer_free(analyzer);
+
+    /* 错误路径：空指针 */
+    CHECK(guide_text_analyzer_new(NULL, &analyzer) == GUIDE_RESULT_NULL_POINTER);
+    CHECK(last_error_contains("text"));
+}
+
+static void test_matrix(void) {
+    const double a_data[] = {1, 2, 3, 4};
+    const double b_data[] = {5, 6, 7, 8};
+    GuideMatrix *a = NULL, *b = NULL, *product = NULL, *t = NULL;
+    CHECK(guide_matrix_from_data(2, 2, a_data, &a) == GUIDE_RESULT_OK);
+    CHECK(guide_matrix_from_data(2, 2, b_data, &b) == GUIDE_RESULT_OK);
+    CHECK(guide_matrix_multiply(a, b, &product) == GUIDE_RESULT_OK);
+
+    double out[4];
+    CHECK(guide_matrix_copy_data(product, out, 4) == GUIDE_RESULT_OK);
+    CHECK(out[0] == 19 && out[1] == 22 && out[2] == 43 && out[3] == 50);
+
+    char *text = guide_matrix_to_string(product);
+    printf("product:\n%s\n", text);
+    guide_string_free(text);
+
+    /* 转置后维度变化，相加失败 */
+    GuideMatrix *wide = NULL, *sum = NULL;
+    CHECK(guide_matrix_new(1, 3, &wide) == GUIDE_RESULT_OK);
+    CHECK(guide_matrix_transpose(wide, &t) == GUIDE_RESULT_OK);
+    CHECK(guide_matrix_rows(t) == 3 && guide_matrix_cols(t) == 1);
+    CHECK(guide_matrix_add(a, t, &sum) == GUIDE_RESULT_DIMENSION_MISMATCH);
+    CHECK(sum == NULL);
+    CHECK(last_error_contains("2x2"));
+
+    double value = 0;
+    CHECK(guide_matrix_set(a, 1, 1, 9.5) == GUIDE_RESULT_OK);
+    CHECK(guide_matrix_get(a, 1, 1, &value) == GUIDE_RESULT_OK && value == 9.5);
+    CHECK(guide_matrix_get(a, 5, 0, &value) == GUIDE_RESULT_OUT_OF_BOUNDS);
+    CHECK(guide_matrix_copy_data(product, out, 3) == GUIDE_RESULT_BUFFER_TOO_SMALL);
+
+    /* 元素个数溢出 size_t 与下标越界是不同的错误 */
+    GuideMatrix *huge = NULL;
+    CHECK(guide_matrix_new(SIZE_MAX, 2, &huge) == GUIDE_RESULT_TOO_LARGE);
+    CHECK(huge == NULL);
+
+    guide_matrix_free(a);
+    guide_matrix_free(b);
+    guide_matrix_free(product);
+    guide_matrix_free(wide);
+    guide_matrix_free(t);
+    guide_matrix_free(NULL);
+}
+
+int main(void) {
+    printf("rust_code_guide %s\n", guide_version());
+    test_text_analyzer();
+    test_matrix();
+    if (failures != 0) {
+        fprintf(stderr, "%d check(s) failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    printf("all C API checks passed\n");
+    return EXIT_SUCCESS;
+}
@@ -0,0 +1,70 @@
+// 第 18 章 GUI 组件的渲染结果
+// 每个用例的像素以文本 PPM（P3）与 tests/data/canvas/<用例名>.ppm 逐行比较；
+// 不一致时先在临时目录写出标红差异的 <用例名>.diff.ppm，方便用图片查看器对比
+// 有意修改组件外观后，用 UPDATE_GOLDEN=1 cargo test --test canvas 重新生成期望图像
+
+mod common;
+
+use common::golden::check_golden;
+use rust_code_guide::canvas::{self, Canvas, PixelDiff};
+use rust_code_guide::image::{ImageBuffer, Rgb};
+use rust_code_guide::mod_18_oop_features::{
+    Draw, DrawableButton, DrawableTextField, SCREEN_BACKGROUND, Screen,
+};
+use std::path::PathBuf;
+
+const DIR: &str = "tests/data/canvas";
+
+fn component(draw: &dyn Draw) -> Canvas {
+    let (width, height) = draw.size();
+    let mut canvas = Canvas::new(width, height, SCREEN_BACKGROUND);
+    draw.draw(&mut canvas);
+    canvas
+}
+
+fn write_diff_images(cases: &[(&str, Canvas)]) {
+    for (name, canvas) in cases {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(DIR)
+            .join(format!("{}.ppm", name));
+        let Ok(expected) = ImageBuffer::<Rgb>::open_pnm(&path) else {
+            continue;
+        };
+        let result = canvas::diff(&expected, canvas.image());
+        if result != PixelDiff::Identical {
+            let diff_path = std::env::temp_dir().join(format!("{}.diff.ppm", name));
+            canvas::diff_image(&expected, canvas.image())
+                .save_pnm(&diff_path)
+                .unwrap();
+            eprintln!("{}: {}，差异图: {}", name, result, diff_path.display());
+        }
+    }
+}
+
+#[test]
+fn components_match_golden_images() {
+    let mut screen = Screen::new();
+    screen.add_component(Box::new(DrawableButton::new(100, 40, "Submit")));
+    screen.add_component(Box::new(DrawableTextField::new(200, 30, "输入内容")));
+    // 占位文字比输入框长，超出的部分不能画到边框和屏幕背景上
+    screen.add_component(Box::new(DrawableTextField::new(
+        60,
+        16,
+        "a placeholder that does not fit",
+    )));
+
+    let cases = [
+        ("button", component(&DrawableButton::new(60, 20, "Submit"))),
+        (
+            "text_field",
+            component(&DrawableTextField::new(80, 16, "Search...")),
+        ),
+        ("screen", screen.run()),
+    ];
+    write_diff_images(&cases);
+    check_golden(
+        DIR,
+        "ppm",
+        cases.map(|(name, canvas)| (name, canvas.image().to_pnm_ascii())),
+    );
+}
@@ -0,0 +1,303 @@
+// src/capi.rs 导出的 C ABI 的集成测试
+// 1. 根据 src/capi.rs 生成 include/guide.h，并与仓库中的版本逐字比较
+//    修改导出函数后用 UPDATE_GOLDEN=1 cargo test --test capi 重新生成
+// 2. 用本机的 C 编译器编译 tests/c/capi_test.c，链接 cargo 构建出的 cdylib 并运行
+//
+// 头文件生成器只认识 capi.rs 中用到的写法：
+// - 名称以 Guide 开头的类型才会导出：带 #[repr(C)] 的 enum / struct 生成完整定义，其余 struct 生成不透明类型
+// - 带 #[unsafe(no_mangle)] 的 pub (unsafe) extern "C" fn 生成函数声明
+// - 紧挨在条目前的 /// 文档注释（"# Safety" 之前的部分）转成 C 注释
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn c_type(rust: &str) -> String {
+    let rust = rust.trim();
+    if let Some(inner) = rust.strip_prefix("*const ") {
+        return format!("const {} *", c_type(inner)).replace("* *", "**");
+    }
+    if let Some(inner) = rust.strip_prefix("*mut ") {
+        return format!("{} *", c_type(inner)).replace("* *", "**");
+    }
+    match rust {
+        "c_char" => "char",
+        "c_int" => "int",
+        "usize" => "size_t",
+        "f64" => "double",
+        "bool" => "bool",
+        other if other.starts_with("Guide") => other,
+        other => panic!("头文件生成器不支持类型 {}", other),
+    }
+    .to_string()
+}
+
+// 声明与类型之间的空格：指针类型写成 "char *name"
+fn c_decl(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{}{}", ty, name)
+    } else {
+        format!("{} {}", ty, name)
+    }
+}
+
+// CamelCase -> SCREAMING_SNAKE_CASE
+fn screaming(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+// 条目主体：从 start 开始到配对的右括号为止
+fn body_until(lines: &[&str], start: usize, close: &str) -> (String, usize) {
+    let mut text = String::new();
+    let mut i = start;
+    loop {
+        text.push_str(lines[i].trim());
+        text.push(' ');
+        if lines[i].trim_end().ends_with(close) || lines[i].trim() == close {
+            return (text, i);
+        }
+        i += 1;
+    }
+}
+
+fn generate_header(source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut types = Vec::new();
+    let mut functions = Vec::new();
+    let mut docs: Vec<String> = Vec::new();
+    let mut in_safety = false;
+    let mut repr_c = false;
+    let mut no_mangle = false;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if line.starts_with("#[cfg(test)]") {
+            break;
+        }
+        if let Some(doc) = line.strip_prefix("///") {
+            let doc = doc.trim();
+            in_safety |= doc.starts_with("# Safety");
+            if !in_safety && !doc.is_empty() {
+                docs.push(doc.to_string());
+            }
+            i += 1;
+            continue;
+        }
+        repr_c |= line == "#[repr(C)]";
+        no_mangle |= line == "#[unsafe(no_mangle)]";
+        if line.starts_with("#[") {
+            i += 1;
+            continue;
+        }
+
+        // 多行文档注释合并成一个 C 注释块
+        let comment = match std::mem::take(&mut docs).as_slice() {
+            [] => String::new(),
+            lines => format!("/* {} */\n", lines.join("\n * ")),
+        };
+
+        if let Some(rest) = line
+            .strip_prefix("pub enum ")
+            .filter(|rest| repr_c && rest.starts_with("Guide"))
+        {
+            let name = rest.trim_end_matches(" {");
+            let (body, end) = body_until(&lines, i + 1, "}");
+            let prefix = screaming(name);
+            let variants: Vec<String> = body
+                .trim_end_matches("} ")
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(|v| {
+                    let (variant, value) = v.split_once(" = ").expect("枚举成员需要显式判别值");
+                    format!("    {}_{} = {},", prefix, screaming(variant), value)
+                })
+                .collect();
+            types.push(format!(
+                "{}typedef enum {{\n{}\n}} {};\n",
+                comment,
+                variants.join("\n"),
+                name
+            ));
+            i = end;
+        } else if let Some(rest) = line
+            .strip_prefix("pub struct ")
+            .filter(|rest| rest.starts_with("Guide"))
+        {
+            let name = rest.trim_end_matches(" {").trim_end_matches(';');
+            let (body, end) = body_until(&lines, i + 1, "}");
+            if repr_c {
+                let fields: Vec<String> = body
+                    .trim_end_matches("} ")
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|f| !f.is_empty())
+                    .map(|f| {
+                        let (field, ty) = f.trim_start_matches("pub ").split_once(": ").unwrap();
+                        format!("    {};", c_decl(&c_type(ty), field))
+                    })
+                    .collect();
+                types.push(format!(
+                    "{}typedef struct {{\n{}\n}} {};\n",
+                    comment,
+                    fields.join("\n"),
+                    name
+                ));
+            } else {
+                types.push(format!("{}typedef struct {} {};\n", comment, name, name));
+            }
+            i = end;
+        } else if line.starts_with("pub extern \"C\" fn ")
+            || line.starts_with("pub unsafe extern \"C\" fn ")
+        {
+            assert!(no_mangle, "{} 缺少 #[unsafe(no_mangle)]", line);
+            let (signature, end) = body_until(&lines, i, "{");
+            let signature = signature.replace("( ", "(").replace(", )", ")");
+            let after_fn = &signature[signature.find(" fn ").unwrap() + 4..];
+            let (name, rest) = after_fn.split_once('(').unwrap();
+            let (params, ret) = rest.rsplit_once(')').unwrap();
+            let ret = ret.trim().trim_end_matches('{').trim();
+            let ret = match ret.strip_prefix("->") {
+                Some(ty) => c_type(ty),
+                None => "void".to_string(),
+            };
+            let params: Vec<String> = params
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    let (param, ty) = p.split_once(": ").unwrap();
+                    c_decl(&c_type(ty), param)
+                })
+                .collect();
+            let params = if params.is_empty() {
+                "void".to_string()
+            } else {
+                params.join(", ")
+            };
+            functions.push(format!(
+                "{}{};\n",
+                comment,
+                c_decl(&ret, &format!("{}({})", name, params))
+            ));
+            i = end;
+        }
+
+        repr_c = false;
+        no_mangle = false;
+        in_safety = false;
+        i += 1;
+    }
+
+    format!(
+        "/* 由 tests/capi.rs 根据 src/capi.rs 生成，不要手动修改\n \
+         * 重新生成：UPDATE_GOLDEN=1 cargo test --test capi */\n\
+         #ifndef GUIDE_H\n#define GUIDE_H\n\n\
+         #include <stdbool.h>\n#include <stddef.h>\n\n\
+         #ifdef __cplusplus\nextern \"C\" {{\n#endif\n\n\
+         {}\n{}\n\
+         #ifdef __cplusplus\n}}\n#endif\n\n#endif\n",
+        types.join("\n"),
+        functions.join("\n")
+    )
+}
+
+#[test]
+fn header_matches_exports() {
+    let source = fs::read_to_string(manifest_dir().join("src/capi.rs")).unwrap();
+    let header = generate_header(&source);
+    let path = manifest_dir().join("include/guide.h");
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &header).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("缺少头文件 {}: {}", path.display(), e));
+    assert!(
+        expected == header,
+        "include/guide.h 与 src/capi.rs 不一致，请用 UPDATE_GOLDEN=1 重新生成\n实际生成:\n{}",
+        header
+    );
+}
+
+// cargo 把 cdylib 放在集成测试可执行文件所在的 deps 目录，cargo build 之后还会复制到上一级
+fn find_cdylib() -> Option<PathBuf> {
+    let file = format!(
+        "{}rust_code_guide{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    let exe = std::env::current_exe().ok()?;
+    let deps = exe.parent()?;
+    [deps, deps.parent()?]
+        .into_iter()
+        .map(|dir| dir.join(&file))
+        .find(|path| path.exists())
+}
+
+fn compile_c(compiler: &str, source: &Path, lib: &Path, output: &Path) -> std::io::Result<bool> {
+    let lib_dir = lib.parent().unwrap();
+    let status = Command::new(compiler)
+        .arg("-std=c11")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg(source)
+        .arg("-I")
+        .arg(manifest_dir().join("include"))
+        .arg("-o")
+        .arg(output)
+        .arg(lib)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .status()?;
+    Ok(status.success())
+}
+
+#[cfg(unix)]
+#[test]
+fn c_program_uses_exported_api() {
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let Some(lib) = find_cdylib() else {
+        panic!("找不到 cdylib，请确认 Cargo.toml 的 crate-type 包含 cdylib");
+    };
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let exe = out_dir.join("capi_test");
+
+    match compile_c(
+        &compiler,
+        &manifest_dir().join("tests/c/capi_test.c"),
+        &lib,
+        &exe,
+    ) {
+        Ok(true) => {}
+        Ok(false) => panic!("C 测试程序编译失败"),
+        // 没有 C 编译器的环境只能跳过；build.rs 同样需要 C 编译器，所以一般不会走到这里
+        Err(e) => {
+            eprintln!("跳过：无法运行 C 编译器 {}: {}", compiler, e);
+            return;
+        }
+    }
+
+    let output = Command::new(&exe).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "C 测试程序失败:\n{}\n{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("all C API checks passed"), "{}", stdout);
+}
@@ -0,0 +1,33 @@
+// golden 文件比较
+// 每个用例的输出与 <dir>/<用例名>.<ext> 逐字比较，dir 相对于 crate 根目录；
+// 设置 UPDATE_GOLDEN=1 时改为把输出写入期望文件
+
+use std::fs;
+use std::path::PathBuf;
+
+pub fn check_golden<N, I>(dir: &str, ext: &str, cases: I)
+where
+    N: AsRef<str>,
+    I: IntoIterator<Item = (N, String)>,
+{
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(dir);
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+
+    for (name, actual) in cases {
+        let name = name.as_ref();
+        let path = dir.join(format!("{}.{}", name, ext));
+        if update {
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(&path, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("缺少期望文件 {}: {}", path.display(), e));
+        if expected != actual {
+            mismatches.push(format!("{}:\n期望:\n{}实际:\n{}", name, expected, actual));
+        }
+    }
+
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
@@ -0,0 +1,3 @@
+// 集成测试共用的辅助函数；各测试文件用 mod common; 引入
+
+pub mod golden;
@@ -0,0 +1,3 @@
+DELETE FROM sessions WHERE expires_at < ?
+-- params: [1700000000]
+-- inline: DELETE FROM sessions WHERE expires_at < 1700000000
@@ -0,0 +1 @@
+-- error: SELECT 查询缺少 FROM 子句
@@ -0,0 +1 @@
+-- error: 第 1 行有 1 个值，但指定了 2 列
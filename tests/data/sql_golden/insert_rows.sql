@@ -0,0 +1,3 @@
+INSERT INTO users (name, age, score) VALUES (?, ?, ?), (?, ?, ?)
+-- params: ['O''Brien', 30, 9.5, 'Eve', NULL, 7.0]
+-- inline: INSERT INTO users (name, age, score) VALUES ('O''Brien', 30, 9.5), ('Eve', NULL, 7.0)
@@ -0,0 +1,3 @@
+SELECT * FROM users
+-- params: []
+-- inline: SELECT * FROM users
@@ -0,0 +1,3 @@
+SELECT id FROM users WHERE name = ?
+-- params: ['x''; DROP TABLE users; --']
+-- inline: SELECT id FROM users WHERE name = 'x''; DROP TABLE users; --'
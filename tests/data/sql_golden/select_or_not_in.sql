@@ -0,0 +1,3 @@
+SELECT id FROM orders WHERE (status = ? OR status = ?) AND NOT (customer_id IN (?, ?, ?)) AND deleted_at IS NULL ORDER BY created_at DESC
+-- params: ['paid', 'shipped', 3, 5, 8]
+-- inline: SELECT id FROM orders WHERE (status = 'paid' OR status = 'shipped') AND NOT (customer_id IN (3, 5, 8)) AND deleted_at IS NULL ORDER BY created_at DESC
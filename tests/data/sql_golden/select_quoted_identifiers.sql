@@ -0,0 +1,3 @@
+SELECT "order", "first name" FROM "group" WHERE "select" IS NOT NULL
+-- params: []
+-- inline: SELECT "order", "first name" FROM "group" WHERE "select" IS NOT NULL
@@ -0,0 +1,3 @@
+SELECT id, name FROM users WHERE age >= ? AND name LIKE ? ORDER BY name DESC LIMIT 10 OFFSET 20
+-- params: [18, 'A%']
+-- inline: SELECT id, name FROM users WHERE age >= 18 AND name LIKE 'A%' ORDER BY name DESC LIMIT 10 OFFSET 20
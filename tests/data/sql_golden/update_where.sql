@@ -0,0 +1,3 @@
+UPDATE users SET age = ?, nickname = ? WHERE id = ? AND deleted_at IS NULL
+-- params: [31, NULL, 1]
+-- inline: UPDATE users SET age = 31, nickname = NULL WHERE id = 1 AND deleted_at IS NULL
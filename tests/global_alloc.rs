@@ -0,0 +1,27 @@
+// 把 CountingAlloc 安装为整个测试程序的全局分配器
+// 测试框架本身也会分配内存，所以只检查增量的下限，并且这个文件只放一个测试，避免其他测试线程干扰
+
+use rust_code_guide::alloc::CountingAlloc;
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc::system();
+
+#[test]
+fn counts_heap_allocations_of_std_types() {
+    let before = GLOBAL.stats();
+    let mut numbers: Vec<u64> = Vec::with_capacity(1000);
+    let boxed = Box::new([0u8; 4096]);
+    let during = GLOBAL.stats().since(&before);
+    assert!(during.allocations >= 2);
+    assert!(during.current_bytes >= 8000 + 4096);
+
+    // 超出容量时 Vec 通过 realloc 扩容
+    numbers.extend(0..2000);
+    assert!(GLOBAL.stats().since(&before).reallocations >= 1);
+
+    drop(numbers);
+    drop(boxed);
+    let after = GLOBAL.stats().since(&before);
+    assert!(after.deallocations >= 2);
+    assert!(GLOBAL.stats().peak_bytes >= 16000 + 4096);
+}
@@ -0,0 +1,50 @@
+// JSONTestSuite（https://github.com/nst/JSONTestSuite）的子集
+// y_ 开头的用例必须解析成功，n_ 开头的必须报错
+// 原始套件中包含非法 UTF-8 字节的用例没有收录：json::parse 接受 &str，这类输入在读取阶段就会被拒绝
+
+use rust_code_guide::json;
+use std::fs;
+use std::path::Path;
+
+fn cases() -> Vec<(String, String)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/json_test_suite");
+    let mut cases: Vec<(String, String)> = fs::read_dir(&dir)
+        .expect("读取测试用例目录失败")
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            (name, fs::read_to_string(&path).unwrap())
+        })
+        .collect();
+    cases.sort();
+    cases
+}
+
+#[test]
+fn accepts_valid_documents() {
+    let mut count = 0;
+    for (name, text) in cases().iter().filter(|(name, _)| name.starts_with("y_")) {
+        let value = json::parse(text).unwrap_or_else(|e| panic!("{} 应当解析成功: {}", name, e));
+        // 序列化后再解析应得到相同的值
+        assert_eq!(
+            json::parse(&value.to_string()).as_ref(),
+            Ok(&value),
+            "{}",
+            name
+        );
+        count += 1;
+    }
+    assert!(count > 50);
+}
+
+#[test]
+fn rejects_invalid_documents() {
+    let mut count = 0;
+    for (name, text) in cases().iter().filter(|(name, _)| name.starts_with("n_")) {
+        if let Ok(value) = json::parse(text) {
+            panic!("{} 应当解析失败，实际得到 {}", name, value);
+        }
+        count += 1;
+    }
+    assert!(count > 50);
+}
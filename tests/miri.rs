@@ -0,0 +1,321 @@
+// 可以在 Miri 下运行的测试
+// Miri 逐条解释执行 MIR，能发现越界访问、未初始化读取、违反别名规则和内存泄漏，
+// 但不支持 FFI 和大部分系统调用，所以这里只放纯 Rust 的 unsafe 代码（src/alloc.rs、src/fixed/）的测试
+//
+// 运行：cargo +nightly miri test --features miri --test miri
+// 普通的 cargo test --features miri 也会运行这些测试
+#![cfg(feature = "miri")]
+
+use rust_code_guide::alloc::{Arena, CountingAlloc, FreeList, Pool};
+use rust_code_guide::fixed::{ArrayString, ArrayVec, RingBuffer};
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr::NonNull;
+
+// Miri 下执行很慢，用一个简单的 xorshift 代替 rand 生成操作序列
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> usize {
+        (self.next() % n) as usize
+    }
+}
+
+#[test]
+fn arena_mixed_types_keep_alignment_and_values() {
+    let arena = Arena::with_capacity(32);
+    let mut refs = Vec::new();
+    for i in 0..40u8 {
+        let byte = arena.alloc(i);
+        let word = arena.alloc(i as u64 * 1000);
+        let wide = arena.alloc(i as u128 * 7);
+        let triple = arena.alloc([i; 3]);
+        assert_eq!((wide as *const u128).addr() % align_of::<u128>(), 0);
+        refs.push((byte, word, wide, triple));
+    }
+    // 扩容产生的新块不会影响早先返回的引用
+    assert!(arena.chunk_count() > 1);
+    for (i, (byte, word, wide, triple)) in refs.iter().enumerate() {
+        let i = i as u8;
+        assert_eq!(
+            (**byte, **word, **wide, **triple),
+            (i, i as u64 * 1000, i as u128 * 7, [i; 3])
+        );
+    }
+    *refs[3].1 += 1;
+    assert_eq!(*refs[3].1, 3001);
+
+    let text = arena.alloc_str("不复制到堆上");
+    let numbers = arena.alloc_slice_copy(&[1u16, 2, 3]);
+    numbers[1] = 20;
+    assert_eq!((text, &*numbers), ("不复制到堆上", &[1, 20, 3][..]));
+
+    let mut arena = arena;
+    arena.reset();
+    assert_eq!(*arena.alloc(5u32), 5);
+}
+
+#[test]
+fn free_list_random_workload_never_overlaps() {
+    let mut heap = FreeList::new(2048);
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let mut live: Vec<(NonNull<u8>, usize, u8)> = Vec::new();
+
+    for step in 0..300 {
+        if live.is_empty() || rng.below(3) != 0 {
+            let size = 1 + rng.below(96);
+            let align = 1 << rng.below(7);
+            let layout = Layout::from_size_align(size, align).unwrap();
+            if let Some(ptr) = heap.allocate(layout) {
+                assert_eq!(ptr.as_ptr().addr() % align, 0);
+                // 写满整个分配，释放前检查：分配之间有重叠就会被其他写入破坏
+                let tag = step as u8;
+                unsafe { ptr.as_ptr().write_bytes(tag, size) };
+                live.push((ptr, size, tag));
+            }
+        } else {
+            let (ptr, size, tag) = live.swap_remove(rng.below(live.len() as u64));
+            let bytes = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), size) };
+            assert!(bytes.iter().all(|&b| b == tag), "分配被覆盖");
+            heap.deallocate(ptr);
+        }
+        let stats = heap.stats();
+        assert_eq!(stats.live_allocations, live.len());
+        assert!(stats.free_bytes <= stats.capacity);
+    }
+
+    for (ptr, _, _) in live {
+        heap.deallocate(ptr);
+    }
+    // 全部释放后所有空闲块合并回一整块
+    let stats = heap.stats();
+    assert_eq!((stats.free_blocks, stats.free_bytes), (1, 2048));
+}
+
+#[test]
+fn pool_handles_survive_growth_and_drop_exactly_once() {
+    struct Tracked<'a> {
+        name: String,
+        drops: &'a Cell<usize>,
+    }
+
+    impl Drop for Tracked<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    let drops = Cell::new(0);
+    let pool = Pool::with_segment_len(4);
+    let mut rng = XorShift(42);
+    let mut handles = Vec::new();
+    let mut created = 0;
+
+    for round in 0..60 {
+        if handles.is_empty() || rng.below(4) != 0 {
+            handles.push(pool.alloc(Tracked {
+                name: format!("对象 {}", round),
+                drops: &drops,
+            }));
+            created += 1;
+        } else {
+            let index = rng.below(handles.len() as u64);
+            if rng.below(2) == 0 {
+                drop(handles.swap_remove(index));
+            } else {
+                let value = handles.swap_remove(index).into_inner();
+                assert!(value.name.starts_with("对象"));
+            }
+        }
+    }
+    assert_eq!(pool.len(), handles.len());
+    assert!(pool.capacity() >= handles.len());
+    for handle in handles.iter_mut() {
+        handle.name.push('!');
+    }
+    assert!(handles.iter().all(|h| h.name.ends_with('!')));
+
+    drop(handles);
+    assert_eq!(drops.get(), created);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn counting_alloc_balances() {
+    let counter = CountingAlloc::system();
+    let mut blocks = Vec::new();
+    unsafe {
+        for size in [8, 64, 24, 512] {
+            let layout = Layout::from_size_align(size, 8).unwrap();
+            let ptr = counter.alloc_zeroed(layout);
+            assert!(
+                std::slice::from_raw_parts(ptr, size)
+                    .iter()
+                    .all(|&b| b == 0)
+            );
+            blocks.push((ptr, layout));
+        }
+        let (ptr, layout) = blocks[1];
+        let grown = counter.realloc(ptr, layout, 128);
+        blocks[1] = (grown, Layout::from_size_align(128, 8).unwrap());
+        assert_eq!(counter.stats().current_bytes, 8 + 128 + 24 + 512);
+
+        for (ptr, layout) in blocks {
+            counter.dealloc(ptr, layout);
+        }
+    }
+    let stats = counter.stats();
+    assert_eq!(
+        (stats.allocations, stats.deallocations, stats.reallocations),
+        (4, 4, 1)
+    );
+    assert_eq!((stats.current_bytes, stats.peak_bytes), (0, 672));
+}
+
+// 析构时计数
+struct Counted<'a> {
+    id: usize,
+    drops: &'a Cell<usize>,
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+#[test]
+fn array_vec_matches_vec_model() {
+    let mut rng = XorShift(7);
+    let mut vec: ArrayVec<String, 8> = ArrayVec::new();
+    let mut model: Vec<String> = Vec::new();
+
+    for step in 0..400 {
+        match rng.below(7) {
+            0 | 1 => {
+                let value = format!("v{}", step);
+                let pushed = vec.try_push(value.clone());
+                assert_eq!(pushed.is_ok(), model.len() < 8);
+                if pushed.is_ok() {
+                    model.push(value);
+                }
+            }
+            2 => assert_eq!(vec.pop(), model.pop()),
+            3 if !model.is_empty() => {
+                let index = rng.below(model.len() as u64);
+                assert_eq!(vec.remove(index), model.remove(index));
+            }
+            4 if model.len() < 8 => {
+                let index = rng.below(model.len() as u64 + 1);
+                vec.insert(index, format!("i{}", step));
+                model.insert(index, format!("i{}", step));
+            }
+            5 => {
+                let start = rng.below(model.len() as u64 + 1);
+                let end = start + rng.below((model.len() - start) as u64 + 1);
+                // 只取出一部分就 drop，检查剩余元素的析构和尾部的搬移
+                let taken = rng.below(end as u64 - start as u64 + 1);
+                let drained: Vec<String> = vec.drain(start..end).take(taken).collect();
+                let expected: Vec<String> = model.drain(start..end).take(taken).collect();
+                assert_eq!(drained, expected);
+            }
+            _ => {
+                let modulus = 2 + rng.below(3);
+                vec.retain(|s| s.len() % modulus != 0);
+                model.retain(|s| s.len() % modulus != 0);
+            }
+        }
+        assert_eq!(vec.as_slice(), model.as_slice());
+    }
+
+    let rest: Vec<String> = vec.clone().into_iter().rev().collect();
+    assert_eq!(rest, model.iter().rev().cloned().collect::<Vec<_>>());
+}
+
+#[test]
+fn ring_buffer_matches_vec_deque_model() {
+    let mut rng = XorShift(99);
+    let mut ring: RingBuffer<Box<u32>, 5> = RingBuffer::new();
+    let mut model: VecDeque<Box<u32>> = VecDeque::new();
+
+    for step in 0..400u32 {
+        match rng.below(6) {
+            0 => {
+                if ring.try_push_back(Box::new(step)).is_ok() {
+                    model.push_back(Box::new(step));
+                }
+            }
+            1 => {
+                if ring.try_push_front(Box::new(step)).is_ok() {
+                    model.push_front(Box::new(step));
+                }
+            }
+            2 => assert_eq!(ring.pop_front(), model.pop_front()),
+            3 => assert_eq!(ring.pop_back(), model.pop_back()),
+            4 => {
+                let evicted = ring.push_overwrite(Box::new(step));
+                if model.len() == 5 {
+                    assert_eq!(evicted, model.pop_front());
+                }
+                model.push_back(Box::new(step));
+            }
+            _ => {
+                if rng.below(4) == 0 {
+                    ring.make_contiguous();
+                }
+                for item in ring.iter_mut() {
+                    **item += 1;
+                }
+                for item in model.iter_mut() {
+                    **item += 1;
+                }
+            }
+        }
+        assert!(ring.iter().eq(model.iter()));
+    }
+    assert_eq!(ring.len(), model.len());
+}
+
+#[test]
+fn fixed_collections_survive_panics_without_double_drop() {
+    let drops = Cell::new(0);
+    let mut vec: ArrayVec<Counted, 6> = (0..6).map(|id| Counted { id, drops: &drops }).collect();
+
+    // retain 的回调 panic：已经判定删除的元素析构一次，其余元素留在 vec 中
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        vec.retain(|item| {
+            assert!(item.id != 3, "retain 中途 panic");
+            item.id % 2 == 0
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 1);
+    assert_eq!(
+        vec.iter().map(|c| c.id).collect::<Vec<_>>(),
+        [0, 2, 3, 4, 5]
+    );
+    drop(vec);
+    assert_eq!(drops.get(), 6);
+
+    // 按值迭代到一半就 drop，剩下的元素正好析构一次
+    drops.set(0);
+    let ring: RingBuffer<Counted, 4> = (0..4).map(|id| Counted { id, drops: &drops }).collect();
+    let mut iter = ring.into_iter();
+    assert_eq!(iter.next_back().map(|c| c.id), Some(3));
+    drop(iter);
+    assert_eq!(drops.get(), 4);
+
+    let mut name = ArrayString::<6>::new();
+    name.push_str("añb");
+    assert_eq!(name.try_push_str("cde").unwrap_err().element(), "cde");
+    assert_eq!((name.remove(1), name.as_str()), ('ñ', "ab"));
+}
@@ -0,0 +1,102 @@
+// 插件系统的集成测试
+// 用 cargo 构建 crates/sample-plugin（cdylib），复制到临时目录，再通过 PluginRegistry::load_dir 在运行时加载
+#![cfg(unix)]
+
+use rust_code_guide::errors::{Diagnostic, ErrorCode};
+use rust_code_guide::plugin::{PluginError, PluginRegistry, abi};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn library_file(name: &str) -> String {
+    format!(
+        "{}{}{}",
+        std::env::consts::DLL_PREFIX,
+        name,
+        std::env::consts::DLL_SUFFIX
+    )
+}
+
+// 集成测试可执行文件位于 target/<profile>/deps，插件构建在 target/<profile>
+fn build_sample_plugin() -> PathBuf {
+    let mut command = Command::new(env!("CARGO"));
+    command
+        .current_dir(manifest_dir())
+        .args(["build", "--quiet", "-p", "sample-plugin"]);
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    let status = command.status().expect("无法运行 cargo");
+    assert!(status.success(), "构建 sample-plugin 失败");
+
+    let exe = std::env::current_exe().unwrap();
+    let profile_dir = exe.parent().unwrap().parent().unwrap();
+    let path = profile_dir.join(library_file("sample_plugin"));
+    assert!(path.exists(), "找不到 {}", path.display());
+    path
+}
+
+fn plugin_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn load_sample_plugin_from_directory() {
+    let dir = plugin_dir("plugins");
+    fs::copy(
+        build_sample_plugin(),
+        dir.join(library_file("sample_plugin")),
+    )
+    .unwrap();
+    // 扩展名不对的文件被忽略，扩展名对但不是动态库的文件被跳过并报告原因
+    fs::write(dir.join("README.txt"), "不是插件").unwrap();
+    fs::write(dir.join(library_file("broken")), "不是动态库").unwrap();
+
+    let mut registry = PluginRegistry::new();
+    let skipped = registry.load_dir(&dir).unwrap();
+    assert_eq!(registry.names(), ["shout"]);
+    assert_eq!(skipped.len(), 1);
+    assert!(skipped[0].0.ends_with(library_file("broken")));
+    assert!(matches!(skipped[0].1, PluginError::Load { .. }));
+
+    let plugin = registry.get_mut("shout").unwrap();
+    assert_eq!(plugin.version(), "1.0.0");
+    assert_eq!(plugin.process("hello").unwrap(), "#1 HELLO!");
+    // 实例在调用之间保留状态
+    assert_eq!(plugin.process("rust 插件").unwrap(), "#2 RUST 插件!");
+
+    let err = plugin.process("").unwrap_err();
+    assert!(matches!(&err, PluginError::Call { status, message, .. }
+            if *status == abi::STATUS_ERROR && message == "输入为空"));
+    assert_eq!(err.code(), ErrorCode::PLUGIN_CALL);
+
+    // 插件内部的 panic 被转换为错误码，宿主和插件都可以继续使用
+    let err = plugin.process("panic").unwrap_err();
+    assert!(matches!(err, PluginError::Call { status, .. } if status == abi::STATUS_PANIC));
+    assert!(plugin.process("again").unwrap().ends_with("AGAIN!"));
+
+    // 同名插件不能重复加载
+    let err = registry
+        .load(dir.join(library_file("sample_plugin")))
+        .unwrap_err();
+    assert!(matches!(err, PluginError::DuplicateName(name) if name == "shout"));
+    assert!(registry.get_mut("missing").is_none());
+}
+
+#[test]
+fn missing_directory_is_an_io_error() {
+    let mut registry = PluginRegistry::new();
+    let err = registry
+        .load_dir(plugin_dir("empty").join("does-not-exist"))
+        .unwrap_err();
+    assert!(matches!(err, PluginError::Io { .. }));
+    assert_eq!(err.code(), ErrorCode::FILE_NOT_FOUND);
+    assert!(registry.is_empty());
+}
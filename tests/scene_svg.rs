@@ -0,0 +1,105 @@
+// 场景图 SVG 输出的快照测试
+// 每个用例渲染出的 SVG 与 tests/data/scene_svg/<用例名>.svg 逐字比较，可以直接用浏览器打开查看
+// 有意修改输出格式后，用 UPDATE_GOLDEN=1 cargo test --test scene_svg 重新生成期望文件
+
+mod common;
+
+use common::golden::check_golden;
+use rust_code_guide::image::Rgb;
+use rust_code_guide::scene::{Pick, Point, Scene, Shape, Style, Transform, svg};
+
+const RED: Rgb = Rgb([220, 40, 40]);
+const GREEN: Rgb = Rgb([40, 160, 80]);
+const BLUE: Rgb = Rgb([40, 80, 220]);
+const BLACK: Rgb = Rgb([0, 0, 0]);
+
+// 第 8 章 GraphicsManager 中的三个图形；绿色正方形绕自己的中心旋转 45°
+fn basic_shapes() -> Scene {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let red = scene
+        .add_shape(root, "红色矩形", Shape::rect(100.0, 50.0), Style::fill(RED))
+        .unwrap();
+    scene.node_mut(red).unwrap().transform = Transform::translate(10.0, 10.0);
+    let blue = scene
+        .add_shape(root, "蓝色圆形", Shape::circle(30.0), Style::fill(BLUE))
+        .unwrap();
+    scene.node_mut(blue).unwrap().transform = Transform::translate(200.0, 100.0);
+    let green = scene
+        .add_shape(
+            root,
+            "绿色矩形",
+            Shape::rect(80.0, 80.0),
+            Style::fill(GREEN).with_stroke(BLACK, 2.0),
+        )
+        .unwrap();
+    scene.node_mut(green).unwrap().transform =
+        Transform::rotate_about(45f64.to_radians(), Point::new(40.0, 40.0))
+            .then(Transform::translate(50.0, 200.0));
+    scene
+}
+
+// 嵌套的组、z_index 排序、隐藏节点、需要转义的名字和只有描边的多边形
+fn nested_groups() -> Scene {
+    let mut scene = Scene::new();
+    let root = scene.root();
+    let panel = scene.add_group(root, "panel").unwrap();
+    scene.node_mut(panel).unwrap().transform =
+        Transform::scale(2.0, 2.0).then(Transform::translate(40.0, 20.0));
+    let front = scene
+        .add_shape(panel, "front", Shape::circle(10.0), Style::fill(RED))
+        .unwrap();
+    scene.node_mut(front).unwrap().z_index = 1;
+    scene
+        .add_shape(panel, "back", Shape::rect(30.0, 20.0), Style::fill(BLUE))
+        .unwrap();
+    let hidden = scene
+        .add_shape(panel, "hidden", Shape::rect(5.0, 5.0), Style::fill(BLACK))
+        .unwrap();
+    scene.node_mut(hidden).unwrap().visible = false;
+    let arrow = scene.add_group(root, "arrow <\"→\"> & more").unwrap();
+    scene.node_mut(arrow).unwrap().transform = Transform::rotate_degrees(-30.0);
+    scene
+        .add_shape(
+            arrow,
+            "head",
+            Shape::polygon([
+                Point::new(0.0, 0.0),
+                Point::new(20.0, 10.0),
+                Point::new(0.0, 20.0),
+            ]),
+            Style::stroke(GREEN, 1.5),
+        )
+        .unwrap();
+    scene.add_group(root, "empty").unwrap();
+    scene
+}
+
+// 点选最外层组、框选后合并成组，再整体旋转
+fn grouped_selection() -> Scene {
+    let mut scene = basic_shapes();
+    let hit = scene.select_at(Point::new(60.0, 35.0), Pick::Group);
+    assert_eq!(hit, scene.find("红色矩形"));
+    scene
+        .toggle_selected(scene.find("蓝色圆形").unwrap())
+        .unwrap();
+    scene.group_selected("选中的图形").unwrap();
+    let center = scene.selection_bounds().unwrap().center();
+    scene
+        .transform_selected(Transform::rotate_about(90f64.to_radians(), center))
+        .unwrap();
+    scene
+}
+
+#[test]
+fn svg_matches_golden_files() {
+    let cases = [
+        ("basic_shapes", basic_shapes()),
+        ("nested_groups", nested_groups()),
+        ("grouped_selection", grouped_selection()),
+    ];
+    let cases = cases
+        .into_iter()
+        .map(|(name, scene)| (name, svg::render(&scene, 320, 320)));
+    check_golden("tests/data/scene_svg", "svg", cases);
+}
@@ -0,0 +1,96 @@
+// SQL 构建器的 golden 测试
+// 每个用例生成的 SQL 和参数与 tests/data/sql_golden/<用例名>.sql 逐字比较
+// 有意修改输出格式后，用 UPDATE_GOLDEN=1 cargo test --test sql_golden 重新生成期望文件
+
+mod common;
+
+use common::golden::check_golden;
+use rust_code_guide::query;
+use rust_code_guide::sql::{Order, Query, SqlError, SqlQuery, Value, col};
+
+fn cases() -> Vec<(&'static str, Result<SqlQuery, SqlError>)> {
+    let min_age = 18;
+    let name = "O'Brien";
+    vec![
+        ("select_all", query!(SELECT * FROM users).build()),
+        (
+            "select_where_order_limit",
+            query!(
+                SELECT id, name FROM users
+                WHERE age >= min_age AND name LIKE "A%"
+                ORDER BY name DESC LIMIT 10 OFFSET 20
+            )
+            .build(),
+        ),
+        (
+            "select_or_not_in",
+            Query::select(["id"])
+                .from("orders")
+                .where_(col("status").eq("paid").or(col("status").eq("shipped")))
+                .where_(!col("customer_id").in_list([3, 5, 8]))
+                .where_(col("deleted_at").is_null())
+                .order_by("created_at", Order::Desc)
+                .build(),
+        ),
+        (
+            "select_quoted_identifiers",
+            Query::select(["order", "first name"])
+                .from("group")
+                .where_(col("select").ne(Value::Null))
+                .build(),
+        ),
+        (
+            "select_injection_attempt",
+            Query::select(["id"])
+                .from("users")
+                .where_(col("name").eq("x'; DROP TABLE users; --"))
+                .build(),
+        ),
+        (
+            "insert_rows",
+            query!(INSERT INTO users (name, age, score) VALUES (name, 30, 9.5), ("Eve", NULL, 7.0))
+                .build(),
+        ),
+        (
+            "update_where",
+            query!(UPDATE users SET age = 31, nickname = NULL WHERE id = 1 AND deleted_at IS NULL)
+                .build(),
+        ),
+        (
+            "delete_where",
+            query!(DELETE FROM sessions WHERE expires_at < 1700000000).build(),
+        ),
+        ("error_missing_table", Query::select(["id"]).build()),
+        (
+            "error_row_length",
+            Query::insert_into("users")
+                .columns(["name", "age"])
+                .values([Value::from("Bob")])
+                .build(),
+        ),
+    ]
+}
+
+// 期望文件的格式：SQL 一行，参数一行，代入参数后的 SQL 一行；构建失败时只有错误信息
+fn render(result: &Result<SqlQuery, SqlError>) -> String {
+    match result {
+        Ok(query) => {
+            let params: Vec<String> = query.params.iter().map(Value::to_string).collect();
+            format!(
+                "{}\n-- params: [{}]\n-- inline: {}\n",
+                query.sql,
+                params.join(", "),
+                query.to_inline_sql()
+            )
+        }
+        Err(e) => format!("-- error: {}\n", e),
+    }
+}
+
+#[test]
+fn sql_matches_golden_files() {
+    let cases = cases()
+        .into_iter()
+        .map(|(name, result)| (name, render(&result)));
+    check_golden("tests/data/sql_golden", "sql", cases);
+}
@@ -0,0 +1,41 @@
+// sql! 与 minidb 的语法一致性：sql! 在编译期接受的语句，Database::execute 在运行时也必须接受
+// 两者共用 crates/guide-sql 的语法分析；这里覆盖每一种语句和条件的写法，防止一方悄悄分叉
+
+use guide_macros::sql;
+use rust_code_guide::minidb::Database;
+
+#[test]
+fn test_sql_macro_output_runs_on_minidb() {
+    let statements = [
+        sql!(
+            "create table users (id integer primary key, name text not null, score real, note varchar)"
+        ),
+        sql!("create table if not exists users (id int)"),
+        sql!("create table \"order\" (\"select\" float, x double, y)"),
+        sql!("insert into users values (1, 'Alice', 9.5, null)"),
+        sql!("insert into users (id, name) values (2, 'O''Brien'), (3, 'Carol')"),
+        sql!("insert into \"order\" values (-1.5, -2, 'any');"),
+        sql!("select * from users"),
+        sql!(
+            "select id, name from users where score >= ? order by name desc, id asc limit 10 offset 1"
+        ),
+        sql!("select * from users limit 9223372036854775807"),
+        sql!("select \"select\" from \"order\" where x = -2 -- 注释"),
+        sql!(
+            "select id from users where not (id = 1 or note is not null) and name not in ('a', ?)"
+        ),
+        sql!("select id from users where name like 'A%' or name not like ? or score is null"),
+        sql!("select id from users where id <> 1 and id != 2 and id < 3 and id <= 4 and id > 0"),
+        sql!("update users set score = -0.5, note = 'x' where id = ?"),
+        sql!("update users set note = null"),
+        sql!("delete from users where id in (1, 2)"),
+        sql!("delete from \"order\""),
+    ];
+
+    let mut db = Database::new();
+    for sql in statements {
+        if let Err(e) = db.execute(sql, &[]) {
+            panic!("sql! 接受但 minidb 拒绝: {}\n{}", sql, e);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// 终端组件工具箱的端到端测试
+// App 通过 Terminal 渲染到内存中的 TestBackend，检查虚拟屏幕上的内容、焦点的位置和每一帧输出的字节
+
+use rust_code_guide::tui::{
+    App, Button, Constraint, EventResult, Flex, Key, Label, Terminal, TestBackend, TextInput,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// 一个登录表单：输入框提交或按下“确定”时更新状态行，“退出”按钮和 Esc 结束程序
+fn login_form(status: Rc<RefCell<String>>) -> App {
+    let on_submit = status.clone();
+    let on_ok = status.clone();
+    let shown = status.clone();
+    let form = Flex::column()
+        .bordered("登录")
+        .margin(1)
+        .child(Constraint::Fixed(1), Label::new("用户名"))
+        .child(
+            Constraint::Fixed(1),
+            TextInput::new("name").on_submit(move |value| {
+                *on_submit.borrow_mut() = format!("你好, {}", value);
+                EventResult::Consumed
+            }),
+        )
+        .child(
+            Constraint::Flex(1),
+            Label::dynamic(move || shown.borrow().clone()),
+        )
+        .child(
+            Constraint::Fixed(1),
+            Flex::row()
+                .gap(2)
+                .child(
+                    Constraint::Flex(1),
+                    Button::new("确定").on_press(move || {
+                        *on_ok.borrow_mut() = "已确定".to_string();
+                        EventResult::Consumed
+                    }),
+                )
+                .child(
+                    Constraint::Flex(1),
+                    Button::new("退出").on_press(|| EventResult::Quit),
+                ),
+        );
+    App::new(form).bind(Key::Esc, || EventResult::Quit)
+}
+
+#[test]
+fn layout_renders_to_virtual_screen() {
+    let status = Rc::new(RefCell::new("等待输入".to_string()));
+    let app = login_form(status);
+    let mut terminal = Terminal::new(TestBackend::new(30, 9));
+    app.draw(&mut terminal).unwrap();
+    assert_eq!(
+        terminal.backend().lines(),
+        [
+            "┌─ 登录 ─────────────────────┐",
+            "│                            │",
+            "│ 用户名                     │",
+            "│ name                       │",
+            "│ 等待输入                   │",
+            "│                            │",
+            "│   [ 确定 ]      [ 退出 ]   │",
+            "│                            │",
+            "└────────────────────────────┘",
+        ]
+    );
+    // 第一个可聚焦的组件是输入框，光标显示在它的第一格
+    assert_eq!(app.focused(), Some(0));
+    assert_eq!(app.focus_count(), 3);
+    let screen = terminal.backend().screen();
+    assert!(screen.cell(2, 3).style.reversed);
+    assert!(!screen.cell(3, 3).style.reversed);
+}
+
+#[test]
+fn keys_are_dispatched_to_focused_widget() {
+    let status = Rc::new(RefCell::new(String::new()));
+    let mut app = login_form(status.clone());
+    let mut terminal = Terminal::new(TestBackend::new(30, 9));
+
+    let keys = Key::decode("ann\x7fa\r".as_bytes());
+    app.run(&mut terminal, keys).unwrap();
+    assert_eq!(status.borrow().as_str(), "你好, ana");
+    assert_eq!(
+        terminal.backend().lines()[3],
+        "│ ana                        │"
+    );
+    assert_eq!(
+        terminal.backend().lines()[4],
+        "│ 你好, ana                  │"
+    );
+
+    // Tab 把焦点移到“确定”，空格按下它；Shift+Tab 回到输入框
+    app.run(&mut terminal, [Key::Tab, Key::Char(' ')]).unwrap();
+    assert_eq!(app.focused(), Some(1));
+    assert_eq!(status.borrow().as_str(), "已确定");
+    assert!(terminal.backend().screen().cell(4, 6).style.reversed);
+    app.handle_key(Key::BackTab);
+    app.handle_key(Key::BackTab);
+    assert_eq!(app.focused(), Some(2));
+
+    // 输入框不处理 Esc，交给全局绑定
+    assert!(app.is_running());
+    assert_eq!(app.handle_key(Key::Esc), EventResult::Quit);
+    assert!(!app.is_running());
+}
+
+#[test]
+fn quit_button_stops_run() {
+    let mut app = login_form(Rc::new(RefCell::new(String::new())));
+    let mut terminal = Terminal::new(TestBackend::new(30, 9));
+    // Quit 之后的按键不再处理
+    app.run(
+        &mut terminal,
+        [Key::Tab, Key::Tab, Key::Enter, Key::Tab, Key::Tab],
+    )
+    .unwrap();
+    assert!(!app.is_running());
+    assert_eq!(app.focused(), Some(2));
+}
+
+#[test]
+fn renderer_emits_minimal_updates() {
+    let status = Rc::new(RefCell::new(String::new()));
+    let mut app = login_form(status.clone());
+    let mut terminal = Terminal::new(TestBackend::new(30, 9));
+    app.draw(&mut terminal).unwrap();
+    let first = terminal.backend_mut().take_output();
+
+    // 没有变化的帧不输出任何内容
+    assert_eq!(app.draw(&mut terminal).unwrap(), 0);
+
+    // 输入一个字符：占位文字 "name" 变成 "x" 加上光标，变化的四个单元相邻，光标只定位一次
+    app.handle_key(Key::Char('x'));
+    app.draw(&mut terminal).unwrap();
+    let update = terminal.backend_mut().take_output();
+    assert_eq!(update, "\x1b[4;3H\x1b[0;4mx\x1b[0;4;7m \x1b[0;4m  \x1b[0m");
+    assert!(update.len() < first.len());
+
+    // 虚拟屏幕与完整重绘的结果一致
+    let mut fresh = Terminal::new(TestBackend::new(30, 9));
+    app.draw(&mut fresh).unwrap();
+    assert_eq!(terminal.backend().screen(), fresh.backend().screen());
+
+    // 只改变样式也会被输出
+    app.handle_key(Key::Tab);
+    app.draw(&mut terminal).unwrap();
+    let restyled = terminal.backend_mut().take_output();
+    assert!(restyled.contains("\x1b[0;1;7m[ 确定 ]"), "{:?}", restyled);
+}
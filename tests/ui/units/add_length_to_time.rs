@@ -0,0 +1,6 @@
+// 不同量纲不能相加
+use rust_code_guide::units::{Length, Time};
+
+fn main() {
+    let _ = Length::meters(1.0) + Time::seconds(1.0);
+}
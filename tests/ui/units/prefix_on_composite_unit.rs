@@ -0,0 +1,6 @@
+// m/s 没有单一的单位符号，不能直接加词头
+use rust_code_guide::units::{Prefix, Velocity};
+
+fn main() {
+    let _ = Velocity::from_prefixed(3.6, Prefix::Kilo);
+}
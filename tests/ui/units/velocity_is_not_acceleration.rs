@@ -0,0 +1,6 @@
+// m / s 是速度，少除了一次时间，不能当作加速度
+use rust_code_guide::units::{Acceleration, Length, Time};
+
+fn main() {
+    let _: Acceleration = Length::meters(10.0) / Time::seconds(2.0);
+}
@@ -0,0 +1,19 @@
+// 草稿没有 Approve 的转换，必须先提交审核
+use rust_code_guide::typestate;
+use rust_code_guide::workflow::Machine;
+
+struct Save;
+struct RequestReview;
+struct Approve;
+
+typestate! {
+    states: Draft, PendingReview, Published;
+    Draft + Save => Draft;
+    Draft + RequestReview => PendingReview;
+    PendingReview + Approve => Published;
+}
+
+fn main() {
+    let draft: Machine<Draft> = Machine::new(());
+    let _ = draft.fire(Approve);
+}
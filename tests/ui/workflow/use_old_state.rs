@@ -0,0 +1,16 @@
+// fire 按值接收状态机，转换之后旧状态不能再使用
+use rust_code_guide::typestate;
+use rust_code_guide::workflow::Machine;
+
+struct RequestReview;
+
+typestate! {
+    states: Draft, PendingReview;
+    Draft + RequestReview => PendingReview;
+}
+
+fn main() {
+    let draft: Machine<Draft> = Machine::new(());
+    let _pending = draft.fire(RequestReview);
+    let _again = draft.fire(RequestReview);
+}
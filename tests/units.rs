@@ -0,0 +1,9 @@
+// src/units.rs 的编译期检查
+// tests/ui/units/ 下的每个文件都必须编译失败，错误信息与同名的 .stderr 比较
+// 编译器版本更新导致输出变化时，用 TRYBUILD=overwrite cargo test --test units 重新生成
+
+#[test]
+fn dimension_mismatches_do_not_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/units/*.rs");
+}
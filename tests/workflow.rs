@@ -0,0 +1,125 @@
+// 状态机工作流的端到端测试
+// 1. 运行时工作流的 DOT 输出与 tests/data/workflow/<用例名>.dot 逐字比较，可以用 dot -Tsvg 渲染查看；
+//    有意修改输出格式后，用 UPDATE_GOLDEN=1 cargo test --test workflow 重新生成期望文件
+// 2. tests/ui/workflow/ 下的每个文件都必须编译失败，错误信息与同名的 .stderr 比较；
+//    编译器版本更新导致输出变化时，用 TRYBUILD=overwrite cargo test --test workflow 重新生成
+
+mod common;
+
+use common::golden::check_golden;
+use rust_code_guide::workflow::{Workflow, WorkflowError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Order {
+    Created,
+    Paid,
+    Shipped,
+    Delivered,
+    Cancelled,
+    Refunded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OrderEvent {
+    Pay,
+    Ship,
+    Deliver,
+    Cancel,
+}
+
+#[derive(Debug, Default)]
+struct Account {
+    balance: u32,
+    price: u32,
+    log: Vec<String>,
+}
+
+// 付款需要余额足够；已付款的订单取消时退款，未付款的直接取消
+fn order_workflow() -> Workflow<Order, OrderEvent, Account> {
+    Workflow::builder("订单", Order::Created)
+        .transition(Order::Created, OrderEvent::Pay, Order::Paid)
+        .guard("余额足够", |account: &Account| {
+            account.balance >= account.price
+        })
+        .action(|account, _| account.balance -= account.price)
+        .transition(Order::Created, OrderEvent::Cancel, Order::Cancelled)
+        .transition(Order::Paid, OrderEvent::Ship, Order::Shipped)
+        .transition(Order::Paid, OrderEvent::Cancel, Order::Refunded)
+        .action(|account, _| account.balance += account.price)
+        .transition(Order::Shipped, OrderEvent::Deliver, Order::Delivered)
+        .on_exit(Order::Created, |account, step| {
+            account.log.push(format!("离开 {:?}", step.from))
+        })
+        .on_enter(Order::Refunded, |account, step| {
+            account.log.push(format!("{:?} 后退款", step.event))
+        })
+        .build()
+        .unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Light {
+    Red,
+    Green,
+    Yellow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    Timer,
+}
+
+// 第 4 章的红绿灯：一个环，没有终态
+fn traffic_light() -> Workflow<Light, Signal> {
+    Workflow::builder("红绿灯", Light::Red)
+        .transition(Light::Red, Signal::Timer, Light::Green)
+        .transition(Light::Green, Signal::Timer, Light::Yellow)
+        .transition(Light::Yellow, Signal::Timer, Light::Red)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn order_lifecycle() {
+    let workflow = order_workflow();
+    let mut account = Account {
+        balance: 80,
+        price: 100,
+        ..Account::default()
+    };
+    let err = workflow
+        .fire(Order::Created, OrderEvent::Pay, &mut account)
+        .unwrap_err();
+    assert!(matches!(err, WorkflowError::Rejected { ref guards, .. } if guards == &["余额足够"]));
+
+    account.balance = 150;
+    let paid = workflow
+        .fire(Order::Created, OrderEvent::Pay, &mut account)
+        .unwrap();
+    assert_eq!(account.balance, 50);
+    let refunded = workflow
+        .fire(paid, OrderEvent::Cancel, &mut account)
+        .unwrap();
+    assert_eq!((refunded, account.balance), (Order::Refunded, 150));
+    assert_eq!(account.log, ["离开 Created", "Cancel 后退款"]);
+    assert!(workflow.is_final(Order::Refunded));
+    assert_eq!(
+        workflow.events(Order::Paid),
+        [OrderEvent::Ship, OrderEvent::Cancel]
+    );
+}
+
+#[test]
+fn dot_matches_golden_files() {
+    let cases = [
+        ("order", order_workflow().to_dot()),
+        ("traffic_light", traffic_light().to_dot()),
+    ];
+    check_golden("tests/data/workflow", "dot", cases);
+}
+
+#[test]
+fn illegal_typestate_transitions_do_not_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/workflow/*.rs");
+}